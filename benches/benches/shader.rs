@@ -114,6 +114,8 @@ fn parse_glsl(stage: naga::ShaderStage, inputs: &Inputs) {
     let options = naga::front::glsl::Options {
         stage,
         defines: Default::default(),
+        include_resolver: None,
+        geometry: None,
     };
     for input in &inputs.inner {
         parser
@@ -49,6 +49,11 @@ struct Args {
     #[argh(option)]
     block_ctx_dir: Option<String>,
 
+    /// translate SPIR-V `NonSemantic.DebugPrintf` instructions, rather than
+    /// ignoring them
+    #[argh(switch)]
+    spv_debug_printf: bool,
+
     /// the shader entrypoint to use when compiling to GLSL
     #[argh(option)]
     entry_point: Option<String>,
@@ -65,7 +70,8 @@ struct Args {
 
     /// the shader stage, for example 'frag', 'vert', or 'compute'.
     /// if the shader stage is unspecified it will be derived from
-    /// the file extension.
+    /// the file extension. GLSL geometry shaders ('geom') are lowered
+    /// to compute shaders.
     #[argh(option)]
     shader_stage: Option<ShaderStage>,
 
@@ -133,11 +139,27 @@ struct Args {
     #[argh(positional)]
     files: Vec<String>,
 
-    /// defines to be passed to the parser (only glsl is supported)
+    /// defines to be passed to the parser (only glsl and wgsl are supported)
+    ///
+    /// For wgsl, values must be `true`, `false`, or an integer; a define
+    /// with no value is `true`.
     #[argh(option, short = 'D')]
     defines: Vec<Defines>,
 }
 
+/// Reformat WGSL files in place.
+#[derive(argh::FromArgs, Debug, Clone)]
+struct FmtArgs {
+    /// don't write the files, but fail if any of them aren't formatted
+    #[argh(switch)]
+    check: bool,
+
+    /// the WGSL files to format. If none are given, the source is read from
+    /// stdin, and the formatted source is written to stdout.
+    #[argh(positional)]
+    files: Vec<String>,
+}
+
 /// Newtype so we can implement [`FromStr`] for `BoundsCheckPolicy`.
 #[derive(Debug, Clone, Copy)]
 struct BoundsCheckPolicyArg(naga::proc::BoundsCheckPolicy);
@@ -186,8 +208,11 @@ impl FromStr for ShaderModelArg {
 }
 
 /// Newtype so we can implement [`FromStr`] for `ShaderSource`.
+///
+/// `None` stands for GLSL geometry shaders, which are lowered to compute
+/// shaders.
 #[derive(Debug, Clone, Copy)]
-struct ShaderStage(naga::ShaderStage);
+struct ShaderStage(Option<naga::ShaderStage>);
 
 impl FromStr for ShaderStage {
     type Err = anyhow::Error;
@@ -195,9 +220,10 @@ impl FromStr for ShaderStage {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use naga::ShaderStage;
         Ok(Self(match s.to_lowercase().as_str() {
-            "frag" | "fragment" => ShaderStage::Fragment,
-            "comp" | "compute" => ShaderStage::Compute,
-            "vert" | "vertex" => ShaderStage::Vertex,
+            "frag" | "fragment" => Some(ShaderStage::Fragment),
+            "comp" | "compute" => Some(ShaderStage::Compute),
+            "vert" | "vertex" => Some(ShaderStage::Vertex),
+            "geom" | "geometry" => None,
             _ => return Err(anyhow!("Invalid shader stage: {s}")),
         }))
     }
@@ -382,6 +408,26 @@ impl std::error::Error for CliError {}
 fn run() -> anyhow::Result<()> {
     env_logger::init();
 
+    // `naga fmt` has its own arguments.
+    let mut env_args = std::env::args();
+    let command = env_args.next().unwrap_or_default();
+    let rest: Vec<String> = env_args.collect();
+    if rest.first().map(String::as_str) == Some("fmt") {
+        let command = format!("{command} fmt");
+        let rest: Vec<&str> = rest[1..].iter().map(String::as_str).collect();
+        let args = match <FmtArgs as argh::FromArgs>::from_args(&[&command], &rest) {
+            Ok(args) => args,
+            Err(exit) => {
+                match exit.status {
+                    Ok(()) => println!("{}", exit.output),
+                    Err(()) => eprintln!("{}", exit.output),
+                }
+                std::process::exit(exit.status.map_or(1, |()| 0));
+            }
+        };
+        return format_wgsl(args);
+    }
+
     // Parse commandline arguments
     let args: Args = argh::from_env();
     if args.version {
@@ -431,6 +477,7 @@ fn run() -> anyhow::Result<()> {
         adjust_coordinate_space: !args.keep_coordinate_space,
         strict_capabilities: false,
         block_ctx_dump_prefix: args.block_ctx_dir.clone().map(std::path::PathBuf::from),
+        debug_printf: args.spv_debug_printf,
     };
 
     params.entry_point.clone_from(&args.entry_point);
@@ -474,6 +521,7 @@ fn run() -> anyhow::Result<()> {
     let Parsed {
         mut module,
         input_text,
+        source_map,
     } = parse_input(input_path, input, &params)?;
 
     // Include debugging information if requested.
@@ -521,8 +569,10 @@ fn run() -> anyhow::Result<()> {
         Ok(info) => Some(info),
         Err(error) => {
             // Validation failure is not fatal. Just report the error.
-            if let Some(input) = &input_text {
-                let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
+            let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
+            if let Some(ref source_map) = source_map {
+                emit_annotated_source_files_error(&error, source_map, filename.unwrap_or("input"));
+            } else if let Some(input) = &input_text {
                 emit_annotated_error(&error, filename.unwrap_or("input"), input);
             }
             print_err(&error);
@@ -549,8 +599,14 @@ fn run() -> anyhow::Result<()> {
                 Err(error) => {
                     // Validation failure is not fatal. Just report the error.
                     eprintln!("Error validating compacted module:");
-                    if let Some(input) = &input_text {
-                        let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
+                    let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
+                    if let Some(ref source_map) = source_map {
+                        emit_annotated_source_files_error(
+                            &error,
+                            source_map,
+                            filename.unwrap_or("input"),
+                        );
+                    } else if let Some(input) = &input_text {
                         emit_annotated_error(&error, filename.unwrap_or("input"), input);
                     }
                     print_err(&error);
@@ -588,6 +644,29 @@ fn run() -> anyhow::Result<()> {
 struct Parsed {
     module: naga::Module,
     input_text: Option<String>,
+    /// The source files recorded in SPIR-V input's debug info, or included
+    /// by GLSL input, if any.
+    source_map: Option<naga::SourceMap>,
+}
+
+/// Resolve GLSL `#include` directives relative to the including file.
+fn glsl_include_resolver(input_path: &Path) -> naga::front::glsl::IncludeResolver {
+    let main_dir = input_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    naga::front::glsl::IncludeResolver::new(move |includer, path, _kind| {
+        let dir = match includer {
+            "" => main_dir.clone(),
+            _ => Path::new(includer)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf(),
+        };
+        let path = dir.join(path);
+        let source = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+        Ok(naga::front::glsl::ResolvedInclude {
+            name: path.display().to_string(),
+            source,
+        })
+    })
 }
 
 fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow::Result<Parsed> {
@@ -602,14 +681,37 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
             .context("Unable to determine --input-kind from filename")?,
     };
 
+    let mut source_map = None;
     let (module, input_text) = match input_kind {
         InputKind::Bincode => (bincode::deserialize(&input)?, None),
         InputKind::SpirV => {
-            naga::front::spv::parse_u8_slice(&input, &params.spv_in).map(|m| (m, None))?
+            let (module, map) =
+                naga::front::spv::parse_u8_slice_with_source_map(&input, &params.spv_in)?;
+            let input_text = map.as_ref().map(|map| map.text().to_string());
+            source_map = map;
+            (module, input_text)
         }
         InputKind::Wgsl => {
             let input = String::from_utf8(input)?;
-            let result = naga::front::wgsl::parse_str(&input);
+            let defines = params
+                .defines
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value.as_str() {
+                        "" | "true" => naga::front::wgsl::Define::Bool(true),
+                        "false" => naga::front::wgsl::Define::Bool(false),
+                        _ => naga::front::wgsl::Define::Int(value.parse().with_context(|| {
+                            format!("WGSL define `{name}` is not a boolean or integer")
+                        })?),
+                    };
+                    Ok((name.clone(), value))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let result =
+                naga::front::wgsl::Frontend::new_with_options(naga::front::wgsl::Options {
+                    defines,
+                })
+                .parse(&input);
             match result {
                 Ok(v) => (v, Some(input)),
                 Err(ref e) => {
@@ -639,31 +741,47 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
                 }
             };
             let input = String::from_utf8(input)?;
+            let filename = input_path
+                .file_name()
+                .and_then(std::ffi::OsStr::to_str)
+                .unwrap_or("glsl");
+            let options = match shader_stage.0 {
+                Some(stage) => naga::front::glsl::Options::from(stage),
+                None => naga::front::glsl::GeometryOptions::default().into(),
+            };
             let mut parser = naga::front::glsl::Frontend::default();
-            (
-                parser
-                    .parse(
-                        &naga::front::glsl::Options {
-                            stage: shader_stage.0,
-                            defines: params.defines.clone(),
-                        },
-                        &input,
-                    )
-                    .unwrap_or_else(|error| {
-                        let filename = input_path
-                            .file_name()
-                            .and_then(std::ffi::OsStr::to_str)
-                            .unwrap_or("glsl");
-                        let mut writer = StandardStream::stderr(ColorChoice::Auto);
-                        error.emit_to_writer_with_path(&mut writer, &input, filename);
-                        std::process::exit(1);
-                    }),
-                Some(input),
-            )
+            let module = parser
+                .parse(
+                    &naga::front::glsl::Options {
+                        defines: params.defines.clone(),
+                        include_resolver: Some(glsl_include_resolver(input_path)),
+                        ..options
+                    },
+                    &input,
+                )
+                .unwrap_or_else(|error| {
+                    let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                    error.emit_to_writer_with_source_map(
+                        &mut writer,
+                        parser.source_map(),
+                        filename,
+                    );
+                    std::process::exit(1);
+                });
+
+            let map = parser.source_map();
+            if map.files().len() > 1 {
+                source_map = Some(map.clone());
+            }
+            (module, Some(map.text().to_string()))
         }
     };
 
-    Ok(Parsed { module, input_text })
+    Ok(Parsed {
+        module,
+        input_text,
+        source_map,
+    })
 }
 
 fn write_output(
@@ -842,7 +960,11 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
         let path = Path::new(&input_path);
         let input = fs::read(path)?;
 
-        let Parsed { module, input_text } = match parse_input(path, input, params) {
+        let Parsed {
+            module,
+            input_text,
+            source_map,
+        } = match parse_input(path, input, params) {
             Ok(parsed) => parsed,
             Err(error) => {
                 invalid.push(input_path.clone());
@@ -860,8 +982,10 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
         if let Err(error) = validator.validate(&module) {
             invalid.push(input_path.clone());
             eprintln!("Error validating {}:", input_path);
-            if let Some(input) = &input_text {
-                let filename = path.file_name().and_then(std::ffi::OsStr::to_str);
+            let filename = path.file_name().and_then(std::ffi::OsStr::to_str);
+            if let Some(ref source_map) = source_map {
+                emit_annotated_source_files_error(&error, source_map, filename.unwrap_or("input"));
+            } else if let Some(input) = &input_text {
                 emit_annotated_error(&error, filename.unwrap_or("input"), input);
             }
             print_err(&error);
@@ -885,9 +1009,60 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn format_wgsl(args: FmtArgs) -> anyhow::Result<()> {
+    let format = |source: &str, path: &str| {
+        naga::front::wgsl::format(source).map_err(|error| {
+            anyhow!(
+                "Could not parse WGSL:\n{}",
+                error.emit_to_string_with_path(source, path)
+            )
+        })
+    };
+
+    if args.files.is_empty() {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        let formatted = format(&source, "stdin")?;
+        if args.check {
+            if formatted != source {
+                return Err(CliError("Input is not formatted").into());
+            }
+        } else {
+            print!("{formatted}");
+        }
+        return Ok(());
+    }
+
+    let mut unformatted = vec![];
+    for path in args.files {
+        let source = fs::read_to_string(&path).with_context(|| format!("Reading {path}"))?;
+        let formatted = format(&source, &path)?;
+        if formatted == source {
+            continue;
+        }
+        if args.check {
+            unformatted.push(path);
+        } else {
+            fs::write(&path, formatted).with_context(|| format!("Writing {path}"))?;
+        }
+    }
+
+    if !unformatted.is_empty() {
+        use std::fmt::Write;
+        let mut formatted = String::new();
+        writeln!(&mut formatted, "The following files are not formatted:").unwrap();
+        for path in unformatted {
+            writeln!(&mut formatted, "  {path}").unwrap();
+        }
+        return Err(anyhow!(formatted));
+    }
+
+    Ok(())
+}
+
 use codespan_reporting::{
     diagnostic::{Diagnostic, Label},
-    files::SimpleFile,
+    files::{SimpleFile, SimpleFiles},
     term::{
         self,
         termcolor::{ColorChoice, StandardStream},
@@ -911,3 +1086,38 @@ pub fn emit_annotated_error<E: Error>(ann_err: &WithSpan<E>, filename: &str, sou
 
     term::emit(&mut writer.lock(), &config, &files, &diagnostic).expect("cannot write error");
 }
+
+/// Like [`emit_annotated_error`], but for spans in input made of several
+/// files, like the high-level source recorded in a SPIR-V module's debug
+/// info, or GLSL with `#include` directives.
+///
+/// Files without a name, like the main file of a GLSL shader, are called
+/// `filename`.
+fn emit_annotated_source_files_error<E: Error>(
+    ann_err: &WithSpan<E>,
+    source_map: &naga::SourceMap,
+    filename: &str,
+) {
+    let mut files = SimpleFiles::new();
+    for file in source_map.files() {
+        let name = match file.name() {
+            "" => filename,
+            name => name,
+        };
+        files.add(name, source_map.source(file));
+    }
+    let config = codespan_reporting::term::Config::default();
+    let writer = StandardStream::stderr(ColorChoice::Auto);
+
+    let diagnostic = Diagnostic::error().with_labels(
+        ann_err
+            .spans()
+            .filter_map(|&(span, ref desc)| {
+                let (file, span) = source_map.file_for_span(span)?;
+                Some(Label::primary(file, span.to_range()?).with_message(desc.to_owned()))
+            })
+            .collect(),
+    );
+
+    term::emit(&mut writer.lock(), &config, &files, &diagnostic).expect("cannot write error");
+}
@@ -18,7 +18,7 @@ use std::ops;
 ///
 /// [`insert`]: HandleVec::insert
 /// [`HashMap::insert`]: std::collections::HashMap::insert
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct HandleVec<T, U> {
     inner: Vec<U>,
     as_keys: PhantomData<T>,
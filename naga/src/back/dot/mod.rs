@@ -369,6 +369,16 @@ impl StatementGraph {
                         crate::GatherMode::ShuffleXor(_) => "SubgroupShuffleXor",
                     }
                 }
+                S::DebugPrintf {
+                    format: _,
+                    ref arguments,
+                } => {
+                    for &arg in arguments {
+                        self.dependencies.push((id, arg, "arg"));
+                    }
+                    "DebugPrintf"
+                }
+                S::DiagnosticFilter(_) => "DiagnosticFilter",
             };
             // Set the last node to the merge node
             last_node = merge_id;
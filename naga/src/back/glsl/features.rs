@@ -1,4 +1,4 @@
-use super::{BackendResult, Error, Version, Writer};
+use super::{BackendResult, EntryPointWriter, Error, Version};
 use crate::{
     back::glsl::{Options, WriterFlags},
     AddressSpace, Binding, Expression, Handle, ImageClass, ImageDimension, Interpolation,
@@ -52,6 +52,8 @@ bitflags::bitflags! {
         const TEXTURE_SHADOW_LOD = 1 << 23;
         /// Subgroup operations
         const SUBGROUP_OPERATIONS = 1 << 24;
+        /// 2 byte floats.
+        const HALF_TYPE = 1 << 25;
     }
 }
 
@@ -120,6 +122,7 @@ impl FeaturesManager {
         check_feature!(DYNAMIC_ARRAY_SIZE, 430, 310);
         check_feature!(DUAL_SOURCE_BLENDING, 330, 300 /* with extension */);
         check_feature!(SUBGROUP_OPERATIONS, 430, 310);
+        check_feature!(HALF_TYPE, 450, 320);
         match version {
             Version::Embedded { is_webgl: true, .. } => check_feature!(MULTI_VIEW, 140, 300),
             _ => check_feature!(MULTI_VIEW, 140, 310),
@@ -164,6 +167,14 @@ impl FeaturesManager {
             writeln!(out, "#extension GL_ARB_gpu_shader_fp64 : require")?;
         }
 
+        if self.0.contains(Features::HALF_TYPE) {
+            // https://github.com/KhronosGroup/GLSL/blob/master/extensions/ext/GL_EXT_shader_explicit_arithmetic_types.txt
+            writeln!(
+                out,
+                "#extension GL_EXT_shader_explicit_arithmetic_types_float16 : require"
+            )?;
+        }
+
         if self.0.contains(Features::CUBE_TEXTURES_ARRAY) {
             if options.version.is_es() {
                 // https://www.khronos.org/registry/OpenGL/extensions/EXT/EXT_texture_cube_map_array.txt
@@ -282,7 +293,7 @@ impl FeaturesManager {
     }
 }
 
-impl<'a, W> Writer<'a, W> {
+impl<'a, W> EntryPointWriter<'a, W> {
     /// Helper method that searches the module for all the needed [`Features`]
     ///
     /// # Errors
@@ -319,6 +330,14 @@ impl<'a, W> Writer<'a, W> {
 
         for (ty_handle, ty) in self.module.types.iter() {
             match ty.inner {
+                TypeInner::Pointer {
+                    space: AddressSpace::PhysicalStorage,
+                    ..
+                } => {
+                    return Err(Error::Custom(
+                        "Physical storage pointers are not supported".to_string(),
+                    ))
+                }
                 TypeInner::Scalar(scalar)
                 | TypeInner::Vector { scalar, .. }
                 | TypeInner::Matrix { scalar, .. } => self.scalar_required_features(scalar),
@@ -541,6 +560,9 @@ impl<'a, W> Writer<'a, W> {
                 Expression::SubgroupOperationResult { .. } => {
                     features.request(Features::SUBGROUP_OPERATIONS)
                 }
+                Expression::Literal(crate::Literal::F16(_)) => {
+                    features.request(Features::HALF_TYPE)
+                }
                 _ => {}
             }
             }
@@ -551,8 +573,12 @@ impl<'a, W> Writer<'a, W> {
 
     /// Helper method that checks the [`Features`] needed by a scalar
     fn scalar_required_features(&mut self, scalar: Scalar) {
-        if scalar.kind == ScalarKind::Float && scalar.width == 8 {
-            self.features.request(Features::DOUBLE_TYPE);
+        if scalar.kind == ScalarKind::Float {
+            match scalar.width {
+                8 => self.features.request(Features::DOUBLE_TYPE),
+                2 => self.features.request(Features::HALF_TYPE),
+                _ => {}
+            }
         }
     }
 
@@ -52,6 +52,7 @@ use crate::{
 };
 use features::FeaturesManager;
 use std::{
+    borrow::Cow,
     cmp::Ordering,
     fmt::{self, Error as FmtError, Write},
     mem,
@@ -112,7 +113,8 @@ impl crate::AddressSpace {
             | crate::AddressSpace::Uniform
             | crate::AddressSpace::Storage { .. }
             | crate::AddressSpace::Handle
-            | crate::AddressSpace::PushConstant => false,
+            | crate::AddressSpace::PushConstant
+            | crate::AddressSpace::PhysicalStorage => false,
         }
     }
 }
@@ -498,6 +500,8 @@ pub enum Error {
     Custom(String),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error(transparent)]
+    PointerArguments(#[from] back::pointer_arguments::PointerArgumentError),
 }
 
 /// Binary operation with a different logic on the GLSL side.
@@ -513,7 +517,99 @@ enum BinaryOperation {
 }
 
 /// Writer responsible for all code generation.
+///
+/// This checks the module and specializes any functions that take pointers
+/// into buffers or workgroup memory, then writes the selected entry point.
 pub struct Writer<'a, W> {
+    /// The output writer.
+    out: W,
+    /// The module being written, with pointer arguments specialized.
+    module: Cow<'a, crate::Module>,
+    /// The analysis of `module`.
+    info: Cow<'a, valid::ModuleInfo>,
+    /// User defined configuration to be used.
+    options: &'a Options,
+    /// The pipeline configuration to be used.
+    pipeline_options: &'a PipelineOptions,
+    /// The bound checking policies to be used
+    policies: proc::BoundsCheckPolicies,
+    /// The index of the selected entry point.
+    entry_point_idx: usize,
+}
+
+impl<'a, W: Write> Writer<'a, W> {
+    /// Creates a new [`Writer`] instance.
+    ///
+    /// # Errors
+    /// - If the version specified is invalid or supported.
+    /// - If the entry point couldn't be found in the module.
+    /// - If specializing pointer arguments fails.
+    pub fn new(
+        out: W,
+        module: &'a crate::Module,
+        info: &'a valid::ModuleInfo,
+        options: &'a Options,
+        pipeline_options: &'a PipelineOptions,
+        policies: proc::BoundsCheckPolicies,
+    ) -> Result<Self, Error> {
+        if !module.overrides.is_empty() {
+            return Err(Error::Override);
+        }
+
+        // Check if the requested version is supported
+        if !options.version.is_supported() {
+            log::error!("Version {}", options.version);
+            return Err(Error::VersionNotSupported);
+        }
+
+        // Try to find the entry point and corresponding index
+        let entry_point_idx = module
+            .entry_points
+            .iter()
+            .position(|ep| {
+                pipeline_options.shader_stage == ep.stage && pipeline_options.entry_point == ep.name
+            })
+            .ok_or(Error::EntryPointNotFound)?;
+
+        let (module, info) = back::pointer_arguments::specialize_pointer_arguments(module, info)?;
+
+        Ok(Self {
+            out,
+            module,
+            info,
+            options,
+            pipeline_options,
+            policies,
+            entry_point_idx,
+        })
+    }
+
+    /// Writes the [`Module`](crate::Module) as glsl to the output
+    ///
+    /// # Errors
+    /// - If the version specified doesn't support some used features.
+    ///
+    /// # Notes
+    /// If an error occurs while writing, the output might have been written partially
+    ///
+    /// # Panics
+    /// Might panic if the module is invalid
+    pub fn write(&mut self) -> Result<ReflectionInfo, Error> {
+        EntryPointWriter::new(
+            &mut self.out,
+            &self.module,
+            &self.info,
+            self.options,
+            self.pipeline_options,
+            self.policies,
+            self.entry_point_idx,
+        )?
+        .write()
+    }
+}
+
+/// Writer for a single entry point of a module that [`Writer`] has prepared.
+struct EntryPointWriter<'a, W> {
     // Inputs
     /// The module being written.
     module: &'a crate::Module,
@@ -551,40 +647,20 @@ pub struct Writer<'a, W> {
     varying: crate::FastHashMap<String, VaryingLocation>,
 }
 
-impl<'a, W: Write> Writer<'a, W> {
-    /// Creates a new [`Writer`] instance.
+impl<'a, W: Write> EntryPointWriter<'a, W> {
+    /// Creates a new [`EntryPointWriter`] for the entry point at `ep_idx`.
     ///
     /// # Errors
-    /// - If the version specified is invalid or supported.
-    /// - If the entry point couldn't be found in the module.
     /// - If the version specified doesn't support some used features.
-    pub fn new(
+    fn new(
         out: W,
         module: &'a crate::Module,
         info: &'a valid::ModuleInfo,
         options: &'a Options,
         pipeline_options: &'a PipelineOptions,
         policies: proc::BoundsCheckPolicies,
+        ep_idx: usize,
     ) -> Result<Self, Error> {
-        if !module.overrides.is_empty() {
-            return Err(Error::Override);
-        }
-
-        // Check if the requested version is supported
-        if !options.version.is_supported() {
-            log::error!("Version {}", options.version);
-            return Err(Error::VersionNotSupported);
-        }
-
-        // Try to find the entry point and corresponding index
-        let ep_idx = module
-            .entry_points
-            .iter()
-            .position(|ep| {
-                pipeline_options.shader_stage == ep.stage && pipeline_options.entry_point == ep.name
-            })
-            .ok_or(Error::EntryPointNotFound)?;
-
         // Generate a map with names required to write the module
         let mut names = crate::FastHashMap::default();
         let mut namer = proc::Namer::default();
@@ -629,13 +705,7 @@ impl<'a, W: Write> Writer<'a, W> {
     }
 
     /// Writes the [`Module`](crate::Module) as glsl to the output
-    ///
-    /// # Notes
-    /// If an error occurs while writing, the output might have been written partially
-    ///
-    /// # Panics
-    /// Might panic if the module is invalid
-    pub fn write(&mut self) -> Result<ReflectionInfo, Error> {
+    fn write(&mut self) -> Result<ReflectionInfo, Error> {
         // We use `writeln!(self.out)` throughout the write to add newlines
         // to make the output more readable
 
@@ -726,6 +796,9 @@ impl<'a, W: Write> Writer<'a, W> {
                     .inner
                     .is_dynamically_sized(&self.module.types)
                 {
+                    back::write_doc_comments(&mut self.out, back::Level(0), self.module, |doc| {
+                        doc.types.get(&handle)
+                    })?;
                     let name = &self.names[&NameKey::Type(handle)];
                     write!(self.out, "struct {name} ")?;
                     self.write_struct_body(handle, members)?;
@@ -741,13 +814,19 @@ impl<'a, W: Write> Writer<'a, W> {
                 | &crate::PredeclaredType::FrexpResult { size, width } => {
                     let arg_type_name_owner;
                     let arg_type_name = if let Some(size) = size {
-                        arg_type_name_owner =
-                            format!("{}vec{}", if width == 8 { "d" } else { "" }, size as u8);
+                        let prefix = match width {
+                            8 => "d",
+                            2 => "f16",
+                            _ => "",
+                        };
+                        arg_type_name_owner = format!("{prefix}vec{}", size as u8);
                         &arg_type_name_owner
-                    } else if width == 8 {
-                        "double"
                     } else {
-                        "float"
+                        match width {
+                            8 => "double",
+                            2 => "float16_t",
+                            _ => "float",
+                        }
                     };
 
                     let other_type_name_owner;
@@ -834,6 +913,10 @@ impl<'a, W: Write> Writer<'a, W> {
                     arrayed,
                     class,
                 } => {
+                    back::write_doc_comments(&mut self.out, back::Level(0), self.module, |doc| {
+                        doc.global_variables.get(&handle)
+                    })?;
+
                     // Gather the storage format if needed
                     let storage_format_access = match self.module.types[global.ty].inner {
                         TypeInner::Image {
@@ -968,6 +1051,8 @@ impl<'a, W: Write> Writer<'a, W> {
             crate::ArraySize::Constant(size) => {
                 write!(self.out, "{size}")?;
             }
+            // Modules with overrides are rejected by `Writer::new`.
+            crate::ArraySize::Pending(_) => unreachable!(),
             crate::ArraySize::Dynamic => (),
         }
 
@@ -1128,6 +1213,10 @@ impl<'a, W: Write> Writer<'a, W> {
         handle: Handle<crate::GlobalVariable>,
         global: &crate::GlobalVariable,
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), self.module, |doc| {
+            doc.global_variables.get(&handle)
+        })?;
+
         if self.options.version.supports_explicit_locations() {
             if let Some(ref br) = global.binding {
                 match self.options.binding_map.get(br) {
@@ -1184,8 +1273,10 @@ impl<'a, W: Write> Writer<'a, W> {
             // A global variable in the `Function` address space is a
             // contradiction in terms.
             crate::AddressSpace::Function => unreachable!(),
-            // Textures and samplers are handled directly in `Writer::write`.
+            // Textures and samplers are handled directly in `EntryPointWriter::write`.
             crate::AddressSpace::Handle => unreachable!(),
+            // Nothing can be declared in the physical storage address space.
+            crate::AddressSpace::PhysicalStorage => unreachable!(),
         }
 
         Ok(())
@@ -1423,7 +1514,7 @@ impl<'a, W: Write> Writer<'a, W> {
     /// shader stages are being connected, and choose the `in` or `out` storage
     /// qualifier.
     ///
-    /// [`self.entry_point`]: Writer::entry_point
+    /// [`self.entry_point`]: EntryPointWriter::entry_point
     /// [`self.entry_point.stage`]: crate::EntryPoint::stage
     /// [`Location`]: crate::Binding::Location
     /// [`BuiltIn`]: crate::Binding::BuiltIn
@@ -1584,6 +1675,16 @@ impl<'a, W: Write> Writer<'a, W> {
         self.named_expressions.clear();
         self.update_expressions_to_bake(func, info);
 
+        back::write_doc_comments(
+            &mut self.out,
+            back::Level(0),
+            self.module,
+            |doc| match ctx.ty {
+                back::FunctionType::Function(handle) => doc.functions.get(&handle),
+                back::FunctionType::EntryPoint(index) => doc.entry_points.get(&(index as usize)),
+            },
+        )?;
+
         // Write the function header
         //
         // glsl headers are the same as in c:
@@ -1842,6 +1943,9 @@ impl<'a, W: Write> Writer<'a, W> {
 
     /// Helper method used to write global constants
     fn write_global_constant(&mut self, handle: Handle<crate::Constant>) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), self.module, |doc| {
+            doc.constants.get(&handle)
+        })?;
         write!(self.out, "const ")?;
         let constant = &self.module.constants[handle];
         self.write_type(constant.ty)?;
@@ -1912,6 +2016,9 @@ impl<'a, W: Write> Writer<'a, W> {
         writeln!(self.out, "{{")?;
 
         for (idx, member) in members.iter().enumerate() {
+            back::write_doc_comments(&mut self.out, back::Level(1), self.module, |doc| {
+                doc.struct_members.get(&(handle, idx))
+            })?;
             // The indentation is only for readability
             write!(self.out, "{}", back::INDENT)?;
 
@@ -2516,6 +2623,10 @@ impl<'a, W: Write> Writer<'a, W> {
                 }
                 writeln!(self.out, ");")?;
             }
+            // OpenGL has no way to print from a shader.
+            Statement::DebugPrintf { .. } => {}
+            // Diagnostic filters only affect validation.
+            Statement::DiagnosticFilter(_) => {}
         }
 
         Ok(())
@@ -2578,6 +2689,7 @@ impl<'a, W: Write> Writer<'a, W> {
                     // decimal part even it's zero which is needed for a valid glsl float constant
                     crate::Literal::F64(value) => write!(self.out, "{:?}LF", value)?,
                     crate::Literal::F32(value) => write!(self.out, "{:?}", value)?,
+                    crate::Literal::F16(value) => write!(self.out, "{:?}HF", value)?,
                     // Unsigned integers need a `u` at the end
                     //
                     // While `core` doesn't necessarily need it, it's allowed and since `es` needs it we
@@ -4340,6 +4452,7 @@ impl<'a, W: Write> Writer<'a, W> {
                     .expect("Bad array size")
                 {
                     proc::IndexableLength::Known(count) => count,
+                    proc::IndexableLength::Pending => unreachable!(),
                     proc::IndexableLength::Dynamic => return Ok(()),
                 };
                 self.write_type(base)?;
@@ -4585,7 +4698,7 @@ struct ScalarString<'a> {
 /// Check [`ScalarString`] for the information provided
 ///
 /// # Errors
-/// If a [`Float`](crate::ScalarKind::Float) with an width that isn't 4 or 8
+/// If a [`Float`](crate::ScalarKind::Float) with an width that isn't 2, 4 or 8
 const fn glsl_scalar(scalar: crate::Scalar) -> Result<ScalarString<'static>, Error> {
     use crate::ScalarKind as Sk;
 
@@ -4599,6 +4712,10 @@ const fn glsl_scalar(scalar: crate::Scalar) -> Result<ScalarString<'static>, Err
             full: "uint",
         },
         Sk::Float => match scalar.width {
+            2 => ScalarString {
+                prefix: "f16",
+                full: "float16_t",
+            },
             4 => ScalarString {
                 prefix: "",
                 full: "float",
@@ -4688,6 +4805,7 @@ const fn glsl_storage_qualifier(space: crate::AddressSpace) -> Option<&'static s
         As::Handle => Some("uniform"),
         As::WorkGroup => Some("shared"),
         As::PushConstant => Some("uniform"),
+        As::PhysicalStorage => None,
     }
 }
 
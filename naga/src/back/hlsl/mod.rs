@@ -263,6 +263,8 @@ pub enum Error {
     Custom(String),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error(transparent)]
+    PointerArguments(#[from] back::pointer_arguments::PointerArgumentError),
 }
 
 #[derive(Default)]
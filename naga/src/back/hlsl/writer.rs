@@ -205,6 +205,24 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             return Err(Error::Override);
         }
 
+        let (module, module_info) =
+            back::pointer_arguments::specialize_pointer_arguments(module, module_info)?;
+        let (module, module_info) = (module.as_ref(), module_info.as_ref());
+
+        if module.types.iter().any(|(_, ty)| {
+            matches!(
+                ty.inner,
+                TypeInner::Pointer {
+                    space: crate::AddressSpace::PhysicalStorage,
+                    ..
+                }
+            )
+        }) {
+            return Err(Error::Unimplemented(
+                "Physical storage pointers are not supported".to_string(),
+            ));
+        }
+
         self.reset(module);
 
         // Write special constants, if needed
@@ -341,6 +359,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
 
             self.write_wrapped_functions(module, &ctx)?;
 
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.functions.get(&handle)
+            })?;
             self.write_function(module, name.as_str(), function, &ctx, info)?;
 
             writeln!(self.out)?;
@@ -380,6 +401,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
 
             self.write_wrapped_functions(module, &ctx)?;
 
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.entry_points.get(&index)
+            })?;
             if ep.stage == ShaderStage::Compute {
                 // HLSL is calling workgroup size "num threads"
                 let num_threads = ep.workgroup_size;
@@ -748,9 +772,14 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             }
         }
 
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.global_variables.get(&handle)
+        })?;
+
         // https://docs.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl-variable-register
         let register_ty = match global.space {
             crate::AddressSpace::Function => unreachable!("Function address space"),
+            crate::AddressSpace::PhysicalStorage => unreachable!("PhysicalStorage address space"),
             crate::AddressSpace::Private => {
                 write!(self.out, "static ")?;
                 self.write_type(module, global.ty)?;
@@ -899,6 +928,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         module: &Module,
         handle: Handle<crate::Constant>,
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.constants.get(&handle)
+        })?;
         write!(self.out, "static const ")?;
         let constant = &module.constants[handle];
         self.write_type(module, constant.ty)?;
@@ -926,7 +958,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             crate::ArraySize::Constant(size) => {
                 write!(self.out, "{size}")?;
             }
-            crate::ArraySize::Dynamic => unreachable!(),
+            crate::ArraySize::Pending(_) | crate::ArraySize::Dynamic => unreachable!(),
         }
 
         write!(self.out, "]")?;
@@ -955,6 +987,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         span: u32,
         shader_stage: Option<(ShaderStage, Io)>,
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.types.get(&handle)
+        })?;
+
         // Write struct name
         let struct_name = &self.names[&NameKey::Type(handle)];
         writeln!(self.out, "struct {struct_name} {{")?;
@@ -973,6 +1009,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             let ty_inner = &module.types[member.ty].inner;
             last_offset = member.offset + ty_inner.size_hlsl(module.to_ctx());
 
+            back::write_doc_comments(&mut self.out, back::Level(1), module, |doc| {
+                doc.struct_members.get(&(handle, index))
+            })?;
+
             // The indentation is only for readability
             write!(self.out, "{}", back::INDENT)?;
 
@@ -2220,6 +2260,30 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 }
                 writeln!(self.out, ");")?;
             }
+            Statement::DebugPrintf {
+                ref format,
+                ref arguments,
+            } => {
+                write!(self.out, "{level}printf(\"")?;
+                for c in format.chars() {
+                    match c {
+                        '"' => write!(self.out, "\\\"")?,
+                        '\\' => write!(self.out, "\\\\")?,
+                        '\n' => write!(self.out, "\\n")?,
+                        '\r' => write!(self.out, "\\r")?,
+                        '\t' => write!(self.out, "\\t")?,
+                        _ => write!(self.out, "{c}")?,
+                    }
+                }
+                write!(self.out, "\"")?;
+                for &argument in arguments {
+                    write!(self.out, ", ")?;
+                    self.write_expr(module, argument, func_ctx)?;
+                }
+                writeln!(self.out, ");")?;
+            }
+            // Diagnostic filters only affect validation.
+            Statement::DiagnosticFilter(_) => {}
         }
 
         Ok(())
@@ -2256,6 +2320,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 // decimal part even it's zero
                 crate::Literal::F64(value) => write!(self.out, "{value:?}L")?,
                 crate::Literal::F32(value) => write!(self.out, "{value:?}")?,
+                crate::Literal::F16(value) => write!(self.out, "{value:?}h")?,
                 crate::Literal::U32(value) => write!(self.out, "{}u", value)?,
                 crate::Literal::I32(value) => write!(self.out, "{}", value)?,
                 crate::Literal::U64(value) => write!(self.out, "{}uL", value)?,
@@ -23,6 +23,13 @@ pub mod wgsl;
     feature = "glsl-out"
 ))]
 pub mod pipeline_constants;
+#[cfg(any(
+    feature = "hlsl-out",
+    feature = "msl-out",
+    feature = "spv-out",
+    feature = "glsl-out"
+))]
+pub mod pointer_arguments;
 
 /// Names of vector components.
 pub const COMPONENTS: &[char] = &['x', 'y', 'z', 'w'];
@@ -73,6 +80,32 @@ impl std::fmt::Display for Level {
     }
 }
 
+/// Write the documentation that `lookup` finds in `module`'s [`DocComments`]
+/// as `///` comments at `level`.
+///
+/// Every text language we generate treats `//` as the start of a line
+/// comment, so all the text back ends write documentation the same way.
+///
+/// [`DocComments`]: crate::DocComments
+fn write_doc_comments<'a>(
+    out: &mut impl std::fmt::Write,
+    level: Level,
+    module: &'a crate::Module,
+    lookup: impl FnOnce(&'a crate::DocComments) -> Option<&'a Vec<String>>,
+) -> std::fmt::Result {
+    let Some(comments) = module.doc_comments.as_deref().and_then(lookup) else {
+        return Ok(());
+    };
+    for line in comments {
+        if line.is_empty() {
+            writeln!(out, "{level}///")?;
+        } else {
+            writeln!(out, "{level}/// {line}")?;
+        }
+    }
+    Ok(())
+}
+
 /// Whether we're generating an entry point or a regular function.
 ///
 /// Backend languages often require different code for a [`Function`]
@@ -150,6 +150,8 @@ pub enum Error {
     UnsupportedRayTracing,
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error(transparent)]
+    PointerArguments(#[from] crate::back::pointer_arguments::PointerArgumentError),
 }
 
 #[derive(Clone, Debug, PartialEq, thiserror::Error)]
@@ -159,9 +159,11 @@ impl<'a> Display for TypeContext<'a> {
                 write!(out, "{}::atomic_{}", NAMESPACE, scalar.to_msl_name())
             }
             crate::TypeInner::Vector { size, scalar } => put_numeric_type(out, scalar, &[size]),
-            crate::TypeInner::Matrix { columns, rows, .. } => {
-                put_numeric_type(out, crate::Scalar::F32, &[rows, columns])
-            }
+            crate::TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => put_numeric_type(out, scalar, &[rows, columns]),
             crate::TypeInner::Pointer { base, space } => {
                 let sub = Self {
                     handle: base,
@@ -172,7 +174,11 @@ impl<'a> Display for TypeContext<'a> {
                     Some(name) => name,
                     None => return Ok(()),
                 };
-                write!(out, "{space_name} {sub}&")
+                let declarator = match space {
+                    crate::AddressSpace::PhysicalStorage => '*',
+                    _ => '&',
+                };
+                write!(out, "{space_name} {sub}{declarator}")
             }
             crate::TypeInner::ValuePointer {
                 size,
@@ -188,7 +194,10 @@ impl<'a> Display for TypeContext<'a> {
                     None => put_numeric_type(out, scalar, &[])?,
                 };
 
-                write!(out, "&")
+                match space {
+                    crate::AddressSpace::PhysicalStorage => write!(out, "*"),
+                    _ => write!(out, "&"),
+                }
             }
             crate::TypeInner::Array { base, .. } => {
                 let sub = Self {
@@ -380,6 +389,10 @@ impl crate::Scalar {
     fn to_msl_name(self) -> &'static str {
         use crate::ScalarKind as Sk;
         match self {
+            Self {
+                kind: Sk::Float,
+                width: 2,
+            } => "half",
             Self {
                 kind: Sk::Float,
                 width: _,
@@ -440,7 +453,7 @@ fn should_pack_struct_member(
     match *ty_inner {
         crate::TypeInner::Vector {
             size: crate::VectorSize::Tri,
-            scalar: scalar @ crate::Scalar { width: 4, .. },
+            scalar: scalar @ crate::Scalar { width: 2 | 4, .. },
         } if is_tight => Some(scalar),
         _ => None,
     }
@@ -480,7 +493,7 @@ impl crate::AddressSpace {
             | Self::WorkGroup
             | Self::PushConstant
             | Self::Handle => true,
-            Self::Function => false,
+            Self::Function | Self::PhysicalStorage => false,
         }
     }
 
@@ -497,7 +510,7 @@ impl crate::AddressSpace {
             // These translate to `constant` address space, no need for qualifiers.
             Self::Uniform | Self::PushConstant => false,
             // Not applicable.
-            Self::Handle | Self::Function => false,
+            Self::Handle | Self::Function | Self::PhysicalStorage => false,
         }
     }
 
@@ -505,7 +518,7 @@ impl crate::AddressSpace {
         match self {
             Self::Handle => None,
             Self::Uniform | Self::PushConstant => Some("constant"),
-            Self::Storage { .. } => Some("device"),
+            Self::Storage { .. } | Self::PhysicalStorage => Some("device"),
             Self::Private | Self::Function => Some("thread"),
             Self::WorkGroup => Some("threadgroup"),
         }
@@ -594,6 +607,21 @@ impl<'a> ExpressionContext<'a> {
         self.info[handle].ty.inner_with(&self.module.types)
     }
 
+    /// Return true if `handle` is a pointer into the physical storage address
+    /// space held as a value, rather than a step in an access chain through one.
+    fn is_physical_pointer_value(&self, handle: Handle<crate::Expression>) -> bool {
+        if self.resolve_type(handle).pointer_space() != Some(crate::AddressSpace::PhysicalStorage) {
+            return false;
+        }
+        match self.function.expressions[handle] {
+            crate::Expression::Access { base, .. }
+            | crate::Expression::AccessIndex { base, .. } => {
+                self.resolve_type(base).pointer_space().is_none()
+            }
+            _ => true,
+        }
+    }
+
     /// Return true if calls to `image`'s `read` and `write` methods should supply a level of detail.
     ///
     /// Only mipmapped images need to specify a level of detail. Since 1D
@@ -1209,7 +1237,7 @@ impl<W: Write> Writer<W> {
             self.out,
             "{NAMESPACE}::atomic_{key}_explicit({ATOMIC_REFERENCE}"
         )?;
-        self.put_access_chain(pointer, policy, context)?;
+        self.put_pointer_access_chain(pointer, policy, context)?;
         write!(self.out, ", ")?;
         self.put_expression(value, context, true)?;
         write!(self.out, ", {NAMESPACE}::memory_order_relaxed)")?;
@@ -1332,6 +1360,16 @@ impl<W: Write> Writer<W> {
                         write!(self.out, "{value}{suffix}")?;
                     }
                 }
+                crate::Literal::F16(value) => {
+                    if value.is_infinite() {
+                        let sign = if value.is_sign_negative() { "-" } else { "" };
+                        write!(self.out, "{sign}INFINITY")?;
+                    } else if value.is_nan() {
+                        write!(self.out, "NAN")?;
+                    } else {
+                        write!(self.out, "{value:?}h")?;
+                    }
+                }
                 crate::Literal::U32(value) => {
                     write!(self.out, "{value}u")?;
                 }
@@ -2322,6 +2360,7 @@ impl<W: Write> Writer<W> {
                     self.out.write_str(") < ")?;
                     match length {
                         index::IndexableLength::Known(value) => write!(self.out, "{value}")?,
+                        index::IndexableLength::Pending => unreachable!(),
                         index::IndexableLength::Dynamic => {
                             let global =
                                 context.function.originating_global(base).ok_or_else(|| {
@@ -2401,12 +2440,12 @@ impl<W: Write> Writer<W> {
                 match *base_ty {
                     crate::TypeInner::Struct { .. } => {
                         let base_ty = base_ty_handle.unwrap();
-                        self.put_access_chain(base, policy, context)?;
+                        self.put_pointer_access_chain(base, policy, context)?;
                         let name = &self.names[&NameKey::StructMember(base_ty, index)];
                         write!(self.out, ".{name}")?;
                     }
                     crate::TypeInner::ValuePointer { .. } | crate::TypeInner::Vector { .. } => {
-                        self.put_access_chain(base, policy, context)?;
+                        self.put_pointer_access_chain(base, policy, context)?;
                         // Prior to Metal v2.1 component access for packed vectors wasn't available
                         // however array indexing is
                         if context.get_packed_vec_kind(base).is_some() {
@@ -2432,6 +2471,30 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
+    /// Write the access chain `chain`, which must produce a pointer, as the
+    /// location it refers to.
+    ///
+    /// Pointers into the [`PhysicalStorage`] address space are values, which
+    /// we write as `device T*` pointers rather than references, so when
+    /// `chain` is such a pointer we dereference it.
+    ///
+    /// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
+    fn put_pointer_access_chain(
+        &mut self,
+        chain: Handle<crate::Expression>,
+        policy: index::BoundsCheckPolicy,
+        context: &ExpressionContext,
+    ) -> BackendResult {
+        if context.is_physical_pointer_value(chain) {
+            write!(self.out, "(*")?;
+            self.put_access_chain(chain, policy, context)?;
+            write!(self.out, ")")?;
+        } else {
+            self.put_access_chain(chain, policy, context)?;
+        }
+        Ok(())
+    }
+
     /// Write a `[]`-style access of `base` by `index`.
     ///
     /// If `policy` is [`Restrict`], then generate code as needed to force all index
@@ -2464,7 +2527,7 @@ impl<W: Write> Writer<W> {
             _ => false,
         };
 
-        self.put_access_chain(base, policy, context)?;
+        self.put_pointer_access_chain(base, policy, context)?;
         if accessing_wrapped_array {
             write!(self.out, ".{WRAPPED_ARRAY_FIELD}")?;
         }
@@ -2484,6 +2547,7 @@ impl<W: Write> Writer<W> {
                 index::IndexableLength::Known(limit) => {
                     write!(self.out, "{}u", limit - 1)?;
                 }
+                index::IndexableLength::Pending => unreachable!(),
                 index::IndexableLength::Dynamic => {
                     let global = context.function.originating_global(base).ok_or_else(|| {
                         Error::GenericValidation("Could not find originating global".into())
@@ -2547,13 +2611,13 @@ impl<W: Write> Writer<W> {
                 self.out,
                 "{NAMESPACE}::atomic_load_explicit({ATOMIC_REFERENCE}"
             )?;
-            self.put_access_chain(pointer, policy, context)?;
+            self.put_pointer_access_chain(pointer, policy, context)?;
             write!(self.out, ", {NAMESPACE}::memory_order_relaxed)")?;
         } else {
             // We don't do any dereferencing with `*` here as pointer arguments to functions
             // are done by `&` references and not `*` pointers. These do not need to be
             // dereferenced.
-            self.put_access_chain(pointer, policy, context)?;
+            self.put_pointer_access_chain(pointer, policy, context)?;
         }
 
         Ok(())
@@ -3327,6 +3391,10 @@ impl<W: Write> Writer<W> {
                     }
                     writeln!(self.out, ");")?;
                 }
+                // Metal has no `printf`-style printing from shaders.
+                crate::Statement::DebugPrintf { .. } => {}
+                // Diagnostic filters only affect validation.
+                crate::Statement::DiagnosticFilter(_) => {}
             }
         }
 
@@ -3381,13 +3449,13 @@ impl<W: Write> Writer<W> {
                 self.out,
                 "{level}{NAMESPACE}::atomic_store_explicit({ATOMIC_REFERENCE}"
             )?;
-            self.put_access_chain(pointer, policy, &context.expression)?;
+            self.put_pointer_access_chain(pointer, policy, &context.expression)?;
             write!(self.out, ", ")?;
             self.put_expression(value, &context.expression, true)?;
             writeln!(self.out, ", {NAMESPACE}::memory_order_relaxed);")?;
         } else {
             write!(self.out, "{level}")?;
-            self.put_access_chain(pointer, policy, &context.expression)?;
+            self.put_pointer_access_chain(pointer, policy, &context.expression)?;
             write!(self.out, " = ")?;
             self.put_expression(value, &context.expression, true)?;
             writeln!(self.out, ";")?;
@@ -3407,6 +3475,9 @@ impl<W: Write> Writer<W> {
             return Err(Error::Override);
         }
 
+        let (module, info) = back::pointer_arguments::specialize_pointer_arguments(module, info)?;
+        let (module, info) = (module.as_ref(), info.as_ref());
+
         self.names.clear();
         self.namer.reset(
             module,
@@ -3604,6 +3675,7 @@ impl<W: Write> Writer<W> {
                             )?;
                             writeln!(self.out, "}};")?;
                         }
+                        crate::ArraySize::Pending(_) => unreachable!(),
                         crate::ArraySize::Dynamic => {
                             writeln!(self.out, "typedef {base_name} {name}[1];")?;
                         }
@@ -3612,6 +3684,9 @@ impl<W: Write> Writer<W> {
                 crate::TypeInner::Struct {
                     ref members, span, ..
                 } => {
+                    back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                        doc.types.get(&handle)
+                    })?;
                     writeln!(self.out, "struct {name} {{")?;
                     let mut last_offset = 0;
                     for (index, member) in members.iter().enumerate() {
@@ -3623,6 +3698,9 @@ impl<W: Write> Writer<W> {
                         let ty_inner = &module.types[member.ty].inner;
                         last_offset = member.offset + ty_inner.size(module.to_ctx());
 
+                        back::write_doc_comments(&mut self.out, back::Level(1), module, |doc| {
+                            doc.struct_members.get(&(handle, index))
+                        })?;
                         let member_name = &self.names[&NameKey::StructMember(handle, index as u32)];
 
                         // If the member should be packed (as is the case for a misaligned vec3) issue a packed vector
@@ -3690,14 +3768,20 @@ impl<W: Write> Writer<W> {
                     let arg_type_name = if let Some(size) = size {
                         arg_type_name_owner = format!(
                             "{NAMESPACE}::{}{}",
-                            if width == 8 { "double" } else { "float" },
+                            match width {
+                                8 => "double",
+                                2 => "half",
+                                _ => "float",
+                            },
                             size as u8
                         );
                         &arg_type_name_owner
-                    } else if width == 8 {
-                        "double"
                     } else {
-                        "float"
+                        match width {
+                            8 => "double",
+                            2 => "half",
+                            _ => "float",
+                        }
                     };
 
                     let other_type_name_owner;
@@ -3751,6 +3835,9 @@ impl<W: Write> Writer<W> {
                 binding: None,
                 first_time: false,
             };
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.constants.get(&handle)
+            })?;
             let name = &self.names[&NameKey::Constant(handle)];
             write!(self.out, "constant {ty_name} {name} = ")?;
             self.put_const_expression(constant.init, module, mod_info)?;
@@ -4628,6 +4715,9 @@ impl<W: Write> Writer<W> {
             }
 
             writeln!(self.out)?;
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.functions.get(&fun_handle)
+            })?;
             let fun_name = &self.names[&NameKey::Function(fun_handle)];
             match fun.result {
                 Some(ref result) => {
@@ -4851,7 +4941,8 @@ impl<W: Write> Writer<W> {
                         }
                         crate::AddressSpace::Function
                         | crate::AddressSpace::Private
-                        | crate::AddressSpace::WorkGroup => {}
+                        | crate::AddressSpace::WorkGroup
+                        | crate::AddressSpace::PhysicalStorage => {}
                     }
                 }
                 if needs_buffer_sizes {
@@ -5068,6 +5159,9 @@ impl<W: Write> Writer<W> {
             }
 
             // Write the entry point function's name, and begin its argument list.
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.entry_points.get(&ep_index)
+            })?;
             writeln!(self.out, "{em_str} {result_type_name} {fun_name}(")?;
             let mut is_first_argument = true;
 
@@ -5298,6 +5392,9 @@ impl<W: Write> Writer<W> {
                 } else {
                     ','
                 };
+                back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                    doc.global_variables.get(&handle)
+                })?;
                 write!(self.out, "{separator} ")?;
                 tyvar.try_fmt(&mut self.out)?;
                 if let Some(resolved) = resolved {
@@ -5839,7 +5936,9 @@ mod workgroup_mem_init {
                         let count = match size.to_indexable_length(module).expect("Bad array size")
                         {
                             proc::IndexableLength::Known(count) => count,
-                            proc::IndexableLength::Dynamic => unreachable!(),
+                            proc::IndexableLength::Pending | proc::IndexableLength::Dynamic => {
+                                unreachable!()
+                            }
                         };
 
                         access_stack.enter_array(|access_stack, array_depth| {
@@ -3,10 +3,10 @@ use crate::{
     arena::HandleVec,
     proc::{ConstantEvaluator, ConstantEvaluatorError, Emitter},
     valid::{Capabilities, ModuleInfo, ValidationError, ValidationFlags, Validator},
-    Arena, Block, Constant, Expression, Function, Handle, Literal, Module, Override, Range, Scalar,
-    Span, Statement, TypeInner, WithSpan,
+    Arena, ArraySize, Block, Constant, Expression, Function, Handle, Literal, Module, Override,
+    PendingArraySize, Range, Scalar, Span, Statement, TypeInner, WithSpan,
 };
-use std::{borrow::Cow, collections::HashSet, mem};
+use std::{borrow::Cow, collections::HashSet, mem, num::NonZeroU32};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
@@ -18,6 +18,8 @@ pub enum PipelineConstantError {
     SrcNeedsToBeFinite,
     #[error("Source f64 value doesn't fit in destination")]
     DstRangeTooSmall,
+    #[error("Array element count must be positive (> 0)")]
+    NonPositiveArraySize,
     #[error(transparent)]
     ConstantEvaluatorError(#[from] ConstantEvaluatorError),
     #[error(transparent)]
@@ -190,6 +192,8 @@ pub fn process_overrides<'a>(
     }
     module.entry_points = entry_points;
 
+    process_pending_array_sizes(&mut module, &override_map, &adjusted_global_expressions)?;
+
     // Now that we've rewritten all the expressions, we need to
     // recompute their types and other metadata. For the time being,
     // do a full re-validation.
@@ -246,6 +250,11 @@ fn process_override(
         init,
     };
     let h = module.constants.append(constant, span);
+    if let Some(ref mut doc_comments) = module.doc_comments {
+        if let Some(comments) = doc_comments.overrides.swap_remove(&old_h) {
+            doc_comments.constants.insert(h, comments);
+        }
+    }
     override_map.insert(old_h, h);
     adjusted_constant_initializers.insert(h);
     Ok(h)
@@ -323,9 +332,154 @@ fn process_function(
     Ok(())
 }
 
+/// Replace every [`ArraySize::Pending`] in `module`'s types with the
+/// now-known constant size.
+///
+/// Since resolving a size may make an array type identical to one
+/// already in the arena, this rebuilds `module.types` from scratch and
+/// updates every type handle in `module` to refer to the new arena.
+///
+/// If `h` is a `Handle<Override>`, then `override_map[h]` is the
+/// `Handle<Constant>` for the override's final value.
+/// `adjusted_global_expressions` maps the original global expression
+/// handles to their fully-evaluated counterparts.
+///
+/// [`ArraySize::Pending`]: crate::ArraySize::Pending
+fn process_pending_array_sizes(
+    module: &mut Module,
+    override_map: &HandleVec<Override, Handle<Constant>>,
+    adjusted_global_expressions: &HandleVec<Expression, Handle<Expression>>,
+) -> Result<(), PipelineConstantError> {
+    let has_pending = module.types.iter().any(|(_, ty)| {
+        matches!(
+            ty.inner,
+            TypeInner::Array {
+                size: ArraySize::Pending(_),
+                ..
+            }
+        )
+    });
+    if !has_pending {
+        return Ok(());
+    }
+
+    let old_types = mem::take(&mut module.types);
+    let mut type_map = HandleVec::with_capacity(old_types.len());
+    for (old_h, ty) in old_types.iter() {
+        let mut ty = ty.clone();
+        match ty.inner {
+            TypeInner::Pointer { ref mut base, .. }
+            | TypeInner::BindingArray { ref mut base, .. } => *base = type_map[*base],
+            TypeInner::Array {
+                ref mut base,
+                ref mut size,
+                ..
+            } => {
+                *base = type_map[*base];
+                if let ArraySize::Pending(pending) = *size {
+                    let expr = match pending {
+                        PendingArraySize::Override(h) => module.constants[override_map[h]].init,
+                        PendingArraySize::Expression(h) => adjusted_global_expressions[h],
+                    };
+                    let value = module
+                        .to_ctx()
+                        .eval_expr_to_u32(expr)
+                        .ok()
+                        .and_then(NonZeroU32::new)
+                        .ok_or(PipelineConstantError::NonPositiveArraySize)?;
+                    *size = ArraySize::Constant(value);
+                }
+            }
+            TypeInner::Struct {
+                ref mut members, ..
+            } => {
+                for member in members.iter_mut() {
+                    member.ty = type_map[member.ty];
+                }
+            }
+            _ => {}
+        }
+        let new_h = module.types.insert(ty, old_types.get_span(old_h));
+        type_map.insert(old_h, new_h);
+    }
+
+    for (_, c) in module.constants.iter_mut() {
+        c.ty = type_map[c.ty];
+    }
+    for (_, v) in module.global_variables.iter_mut() {
+        v.ty = type_map[v.ty];
+    }
+    for (_, expr) in module.global_expressions.iter_mut() {
+        adjust_expr_type(&type_map, expr);
+    }
+
+    let special_types = &mut module.special_types;
+    for ty in [
+        &mut special_types.ray_desc,
+        &mut special_types.ray_intersection,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *ty = type_map[*ty];
+    }
+    for (_, ty) in special_types.predeclared_types.iter_mut() {
+        *ty = type_map[*ty];
+    }
+
+    if let Some(ref mut doc_comments) = module.doc_comments {
+        doc_comments.types = mem::take(&mut doc_comments.types)
+            .into_iter()
+            .map(|(ty, comments)| (type_map[ty], comments))
+            .collect();
+        doc_comments.struct_members = mem::take(&mut doc_comments.struct_members)
+            .into_iter()
+            .map(|((ty, index), comments)| ((type_map[ty], index), comments))
+            .collect();
+    }
+
+    let functions = module
+        .functions
+        .iter_mut()
+        .map(|(_, f)| f)
+        .chain(module.entry_points.iter_mut().map(|ep| &mut ep.function));
+    for function in functions {
+        for arg in function.arguments.iter_mut() {
+            arg.ty = type_map[arg.ty];
+        }
+        if let Some(ref mut result) = function.result {
+            result.ty = type_map[result.ty];
+        }
+        for (_, local) in function.local_variables.iter_mut() {
+            local.ty = type_map[local.ty];
+        }
+        for (_, expr) in function.expressions.iter_mut() {
+            adjust_expr_type(&type_map, expr);
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace every type handle in `expr` with its counterpart given by
+/// `type_map`.
+fn adjust_expr_type(type_map: &HandleVec<crate::Type, Handle<crate::Type>>, expr: &mut Expression) {
+    match *expr {
+        Expression::Compose { ref mut ty, .. }
+        | Expression::ZeroValue(ref mut ty)
+        | Expression::AtomicResult { ref mut ty, .. }
+        | Expression::WorkGroupUniformLoadResult { ref mut ty }
+        | Expression::SubgroupOperationResult { ref mut ty } => *ty = type_map[*ty],
+        _ => {}
+    }
+}
+
 /// Replace every expression handle in `expr` with its counterpart
 /// given by `new_pos`.
-fn adjust_expr(new_pos: &HandleVec<Expression, Handle<Expression>>, expr: &mut Expression) {
+pub(super) fn adjust_expr(
+    new_pos: &HandleVec<Expression, Handle<Expression>>,
+    expr: &mut Expression,
+) {
     let adjust = |expr: &mut Handle<Expression>| {
         *expr = new_pos[*expr];
     };
@@ -530,7 +684,7 @@ fn adjust_expr(new_pos: &HandleVec<Expression, Handle<Expression>>, expr: &mut E
 
 /// Replace every expression handle in `block` with its counterpart
 /// given by `new_pos`.
-fn adjust_block(new_pos: &HandleVec<Expression, Handle<Expression>>, block: &mut Block) {
+pub(super) fn adjust_block(new_pos: &HandleVec<Expression, Handle<Expression>>, block: &mut Block) {
     for stmt in block.iter_mut() {
         adjust_stmt(new_pos, stmt);
     }
@@ -707,7 +861,19 @@ fn adjust_stmt(new_pos: &HandleVec<Expression, Handle<Expression>>, stmt: &mut S
                 crate::RayQueryFunction::Terminate => {}
             }
         }
-        Statement::Break | Statement::Continue | Statement::Kill | Statement::Barrier(_) => {}
+        Statement::DebugPrintf {
+            format: _,
+            ref mut arguments,
+        } => {
+            for argument in arguments.iter_mut() {
+                adjust(argument);
+            }
+        }
+        Statement::Break
+        | Statement::Continue
+        | Statement::Kill
+        | Statement::Barrier(_)
+        | Statement::DiagnosticFilter(_) => {}
     }
 }
 
@@ -849,6 +1015,19 @@ fn map_value_to_literal(value: f64, scalar: Scalar) -> Result<Literal, PipelineC
 
             Ok(Literal::F32(value))
         }
+        Scalar::F16 => {
+            // https://gpuweb.github.io/gpuweb/#dom-gpuprogrammablestage-constants
+            if !value.is_finite() {
+                return Err(PipelineConstantError::SrcNeedsToBeFinite);
+            }
+
+            let value = half::f16::from_f64(value);
+            if !value.is_finite() {
+                return Err(PipelineConstantError::DstRangeTooSmall);
+            }
+
+            Ok(Literal::F16(value))
+        }
         Scalar::F64 => {
             // https://webidl.spec.whatwg.org/#js-double
             if !value.is_finite() {
@@ -876,7 +1055,13 @@ fn test_map_value_to_literal() {
         assert_eq!(map_value_to_literal(value, Scalar::BOOL), res);
     }
 
-    for scalar in [Scalar::I32, Scalar::U32, Scalar::F32, Scalar::F64] {
+    for scalar in [
+        Scalar::I32,
+        Scalar::U32,
+        Scalar::F16,
+        Scalar::F32,
+        Scalar::F64,
+    ] {
         for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
             let res = Err(PipelineConstantError::SrcNeedsToBeFinite);
             assert_eq!(map_value_to_literal(value, scalar), res);
@@ -945,6 +1130,24 @@ fn test_map_value_to_literal() {
         Err(PipelineConstantError::DstRangeTooSmall)
     );
 
+    // f16
+    assert_eq!(
+        map_value_to_literal(f64::from(half::f16::MIN), Scalar::F16),
+        Ok(Literal::F16(half::f16::MIN))
+    );
+    assert_eq!(
+        map_value_to_literal(f64::from(half::f16::MAX), Scalar::F16),
+        Ok(Literal::F16(half::f16::MAX))
+    );
+    assert_eq!(
+        map_value_to_literal(65520.0, Scalar::F16),
+        Err(PipelineConstantError::DstRangeTooSmall)
+    );
+    assert_eq!(
+        map_value_to_literal(-65520.0, Scalar::F16),
+        Err(PipelineConstantError::DstRangeTooSmall)
+    );
+
     // f64
     assert_eq!(
         map_value_to_literal(f64::MIN, Scalar::F64),
@@ -0,0 +1,610 @@
+/*!
+Specialization of functions that take pointers into buffers or workgroup memory.
+
+WGSL's `unrestricted_pointer_parameters` language extension lets functions
+take pointers into the [`Storage`], [`Uniform`] and [`WorkGroup`] address
+spaces, and Naga's IR allows the same. The WGSL back end can write such
+parameters directly, but the others can't: GLSL and HLSL have no way to pass a
+reference to a buffer to a function, SPIR-V's logical addressing model only
+permits pointer parameters in a few narrow cases, and MSL can only find the
+length of a runtime-sized array in a global variable.
+
+Back ends like these pass their module through
+[`specialize_pointer_arguments`] first. For each distinct way a function is
+called with such pointers, it makes a copy of the function that refers to the
+global variable directly, and rewrites the call to use that copy.
+
+The SPIR-V, HLSL, MSL and GLSL writers do this themselves, so callers only
+need it when using the specialized module for something else.
+
+[`Storage`]: crate::AddressSpace::Storage
+[`Uniform`]: crate::AddressSpace::Uniform
+[`WorkGroup`]: crate::AddressSpace::WorkGroup
+*/
+
+use super::pipeline_constants::{adjust_block, adjust_expr};
+use crate::{
+    arena::HandleVec,
+    valid::{Capabilities, ModuleInfo, ValidationError, ValidationFlags, Validator},
+    AddressSpace, Arena, Block, Expression, FastHashMap, FastIndexMap, Function, FunctionArgument,
+    GlobalVariable, Handle, Module, Range, Scalar, Span, Statement, Type, TypeInner, WithSpan,
+};
+use std::borrow::Cow;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone)]
+pub enum PointerArgumentError {
+    #[error(transparent)]
+    ValidationError(#[from] Box<WithSpan<ValidationError>>),
+}
+
+/// Replace all functions in `module` that take pointers into buffers or
+/// workgroup memory with specialized copies.
+///
+/// A pointer passed to a function must point into some global variable,
+/// perhaps indexed: `&buffer.values[i]`. At each call that passes pointers in
+/// the [`Storage`], [`Uniform`] or [`WorkGroup`] address spaces, this finds the
+/// global variable and the sequence of indices each pointer was built from,
+/// and calls a copy of the function specialized for those globals instead.
+/// The copy takes no pointer parameters for those arguments. It takes any
+/// non-constant indices as additional `u32` or `i32` parameters at the end,
+/// and rebuilds the pointers itself at the top of its body. Calls that pass
+/// the same globals with the same shape of indexing share a copy.
+///
+/// Functions that take such pointers are dropped from the result; only their
+/// specializations remain.
+///
+/// If no changes are needed, this just returns `Cow::Borrowed` references to
+/// `module` and `module_info`. Otherwise, it returns `Cow::Owned` values
+/// holding the rewritten module and its validation results.
+///
+/// [`Storage`]: AddressSpace::Storage
+/// [`Uniform`]: AddressSpace::Uniform
+/// [`WorkGroup`]: AddressSpace::WorkGroup
+pub fn specialize_pointer_arguments<'a>(
+    module: &'a Module,
+    module_info: &'a ModuleInfo,
+) -> Result<(Cow<'a, Module>, Cow<'a, ModuleInfo>), PointerArgumentError> {
+    if !needs_specialization(module) {
+        return Ok((Cow::Borrowed(module), Cow::Borrowed(module_info)));
+    }
+
+    let mut new_module = module.clone();
+    new_module.functions = Arena::new();
+    let entry_points = std::mem::take(&mut new_module.entry_points);
+
+    let mut specializer = Specializer {
+        original: module,
+        info: module_info,
+        module: new_module,
+        function_map: HandleVec::with_capacity(module.functions.len()),
+        specializations: FastHashMap::default(),
+        function_doc_comments: FastIndexMap::default(),
+    };
+
+    for (handle, function) in module.functions.iter() {
+        if takes_specialized_pointers(module, function) {
+            // Only copies of this function will be called.
+            specializer.function_map.insert(handle, None);
+            continue;
+        }
+
+        let fun_info = &module_info[handle];
+        let scalars = function
+            .expressions
+            .iter()
+            .map(|(expr, _)| specializer.scalar(&fun_info[expr].ty))
+            .collect::<Vec<_>>();
+        let mut function = function.clone();
+        specializer.rewrite_calls(&mut function, &scalars);
+
+        let span = module.functions.get_span(handle);
+        let new_handle = specializer.module.functions.append(function, span);
+        specializer.copy_doc_comments(handle, new_handle);
+        specializer.function_map.insert(handle, Some(new_handle));
+    }
+
+    for (index, mut entry_point) in entry_points.into_iter().enumerate() {
+        let ep_info = module_info.get_entry_point(index);
+        let scalars = entry_point
+            .function
+            .expressions
+            .iter()
+            .map(|(expr, _)| specializer.scalar(&ep_info[expr].ty))
+            .collect::<Vec<_>>();
+        specializer.rewrite_calls(&mut entry_point.function, &scalars);
+        specializer.module.entry_points.push(entry_point);
+    }
+
+    let mut module = specializer.module;
+    if let Some(ref mut doc_comments) = module.doc_comments {
+        doc_comments.functions = specializer.function_doc_comments;
+    }
+
+    // The new functions' expressions need types and other metadata. For the
+    // time being, do a full re-validation.
+    let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+    let module_info = validator.validate(&module).map_err(Box::new)?;
+
+    Ok((Cow::Owned(module), Cow::Owned(module_info)))
+}
+
+/// Return true if a pointer argument in `space` needs specializing away.
+const fn is_specialized_space(space: AddressSpace) -> bool {
+    matches!(
+        space,
+        AddressSpace::Storage { .. } | AddressSpace::Uniform | AddressSpace::WorkGroup
+    )
+}
+
+fn is_specialized_argument(module: &Module, argument: &FunctionArgument) -> bool {
+    module.types[argument.ty]
+        .inner
+        .pointer_space()
+        .is_some_and(is_specialized_space)
+}
+
+/// Return `true` if [`specialize_pointer_arguments`] would change `module`.
+fn needs_specialization(module: &Module) -> bool {
+    module
+        .functions
+        .iter()
+        .any(|(_, function)| takes_specialized_pointers(module, function))
+}
+
+fn takes_specialized_pointers(module: &Module, function: &Function) -> bool {
+    function
+        .arguments
+        .iter()
+        .any(|argument| is_specialized_argument(module, argument))
+}
+
+/// One step in building a pointer from a global variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Step {
+    /// An [`AccessIndex`] with the given index.
+    ///
+    /// [`AccessIndex`]: Expression::AccessIndex
+    Constant(u32),
+
+    /// An [`Access`] whose index has the given type, passed to the
+    /// specialized function as an extra argument.
+    ///
+    /// [`Access`]: Expression::Access
+    Index(Scalar),
+}
+
+/// How a pointer argument was built: a global variable, and a series of
+/// indexing operations applied to it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Root {
+    global: Handle<GlobalVariable>,
+    steps: Vec<Step>,
+}
+
+/// A function in the original module, specialized for particular pointers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Specialization {
+    function: Handle<Function>,
+
+    /// The roots of the pointers passed to `function`, one for each
+    /// argument in a specialized address space, in order.
+    roots: Vec<Root>,
+}
+
+struct Specializer<'a> {
+    original: &'a Module,
+    info: &'a ModuleInfo,
+
+    /// The module we're building.
+    module: Module,
+
+    /// The handles in `module` of the original module's functions, or
+    /// `None` for functions that take pointers we specialize away.
+    function_map: HandleVec<Function, Option<Handle<Function>>>,
+
+    /// The specializations we've generated so far.
+    specializations: FastHashMap<Specialization, Handle<Function>>,
+
+    /// Documentation for the functions in `module`.
+    function_doc_comments: FastIndexMap<Handle<Function>, Vec<String>>,
+}
+
+impl Specializer<'_> {
+    fn scalar(&self, resolution: &crate::proc::TypeResolution) -> Option<Scalar> {
+        resolution.inner_with(&self.module.types).scalar()
+    }
+
+    fn copy_doc_comments(&mut self, old: Handle<Function>, new: Handle<Function>) {
+        if let Some(comments) = self
+            .original
+            .doc_comments
+            .as_ref()
+            .and_then(|doc_comments| doc_comments.functions.get(&old))
+        {
+            self.function_doc_comments.insert(new, comments.clone());
+        }
+    }
+
+    /// Point all calls in `function` at functions in the new module.
+    ///
+    /// The handles in `function` still refer to the original module's
+    /// functions. `scalars` gives the scalar type of each of `function`'s
+    /// expressions, if it has one.
+    fn rewrite_calls(&mut self, function: &mut Function, scalars: &[Option<Scalar>]) {
+        self.rewrite_block(&mut function.body, &mut function.expressions, scalars);
+    }
+
+    fn rewrite_block(
+        &mut self,
+        block: &mut Block,
+        expressions: &mut Arena<Expression>,
+        scalars: &[Option<Scalar>],
+    ) {
+        for statement in block.iter_mut() {
+            match *statement {
+                Statement::Block(ref mut block) => {
+                    self.rewrite_block(block, expressions, scalars);
+                }
+                Statement::If {
+                    condition: _,
+                    ref mut accept,
+                    ref mut reject,
+                } => {
+                    self.rewrite_block(accept, expressions, scalars);
+                    self.rewrite_block(reject, expressions, scalars);
+                }
+                Statement::Switch {
+                    selector: _,
+                    ref mut cases,
+                } => {
+                    for case in cases {
+                        self.rewrite_block(&mut case.body, expressions, scalars);
+                    }
+                }
+                Statement::Loop {
+                    ref mut body,
+                    ref mut continuing,
+                    break_if: _,
+                } => {
+                    self.rewrite_block(body, expressions, scalars);
+                    self.rewrite_block(continuing, expressions, scalars);
+                }
+                Statement::Call {
+                    ref mut function,
+                    ref mut arguments,
+                    result,
+                } => {
+                    *function = self.rewrite_call(*function, arguments, expressions, scalars);
+                    if let Some(result) = result {
+                        expressions[result] = Expression::CallResult(*function);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Return the function in the new module that a call to `callee` with
+    /// `arguments` should use, and adjust `arguments` to suit.
+    fn rewrite_call(
+        &mut self,
+        callee: Handle<Function>,
+        arguments: &mut Vec<Handle<Expression>>,
+        expressions: &Arena<Expression>,
+        scalars: &[Option<Scalar>],
+    ) -> Handle<Function> {
+        if let Some(new_callee) = self.function_map[callee] {
+            return new_callee;
+        }
+
+        let original = self.original;
+        let mut roots = Vec::new();
+        let mut kept = Vec::with_capacity(arguments.len());
+        let mut indices = Vec::new();
+        for (argument, &expr) in original.functions[callee].arguments.iter().zip(&*arguments) {
+            if is_specialized_argument(original, argument) {
+                roots.push(trace(expr, expressions, scalars, &mut indices));
+            } else {
+                kept.push(expr);
+            }
+        }
+
+        // Dynamic indices are passed after all the other arguments.
+        kept.extend(indices);
+        *arguments = kept;
+
+        self.specialize(Specialization {
+            function: callee,
+            roots,
+        })
+    }
+
+    /// Return the function in the new module for `key`, creating it if
+    /// necessary.
+    fn specialize(&mut self, key: Specialization) -> Handle<Function> {
+        if let Some(&handle) = self.specializations.get(&key) {
+            return handle;
+        }
+
+        let original = self.original;
+        let callee = &original.functions[key.function];
+        let fun_info = &self.info[key.function];
+
+        let mut arguments = Vec::with_capacity(callee.arguments.len());
+        let mut expressions = Arena::new();
+        let mut scalars = Vec::new();
+
+        // For each original argument, the index of the argument that replaces
+        // it, or `None` if it's a pointer we're specializing away.
+        let mut argument_map = Vec::with_capacity(callee.arguments.len());
+        for argument in callee.arguments.iter() {
+            if is_specialized_argument(original, argument) {
+                argument_map.push(None);
+            } else {
+                argument_map.push(Some(arguments.len() as u32));
+                arguments.push(argument.clone());
+            }
+        }
+
+        // Start the new arena with the globals the pointers are built from,
+        // and arguments for their dynamic indices.
+        let mut bases = Vec::with_capacity(key.roots.len());
+        let pointer_arguments = callee
+            .arguments
+            .iter()
+            .filter(|argument| is_specialized_argument(original, argument));
+        for (root, argument) in key.roots.iter().zip(pointer_arguments) {
+            let global =
+                expressions.append(Expression::GlobalVariable(root.global), Span::UNDEFINED);
+            scalars.push(None);
+            let mut indices = Vec::new();
+            for step in root.steps.iter() {
+                if let Step::Index(scalar) = *step {
+                    let ty = self.module.types.insert(
+                        Type {
+                            name: None,
+                            inner: TypeInner::Scalar(scalar),
+                        },
+                        Span::UNDEFINED,
+                    );
+                    let index = Expression::FunctionArgument(arguments.len() as u32);
+                    indices.push(expressions.append(index, Span::UNDEFINED));
+                    scalars.push(Some(scalar));
+                    arguments.push(FunctionArgument {
+                        name: argument.name.as_ref().map(|name| format!("{name}_index")),
+                        ty,
+                        binding: None,
+                    });
+                }
+            }
+            bases.push((global, indices));
+        }
+
+        // Then rebuild the pointers themselves. These all need to be emitted,
+        // so keep them together.
+        let mut emitted = None;
+        let mut pointers = Vec::with_capacity(key.roots.len());
+        for (root, (global, indices)) in key.roots.iter().zip(bases) {
+            let mut pointer = global;
+            let mut indices = indices.into_iter();
+            for step in root.steps.iter() {
+                let expr = match *step {
+                    Step::Constant(index) => Expression::AccessIndex {
+                        base: pointer,
+                        index,
+                    },
+                    Step::Index(_) => Expression::Access {
+                        base: pointer,
+                        index: indices.next().unwrap(),
+                    },
+                };
+                pointer = expressions.append(expr, Span::UNDEFINED);
+                scalars.push(None);
+                let first = emitted.map_or(pointer, |(first, _)| first);
+                emitted = Some((first, pointer));
+            }
+            pointers.push(pointer);
+        }
+
+        // Copy the callee's expressions, replacing uses of the pointer
+        // arguments with the pointers we just built.
+        let mut pointers = pointers.into_iter();
+        let pointer_map = argument_map
+            .iter()
+            .map(|new_index| match *new_index {
+                Some(_) => None,
+                None => pointers.next(),
+            })
+            .collect::<Vec<_>>();
+        let mut new_pos = HandleVec::with_capacity(callee.expressions.len());
+        for (old, expr) in callee.expressions.iter() {
+            let new = match *expr {
+                Expression::FunctionArgument(index) => match argument_map[index as usize] {
+                    Some(new_index) => expressions.append(
+                        Expression::FunctionArgument(new_index),
+                        callee.expressions.get_span(old),
+                    ),
+                    None => {
+                        new_pos.insert(old, pointer_map[index as usize].unwrap());
+                        continue;
+                    }
+                },
+                _ => {
+                    let mut expr = expr.clone();
+                    adjust_expr(&new_pos, &mut expr);
+                    expressions.append(expr, callee.expressions.get_span(old))
+                }
+            };
+            scalars.push(self.scalar(&fun_info[old].ty));
+            new_pos.insert(old, new);
+        }
+
+        let mut body = Block::new();
+        if let Some((first, last)) = emitted {
+            body.push(
+                Statement::Emit(Range::new_from_bounds(first, last)),
+                Span::UNDEFINED,
+            );
+        }
+        let mut callee_body = callee.body.clone();
+        adjust_block(&new_pos, &mut callee_body);
+        body.append(&mut callee_body);
+
+        let mut local_variables = callee.local_variables.clone();
+        for (_, local) in local_variables.iter_mut() {
+            if let Some(ref mut init) = local.init {
+                *init = new_pos[*init];
+            }
+        }
+
+        let named_expressions = callee
+            .named_expressions
+            .iter()
+            .map(|(&expr, name)| (new_pos[expr], name.clone()))
+            .collect();
+
+        let globals = key
+            .roots
+            .iter()
+            .map(|root| {
+                original.global_variables[root.global]
+                    .name
+                    .as_deref()
+                    .unwrap_or("global")
+            })
+            .collect::<Vec<_>>()
+            .join("_");
+        let name = format!("{}_{globals}", callee.name.as_deref().unwrap_or("function"));
+
+        let mut function = Function {
+            name: Some(name),
+            arguments,
+            result: callee.result.clone(),
+            local_variables,
+            expressions,
+            named_expressions,
+            body,
+            diagnostic_filter_leaf: callee.diagnostic_filter_leaf,
+        };
+
+        // Specialize the calls this function makes before adding it, so that
+        // its callees come before it in the arena.
+        self.rewrite_calls(&mut function, &scalars);
+
+        let span = original.functions.get_span(key.function);
+        let handle = self.module.functions.append(function, span);
+        self.copy_doc_comments(key.function, handle);
+        self.specializations.insert(key, handle);
+        handle
+    }
+}
+
+/// Find the global variable and indexing steps that `pointer` was built from.
+///
+/// Append the expressions for any dynamic indices to `indices`.
+fn trace(
+    mut pointer: Handle<Expression>,
+    expressions: &Arena<Expression>,
+    scalars: &[Option<Scalar>],
+    indices: &mut Vec<Handle<Expression>>,
+) -> Root {
+    let first_index = indices.len();
+    let mut steps = Vec::new();
+    let global = loop {
+        match expressions[pointer] {
+            Expression::GlobalVariable(global) => break global,
+            Expression::Access { base, index } => {
+                steps.push(Step::Index(scalars[index.index()].unwrap()));
+                indices.push(index);
+                pointer = base;
+            }
+            Expression::AccessIndex { base, index } => {
+                steps.push(Step::Constant(index));
+                pointer = base;
+            }
+            ref other => {
+                // Validation ensures that pointers in these address spaces
+                // are always built from global variables.
+                unreachable!("pointer argument built from {other:?}")
+            }
+        }
+    };
+    steps.reverse();
+    indices[first_index..].reverse();
+    Root { global, steps }
+}
+
+/// Writers that specialize modules themselves must accept ones that haven't
+/// been through [`specialize_pointer_arguments`], as when a shader has no
+/// overrides to process first.
+#[cfg(all(
+    feature = "wgsl-in",
+    feature = "spv-out",
+    feature = "hlsl-out",
+    feature = "msl-out",
+    feature = "glsl-out"
+))]
+#[test]
+fn test_unspecialized_module() {
+    use crate::back::{glsl, hlsl, msl, spv};
+
+    let source = "
+        requires unrestricted_pointer_parameters;
+
+        @group(0) @binding(0)
+        var<storage, read_write> values: array<u32>;
+
+        fn bump(value: ptr<storage, u32, read_write>) {
+            *value += 1u;
+        }
+
+        @compute @workgroup_size(1)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            bump(&values[id.x]);
+        }
+    ";
+    let module = crate::front::wgsl::parse_str(source).unwrap();
+    let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .unwrap();
+    assert!(needs_specialization(&module));
+
+    let words = spv::write_vec(&module, &info, &spv::Options::default(), None).unwrap();
+    assert!(!words.is_empty());
+
+    let mut hlsl = String::new();
+    hlsl::Writer::new(&mut hlsl, &hlsl::Options::default())
+        .write(&module, &info)
+        .unwrap();
+    assert!(!hlsl.contains("bump("));
+
+    let (msl, _) = msl::write_string(
+        &module,
+        &info,
+        &msl::Options::default(),
+        &msl::PipelineOptions::default(),
+    )
+    .unwrap();
+    assert!(!msl.contains("bump("));
+
+    let options = glsl::Options::default();
+    let pipeline_options = glsl::PipelineOptions {
+        shader_stage: crate::ShaderStage::Compute,
+        entry_point: "main".to_string(),
+        multiview: None,
+    };
+    let mut glsl = String::new();
+    glsl::Writer::new(
+        &mut glsl,
+        &module,
+        &info,
+        &options,
+        &pipeline_options,
+        crate::proc::BoundsCheckPolicies::default(),
+    )
+    .unwrap()
+    .write()
+    .unwrap();
+    assert!(!glsl.contains("bump("));
+}
@@ -217,6 +217,64 @@ impl<'w> BlockContext<'w> {
         }
     }
 
+    /// Return true if `expr_handle` is a pointer into the physical storage
+    /// address space.
+    ///
+    /// Such pointers are values like any other, but `Access` and `AccessIndex`
+    /// expressions based on them are still gathered into access chains by
+    /// `write_expression_pointer`.
+    fn is_physical_pointer(&self, expr_handle: Handle<crate::Expression>) -> bool {
+        self.fun_info[expr_handle]
+            .ty
+            .inner_with(&self.ir_module.types)
+            .pointer_space()
+            == Some(crate::AddressSpace::PhysicalStorage)
+    }
+
+    /// Return true if `expr_handle` is a physical storage pointer value that
+    /// starts an access chain, rather than a step within one.
+    fn is_physical_pointer_root(&self, expr_handle: Handle<crate::Expression>) -> bool {
+        if !self.is_physical_pointer(expr_handle) {
+            return false;
+        }
+        match self.ir_function.expressions[expr_handle] {
+            crate::Expression::Access { base, .. }
+            | crate::Expression::AccessIndex { base, .. } => self.fun_info[base]
+                .ty
+                .inner_with(&self.ir_module.types)
+                .pointer_space()
+                .is_none(),
+            _ => true,
+        }
+    }
+
+    /// Return the alignment that loads and stores through `pointer` must
+    /// state, if it's a pointer into the physical storage address space.
+    fn physical_alignment(&self, pointer: Handle<crate::Expression>) -> Option<Word> {
+        use crate::proc::Alignment;
+
+        let layouter = self.writer.physical_layouter.as_ref()?;
+        let alignment = match *self.fun_info[pointer].ty.inner_with(&self.ir_module.types) {
+            crate::TypeInner::Pointer {
+                base,
+                space: crate::AddressSpace::PhysicalStorage,
+            } => layouter[base].alignment,
+            crate::TypeInner::ValuePointer {
+                size,
+                scalar,
+                space: crate::AddressSpace::PhysicalStorage,
+            } => {
+                let alignment = Alignment::from_width(scalar.width);
+                match size {
+                    Some(size) => Alignment::from(size) * alignment,
+                    None => alignment,
+                }
+            }
+            _ => return None,
+        };
+        Some(alignment * 1)
+    }
+
     /// Cache an expression for a value.
     pub(super) fn cache_expression_value(
         &mut self,
@@ -287,7 +345,9 @@ impl<'w> BlockContext<'w> {
                     id
                 }
             }
-            crate::Expression::Access { base, index: _ } if self.is_intermediate(base) => {
+            crate::Expression::Access { base, index: _ }
+                if self.is_intermediate(base) || self.is_physical_pointer(base) =>
+            {
                 // See `is_intermediate`; we'll handle this later in
                 // `write_expression_pointer`.
                 0
@@ -358,7 +418,9 @@ impl<'w> BlockContext<'w> {
                     }
                 }
             }
-            crate::Expression::AccessIndex { base, index: _ } if self.is_intermediate(base) => {
+            crate::Expression::AccessIndex { base, index: _ }
+                if self.is_intermediate(base) || self.is_physical_pointer(base) =>
+            {
                 // See `is_intermediate`; we'll handle this later in
                 // `write_expression_pointer`.
                 0
@@ -1393,6 +1455,8 @@ impl<'w> BlockContext<'w> {
                                 scope_constant_id,
                                 semantics_id,
                             )
+                        } else if let Some(alignment) = self.physical_alignment(pointer) {
+                            Instruction::load_aligned(result_type_id, id, pointer_id, alignment)
                         } else {
                             Instruction::load(result_type_id, id, pointer_id, None)
                         };
@@ -1401,6 +1465,7 @@ impl<'w> BlockContext<'w> {
                     }
                     ExpressionPointer::Conditional { condition, access } => {
                         //TODO: support atomics?
+                        let alignment = self.physical_alignment(pointer);
                         self.write_conditional_indexed_load(
                             result_type_id,
                             condition,
@@ -1410,12 +1475,20 @@ impl<'w> BlockContext<'w> {
                                 let pointer_id = access.result_id.unwrap();
                                 let value_id = id_gen.next();
                                 block.body.push(access);
-                                block.body.push(Instruction::load(
-                                    result_type_id,
-                                    value_id,
-                                    pointer_id,
-                                    None,
-                                ));
+                                block.body.push(match alignment {
+                                    Some(alignment) => Instruction::load_aligned(
+                                        result_type_id,
+                                        value_id,
+                                        pointer_id,
+                                        alignment,
+                                    ),
+                                    None => Instruction::load(
+                                        result_type_id,
+                                        value_id,
+                                        pointer_id,
+                                        None,
+                                    ),
+                                });
                                 value_id
                             },
                         )
@@ -1774,6 +1847,9 @@ impl<'w> BlockContext<'w> {
 
         self.temp_list.clear();
         let root_id = loop {
+            if self.is_physical_pointer_root(expr_handle) {
+                break self.cached[expr_handle];
+            }
             expr_handle = match self.ir_function.expressions[expr_handle] {
                 crate::Expression::Access { base, index } => {
                     if let crate::Expression::GlobalVariable(var_handle) =
@@ -2361,22 +2437,27 @@ impl<'w> BlockContext<'w> {
                                     semantics_id,
                                     value_id,
                                 )
+                            } else if let Some(alignment) = self.physical_alignment(pointer) {
+                                Instruction::store_aligned(pointer_id, value_id, alignment)
                             } else {
                                 Instruction::store(pointer_id, value_id, None)
                             };
                             block.body.push(instruction);
                         }
                         ExpressionPointer::Conditional { condition, access } => {
+                            let alignment = self.physical_alignment(pointer);
                             let mut selection = Selection::start(&mut block, ());
                             selection.if_true(self, condition, ());
 
                             // The in-bounds path. Perform the access and the store.
                             let pointer_id = access.result_id.unwrap();
                             selection.block().body.push(access);
-                            selection
-                                .block()
-                                .body
-                                .push(Instruction::store(pointer_id, value_id, None));
+                            selection.block().body.push(match alignment {
+                                Some(alignment) => {
+                                    Instruction::store_aligned(pointer_id, value_id, alignment)
+                                }
+                                None => Instruction::store(pointer_id, value_id, None),
+                            });
 
                             // Finish the in-bounds block and start the merge block. This
                             // is the block we'll leave current on return.
@@ -2666,6 +2747,32 @@ impl<'w> BlockContext<'w> {
                 } => {
                     self.write_subgroup_gather(mode, argument, result, &mut block)?;
                 }
+                Statement::DebugPrintf {
+                    ref format,
+                    ref arguments,
+                } => {
+                    let set_id = self.writer.get_debug_printf_ext_inst_id();
+                    // The format string must be declared with `OpString`,
+                    // even when we're not writing other debug information.
+                    let format_id = self.gen_id();
+                    Instruction::string(format, format_id)
+                        .to_words(&mut self.writer.logical_layout.debugs);
+
+                    self.temp_list.clear();
+                    for &argument in arguments {
+                        self.temp_list.push(self.cached[argument]);
+                    }
+                    let id = self.gen_id();
+                    block.body.push(Instruction::debug_printf(
+                        set_id,
+                        self.writer.void_type,
+                        id,
+                        format_id,
+                        &self.temp_list,
+                    ));
+                }
+                // Diagnostic filters only affect validation.
+                Statement::DiagnosticFilter(_) => {}
             }
         }
 
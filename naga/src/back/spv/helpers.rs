@@ -48,6 +48,7 @@ pub(super) const fn map_storage_class(space: crate::AddressSpace) -> spirv::Stor
         crate::AddressSpace::Uniform => spirv::StorageClass::Uniform,
         crate::AddressSpace::WorkGroup => spirv::StorageClass::Workgroup,
         crate::AddressSpace::PushConstant => spirv::StorageClass::PushConstant,
+        crate::AddressSpace::PhysicalStorage => spirv::StorageClass::PhysicalStorageBuffer,
     }
 }
 
@@ -68,6 +69,43 @@ pub(super) fn contains_builtin(
     }
 }
 
+/// Return `true` if a value of type `ty` holds any `f16` scalars.
+pub(super) fn contains_f16(ty: Handle<crate::Type>, arena: &UniqueArena<crate::Type>) -> bool {
+    match arena[ty].inner {
+        crate::TypeInner::Scalar(scalar)
+        | crate::TypeInner::Vector { scalar, .. }
+        | crate::TypeInner::Matrix { scalar, .. } => scalar == crate::Scalar::F16,
+        crate::TypeInner::Array { base, .. } | crate::TypeInner::BindingArray { base, .. } => {
+            contains_f16(base, arena)
+        }
+        crate::TypeInner::Struct { ref members, .. } => {
+            members.iter().any(|member| contains_f16(member.ty, arena))
+        }
+        _ => false,
+    }
+}
+
+/// Return `true` if a value of type `ty` holds any physical storage buffer
+/// pointers.
+///
+/// SPIR-V has no null value for such pointers, so variables holding them
+/// can't be zero-initialized.
+pub(super) fn contains_physical_pointer(
+    ty: Handle<crate::Type>,
+    arena: &UniqueArena<crate::Type>,
+) -> bool {
+    match arena[ty].inner {
+        crate::TypeInner::Pointer { space, .. } | crate::TypeInner::ValuePointer { space, .. } => {
+            space == crate::AddressSpace::PhysicalStorage
+        }
+        crate::TypeInner::Array { base, .. } => contains_physical_pointer(base, arena),
+        crate::TypeInner::Struct { ref members, .. } => members
+            .iter()
+            .any(|member| contains_physical_pointer(member.ty, arena)),
+        _ => false,
+    }
+}
+
 impl crate::AddressSpace {
     pub(super) const fn to_spirv_semantics_and_scope(
         self,
@@ -175,6 +175,28 @@ impl super::Instruction {
         instruction
     }
 
+    /// A `DebugPrintf` from the `NonSemantic.DebugPrintf` instruction set.
+    pub(super) fn debug_printf(
+        set_id: Word,
+        result_type_id: Word,
+        id: Word,
+        format_id: Word,
+        arguments: &[Word],
+    ) -> Self {
+        const DEBUG_PRINTF: Word = 1;
+
+        let mut instruction = Self::new(Op::ExtInst);
+        instruction.set_type(result_type_id);
+        instruction.set_result(id);
+        instruction.add_operand(set_id);
+        instruction.add_operand(DEBUG_PRINTF);
+        instruction.add_operand(format_id);
+        for argument in arguments {
+            instruction.add_operand(*argument)
+        }
+        instruction
+    }
+
     //
     //  Mode-Setting Instructions
     //
@@ -406,6 +428,10 @@ impl super::Instruction {
         instruction
     }
 
+    pub(super) fn constant_16bit(result_type_id: Word, id: Word, low: u16) -> Self {
+        Self::constant(result_type_id, id, &[low as Word])
+    }
+
     pub(super) fn constant_32bit(result_type_id: Word, id: Word, value: Word) -> Self {
         Self::constant(result_type_id, id, &[value])
     }
@@ -482,6 +508,25 @@ impl super::Instruction {
         instruction
     }
 
+    /// Like [`Instruction::load`], for pointers whose accesses must state
+    /// their alignment, like pointers into the physical storage buffer
+    /// storage class.
+    pub(super) fn load_aligned(
+        result_type_id: Word,
+        id: Word,
+        pointer_id: Word,
+        alignment: Word,
+    ) -> Self {
+        let mut instruction = Self::load(
+            result_type_id,
+            id,
+            pointer_id,
+            Some(spirv::MemoryAccess::ALIGNED),
+        );
+        instruction.add_operand(alignment);
+        instruction
+    }
+
     pub(super) fn atomic_load(
         result_type_id: Word,
         id: Word,
@@ -514,6 +559,14 @@ impl super::Instruction {
         instruction
     }
 
+    /// Like [`Instruction::store`], for pointers whose accesses must state
+    /// their alignment.
+    pub(super) fn store_aligned(pointer_id: Word, value_id: Word, alignment: Word) -> Self {
+        let mut instruction = Self::store(pointer_id, value_id, Some(spirv::MemoryAccess::ALIGNED));
+        instruction.add_operand(alignment);
+        instruction
+    }
+
     pub(super) fn atomic_store(
         pointer_id: Word,
         scope_id: Word,
@@ -73,6 +73,8 @@ pub enum Error {
     Validation(&'static str),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error(transparent)]
+    PointerArguments(#[from] crate::back::pointer_arguments::PointerArgumentError),
 }
 
 #[derive(Default)]
@@ -673,6 +675,19 @@ pub struct Writer {
 
     gl450_ext_inst_id: Word,
 
+    /// The id of the `NonSemantic.DebugPrintf` instruction set, once a
+    /// [`DebugPrintf`] statement has needed it.
+    ///
+    /// [`DebugPrintf`]: crate::Statement::DebugPrintf
+    debug_printf_ext_inst_id: Option<Word>,
+
+    /// The layout of the module's types, if it has [`PhysicalStorage`]
+    /// pointers, since loads and stores through them must state their
+    /// alignment.
+    ///
+    /// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
+    physical_layouter: Option<crate::proc::Layouter>,
+
     // Just a temporary list of SPIR-V ids
     temp_list: Vec<Word>,
 }
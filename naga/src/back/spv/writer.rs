@@ -1,6 +1,9 @@
 use super::{
     block::DebugInfoInner,
-    helpers::{contains_builtin, global_needs_wrapper, map_storage_class},
+    helpers::{
+        contains_builtin, contains_f16, contains_physical_pointer, global_needs_wrapper,
+        map_storage_class,
+    },
     make_local, Block, BlockContext, CachedConstant, CachedExpressions, DebugInfo,
     EntryPointContext, Error, Function, FunctionArgument, GlobalVariable, IdGenerator, Instruction,
     LocalType, LocalVariable, LogicalLayout, LookupFunctionType, LookupType, LoopContext, Options,
@@ -77,6 +80,8 @@ impl Writer {
             binding_map: options.binding_map.clone(),
             saved_cached: CachedExpressions::default(),
             gl450_ext_inst_id,
+            debug_printf_ext_inst_id: None,
+            physical_layouter: None,
             temp_list: Vec::new(),
         })
     }
@@ -112,6 +117,8 @@ impl Writer {
             id_gen,
             void_type,
             gl450_ext_inst_id,
+            debug_printf_ext_inst_id: None,
+            physical_layouter: None,
 
             // Recycled:
             capabilities_used: take(&mut self.capabilities_used).recycle(),
@@ -176,11 +183,56 @@ impl Writer {
         }
     }
 
+    /// Require the capability and extension needed to keep `f16` values in
+    /// variables of storage class `class`, if `ty` holds any.
+    ///
+    /// `f16` arithmetic only needs `Float16`, which [`make_scalar`] requests,
+    /// but loading and storing 16-bit values in buffers and shader interfaces
+    /// is governed separately by `SPV_KHR_16bit_storage`.
+    ///
+    /// [`make_scalar`]: Writer::make_scalar
+    fn request_f16_storage_capabilities(
+        &mut self,
+        ir_module: &crate::Module,
+        ty: Handle<crate::Type>,
+        class: spirv::StorageClass,
+    ) -> Result<(), Error> {
+        use spirv::{Capability as Ca, StorageClass as Sc};
+
+        if !contains_f16(ty, &ir_module.types) {
+            return Ok(());
+        }
+        let capability = match class {
+            Sc::StorageBuffer => Ca::StorageBuffer16BitAccess,
+            Sc::Uniform => Ca::UniformAndStorageBuffer16BitAccess,
+            Sc::PushConstant => Ca::StoragePushConstant16,
+            Sc::Input | Sc::Output => Ca::StorageInputOutput16,
+            _ => return Ok(()),
+        };
+        self.require_any("16-bit floating-point storage", &[capability])?;
+        self.use_extension("SPV_KHR_16bit_storage");
+        Ok(())
+    }
+
     /// Indicate that the code uses the given extension.
     pub(super) fn use_extension(&mut self, extension: &'static str) {
         self.extensions_used.insert(extension);
     }
 
+    /// Return the id of the `NonSemantic.DebugPrintf` instruction set,
+    /// importing it if this is its first use.
+    pub(super) fn get_debug_printf_ext_inst_id(&mut self) -> Word {
+        if let Some(id) = self.debug_printf_ext_inst_id {
+            return id;
+        }
+        let id = self.id_gen.next();
+        self.use_extension("SPV_KHR_non_semantic_info");
+        Instruction::ext_inst_import(id, "NonSemantic.DebugPrintf")
+            .to_words(&mut self.logical_layout.ext_inst_imports);
+        self.debug_printf_ext_inst_id = Some(id);
+        id
+    }
+
     pub(super) fn get_type_id(&mut self, lookup_ty: LookupType) -> Word {
         match self.lookup_type.entry(lookup_ty) {
             Entry::Occupied(e) => *e.get(),
@@ -217,8 +269,14 @@ impl Writer {
         class: spirv::StorageClass,
     ) -> Result<Word, Error> {
         let ty_id = self.get_type_id(LookupType::Handle(handle));
-        if let crate::TypeInner::Pointer { .. } = arena[handle].inner {
-            return Ok(ty_id);
+        // Pointers into physical storage are values, which variables can hold.
+        match arena[handle].inner {
+            crate::TypeInner::Pointer {
+                space: crate::AddressSpace::PhysicalStorage,
+                ..
+            } => {}
+            crate::TypeInner::Pointer { .. } => return Ok(ty_id),
+            _ => {}
         }
         let lookup_type = LookupType::Local(LocalType::Pointer {
             base: handle,
@@ -639,6 +697,14 @@ impl Writer {
                 }
             }
 
+            if ir_module.types[variable.ty].inner.pointer_space()
+                == Some(crate::AddressSpace::PhysicalStorage)
+            {
+                context
+                    .writer
+                    .decorate(id, spirv::Decoration::AliasedPointer, &[]);
+            }
+
             let init_word = variable.init.map(|constant| context.cached[constant]);
             let pointer_type_id = context.writer.get_pointer_id(
                 &ir_module.types,
@@ -651,6 +717,7 @@ impl Writer {
                 spirv::StorageClass::Function,
                 init_word.or_else(|| match ir_module.types[variable.ty].inner {
                     crate::TypeInner::RayQuery => None,
+                    _ if contains_physical_pointer(variable.ty, &ir_module.types) => None,
                     _ => {
                         let type_id = context.get_type_id(LookupType::Handle(variable.ty));
                         Some(context.writer.write_constant_null(type_id))
@@ -820,8 +887,14 @@ impl Writer {
                 Instruction::type_int(id, bits, signedness)
             }
             Sk::Float => {
-                if bits == 64 {
-                    self.capabilities_used.insert(spirv::Capability::Float64);
+                match bits {
+                    16 => {
+                        self.capabilities_used.insert(spirv::Capability::Float16);
+                    }
+                    64 => {
+                        self.capabilities_used.insert(spirv::Capability::Float64);
+                    }
+                    _ => {}
                 }
                 Instruction::type_float(id, bits)
             }
@@ -1010,6 +1083,7 @@ impl Writer {
                             let length_id = self.get_index_constant(length.get());
                             Instruction::type_array(id, type_id, length_id)
                         }
+                        crate::ArraySize::Pending(_) => unreachable!(),
                         crate::ArraySize::Dynamic => Instruction::type_runtime_array(id, type_id),
                     }
                 }
@@ -1020,6 +1094,7 @@ impl Writer {
                             let length_id = self.get_index_constant(length.get());
                             Instruction::type_array(id, type_id, length_id)
                         }
+                        crate::ArraySize::Pending(_) => unreachable!(),
                         crate::ArraySize::Dynamic => Instruction::type_runtime_array(id, type_id),
                     }
                 }
@@ -1186,6 +1261,7 @@ impl Writer {
                 Instruction::constant_64bit(type_id, id, bits as u32, (bits >> 32) as u32)
             }
             crate::Literal::F32(value) => Instruction::constant_32bit(type_id, id, value.to_bits()),
+            crate::Literal::F16(value) => Instruction::constant_16bit(type_id, id, value.to_bits()),
             crate::Literal::U32(value) => Instruction::constant_32bit(type_id, id, value),
             crate::Literal::I32(value) => Instruction::constant_32bit(type_id, id, value as u32),
             crate::Literal::U64(value) => {
@@ -1465,6 +1541,7 @@ impl Writer {
     ) -> Result<Word, Error> {
         let id = self.id_gen.next();
         let pointer_type_id = self.get_pointer_id(&ir_module.types, ty, class)?;
+        self.request_f16_storage_capabilities(ir_module, ty, class)?;
         Instruction::variable(pointer_type_id, id, class, None)
             .to_words(&mut self.logical_layout.declarations);
 
@@ -1675,6 +1752,7 @@ impl Writer {
         let class = map_storage_class(global_variable.space);
 
         //self.check(class.required_capabilities())?;
+        self.request_f16_storage_capabilities(ir_module, global_variable.ty, class)?;
 
         if self.flags.contains(WriterFlags::DEBUG) {
             if let Some(ref name) = global_variable.name {
@@ -1682,6 +1760,14 @@ impl Writer {
             }
         }
 
+        // Variables holding physical storage buffer pointers must say whether
+        // the pointers they hold may alias.
+        if ir_module.types[global_variable.ty].inner.pointer_space()
+            == Some(crate::AddressSpace::PhysicalStorage)
+        {
+            self.decorate(id, Decoration::AliasedPointer, &[]);
+        }
+
         let storage_access = match global_variable.space {
             crate::AddressSpace::Storage { access } => Some(access),
             _ => match ir_module.types[global_variable.ty].inner {
@@ -1796,6 +1882,11 @@ impl Writer {
         };
 
         let init_word = match (global_variable.space, self.zero_initialize_workgroup_memory) {
+            (crate::AddressSpace::Private, _)
+                if contains_physical_pointer(global_variable.ty, &ir_module.types) =>
+            {
+                init_word
+            }
             (crate::AddressSpace::Private, _)
             | (crate::AddressSpace::WorkGroup, super::ZeroInitializeWorkgroupMemoryMode::Native) => {
                 init_word.or_else(|| Some(self.get_constant_null(inner_type_id)))
@@ -1931,6 +2022,24 @@ impl Writer {
             }
         }
 
+        let has_physical_pointers = ir_module
+            .types
+            .iter()
+            .any(|(_, ty)| ty.inner.pointer_space() == Some(crate::AddressSpace::PhysicalStorage));
+        if has_physical_pointers {
+            self.require_any(
+                "physical storage buffer pointers",
+                &[spirv::Capability::PhysicalStorageBufferAddresses],
+            )?;
+            self.use_extension("SPV_KHR_physical_storage_buffer");
+
+            let mut layouter = crate::proc::Layouter::default();
+            layouter
+                .update(ir_module.to_ctx())
+                .map_err(|_| Error::Validation("type layout"))?;
+            self.physical_layouter = Some(layouter);
+        }
+
         if self.physical_layout.version < 0x10300 && has_storage_buffers {
             // enable the storage buffer class on < SPV-1.3
             Instruction::extension("SPV_KHR_storage_buffer_storage_class")
@@ -2062,7 +2171,11 @@ impl Writer {
                 .to_words(&mut self.logical_layout.capabilities);
         }
 
-        let addressing_model = spirv::AddressingModel::Logical;
+        let addressing_model = if has_physical_pointers {
+            spirv::AddressingModel::PhysicalStorageBuffer64
+        } else {
+            spirv::AddressingModel::Logical
+        };
         let memory_model = spirv::MemoryModel::GLSL450;
         //self.check(addressing_model.required_capabilities())?;
         //self.check(memory_model.required_capabilities())?;
@@ -2095,6 +2208,10 @@ impl Writer {
             return Err(Error::Override);
         }
 
+        let (ir_module, info) =
+            crate::back::pointer_arguments::specialize_pointer_arguments(ir_module, info)?;
+        let (ir_module, info) = (ir_module.as_ref(), info.as_ref());
+
         self.reset();
 
         // Try to find the entry point and corresponding index
@@ -1,6 +1,7 @@
 use super::Error;
 use crate::{
     back::{self, Baked},
+    diagnostic_filter::DiagnosticFilterNode,
     proc::{self, NameKey},
     valid, Handle, Module, ShaderStage, TypeInner,
 };
@@ -68,6 +69,12 @@ pub struct Writer<W> {
     namer: proc::Namer,
     named_expressions: crate::NamedExpressions,
     ep_results: Vec<(ShaderStage, Handle<crate::Type>)>,
+    /// The leaf of the diagnostic filters in effect at the statement being
+    /// written, so that only the ones a [`DiagnosticFilter`] statement adds
+    /// are written as attributes.
+    ///
+    /// [`DiagnosticFilter`]: crate::Statement::DiagnosticFilter
+    diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
 }
 
 impl<W: Write> Writer<W> {
@@ -79,6 +86,7 @@ impl<W: Write> Writer<W> {
             namer: proc::Namer::default(),
             named_expressions: crate::NamedExpressions::default(),
             ep_results: vec![],
+            diagnostic_filter_leaf: None,
         }
     }
 
@@ -112,8 +120,46 @@ impl<W: Write> Writer<W> {
             ));
         }
 
+        if module.types.iter().any(|(_, ty)| {
+            matches!(
+                ty.inner,
+                TypeInner::Pointer {
+                    space: crate::AddressSpace::PhysicalStorage,
+                    ..
+                }
+            )
+        }) {
+            return Err(Error::Unimplemented(
+                "Physical storage pointers are not supported by WGSL".to_string(),
+            ));
+        }
+
         self.reset(module);
 
+        // Write all enable directives
+        let enable_extensions = required_enable_extensions(module);
+        for extension in enable_extensions.iter() {
+            writeln!(self.out, "enable {extension};")?;
+        }
+        if !enable_extensions.is_empty() {
+            writeln!(self.out)?;
+        }
+
+        // Write all module-scope diagnostic directives
+        let diagnostic_filters =
+            diagnostic_filter_chain(module, module.diagnostic_filter_leaf, None);
+        for filter in diagnostic_filters.iter() {
+            writeln!(
+                self.out,
+                "diagnostic({}, {});",
+                filter.new_severity.to_ident(),
+                filter.triggering_rule
+            )?;
+        }
+        if !diagnostic_filters.is_empty() {
+            writeln!(self.out)?;
+        }
+
         // Save all ep result types
         for ep in &module.entry_points {
             if let Some(ref result) = ep.function.result {
@@ -169,6 +215,10 @@ impl<W: Write> Writer<W> {
             };
 
             // Write the function
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.functions.get(&handle)
+            })?;
+            self.write_diagnostic_attributes(module, function)?;
             self.write_function(module, function, &func_ctx)?;
 
             writeln!(self.out)?;
@@ -184,6 +234,10 @@ impl<W: Write> Writer<W> {
                 ],
             };
 
+            back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+                doc.entry_points.get(&index)
+            })?;
+            self.write_diagnostic_attributes(module, &ep.function)?;
             self.write_attributes(&attributes)?;
             // Add a newline after attribute
             writeln!(self.out)?;
@@ -310,10 +364,9 @@ impl<W: Write> Writer<W> {
         }
 
         // Write the function body (statement list)
-        for sta in func.body.iter() {
-            // The indentation should always be 1 when writing the function body
-            self.write_stmt(module, sta, func_ctx, back::Level(1))?;
-        }
+        // The indentation should always be 1 when writing the function body
+        self.diagnostic_filter_leaf = func.diagnostic_filter_leaf;
+        self.write_block(module, &func.body, func_ctx, back::Level(1))?;
 
         writeln!(self.out, "}}")?;
 
@@ -322,6 +375,91 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
+    /// Write a `@diagnostic(…)` attribute, on its own line, for each
+    /// diagnostic filter that applies to `func` but not to the whole module.
+    fn write_diagnostic_attributes(
+        &mut self,
+        module: &Module,
+        func: &crate::Function,
+    ) -> BackendResult {
+        for filter in diagnostic_filter_chain(
+            module,
+            func.diagnostic_filter_leaf,
+            module.diagnostic_filter_leaf,
+        ) {
+            writeln!(
+                self.out,
+                "@diagnostic({}, {})",
+                filter.new_severity.to_ident(),
+                filter.triggering_rule
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the `{` that opens `block`.
+    ///
+    /// If `block` starts with a [`DiagnosticFilter`] statement, it is written
+    /// as `@diagnostic(…)` attributes before the brace, and becomes the
+    /// [`diagnostic_filter_leaf`] in effect. The leaf that was in effect
+    /// before is returned, for the caller to restore after the closing brace.
+    ///
+    /// [`DiagnosticFilter`]: crate::Statement::DiagnosticFilter
+    /// [`diagnostic_filter_leaf`]: Writer::diagnostic_filter_leaf
+    fn open_block(
+        &mut self,
+        module: &Module,
+        block: &[crate::Statement],
+    ) -> Result<Option<Handle<DiagnosticFilterNode>>, Error> {
+        let outer_leaf = self.diagnostic_filter_leaf;
+        if let Some(&crate::Statement::DiagnosticFilter(leaf)) = block.first() {
+            for filter in diagnostic_filter_chain(module, Some(leaf), outer_leaf) {
+                write!(
+                    self.out,
+                    "@diagnostic({}, {}) ",
+                    filter.new_severity.to_ident(),
+                    filter.triggering_rule
+                )?;
+            }
+            self.diagnostic_filter_leaf = Some(leaf);
+        }
+        writeln!(self.out, "{{")?;
+        Ok(outer_leaf)
+    }
+
+    /// Write the statements of `block` at indentation `level`.
+    ///
+    /// A [`DiagnosticFilter`] statement that [`open_block`] didn't already
+    /// write applies to the rest of the block, so that is written as a
+    /// nested compound statement with `@diagnostic(…)` attributes.
+    ///
+    /// [`DiagnosticFilter`]: crate::Statement::DiagnosticFilter
+    /// [`open_block`]: Writer::open_block
+    fn write_block(
+        &mut self,
+        module: &Module,
+        block: &[crate::Statement],
+        func_ctx: &back::FunctionCtx<'_>,
+        level: back::Level,
+    ) -> BackendResult {
+        for (index, sta) in block.iter().enumerate() {
+            if let crate::Statement::DiagnosticFilter(leaf) = *sta {
+                if Some(leaf) != self.diagnostic_filter_leaf {
+                    let rest = &block[index..];
+                    write!(self.out, "{level}")?;
+                    let outer_leaf = self.open_block(module, rest)?;
+                    self.write_block(module, &rest[1..], func_ctx, level.next())?;
+                    writeln!(self.out, "{level}}}")?;
+                    self.diagnostic_filter_leaf = outer_leaf;
+                    break;
+                }
+                continue;
+            }
+            self.write_stmt(module, sta, func_ctx, level)?;
+        }
+        Ok(())
+    }
+
     /// Helper method to write a attribute
     fn write_attributes(&mut self, attributes: &[Attribute]) -> BackendResult {
         for attribute in attributes {
@@ -387,11 +525,17 @@ impl<W: Write> Writer<W> {
         handle: Handle<crate::Type>,
         members: &[crate::StructMember],
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.types.get(&handle)
+        })?;
         write!(self.out, "struct ")?;
         self.write_struct_name(module, handle)?;
         write!(self.out, " {{")?;
         writeln!(self.out)?;
         for (index, member) in members.iter().enumerate() {
+            back::write_doc_comments(&mut self.out, back::Level(1), module, |doc| {
+                doc.struct_members.get(&(handle, index))
+            })?;
             // The indentation is only for readability
             write!(self.out, "{}", back::INDENT)?;
             if let Some(ref binding) = member.binding {
@@ -507,6 +651,8 @@ impl<W: Write> Writer<W> {
                         self.write_type(module, base)?;
                         write!(self.out, ", {len}")?;
                     }
+                    // `Writer::write` rejects modules with overrides.
+                    crate::ArraySize::Pending(_) => unreachable!(),
                     crate::ArraySize::Dynamic => {
                         self.write_type(module, base)?;
                     }
@@ -521,6 +667,8 @@ impl<W: Write> Writer<W> {
                         self.write_type(module, base)?;
                         write!(self.out, ", {len}")?;
                     }
+                    // `Writer::write` rejects modules with overrides.
+                    crate::ArraySize::Pending(_) => unreachable!(),
                     crate::ArraySize::Dynamic => {
                         self.write_type(module, base)?;
                     }
@@ -665,23 +813,23 @@ impl<W: Write> Writer<W> {
                 write!(self.out, "{level}")?;
                 write!(self.out, "if ")?;
                 self.write_expr(module, condition, func_ctx)?;
-                writeln!(self.out, " {{")?;
+                write!(self.out, " ")?;
+                let outer_leaf = self.open_block(module, accept)?;
 
                 let l2 = level.next();
-                for sta in accept {
-                    // Increase indentation to help with readability
-                    self.write_stmt(module, sta, func_ctx, l2)?;
-                }
+                // Increase indentation to help with readability
+                self.write_block(module, accept, func_ctx, l2)?;
+                self.diagnostic_filter_leaf = outer_leaf;
 
                 // If there are no statements in the reject block we skip writing it
                 // This is only for readability
                 if !reject.is_empty() {
-                    writeln!(self.out, "{level}}} else {{")?;
+                    write!(self.out, "{level}}} else ")?;
+                    self.open_block(module, reject)?;
 
-                    for sta in reject {
-                        // Increase indentation to help with readability
-                        self.write_stmt(module, sta, func_ctx, l2)?;
-                    }
+                    // Increase indentation to help with readability
+                    self.write_block(module, reject, func_ctx, l2)?;
+                    self.diagnostic_filter_leaf = outer_leaf;
                 }
 
                 writeln!(self.out, "{level}}}")?
@@ -803,11 +951,10 @@ impl<W: Write> Writer<W> {
             // TODO: copy-paste from glsl-out
             Statement::Block(ref block) => {
                 write!(self.out, "{level}")?;
-                writeln!(self.out, "{{")?;
-                for sta in block.iter() {
-                    // Increase the indentation to help with readability
-                    self.write_stmt(module, sta, func_ctx, level.next())?
-                }
+                let outer_leaf = self.open_block(module, block)?;
+                // Increase the indentation to help with readability
+                self.write_block(module, block, func_ctx, level.next())?;
+                self.diagnostic_filter_leaf = outer_leaf;
                 writeln!(self.out, "{level}}}")?
             }
             Statement::Switch {
@@ -860,14 +1007,10 @@ impl<W: Write> Writer<W> {
                     if case.fall_through {
                         write!(self.out, ", ")?;
                     } else {
-                        writeln!(self.out, ": {{")?;
-                    }
-
-                    for sta in case.body.iter() {
-                        self.write_stmt(module, sta, func_ctx, l2.next())?;
-                    }
-
-                    if !case.fall_through {
+                        write!(self.out, ": ")?;
+                        let outer_leaf = self.open_block(module, &case.body)?;
+                        self.write_block(module, &case.body, func_ctx, l2.next())?;
+                        self.diagnostic_filter_leaf = outer_leaf;
                         writeln!(self.out, "{l2}}}")?;
                     }
                 }
@@ -880,22 +1023,20 @@ impl<W: Write> Writer<W> {
                 break_if,
             } => {
                 write!(self.out, "{level}")?;
-                writeln!(self.out, "loop {{")?;
+                write!(self.out, "loop ")?;
+                let outer_leaf = self.open_block(module, body)?;
 
                 let l2 = level.next();
-                for sta in body.iter() {
-                    self.write_stmt(module, sta, func_ctx, l2)?;
-                }
+                self.write_block(module, body, func_ctx, l2)?;
 
                 // The continuing is optional so we don't need to write it if
                 // it is empty, but the `break if` counts as a continuing statement
                 // so even if `continuing` is empty we must generate it if a
                 // `break if` exists
                 if !continuing.is_empty() || break_if.is_some() {
-                    writeln!(self.out, "{l2}continuing {{")?;
-                    for sta in continuing.iter() {
-                        self.write_stmt(module, sta, func_ctx, l2.next())?;
-                    }
+                    write!(self.out, "{l2}continuing ")?;
+                    let body_leaf = self.open_block(module, continuing)?;
+                    self.write_block(module, continuing, func_ctx, l2.next())?;
 
                     // The `break if` is always the last
                     // statement of the `continuing` block
@@ -908,9 +1049,11 @@ impl<W: Write> Writer<W> {
                     }
 
                     writeln!(self.out, "{l2}}}")?;
+                    self.diagnostic_filter_leaf = body_leaf;
                 }
 
-                writeln!(self.out, "{level}}}")?
+                writeln!(self.out, "{level}}}")?;
+                self.diagnostic_filter_leaf = outer_leaf;
             }
             Statement::Break => {
                 writeln!(self.out, "{level}break;")?;
@@ -1044,6 +1187,10 @@ impl<W: Write> Writer<W> {
                 }
                 writeln!(self.out, ");")?;
             }
+            // WGSL has no way to print from a shader.
+            Statement::DebugPrintf { .. } => {}
+            // `write_block` writes these as attributes.
+            Statement::DiagnosticFilter(_) => {}
         }
 
         Ok(())
@@ -1214,6 +1361,7 @@ impl<W: Write> Writer<W> {
         match expressions[expr] {
             Expression::Literal(literal) => match literal {
                 crate::Literal::F32(value) => write!(self.out, "{}f", value)?,
+                crate::Literal::F16(value) => write!(self.out, "{:?}h", value)?,
                 crate::Literal::U32(value) => write!(self.out, "{}u", value)?,
                 crate::Literal::I32(value) => {
                     // `-2147483648i` is not valid WGSL. The most negative `i32`
@@ -1837,6 +1985,10 @@ impl<W: Write> Writer<W> {
         global: &crate::GlobalVariable,
         handle: Handle<crate::GlobalVariable>,
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.global_variables.get(&handle)
+        })?;
+
         // Write group and binding attributes if present
         if let Some(ref binding) = global.binding {
             self.write_attributes(&[
@@ -1886,6 +2038,9 @@ impl<W: Write> Writer<W> {
         module: &Module,
         handle: Handle<crate::Constant>,
     ) -> BackendResult {
+        back::write_doc_comments(&mut self.out, back::Level(0), module, |doc| {
+            doc.constants.get(&handle)
+        })?;
         let name = &self.names[&NameKey::Constant(handle)];
         // First write only constant name
         write!(self.out, "const {name}: ")?;
@@ -1939,6 +2094,152 @@ fn builtin_str(built_in: crate::BuiltIn) -> Result<&'static str, Error> {
     })
 }
 
+/// Return the names of the enable-extensions `module` needs, in the order
+/// their `enable` directives should be written.
+fn required_enable_extensions(module: &Module) -> Vec<&'static str> {
+    let mut extensions = Vec::new();
+    if module_uses_f16(module) {
+        extensions.push("f16");
+    }
+
+    let mut uses_dual_source_blending = false;
+    let mut uses_subgroups = false;
+    let mut visit_binding = |binding: &crate::Binding| match *binding {
+        crate::Binding::Location {
+            second_blend_source: true,
+            ..
+        } => uses_dual_source_blending = true,
+        crate::Binding::BuiltIn(
+            crate::BuiltIn::NumSubgroups
+            | crate::BuiltIn::SubgroupId
+            | crate::BuiltIn::SubgroupSize
+            | crate::BuiltIn::SubgroupInvocationId,
+        ) => uses_subgroups = true,
+        _ => {}
+    };
+    for (_, ty) in module.types.iter() {
+        if let TypeInner::Struct { ref members, .. } = ty.inner {
+            for binding in members.iter().filter_map(|m| m.binding.as_ref()) {
+                visit_binding(binding);
+            }
+        }
+    }
+    for ep in module.entry_points.iter() {
+        let function = &ep.function;
+        for binding in function.arguments.iter().filter_map(|a| a.binding.as_ref()) {
+            visit_binding(binding);
+        }
+        if let Some(binding) = function.result.as_ref().and_then(|r| r.binding.as_ref()) {
+            visit_binding(binding);
+        }
+    }
+
+    let function_uses_subgroups = |function: &crate::Function| {
+        function.expressions.iter().any(|(_, e)| {
+            matches!(
+                *e,
+                crate::Expression::SubgroupBallotResult
+                    | crate::Expression::SubgroupOperationResult { .. }
+            )
+        }) || block_uses_subgroup_barrier(&function.body)
+    };
+    uses_subgroups = uses_subgroups
+        || module
+            .functions
+            .iter()
+            .any(|(_, f)| function_uses_subgroups(f))
+        || module
+            .entry_points
+            .iter()
+            .any(|ep| function_uses_subgroups(&ep.function));
+
+    if uses_dual_source_blending {
+        extensions.push("dual_source_blending");
+    }
+    if uses_subgroups {
+        extensions.push("subgroups");
+    }
+    extensions
+}
+
+/// Return `true` if `block` contains a `subgroupBarrier()`, which has no
+/// other trace in the expression arena.
+fn block_uses_subgroup_barrier(block: &crate::Block) -> bool {
+    use crate::Statement as S;
+
+    block.iter().any(|statement| match *statement {
+        S::Barrier(barrier) => barrier.contains(crate::Barrier::SUB_GROUP),
+        S::Block(ref block) => block_uses_subgroup_barrier(block),
+        S::If {
+            ref accept,
+            ref reject,
+            ..
+        } => block_uses_subgroup_barrier(accept) || block_uses_subgroup_barrier(reject),
+        S::Switch { ref cases, .. } => cases
+            .iter()
+            .any(|case| block_uses_subgroup_barrier(&case.body)),
+        S::Loop {
+            ref body,
+            ref continuing,
+            ..
+        } => block_uses_subgroup_barrier(body) || block_uses_subgroup_barrier(continuing),
+        _ => false,
+    })
+}
+
+/// Return the diagnostic filters on the chain of nodes from `leaf` up to, but
+/// not including, `root`, with the outermost filter first.
+///
+/// WGSL forbids one attribute list from giving the same rule two severities,
+/// so a filter is left out if one nearer `leaf` overrides it.
+fn diagnostic_filter_chain(
+    module: &Module,
+    leaf: Option<Handle<DiagnosticFilterNode>>,
+    root: Option<Handle<DiagnosticFilterNode>>,
+) -> Vec<&crate::diagnostic_filter::DiagnosticFilter> {
+    let mut filters: Vec<&crate::diagnostic_filter::DiagnosticFilter> = Vec::new();
+    let mut next = leaf;
+    while let Some(handle) = next {
+        if Some(handle) == root {
+            break;
+        }
+        let node = &module.diagnostic_filters[handle];
+        if !filters
+            .iter()
+            .any(|filter| filter.triggering_rule == node.inner.triggering_rule)
+        {
+            filters.push(&node.inner);
+        }
+        next = node.parent;
+    }
+    filters.reverse();
+    filters
+}
+
+/// Return `true` if `module` uses the `f16` type anywhere, and so needs an
+/// `enable f16;` directive.
+fn module_uses_f16(module: &Module) -> bool {
+    let is_f16_literal = |expr: &crate::Expression| {
+        matches!(*expr, crate::Expression::Literal(crate::Literal::F16(_)))
+    };
+    let function_uses_f16 =
+        |function: &crate::Function| function.expressions.iter().any(|(_, e)| is_f16_literal(e));
+
+    module
+        .types
+        .iter()
+        .any(|(_, ty)| ty.inner.scalar() == Some(crate::Scalar::F16))
+        || module
+            .global_expressions
+            .iter()
+            .any(|(_, e)| is_f16_literal(e))
+        || module.functions.iter().any(|(_, f)| function_uses_f16(f))
+        || module
+            .entry_points
+            .iter()
+            .any(|ep| function_uses_f16(&ep.function))
+}
+
 const fn image_dimension_str(dim: crate::ImageDimension) -> &'static str {
     use crate::ImageDimension as IDim;
 
@@ -1963,6 +2264,10 @@ const fn scalar_kind_str(scalar: crate::Scalar) -> &'static str {
             kind: Sk::Float,
             width: 4,
         } => "f32",
+        Scalar {
+            kind: Sk::Float,
+            width: 2,
+        } => "f16",
         Scalar {
             kind: Sk::Sint,
             width: 4,
@@ -2076,6 +2381,8 @@ const fn address_space_str(
             As::WorkGroup => "workgroup",
             As::Handle => return (None, None),
             As::Function => "function",
+            // Rejected by `Writer::write`.
+            As::PhysicalStorage => unreachable!(),
         }),
         None,
     )
@@ -63,6 +63,18 @@ pub fn compact(module: &mut crate::Module) {
         }
     }
 
+    // We treat all override-dependent array sizes as used by
+    // definition, since types are traced after constant expressions.
+    for (_, ty) in module.types.iter() {
+        if let crate::TypeInner::Array {
+            size: crate::ArraySize::Pending(crate::PendingArraySize::Expression(size_expr)),
+            ..
+        } = ty.inner
+        {
+            module_tracer.global_expressions_used.insert(size_expr);
+        }
+    }
+
     // We assume that all functions are used.
     //
     // Observe which types, constant expressions, constants, and
@@ -186,6 +198,13 @@ pub fn compact(module: &mut crate::Module) {
         }
     }
 
+    // Adjust the handles of documented items, dropping the comments of
+    // items we removed.
+    if let Some(ref mut doc_comments) = module.doc_comments {
+        log::trace!("adjusting doc comments");
+        module_map.adjust_doc_comments(doc_comments);
+    }
+
     // Temporary storage to help us reuse allocations of existing
     // named expression tables.
     let mut reused_named_expressions = crate::NamedExpressions::default();
@@ -312,6 +331,33 @@ impl ModuleMap {
             self.types.adjust(handle);
         }
     }
+
+    fn adjust_doc_comments(&self, doc_comments: &mut crate::DocComments) {
+        let crate::DocComments {
+            ref mut types,
+            ref mut struct_members,
+            entry_points: _,
+            functions: _,
+            ref mut constants,
+            overrides: _,
+            global_variables: _,
+        } = *doc_comments;
+
+        *types = std::mem::take(types)
+            .into_iter()
+            .filter_map(|(ty, comments)| Some((self.types.try_adjust(ty)?, comments)))
+            .collect();
+        *struct_members = std::mem::take(struct_members)
+            .into_iter()
+            .filter_map(|((ty, index), comments)| {
+                Some(((self.types.try_adjust(ty)?, index), comments))
+            })
+            .collect();
+        *constants = std::mem::take(constants)
+            .into_iter()
+            .filter_map(|(c, comments)| Some((self.constants.try_adjust(c)?, comments)))
+            .collect();
+    }
 }
 
 struct FunctionMap {
@@ -134,11 +134,20 @@ impl FunctionTracer<'_> {
                     }
 
                     // Trivial statements.
+                    St::DebugPrintf {
+                        format: _,
+                        ref arguments,
+                    } => {
+                        for expr in arguments {
+                            self.expressions_used.insert(*expr);
+                        }
+                    }
                     St::Break
                     | St::Continue
                     | St::Kill
                     | St::Barrier(_)
-                    | St::Return { value: None } => {}
+                    | St::Return { value: None }
+                    | St::DiagnosticFilter(_) => {}
                 }
             }
         }
@@ -323,11 +332,20 @@ impl FunctionMap {
                     }
 
                     // Trivial statements.
+                    St::DebugPrintf {
+                        format: _,
+                        ref mut arguments,
+                    } => {
+                        for expr in arguments {
+                            adjust(expr);
+                        }
+                    }
                     St::Break
                     | St::Continue
                     | St::Kill
                     | St::Barrier(_)
-                    | St::Return { value: None } => {}
+                    | St::Return { value: None }
+                    | St::DiagnosticFilter(_) => {}
                 }
             }
         }
@@ -82,9 +82,17 @@ impl ModuleMap {
             } => adjust(base),
             Ti::Array {
                 ref mut base,
-                size: _,
+                ref mut size,
                 stride: _,
-            } => adjust(base),
+            } => {
+                adjust(base);
+                if let crate::ArraySize::Pending(crate::PendingArraySize::Expression(
+                    ref mut size_expr,
+                )) = *size
+                {
+                    self.global_expressions.adjust(size_expr);
+                }
+            }
             Ti::Struct {
                 ref mut members,
                 span: _,
@@ -0,0 +1,303 @@
+//! [`DiagnosticFilter`]s and supporting functionality.
+//!
+//! Diagnostic filters let a shader change the severity with which Naga
+//! reports certain findings, like uniformity violations. In WGSL, these come
+//! from [`diagnostic(…)` directives] and [`@diagnostic(…)` attributes].
+//!
+//! [`diagnostic(…)` directives]: https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-directive
+//! [`@diagnostic(…)` attributes]: https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-attr
+
+use crate::{Arena, Handle};
+#[cfg(feature = "wgsl-in")]
+use crate::{FastIndexMap, Span};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "deserialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+
+/// A severity set on a [`DiagnosticFilter`].
+///
+/// <https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-severity>
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    const ERROR: &'static str = "error";
+    const WARNING: &'static str = "warning";
+    const INFO: &'static str = "info";
+    const OFF: &'static str = "off";
+
+    /// Convert from a sentinel word in WGSL into its associated [`Severity`], if possible.
+    pub fn from_ident(s: &str) -> Option<Self> {
+        Some(match s {
+            Self::ERROR => Self::Error,
+            Self::WARNING => Self::Warning,
+            Self::INFO => Self::Info,
+            Self::OFF => Self::Off,
+            _ => return None,
+        })
+    }
+
+    /// Maps this [`Severity`] into the sentinel word associated with it in WGSL.
+    pub const fn to_ident(self) -> &'static str {
+        match self {
+            Self::Error => Self::ERROR,
+            Self::Warning => Self::WARNING,
+            Self::Info => Self::INFO,
+            Self::Off => Self::OFF,
+        }
+    }
+
+    /// Report `err` according to this severity.
+    ///
+    /// An [`Error`](Self::Error) is returned as `Err`. Lesser severities are
+    /// logged at the corresponding [`log::Level`], and `Ok(())` is returned.
+    pub(crate) fn report_diag<E: std::fmt::Display>(self, err: E) -> Result<(), E> {
+        let level = match self {
+            Self::Off => return Ok(()),
+            Self::Info => log::Level::Info,
+            Self::Warning => log::Level::Warn,
+            Self::Error => return Err(err),
+        };
+        log::log!(level, "{err}");
+        Ok(())
+    }
+}
+
+/// A filterable triggering rule in a [`DiagnosticFilter`].
+///
+/// <https://gpuweb.github.io/gpuweb/wgsl/#filterable-triggering-rules>
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum FilterableTriggeringRule {
+    /// A rule defined by the WGSL standard, and understood by Naga.
+    Standard(StandardFilterableTriggeringRule),
+
+    /// A single-identifier rule that Naga doesn't know about.
+    ///
+    /// The WGSL standard asks implementations to warn about these, and
+    /// otherwise ignore them.
+    Unknown(String),
+
+    /// A rule of the form `<ident>.<ident>`, intended for use by other tools.
+    /// Naga ignores these.
+    User(String, String),
+}
+
+impl std::fmt::Display for FilterableTriggeringRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Standard(rule) => write!(f, "{}", rule.to_ident()),
+            Self::Unknown(ref name) => write!(f, "{name}"),
+            Self::User(ref tool, ref name) => write!(f, "{tool}.{name}"),
+        }
+    }
+}
+
+/// A filterable triggering rule defined by the WGSL standard.
+///
+/// <https://gpuweb.github.io/gpuweb/wgsl/#filterable-triggering-rules>
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum StandardFilterableTriggeringRule {
+    /// A derivative, or an implicit-level texture sample, in non-uniform
+    /// control flow.
+    DerivativeUniformity,
+}
+
+impl StandardFilterableTriggeringRule {
+    const DERIVATIVE_UNIFORMITY: &'static str = "derivative_uniformity";
+
+    /// Convert from a sentinel word in WGSL into its associated
+    /// [`StandardFilterableTriggeringRule`], if possible.
+    pub fn from_ident(s: &str) -> Option<Self> {
+        Some(match s {
+            Self::DERIVATIVE_UNIFORMITY => Self::DerivativeUniformity,
+            _ => return None,
+        })
+    }
+
+    /// Maps this [`StandardFilterableTriggeringRule`] into the sentinel word
+    /// associated with it in WGSL.
+    pub const fn to_ident(self) -> &'static str {
+        match self {
+            Self::DerivativeUniformity => Self::DERIVATIVE_UNIFORMITY,
+        }
+    }
+
+    /// The severity Naga reports this rule with when no [`DiagnosticFilter`]
+    /// applies.
+    ///
+    /// The WGSL standard makes `derivative_uniformity` an error by default.
+    /// However, Naga's uniformity analysis is more conservative than the one
+    /// in the standard, and rejects some valid programs, so until that is
+    /// fixed these findings are only reported if a shader asks for them.
+    pub const fn default_severity(self) -> Severity {
+        match self {
+            Self::DerivativeUniformity => Severity::Off,
+        }
+    }
+}
+
+/// A filter that modifies how diagnostics are emitted for shaders.
+///
+/// <https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-filter>
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct DiagnosticFilter {
+    pub new_severity: Severity,
+    pub triggering_rule: FilterableTriggeringRule,
+}
+
+/// A map of diagnostic filters to their severity and first occurrence's span.
+///
+/// Intended for front ends' first step into storing parsed [`DiagnosticFilter`]s.
+#[cfg(feature = "wgsl-in")]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DiagnosticFilterMap(FastIndexMap<FilterableTriggeringRule, (Severity, Span)>);
+
+#[cfg(feature = "wgsl-in")]
+impl DiagnosticFilterMap {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the given `diagnostic_filter` parsed at the given `span` to this map.
+    ///
+    /// The WGSL standard forbids two filters in the same scope from naming
+    /// the same triggering rule with different severities.
+    pub(crate) fn add(
+        &mut self,
+        diagnostic_filter: DiagnosticFilter,
+        span: Span,
+    ) -> Result<(), ConflictingDiagnosticRuleError> {
+        use indexmap::map::Entry;
+
+        let &mut Self(ref mut diagnostic_filters) = self;
+        let DiagnosticFilter {
+            new_severity,
+            triggering_rule,
+        } = diagnostic_filter;
+
+        match diagnostic_filters.entry(triggering_rule) {
+            Entry::Vacant(entry) => {
+                entry.insert((new_severity, span));
+            }
+            Entry::Occupied(entry) => {
+                let &(first_severity, first_span) = entry.get();
+                if first_severity != new_severity {
+                    return Err(ConflictingDiagnosticRuleError {
+                        triggering_rule: entry.key().clone(),
+                        triggering_rule_spans: [first_span, span],
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the filters in this map to `arena` as a chain of
+    /// [`DiagnosticFilterNode`]s hanging off of `parent`, returning the new
+    /// leaf.
+    ///
+    /// If this map is empty, `parent` is returned unchanged.
+    pub(crate) fn into_nodes(
+        self,
+        arena: &mut Arena<DiagnosticFilterNode>,
+        parent: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Option<Handle<DiagnosticFilterNode>> {
+        let mut leaf = parent;
+        for (triggering_rule, (new_severity, span)) in self.0 {
+            leaf = Some(arena.append(
+                DiagnosticFilterNode {
+                    inner: DiagnosticFilter {
+                        new_severity,
+                        triggering_rule,
+                    },
+                    parent: leaf,
+                },
+                span,
+            ));
+        }
+        leaf
+    }
+}
+
+/// An error returned by [`DiagnosticFilterMap::add`] when it encounters
+/// conflicting rules.
+#[cfg(feature = "wgsl-in")]
+#[derive(Clone, Debug)]
+pub(crate) struct ConflictingDiagnosticRuleError {
+    pub triggering_rule: FilterableTriggeringRule,
+    pub triggering_rule_spans: [Span; 2],
+}
+
+/// Represents a single parent-linking node in a tree of [`DiagnosticFilter`]s
+/// backed by an [`Arena`].
+///
+/// A single element of a _tree_ of diagnostic filter rules stored in
+/// [`crate::Module::diagnostic_filters`]. When nodes are built by a front-end,
+/// module-applicable rules are stored in [`crate::Module::diagnostic_filter_leaf`],
+/// and function-applicable rules in
+/// [`crate::Function::diagnostic_filter_leaf`]. Each node in the chain
+/// applies to all of the nodes below it, with later (child) nodes overriding
+/// earlier (parent) ones for the same triggering rule.
+///
+/// [`crate::Function::diagnostic_filter_leaf`]: crate::Function::diagnostic_filter_leaf
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct DiagnosticFilterNode {
+    pub inner: DiagnosticFilter,
+    pub parent: Option<Handle<DiagnosticFilterNode>>,
+}
+
+impl DiagnosticFilterNode {
+    /// Finds the most specific filter rule applicable to `triggering_rule` from
+    /// the chain of diagnostic filter rules in `arena`, starting with `node`,
+    /// and returns its severity. If none is found, return the value of
+    /// [`StandardFilterableTriggeringRule::default_severity`].
+    ///
+    /// When `triggering_rule` is not applicable to this node, its parent is
+    /// consulted recursively.
+    pub(crate) fn search(
+        node: Option<Handle<Self>>,
+        arena: &Arena<Self>,
+        triggering_rule: StandardFilterableTriggeringRule,
+    ) -> Severity {
+        let mut next = node;
+        while let Some(handle) = next {
+            let node = &arena[handle];
+            let &Self { ref inner, parent } = node;
+            let &DiagnosticFilter {
+                triggering_rule: ref rule,
+                new_severity,
+            } = inner;
+
+            if rule == &FilterableTriggeringRule::Standard(triggering_rule) {
+                return new_severity;
+            }
+
+            next = parent;
+        }
+        triggering_rule.default_severity()
+    }
+}
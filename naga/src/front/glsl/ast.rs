@@ -193,6 +193,10 @@ pub struct TypeQualifiers<'a> {
     pub span: Span,
     pub storage: (StorageQualifier, Span),
     pub invariant: Option<Span>,
+    /// The `precise` qualifier, it's reported as not implemented when parsed since
+    /// the IR has no way to forbid the contraction of arithmetic operations, but
+    /// it's kept so that redeclarations using it don't report a second error
+    pub precise: Option<Span>,
     pub interpolation: Option<(Interpolation, Span)>,
     pub precision: Option<(Precision, Span)>,
     pub sampling: Option<(Sampling, Span)>,
@@ -300,6 +304,37 @@ impl<'a> TypeQualifiers<'a> {
     }
 }
 
+/// The qualifiers of a struct or block member that have a meaning for the member
+/// itself, every other qualifier is reported as an error while parsing it
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemberQualifiers {
+    pub invariant: Option<Span>,
+    pub storage_access: Option<(StorageAccess, Span)>,
+}
+
+impl MemberQualifiers {
+    /// Appends `errors` with errors for all unused qualifiers
+    pub fn unused_errors(&self, errors: &mut Vec<super::Error>) {
+        if let Some(meta) = self.invariant {
+            errors.push(super::Error {
+                kind: super::ErrorKind::SemanticError(
+                    "Invariant qualifier can only be used in in/out variables".into(),
+                ),
+                meta,
+            });
+        }
+
+        if let Some((_, meta)) = self.storage_access {
+            errors.push(super::Error {
+                kind: super::ErrorKind::SemanticError(
+                    "Memory qualifiers can only be used in storage variables".into(),
+                ),
+                meta,
+            });
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FunctionCallKind {
     TypeConstructor(Handle<Type>),
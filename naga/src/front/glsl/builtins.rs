@@ -4,12 +4,14 @@ use super::{
         ParameterQualifier,
     },
     context::Context,
+    geometry::GeometryFunction,
     Error, ErrorKind, Frontend, Result,
 };
 use crate::{
-    BinaryOperator, DerivativeAxis as Axis, DerivativeControl as Ctrl, Expression, Handle,
-    ImageClass, ImageDimension as Dim, ImageQuery, MathFunction, Module, RelationalFunction,
-    SampleLevel, Scalar, ScalarKind as Sk, Span, Type, TypeInner, UnaryOperator, VectorSize,
+    BinaryOperator, CollectiveOperation, DerivativeAxis as Axis, DerivativeControl as Ctrl,
+    Expression, Handle, ImageClass, ImageDimension as Dim, ImageQuery, MathFunction, Module,
+    RelationalFunction, SampleLevel, Scalar, ScalarKind as Sk, Span, SubgroupOperation, Type,
+    TypeInner, UnaryOperator, VectorSize,
 };
 
 impl crate::ScalarKind {
@@ -995,6 +997,139 @@ fn inject_standard_builtins(
         "barrier" => declaration
             .overloads
             .push(module.add_builtin(Vec::new(), MacroCall::Barrier)),
+        "subgroupBarrier" => declaration
+            .overloads
+            .push(module.add_builtin(Vec::new(), MacroCall::SubgroupBarrier)),
+        "EmitVertex" => declaration.overloads.push(module.add_builtin(
+            Vec::new(),
+            MacroCall::Geometry(GeometryFunction::EmitVertex),
+        )),
+        "EndPrimitive" => declaration.overloads.push(module.add_builtin(
+            Vec::new(),
+            MacroCall::Geometry(GeometryFunction::EndPrimitive),
+        )),
+        "subgroupAll" | "subgroupAny" => {
+            let op = match name {
+                "subgroupAll" => SubgroupOperation::All,
+                _ => SubgroupOperation::Any,
+            };
+
+            declaration.overloads.push(module.add_builtin(
+                vec![TypeInner::Scalar(Scalar::BOOL)],
+                MacroCall::SubgroupOperation(op, CollectiveOperation::Reduce),
+            ))
+        }
+        "subgroupAdd"
+        | "subgroupMul"
+        | "subgroupMin"
+        | "subgroupMax"
+        | "subgroupAnd"
+        | "subgroupOr"
+        | "subgroupXor"
+        | "subgroupInclusiveAdd"
+        | "subgroupInclusiveMul"
+        | "subgroupExclusiveAdd"
+        | "subgroupExclusiveMul" => {
+            let (op, collective_op) = match name {
+                "subgroupAdd" => (SubgroupOperation::Add, CollectiveOperation::Reduce),
+                "subgroupMul" => (SubgroupOperation::Mul, CollectiveOperation::Reduce),
+                "subgroupMin" => (SubgroupOperation::Min, CollectiveOperation::Reduce),
+                "subgroupMax" => (SubgroupOperation::Max, CollectiveOperation::Reduce),
+                "subgroupAnd" => (SubgroupOperation::And, CollectiveOperation::Reduce),
+                "subgroupOr" => (SubgroupOperation::Or, CollectiveOperation::Reduce),
+                "subgroupXor" => (SubgroupOperation::Xor, CollectiveOperation::Reduce),
+                "subgroupInclusiveAdd" => {
+                    (SubgroupOperation::Add, CollectiveOperation::InclusiveScan)
+                }
+                "subgroupInclusiveMul" => {
+                    (SubgroupOperation::Mul, CollectiveOperation::InclusiveScan)
+                }
+                "subgroupExclusiveAdd" => {
+                    (SubgroupOperation::Add, CollectiveOperation::ExclusiveScan)
+                }
+                _ => (SubgroupOperation::Mul, CollectiveOperation::ExclusiveScan),
+            };
+            // The bitwise operations don't accept floats
+            let bitwise = matches!(
+                op,
+                SubgroupOperation::And | SubgroupOperation::Or | SubgroupOperation::Xor
+            );
+
+            // bits layout
+            // bit 0 through 1 - float/int/uint
+            // bit 2 through 3 - dims
+            for bits in 0..0b1111 {
+                let scalar = match bits & 0b11 {
+                    0b00 if !bitwise => Scalar::F32,
+                    0b01 => Scalar::I32,
+                    0b10 => Scalar::U32,
+                    _ => continue,
+                };
+                let size = match bits >> 2 {
+                    0b00 => None,
+                    0b01 => Some(VectorSize::Bi),
+                    0b10 => Some(VectorSize::Tri),
+                    _ => Some(VectorSize::Quad),
+                };
+                let ty = match size {
+                    Some(size) => TypeInner::Vector { size, scalar },
+                    None => TypeInner::Scalar(scalar),
+                };
+
+                declaration.overloads.push(
+                    module.add_builtin(vec![ty], MacroCall::SubgroupOperation(op, collective_op)),
+                )
+            }
+        }
+        "subgroupBallot" => declaration.overloads.push(module.add_builtin(
+            vec![TypeInner::Scalar(Scalar::BOOL)],
+            MacroCall::SubgroupBallot,
+        )),
+        "subgroupBroadcastFirst"
+        | "subgroupBroadcast"
+        | "subgroupShuffle"
+        | "subgroupShuffleXor"
+        | "subgroupShuffleUp"
+        | "subgroupShuffleDown" => {
+            let mode = match name {
+                "subgroupBroadcastFirst" => SubgroupGatherMode::BroadcastFirst,
+                "subgroupBroadcast" => SubgroupGatherMode::Broadcast,
+                "subgroupShuffle" => SubgroupGatherMode::Shuffle,
+                "subgroupShuffleXor" => SubgroupGatherMode::ShuffleXor,
+                "subgroupShuffleUp" => SubgroupGatherMode::ShuffleUp,
+                _ => SubgroupGatherMode::ShuffleDown,
+            };
+
+            // bits layout
+            // bit 0 through 1 - float/int/uint
+            // bit 2 through 3 - dims
+            for bits in 0..0b1111 {
+                let scalar = match bits & 0b11 {
+                    0b00 => Scalar::F32,
+                    0b01 => Scalar::I32,
+                    0b10 => Scalar::U32,
+                    _ => continue,
+                };
+                let size = match bits >> 2 {
+                    0b00 => None,
+                    0b01 => Some(VectorSize::Bi),
+                    0b10 => Some(VectorSize::Tri),
+                    _ => Some(VectorSize::Quad),
+                };
+                let ty = match size {
+                    Some(size) => TypeInner::Vector { size, scalar },
+                    None => TypeInner::Scalar(scalar),
+                };
+                let args = match mode {
+                    SubgroupGatherMode::BroadcastFirst => vec![ty],
+                    _ => vec![ty, TypeInner::Scalar(Scalar::U32)],
+                };
+
+                declaration
+                    .overloads
+                    .push(module.add_builtin(args, MacroCall::SubgroupGather(mode)))
+            }
+        }
         // Add common builtins with floats
         _ => inject_common_builtin(declaration, module, name, 4),
     }
@@ -1497,6 +1632,19 @@ pub enum TextureLevelType {
     Grad,
 }
 
+/// The thread a subgroup gather builtin reads from, see [`GatherMode`]
+///
+/// [`GatherMode`]: crate::GatherMode
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SubgroupGatherMode {
+    BroadcastFirst,
+    Broadcast,
+    Shuffle,
+    ShuffleDown,
+    ShuffleUp,
+    ShuffleXor,
+}
+
 /// A compiler defined builtin function
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum MacroCall {
@@ -1530,6 +1678,13 @@ pub enum MacroCall {
     BitCast(Sk),
     Derivate(Axis, Ctrl),
     Barrier,
+    SubgroupBarrier,
+    /// `EmitVertex` and `EndPrimitive`, which call functions generated along
+    /// with the entry point of a geometry shader
+    Geometry(GeometryFunction),
+    SubgroupOperation(SubgroupOperation, CollectiveOperation),
+    SubgroupBallot,
+    SubgroupGather(SubgroupGatherMode),
     /// SmoothStep needs a separate variant because it might need it's inputs
     /// to be splatted depending on the overload
     SmoothStep {
@@ -1983,6 +2138,81 @@ impl MacroCall {
                     .push(crate::Statement::Barrier(crate::Barrier::all()), meta);
                 return Ok(None);
             }
+            MacroCall::SubgroupBarrier => {
+                ctx.emit_restart();
+                ctx.body
+                    .push(crate::Statement::Barrier(crate::Barrier::SUB_GROUP), meta);
+                return Ok(None);
+            }
+            MacroCall::Geometry(function) => {
+                let function = frontend.geometry_function(ctx, function, meta)?;
+                ctx.emit_restart();
+                ctx.body.push(
+                    crate::Statement::Call {
+                        function,
+                        arguments: Vec::new(),
+                        result: None,
+                    },
+                    meta,
+                );
+                return Ok(None);
+            }
+            MacroCall::SubgroupOperation(op, collective_op) => {
+                let ty = ctx.resolve_type_handle(args[0], meta)?;
+
+                ctx.emit_end();
+                let result =
+                    ctx.add_expression(Expression::SubgroupOperationResult { ty }, meta)?;
+                ctx.body.push(
+                    crate::Statement::SubgroupCollectiveOperation {
+                        op,
+                        collective_op,
+                        argument: args[0],
+                        result,
+                    },
+                    meta,
+                );
+                ctx.emit_start();
+                result
+            }
+            MacroCall::SubgroupBallot => {
+                ctx.emit_end();
+                let result = ctx.add_expression(Expression::SubgroupBallotResult, meta)?;
+                ctx.body.push(
+                    crate::Statement::SubgroupBallot {
+                        result,
+                        predicate: Some(args[0]),
+                    },
+                    meta,
+                );
+                ctx.emit_start();
+                result
+            }
+            MacroCall::SubgroupGather(mode) => {
+                let ty = ctx.resolve_type_handle(args[0], meta)?;
+                let mode = match mode {
+                    SubgroupGatherMode::BroadcastFirst => crate::GatherMode::BroadcastFirst,
+                    SubgroupGatherMode::Broadcast => crate::GatherMode::Broadcast(args[1]),
+                    SubgroupGatherMode::Shuffle => crate::GatherMode::Shuffle(args[1]),
+                    SubgroupGatherMode::ShuffleDown => crate::GatherMode::ShuffleDown(args[1]),
+                    SubgroupGatherMode::ShuffleUp => crate::GatherMode::ShuffleUp(args[1]),
+                    SubgroupGatherMode::ShuffleXor => crate::GatherMode::ShuffleXor(args[1]),
+                };
+
+                ctx.emit_end();
+                let result =
+                    ctx.add_expression(Expression::SubgroupOperationResult { ty }, meta)?;
+                ctx.body.push(
+                    crate::Statement::SubgroupGather {
+                        mode,
+                        argument: args[0],
+                        result,
+                    },
+                    meta,
+                );
+                ctx.emit_start();
+                result
+            }
             MacroCall::SmoothStep { splatted } => {
                 ctx.implicit_splat(&mut args[0], meta, splatted)?;
                 ctx.implicit_splat(&mut args[1], meta, splatted)?;
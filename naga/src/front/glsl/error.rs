@@ -1,8 +1,8 @@
 use super::token::TokenValue;
 use crate::SourceLocation;
-use crate::{proc::ConstantEvaluatorError, Span};
+use crate::{proc::ConstantEvaluatorError, SourceMap, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFile;
+use codespan_reporting::files::{SimpleFile, SimpleFiles};
 use codespan_reporting::term;
 use pp_rs::token::PreprocessorError;
 use std::borrow::Cow;
@@ -114,6 +114,11 @@ pub enum ErrorKind {
     /// An error was returned by the preprocessor.
     #[error("{0:?}")]
     PreprocessorError(PreprocessorError),
+    /// An `#include` directive couldn't be resolved.
+    ///
+    /// This is only reported for directives the preprocessor doesn't skip.
+    #[error("Invalid #include: {0}")]
+    IncludeError(String),
     /// The parser entered an illegal state and exited
     ///
     /// This obviously is a bug and as such should be reported in the github issue tracker
@@ -173,6 +178,41 @@ impl ParseErrors {
         }
     }
 
+    /// Like [`emit_to_writer_with_path`], but for a shader with `#include`
+    /// directives, labelling each error in the file it occurred in.
+    ///
+    /// `path` names the main file.
+    ///
+    /// [`emit_to_writer_with_path`]: ParseErrors::emit_to_writer_with_path
+    pub fn emit_to_writer_with_source_map(
+        &self,
+        writer: &mut impl WriteColor,
+        source_map: &SourceMap,
+        path: &str,
+    ) {
+        let mut files = SimpleFiles::new();
+        for (index, file) in source_map.files().iter().enumerate() {
+            let name = match index {
+                0 => path,
+                _ => file.name(),
+            };
+            files.add(name, source_map.source(file));
+        }
+        let config = term::Config::default();
+
+        for err in &self.errors {
+            let mut diagnostic = Diagnostic::error().with_message(err.kind.to_string());
+
+            if let Some((file, span)) = source_map.file_for_span(err.meta) {
+                if let Some(range) = span.to_range() {
+                    diagnostic = diagnostic.with_labels(vec![Label::primary(file, range)]);
+                }
+            }
+
+            term::emit(writer, &config, &files, &diagnostic).expect("cannot write error");
+        }
+    }
+
     pub fn emit_to_string(&self, source: &str) -> String {
         let mut writer = NoColor::new(Vec::new());
         self.emit_to_writer(&mut writer, source);
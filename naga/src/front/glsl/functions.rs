@@ -1065,6 +1065,7 @@ impl Frontend {
             expressions,
             named_expressions: crate::NamedExpressions::default(),
             body,
+            diagnostic_filter_leaf: None,
         };
 
         'outer: for decl in declaration.overloads.iter_mut() {
@@ -1213,8 +1214,13 @@ impl Frontend {
     pub(crate) fn add_entry_point(
         &mut self,
         function: Handle<Function>,
+        name: &str,
         mut ctx: Context,
     ) -> Result<()> {
+        if let Some(geometry) = self.geometry.take() {
+            return self.add_geometry_entry_point(geometry, function, name, ctx);
+        }
+
         let mut arguments = Vec::new();
 
         let body = Block::with_capacity(
@@ -1357,7 +1363,7 @@ impl Frontend {
         } = ctx;
 
         ctx.module.entry_points.push(EntryPoint {
-            name: "main".to_string(),
+            name: name.to_string(),
             stage: self.meta.stage,
             early_depth_test: Some(crate::EarlyDepthTest { conservative: None })
                 .filter(|_| self.meta.early_fragment_tests),
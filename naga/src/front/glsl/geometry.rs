@@ -0,0 +1,1153 @@
+/*!
+Lowering of geometry shaders to compute shaders.
+
+The IR has no geometry stage, so when [`Options::geometry`] is set the shader's
+`main` is wrapped in a compute entry point which reads the vertices of an input
+primitive from a storage buffer, and `EmitVertex` and `EndPrimitive` become calls
+to generated functions that append the assembled primitives to another storage
+buffer. See [`GeometryOptions`] for the layout of both buffers.
+
+[`Options::geometry`]: super::Options::geometry
+*/
+
+use super::{
+    ast::{
+        GlobalLookup, GlobalLookupKind, StorageQualifier, StructLayout, TypeQualifiers,
+        VariableReference,
+    },
+    context::Context,
+    error::{Error, ErrorKind},
+    offset, Frontend, Result,
+};
+use crate::{
+    proc::Alignment, AddressSpace, Arena, ArraySize, AtomicFunction, BinaryOperator, Binding,
+    Block, BuiltIn, EntryPoint, Expression, Function, FunctionArgument, GlobalVariable, Handle,
+    Literal, ResourceBinding, Scalar, ShaderStage, Span, Statement, StorageAccess, StructMember,
+    Type, TypeInner, VectorSize,
+};
+use std::num::NonZeroU32;
+
+/// The primitive a geometry shader is run on, declared with a layout
+/// qualifier like `layout(triangles) in;`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputPrimitive {
+    Points,
+    Lines,
+    LinesAdjacency,
+    Triangles,
+    TrianglesAdjacency,
+}
+
+impl InputPrimitive {
+    /// Returns the number of vertices of the primitive.
+    pub const fn vertex_count(self) -> u32 {
+        match self {
+            Self::Points => 1,
+            Self::Lines => 2,
+            Self::LinesAdjacency => 4,
+            Self::Triangles => 3,
+            Self::TrianglesAdjacency => 6,
+        }
+    }
+}
+
+/// The primitive emitted by a geometry shader, declared with a layout
+/// qualifier like `layout(triangle_strip, max_vertices = 3) out;`.
+///
+/// Strips are written to the output buffer as lists, so they must be drawn
+/// with the point, line or triangle list topology.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputPrimitive {
+    Points,
+    LineStrip,
+    TriangleStrip,
+}
+
+impl OutputPrimitive {
+    /// Returns the number of vertices written to the output buffer for each
+    /// primitive.
+    pub const fn vertex_count(self) -> u32 {
+        match self {
+            Self::Points => 1,
+            Self::LineStrip => 2,
+            Self::TriangleStrip => 3,
+        }
+    }
+}
+
+/// Options for parsing a geometry shader, see [`Options::geometry`].
+///
+/// The shader is lowered to a compute entry point with one invocation for
+/// each input primitive and each instance of the shader, declared with
+/// `layout(invocations = N) in;`. The primitive index is taken from
+/// `global_invocation_id.x` and the instance from `global_invocation_id.y`,
+/// so it must be dispatched with `(ceil(primitives / workgroup_size),
+/// invocations, 1)` workgroups.
+///
+/// The input vertices are read from the `geometry_input` storage buffer, an
+/// array of `GeometryInputVertex` structs holding `gl_Position` followed by the
+/// shader's `in` variables in the order they are declared. Primitive `i` is
+/// made of the vertices `i * n` up to `(i + 1) * n`, where `n` is the
+/// [`vertex_count`] of the input primitive.
+///
+/// The emitted primitives are appended to the `geometry_output` storage
+/// buffer, which starts with the arguments of a non-indexed indirect draw:
+///
+/// ```glsl
+/// buffer GeometryOutput {
+///     uint vertex_count;
+///     uint instance_count;
+///     uint first_vertex;
+///     uint first_instance;
+///     GeometryOutputVertex vertices[];
+/// };
+/// ```
+///
+/// The `vertices` start at offset 16, each one holding `gl_Position` followed
+/// by the shader's `out` variables in the order they are declared. The first
+/// four words must be set to `0, 1, 0, 0` before the dispatch, after which the
+/// buffer can be used to draw the primitives. Primitives that don't fit in the
+/// buffer are dropped, and the primitives of different invocations are
+/// written in no particular order.
+///
+/// Both vertex structs are laid out with the std430 rules. Built-ins other
+/// than `gl_in[].gl_Position`, `gl_PrimitiveIDIn`, `gl_InvocationID` and
+/// `gl_Position` aren't supported.
+///
+/// [`Options::geometry`]: super::Options::geometry
+/// [`vertex_count`]: InputPrimitive::vertex_count
+#[derive(Clone, Debug)]
+pub struct GeometryOptions {
+    /// The binding of the buffer the input vertices are read from.
+    pub input_binding: ResourceBinding,
+    /// The binding of the buffer the emitted primitives are written to.
+    pub output_binding: ResourceBinding,
+    /// The number of input primitives handled by each workgroup.
+    pub workgroup_size: u32,
+}
+
+impl Default for GeometryOptions {
+    fn default() -> Self {
+        GeometryOptions {
+            input_binding: ResourceBinding {
+                group: 0,
+                binding: 0,
+            },
+            output_binding: ResourceBinding {
+                group: 0,
+                binding: 1,
+            },
+            workgroup_size: 64,
+        }
+    }
+}
+
+/// Information about a geometry shader, see [`ShaderMetadata::geometry`].
+///
+/// [`ShaderMetadata::geometry`]: super::ShaderMetadata::geometry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeometryMetadata {
+    pub input_primitive: InputPrimitive,
+    pub output_primitive: OutputPrimitive,
+    /// The maximum number of vertices emitted by each instance of the shader.
+    pub max_vertices: u32,
+    /// The number of instances of the shader run for each input primitive,
+    /// `1` unless declared with `layout(invocations = N) in;`.
+    pub invocations: u32,
+}
+
+impl GeometryMetadata {
+    /// Returns the maximum number of vertices written to the output buffer
+    /// for each input primitive.
+    pub const fn max_output_vertices(&self) -> u32 {
+        let per_primitive = self.output_primitive.vertex_count();
+        let primitives = self.max_vertices.saturating_sub(per_primitive - 1);
+        primitives * per_primitive * self.invocations
+    }
+}
+
+/// The geometry shader built-in functions lowered to calls to generated
+/// functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryFunction {
+    EmitVertex,
+    EndPrimitive,
+}
+
+/// The state of the geometry shader being parsed.
+#[derive(Debug)]
+pub struct GeometryState {
+    options: GeometryOptions,
+    input_primitive: Option<InputPrimitive>,
+    output_primitive: Option<OutputPrimitive>,
+    max_vertices: Option<u32>,
+    invocations: u32,
+    /// The globals holding `gl_in`, `gl_PrimitiveIDIn` and
+    /// `gl_InvocationID`, added the first time they're used.
+    per_vertex: Option<Handle<GlobalVariable>>,
+    primitive_id: Option<Handle<GlobalVariable>>,
+    invocation_id: Option<Handle<GlobalVariable>>,
+    /// The functions called by `EmitVertex` and `EndPrimitive`, their
+    /// bodies are only generated along with the entry point.
+    emit_vertex: Option<Handle<Function>>,
+    end_primitive: Option<Handle<Function>>,
+}
+
+impl GeometryState {
+    pub const fn new(options: GeometryOptions) -> Self {
+        GeometryState {
+            options,
+            input_primitive: None,
+            output_primitive: None,
+            max_vertices: None,
+            invocations: 1,
+            per_vertex: None,
+            primitive_id: None,
+            invocation_id: None,
+            emit_vertex: None,
+            end_primitive: None,
+        }
+    }
+
+    fn input_vertex_count(&self, meta: Span) -> Result<NonZeroU32> {
+        match self.input_primitive {
+            Some(primitive) => Ok(NonZeroU32::new(primitive.vertex_count()).unwrap()),
+            None => Err(Error {
+                kind: ErrorKind::SemanticError(
+                    "The input primitive must be declared before the inputs of a geometry shader"
+                        .into(),
+                ),
+                meta,
+            }),
+        }
+    }
+}
+
+impl Frontend {
+    /// Handles the layout qualifiers of `layout(...) in;` and
+    /// `layout(...) out;` declarations in geometry shaders.
+    pub(crate) fn add_geometry_layout(&mut self, qualifiers: &mut TypeQualifiers) {
+        let Some(ref mut geometry) = self.geometry else {
+            return;
+        };
+
+        match qualifiers.storage.0 {
+            StorageQualifier::Input => {
+                for (name, primitive) in [
+                    ("points", InputPrimitive::Points),
+                    ("lines", InputPrimitive::Lines),
+                    ("lines_adjacency", InputPrimitive::LinesAdjacency),
+                    ("triangles", InputPrimitive::Triangles),
+                    ("triangles_adjacency", InputPrimitive::TrianglesAdjacency),
+                ] {
+                    if qualifiers.none_layout_qualifier(name, &mut self.errors) {
+                        if geometry.input_primitive.is_some_and(|p| p != primitive) {
+                            self.errors.push(Error {
+                                kind: ErrorKind::SemanticError(
+                                    "The input primitive was already declared".into(),
+                                ),
+                                meta: qualifiers.span,
+                            });
+                        }
+                        geometry.input_primitive = Some(primitive);
+                    }
+                }
+
+                if let Some(invocations) =
+                    qualifiers.uint_layout_qualifier("invocations", &mut self.errors)
+                {
+                    if invocations == 0 {
+                        self.errors.push(Error {
+                            kind: ErrorKind::SemanticError(
+                                "invocations must be greater than zero".into(),
+                            ),
+                            meta: qualifiers.span,
+                        });
+                    } else {
+                        geometry.invocations = invocations;
+                    }
+                }
+            }
+            StorageQualifier::Output => {
+                for (name, primitive) in [
+                    ("points", OutputPrimitive::Points),
+                    ("line_strip", OutputPrimitive::LineStrip),
+                    ("triangle_strip", OutputPrimitive::TriangleStrip),
+                ] {
+                    if qualifiers.none_layout_qualifier(name, &mut self.errors) {
+                        geometry.output_primitive = Some(primitive);
+                    }
+                }
+
+                if let Some(max_vertices) =
+                    qualifiers.uint_layout_qualifier("max_vertices", &mut self.errors)
+                {
+                    geometry.max_vertices = Some(max_vertices);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sizes the array type of a geometry shader input to the number of
+    /// vertices of the input primitive.
+    ///
+    /// Returns `ty` unchanged if the shader isn't a geometry shader.
+    pub(crate) fn geometry_input_type(
+        &mut self,
+        ctx: &mut Context,
+        ty: Handle<Type>,
+        meta: Span,
+    ) -> Result<Handle<Type>> {
+        let Some(ref geometry) = self.geometry else {
+            return Ok(ty);
+        };
+        let vertices = geometry.input_vertex_count(meta)?;
+
+        match ctx.module.types[ty].inner {
+            TypeInner::Array {
+                base,
+                size: ArraySize::Dynamic,
+                stride,
+            } => Ok(ctx.module.types.insert(
+                Type {
+                    name: None,
+                    inner: TypeInner::Array {
+                        base,
+                        size: ArraySize::Constant(vertices),
+                        stride,
+                    },
+                },
+                meta,
+            )),
+            TypeInner::Array {
+                size: ArraySize::Constant(size),
+                ..
+            } if size == vertices => Ok(ty),
+            _ => Err(Error {
+                kind: ErrorKind::SemanticError(
+                    format!("Geometry shader inputs must be arrays of {vertices} elements").into(),
+                ),
+                meta,
+            }),
+        }
+    }
+
+    /// Looks up the built-ins that only exist in geometry shaders.
+    pub(crate) fn lookup_geometry_variable(
+        &mut self,
+        ctx: &mut Context,
+        name: &str,
+        meta: Span,
+    ) -> Result<Option<VariableReference>> {
+        let Some(ref geometry) = self.geometry else {
+            return Ok(None);
+        };
+
+        let inner = match name {
+            "gl_in" => {
+                let size = geometry.input_vertex_count(meta)?;
+                let position = ctx.module.types.insert(
+                    Type {
+                        name: None,
+                        inner: TypeInner::Vector {
+                            size: VectorSize::Quad,
+                            scalar: Scalar::F32,
+                        },
+                    },
+                    meta,
+                );
+                let base = ctx.module.types.insert(
+                    Type {
+                        name: Some("gl_PerVertex".into()),
+                        inner: TypeInner::Struct {
+                            members: vec![StructMember {
+                                name: Some("gl_Position".into()),
+                                ty: position,
+                                binding: None,
+                                offset: 0,
+                            }],
+                            span: 16,
+                        },
+                    },
+                    meta,
+                );
+
+                TypeInner::Array {
+                    base,
+                    size: ArraySize::Constant(size),
+                    stride: 16,
+                }
+            }
+            "gl_PrimitiveIDIn" | "gl_InvocationID" => TypeInner::Scalar(Scalar::U32),
+            _ => return Ok(None),
+        };
+
+        let ty = ctx.module.types.insert(Type { name: None, inner }, meta);
+        let handle = ctx.module.global_variables.append(
+            GlobalVariable {
+                name: Some(name.into()),
+                space: AddressSpace::Private,
+                binding: None,
+                ty,
+                init: None,
+            },
+            meta,
+        );
+
+        if let Some(ref mut geometry) = self.geometry {
+            match name {
+                "gl_in" => geometry.per_vertex = Some(handle),
+                "gl_PrimitiveIDIn" => geometry.primitive_id = Some(handle),
+                _ => geometry.invocation_id = Some(handle),
+            }
+        }
+
+        self.global_variables.push((
+            name.into(),
+            GlobalLookup {
+                kind: GlobalLookupKind::Variable(handle),
+                entry_arg: None,
+                mutable: false,
+            },
+        ));
+
+        let expr = ctx.add_expression(Expression::GlobalVariable(handle), meta)?;
+
+        let var = VariableReference {
+            expr,
+            load: true,
+            mutable: false,
+            constant: None,
+            entry_arg: None,
+        };
+
+        ctx.symbol_table.add_root(name.into(), var.clone());
+
+        Ok(Some(var))
+    }
+
+    /// Returns the function called by `EmitVertex` or `EndPrimitive`.
+    pub(crate) fn geometry_function(
+        &mut self,
+        ctx: &mut Context,
+        function: GeometryFunction,
+        meta: Span,
+    ) -> Result<Handle<Function>> {
+        let name = match function {
+            GeometryFunction::EmitVertex => "EmitVertex",
+            GeometryFunction::EndPrimitive => "EndPrimitive",
+        };
+
+        let Some(ref mut geometry) = self.geometry else {
+            return Err(Error {
+                kind: ErrorKind::SemanticError(
+                    format!("{name} can only be used in geometry shaders").into(),
+                ),
+                meta,
+            });
+        };
+
+        let handle = match function {
+            GeometryFunction::EmitVertex => &mut geometry.emit_vertex,
+            GeometryFunction::EndPrimitive => &mut geometry.end_primitive,
+        };
+
+        Ok(*handle.get_or_insert_with(|| {
+            ctx.module.functions.append(
+                Function {
+                    name: Some(name.into()),
+                    ..Default::default()
+                },
+                Span::default(),
+            )
+        }))
+    }
+
+    /// Adds the compute entry point of a geometry shader calling `function`,
+    /// and generates the bodies of the functions called by `EmitVertex` and
+    /// `EndPrimitive`.
+    pub(crate) fn add_geometry_entry_point(
+        &mut self,
+        geometry: GeometryState,
+        function: Handle<Function>,
+        name: &str,
+        mut ctx: Context,
+    ) -> Result<()> {
+        let meta = Span::default();
+        let missing = |what: &str| Error {
+            kind: ErrorKind::SemanticError(format!("Geometry shaders must declare {what}").into()),
+            meta,
+        };
+        let input_primitive = geometry
+            .input_primitive
+            .ok_or_else(|| missing("the input primitive"))?;
+        let output_primitive = geometry
+            .output_primitive
+            .ok_or_else(|| missing("the output primitive"))?;
+        let max_vertices = geometry
+            .max_vertices
+            .ok_or_else(|| missing("max_vertices"))?;
+
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        let mut position = None;
+        for arg in self.entry_args.iter() {
+            match (arg.storage, &arg.binding) {
+                (StorageQualifier::Input, &Binding::Location { .. }) => inputs.push(arg.handle),
+                (_, &Binding::Location { .. }) => outputs.push(arg.handle),
+                (StorageQualifier::Output, &Binding::BuiltIn(BuiltIn::Position { .. })) => {
+                    position = Some(arg.handle)
+                }
+                (_, &Binding::BuiltIn(_)) => {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            format!(
+                                "{} isn't supported in geometry shaders",
+                                arg.name.as_deref().unwrap_or_default()
+                            )
+                            .into(),
+                        ),
+                        meta: ctx.module.global_variables.get_span(arg.handle),
+                    })
+                }
+            }
+        }
+
+        let vec4 = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Vector {
+                    size: VectorSize::Quad,
+                    scalar: Scalar::F32,
+                },
+            },
+            meta,
+        );
+        let uint = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Scalar(Scalar::U32),
+            },
+            meta,
+        );
+
+        let mut input_members = vec![(Some("gl_Position".to_string()), vec4)];
+        for &handle in inputs.iter() {
+            let var = &ctx.module.global_variables[handle];
+            match ctx.module.types[var.ty].inner {
+                TypeInner::Array { base, .. } => input_members.push((var.name.clone(), base)),
+                _ => unreachable!("geometry shader inputs are arrays"),
+            }
+        }
+        let (input_vertex, input_span) =
+            self.add_geometry_struct(&mut ctx, "GeometryInputVertex", input_members)?;
+        let input_ty = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Array {
+                    base: input_vertex,
+                    size: ArraySize::Dynamic,
+                    stride: input_span,
+                },
+            },
+            meta,
+        );
+        let input = ctx.module.global_variables.append(
+            GlobalVariable {
+                name: Some("geometry_input".into()),
+                space: AddressSpace::Storage {
+                    access: StorageAccess::LOAD,
+                },
+                binding: Some(geometry.options.input_binding.clone()),
+                ty: input_ty,
+                init: None,
+            },
+            meta,
+        );
+
+        let mut output_members = vec![(Some("gl_Position".to_string()), vec4)];
+        for &handle in outputs.iter() {
+            let var = &ctx.module.global_variables[handle];
+            output_members.push((var.name.clone(), var.ty));
+        }
+        let (output_vertex, output_span) =
+            self.add_geometry_struct(&mut ctx, "GeometryOutputVertex", output_members)?;
+        let atomic = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Atomic(Scalar::U32),
+            },
+            meta,
+        );
+        let vertices = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Array {
+                    base: output_vertex,
+                    size: ArraySize::Dynamic,
+                    stride: output_span,
+                },
+            },
+            meta,
+        );
+        let header = |name: &str, ty, offset| StructMember {
+            name: Some(name.into()),
+            ty,
+            binding: None,
+            offset,
+        };
+        let output_ty = ctx.module.types.insert(
+            Type {
+                name: Some("GeometryOutput".into()),
+                inner: TypeInner::Struct {
+                    members: vec![
+                        header("vertex_count", atomic, 0),
+                        header("instance_count", uint, 4),
+                        header("first_vertex", uint, 8),
+                        header("first_instance", uint, 12),
+                        header("vertices", vertices, 16),
+                    ],
+                    span: 16 + output_span,
+                },
+            },
+            meta,
+        );
+        let output = ctx.module.global_variables.append(
+            GlobalVariable {
+                name: Some("geometry_output".into()),
+                space: AddressSpace::Storage {
+                    access: StorageAccess::LOAD | StorageAccess::STORE,
+                },
+                binding: Some(geometry.options.output_binding.clone()),
+                ty: output_ty,
+                init: None,
+            },
+            meta,
+        );
+
+        // The number of vertices emitted by the invocation
+        let emitted = add_private(&mut ctx, "geometry_emitted", uint);
+        // The previous vertices of the current strip, and its length
+        let strip_size = output_primitive.vertex_count() - 1;
+        let strip = match NonZeroU32::new(strip_size) {
+            Some(size) => {
+                let ty = ctx.module.types.insert(
+                    Type {
+                        name: None,
+                        inner: TypeInner::Array {
+                            base: output_vertex,
+                            size: ArraySize::Constant(size),
+                            stride: output_span,
+                        },
+                    },
+                    meta,
+                );
+                Some((
+                    add_private(&mut ctx, "geometry_strip", ty),
+                    add_private(&mut ctx, "geometry_strip_length", uint),
+                ))
+            }
+            None => None,
+        };
+
+        if let Some(handle) = geometry.emit_vertex {
+            let (expressions, body) = self.geometry_function_body(&mut ctx, |ctx| {
+                let emitted = add(ctx, Expression::GlobalVariable(emitted))?;
+                let count = add(ctx, Expression::Load { pointer: emitted })?;
+                let max_vertices = literal(ctx, max_vertices)?;
+                let full = binary(ctx, BinaryOperator::GreaterEqual, count, max_vertices)?;
+                let accept = ctx.new_body(|ctx| {
+                    ctx.body.push(Statement::Return { value: None }, meta);
+                    Ok(())
+                })?;
+                ctx.body.push(
+                    Statement::If {
+                        condition: full,
+                        accept,
+                        reject: Block::new(),
+                    },
+                    meta,
+                );
+                let one = literal(ctx, 1)?;
+                let count = binary(ctx, BinaryOperator::Add, count, one)?;
+                store(ctx, emitted, count);
+
+                let mut components = Vec::with_capacity(outputs.len() + 1);
+                components.push(match position {
+                    Some(handle) => {
+                        let pointer = add(ctx, Expression::GlobalVariable(handle))?;
+                        add(ctx, Expression::Load { pointer })?
+                    }
+                    None => add(ctx, Expression::ZeroValue(vec4))?,
+                });
+                for &handle in outputs.iter() {
+                    let pointer = add(ctx, Expression::GlobalVariable(handle))?;
+                    components.push(add(ctx, Expression::Load { pointer })?);
+                }
+                let vertex = add(
+                    ctx,
+                    Expression::Compose {
+                        ty: output_vertex,
+                        components,
+                    },
+                )?;
+
+                let Some((strip, length)) = strip else {
+                    return append_primitive(ctx, output, vec![vertex]);
+                };
+
+                // Vertex `i` of the strip is kept in `strip[i % strip_size]`, for
+                // triangle strips this also gives odd triangles the winding of
+                // even ones.
+                let strip = add(ctx, Expression::GlobalVariable(strip))?;
+                let length_pointer = add(ctx, Expression::GlobalVariable(length))?;
+                let length = add(
+                    ctx,
+                    Expression::Load {
+                        pointer: length_pointer,
+                    },
+                )?;
+                let strip_size = literal(ctx, strip_size)?;
+                let complete = binary(ctx, BinaryOperator::GreaterEqual, length, strip_size)?;
+                let accept = ctx.new_body(|ctx| {
+                    let mut vertices = Vec::new();
+                    for index in 0..output_primitive.vertex_count() - 1 {
+                        let pointer = add(ctx, Expression::AccessIndex { base: strip, index })?;
+                        vertices.push(add(ctx, Expression::Load { pointer })?);
+                    }
+                    vertices.push(vertex);
+                    append_primitive(ctx, output, vertices)
+                })?;
+                ctx.body.push(
+                    Statement::If {
+                        condition: complete,
+                        accept,
+                        reject: Block::new(),
+                    },
+                    meta,
+                );
+                let index = binary(ctx, BinaryOperator::Modulo, length, strip_size)?;
+                let pointer = add(ctx, Expression::Access { base: strip, index })?;
+                store(ctx, pointer, vertex);
+                let length = binary(ctx, BinaryOperator::Add, length, one)?;
+                store(ctx, length_pointer, length);
+                Ok(())
+            })?;
+            let function = &mut ctx.module.functions[handle];
+            function.expressions = expressions;
+            function.body = body;
+        }
+
+        if let Some(handle) = geometry.end_primitive {
+            let (expressions, body) = self.geometry_function_body(&mut ctx, |ctx| {
+                if let Some((_, length)) = strip {
+                    let pointer = add(ctx, Expression::GlobalVariable(length))?;
+                    let zero = literal(ctx, 0)?;
+                    store(ctx, pointer, zero);
+                }
+                Ok(())
+            })?;
+            let function = &mut ctx.module.functions[handle];
+            function.expressions = expressions;
+            function.body = body;
+        }
+
+        ctx.emit_restart();
+        let global_init_body = std::mem::replace(&mut ctx.body, Block::new());
+
+        let global_id_ty = ctx.module.types.insert(
+            Type {
+                name: None,
+                inner: TypeInner::Vector {
+                    size: VectorSize::Tri,
+                    scalar: Scalar::U32,
+                },
+            },
+            meta,
+        );
+        let arguments = vec![FunctionArgument {
+            name: Some("global_id".into()),
+            ty: global_id_ty,
+            binding: Some(Binding::BuiltIn(BuiltIn::GlobalInvocationId)),
+        }];
+        let global_id = add(&mut ctx, Expression::FunctionArgument(0))?;
+        let primitive = add(
+            &mut ctx,
+            Expression::AccessIndex {
+                base: global_id,
+                index: 0,
+            },
+        )?;
+        let invocation = add(
+            &mut ctx,
+            Expression::AccessIndex {
+                base: global_id,
+                index: 1,
+            },
+        )?;
+
+        // Skip the invocations past the last primitive or instance
+        let input = add(&mut ctx, Expression::GlobalVariable(input))?;
+        let length = add(&mut ctx, Expression::ArrayLength(input))?;
+        let vertex_count = literal(&mut ctx, input_primitive.vertex_count())?;
+        let primitives = binary(&mut ctx, BinaryOperator::Divide, length, vertex_count)?;
+        let past_primitives = binary(
+            &mut ctx,
+            BinaryOperator::GreaterEqual,
+            primitive,
+            primitives,
+        )?;
+        let invocations = literal(&mut ctx, geometry.invocations)?;
+        let past_invocations = binary(
+            &mut ctx,
+            BinaryOperator::GreaterEqual,
+            invocation,
+            invocations,
+        )?;
+        let condition = binary(
+            &mut ctx,
+            BinaryOperator::LogicalOr,
+            past_primitives,
+            past_invocations,
+        )?;
+        let accept = ctx.new_body(|ctx| {
+            ctx.body.push(Statement::Return { value: None }, meta);
+            Ok(())
+        })?;
+        ctx.body.push(
+            Statement::If {
+                condition,
+                accept,
+                reject: Block::new(),
+            },
+            meta,
+        );
+
+        if let Some(handle) = geometry.primitive_id {
+            let pointer = add(&mut ctx, Expression::GlobalVariable(handle))?;
+            store(&mut ctx, pointer, primitive);
+        }
+        if let Some(handle) = geometry.invocation_id {
+            let pointer = add(&mut ctx, Expression::GlobalVariable(handle))?;
+            store(&mut ctx, pointer, invocation);
+        }
+
+        let first = binary(&mut ctx, BinaryOperator::Multiply, primitive, vertex_count)?;
+        for i in 0..input_primitive.vertex_count() {
+            let index = match i {
+                0 => first,
+                _ => {
+                    let offset = literal(&mut ctx, i)?;
+                    binary(&mut ctx, BinaryOperator::Add, first, offset)?
+                }
+            };
+            let vertex = add(&mut ctx, Expression::Access { base: input, index })?;
+
+            if let Some(handle) = geometry.per_vertex {
+                let gl_in = add(&mut ctx, Expression::GlobalVariable(handle))?;
+                let element = add(
+                    &mut ctx,
+                    Expression::AccessIndex {
+                        base: gl_in,
+                        index: i,
+                    },
+                )?;
+                let pointer = add(
+                    &mut ctx,
+                    Expression::AccessIndex {
+                        base: element,
+                        index: 0,
+                    },
+                )?;
+                let member = add(
+                    &mut ctx,
+                    Expression::AccessIndex {
+                        base: vertex,
+                        index: 0,
+                    },
+                )?;
+                let value = add(&mut ctx, Expression::Load { pointer: member })?;
+                store(&mut ctx, pointer, value);
+            }
+
+            for (member, &handle) in (1..).zip(inputs.iter()) {
+                let var = add(&mut ctx, Expression::GlobalVariable(handle))?;
+                let pointer = add(
+                    &mut ctx,
+                    Expression::AccessIndex {
+                        base: var,
+                        index: i,
+                    },
+                )?;
+                let member = add(
+                    &mut ctx,
+                    Expression::AccessIndex {
+                        base: vertex,
+                        index: member,
+                    },
+                )?;
+                let value = add(&mut ctx, Expression::Load { pointer: member })?;
+                store(&mut ctx, pointer, value);
+            }
+        }
+
+        ctx.emit_restart();
+        ctx.body.extend_block(global_init_body);
+        ctx.body.push(
+            Statement::Call {
+                function,
+                arguments: Vec::new(),
+                result: None,
+            },
+            meta,
+        );
+        ctx.body.push(Statement::Return { value: None }, meta);
+
+        let workgroup_size = [geometry.options.workgroup_size, 1, 1];
+        self.meta.workgroup_size = workgroup_size;
+        self.meta.geometry = Some(GeometryMetadata {
+            input_primitive,
+            output_primitive,
+            max_vertices,
+            invocations: geometry.invocations,
+        });
+
+        let Context {
+            body, expressions, ..
+        } = ctx;
+
+        ctx.module.entry_points.push(EntryPoint {
+            name: name.to_string(),
+            stage: ShaderStage::Compute,
+            early_depth_test: None,
+            workgroup_size,
+            function: Function {
+                arguments,
+                expressions,
+                body,
+                ..Default::default()
+            },
+        });
+
+        Ok(())
+    }
+
+    /// Adds a struct with the given members laid out with the std430 rules,
+    /// returning it along with its span.
+    fn add_geometry_struct(
+        &mut self,
+        ctx: &mut Context,
+        name: &str,
+        members: Vec<(Option<String>, Handle<Type>)>,
+    ) -> Result<(Handle<Type>, u32)> {
+        let meta = Span::default();
+        let mut span = 0;
+        let mut align = Alignment::ONE;
+        let mut struct_members = Vec::with_capacity(members.len());
+
+        for (member_name, ty) in members {
+            let info = offset::calculate_offset(
+                ty,
+                meta,
+                StructLayout::Std430,
+                &mut ctx.module.types,
+                &mut self.errors,
+            );
+
+            // The member is copied from or to a variable of type `ty`, so its
+            // layout can't be changed
+            if info.ty != ty {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        format!(
+                            "The type of {} can't be used in the buffers of a geometry shader",
+                            member_name.as_deref().unwrap_or_default()
+                        )
+                        .into(),
+                    ),
+                    meta,
+                });
+            }
+
+            span = info.align.round_up(span);
+            align = info.align.max(align);
+
+            struct_members.push(StructMember {
+                name: member_name,
+                ty,
+                binding: None,
+                offset: span,
+            });
+
+            span += info.span;
+        }
+
+        span = align.round_up(span);
+
+        let ty = ctx.module.types.insert(
+            Type {
+                name: Some(name.into()),
+                inner: TypeInner::Struct {
+                    members: struct_members,
+                    span,
+                },
+            },
+            meta,
+        );
+
+        Ok((ty, span))
+    }
+
+    /// Builds the body of a generated function with `f`.
+    fn geometry_function_body(
+        &self,
+        ctx: &mut Context,
+        f: impl FnOnce(&mut Context) -> Result<()>,
+    ) -> Result<(Arena<Expression>, Block)> {
+        let mut function_ctx =
+            Context::new(self, ctx.module, false, ctx.global_expression_kind_tracker)?;
+        f(&mut function_ctx)?;
+        function_ctx.emit_end();
+
+        Ok((function_ctx.expressions, function_ctx.body))
+    }
+}
+
+/// Appends a primitive made of `vertices` to the output buffer, unless it
+/// doesn't fit.
+fn append_primitive(
+    ctx: &mut Context,
+    output: Handle<GlobalVariable>,
+    vertices: Vec<Handle<Expression>>,
+) -> Result<()> {
+    let meta = Span::default();
+    let uint = ctx.module.types.insert(
+        Type {
+            name: None,
+            inner: TypeInner::Scalar(Scalar::U32),
+        },
+        meta,
+    );
+
+    let output = add(ctx, Expression::GlobalVariable(output))?;
+    let counter = add(
+        ctx,
+        Expression::AccessIndex {
+            base: output,
+            index: 0,
+        },
+    )?;
+    let count = literal(ctx, vertices.len() as u32)?;
+
+    ctx.emit_end();
+    let first = add(
+        ctx,
+        Expression::AtomicResult {
+            ty: uint,
+            comparison: false,
+        },
+    )?;
+    ctx.body.push(
+        Statement::Atomic {
+            pointer: counter,
+            fun: AtomicFunction::Add,
+            value: count,
+            result: Some(first),
+        },
+        meta,
+    );
+    ctx.emit_start();
+
+    let buffer = add(
+        ctx,
+        Expression::AccessIndex {
+            base: output,
+            index: 4,
+        },
+    )?;
+    let capacity = add(ctx, Expression::ArrayLength(buffer))?;
+    let end = binary(ctx, BinaryOperator::Add, first, count)?;
+    let fits = binary(ctx, BinaryOperator::LessEqual, end, capacity)?;
+
+    let accept = ctx.new_body(|ctx| {
+        for (i, value) in vertices.into_iter().enumerate() {
+            let index = match i {
+                0 => first,
+                _ => {
+                    let offset = literal(ctx, i as u32)?;
+                    binary(ctx, BinaryOperator::Add, first, offset)?
+                }
+            };
+            let pointer = add(
+                ctx,
+                Expression::Access {
+                    base: buffer,
+                    index,
+                },
+            )?;
+            store(ctx, pointer, value);
+        }
+        Ok(())
+    })?;
+    // Give back the space taken by the primitive, so the vertex count
+    // doesn't end up past the end of the buffer
+    let reject = ctx.new_body(|ctx| {
+        ctx.body.push(
+            Statement::Atomic {
+                pointer: counter,
+                fun: AtomicFunction::Subtract,
+                value: count,
+                result: None,
+            },
+            meta,
+        );
+        Ok(())
+    })?;
+    ctx.body.push(
+        Statement::If {
+            condition: fits,
+            accept,
+            reject,
+        },
+        meta,
+    );
+
+    Ok(())
+}
+
+fn add_private(ctx: &mut Context, name: &str, ty: Handle<Type>) -> Handle<GlobalVariable> {
+    ctx.module.global_variables.append(
+        GlobalVariable {
+            name: Some(name.into()),
+            space: AddressSpace::Private,
+            binding: None,
+            ty,
+            init: None,
+        },
+        Span::default(),
+    )
+}
+
+fn add(ctx: &mut Context, expr: Expression) -> Result<Handle<Expression>> {
+    ctx.add_expression(expr, Span::default())
+}
+
+fn literal(ctx: &mut Context, value: u32) -> Result<Handle<Expression>> {
+    add(ctx, Expression::Literal(Literal::U32(value)))
+}
+
+fn binary(
+    ctx: &mut Context,
+    op: BinaryOperator,
+    left: Handle<Expression>,
+    right: Handle<Expression>,
+) -> Result<Handle<Expression>> {
+    add(ctx, Expression::Binary { op, left, right })
+}
+
+fn store(ctx: &mut Context, pointer: Handle<Expression>, value: Handle<Expression>) {
+    ctx.emit_restart();
+    ctx.body
+        .push(Statement::Store { pointer, value }, Span::default());
+}
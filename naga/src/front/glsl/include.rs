@@ -0,0 +1,317 @@
+/*!
+Support for the `#include` directive of `GL_GOOGLE_include_directive` and
+`GL_ARB_shading_language_include`.
+
+When [`Options::include_resolver`] is set, every `#include` directive is
+replaced by the text of the file it names before the shader is
+preprocessed, so that macros and include guards work across files as they
+would in a single one:
+
+```glsl
+#version 450
+#extension GL_GOOGLE_include_directive : require
+
+#include "lighting.glsl"
+```
+
+The path is handed to the [`IncludeResolver`], which supplies the file's
+text. An `#include` of a file that is already being included is ignored,
+which is what an include guard in that file would do. Directives that
+can't be resolved only cause an error if the preprocessor doesn't skip
+them, so they can be placed in `#if` blocks.
+
+All the files are kept in a [`SourceMap`]. [`Span`]s in the module and in
+errors are byte ranges in its [`text`], where each file's text is kept
+whole, rather than in the text given to the preprocessor.
+
+[`Options::include_resolver`]: super::Options::include_resolver
+[`text`]: SourceMap::text
+*/
+
+use crate::{SourceMap, Span};
+use std::fmt;
+
+/// How the path in an `#include` directive was written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IncludeKind {
+    /// `#include "path"`, usually looked for next to the including file
+    /// first.
+    Local,
+    /// `#include <path>`, usually only looked for in the include search
+    /// paths.
+    System,
+}
+
+/// A file found by an [`IncludeResolver`].
+#[derive(Clone, Debug)]
+pub struct ResolvedInclude {
+    /// The name of the file, used to identify it and to label errors in it.
+    pub name: String,
+    /// The GLSL source text of the file.
+    pub source: String,
+}
+
+type IncludeCallback =
+    dyn Fn(&str, &str, IncludeKind) -> Result<ResolvedInclude, String> + Send + Sync;
+
+/// Supplies the text of files named in `#include` directives.
+///
+/// Each directive is replaced by the text of the file it names before the
+/// shader is preprocessed, so macros and include guards work across files.
+/// An `#include` of a file that is already being included is ignored, as an
+/// include guard would do. A directive that can't be resolved is only an
+/// error if the preprocessor doesn't skip it.
+///
+/// The callback is passed the name of the including file, the path from the
+/// directive and how it was written. The name is the [`ResolvedInclude::name`]
+/// the callback returned for the including file, or an empty string for the
+/// source passed to [`Frontend::parse`].
+///
+/// The returned [`ResolvedInclude::name`] decides whether two directives
+/// refer to the same file, so it should be something like a canonical path.
+/// On failure, return a message explaining why; it is reported at the
+/// `#include` directive.
+///
+/// ```rust
+/// # use naga::front::glsl::{IncludeResolver, ResolvedInclude};
+/// let resolver = IncludeResolver::new(|_includer, path, _kind| match path {
+///     "common.glsl" => Ok(ResolvedInclude {
+///         name: path.to_string(),
+///         source: "const float PI = 3.14159;".to_string(),
+///     }),
+///     _ => Err("file not found".to_string()),
+/// });
+/// ```
+///
+/// [`Frontend::parse`]: super::Frontend::parse
+pub struct IncludeResolver {
+    callback: Box<IncludeCallback>,
+}
+
+impl IncludeResolver {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&str, &str, IncludeKind) -> Result<ResolvedInclude, String> + Send + Sync + 'static,
+    {
+        IncludeResolver {
+            callback: Box::new(callback),
+        }
+    }
+
+    fn resolve(
+        &self,
+        includer: &str,
+        path: &str,
+        kind: IncludeKind,
+    ) -> Result<ResolvedInclude, String> {
+        (self.callback)(includer, path, kind)
+    }
+}
+
+impl fmt::Debug for IncludeResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncludeResolver").finish_non_exhaustive()
+    }
+}
+
+/// The text given to the preprocessor, with `#include` directives replaced
+/// by the files they name.
+#[derive(Debug)]
+pub struct Expansion {
+    text: String,
+    /// The start of each run of text copied from a single file, paired with
+    /// its offset in the [`SourceMap`]'s text. Sorted by the former.
+    segments: Vec<(usize, usize)>,
+    /// The spans of `#include` directives that couldn't be resolved, from
+    /// the keyword to the end of the line, in the [`SourceMap`]'s text, and
+    /// why.
+    failures: Vec<(Span, String)>,
+}
+
+impl Expansion {
+    /// Expand the includes in `source`, and return the result along with
+    /// the files it was built from.
+    pub fn new(source: &str, resolver: &IncludeResolver) -> (Self, SourceMap) {
+        let mut source_map = SourceMap::new("", source);
+        let mut expansion = Expansion {
+            text: String::with_capacity(source.len()),
+            segments: Vec::new(),
+            failures: Vec::new(),
+        };
+        expansion.expand(&mut source_map, resolver, 0, &mut Vec::new());
+        (expansion, source_map)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Convert a range in [`text`](Self::text) to a span in the
+    /// [`SourceMap`]'s text.
+    pub fn span(&self, start: u32, end: u32) -> Span {
+        let index = self
+            .segments
+            .partition_point(|&(segment, _)| segment <= start as usize)
+            .saturating_sub(1);
+        match self.segments.get(index) {
+            Some(&(segment, offset)) => {
+                let mapped = (offset + start as usize - segment) as u32;
+                Span::new(mapped, mapped + end.saturating_sub(start))
+            }
+            None => Span::new(start, end),
+        }
+    }
+
+    /// If `span` starts an `#include` directive that couldn't be resolved,
+    /// return the directive's span and why.
+    pub fn failure(&self, span: Span) -> Option<(Span, &str)> {
+        let start = span.to_range()?.start;
+        self.failures
+            .iter()
+            .find(|&&(failed, _)| failed.to_range().is_some_and(|range| range.start == start))
+            .map(|&(failed, ref message)| (failed, message.as_str()))
+    }
+
+    fn push(&mut self, text: &str, offset: usize) {
+        self.segments.push((self.text.len(), offset));
+        self.text.push_str(text);
+    }
+
+    fn expand(
+        &mut self,
+        source_map: &mut SourceMap,
+        resolver: &IncludeResolver,
+        file: usize,
+        stack: &mut Vec<usize>,
+    ) {
+        stack.push(file);
+
+        let start = source_map.files()[file].range().start;
+        let source = source_map.source(&source_map.files()[file]).to_string();
+        let mut copied = 0;
+        let mut in_comment = false;
+        let mut line_start = 0;
+        while line_start < source.len() {
+            let line_end = source[line_start..]
+                .find('\n')
+                .map_or(source.len(), |i| line_start + i + 1);
+            let line = &source[line_start..line_end];
+
+            let directive = match in_comment {
+                true => None,
+                false => parse_include(line),
+            };
+            in_comment = ends_in_comment(line, in_comment);
+            let Some((keyword, directive)) = directive else {
+                line_start = line_end;
+                continue;
+            };
+
+            self.push(&source[copied..line_start], start + copied);
+            copied = line_end;
+
+            let keyword = line_start + keyword;
+            let resolved = directive.and_then(|(path, kind)| {
+                let includer = source_map.files()[file].name();
+                resolver
+                    .resolve(includer, path, kind)
+                    .map_err(|message| format!("could not include `{path}`: {message}"))
+            });
+            match resolved {
+                Ok(resolved) => {
+                    let included = match source_map.file_index(&resolved.name) {
+                        Some(index) => index,
+                        None => source_map.add(resolved.name, &resolved.source),
+                    };
+                    if !stack.contains(&included) {
+                        self.expand(source_map, resolver, included, stack);
+                    }
+                    // Keep the rest of the including file on a line of its own.
+                    self.push("\n", start + line_end - 1);
+                }
+                Err(message) => {
+                    // Turn the directive into an `#error` with the same
+                    // length, so that it is only reported if the
+                    // preprocessor doesn't skip it. The path is blanked
+                    // out, since the preprocessor rejects quotes even in
+                    // skipped blocks.
+                    let end = source[..line_end].trim_end_matches(['\r', '\n']).len();
+                    let directive = format!("error{:1$}", "", end - keyword - "error".len());
+                    self.push(&source[line_start..keyword], start + line_start);
+                    self.push(&directive, start + keyword);
+                    self.push(&source[end..line_end], start + end);
+
+                    let span = Span::from(start + keyword..start + end);
+                    self.failures.push((span, message));
+                }
+            }
+
+            line_start = line_end;
+        }
+        self.push(&source[copied..], start + copied);
+
+        stack.pop();
+    }
+}
+
+/// The path in an `#include` directive and how it was written, or why it
+/// couldn't be read.
+type IncludePath<'a> = Result<(&'a str, IncludeKind), String>;
+
+/// If `line` is an `#include` directive, return the offset of the
+/// `include` keyword, and the path.
+fn parse_include(line: &str) -> Option<(usize, IncludePath<'_>)> {
+    let is_blank = |c: char| c == ' ' || c == '\t';
+
+    let directive = line.trim_start_matches(is_blank).strip_prefix('#')?;
+    let keyword = directive.trim_start_matches(is_blank);
+    let rest = keyword.strip_prefix("include")?;
+    if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let offset = line.len() - keyword.len();
+
+    let rest = rest.trim_start_matches(is_blank);
+    let (kind, close) = match rest.chars().next() {
+        Some('"') => (IncludeKind::Local, '"'),
+        Some('<') => (IncludeKind::System, '>'),
+        _ => {
+            let message = "expected a file name in quotes or angle brackets".to_string();
+            return Some((offset, Err(message)));
+        }
+    };
+    let path = match rest[1..].find(close) {
+        Some(end) => Ok((&rest[1..end + 1], kind)),
+        None => Err(format!("missing closing `{close}`")),
+    };
+    Some((offset, path))
+}
+
+/// Return whether `line` ends inside a block comment, given whether it
+/// starts in one.
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    let mut rest = line;
+    loop {
+        if in_comment {
+            match rest.find("*/") {
+                Some(end) => {
+                    rest = &rest[end + 2..];
+                    in_comment = false;
+                }
+                None => return true,
+            }
+        } else {
+            match (rest.find("//"), rest.find("/*")) {
+                (Some(line_comment), Some(block)) if block < line_comment => {
+                    rest = &rest[block + 2..];
+                    in_comment = true;
+                }
+                (None, Some(block)) => {
+                    rest = &rest[block + 2..];
+                    in_comment = true;
+                }
+                _ => return false,
+            }
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use super::{
     ast::Precision,
+    error::ErrorKind,
+    include::Expansion,
     token::{Directive, DirectiveKind, Token, TokenValue},
     types::parse_type,
 };
 use crate::{FastHashMap, Span, StorageAccess};
 use pp_rs::{
     pp::Preprocessor,
-    token::{PreprocessorError, Punct, TokenValue as PPTokenValue},
+    token::{Location, PreprocessorError, Punct, Token as PPToken, TokenValue as PPTokenValue},
 };
 
 #[derive(Debug)]
@@ -21,11 +23,14 @@ pub struct LexerResult {
 pub enum LexerResultKind {
     Token(Token),
     Directive(Directive),
-    Error(PreprocessorError),
+    Error(ErrorKind),
 }
 
 pub struct Lexer<'a> {
     pp: Preprocessor<'a>,
+    /// The included files that `pp` is reading, if any, used to map its
+    /// locations back to the files they came from.
+    expansion: Option<&'a Expansion>,
 }
 
 impl<'a> Lexer<'a> {
@@ -34,7 +39,42 @@ impl<'a> Lexer<'a> {
         for (define, value) in defines {
             pp.add_define(define, value).unwrap(); //TODO: handle error
         }
-        Lexer { pp }
+        Lexer {
+            pp,
+            expansion: None,
+        }
+    }
+
+    /// Creates a lexer reading the text of `expansion`, whose spans are
+    /// offsets in the files it was expanded from.
+    pub fn with_expansion(
+        expansion: &'a Expansion,
+        defines: &'a FastHashMap<String, String>,
+    ) -> Self {
+        Lexer {
+            expansion: Some(expansion),
+            ..Lexer::new(expansion.text(), defines)
+        }
+    }
+
+    fn span(&self, location: Location) -> Span {
+        match self.expansion {
+            Some(expansion) => expansion.span(location.start, location.end),
+            None => location.into(),
+        }
+    }
+
+    /// Maps the locations of the tokens in a directive like [`span`](Self::span).
+    fn map_tokens(&self, mut tokens: Vec<PPToken>) -> Vec<PPToken> {
+        if self.expansion.is_some() {
+            for token in tokens.iter_mut() {
+                if let Some(range) = self.span(token.location).to_range() {
+                    token.location.start = range.start as u32;
+                    token.location.end = range.end as u32;
+                }
+            }
+        }
+        tokens
     }
 }
 
@@ -44,20 +84,27 @@ impl<'a> Iterator for Lexer<'a> {
         let pp_token = match self.pp.next()? {
             Ok(t) => t,
             Err((err, loc)) => {
+                let meta = self.span(loc);
+                let (kind, meta) = match (err, self.expansion.and_then(|e| e.failure(meta))) {
+                    (PreprocessorError::ErrorDirective, Some((directive, message))) => {
+                        (ErrorKind::IncludeError(message.to_string()), directive)
+                    }
+                    (err, _) => (ErrorKind::PreprocessorError(err), meta),
+                };
                 return Some(LexerResult {
-                    kind: LexerResultKind::Error(err),
-                    meta: loc.into(),
+                    kind: LexerResultKind::Error(kind),
+                    meta,
                 });
             }
         };
 
-        let meta = pp_token.location.into();
+        let meta = self.span(pp_token.location);
         let value = match pp_token.value {
             PPTokenValue::Extension(extension) => {
                 return Some(LexerResult {
                     kind: LexerResultKind::Directive(Directive {
                         kind: DirectiveKind::Extension,
-                        tokens: extension.tokens,
+                        tokens: self.map_tokens(extension.tokens),
                     }),
                     meta,
                 })
@@ -73,6 +120,7 @@ impl<'a> Iterator for Lexer<'a> {
                     "buffer" => TokenValue::Buffer,
                     "shared" => TokenValue::Shared,
                     "invariant" => TokenValue::Invariant,
+                    "precise" => TokenValue::Precise,
                     "flat" => TokenValue::Interpolation(crate::Interpolation::Flat),
                     "noperspective" => TokenValue::Interpolation(crate::Interpolation::Linear),
                     "smooth" => TokenValue::Interpolation(crate::Interpolation::Perspective),
@@ -85,6 +133,8 @@ impl<'a> Iterator for Lexer<'a> {
                     "mediump" => TokenValue::PrecisionQualifier(Precision::Medium),
                     "lowp" => TokenValue::PrecisionQualifier(Precision::Low),
                     "restrict" => TokenValue::Restrict,
+                    "coherent" => TokenValue::Coherent,
+                    "volatile" => TokenValue::Volatile,
                     "readonly" => TokenValue::MemoryQualifier(StorageAccess::LOAD),
                     "writeonly" => TokenValue::MemoryQualifier(StorageAccess::STORE),
                     // values
@@ -173,7 +223,7 @@ impl<'a> Iterator for Lexer<'a> {
                 return Some(LexerResult {
                     kind: LexerResultKind::Directive(Directive {
                         kind: DirectiveKind::Pragma,
-                        tokens: pragma.tokens,
+                        tokens: self.map_tokens(pragma.tokens),
                     }),
                     meta,
                 })
@@ -184,7 +234,7 @@ impl<'a> Iterator for Lexer<'a> {
                         kind: DirectiveKind::Version {
                             is_first_directive: version.is_first_directive,
                         },
-                        tokens: version.tokens,
+                        tokens: self.map_tokens(version.tokens),
                     }),
                     meta,
                 })
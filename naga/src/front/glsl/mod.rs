@@ -14,10 +14,15 @@ To begin, take a look at the documentation for the [`Frontend`].
 
 pub use ast::{Precision, Profile};
 pub use error::{Error, ErrorKind, ExpectedToken, ParseErrors};
+pub use geometry::{GeometryMetadata, GeometryOptions, InputPrimitive, OutputPrimitive};
+pub use include::{IncludeKind, IncludeResolver, ResolvedInclude};
 pub use token::TokenValue;
 
-use crate::{proc::Layouter, FastHashMap, FastHashSet, Handle, Module, ShaderStage, Span, Type};
+use crate::{
+    proc::Layouter, FastHashMap, FastHashSet, Handle, Module, ShaderStage, SourceMap, Span, Type,
+};
 use ast::{EntryArg, FunctionDeclaration, GlobalLookup};
+use geometry::GeometryState;
 use parser::ParsingContext;
 
 mod ast;
@@ -25,6 +30,8 @@ mod builtins;
 mod context;
 mod error;
 mod functions;
+mod geometry;
+mod include;
 mod lex;
 mod offset;
 mod parser;
@@ -56,6 +63,17 @@ pub struct Options {
     /// ```
     /// for each key value pair in the map.
     pub defines: FastHashMap<String, String>,
+    /// Supplies the files named in `#include` directives. Without one, the
+    /// directive is an error.
+    ///
+    /// See [`IncludeResolver`] for how files are included.
+    pub include_resolver: Option<IncludeResolver>,
+    /// Parses the shader as a geometry shader, lowered to a compute shader
+    /// since the IR has no geometry stage. `stage` must be
+    /// [`ShaderStage::Compute`].
+    ///
+    /// See [`GeometryOptions`] for how the lowered shader is used.
+    pub geometry: Option<GeometryOptions>,
 }
 
 impl From<ShaderStage> for Options {
@@ -63,6 +81,17 @@ impl From<ShaderStage> for Options {
         Options {
             stage,
             defines: FastHashMap::default(),
+            include_resolver: None,
+            geometry: None,
+        }
+    }
+}
+
+impl From<GeometryOptions> for Options {
+    fn from(geometry: GeometryOptions) -> Self {
+        Options {
+            geometry: Some(geometry),
+            ..Options::from(ShaderStage::Compute)
         }
     }
 }
@@ -89,6 +118,9 @@ pub struct ShaderMetadata {
     /// Whether or not early fragment tests where requested by the shader.
     /// Defaults to `false`.
     pub early_fragment_tests: bool,
+    /// The primitives and limits declared by a geometry shader, `None` for
+    /// other shaders.
+    pub geometry: Option<GeometryMetadata>,
 
     /// The shader can request extensions via the
     /// `#extension` preprocessor directive, in the directive a behavior
@@ -107,6 +139,7 @@ impl ShaderMetadata {
         self.stage = stage;
         self.workgroup_size = [u32::from(stage == ShaderStage::Compute); 3];
         self.early_fragment_tests = false;
+        self.geometry = None;
         self.extensions.clear();
     }
 }
@@ -119,6 +152,7 @@ impl Default for ShaderMetadata {
             stage: ShaderStage::Vertex,
             workgroup_size: [0; 3],
             early_fragment_tests: false,
+            geometry: None,
             extensions: FastHashSet::default(),
         }
     }
@@ -172,19 +206,24 @@ pub struct Frontend {
 
     entry_args: Vec<EntryArg>,
 
+    geometry: Option<GeometryState>,
+
     layouter: Layouter,
 
     errors: Vec<Error>,
+
+    source_map: SourceMap,
 }
 
 impl Frontend {
-    fn reset(&mut self, stage: ShaderStage) {
-        self.meta.reset(stage);
+    fn reset(&mut self, options: &Options) {
+        self.meta.reset(options.stage);
 
         self.lookup_function.clear();
         self.lookup_type.clear();
         self.global_variables.clear();
         self.entry_args.clear();
+        self.geometry = options.geometry.clone().map(GeometryState::new);
         self.layouter.clear();
     }
 
@@ -197,15 +236,82 @@ impl Frontend {
         options: &Options,
         source: &str,
     ) -> std::result::Result<Module, ParseErrors> {
-        self.reset(options.stage);
+        let mut module = Module::default();
+        self.parse_into(options, source, "main", &mut module)?;
+        Ok(module)
+    }
 
-        let lexer = lex::Lexer::new(source, &options.defines);
+    /// Parses a shader into an existing [`Module`], adding an entry point
+    /// named `entry_point` for its `main` function.
+    ///
+    /// This can be used to put several shaders, such as the vertex and
+    /// fragment shaders of a pipeline, in one module. Each shader is parsed
+    /// on its own, as by [`parse`](Frontend::parse), but they share the
+    /// module's types. A `uniform` or `buffer` declaration with the same
+    /// name, type and binding as a global already in the module, or a push
+    /// constant block with the same name and type, refers to that global.
+    ///
+    /// Spans in the module are offsets into the source they came from, so
+    /// spans from different shaders can't be told apart.
+    ///
+    /// The module is left unchanged if parsing fails, or if it already has
+    /// an entry point with this name for the same stage.
+    pub fn parse_into(
+        &mut self,
+        options: &Options,
+        source: &str,
+        entry_point: &str,
+        module: &mut Module,
+    ) -> std::result::Result<(), ParseErrors> {
+        self.reset(options);
+
+        if options.geometry.is_some() && options.stage != ShaderStage::Compute {
+            return Err(vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "Geometry shaders are parsed as compute shaders".into(),
+                ),
+                meta: Span::default(),
+            }]
+            .into());
+        }
+
+        if module
+            .entry_points
+            .iter()
+            .any(|ep| ep.stage == options.stage && ep.name == entry_point)
+        {
+            return Err(vec![Error {
+                kind: ErrorKind::SemanticError(
+                    format!("Entry point {entry_point} already exists").into(),
+                ),
+                meta: Span::default(),
+            }]
+            .into());
+        }
+
+        let expansion = match options.include_resolver {
+            Some(ref resolver) => {
+                let (expansion, source_map) = include::Expansion::new(source, resolver);
+                self.source_map = source_map;
+                Some(expansion)
+            }
+            None => {
+                self.source_map = SourceMap::new("", source);
+                None
+            }
+        };
+        let lexer = match expansion {
+            Some(ref expansion) => lex::Lexer::with_expansion(expansion, &options.defines),
+            None => lex::Lexer::new(source, &options.defines),
+        };
         let mut ctx = ParsingContext::new(lexer);
 
-        match ctx.parse(self) {
-            Ok(module) => {
+        let mut parsed = module.clone();
+        match ctx.parse(self, &mut parsed, entry_point) {
+            Ok(()) => {
                 if self.errors.is_empty() {
-                    Ok(module)
+                    *module = parsed;
+                    Ok(())
                 } else {
                     Err(std::mem::take(&mut self.errors).into())
                 }
@@ -229,4 +335,14 @@ impl Frontend {
     pub const fn metadata(&self) -> &ShaderMetadata {
         &self.meta
     }
+
+    /// Returns the files that make up the previously parsed shader: the
+    /// source passed to [`parse`](Frontend::parse), with an empty name, and
+    /// any files it included, in the order they were first included.
+    ///
+    /// Spans in the parsed [`Module`] and in the errors are offsets in the
+    /// source map's [`text`](SourceMap::text).
+    pub const fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
 }
@@ -84,6 +84,7 @@ pub fn calculate_offset(
 
             let span = match size {
                 crate::ArraySize::Constant(size) => size.get() * stride,
+                crate::ArraySize::Pending(_) => unreachable!(),
                 crate::ArraySize::Dynamic => stride,
             };
 
@@ -160,6 +161,11 @@ pub fn calculate_offset(
 
             (align, span)
         }
+        // Buffer references are 64-bit device addresses
+        TypeInner::Pointer {
+            space: crate::AddressSpace::PhysicalStorage,
+            ..
+        } => (Alignment::EIGHT, crate::proc::PHYSICAL_POINTER_SPAN),
         _ => {
             errors.push(Error {
                 kind: ErrorKind::SemanticError("Invalid struct member type".into()),
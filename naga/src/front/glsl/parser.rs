@@ -97,8 +97,8 @@ impl<'source> ParsingContext<'source> {
                 LexerResultKind::Directive(directive) => {
                     frontend.handle_directive(directive, res.meta)
                 }
-                LexerResultKind::Error(error) => frontend.errors.push(Error {
-                    kind: ErrorKind::PreprocessorError(error),
+                LexerResultKind::Error(kind) => frontend.errors.push(Error {
+                    kind,
                     meta: res.meta,
                 }),
             }
@@ -143,8 +143,8 @@ impl<'source> ParsingContext<'source> {
                         LexerResultKind::Directive(directive) => {
                             frontend.handle_directive(directive, res.meta)
                         }
-                        LexerResultKind::Error(error) => frontend.errors.push(Error {
-                            kind: ErrorKind::PreprocessorError(error),
+                        LexerResultKind::Error(kind) => frontend.errors.push(Error {
+                            kind,
                             meta: res.meta,
                         }),
                         LexerResultKind::Token(_) => unreachable!(),
@@ -162,17 +162,19 @@ impl<'source> ParsingContext<'source> {
         })
     }
 
-    pub fn parse(&mut self, frontend: &mut Frontend) -> Result<Module> {
-        let mut module = Module::default();
-        let mut global_expression_kind_tracker = crate::proc::ExpressionKindTracker::new();
+    /// Parses the shader into `module`, adding an entry point for its `main`
+    /// function named `entry_point`.
+    pub fn parse(
+        &mut self,
+        frontend: &mut Frontend,
+        module: &mut Module,
+        entry_point: &str,
+    ) -> Result<()> {
+        let mut global_expression_kind_tracker =
+            crate::proc::ExpressionKindTracker::from_arena(&module.global_expressions);
 
         // Body and expression arena for global initialization
-        let mut ctx = Context::new(
-            frontend,
-            &mut module,
-            false,
-            &mut global_expression_kind_tracker,
-        )?;
+        let mut ctx = Context::new(frontend, module, false, &mut global_expression_kind_tracker)?;
 
         while self.peek(frontend).is_some() {
             self.parse_external_declaration(frontend, &mut ctx)?;
@@ -184,8 +186,8 @@ impl<'source> ParsingContext<'source> {
             for decl in declaration.overloads.iter() {
                 if let FunctionKind::Call(handle) = decl.kind {
                     if decl.defined && decl.parameters.is_empty() {
-                        frontend.add_entry_point(handle, ctx)?;
-                        return Ok(module);
+                        frontend.add_entry_point(handle, entry_point, ctx)?;
+                        return Ok(());
                     }
                 }
             }
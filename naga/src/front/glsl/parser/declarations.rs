@@ -1,8 +1,8 @@
 use crate::{
     front::glsl::{
         ast::{
-            GlobalLookup, GlobalLookupKind, Precision, QualifierKey, QualifierValue,
-            StorageQualifier, StructLayout, TypeQualifiers,
+            GlobalLookup, GlobalLookupKind, MemberQualifiers, Precision, QualifierKey,
+            QualifierValue, StorageQualifier, StructLayout, TypeQualifiers,
         },
         context::{Context, ExprPos},
         error::ExpectedToken,
@@ -431,22 +431,55 @@ impl<'source> ParsingContext<'source> {
                                 token.meta,
                             )
                             .map(Some)
+                        } else if qualifiers
+                            .none_layout_qualifier("buffer_reference", &mut frontend.errors)
+                        {
+                            // Forward declaration of a buffer reference, the IR has
+                            // no recursive types so the block must be declared
+                            // before its name can be used.
+                            let _ = qualifiers.uint_layout_qualifier(
+                                "buffer_reference_align",
+                                &mut frontend.errors,
+                            );
+                            qualifiers.storage_access.take();
+                            qualifiers.unused_errors(&mut frontend.errors);
+                            self.expect(frontend, TokenValue::Semicolon)?;
+                            Ok(Some(qualifiers.span))
                         } else {
-                            if qualifiers.invariant.take().is_some() {
-                                frontend.make_variable_invariant(ctx, &ty_name, token.meta)?;
+                            // type_qualifier IDENTIFIER SEMICOLON
+                            // type_qualifier IDENTIFIER identifier_list SEMICOLON
+                            let invariant = qualifiers.invariant.take().is_some();
+                            if !invariant && qualifiers.precise.take().is_none() {
+                                return Err(Error {
+                                    kind: ErrorKind::SemanticError(
+                                        "Only the invariant qualifier can be used \
+                                        to redeclare a variable"
+                                            .into(),
+                                    ),
+                                    meta: qualifiers.span,
+                                });
+                            }
+
+                            let (mut name, mut meta) = (ty_name, token.meta);
+                            loop {
+                                if frontend.lookup_variable(ctx, &name, meta)?.is_none() {
+                                    frontend.errors.push(Error {
+                                        kind: ErrorKind::UnknownVariable(name),
+                                        meta,
+                                    });
+                                } else if invariant {
+                                    frontend.make_variable_invariant(ctx, &name, meta)?;
+                                }
 
-                                qualifiers.unused_errors(&mut frontend.errors);
-                                self.expect(frontend, TokenValue::Semicolon)?;
-                                return Ok(Some(qualifiers.span));
+                                if self.bump_if(frontend, TokenValue::Comma).is_none() {
+                                    break;
+                                }
+                                (name, meta) = self.expect_ident(frontend)?;
                             }
 
-                            //TODO: declaration
-                            // type_qualifier IDENTIFIER SEMICOLON
-                            // type_qualifier IDENTIFIER identifier_list SEMICOLON
-                            Err(Error {
-                                kind: ErrorKind::NotImplemented("variable qualifier"),
-                                meta: token.meta,
-                            })
+                            qualifiers.unused_errors(&mut frontend.errors);
+                            self.expect(frontend, TokenValue::Semicolon)?;
+                            Ok(Some(qualifiers.span))
                         }
                     }
                     TokenValue::Semicolon => {
@@ -468,6 +501,7 @@ impl<'source> ParsingContext<'source> {
 
                         frontend.meta.early_fragment_tests |= qualifiers
                             .none_layout_qualifier("early_fragment_tests", &mut frontend.errors);
+                        frontend.add_geometry_layout(&mut qualifiers);
 
                         qualifiers.unused_errors(&mut frontend.errors);
 
@@ -553,9 +587,143 @@ impl<'source> ParsingContext<'source> {
         };
 
         let mut members = Vec::new();
-        let span = self.parse_struct_declaration_list(frontend, ctx, &mut members, layout)?;
+        let mut member_qualifiers = Vec::new();
+        let span = self.parse_struct_declaration_list(
+            frontend,
+            ctx,
+            &mut members,
+            &mut member_qualifiers,
+            layout,
+        )?;
         self.expect(frontend, TokenValue::RightBrace)?;
 
+        // Redeclarations of built-in blocks like `gl_PerVertex` don't declare
+        // any variables, they can only change the qualifiers of the built-ins
+        if ty_name.starts_with("gl_")
+            && matches!(
+                qualifiers.storage.0,
+                StorageQualifier::Input | StorageQualifier::Output
+            )
+        {
+            let token = self.expect(frontend, TokenValue::Semicolon)?;
+            meta.subsume(token.meta);
+
+            let block_invariant = qualifiers.invariant.take();
+            for (member, member_qualifiers) in members.iter().zip(member_qualifiers) {
+                let name = member.name.as_deref().unwrap_or_default();
+                if block_invariant.or(member_qualifiers.invariant).is_some() {
+                    frontend.make_variable_invariant(ctx, name, meta)?;
+                }
+
+                MemberQualifiers {
+                    invariant: None,
+                    ..member_qualifiers
+                }
+                .unused_errors(&mut frontend.errors);
+            }
+
+            qualifiers.unused_errors(&mut frontend.errors);
+            return Ok(meta);
+        }
+
+        // `layout(buffer_reference) buffer Name { ... };` doesn't declare a
+        // variable, it declares `Name` as the type of pointers to the block in
+        // the physical storage address space
+        if qualifiers.none_layout_qualifier("buffer_reference", &mut frontend.errors) {
+            let token = self.expect(frontend, TokenValue::Semicolon)?;
+            meta.subsume(token.meta);
+
+            if !matches!(
+                qualifiers.storage.0,
+                StorageQualifier::AddressSpace(AddressSpace::Storage { .. })
+            ) {
+                frontend.errors.push(Error {
+                    kind: ErrorKind::SemanticError(
+                        "Buffer references must be declared with the buffer qualifier".into(),
+                    ),
+                    meta: qualifiers.storage.1,
+                });
+            }
+
+            if let Some(align) =
+                qualifiers.uint_layout_qualifier("buffer_reference_align", &mut frontend.errors)
+            {
+                if !align.is_power_of_two() {
+                    frontend.errors.push(Error {
+                        kind: ErrorKind::SemanticError(
+                            "buffer_reference_align must be a power of two".into(),
+                        ),
+                        meta,
+                    });
+                }
+            }
+
+            // Pointers in the physical storage address space are always
+            // readable and writable
+            qualifiers.storage_access.take();
+            for member_qualifiers in member_qualifiers {
+                MemberQualifiers {
+                    storage_access: None,
+                    ..member_qualifiers
+                }
+                .unused_errors(&mut frontend.errors);
+            }
+            qualifiers.unused_errors(&mut frontend.errors);
+
+            let base = ctx.module.types.insert(
+                Type {
+                    name: Some(ty_name.clone()),
+                    inner: TypeInner::Struct { members, span },
+                },
+                meta,
+            );
+            let ty = ctx.module.types.insert(
+                Type {
+                    name: None,
+                    inner: TypeInner::Pointer {
+                        base,
+                        space: AddressSpace::PhysicalStorage,
+                    },
+                },
+                meta,
+            );
+            frontend.lookup_type.insert(ty_name, ty);
+
+            return Ok(meta);
+        }
+
+        let mut members_access = None;
+        for member_qualifiers in member_qualifiers {
+            let invariant = match qualifiers.storage.0 {
+                StorageQualifier::Input | StorageQualifier::Output => None,
+                _ => member_qualifiers.invariant,
+            };
+            let storage_access = match qualifiers.storage.0 {
+                StorageQualifier::AddressSpace(AddressSpace::Storage { .. }) => {
+                    let access = member_qualifiers
+                        .storage_access
+                        .map_or(crate::StorageAccess::all(), |(access, _)| access);
+                    *members_access.get_or_insert(crate::StorageAccess::empty()) |= access;
+                    None
+                }
+                _ => member_qualifiers.storage_access,
+            };
+
+            MemberQualifiers {
+                invariant,
+                storage_access,
+            }
+            .unused_errors(&mut frontend.errors);
+        }
+
+        // The access of a storage block is the union of the access of its members
+        if let Some(members_access) = members_access {
+            let storage_access = qualifiers
+                .storage_access
+                .get_or_insert((crate::StorageAccess::all(), Span::default()));
+            storage_access.0 &= members_access;
+        }
+
         let mut ty = ctx.module.types.insert(
             Type {
                 name: Some(ty_name),
@@ -619,19 +787,30 @@ impl<'source> ParsingContext<'source> {
         Ok(meta)
     }
 
+    /// Parses the members of a struct or block, `qualifiers` is appended with
+    /// the qualifiers of each member
     // TODO: Accept layout arguments
     pub fn parse_struct_declaration_list(
         &mut self,
         frontend: &mut Frontend,
         ctx: &mut Context,
         members: &mut Vec<StructMember>,
+        qualifiers: &mut Vec<MemberQualifiers>,
         layout: StructLayout,
     ) -> Result<u32> {
         let mut span = 0;
         let mut align = Alignment::ONE;
 
         loop {
-            // TODO: type_qualifier
+            let mut type_qualifiers = self.parse_type_qualifiers(frontend, ctx)?;
+            let member_qualifiers = MemberQualifiers {
+                invariant: type_qualifiers.invariant.take(),
+                storage_access: type_qualifiers.storage_access.take(),
+            };
+            // Precision qualifiers have no effect and members can repeat the
+            // storage qualifier of their block
+            type_qualifiers.precision = None;
+            type_qualifiers.unused_errors(&mut frontend.errors);
 
             let (base_ty, mut meta) = self.parse_type_non_void(frontend, ctx)?;
 
@@ -660,6 +839,7 @@ impl<'source> ParsingContext<'source> {
                     binding: None,
                     offset: span,
                 });
+                qualifiers.push(member_qualifiers);
 
                 span += info.span;
 
@@ -85,12 +85,17 @@ impl<'source> ParsingContext<'source> {
                 let ty_name = self.expect_ident(frontend)?.0;
                 self.expect(frontend, TokenValue::LeftBrace)?;
                 let mut members = Vec::new();
+                let mut qualifiers = Vec::new();
                 let span = self.parse_struct_declaration_list(
                     frontend,
                     ctx,
                     &mut members,
+                    &mut qualifiers,
                     StructLayout::Std140,
                 )?;
+                for member_qualifiers in qualifiers {
+                    member_qualifiers.unused_errors(&mut frontend.errors);
+                }
                 let end_meta = self.expect(frontend, TokenValue::RightBrace)?.meta;
                 meta.subsume(end_meta);
                 let ty = ctx.module.types.insert(
@@ -149,6 +154,7 @@ impl<'source> ParsingContext<'source> {
     pub fn peek_type_qualifier(&mut self, frontend: &mut Frontend) -> bool {
         self.peek(frontend).map_or(false, |t| match t.value {
             TokenValue::Invariant
+            | TokenValue::Precise
             | TokenValue::Interpolation(_)
             | TokenValue::Sampling(_)
             | TokenValue::PrecisionQualifier(_)
@@ -159,6 +165,8 @@ impl<'source> ParsingContext<'source> {
             | TokenValue::Shared
             | TokenValue::Buffer
             | TokenValue::Restrict
+            | TokenValue::Coherent
+            | TokenValue::Volatile
             | TokenValue::MemoryQualifier(_)
             | TokenValue::Layout => true,
             _ => false,
@@ -197,6 +205,17 @@ impl<'source> ParsingContext<'source> {
 
                     qualifiers.invariant = Some(token.meta);
                 }
+                TokenValue::Precise => {
+                    // The IR can't forbid the contraction of arithmetic operations
+                    // (for example into fused multiply-adds), so dropping the
+                    // qualifier could silently change the results of the shader.
+                    frontend.errors.push(Error {
+                        kind: ErrorKind::NotImplemented("precise qualifier"),
+                        meta: token.meta,
+                    });
+
+                    qualifiers.precise = Some(token.meta);
+                }
                 TokenValue::Interpolation(i) => {
                     if qualifiers.interpolation.is_some() {
                         frontend.errors.push(Error {
@@ -289,7 +308,17 @@ impl<'source> ParsingContext<'source> {
                     storage_access.0 &= access;
                     storage_access.1.subsume(token.meta);
                 }
-                TokenValue::Restrict => continue,
+                // Storage accesses in the IR are never assumed to be free of
+                // aliasing or to be cacheable across barriers, so these don't
+                // restrict anything, but like the other memory qualifiers they
+                // are only allowed in storage variables
+                TokenValue::Restrict | TokenValue::Coherent | TokenValue::Volatile => {
+                    qualifiers
+                        .storage_access
+                        .get_or_insert((crate::StorageAccess::all(), Span::default()))
+                        .1
+                        .subsume(token.meta);
+                }
                 _ => unreachable!(),
             };
         }
@@ -3,9 +3,10 @@ use super::{
     error::ExpectedToken,
     error::{Error, ErrorKind, ParseErrors},
     token::TokenValue,
-    Frontend, Options, Span,
+    Frontend, GeometryMetadata, GeometryOptions, IncludeKind, IncludeResolver, InputPrimitive,
+    Options, OutputPrimitive, ResolvedInclude, Span,
 };
-use crate::ShaderStage;
+use crate::{AddressSpace, Binding, BuiltIn, ShaderStage, TypeInner};
 use pp_rs::token::PreprocessorError;
 
 #[test]
@@ -330,6 +331,187 @@ fn declarations() {
         .unwrap();
 }
 
+#[test]
+fn qualifiers() {
+    let mut frontend = Frontend::default();
+
+    let module = frontend
+        .parse(
+            &Options::from(ShaderStage::Vertex),
+            r#"
+        #version 450
+        out gl_PerVertex {
+            vec4 gl_Position;
+        };
+
+        layout(location = 0) out vec4 color;
+
+        invariant gl_Position;
+
+        void main() {
+            float scale = 2.0;
+            gl_Position = vec4(scale);
+        }
+        "#,
+        )
+        .unwrap();
+    let result = module.entry_points[0].function.result.as_ref().unwrap();
+    let TypeInner::Struct { ref members, .. } = module.types[result.ty].inner else {
+        panic!("entry point result should be a struct");
+    };
+    assert!(members
+        .iter()
+        .any(|member| member.binding
+            == Some(Binding::BuiltIn(BuiltIn::Position { invariant: true }))));
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Vertex),
+                r#"
+        #version 450
+        coherent float value;
+        flat gl_Position;
+
+        void main() {}
+        "#,
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![
+                Error {
+                    kind: ErrorKind::SemanticError(
+                        "Memory qualifiers can only be used in storage variables".into(),
+                    ),
+                    meta: Span::new(30, 38),
+                },
+                Error {
+                    kind: ErrorKind::SemanticError(
+                        "Only the invariant qualifier can be used to redeclare a variable".into(),
+                    ),
+                    meta: Span::new(60, 64),
+                },
+            ]
+        },
+    );
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Vertex),
+                r#"
+        #version 450
+        layout(location = 0) out vec4 color;
+        precise color;
+
+        void main() {
+            precise float scale = 2.0;
+        }
+        "#,
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![
+                Error {
+                    kind: ErrorKind::NotImplemented("precise qualifier"),
+                    meta: Span::new(75, 82),
+                },
+                Error {
+                    kind: ErrorKind::NotImplemented("precise qualifier"),
+                    meta: Span::new(125, 132),
+                },
+            ]
+        },
+    );
+}
+
+#[test]
+fn buffer_references() {
+    let mut frontend = Frontend::default();
+
+    let module = frontend
+        .parse(
+            &Options::from(ShaderStage::Compute),
+            r#"
+        #version 460
+        #extension GL_EXT_buffer_reference : require
+        layout(local_size_x = 64) in;
+
+        layout(buffer_reference) buffer Positions;
+        layout(buffer_reference, std430, buffer_reference_align = 16) buffer Positions {
+            vec4 data[];
+        };
+        layout(buffer_reference, std430) readonly buffer Params {
+            uint count;
+            float scale;
+        };
+
+        layout(push_constant) uniform PushConstants {
+            Positions positions;
+            Params params;
+        } pc;
+
+        void main() {
+            uint index = gl_GlobalInvocationID.x;
+            if (index >= pc.params.count) {
+                return;
+            }
+            Positions positions = pc.positions;
+            positions.data[index] *= pc.params.scale;
+        }
+        "#,
+        )
+        .unwrap();
+    let physical_pointers = module
+        .types
+        .iter()
+        .filter(|&(_, ty)| {
+            matches!(
+                ty.inner,
+                TypeInner::Pointer {
+                    space: AddressSpace::PhysicalStorage,
+                    ..
+                }
+            )
+        })
+        .count();
+    assert_eq!(physical_pointers, 2);
+    crate::valid::Validator::new(
+        crate::valid::ValidationFlags::all(),
+        crate::valid::Capabilities::PUSH_CONSTANT
+            | crate::valid::Capabilities::PHYSICAL_STORAGE_BUFFER,
+    )
+    .validate(&module)
+    .unwrap();
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Compute),
+                r#"
+        #version 460
+        layout(buffer_reference) uniform Data {
+            float value;
+        };
+
+        void main() {}
+        "#,
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "Buffer references must be declared with the buffer qualifier".into(),
+                ),
+                meta: Span::new(55, 62),
+            }],
+        },
+    );
+}
+
 #[test]
 fn textures() {
     let mut frontend = Frontend::default();
@@ -854,3 +1036,310 @@ fn expressions() {
         )
         .unwrap();
 }
+
+#[test]
+fn includes() {
+    let resolver = || {
+        IncludeResolver::new(|includer, path, kind| {
+            let source = match (path, kind) {
+                ("constants.glsl", IncludeKind::Local) => {
+                    "#ifndef CONSTANTS\n#define CONSTANTS\nconst float SCALE = 2.0;\n#endif\n"
+                }
+                ("helpers.glsl", IncludeKind::System) => {
+                    "#include \"constants.glsl\"\nfloat scale(float x) { return x * SCALE; }"
+                }
+                ("cycle.glsl", _) => "#include \"cycle.glsl\"\nfloat cycle() { return 1.0; }\n",
+                ("broken.glsl", _) => "float broken() { return undefined; }\n",
+                _ => return Err("not found".to_string()),
+            };
+            assert!(includer.is_empty() || includer.ends_with(".glsl"));
+            Ok(ResolvedInclude {
+                name: path.to_string(),
+                source: source.to_string(),
+            })
+        })
+    };
+    let options = || Options {
+        include_resolver: Some(resolver()),
+        ..Options::from(ShaderStage::Vertex)
+    };
+
+    let mut frontend = Frontend::default();
+
+    // Include guards, nested and recursive includes, and unresolved
+    // includes that the preprocessor skips
+    frontend
+        .parse(
+            &options(),
+            r#"
+        #version 450
+        #extension GL_GOOGLE_include_directive : require
+        #include "constants.glsl"
+        #include <helpers.glsl>
+        #include "cycle.glsl"
+        /*
+        #include "missing.glsl"
+        */
+        #if 0
+        #include "missing.glsl"
+        #endif
+
+        void main() {
+            gl_Position = vec4(scale(SCALE) + cycle());
+        }
+        "#,
+        )
+        .unwrap();
+    assert_eq!(frontend.source_map().files().len(), 4);
+
+    // Errors point at the file they occurred in
+    let source = "#version 450\n#include \"broken.glsl\"\nvoid main() {}\n";
+    let errors = frontend.parse(&options(), source).unwrap_err().errors;
+    let source_map = frontend.source_map();
+    let (file, span) = source_map.file_for_span(errors[0].meta).unwrap();
+    assert_eq!(source_map.files()[file].name(), "broken.glsl");
+    assert_eq!(span, Span::new(24, 33));
+
+    // Tokens after an include keep their position in the including file
+    let source = "#version 450\n#include \"constants.glsl\"\nvoid main() { bad; }\n";
+    let errors = frontend.parse(&options(), source).unwrap_err().errors;
+    let source_map = frontend.source_map();
+    assert_eq!(
+        source_map.file_for_span(errors[0].meta),
+        Some((0, Span::new(53, 56)))
+    );
+
+    // Unresolved includes
+    assert_eq!(
+        frontend
+            .parse(
+                &options(),
+                "#version 450\n#include \"missing.glsl\"\nvoid main() {}\n",
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::IncludeError("could not include `missing.glsl`: not found".into()),
+                meta: Span::new(14, 36),
+            }],
+        },
+    );
+
+    // Without a resolver, `#include` is an unknown directive
+    frontend
+        .parse(
+            &Options::from(ShaderStage::Vertex),
+            "#version 450\n#include \"constants.glsl\"\nvoid main() {}\n",
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn multiple_entry_points() {
+    let mut frontend = Frontend::default();
+    let mut module = crate::Module::default();
+
+    frontend
+        .parse_into(
+            &Options::from(ShaderStage::Vertex),
+            r#"
+        #version 450
+        layout(set = 0, binding = 0) uniform Globals {
+            mat4 transform;
+            vec4 tint;
+        };
+        layout(location = 0) in vec3 position;
+        layout(location = 0) out vec4 color;
+
+        void main() {
+            color = tint;
+            gl_Position = transform * vec4(position, 1.0);
+        }
+        "#,
+            "vs_main",
+            &mut module,
+        )
+        .unwrap();
+
+    frontend
+        .parse_into(
+            &Options::from(ShaderStage::Fragment),
+            r#"
+        #version 450
+        layout(set = 0, binding = 0) uniform Globals {
+            mat4 transform;
+            vec4 tint;
+        };
+        layout(set = 0, binding = 1) uniform texture2D tex;
+        layout(set = 0, binding = 2) uniform sampler samp;
+        layout(location = 0) in vec4 color;
+        layout(location = 0) out vec4 frag_color;
+
+        void main() {
+            frag_color = color * tint * texture(sampler2D(tex, samp), vec2(0.5));
+        }
+        "#,
+            "fs_main",
+            &mut module,
+        )
+        .unwrap();
+
+    let entry_points: Vec<_> = module
+        .entry_points
+        .iter()
+        .map(|ep| (ep.name.as_str(), ep.stage))
+        .collect();
+    assert_eq!(
+        entry_points,
+        [
+            ("vs_main", ShaderStage::Vertex),
+            ("fs_main", ShaderStage::Fragment)
+        ]
+    );
+
+    // The uniform block is shared, the varyings aren't
+    let uniforms = module
+        .global_variables
+        .iter()
+        .filter(|&(_, global)| global.space == AddressSpace::Uniform)
+        .count();
+    assert_eq!(uniforms, 1);
+    let colors = module
+        .global_variables
+        .iter()
+        .filter(|&(_, global)| global.name.as_deref() == Some("color"))
+        .count();
+    assert_eq!(colors, 2);
+
+    crate::valid::Validator::new(
+        crate::valid::ValidationFlags::all(),
+        crate::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .unwrap();
+
+    // Entry points must be distinct, and a failed parse leaves the module
+    // unchanged
+    let before = module.clone();
+    frontend
+        .parse_into(
+            &Options::from(ShaderStage::Vertex),
+            "#version 450\nvoid main() {}",
+            "vs_main",
+            &mut module,
+        )
+        .unwrap_err();
+    frontend
+        .parse_into(
+            &Options::from(ShaderStage::Compute),
+            "#version 450\nvoid main() { undefined(); }",
+            "cs_main",
+            &mut module,
+        )
+        .unwrap_err();
+    assert_eq!(module.entry_points.len(), before.entry_points.len());
+    assert_eq!(module.functions.len(), before.functions.len());
+    assert_eq!(module.global_variables.len(), before.global_variables.len());
+}
+
+#[test]
+fn geometry_shaders() {
+    let mut frontend = Frontend::default();
+
+    let module = frontend
+        .parse(
+            &Options::from(GeometryOptions::default()),
+            r#"
+        #version 450
+        layout(lines, invocations = 2) in;
+        layout(points, max_vertices = 4) out;
+
+        layout(location = 0) in float size[];
+        layout(location = 0) out vec2 uv;
+
+        void main() {
+            vec4 position = gl_in[gl_InvocationID].gl_Position;
+            for (int i = 0; i < 4; i++) {
+                gl_Position = position + vec4(size[gl_InvocationID] * float(i));
+                uv = vec2(float(gl_PrimitiveIDIn));
+                EmitVertex();
+            }
+            EndPrimitive();
+        }
+        "#,
+        )
+        .unwrap();
+    crate::valid::Validator::new(
+        crate::valid::ValidationFlags::all(),
+        crate::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .unwrap();
+
+    let [ref entry_point] = module.entry_points[..] else {
+        panic!("expected a single entry point");
+    };
+    assert_eq!(entry_point.stage, ShaderStage::Compute);
+    assert_eq!(entry_point.workgroup_size, [64, 1, 1]);
+
+    let metadata = frontend.metadata().geometry.unwrap();
+    assert_eq!(
+        metadata,
+        GeometryMetadata {
+            input_primitive: InputPrimitive::Lines,
+            output_primitive: OutputPrimitive::Points,
+            max_vertices: 4,
+            invocations: 2,
+        }
+    );
+    assert_eq!(metadata.max_output_vertices(), 8);
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Vertex),
+                r#"
+        #version 450
+        void main() {
+            EmitVertex();
+        }
+        "#,
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "EmitVertex can only be used in geometry shaders".into(),
+                ),
+                meta: Span::new(56, 68),
+            }],
+        },
+    );
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(GeometryOptions::default()),
+                r#"
+        #version 450
+        layout(location = 0) in vec3 color[];
+        layout(triangles) in;
+
+        void main() {}
+        "#,
+            )
+            .err()
+            .unwrap(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "The input primitive must be declared before the inputs of a geometry shader"
+                        .into(),
+                ),
+                meta: Span::new(59, 66),
+            }],
+        },
+    );
+}
@@ -38,6 +38,8 @@ pub enum TokenValue {
     Shared,
 
     Restrict,
+    Coherent,
+    Volatile,
     /// A `glsl` memory qualifier such as `writeonly`
     ///
     /// The associated [`crate::StorageAccess`] is the access being allowed
@@ -45,6 +47,7 @@ pub enum TokenValue {
     MemoryQualifier(crate::StorageAccess),
 
     Invariant,
+    Precise,
     Interpolation(Interpolation),
     Sampling(Sampling),
     Precision,
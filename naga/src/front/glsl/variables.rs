@@ -105,6 +105,10 @@ impl Frontend {
             return Ok(Some(var));
         }
 
+        if let Some(var) = self.lookup_geometry_variable(ctx, name, meta)? {
+            return Ok(Some(var));
+        }
+
         let data = match name {
             "gl_Position" => BuiltInData {
                 inner: TypeInner::Vector {
@@ -202,6 +206,10 @@ impl Frontend {
                     "gl_VertexIndex" => BuiltIn::VertexIndex,
                     "gl_SampleID" => BuiltIn::SampleIndex,
                     "gl_LocalInvocationIndex" => BuiltIn::LocalInvocationIndex,
+                    "gl_SubgroupSize" => BuiltIn::SubgroupSize,
+                    "gl_SubgroupInvocationID" => BuiltIn::SubgroupInvocationId,
+                    "gl_NumSubgroups" => BuiltIn::NumSubgroups,
+                    "gl_SubgroupID" => BuiltIn::SubgroupId,
                     _ => return Ok(None),
                 };
 
@@ -239,10 +247,27 @@ impl Frontend {
         &mut self,
         ctx: &mut Context,
         pos: ExprPos,
-        expression: Handle<Expression>,
+        mut expression: Handle<Expression>,
         name: &str,
         meta: Span,
     ) -> Result<Handle<Expression>> {
+        // Selecting a member of a buffer reference goes through the pointer it
+        // holds, so load the reference if only a pointer to it is available.
+        if let TypeInner::Pointer { base, .. } = *ctx.resolve_type(expression, meta)? {
+            if let TypeInner::Pointer {
+                space: AddressSpace::PhysicalStorage,
+                ..
+            } = ctx.module.types[base].inner
+            {
+                expression = ctx.add_expression(
+                    Expression::Load {
+                        pointer: expression,
+                    },
+                    meta,
+                )?;
+            }
+        }
+
         let (ty, is_pointer) = match *ctx.resolve_type(expression, meta)? {
             TypeInner::Pointer { base, .. } => (&ctx.module.types[base].inner, true),
             ref ty => (ty, false),
@@ -418,6 +443,9 @@ impl Frontend {
         let (ret, lookup) = match storage {
             StorageQualifier::Input | StorageQualifier::Output => {
                 let input = storage == StorageQualifier::Input;
+                if input {
+                    ty = self.geometry_input_type(ctx, ty, meta)?;
+                }
                 // TODO: glslang seems to use a counter for variables without
                 // explicit location (even if that causes collisions)
                 let location = qualifiers
@@ -431,6 +459,9 @@ impl Frontend {
                     })
                 });
                 let sampling = qualifiers.sampling.take().map(|(s, _)| s);
+                // The IR can only express invariance for positions, which are
+                // built-ins, so on user defined variables it has no effect
+                qualifiers.invariant.take();
 
                 let handle = ctx.module.global_variables.append(
                     GlobalVariable {
@@ -567,16 +598,36 @@ impl Frontend {
                     _ => None,
                 };
 
-                let handle = ctx.module.global_variables.append(
-                    GlobalVariable {
-                        name: name.clone(),
-                        space,
-                        binding,
-                        ty,
-                        init,
-                    },
-                    meta,
-                );
+                let global = GlobalVariable {
+                    name: name.clone(),
+                    space,
+                    binding,
+                    ty,
+                    init,
+                };
+
+                // Shaders parsed into the same module share their resources
+                let shared = match global.space {
+                    AddressSpace::Uniform
+                    | AddressSpace::Storage { .. }
+                    | AddressSpace::Handle
+                    | AddressSpace::PushConstant => ctx
+                        .module
+                        .global_variables
+                        .iter()
+                        .find(|&(_, other)| {
+                            other.name == global.name
+                                && other.space == global.space
+                                && other.binding == global.binding
+                                && other.ty == global.ty
+                        })
+                        .map(|(handle, _)| handle),
+                    _ => None,
+                };
+                let handle = match shared {
+                    Some(handle) => handle,
+                    None => ctx.module.global_variables.append(global, meta),
+                };
 
                 let lookup = GlobalLookup {
                     kind: GlobalLookupKind::Variable(handle),
@@ -179,6 +179,7 @@ pub(super) fn map_storage_class(word: spirv::Word) -> Result<super::ExtendedClas
         Some(Sc::Uniform) => Ec::Global(crate::AddressSpace::Uniform),
         Some(Sc::Workgroup) => Ec::Global(crate::AddressSpace::WorkGroup),
         Some(Sc::PushConstant) => Ec::Global(crate::AddressSpace::PushConstant),
+        Some(Sc::PhysicalStorageBuffer) => Ec::Global(crate::AddressSpace::PhysicalStorage),
         _ => return Err(Error::UnsupportedStorageClass(word)),
     })
 }
@@ -134,6 +134,10 @@ pub enum Error {
     NonBindingArrayOfImageOrSamplers,
     #[error("naga only supports specialization constant IDs up to 65535 but was given {0}")]
     SpecIdTooHigh(u32),
+    #[error("unsupported specialization constant operation {0:?}")]
+    UnsupportedSpecConstantOp(spirv::Op),
+    #[error("invalid specialization constant operation: {0}")]
+    SpecConstantEvaluation(crate::proc::ConstantEvaluatorError),
 
     #[error("atomic upgrade error: {0}")]
     AtomicUpgradeError(atomic_upgrade::Error),
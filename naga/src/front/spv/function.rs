@@ -57,11 +57,14 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
                     &module.global_variables,
                     &module.constants,
                     &module.overrides,
+                    &module.global_expressions,
                 ),
                 named_expressions: crate::NamedExpressions::default(),
                 body: crate::Block::new(),
+                diagnostic_filter_leaf: None,
             }
         };
+        let global_expression_count = fun.expressions.len();
 
         // read parameters
         for i in 0..fun.arguments.capacity() {
@@ -115,6 +118,24 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
         let mut parameters_sampling =
             vec![super::image::SamplingFlags::empty(); fun.arguments.len()];
 
+        // If the merge annotations don't tell us how to build the function's
+        // statements, collect its control flow graph and work it out ourselves.
+        let flow = if self.function_is_structured()? {
+            None
+        } else {
+            log::debug!("Structurizing function {:?}", fun.name);
+            Some(Default::default())
+        };
+        let u32_type = flow.as_ref().map(|_| {
+            module.types.insert(
+                crate::Type {
+                    name: None,
+                    inner: crate::TypeInner::Scalar(crate::Scalar::U32),
+                },
+                crate::Span::default(),
+            )
+        });
+
         let mut block_ctx = BlockContext {
             phis: Default::default(),
             blocks: Default::default(),
@@ -131,6 +152,7 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             global_arena: &module.global_variables,
             arguments: &fun.arguments,
             parameter_sampling: &mut parameters_sampling,
+            flow,
         };
         // Insert the main body whose parent is also himself
         block_ctx.bodies.push(super::Body::with_parent(0));
@@ -140,16 +162,17 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             let fun_inst = self.next_inst()?;
             log::debug!("{:?}", fun_inst.op);
             match fun_inst.op {
-                spirv::Op::Line => {
-                    fun_inst.expect(4)?;
-                    let _file_id = self.next()?;
-                    let _row_id = self.next()?;
-                    let _col_id = self.next()?;
+                spirv::Op::Line => self.parse_line(fun_inst)?,
+                spirv::Op::NoLine => {
+                    fun_inst.expect(1)?;
+                    self.line_span = None;
                 }
                 spirv::Op::Label => {
                     // Read the label ID
                     fun_inst.expect(2)?;
                     let block_id = self.next()?;
+                    // A line's scope ends with its block.
+                    self.line_span = None;
 
                     self.next_block(block_id, &mut block_ctx)?;
                 }
@@ -163,6 +186,10 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             }
         }
 
+        if let (Some(flow), Some(u32_type)) = (block_ctx.flow.take(), u32_type) {
+            block_ctx.structurize(&flow, u32_type)?;
+        }
+
         if let Some(ref prefix) = self.options.block_ctx_dump_prefix {
             let dump_suffix = match self.lookup_entry_point.get(&fun_id) {
                 Some(ep) => format!("block_ctx.{:?}-{}.txt", ep.stage, ep.name),
@@ -279,6 +306,27 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
 
         fun.body = block_ctx.lower();
 
+        // Emit the composites that `make_expression_storage` copied in from
+        // the global expressions before anything in the body uses them.
+        let mut prologue = crate::Block::new();
+        let mut run = None;
+        for (handle, expr) in fun.expressions.iter().take(global_expression_count) {
+            if !expr.needs_pre_emit() {
+                run = Some((run.map_or(handle, |(first, _)| first), handle));
+            } else if let Some((first, last)) = run.take() {
+                let range = crate::arena::Range::new_from_bounds(first, last);
+                prologue.push(crate::Statement::Emit(range), crate::Span::default());
+            }
+        }
+        if let Some((first, last)) = run {
+            let range = crate::arena::Range::new_from_bounds(first, last);
+            prologue.push(crate::Statement::Emit(range), crate::Span::default());
+        }
+        if !prologue.is_empty() {
+            prologue.append(&mut fun.body);
+            fun.body = prologue;
+        }
+
         // done
         let fun_handle = module.functions.append(fun, self.span_from_with_op(start));
         self.lookup_function.insert(
@@ -311,6 +359,7 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             expressions: Arena::new(),
             named_expressions: crate::NamedExpressions::default(),
             body: crate::Block::new(),
+            diagnostic_filter_leaf: None,
         };
 
         // 1. copy the inputs from arguments to privates
@@ -32,6 +32,8 @@ mod error;
 mod function;
 mod image;
 mod null;
+mod source_map;
+mod structurize;
 
 use convert::*;
 pub use error::Error;
@@ -41,11 +43,11 @@ use indexmap::IndexSet;
 use crate::{
     arena::{Arena, Handle, UniqueArena},
     proc::{Alignment, Layouter},
-    FastHashMap, FastHashSet, FastIndexMap,
+    FastHashMap, FastHashSet, FastIndexMap, SourceMap,
 };
 
 use petgraph::graphmap::GraphMap;
-use std::{convert::TryInto, mem, num::NonZeroU32, path::PathBuf};
+use std::{collections::VecDeque, convert::TryInto, mem, num::NonZeroU32, path::PathBuf};
 
 pub const SUPPORTED_CAPABILITIES: &[spirv::Capability] = &[
     spirv::Capability::Shader,
@@ -69,6 +71,7 @@ pub const SUPPORTED_CAPABILITIES: &[spirv::Capability] = &[
     spirv::Capability::Float64,
     spirv::Capability::Geometry,
     spirv::Capability::MultiView,
+    spirv::Capability::PhysicalStorageBufferAddresses,
     // tricky ones
     spirv::Capability::UniformBufferArrayDynamicIndexing,
     spirv::Capability::StorageBufferArrayDynamicIndexing,
@@ -77,8 +80,26 @@ pub const SUPPORTED_EXTENSIONS: &[&str] = &[
     "SPV_KHR_storage_buffer_storage_class",
     "SPV_KHR_vulkan_memory_model",
     "SPV_KHR_multiview",
+    "SPV_KHR_non_semantic_info",
+    "SPV_KHR_physical_storage_buffer",
+    "SPV_EXT_physical_storage_buffer",
+];
+/// The extended instruction sets we translate.
+///
+/// Modules may also import any other `NonSemantic.*` set: as the SPIR-V
+/// specification permits, we ignore its instructions. `OpenCL.std` is not
+/// supported, since only OpenCL kernels use it, and we don't support the
+/// `Kernel` capability.
+pub const SUPPORTED_EXT_SETS: &[&str] = &[
+    "GLSL.std.450",
+    source_map::DEBUG_INFO_EXT_SET,
+    DEBUG_PRINTF_EXT_SET,
 ];
-pub const SUPPORTED_EXT_SETS: &[&str] = &["GLSL.std.450"];
+
+/// The `NonSemantic.DebugPrintf` extended instruction set.
+const DEBUG_PRINTF_EXT_SET: &str = "NonSemantic.DebugPrintf";
+/// The only instruction in `NonSemantic.DebugPrintf`.
+const DEBUG_PRINTF: spirv::Word = 1;
 
 #[derive(Copy, Clone)]
 pub struct Instruction {
@@ -284,13 +305,28 @@ struct LookupType {
 enum Constant {
     Constant(Handle<crate::Constant>),
     Override(Handle<crate::Override>),
+
+    /// A vector or composite that depends on specialization constants.
+    ///
+    /// Naga overrides must be scalars, so this is just the expression in
+    /// [`Module::global_expressions`] that builds the value. Each function
+    /// that uses it gets its own copy; see [`Frontend::make_expression_storage`].
+    ///
+    /// [`Module::global_expressions`]: crate::Module::global_expressions
+    Composite(Handle<crate::Expression>),
 }
 
 impl Constant {
-    const fn to_expr(&self) -> crate::Expression {
+    /// Return a handle in `global_expressions` for this constant's value.
+    fn to_global_expr(
+        &self,
+        global_expressions: &mut Arena<crate::Expression>,
+        span: crate::Span,
+    ) -> Handle<crate::Expression> {
         match *self {
-            Self::Constant(c) => crate::Expression::Constant(c),
-            Self::Override(o) => crate::Expression::Override(o),
+            Self::Constant(c) => global_expressions.append(crate::Expression::Constant(c), span),
+            Self::Override(o) => global_expressions.append(crate::Expression::Override(o), span),
+            Self::Composite(expr) => expr,
         }
     }
 }
@@ -367,6 +403,12 @@ pub struct Options {
     /// Only allow shaders with the known set of capabilities.
     pub strict_capabilities: bool,
     pub block_ctx_dump_prefix: Option<PathBuf>,
+    /// Translate `DebugPrintf` instructions from the `NonSemantic.DebugPrintf`
+    /// instruction set into [`Statement::DebugPrintf`]. Otherwise, they are
+    /// ignored like any other non-semantic instruction.
+    ///
+    /// [`Statement::DebugPrintf`]: crate::Statement::DebugPrintf
+    pub debug_printf: bool,
 }
 
 impl Default for Options {
@@ -375,6 +417,7 @@ impl Default for Options {
             adjust_coordinate_space: true,
             strict_capabilities: false,
             block_ctx_dump_prefix: None,
+            debug_printf: false,
         }
     }
 }
@@ -559,6 +602,15 @@ struct BlockContext<'function> {
     arguments: &'function [crate::FunctionArgument],
     /// Metadata about the usage of function parameters as sampling objects
     parameter_sampling: &'function mut [image::SamplingFlags],
+
+    /// The blocks parsed so far and how they end, if the function's merge
+    /// annotations can't be followed.
+    ///
+    /// When this is `Some`, [`Frontend::next_block`] gives each block a body
+    /// of its own, and [`structurize`] arranges them once all are parsed.
+    ///
+    /// [`structurize`]: BlockContext::structurize
+    flow: Option<structurize::FlowGraph>,
 }
 
 impl<'a> BlockContext<'a> {
@@ -602,6 +654,9 @@ enum SignAnchor {
 pub struct Frontend<I> {
     data: I,
     data_offset: usize,
+    /// Words already read from `data` that are to be read again, by
+    /// [`Frontend::function_is_structured`].
+    replay: VecDeque<u32>,
     state: ModuleState,
     layouter: Layouter,
     temp_bytes: Vec<u8>,
@@ -656,6 +711,49 @@ pub struct Frontend<I> {
     /// glslang declares those by default even though they are never written to
     /// (see <https://github.com/KhronosGroup/glslang/issues/1868>)
     gl_per_vertex_builtin_access: FastHashSet<crate::BuiltIn>,
+
+    /// The source text recorded by the module's debug instructions.
+    source_map: SourceMap,
+
+    /// Where the lines of each file in [`source_map`] start.
+    ///
+    /// [`source_map`]: Frontend::source_map
+    source_lines: source_map::LineStarts,
+
+    /// Whether spans should be byte ranges in [`source_map`], rather than in
+    /// the SPIR-V binary. See [`Frontend::parse_with_source_map`].
+    ///
+    /// [`source_map`]: Frontend::source_map
+    source_spans: bool,
+
+    /// The text of each `OpString`.
+    debug_strings: FastHashMap<spirv::Word, String>,
+
+    /// The index in [`source_map`] of the file each `OpString` or
+    /// `DebugSource` result refers to, if we have its text.
+    ///
+    /// [`source_map`]: Frontend::source_map
+    debug_files: FastHashMap<spirv::Word, usize>,
+
+    /// The span given by the last `OpLine` or `DebugLine`, while it applies.
+    line_span: Option<crate::Span>,
+
+    /// The id of the `NonSemantic.Shader.DebugInfo.100` instruction set.
+    ext_debug_info_id: Option<spirv::Word>,
+
+    /// The id of the `NonSemantic.DebugPrintf` instruction set.
+    ext_debug_printf_id: Option<spirv::Word>,
+
+    /// The ids of other `NonSemantic.*` instruction sets, whose
+    /// instructions we skip.
+    ext_ignored_ids: FastHashSet<spirv::Word>,
+
+    /// The kinds of the module's global expressions, for evaluating
+    /// `OpSpecConstantOp` instructions.
+    ///
+    /// [`Frontend::append_spec_constant_expression`] brings this up to date
+    /// with any expressions appended to the arena directly.
+    global_expression_kind_tracker: crate::proc::ExpressionKindTracker,
 }
 
 impl<I: Iterator<Item = u32>> Frontend<I> {
@@ -663,6 +761,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Frontend {
             data,
             data_offset: 0,
+            replay: VecDeque::new(),
             state: ModuleState::Empty,
             layouter: Layouter::default(),
             temp_bytes: Vec::new(),
@@ -690,19 +789,43 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             options: options.clone(),
             switch_cases: FastIndexMap::default(),
             gl_per_vertex_builtin_access: FastHashSet::default(),
+            source_map: SourceMap::default(),
+            source_lines: source_map::LineStarts::default(),
+            source_spans: false,
+            debug_strings: FastHashMap::default(),
+            debug_files: FastHashMap::default(),
+            line_span: None,
+            ext_debug_info_id: None,
+            ext_debug_printf_id: None,
+            ext_ignored_ids: FastHashSet::default(),
+            global_expression_kind_tracker: crate::proc::ExpressionKindTracker::new(),
         }
     }
 
     fn span_from(&self, from: usize) -> crate::Span {
+        if self.source_spans {
+            return self.line_span.unwrap_or_default();
+        }
         crate::Span::from(from..self.data_offset)
     }
 
     fn span_from_with_op(&self, from: usize) -> crate::Span {
+        if self.source_spans {
+            return self.line_span.unwrap_or_default();
+        }
         crate::Span::from((from - 4)..self.data_offset)
     }
 
+    /// Return the span of the instruction `inst`, which starts at `start`.
+    fn instruction_span(&self, start: usize, inst: Instruction) -> crate::Span {
+        if self.source_spans {
+            return self.line_span.unwrap_or_default();
+        }
+        crate::Span::from(start..(start + 4 * (inst.wc as usize)))
+    }
+
     fn next(&mut self) -> Result<u32, Error> {
-        if let Some(res) = self.data.next() {
+        if let Some(res) = self.replay.pop_front().or_else(|| self.data.next()) {
             self.data_offset += 4;
             Ok(res)
         } else {
@@ -811,6 +934,8 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             spirv::Decoration::SpecId => {
                 dec.specialization_constant_id = Some(self.next()?);
             }
+            // Naga assumes that physical storage buffer pointers may alias.
+            spirv::Decoration::AliasedPointer | spirv::Decoration::RestrictPointer => {}
             other => {
                 log::warn!("Unknown decoration {:?}", other);
                 for _ in base_words + 1..inst.wc {
@@ -1272,7 +1397,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 let size = match size {
                     crate::ArraySize::Constant(size) => size.get(),
                     // A runtime sized array is not a composite type
-                    crate::ArraySize::Dynamic => {
+                    crate::ArraySize::Pending(_) | crate::ArraySize::Dynamic => {
                         return Err(Error::InvalidAccessType(root_type_id))
                     }
                 };
@@ -1359,7 +1484,21 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         // function's first block's label in advance. Thus, we assume that if
         // this block has no entry in `ctx.body_for_label`, it must be the
         // function's first block. This always has body index zero.
-        let mut body_idx = *ctx.body_for_label.entry(block_id).or_default();
+        //
+        // When we can't follow the function's merge annotations, we don't know
+        // where the block will end up yet, so give it a body of its own. This
+        // makes every use of its values from another block go through a local
+        // variable, which is in scope wherever `structurize` puts that block.
+        let mut body_idx = match ctx.flow {
+            Some(ref mut flow) => {
+                flow.order.push(block_id);
+                let body_idx = ctx.bodies.len();
+                ctx.bodies.push(Body::with_parent(0));
+                ctx.body_for_label.insert(block_id, body_idx);
+                body_idx
+            }
+            None => *ctx.body_for_label.entry(block_id).or_default(),
+        };
 
         // The Naga IR block this call builds. This will end up as
         // `ctx.blocks[&block_id]`, and `ctx.bodies[body_idx]` will refer to it
@@ -1405,17 +1544,15 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             use spirv::Op;
             let start = self.data_offset;
             let inst = self.next_inst()?;
-            let span = crate::Span::from(start..(start + 4 * (inst.wc as usize)));
+            let span = self.instruction_span(start, inst);
             log::debug!("\t\t{:?} [{}]", inst.op, inst.wc);
 
             match inst.op {
-                Op::Line => {
-                    inst.expect(4)?;
-                    let _file_id = self.next()?;
-                    let _row_id = self.next()?;
-                    let _col_id = self.next()?;
+                Op::Line => self.parse_line(inst)?,
+                Op::NoLine => {
+                    inst.expect(1)?;
+                    self.line_span = None;
                 }
-                Op::NoLine => inst.expect(1)?,
                 Op::Undef => {
                     inst.expect(3)?;
                     let type_id = self.next()?;
@@ -1445,7 +1582,18 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                         inst.expect(5)?;
                         let init_id = self.next()?;
                         let lconst = self.lookup_constant.lookup(init_id)?;
-                        Some(ctx.expressions.append(lconst.inner.to_expr(), span))
+                        Some(match lconst.inner {
+                            Constant::Constant(c) => {
+                                ctx.expressions.append(crate::Expression::Constant(c), span)
+                            }
+                            Constant::Override(o) => {
+                                ctx.expressions.append(crate::Expression::Override(o), span)
+                            }
+                            // Already copied into this function by `make_expression_storage`.
+                            Constant::Composite(_) => {
+                                self.lookup_expression.lookup(init_id)?.handle
+                            }
+                        })
                     } else {
                         None
                     };
@@ -1477,7 +1625,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                                 .expressions
                                 .append(crate::Expression::LocalVariable(var_handle), span),
                             type_id: result_type_id,
-                            block_id,
+                            // Like function arguments, local variables are in
+                            // scope throughout the function, so they never need
+                            // to be spilled.
+                            block_id: 0,
                         },
                     );
                     emitter.start(ctx.expressions);
@@ -2040,9 +2191,11 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let result_type_id = self.next()?;
                     let result_id = self.next()?;
                     let pointer_id = self.next()?;
-                    if inst.wc != 4 {
-                        inst.expect(5)?;
-                        let _memory_access = self.next()?;
+                    // Skip the memory operands, along with any operands they
+                    // take, like the alignment of physical storage buffer
+                    // accesses.
+                    for _ in 4..inst.wc {
+                        let _operand = self.next()?;
                     }
 
                     let base_lexp = self.lookup_expression.lookup(pointer_id)?;
@@ -2078,9 +2231,8 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
 
                     let pointer_id = self.next()?;
                     let value_id = self.next()?;
-                    if inst.wc != 3 {
-                        inst.expect(4)?;
-                        let _memory_access = self.next()?;
+                    for _ in 3..inst.wc {
+                        let _operand = self.next()?;
                     }
                     let base_expr = self.lookup_expression.lookup(pointer_id)?;
                     let base_handle = get_expr_handle!(pointer_id, base_expr);
@@ -2960,6 +3112,41 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let result_type_id = self.next()?;
                     let result_id = self.next()?;
                     let set_id = self.next()?;
+                    if Some(set_id) == self.ext_debug_info_id {
+                        self.parse_debug_info_inst(inst, result_id, ctx.gctx())?;
+                        continue;
+                    }
+                    if Some(set_id) == self.ext_debug_printf_id && self.options.debug_printf {
+                        let inst_id = self.next()?;
+                        if inst_id != DEBUG_PRINTF {
+                            return Err(Error::UnsupportedExtInst(inst_id));
+                        }
+                        inst.expect_at_least(base_wc + 1)?;
+                        let format_id = self.next()?;
+                        let format = self
+                            .debug_strings
+                            .get(&format_id)
+                            .cloned()
+                            .ok_or(Error::InvalidId(format_id))?;
+                        let mut arguments = Vec::with_capacity(inst.wc as usize - 6);
+                        for _ in 6..inst.wc {
+                            let arg_id = self.next()?;
+                            let lexp = self.lookup_expression.lookup(arg_id)?;
+                            arguments.push(get_expr_handle!(arg_id, lexp));
+                        }
+                        block.extend(emitter.finish(ctx.expressions));
+                        block.push(crate::Statement::DebugPrintf { format, arguments }, span);
+                        emitter.start(ctx.expressions);
+                        continue;
+                    }
+                    if Some(set_id) == self.ext_debug_printf_id
+                        || self.ext_ignored_ids.contains(&set_id)
+                    {
+                        for _ in 4..inst.wc {
+                            self.next()?;
+                        }
+                        continue;
+                    }
                     if Some(set_id) != self.ext_glsl_id {
                         return Err(Error::UnsupportedExtInstSet(set_id));
                     }
@@ -3187,6 +3374,12 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     inst.expect(2)?;
                     let target_id = self.next()?;
 
+                    if let Some(ref mut flow) = ctx.flow {
+                        flow.terminators
+                            .insert(block_id, structurize::Terminator::Branch(target_id));
+                        break None;
+                    }
+
                     // If this is a branch to a merge or continue block, then
                     // that ends the current body.
                     //
@@ -3255,6 +3448,18 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                         let _ = self.next()?;
                     }
 
+                    if let Some(ref mut flow) = ctx.flow {
+                        flow.terminators.insert(
+                            block_id,
+                            structurize::Terminator::BranchConditional {
+                                condition,
+                                accept: true_target.label_id,
+                                reject: false_target.label_id,
+                            },
+                        );
+                        break None;
+                    }
+
                     // Handle `OpBranchConditional`s used at the end of a loop
                     // body's "continuing" section as a "conditional backedge",
                     // i.e. a `do`-`while` condition, or `break if` in WGSL.
@@ -3398,10 +3603,6 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                             .insert(merge, MergeBlockInformation::SwitchMerge);
                     }
 
-                    let default = ctx.bodies.len();
-                    ctx.bodies.push(Body::with_parent(body_idx));
-                    ctx.body_for_label.entry(default_id).or_insert(default);
-
                     let selector_lexp = &self.lookup_expression[&selector];
                     let selector_lty = self.lookup_type.lookup(selector_lexp.type_id)?;
                     let selector_handle = get_expr_handle!(selector, selector_lexp);
@@ -3427,6 +3628,27 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                         ref other => unimplemented!("Unexpected selector {:?}", other),
                     };
 
+                    if let Some(ref mut flow) = ctx.flow {
+                        let mut cases = Vec::with_capacity((inst.wc as usize - 3) / 2);
+                        for _ in 0..(inst.wc - 3) / 2 {
+                            let literal = self.next()?;
+                            cases.push((literal as i32, self.next()?));
+                        }
+                        flow.terminators.insert(
+                            block_id,
+                            structurize::Terminator::Switch {
+                                selector,
+                                cases,
+                                default: default_id,
+                            },
+                        );
+                        break None;
+                    }
+
+                    let default = ctx.bodies.len();
+                    ctx.bodies.push(Body::with_parent(body_idx));
+                    ctx.body_for_label.entry(default_id).or_insert(default);
+
                     // Clear past switch cases to prevent them from entering this one
                     self.switch_cases.clear();
 
@@ -3504,6 +3726,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let merge_block_id = self.next()?;
                     // TODO: Selection Control Mask
                     let _selection_control = self.next()?;
+                    if ctx.flow.is_some() {
+                        continue;
+                    }
 
                     // Indicate that the merge block is a continuation of the
                     // current `Body`.
@@ -3525,6 +3750,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     for _ in 0..inst.wc - 3 {
                         self.next()?;
                     }
+                    if ctx.flow.is_some() {
+                        continue;
+                    }
 
                     // Indicate that the merge block is a continuation of the
                     // current `Body`.
@@ -3763,7 +3991,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                                 ctx.gctx().global_expressions[ctx.gctx().constants[constant].init],
                                 crate::Expression::Literal(crate::Literal::Bool(true)),
                             ),
-                            Constant::Override(_) => false,
+                            Constant::Override(_) | Constant::Composite(_) => false,
                         })
                         .is_some()
                     {
@@ -4071,11 +4299,17 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
+    /// Create a function's expression arena, with entries for every global
+    /// variable and constant.
+    ///
+    /// [`Constant::Composite`] values are copied into the arena in full. The
+    /// caller must emit those copies at the start of the function's body.
     fn make_expression_storage(
         &mut self,
         globals: &Arena<crate::GlobalVariable>,
         constants: &Arena<crate::Constant>,
         overrides: &Arena<crate::Override>,
+        global_expressions: &Arena<crate::Expression>,
     ) -> Arena<crate::Expression> {
         let mut expressions = Arena::new();
         #[allow(clippy::panic)]
@@ -4099,12 +4333,19 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             );
         }
         // register constants
+        let mut copies = FastHashMap::default();
         for (&id, con) in self.lookup_constant.iter() {
-            let (expr, span) = match con.inner {
-                Constant::Constant(c) => (crate::Expression::Constant(c), constants.get_span(c)),
-                Constant::Override(o) => (crate::Expression::Override(o), overrides.get_span(o)),
+            let handle = match con.inner {
+                Constant::Constant(c) => {
+                    expressions.append(crate::Expression::Constant(c), constants.get_span(c))
+                }
+                Constant::Override(o) => {
+                    expressions.append(crate::Expression::Override(o), overrides.get_span(o))
+                }
+                Constant::Composite(expr) => {
+                    copy_global_expression(global_expressions, expr, &mut expressions, &mut copies)
+                }
             };
-            let handle = expressions.append(expr, span);
             self.lookup_expression.insert(
                 id,
                 LookupExpression {
@@ -4181,7 +4422,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 | S::RayQuery { .. }
                 | S::SubgroupBallot { .. }
                 | S::SubgroupCollectiveOperation { .. }
-                | S::SubgroupGather { .. } => {}
+                | S::SubgroupGather { .. }
+                | S::DebugPrintf { .. }
+                | S::DiagnosticFilter(_) => {}
                 S::Call {
                     function: ref mut callee,
                     ref arguments,
@@ -4256,6 +4499,34 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
     }
 
     pub fn parse(mut self) -> Result<crate::Module, Error> {
+        self.parse_module()
+    }
+
+    /// Parse the module, giving its spans as byte ranges in the high-level
+    /// source text recorded by its debug instructions.
+    ///
+    /// Compilers can record the source a module was built from with
+    /// `OpSource` and `OpSourceContinued`, or with `DebugSource` and
+    /// `DebugSourceContinued` from the `NonSemantic.Shader.DebugInfo.100`
+    /// extended instruction set. `OpLine` and `DebugLine` say which part of
+    /// that source each instruction came from. Errors from validating the
+    /// module can then be shown against the returned [`SourceMap`]'s text.
+    ///
+    /// Anything no line applies to gets [`Span::UNDEFINED`]. If the module
+    /// records no source text, the returned map is `None`, and all spans
+    /// are undefined; use [`parse`] to get spans in the binary instead.
+    ///
+    /// [`Span::UNDEFINED`]: crate::Span::UNDEFINED
+    /// [`parse`]: Frontend::parse
+    pub fn parse_with_source_map(mut self) -> Result<(crate::Module, Option<SourceMap>), Error> {
+        self.source_spans = true;
+        let module = self.parse_module()?;
+        let source_map =
+            Some(mem::take(&mut self.source_map)).filter(|map| !map.files().is_empty());
+        Ok((module, source_map))
+    }
+
+    fn parse_module(&mut self) -> Result<crate::Module, Error> {
         let mut module = {
             if self.next()? != spirv::MAGIC_NUMBER {
                 return Err(Error::InvalidHeader);
@@ -4292,6 +4563,13 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 Op::ExecutionMode => self.parse_execution_mode(inst),
                 Op::String => self.parse_string(inst),
                 Op::Source => self.parse_source(inst),
+                Op::SourceContinued => self.parse_source_continued(inst),
+                Op::Line => self.parse_line(inst),
+                Op::NoLine => {
+                    self.line_span = None;
+                    inst.expect(1)
+                }
+                Op::ExtInst => self.parse_module_ext_inst(inst, &module),
                 Op::SourceExtension => self.parse_source_extension(inst),
                 Op::Name => self.parse_name(inst),
                 Op::MemberName => self.parse_member_name(inst),
@@ -4306,6 +4584,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 Op::TypeMatrix => self.parse_type_matrix(inst, &mut module),
                 Op::TypeFunction => self.parse_type_function(inst),
                 Op::TypePointer => self.parse_type_pointer(inst, &mut module),
+                Op::TypeForwardPointer => self.parse_type_forward_pointer(inst),
                 Op::TypeArray => self.parse_type_array(inst, &mut module),
                 Op::TypeRuntimeArray => self.parse_type_runtime_array(inst, &mut module),
                 Op::TypeStruct => self.parse_type_struct(inst, &mut module),
@@ -4323,6 +4602,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 Op::ConstantFalse | Op::SpecConstantFalse => {
                     self.parse_bool_constant(inst, false, &mut module)
                 }
+                Op::SpecConstantOp => self.parse_spec_constant_op(inst, &mut module),
                 Op::Variable => self.parse_global_variable(inst, &mut module),
                 Op::Function => {
                     self.switch(ModuleState::Function, inst.op)?;
@@ -4432,10 +4712,15 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         if left != 0 {
             return Err(Error::InvalidOperand);
         }
-        if !SUPPORTED_EXT_SETS.contains(&name.as_str()) {
-            return Err(Error::UnsupportedExtSet(name));
+        match name.as_str() {
+            "GLSL.std.450" => self.ext_glsl_id = Some(result_id),
+            source_map::DEBUG_INFO_EXT_SET => self.ext_debug_info_id = Some(result_id),
+            DEBUG_PRINTF_EXT_SET => self.ext_debug_printf_id = Some(result_id),
+            _ if name.starts_with("NonSemantic.") => {
+                self.ext_ignored_ids.insert(result_id);
+            }
+            _ => return Err(Error::UnsupportedExtSet(name)),
         }
-        self.ext_glsl_id = Some(result_id);
         Ok(())
     }
 
@@ -4529,15 +4814,134 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
     fn parse_string(&mut self, inst: Instruction) -> Result<(), Error> {
         self.switch(ModuleState::Source, inst.op)?;
         inst.expect_at_least(3)?;
-        let _id = self.next()?;
-        let (_name, _) = self.next_string(inst.wc - 2)?;
+        let id = self.next()?;
+        let (string, _) = self.next_string(inst.wc - 2)?;
+        self.debug_strings.insert(id, string);
         Ok(())
     }
 
     fn parse_source(&mut self, inst: Instruction) -> Result<(), Error> {
         self.switch(ModuleState::Source, inst.op)?;
-        for _ in 1..inst.wc {
-            let _ = self.next()?;
+        inst.expect_at_least(3)?;
+        let _language = self.next()?;
+        let _version = self.next()?;
+        if inst.wc > 3 {
+            let file_id = self.next()?;
+            if inst.wc > 4 {
+                let (text, _) = self.next_string(inst.wc - 4)?;
+                let name = self
+                    .debug_strings
+                    .get(&file_id)
+                    .cloned()
+                    .unwrap_or_default();
+                let file = self.source_map.add(name, &text);
+                self.debug_files.insert(file_id, file);
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_source_continued(&mut self, inst: Instruction) -> Result<(), Error> {
+        self.switch(ModuleState::Source, inst.op)?;
+        inst.expect_at_least(2)?;
+        let (text, _) = self.next_string(inst.wc - 1)?;
+        self.source_map.continue_last(&text);
+        Ok(())
+    }
+
+    fn parse_line(&mut self, inst: Instruction) -> Result<(), Error> {
+        inst.expect(4)?;
+        let file_id = self.next()?;
+        let line = self.next()?;
+        let column = self.next()?;
+        self.line_span = self.debug_files.get(&file_id).and_then(|&file| {
+            self.source_lines
+                .span(&self.source_map, file, line..line, column..0)
+        });
+        Ok(())
+    }
+
+    /// Parse an `OpExtInst` outside of any function.
+    fn parse_module_ext_inst(
+        &mut self,
+        inst: Instruction,
+        module: &crate::Module,
+    ) -> Result<(), Error> {
+        inst.expect_at_least(5)?;
+        let _result_type_id = self.next()?;
+        let result_id = self.next()?;
+        let set_id = self.next()?;
+        if Some(set_id) == self.ext_debug_printf_id || self.ext_ignored_ids.contains(&set_id) {
+            for _ in 4..inst.wc {
+                self.next()?;
+            }
+            return Ok(());
+        }
+        if Some(set_id) != self.ext_debug_info_id {
+            return Err(Error::UnsupportedInstruction(self.state, inst.op));
+        }
+        self.parse_debug_info_inst(inst, result_id, module.to_ctx())
+    }
+
+    /// Parse an instruction from the `NonSemantic.Shader.DebugInfo.100`
+    /// extended instruction set, after its result type, result id and set.
+    ///
+    /// Only the instructions recording source text and line numbers are
+    /// used; the rest are skipped.
+    fn parse_debug_info_inst(
+        &mut self,
+        inst: Instruction,
+        result_id: spirv::Word,
+        gctx: crate::proc::GlobalCtx,
+    ) -> Result<(), Error> {
+        let number = self.next()?;
+        let operands = (5..inst.wc)
+            .map(|_| self.next())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (number, operands.as_slice()) {
+            (source_map::DEBUG_SOURCE, &[file_id, ref text @ ..]) => {
+                // `OpSource` may already have given us this file's text.
+                if let Some(&file) = self.debug_files.get(&file_id) {
+                    self.debug_files.insert(result_id, file);
+                } else if let Some(text) = text.first().and_then(|id| self.debug_strings.get(id)) {
+                    let name = self
+                        .debug_strings
+                        .get(&file_id)
+                        .cloned()
+                        .unwrap_or_default();
+                    let file = self.source_map.add(name, &text.clone());
+                    self.debug_files.insert(result_id, file);
+                }
+            }
+            (source_map::DEBUG_SOURCE_CONTINUED, &[text_id]) => {
+                if let Some(text) = self.debug_strings.get(&text_id) {
+                    self.source_map.continue_last(&text.clone());
+                }
+            }
+            (
+                source_map::DEBUG_LINE,
+                &[source_id, line_start, line_end, column_start, column_end],
+            ) => {
+                let value = |id| {
+                    self.lookup_constant
+                        .get(&id)
+                        .and_then(|constant| resolve_constant(gctx, &constant.inner))
+                };
+                let lines = value(line_start).zip(value(line_end));
+                let columns = value(column_start).zip(value(column_end));
+                self.line_span = match (self.debug_files.get(&source_id), lines, columns) {
+                    (Some(&file), Some(lines), Some(columns)) => self.source_lines.span(
+                        &self.source_map,
+                        file,
+                        lines.0..lines.1,
+                        columns.0..columns.1,
+                    ),
+                    _ => None,
+                };
+            }
+            (source_map::DEBUG_NO_LINE, _) => self.line_span = None,
+            _ => {}
         }
         Ok(())
     }
@@ -4817,7 +5221,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         let base_lookup_ty = self.lookup_type.lookup(type_id)?;
         let base_inner = &module.types[base_lookup_ty.handle].inner;
 
-        let space = if let Some(space) = base_inner.pointer_space() {
+        let space = if let Some(space) = base_inner
+            .pointer_space()
+            .filter(|&space| space != crate::AddressSpace::PhysicalStorage)
+        {
             space
         } else if self
             .lookup_storage_buffer_types
@@ -4842,7 +5249,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         } = *base_inner
         {
             match space {
-                crate::AddressSpace::Storage { .. } => {}
+                crate::AddressSpace::Storage { .. } | crate::AddressSpace::PhysicalStorage => {}
                 _ => {
                     return Err(Error::UnsupportedRuntimeArrayStorageClass);
                 }
@@ -4871,6 +5278,21 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
+    /// Parse an `OpTypeForwardPointer`.
+    ///
+    /// Naga types can't refer to themselves, so we only accept forward
+    /// pointers that are defined before anything uses them.
+    fn parse_type_forward_pointer(&mut self, inst: Instruction) -> Result<(), Error> {
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect(3)?;
+        let _pointer_type_id = self.next()?;
+        let storage_class = self.next()?;
+        match map_storage_class(storage_class)? {
+            ExtendedClass::Global(crate::AddressSpace::PhysicalStorage) => Ok(()),
+            _ => Err(Error::UnsupportedStorageClass(storage_class)),
+        }
+    }
+
     fn parse_type_array(
         &mut self,
         inst: Instruction,
@@ -5285,6 +5707,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             }) => {
                 let low = self.next()?;
                 match width {
+                    // https://registry.khronos.org/SPIR-V/specs/unified1/SPIRV.html#Literal
+                    // If a numeric type’s bit width is less than 32-bits, the value appears in the low-order bits of the word.
+                    2 => crate::Literal::F16(half::f16::from_bits(low as u16)),
                     4 => crate::Literal::F32(f32::from_bits(low)),
                     8 => {
                         inst.expect(5)?;
@@ -5328,9 +5753,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             let component_id = self.next()?;
             let span = self.span_from_with_op(start);
             let constant = self.lookup_constant.lookup(component_id)?;
-            let expr = module
-                .global_expressions
-                .append(constant.inner.to_expr(), span);
+            let expr = constant
+                .inner
+                .to_global_expr(&mut module.global_expressions, span);
             components.push(expr);
         }
 
@@ -5389,6 +5814,212 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         self.insert_parsed_constant(module, id, type_id, ty, init, span)
     }
 
+    /// Parse an `OpSpecConstantOp`, whose value is computed from other
+    /// constants and specialization constants.
+    ///
+    /// This supports the operations SPIR-V allows with the `Shader`
+    /// capability, except `CompositeInsert` and `QuantizeToF16`, plus
+    /// floating-point arithmetic.
+    fn parse_spec_constant_op(
+        &mut self,
+        inst: Instruction,
+        module: &mut crate::Module,
+    ) -> Result<(), Error> {
+        use crate::{BinaryOperator as Bo, Expression as E, UnaryOperator as Uo};
+        use spirv::Op;
+
+        let start = self.data_offset;
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect_at_least(5)?;
+        let type_id = self.next()?;
+        let id = self.next()?;
+        let opcode = self.next()?;
+        let op = Op::from_u32(opcode).ok_or(Error::UnknownInstruction(opcode as u16))?;
+        let span = self.span_from_with_op(start);
+
+        let ty = self.lookup_type.lookup(type_id)?.handle;
+
+        let init = match op {
+            Op::SConvert | Op::UConvert | Op::FConvert => {
+                inst.expect(5)?;
+                let scalar = module.types[ty]
+                    .inner
+                    .scalar()
+                    .ok_or(Error::UnsupportedType(ty))?;
+                let (expr, _) = self.next_spec_constant_operand(module, span)?;
+                E::As {
+                    expr,
+                    kind: scalar.kind,
+                    convert: Some(scalar.width),
+                }
+            }
+            Op::SNegate | Op::FNegate | Op::Not | Op::LogicalNot => {
+                inst.expect(5)?;
+                let (expr, _) = self.next_spec_constant_operand(module, span)?;
+                let op = match op {
+                    Op::Not => Uo::BitwiseNot,
+                    Op::LogicalNot => Uo::LogicalNot,
+                    _ => Uo::Negate,
+                };
+                E::Unary { op, expr }
+            }
+            Op::ShiftRightLogical | Op::ShiftRightArithmetic | Op::ShiftLeftLogical => {
+                inst.expect(6)?;
+                let (left, _) = self.next_spec_constant_operand(module, span)?;
+                let (mut right, right_ty) = self.next_spec_constant_operand(module, span)?;
+                // Naga wants an unsigned shift. Constant evaluation doesn't
+                // support bitcasts, so convert the value; shift amounts are
+                // never negative anyway.
+                if let Some(scalar) = module.types[right_ty].inner.scalar() {
+                    if scalar.kind == crate::ScalarKind::Sint {
+                        right = self.append_spec_constant_expression(
+                            module,
+                            E::As {
+                                expr: right,
+                                kind: crate::ScalarKind::Uint,
+                                convert: Some(scalar.width),
+                            },
+                            span,
+                        )?;
+                    }
+                }
+                let op = match op {
+                    Op::ShiftLeftLogical => Bo::ShiftLeft,
+                    _ => Bo::ShiftRight,
+                };
+                E::Binary { op, left, right }
+            }
+            Op::IAdd
+            | Op::ISub
+            | Op::IMul
+            | Op::UDiv
+            | Op::SDiv
+            | Op::UMod
+            | Op::SRem
+            | Op::FAdd
+            | Op::FSub
+            | Op::FMul
+            | Op::FDiv
+            | Op::BitwiseOr
+            | Op::BitwiseXor
+            | Op::BitwiseAnd
+            | Op::LogicalOr
+            | Op::LogicalAnd
+            | Op::LogicalEqual
+            | Op::LogicalNotEqual
+            | Op::IEqual
+            | Op::INotEqual
+            | Op::ULessThan
+            | Op::SLessThan
+            | Op::UGreaterThan
+            | Op::SGreaterThan
+            | Op::ULessThanEqual
+            | Op::SLessThanEqual
+            | Op::UGreaterThanEqual
+            | Op::SGreaterThanEqual => {
+                inst.expect(6)?;
+                let op = match op {
+                    Op::UMod => Bo::Modulo,
+                    Op::LogicalOr => Bo::LogicalOr,
+                    Op::LogicalAnd => Bo::LogicalAnd,
+                    _ => map_binary_operator(op)?,
+                };
+                let (left, _) = self.next_spec_constant_operand(module, span)?;
+                let (right, _) = self.next_spec_constant_operand(module, span)?;
+                E::Binary { op, left, right }
+            }
+            Op::Select => {
+                inst.expect(7)?;
+                let (condition, _) = self.next_spec_constant_operand(module, span)?;
+                let (accept, _) = self.next_spec_constant_operand(module, span)?;
+                let (reject, _) = self.next_spec_constant_operand(module, span)?;
+                E::Select {
+                    condition,
+                    accept,
+                    reject,
+                }
+            }
+            Op::CompositeExtract => {
+                let index_count = inst.expect_at_least(6)? + 1;
+                let (mut base, _) = self.next_spec_constant_operand(module, span)?;
+                for _ in 1..index_count {
+                    let index = self.next()?;
+                    base = self.append_spec_constant_expression(
+                        module,
+                        E::AccessIndex { base, index },
+                        span,
+                    )?;
+                }
+                E::AccessIndex {
+                    base,
+                    index: self.next()?,
+                }
+            }
+            Op::VectorShuffle => {
+                let component_count = inst.expect_at_least(7)? + 1;
+                let (v1, v1_ty) = self.next_spec_constant_operand(module, span)?;
+                let (v2, _) = self.next_spec_constant_operand(module, span)?;
+                let v1_size = match module.types[v1_ty].inner {
+                    crate::TypeInner::Vector { size, .. } => size as u32,
+                    _ => return Err(Error::InvalidInnerType(type_id)),
+                };
+                let mut components = Vec::with_capacity(component_count as usize);
+                for _ in 0..component_count {
+                    let index = self.next()?;
+                    // An index of 0xFFFFFFFF means the component is undefined.
+                    let expr = if index < v1_size || index == !0 {
+                        E::AccessIndex {
+                            base: v1,
+                            index: index.min(v1_size - 1),
+                        }
+                    } else {
+                        E::AccessIndex {
+                            base: v2,
+                            index: index - v1_size,
+                        }
+                    };
+                    components.push(self.append_spec_constant_expression(module, expr, span)?);
+                }
+                E::Compose { ty, components }
+            }
+            _ => return Err(Error::UnsupportedSpecConstantOp(op)),
+        };
+
+        let init = self.append_spec_constant_expression(module, init, span)?;
+        self.insert_parsed_constant(module, id, type_id, ty, init, span)
+    }
+
+    /// Add `expr` to `module`'s global expressions, evaluating it if it does
+    /// not depend on any overrides.
+    fn append_spec_constant_expression(
+        &mut self,
+        module: &mut crate::Module,
+        expr: crate::Expression,
+        span: crate::Span,
+    ) -> Result<Handle<crate::Expression>, Error> {
+        let tracker = &mut self.global_expression_kind_tracker;
+        tracker.extend_from_arena(&module.global_expressions);
+        crate::proc::ConstantEvaluator::for_wgsl_module(module, tracker, true)
+            .try_eval_and_append(expr, span)
+            .map_err(Error::SpecConstantEvaluation)
+    }
+
+    /// Read a constant id operand of an `OpSpecConstantOp`, and return a
+    /// global expression for its value along with its type.
+    fn next_spec_constant_operand(
+        &mut self,
+        module: &mut crate::Module,
+        span: crate::Span,
+    ) -> Result<(Handle<crate::Expression>, Handle<crate::Type>), Error> {
+        let id = self.next()?;
+        let constant = self.lookup_constant.lookup(id)?;
+        let ty = self.lookup_type.lookup(constant.type_id)?.handle;
+        let expr = constant
+            .inner
+            .to_global_expr(&mut module.global_expressions, span);
+        Ok((expr, ty))
+    }
+
     fn insert_parsed_constant(
         &mut self,
         module: &mut crate::Module,
@@ -5408,6 +6039,21 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 init: Some(init),
             };
             Constant::Override(module.overrides.append(o, span))
+        } else if depends_on_override(&module.global_expressions, init) {
+            match module.types[ty].inner {
+                crate::TypeInner::Scalar(_) => {
+                    // An override needs a name or an id, and this has no
+                    // `SpecId` of its own.
+                    let o = crate::Override {
+                        name: Some(decor.name.unwrap_or_else(|| format!("spec_constant_{id}"))),
+                        id: None,
+                        ty,
+                        init: Some(init),
+                    };
+                    Constant::Override(module.overrides.append(o, span))
+                }
+                _ => Constant::Composite(init),
+            }
         } else {
             let c = crate::Constant {
                 name: decor.name,
@@ -5439,9 +6085,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             let init_id = self.next()?;
             let span = self.span_from_with_op(start);
             let lconst = self.lookup_constant.lookup(init_id)?;
-            let expr = module
-                .global_expressions
-                .append(lconst.inner.to_expr(), span);
+            let expr = lconst
+                .inner
+                .to_global_expr(&mut module.global_expressions, span);
             Some(expr)
         } else {
             None
@@ -5656,10 +6302,121 @@ fn make_index_literal(
     Ok(expr)
 }
 
+/// Return true if the global expression `expr` refers to an override.
+fn depends_on_override(
+    global_expressions: &Arena<crate::Expression>,
+    expr: Handle<crate::Expression>,
+) -> bool {
+    use crate::Expression as E;
+
+    match global_expressions[expr] {
+        E::Override(_) => true,
+        E::Compose { ref components, .. } => components
+            .iter()
+            .any(|&component| depends_on_override(global_expressions, component)),
+        E::Splat { value: expr, .. }
+        | E::Swizzle { vector: expr, .. }
+        | E::AccessIndex { base: expr, .. }
+        | E::Unary { expr, .. }
+        | E::As { expr, .. } => depends_on_override(global_expressions, expr),
+        E::Binary { left, right, .. } => {
+            depends_on_override(global_expressions, left)
+                || depends_on_override(global_expressions, right)
+        }
+        E::Select {
+            condition,
+            accept,
+            reject,
+        } => [condition, accept, reject]
+            .into_iter()
+            .any(|expr| depends_on_override(global_expressions, expr)),
+        _ => false,
+    }
+}
+
+/// Copy the global expression `expr`, and those it refers to, into a
+/// function's `expressions`.
+///
+/// `copies` maps global expressions to the copies made so far, so that
+/// shared subexpressions are only copied once.
+fn copy_global_expression(
+    global_expressions: &Arena<crate::Expression>,
+    expr: Handle<crate::Expression>,
+    expressions: &mut Arena<crate::Expression>,
+    copies: &mut FastHashMap<Handle<crate::Expression>, Handle<crate::Expression>>,
+) -> Handle<crate::Expression> {
+    use crate::Expression as E;
+
+    if let Some(&copy) = copies.get(&expr) {
+        return copy;
+    }
+    let mut copy = |expr| copy_global_expression(global_expressions, expr, expressions, copies);
+    let new = match global_expressions[expr] {
+        ref leaf @ (E::Literal(_) | E::Constant(_) | E::Override(_) | E::ZeroValue(_)) => {
+            leaf.clone()
+        }
+        E::Compose { ty, ref components } => E::Compose {
+            ty,
+            components: components
+                .iter()
+                .map(|&component| copy(component))
+                .collect(),
+        },
+        E::Splat { size, value } => E::Splat {
+            size,
+            value: copy(value),
+        },
+        E::Swizzle {
+            size,
+            vector,
+            pattern,
+        } => E::Swizzle {
+            size,
+            vector: copy(vector),
+            pattern,
+        },
+        E::AccessIndex { base, index } => E::AccessIndex {
+            base: copy(base),
+            index,
+        },
+        E::Unary { op, expr } => E::Unary {
+            op,
+            expr: copy(expr),
+        },
+        E::Binary { op, left, right } => E::Binary {
+            op,
+            left: copy(left),
+            right: copy(right),
+        },
+        E::Select {
+            condition,
+            accept,
+            reject,
+        } => E::Select {
+            condition: copy(condition),
+            accept: copy(accept),
+            reject: copy(reject),
+        },
+        E::As {
+            expr,
+            kind,
+            convert,
+        } => E::As {
+            expr: copy(expr),
+            kind,
+            convert,
+        },
+        ref other => unreachable!("Unexpected specialization constant expression {:?}", other),
+    };
+    let handle = expressions.append(new, global_expressions.get_span(expr));
+    copies.insert(expr, handle);
+    handle
+}
+
 fn resolve_constant(gctx: crate::proc::GlobalCtx, constant: &Constant) -> Option<u32> {
     let constant = match *constant {
         Constant::Constant(constant) => constant,
-        Constant::Override(_) => return None,
+        Constant::Override(_) | Constant::Composite(_) => return None,
     };
     match gctx.global_expressions[gctx.constants[constant].init] {
         crate::Expression::Literal(crate::Literal::U32(id)) => Some(id),
@@ -5679,6 +6436,22 @@ pub fn parse_u8_slice(data: &[u8], options: &Options) -> Result<crate::Module, E
     Frontend::new(words, options).parse()
 }
 
+/// Like [`parse_u8_slice`], but with spans in the high-level source recorded
+/// in the module's debug info. See [`Frontend::parse_with_source_map`].
+pub fn parse_u8_slice_with_source_map(
+    data: &[u8],
+    options: &Options,
+) -> Result<(crate::Module, Option<SourceMap>), Error> {
+    if data.len() % 4 != 0 {
+        return Err(Error::IncompleteData);
+    }
+
+    let words = data
+        .chunks(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
+    Frontend::new(words, options).parse_with_source_map()
+}
+
 /// Helper function to check if `child` is in the scope of `parent`
 fn is_parent(mut child: usize, parent: usize, block_ctx: &BlockContext) -> bool {
     loop {
@@ -5746,4 +6519,83 @@ mod test {
             panic!("invalid generated wgsl");
         }
     }
+
+    #[test]
+    fn debug_info_source_map() {
+        let bytes = include_bytes!("../../../tests/in/spv/debug-info.spv");
+        let (m, source_map) =
+            super::parse_u8_slice_with_source_map(bytes, &Default::default()).unwrap();
+        let source_map = source_map.unwrap();
+        assert_eq!(source_map.files().len(), 1);
+        let file = &source_map.files()[0];
+        assert_eq!(file.name(), "debug-info.comp");
+        assert_eq!(
+            source_map.source(file),
+            include_str!("../../../tests/in/spv/debug-info.comp")
+        );
+
+        let text = |span: crate::Span| {
+            let (index, span) = source_map.file_for_span(span).unwrap();
+            assert_eq!(index, 0);
+            &source_map.source(file)[span]
+        };
+        let (_, fun) = m.functions.iter().next().unwrap();
+        let store = fun
+            .body
+            .span_iter()
+            .find(|&(statement, _)| matches!(*statement, crate::Statement::Store { .. }))
+            .unwrap();
+        assert_eq!(text(*store.1), "data.a = sqrt(data.b)");
+        let (math, _) = fun
+            .expressions
+            .iter()
+            .find(|&(_, expr)| matches!(*expr, crate::Expression::Math { .. }))
+            .unwrap();
+        assert_eq!(text(fun.expressions.get_span(math)), "sqrt(data.b)");
+    }
+
+    #[cfg(all(feature = "wgsl-in", feature = "spv-out"))]
+    #[test]
+    fn op_line_source_map() {
+        let source = "\
+@group(0) @binding(0) var<storage, read_write> x: f32;
+
+@compute @workgroup_size(1)
+fn main() {
+    x = sqrt(x);
+}
+";
+        let module = crate::front::wgsl::parse_str(source).unwrap();
+        let info = crate::valid::Validator::new(Default::default(), Default::default())
+            .validate(&module)
+            .unwrap();
+        let options = crate::back::spv::Options {
+            flags: crate::back::spv::WriterFlags::DEBUG,
+            debug_info: Some(crate::back::spv::DebugInfo {
+                source_code: source,
+                file_name: "op-line.wgsl".as_ref(),
+            }),
+            ..Default::default()
+        };
+        let words = crate::back::spv::write_vec(&module, &info, &options, None).unwrap();
+        let bytes = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let (m, source_map) =
+            super::parse_u8_slice_with_source_map(&bytes, &Default::default()).unwrap();
+        let source_map = source_map.unwrap();
+        assert_eq!(source_map.text(), source);
+        assert_eq!(source_map.files()[0].name(), "op-line.wgsl");
+
+        let (_, fun) = m.functions.iter().next().unwrap();
+        let (_, &span) = fun
+            .body
+            .span_iter()
+            .find(|&(statement, _)| matches!(*statement, crate::Statement::Store { .. }))
+            .unwrap();
+        // `OpLine` has no end column, so the span runs to the end of the line.
+        assert_eq!(&source[span], "x = sqrt(x);");
+    }
 }
@@ -0,0 +1,102 @@
+/*!
+High-level source text recovered from SPIR-V debug instructions.
+
+Compilers can record the source a SPIR-V module was built from with
+`OpSource` and `OpSourceContinued`, or with the `DebugSource` and
+`DebugSourceContinued` instructions of the
+`NonSemantic.Shader.DebugInfo.100` extended instruction set. `OpLine` and
+`DebugLine` then say which part of that source each instruction came from.
+
+[`Frontend::parse_with_source_map`] collects the source files into a
+[`SourceMap`], and gives the module's [`Span`]s as byte ranges in its
+[`text`], rather than in the SPIR-V binary.
+
+[`Frontend::parse_with_source_map`]: super::Frontend::parse_with_source_map
+[`text`]: SourceMap::text
+*/
+
+use crate::{SourceMap, Span};
+use std::ops::Range;
+
+/// The offset of the start of each line of each file in a [`SourceMap`].
+///
+/// This is computed when first needed, and again if text has been added to
+/// the map since.
+#[derive(Debug, Default)]
+pub(super) struct LineStarts {
+    /// The length of the map's text when `starts` was computed.
+    text_len: usize,
+    starts: Vec<Vec<usize>>,
+}
+
+impl LineStarts {
+    /// Return the span of the given lines and columns of `file` in `map`.
+    ///
+    /// Lines and columns count from one. A start column of zero means the
+    /// start of the first line; an end column of zero, or one not after the
+    /// start, means the end of the last. Positions past the end of a line
+    /// are clamped to it.
+    pub(super) fn span(
+        &mut self,
+        map: &SourceMap,
+        file: usize,
+        lines: Range<u32>,
+        columns: Range<u32>,
+    ) -> Option<Span> {
+        if self.starts.len() != map.files().len() || self.text_len != map.text().len() {
+            self.text_len = map.text().len();
+            self.starts = map
+                .files()
+                .iter()
+                .map(|file| {
+                    let start = file.range().start;
+                    std::iter::once(start)
+                        .chain(
+                            map.source(file)
+                                .match_indices('\n')
+                                .map(|(i, _)| start + i + 1),
+                        )
+                        .collect()
+                })
+                .collect();
+        }
+
+        let file_end = map.files().get(file)?.range().end;
+        let line_starts = &self.starts[file];
+        let line = |line: u32| -> Option<Range<usize>> {
+            let index = (line as usize).checked_sub(1)?;
+            let start = *line_starts.get(index)?;
+            let end = line_starts
+                .get(index + 1)
+                .map_or(file_end, |&next| next - 1);
+            Some(start..end)
+        };
+
+        let first = line(lines.start)?;
+        let last = line(lines.end.max(lines.start)).unwrap_or_else(|| first.clone());
+        let start = match columns.start {
+            0 => first.start,
+            column => (first.start + column as usize - 1).min(first.end),
+        };
+        let end = match columns.end {
+            column if column > 0 => (last.start + column as usize - 1).min(last.end),
+            _ => last.end,
+        };
+        // Without a usable end column, run to the end of the last line.
+        let end = if end > start {
+            end
+        } else {
+            last.end.max(start)
+        };
+        Some(Span::from(start..end))
+    }
+}
+
+/// The `NonSemantic.Shader.DebugInfo.100` extended instruction set.
+pub(super) const DEBUG_INFO_EXT_SET: &str = "NonSemantic.Shader.DebugInfo.100";
+
+// Instruction numbers in `NonSemantic.Shader.DebugInfo.100`.
+pub(super) const DEBUG_SOURCE: spirv::Word = 35;
+pub(super) const DEBUG_SOURCE_CONTINUED: spirv::Word = 102;
+pub(super) const DEBUG_LINE: spirv::Word = 103;
+pub(super) const DEBUG_NO_LINE: spirv::Word = 104;
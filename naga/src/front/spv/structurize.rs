@@ -0,0 +1,793 @@
+/*!
+Structured control flow for SPIR-V functions without usable merge annotations.
+
+[`Frontend::next_block`] builds Naga's `Statement` tree by following the
+`OpSelectionMerge` and `OpLoopMerge` annotations that SPIR-V's structured
+control flow rules require. Optimizers and other producers don't always
+leave those in a state we can follow: inlining can add branches that skip
+past merge blocks, and some modules have loops with several exits, or no
+annotations at all.
+
+For such functions, [`Frontend::function_is_structured`] returns `false`,
+and we parse each block on its own, recording how it ends in a
+[`FlowGraph`]. Then [`BlockContext::structurize`] arranges the blocks into
+`Loop`, `If` and `Switch` statements, following Norman Ramsey's "Beyond
+Relooper" algorithm, which handles any reducible control flow graph:
+
+- A block targeted by a back edge is a loop header. Its code, and the code
+  of every block it dominates, goes in a `Loop`, and branches back to it
+  become `Continue` statements.
+
+- A block with more than one forward predecessor is a merge node. It comes
+  after a default-only `Switch` holding the code of its immediate dominator,
+  so that branches to it become `Break` statements.
+
+- Any other block has a single predecessor, whose branch to it is replaced
+  by its code.
+
+Naga's `Break` and `Continue` only affect the innermost loop or switch, so
+a branch that must leave several of them at once stores its destination in
+a local variable and breaks out of one at a time, checking the variable
+after each.
+
+[`Frontend::next_block`]: super::Frontend::next_block
+[`Frontend::function_is_structured`]: super::Frontend::function_is_structured
+*/
+
+use super::{BlockContext, Body, BodyFragment, BodyIndex, Error};
+use crate::arena::Handle;
+use crate::{FastHashMap, FastHashSet};
+
+use petgraph::graphmap::DiGraphMap;
+
+/// How a SPIR-V block ends, when parsed without following merge annotations.
+#[derive(Debug)]
+pub(super) enum Terminator {
+    Branch(spirv::Word),
+    BranchConditional {
+        condition: Handle<crate::Expression>,
+        accept: spirv::Word,
+        reject: spirv::Word,
+    },
+    Switch {
+        selector: Handle<crate::Expression>,
+        cases: Vec<(i32, spirv::Word)>,
+        default: spirv::Word,
+    },
+}
+
+impl Terminator {
+    /// The distinct blocks this terminator may branch to.
+    fn successors(&self) -> Vec<spirv::Word> {
+        let mut successors = Vec::new();
+        let mut add = |target| {
+            if !successors.contains(&target) {
+                successors.push(target);
+            }
+        };
+        match *self {
+            Terminator::Branch(target) => add(target),
+            Terminator::BranchConditional { accept, reject, .. } => {
+                add(accept);
+                add(reject);
+            }
+            Terminator::Switch {
+                ref cases, default, ..
+            } => {
+                for &(_, target) in cases.iter() {
+                    add(target);
+                }
+                add(default);
+            }
+        }
+        successors
+    }
+}
+
+/// The blocks of a function parsed without following merge annotations.
+#[derive(Debug, Default)]
+pub(super) struct FlowGraph {
+    /// The labels of the function's blocks, in the order they appear.
+    pub(super) order: Vec<spirv::Word>,
+
+    /// How each block ends.
+    ///
+    /// Blocks ending in `OpReturn`, `OpKill` or the like have no entry here:
+    /// their terminator is already the last statement in their block.
+    pub(super) terminators: FastHashMap<spirv::Word, Terminator>,
+}
+
+/// What [`Frontend::function_is_structured`] learns about a block.
+///
+/// [`Frontend::function_is_structured`]: super::Frontend::function_is_structured
+#[derive(Default)]
+struct ScannedBlock {
+    successors: Vec<spirv::Word>,
+    has_merge: bool,
+}
+
+impl<I: Iterator<Item = u32>> super::Frontend<I> {
+    /// Read ahead through the body of the function being parsed, and decide
+    /// whether its merge annotations describe its control flow well enough
+    /// for [`next_block`] to follow them.
+    ///
+    /// The words read are queued up to be read again.
+    ///
+    /// [`next_block`]: super::Frontend::next_block
+    pub(super) fn function_is_structured(&mut self) -> Result<bool, Error> {
+        use spirv::Op;
+
+        let start = self.data_offset;
+        let mut words = Vec::new();
+        let mut blocks: Vec<(spirv::Word, ScannedBlock)> = Vec::new();
+        // Blocks that other blocks may branch to without ending a body of
+        // their own: merge blocks, continue targets and switch cases.
+        let mut merge_targets = FastHashSet::default();
+        let mut loop_headers = FastHashSet::default();
+
+        loop {
+            let first = self.next()?;
+            let wc = (first >> 16) as usize;
+            if wc == 0 {
+                return Err(Error::InvalidWordCount);
+            }
+            let begin = words.len();
+            words.push(first);
+            for _ in 1..wc {
+                words.push(self.next()?);
+            }
+            let operands = &words[begin + 1..];
+
+            match (Op::from_u32(first & 0xffff), blocks.last_mut()) {
+                (Some(Op::FunctionEnd), _) => break,
+                (Some(Op::Label), _) => {
+                    let &label = operands.first().ok_or(Error::InvalidOperand)?;
+                    blocks.push((label, ScannedBlock::default()));
+                }
+                (Some(Op::SelectionMerge), Some(&mut (_, ref mut block))) => {
+                    merge_targets.extend(operands.first());
+                    block.has_merge = true;
+                }
+                (Some(Op::LoopMerge), Some(&mut (label, ref mut block))) => {
+                    merge_targets.extend(operands.iter().take(2));
+                    loop_headers.insert(label);
+                    block.has_merge = true;
+                }
+                (Some(Op::Branch), Some(&mut (_, ref mut block))) => {
+                    block.successors.extend(operands.first());
+                }
+                (Some(Op::BranchConditional), Some(&mut (_, ref mut block))) => {
+                    for &target in operands.iter().skip(1).take(2) {
+                        if !block.successors.contains(&target) {
+                            block.successors.push(target);
+                        }
+                    }
+                }
+                (Some(Op::Switch), Some(&mut (_, ref mut block))) => {
+                    let targets = operands.iter().skip(1).take(1);
+                    let cases = operands.iter().skip(3).step_by(2);
+                    for &target in targets.chain(cases) {
+                        merge_targets.insert(target);
+                        if !block.successors.contains(&target) {
+                            block.successors.push(target);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.data_offset = start;
+        self.replay.extend(words);
+
+        let entry = match blocks.first() {
+            Some(&(label, _)) => label,
+            None => return Ok(true),
+        };
+        let index: FastHashMap<_, _> = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, &(label, _))| (label, index))
+            .collect();
+
+        // A selection without a merge annotation must be a branch out of
+        // some enclosing construct.
+        let unannotated_selection = blocks.iter().any(|&(_, ref block)| {
+            block.successors.len() > 1
+                && !block.has_merge
+                && !block
+                    .successors
+                    .iter()
+                    .any(|target| merge_targets.contains(target))
+        });
+        if unannotated_selection {
+            return Ok(false);
+        }
+
+        // Walk the graph depth-first. Every edge back to a block still on the
+        // stack must go to a loop header, and every block with several
+        // predecessors must be the target of some merge annotation.
+        let mut on_stack = vec![false; blocks.len()];
+        let mut visited = vec![false; blocks.len()];
+        let mut predecessors = vec![FastHashSet::default(); blocks.len()];
+        let mut stack = vec![(index[&entry], 0)];
+        visited[index[&entry]] = true;
+        on_stack[index[&entry]] = true;
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            let Some(&target) = blocks[node].1.successors.get(*next) else {
+                on_stack[node] = false;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let Some(&target_index) = index.get(&target) else {
+                continue;
+            };
+            if on_stack[target_index] {
+                if !loop_headers.contains(&target) {
+                    return Ok(false);
+                }
+                continue;
+            }
+            predecessors[target_index].insert(node);
+            if !visited[target_index] {
+                visited[target_index] = true;
+                on_stack[target_index] = true;
+                stack.push((target_index, 0));
+            }
+        }
+
+        Ok(blocks
+            .iter()
+            .zip(predecessors.iter())
+            .all(|(&(label, _), predecessors)| {
+                predecessors.len() < 2 || merge_targets.contains(&label)
+            }))
+    }
+}
+
+/// A destination for a branch that isn't simply replaced by the target's
+/// code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Jump {
+    /// A branch to a merge node, which follows some enclosing construct.
+    To(spirv::Word),
+
+    /// A branch back to the header of an enclosing loop.
+    Continue(spirv::Word),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ConstructKind {
+    If,
+
+    /// A default-only `Switch`, followed by the code of the given merge node.
+    Block(spirv::Word),
+
+    /// A `Loop` headed by the given block.
+    Loop(spirv::Word),
+
+    /// A `Switch` translating an `OpSwitch`.
+    Switch,
+}
+
+/// A statement enclosing the code being generated.
+struct Construct {
+    kind: ConstructKind,
+
+    /// Whether a `Break` statement leaves this construct.
+    broken: bool,
+
+    /// Jumps that break out of this construct to go further.
+    escapes: Vec<Jump>,
+}
+
+impl Construct {
+    const fn new(kind: ConstructKind) -> Self {
+        Construct {
+            kind,
+            broken: false,
+            escapes: Vec::new(),
+        }
+    }
+}
+
+struct Structurizer<'a, 'function> {
+    ctx: &'a mut BlockContext<'function>,
+    flow: &'a FlowGraph,
+
+    /// Each reachable block's position in reverse postorder.
+    rpo: FastHashMap<spirv::Word, usize>,
+
+    /// Blocks targeted by back edges.
+    loop_headers: FastHashSet<spirv::Word>,
+
+    /// Blocks with more than one forward predecessor.
+    merge_nodes: FastHashSet<spirv::Word>,
+
+    /// The children of each block in the dominator tree that are merge
+    /// nodes, latest in reverse postorder first.
+    merge_children: FastHashMap<spirv::Word, Vec<spirv::Word>>,
+
+    /// The statements enclosing the code being generated, innermost last.
+    constructs: Vec<Construct>,
+
+    /// The type of [`destination`].
+    ///
+    /// [`destination`]: Structurizer::destination
+    u32_type: Handle<crate::Type>,
+
+    /// The local variable holding the destination of a branch that leaves
+    /// several constructs, if we've needed one.
+    destination: Option<Handle<crate::LocalVariable>>,
+
+    /// The value [`destination`] holds for each such branch. Zero means
+    /// there is no such branch in progress.
+    ///
+    /// [`destination`]: Structurizer::destination
+    destination_values: FastHashMap<Jump, u32>,
+
+    /// The id to give the next block of statements we generate.
+    next_block_id: spirv::Word,
+}
+
+impl BlockContext<'_> {
+    /// Arrange the blocks of a function parsed without following its merge
+    /// annotations into a `Statement` tree, built in [`bodies`] from body 0.
+    ///
+    /// `u32_type` is used for the local variable that guides branches out of
+    /// several constructs at once, if any are needed.
+    ///
+    /// [`bodies`]: BlockContext::bodies
+    pub(super) fn structurize(
+        &mut self,
+        flow: &FlowGraph,
+        u32_type: Handle<crate::Type>,
+    ) -> Result<(), Error> {
+        let entry = match flow.order.first() {
+            Some(&entry) => entry,
+            None => return Ok(()),
+        };
+
+        let mut graph = DiGraphMap::<spirv::Word, ()>::new();
+        for &label in flow.order.iter() {
+            graph.add_node(label);
+        }
+        for (&label, terminator) in flow.terminators.iter() {
+            for target in terminator.successors() {
+                if !graph.contains_node(target) {
+                    return Err(Error::InvalidTerminator);
+                }
+                graph.add_edge(label, target, ());
+            }
+        }
+
+        let mut postorder = Vec::new();
+        let mut dfs = petgraph::visit::DfsPostOrder::new(&graph, entry);
+        while let Some(label) = dfs.next(&graph) {
+            postorder.push(label);
+        }
+        let rpo: FastHashMap<_, _> = postorder
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(position, &label)| (label, position))
+            .collect();
+        let dominators = petgraph::algo::dominators::simple_fast(&graph, entry);
+
+        let mut loop_headers = FastHashSet::default();
+        let mut forward_predecessors = FastHashMap::<_, usize>::default();
+        for &label in postorder.iter() {
+            for target in graph.neighbors(label) {
+                if rpo[&target] > rpo[&label] {
+                    *forward_predecessors.entry(target).or_default() += 1;
+                    continue;
+                }
+                // An edge back to a block that doesn't dominate its source
+                // means the loop can be entered in more than one place.
+                let is_back_edge = dominators
+                    .dominators(label)
+                    .is_some_and(|mut dominators| dominators.any(|d| d == target));
+                if !is_back_edge {
+                    return Err(Error::UnsupportedControlFlow(target));
+                }
+                loop_headers.insert(target);
+            }
+        }
+        let merge_nodes: FastHashSet<_> = forward_predecessors
+            .into_iter()
+            .filter_map(|(label, count)| (count > 1).then_some(label))
+            .collect();
+
+        let mut merge_children = FastHashMap::<_, Vec<_>>::default();
+        for &label in postorder.iter() {
+            if merge_nodes.contains(&label) {
+                if let Some(parent) = dominators.immediate_dominator(label) {
+                    merge_children.entry(parent).or_default().push(label);
+                }
+            }
+        }
+
+        let next_block_id = self.blocks.keys().max().map_or(0, |&id| id + 1);
+        let mut structurizer = Structurizer {
+            ctx: self,
+            flow,
+            rpo,
+            loop_headers,
+            merge_nodes,
+            merge_children,
+            constructs: Vec::new(),
+            u32_type,
+            destination: None,
+            destination_values: FastHashMap::default(),
+            next_block_id,
+        };
+        structurizer.tree(entry, 0)
+    }
+}
+
+impl Structurizer<'_, '_> {
+    /// Add a new, empty body to the table.
+    fn new_body(&mut self, parent: BodyIndex) -> BodyIndex {
+        let index = self.ctx.bodies.len();
+        self.ctx.bodies.push(Body::with_parent(parent));
+        index
+    }
+
+    /// Append the code for `label` and the blocks it dominates to `body`.
+    fn tree(&mut self, label: spirv::Word, body: BodyIndex) -> Result<(), Error> {
+        if !self.loop_headers.contains(&label) {
+            return self.node_within(label, 0, body);
+        }
+
+        let loop_body = self.new_body(body);
+        let continuing = self.new_body(loop_body);
+        self.constructs
+            .push(Construct::new(ConstructKind::Loop(label)));
+        self.node_within(label, 0, loop_body)?;
+        let construct = self.constructs.pop().unwrap();
+        self.ctx.bodies[body].data.push(BodyFragment::Loop {
+            body: loop_body,
+            continuing,
+            break_if: None,
+        });
+        self.check_escapes(construct.escapes, body)
+    }
+
+    /// Append the code for `label` to `body`, wrapped in constructs for its
+    /// merge children from the `skip`'th on, each followed by its code.
+    fn node_within(
+        &mut self,
+        label: spirv::Word,
+        skip: usize,
+        body: BodyIndex,
+    ) -> Result<(), Error> {
+        let merge_child = self
+            .merge_children
+            .get(&label)
+            .and_then(|children| children.get(skip))
+            .copied();
+        if let Some(child) = merge_child {
+            let inner = self.new_body(body);
+            self.constructs
+                .push(Construct::new(ConstructKind::Block(child)));
+            self.node_within(label, skip + 1, inner)?;
+            let construct = self.constructs.pop().unwrap();
+            if construct.broken {
+                let selector = self.ctx.expressions.append(
+                    crate::Expression::Literal(crate::Literal::I32(0)),
+                    crate::Span::default(),
+                );
+                self.ctx.bodies[body].data.push(BodyFragment::Switch {
+                    selector,
+                    cases: Vec::new(),
+                    default: inner,
+                });
+            } else {
+                // Nothing breaks out of the switch, so we don't need it.
+                let data = std::mem::take(&mut self.ctx.bodies[inner].data);
+                self.ctx.bodies[body].data.extend(data);
+            }
+            self.check_escapes(construct.escapes, body)?;
+            return self.tree(child, body);
+        }
+
+        self.ctx.bodies[body]
+            .data
+            .push(BodyFragment::BlockId(label));
+        let flow = self.flow;
+        match flow.terminators.get(&label) {
+            None => Ok(()),
+            Some(&Terminator::Branch(target)) => self.branch(label, target, body),
+            Some(&Terminator::BranchConditional {
+                condition,
+                accept,
+                reject,
+            }) => {
+                if accept == reject {
+                    return self.branch(label, accept, body);
+                }
+                let accept_body = self.new_body(body);
+                let reject_body = self.new_body(body);
+                self.constructs.push(Construct::new(ConstructKind::If));
+                self.branch(label, accept, accept_body)?;
+                self.branch(label, reject, reject_body)?;
+                self.constructs.pop();
+                self.ctx.bodies[body].data.push(BodyFragment::If {
+                    condition,
+                    accept: accept_body,
+                    reject: reject_body,
+                });
+                Ok(())
+            }
+            Some(&Terminator::Switch {
+                selector,
+                ref cases,
+                default,
+            }) => {
+                // Group the cases by target, so that each target's code
+                // appears once, with the other cases falling through to it.
+                let mut groups: Vec<(spirv::Word, Vec<i32>)> = Vec::new();
+                let mut default_values = Vec::new();
+                for &(value, target) in cases.iter() {
+                    if target == default {
+                        default_values.push(value);
+                    } else if let Some(&mut (_, ref mut values)) =
+                        groups.iter_mut().find(|&&mut (t, _)| t == target)
+                    {
+                        values.push(value);
+                    } else {
+                        groups.push((target, vec![value]));
+                    }
+                }
+
+                self.constructs.push(Construct::new(ConstructKind::Switch));
+                let mut ir_cases = Vec::with_capacity(cases.len());
+                for (target, values) in groups {
+                    let case_body = self.new_body(body);
+                    self.branch(label, target, case_body)?;
+                    self.end_case(case_body);
+                    let (&last, rest) = values.split_last().unwrap();
+                    for &value in rest {
+                        ir_cases.push((value, self.new_body(body)));
+                    }
+                    ir_cases.push((last, case_body));
+                }
+                // `lower` puts the default case last, so these fall through
+                // to it.
+                for value in default_values {
+                    ir_cases.push((value, self.new_body(body)));
+                }
+                let default_body = self.new_body(body);
+                self.branch(label, default, default_body)?;
+                self.end_case(default_body);
+                let construct = self.constructs.pop().unwrap();
+
+                self.ctx.bodies[body].data.push(BodyFragment::Switch {
+                    selector,
+                    cases: ir_cases,
+                    default: default_body,
+                });
+                self.check_escapes(construct.escapes, body)
+            }
+        }
+    }
+
+    /// Append the code for a branch from `source` to `target` to `body`.
+    fn branch(
+        &mut self,
+        source: spirv::Word,
+        target: spirv::Word,
+        body: BodyIndex,
+    ) -> Result<(), Error> {
+        if self.loop_headers.contains(&target) && self.rpo[&target] <= self.rpo[&source] {
+            self.jump(Jump::Continue(target), body, false)
+        } else if self.merge_nodes.contains(&target) {
+            self.jump(Jump::To(target), body, false)
+        } else {
+            self.tree(target, body)
+        }
+    }
+
+    /// Where control goes on reaching the end of the innermost of the first
+    /// `depth` constructs, if that's a branch.
+    fn fall_off(&self, depth: usize) -> Option<Jump> {
+        for construct in self.constructs[..depth].iter().rev() {
+            match construct.kind {
+                ConstructKind::If => {}
+                ConstructKind::Block(label) => return Some(Jump::To(label)),
+                ConstructKind::Loop(label) => return Some(Jump::Continue(label)),
+                // Naga would fall through to the next case.
+                ConstructKind::Switch => return None,
+            }
+        }
+        None
+    }
+
+    /// Append the code for `jump` to `body`.
+    ///
+    /// If `checking` is true, the code is for a branch that has already left
+    /// some constructs on its way to its destination, as recorded in
+    /// [`destination`], and it is not at the end of its construct.
+    ///
+    /// [`destination`]: Structurizer::destination
+    fn jump(&mut self, jump: Jump, body: BodyIndex, checking: bool) -> Result<(), Error> {
+        if !checking && self.fall_off(self.constructs.len()) == Some(jump) {
+            return Ok(());
+        }
+
+        let breakable = self
+            .constructs
+            .iter()
+            .rposition(|construct| construct.kind != ConstructKind::If);
+        let fragment = match jump {
+            Jump::Continue(header) => {
+                let innermost_loop =
+                    self.constructs
+                        .iter()
+                        .rev()
+                        .find_map(|construct| match construct.kind {
+                            ConstructKind::Loop(label) => Some(label),
+                            _ => None,
+                        });
+                (innermost_loop == Some(header)).then_some(BodyFragment::Continue)
+            }
+            Jump::To(_) => breakable
+                .filter(|&index| {
+                    let after = match self.constructs[index].kind {
+                        ConstructKind::Block(label) => Some(Jump::To(label)),
+                        _ => self.fall_off(index),
+                    };
+                    after == Some(jump)
+                })
+                .map(|index| {
+                    self.constructs[index].broken = true;
+                    BodyFragment::Break
+                }),
+        };
+
+        if let Some(fragment) = fragment {
+            if checking {
+                self.set_destination(0, body);
+            }
+            self.ctx.bodies[body].data.push(fragment);
+            return Ok(());
+        }
+
+        // Break out of the innermost construct, and try again from there.
+        let index = breakable.ok_or(Error::UnsupportedControlFlow(match jump {
+            Jump::To(label) | Jump::Continue(label) => label,
+        }))?;
+        if !checking {
+            let next_value = self.destination_values.len() as u32 + 1;
+            let value = *self.destination_values.entry(jump).or_insert(next_value);
+            self.set_destination(value, body);
+        }
+        let construct = &mut self.constructs[index];
+        construct.broken = true;
+        if !construct.escapes.contains(&jump) {
+            construct.escapes.push(jump);
+        }
+        self.ctx.bodies[body].data.push(BodyFragment::Break);
+        Ok(())
+    }
+
+    /// Append code to `body` that continues each of `escapes`, the jumps
+    /// that broke out of the construct just added to it.
+    fn check_escapes(&mut self, escapes: Vec<Jump>, body: BodyIndex) -> Result<(), Error> {
+        for jump in escapes {
+            let value = self.destination_values[&jump];
+            let condition = self.destination_is(value, body);
+            let accept = self.new_body(body);
+            let reject = self.new_body(body);
+            self.constructs.push(Construct::new(ConstructKind::If));
+            self.jump(jump, accept, true)?;
+            self.constructs.pop();
+            self.ctx.bodies[body].data.push(BodyFragment::If {
+                condition,
+                accept,
+                reject,
+            });
+        }
+        Ok(())
+    }
+
+    /// Make sure the switch case `body` doesn't fall through to the next.
+    ///
+    /// [`lower`] only looks at the last statement of a case to decide whether
+    /// it falls through, so this adds a `Break` even after an `If` whose
+    /// branches both end in one.
+    ///
+    /// [`lower`]: BlockContext::lower
+    fn end_case(&mut self, body: BodyIndex) {
+        let ends_in_terminator = match self.ctx.bodies[body].data.last() {
+            Some(&BodyFragment::Break | &BodyFragment::Continue) => true,
+            Some(&BodyFragment::BlockId(id)) => self.ctx.blocks[&id]
+                .last()
+                .is_some_and(|statement| statement.is_terminator()),
+            _ => false,
+        };
+        if !ends_in_terminator {
+            self.ctx.bodies[body].data.push(BodyFragment::Break);
+        }
+    }
+
+    /// Append a new block of `statements` to `body`.
+    fn push_block(&mut self, statements: crate::Block, body: BodyIndex) {
+        let id = self.next_block_id;
+        self.next_block_id += 1;
+        self.ctx.blocks.insert(id, statements);
+        self.ctx.bodies[body].data.push(BodyFragment::BlockId(id));
+    }
+
+    /// Return a pointer to the destination variable, creating it if needed.
+    fn destination_pointer(&mut self) -> Handle<crate::Expression> {
+        let local = match self.destination {
+            Some(local) => local,
+            None => {
+                let init = self.ctx.expressions.append(
+                    crate::Expression::Literal(crate::Literal::U32(0)),
+                    crate::Span::default(),
+                );
+                let local = self.ctx.local_arena.append(
+                    crate::LocalVariable {
+                        name: Some("branch_destination".to_string()),
+                        ty: self.u32_type,
+                        init: Some(init),
+                    },
+                    crate::Span::default(),
+                );
+                *self.destination.insert(local)
+            }
+        };
+        self.ctx.expressions.append(
+            crate::Expression::LocalVariable(local),
+            crate::Span::default(),
+        )
+    }
+
+    /// Append code to `body` that stores `value` in the destination variable.
+    fn set_destination(&mut self, value: u32, body: BodyIndex) {
+        let pointer = self.destination_pointer();
+        let value = self.ctx.expressions.append(
+            crate::Expression::Literal(crate::Literal::U32(value)),
+            crate::Span::default(),
+        );
+        let mut block = crate::Block::new();
+        block.push(
+            crate::Statement::Store { pointer, value },
+            crate::Span::default(),
+        );
+        self.push_block(block, body);
+    }
+
+    /// Append code to `body` that compares the destination variable with
+    /// `value`, and return the comparison.
+    fn destination_is(&mut self, value: u32, body: BodyIndex) -> Handle<crate::Expression> {
+        let pointer = self.destination_pointer();
+        let value = self.ctx.expressions.append(
+            crate::Expression::Literal(crate::Literal::U32(value)),
+            crate::Span::default(),
+        );
+        let start = self.ctx.expressions.len();
+        let load = self
+            .ctx
+            .expressions
+            .append(crate::Expression::Load { pointer }, crate::Span::default());
+        let condition = self.ctx.expressions.append(
+            crate::Expression::Binary {
+                op: crate::BinaryOperator::Equal,
+                left: load,
+                right: value,
+            },
+            crate::Span::default(),
+        );
+        let mut block = crate::Block::new();
+        block.push(
+            crate::Statement::Emit(self.ctx.expressions.range_from(start)),
+            crate::Span::default(),
+        );
+        self.push_block(block, body);
+        condition
+    }
+}
@@ -0,0 +1,545 @@
+/*!
+Queries about WGSL source code, for editors and language servers.
+*/
+
+use crate::front::wgsl::error::{ParseError, ParseErrors};
+use crate::front::wgsl::index::{self, Index};
+use crate::front::wgsl::lower::Lowerer;
+use crate::front::wgsl::parse::{ast, Parser, Recovery};
+use crate::front::Typifier;
+use crate::proc::{ResolveContext, TypeResolution};
+use crate::{Arena, Handle, Span};
+
+/// What sort of thing a [`Symbol`] declares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    EntryPoint(crate::ShaderStage),
+    Struct,
+    /// A member of a struct.
+    Member,
+    TypeAlias,
+    Const,
+    Override,
+    /// A module-scope `var`.
+    GlobalVariable,
+    /// A function argument.
+    Argument,
+    /// A function-scope `var`.
+    LocalVariable,
+    Let,
+}
+
+/// A declaration in a WGSL module.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The entire declaration.
+    pub span: Span,
+    /// The declared name.
+    pub name_span: Span,
+    /// Declarations within this one: the members of a struct, or the
+    /// arguments and local variables of a function, in source order.
+    pub children: Vec<Symbol>,
+}
+
+impl Symbol {
+    fn new(name: ast::Ident, kind: SymbolKind, span: Span) -> Self {
+        Symbol {
+            name: name.name.to_string(),
+            kind,
+            span,
+            name_span: name.span,
+            children: Vec::new(),
+        }
+    }
+
+    /// Visit this symbol and all its children.
+    fn walk<'s>(&'s self, f: &mut impl FnMut(&'s Symbol)) {
+        f(self);
+        for child in self.children.iter() {
+            child.walk(f);
+        }
+    }
+}
+
+/// The type of an expression, as returned by [`Analysis::type_at`].
+#[derive(Clone, Debug)]
+pub struct ExpressionType {
+    /// The expression this is the type of.
+    pub span: Span,
+    /// The type, referring to the [`Analysis::module`]'s type arena.
+    pub ty: TypeResolution,
+    /// The type, written as WGSL.
+    pub name: String,
+}
+
+/// Where to find the type of a function-local name in the lowered module.
+#[derive(Clone, Copy, Debug)]
+enum LocalType {
+    /// The type is that of the expression with this span: a `let`'s
+    /// initializer, or a [`FunctionArgument`] expression.
+    ///
+    /// [`FunctionArgument`]: crate::Expression::FunctionArgument
+    Expression(Span),
+    /// The name is a `var`, whose [`LocalVariable`] has this span.
+    ///
+    /// [`LocalVariable`]: crate::LocalVariable
+    Variable(Span),
+}
+
+/// The results of analyzing a WGSL module with [`Frontend::analyze`].
+///
+/// An `Analysis` can answer questions like:
+///
+/// - What does this module declare? ([`symbols`])
+///
+/// - What declaration encloses this offset? ([`declaration_at`])
+///
+/// - Where is the name at this offset declared? ([`definition_at`])
+///
+/// - What is the type of the expression at this offset? ([`type_at`])
+///
+/// All offsets and spans are byte offsets into the source text. Since
+/// editors need answers while the code is still being written, analysis
+/// recovers from errors where it can, like
+/// [`Frontend::parse_with_recovery`]; the errors are available from
+/// [`errors`].
+///
+/// [`Frontend::analyze`]: super::Frontend::analyze
+/// [`Frontend::parse_with_recovery`]: super::Frontend::parse_with_recovery
+/// [`symbols`]: Analysis::symbols
+/// [`declaration_at`]: Analysis::declaration_at
+/// [`definition_at`]: Analysis::definition_at
+/// [`type_at`]: Analysis::type_at
+/// [`errors`]: Analysis::errors
+#[derive(Debug)]
+pub struct Analysis {
+    symbols: Vec<Symbol>,
+
+    /// Every use of a user-declared name, including the declaration itself,
+    /// paired with the span of the declared name.
+    references: Vec<(Span, Span)>,
+
+    /// For each function-local name, given by the span of its declared
+    /// name, where to find its type.
+    local_types: Vec<(Span, LocalType)>,
+
+    module: Option<crate::Module>,
+    errors: Vec<ParseError>,
+}
+
+impl Analysis {
+    pub(super) fn new(parser: &mut Parser, source: &str) -> Self {
+        let mut analysis = Analysis {
+            symbols: Vec::new(),
+            references: Vec::new(),
+            local_types: Vec::new(),
+            module: None,
+            errors: Vec::new(),
+        };
+
+        let mut recovery = Recovery::default();
+        match parser.parse_with_recovery(source, &mut recovery) {
+            Ok(tu) => {
+                for (handle, decl) in tu.decls.iter() {
+                    analysis.global_decl(&tu, decl, tu.decls.get_span(handle));
+                }
+                match Index::generate(&tu) {
+                    Ok(index) => {
+                        analysis.references(&tu, &index);
+                        match Lowerer::new(&index).lower_with_recovery(&tu, &mut recovery) {
+                            Ok(module) => analysis.module = Some(module),
+                            Err(error) => recovery.errors.push(error),
+                        }
+                    }
+                    Err(error) => recovery.errors.push(error),
+                }
+            }
+            Err(error) => recovery.errors.push(error),
+        }
+
+        analysis.errors = ParseErrors::new(
+            recovery
+                .errors
+                .iter()
+                .map(|error| error.as_parse_error(source))
+                .collect(),
+        )
+        .into_iter()
+        .collect();
+        analysis
+    }
+
+    /// The module-scope declarations, in source order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Return the innermost declaration that contains `offset`.
+    pub fn declaration_at(&self, offset: usize) -> Option<&Symbol> {
+        let mut found = None;
+        let mut symbols = &self.symbols[..];
+        while let Some(symbol) = symbols
+            .iter()
+            .find(|symbol| span_contains(symbol.span, offset))
+        {
+            found = Some(symbol);
+            symbols = &symbol.children;
+        }
+        found
+    }
+
+    /// If `offset` is in a name that refers to a declaration in this module,
+    /// return the span of the name in that declaration.
+    ///
+    /// Predeclared names, like `vec4` or `sin`, have no declaration.
+    pub fn definition_at(&self, offset: usize) -> Option<Span> {
+        self.reference_at(offset).map(|(_, definition)| definition)
+    }
+
+    /// Return the spans of every use of the name declared at `definition`,
+    /// as returned by [`definition_at`], not including the declaration
+    /// itself.
+    ///
+    /// [`definition_at`]: Analysis::definition_at
+    pub fn references_to(&self, definition: Span) -> impl Iterator<Item = Span> + '_ {
+        self.references
+            .iter()
+            .filter(move |&&(usage, target)| target == definition && usage != definition)
+            .map(|&(usage, _)| usage)
+    }
+
+    /// Return the type of the innermost expression containing `offset`.
+    ///
+    /// For a name bound by a `let`, `var` or function argument, this is the
+    /// type of the value it holds.
+    ///
+    /// This needs the lowered [`module`], so it returns `None` if lowering
+    /// failed.
+    ///
+    /// [`module`]: Analysis::module
+    pub fn type_at(&self, offset: usize) -> Option<ExpressionType> {
+        let module = self.module.as_ref()?;
+        let functions: Vec<&crate::Function> = module
+            .functions
+            .iter()
+            .map(|(_, function)| function)
+            .chain(module.entry_points.iter().map(|ep| &ep.function))
+            .collect();
+
+        if let Some((usage, definition)) = self.reference_at(offset) {
+            if let Some(&(_, local_type)) = self
+                .local_types
+                .iter()
+                .find(|&&(name_span, _)| name_span == definition)
+            {
+                for &function in functions.iter() {
+                    let ty = match local_type {
+                        LocalType::Expression(span) => {
+                            match find_expression(&function.expressions, |s| s == span) {
+                                Some(handle) => resolve(module, function, handle),
+                                None => continue,
+                            }
+                        }
+                        LocalType::Variable(span) => {
+                            match function.local_variables.iter().find(|&(handle, _)| {
+                                function.local_variables.get_span(handle) == span
+                            }) {
+                                Some((_, var)) => Some(TypeResolution::Handle(var.ty)),
+                                None => continue,
+                            }
+                        }
+                    };
+                    return ty.map(|ty| expression_type(module, usage, ty));
+                }
+            }
+        }
+
+        let contains = |span| span_contains(span, offset);
+        for function in functions {
+            if let Some(handle) = find_expression(&function.expressions, contains) {
+                let span = function.expressions.get_span(handle);
+                let ty = resolve(module, function, handle)?;
+                return Some(expression_type(module, span, ty));
+            }
+        }
+
+        let handle = find_expression(&module.global_expressions, contains)?;
+        let locals = Arena::new();
+        let ctx = ResolveContext::with_locals(module, &locals, &[]);
+        let mut typifier = Typifier::new();
+        typifier
+            .grow(handle, &module.global_expressions, &ctx)
+            .ok()?;
+        let span = module.global_expressions.get_span(handle);
+        Some(expression_type(module, span, typifier[handle].clone()))
+    }
+
+    /// The lowered module, if lowering succeeded.
+    ///
+    /// If there were errors, declarations that had them, and declarations
+    /// that depend on those, are missing.
+    pub const fn module(&self) -> Option<&crate::Module> {
+        self.module.as_ref()
+    }
+
+    /// The errors found in the module, in source order.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    fn reference_at(&self, offset: usize) -> Option<(Span, Span)> {
+        self.references
+            .iter()
+            .copied()
+            .filter(|&(usage, _)| span_contains(usage, offset))
+            .min_by_key(|&(usage, _)| span_len(usage))
+    }
+
+    fn global_decl(&mut self, tu: &ast::TranslationUnit, decl: &ast::GlobalDecl, span: Span) {
+        let Some(name) = index::decl_ident(decl) else {
+            return;
+        };
+        let symbol = match decl.kind {
+            ast::GlobalDeclKind::Fn(ref f) => {
+                let kind = match f.entry_point {
+                    Some(ref ep) => SymbolKind::EntryPoint(ep.stage),
+                    None => SymbolKind::Function,
+                };
+                let mut symbol = Symbol::new(name, kind, span);
+                for arg in f.arguments.iter() {
+                    let span = arg.name.span.until(&tu.types.get_span(arg.ty));
+                    symbol
+                        .children
+                        .push(Symbol::new(arg.name, SymbolKind::Argument, span));
+                    self.local_types
+                        .push((arg.name.span, LocalType::Expression(arg.name.span)));
+                }
+                self.block(tu, &f.body, &mut symbol.children);
+                symbol
+            }
+            ast::GlobalDeclKind::Struct(ref s) => {
+                let mut symbol = Symbol::new(name, SymbolKind::Struct, span);
+                for member in s.members.iter() {
+                    let span = member.name.span.until(&tu.types.get_span(member.ty));
+                    symbol
+                        .children
+                        .push(Symbol::new(member.name, SymbolKind::Member, span));
+                }
+                symbol
+            }
+            ast::GlobalDeclKind::Var(_) => Symbol::new(name, SymbolKind::GlobalVariable, span),
+            ast::GlobalDeclKind::Const(_) => Symbol::new(name, SymbolKind::Const, span),
+            ast::GlobalDeclKind::Override(_) => Symbol::new(name, SymbolKind::Override, span),
+            ast::GlobalDeclKind::Type(_) => Symbol::new(name, SymbolKind::TypeAlias, span),
+            ast::GlobalDeclKind::ConstAssert(_) => return,
+        };
+        self.symbols.push(symbol);
+    }
+
+    /// Add symbols for the local declarations in `block` to `symbols`.
+    fn block(&mut self, tu: &ast::TranslationUnit, block: &ast::Block, symbols: &mut Vec<Symbol>) {
+        for stmt in block.stmts.iter() {
+            match stmt.kind {
+                ast::StatementKind::LocalDecl(ast::LocalDecl::Let(ref l)) => {
+                    symbols.push(Symbol::new(l.name, SymbolKind::Let, stmt.span));
+                    let init = tu.expressions.get_span(l.init);
+                    self.local_types
+                        .push((l.name.span, LocalType::Expression(init)));
+                }
+                ast::StatementKind::LocalDecl(ast::LocalDecl::Var(ref v)) => {
+                    symbols.push(Symbol::new(v.name, SymbolKind::LocalVariable, stmt.span));
+                    self.local_types
+                        .push((v.name.span, LocalType::Variable(stmt.span)));
+                }
+                ast::StatementKind::Block(ref block) => self.block(tu, block, symbols),
+                ast::StatementKind::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.block(tu, accept, symbols);
+                    self.block(tu, reject, symbols);
+                }
+                ast::StatementKind::Switch { ref cases, .. } => {
+                    for case in cases.iter() {
+                        self.block(tu, &case.body, symbols);
+                    }
+                }
+                ast::StatementKind::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.block(tu, body, symbols);
+                    self.block(tu, continuing, symbols);
+                }
+                ast::StatementKind::Break
+                | ast::StatementKind::Continue
+                | ast::StatementKind::Return { .. }
+                | ast::StatementKind::Kill
+                | ast::StatementKind::Call { .. }
+                | ast::StatementKind::Assign { .. }
+                | ast::StatementKind::Increment(_)
+                | ast::StatementKind::Decrement(_)
+                | ast::StatementKind::Ignore(_)
+                | ast::StatementKind::ConstAssert(_) => {}
+            }
+        }
+    }
+
+    /// Record every use of a user-declared name in `tu`.
+    fn references<'a>(&mut self, tu: &ast::TranslationUnit<'a>, index: &Index<'a>) {
+        let global = |name: &str| {
+            let decl = index.lookup(0, name)?;
+            index::decl_ident(&tu.decls[decl]).map(|ident| ident.span)
+        };
+
+        let mut declarations = Vec::new();
+        for symbol in self.symbols.iter() {
+            symbol.walk(&mut |symbol| declarations.push((symbol.name_span, symbol.name_span)));
+        }
+        self.references.extend(declarations);
+
+        // Function-local names are resolved by the parser, but their
+        // `Handle<Local>`s are only meaningful within their function.
+        let functions: Vec<_> = tu
+            .decls
+            .iter()
+            .filter_map(|(handle, decl)| match decl.kind {
+                ast::GlobalDeclKind::Fn(ref f) => Some((tu.decls.get_span(handle), f)),
+                _ => None,
+            })
+            .collect();
+
+        for (handle, expr) in tu.expressions.iter() {
+            let usage = tu.expressions.get_span(handle);
+            let Some(range) = usage.to_range() else {
+                continue;
+            };
+            let definition = match *expr {
+                ast::Expression::Ident(ast::IdentExpr::Unresolved(name)) => global(name),
+                ast::Expression::Ident(ast::IdentExpr::Local(local)) => functions
+                    .iter()
+                    .find(|&&(span, _)| span_contains(span, range.start))
+                    .map(|&(_, f)| f.locals.get_span(local)),
+                ast::Expression::Call { ref function, .. } => {
+                    if let Some(definition) = global(function.name) {
+                        self.references.push((function.span, definition));
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some(definition) = definition {
+                self.references.push((usage, definition));
+            }
+        }
+
+        for (_, ty) in tu.types.iter() {
+            if let ast::Type::User(ident) = *ty {
+                if let Some(definition) = global(ident.name) {
+                    self.references.push((ident.span, definition));
+                }
+            }
+        }
+
+        for &(_, f) in functions.iter() {
+            self.call_statements(&f.body, &global);
+        }
+    }
+
+    /// Record the function names in call statements in `block`, which
+    /// aren't expressions, and so aren't covered by [`Analysis::references`].
+    fn call_statements(&mut self, block: &ast::Block, global: &impl Fn(&str) -> Option<Span>) {
+        for stmt in block.stmts.iter() {
+            match stmt.kind {
+                ast::StatementKind::Call { ref function, .. } => {
+                    if let Some(definition) = global(function.name) {
+                        self.references.push((function.span, definition));
+                    }
+                }
+                ast::StatementKind::Block(ref block) => self.call_statements(block, global),
+                ast::StatementKind::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.call_statements(accept, global);
+                    self.call_statements(reject, global);
+                }
+                ast::StatementKind::Switch { ref cases, .. } => {
+                    for case in cases.iter() {
+                        self.call_statements(&case.body, global);
+                    }
+                }
+                ast::StatementKind::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.call_statements(body, global);
+                    self.call_statements(continuing, global);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn span_contains(span: Span, offset: usize) -> bool {
+    span.to_range().is_some_and(|range| range.contains(&offset))
+}
+
+fn span_len(span: Span) -> usize {
+    span.to_range().map_or(0, |range| range.len())
+}
+
+/// Return the expression in `arena` whose span satisfies `predicate`, and
+/// is the smallest such span.
+///
+/// Of several expressions with the same span, prefer the last: for
+/// example, a [`Load`] rather than the pointer it loads from.
+///
+/// [`Load`]: crate::Expression::Load
+fn find_expression(
+    arena: &Arena<crate::Expression>,
+    predicate: impl Fn(Span) -> bool,
+) -> Option<Handle<crate::Expression>> {
+    arena
+        .iter()
+        .map(|(handle, _)| (handle, arena.get_span(handle)))
+        .filter(|&(_, span)| predicate(span))
+        .min_by_key(|&(handle, span)| (span_len(span), std::cmp::Reverse(handle.index())))
+        .map(|(handle, _)| handle)
+}
+
+fn resolve(
+    module: &crate::Module,
+    function: &crate::Function,
+    handle: Handle<crate::Expression>,
+) -> Option<TypeResolution> {
+    // In WGSL, variables' names are references, whose type is written as
+    // the type of the value they hold.
+    match function.expressions[handle] {
+        crate::Expression::GlobalVariable(var) => {
+            return Some(TypeResolution::Handle(module.global_variables[var].ty))
+        }
+        crate::Expression::LocalVariable(var) => {
+            return Some(TypeResolution::Handle(function.local_variables[var].ty))
+        }
+        _ => {}
+    }
+
+    let ctx = ResolveContext::with_locals(module, &function.local_variables, &function.arguments);
+    let mut typifier = Typifier::new();
+    typifier.grow(handle, &function.expressions, &ctx).ok()?;
+    Some(typifier[handle].clone())
+}
+
+fn expression_type(module: &crate::Module, span: Span, ty: TypeResolution) -> ExpressionType {
+    let name = ty.to_wgsl(&module.to_ctx());
+    ExpressionType { span, ty, name }
+}
@@ -1,13 +1,21 @@
+use crate::diagnostic_filter::ConflictingDiagnosticRuleError;
+use crate::front::wgsl::parse::directive::enable_extension::{
+    EnableExtension, ImplementedEnableExtension, UnimplementedEnableExtension,
+};
+use crate::front::wgsl::parse::directive::language_extension::{
+    LanguageExtension, UnimplementedLanguageExtension,
+};
 use crate::front::wgsl::parse::lexer::Token;
 use crate::front::wgsl::Scalar;
 use crate::proc::{Alignment, ConstantEvaluatorError, ResolveError};
-use crate::{SourceLocation, Span};
-use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFile;
+use crate::{SourceLocation, SourceMap, Span};
+use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
+use codespan_reporting::files::{SimpleFile, SimpleFiles};
 use codespan_reporting::term;
 use std::borrow::Cow;
 use std::ops::Range;
-use termcolor::{ColorChoice, NoColor, StandardStream};
+use std::sync::Arc;
+use termcolor::{ColorChoice, NoColor, StandardStream, WriteColor};
 use thiserror::Error;
 
 #[derive(Clone, Debug)]
@@ -16,6 +24,9 @@ pub struct ParseError {
     // The first span should be the primary span, and the other ones should be complementary.
     labels: Vec<(Span, Cow<'static, str>)>,
     notes: Vec<String>,
+    /// The files the spans in `labels` refer to, if the module was made up
+    /// of several files.
+    source_map: Option<Arc<SourceMap>>,
 }
 
 impl ParseError {
@@ -29,15 +40,36 @@ impl ParseError {
         &self.message
     }
 
-    fn diagnostic(&self) -> Diagnostic<()> {
-        let diagnostic = Diagnostic::error()
+    pub(super) fn with_source_map(self, source_map: Arc<SourceMap>) -> Self {
+        Self {
+            source_map: Some(source_map),
+            ..self
+        }
+    }
+
+    /// The files this error's spans refer to, if the module was parsed from
+    /// several files by [`Frontend::parse_with_imports`].
+    ///
+    /// [`Frontend::parse_with_imports`]: super::Frontend::parse_with_imports
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
+
+    /// Build a diagnostic for this error, using `locate` to find the file
+    /// each label's span falls in, and the span's range within that file.
+    fn diagnostic<F>(
+        &self,
+        severity: Severity,
+        locate: impl Fn(Span) -> Option<(F, Range<usize>)>,
+    ) -> Diagnostic<F> {
+        let diagnostic = Diagnostic::new(severity)
             .with_message(self.message.to_string())
             .with_labels(
                 self.labels
                     .iter()
-                    .filter_map(|label| label.0.to_range().map(|range| (label, range)))
-                    .map(|(label, range)| {
-                        Label::primary((), range).with_message(label.1.to_string())
+                    .filter_map(|label| Some((label, locate(label.0)?)))
+                    .map(|(label, (file, range))| {
+                        Label::primary(file, range).with_message(label.1.to_string())
                     })
                     .collect(),
             )
@@ -61,11 +93,8 @@ impl ParseError {
         P: AsRef<std::path::Path>,
     {
         let path = path.as_ref().display().to_string();
-        let files = SimpleFile::new(path, source);
-        let config = term::Config::default();
         let writer = StandardStream::stderr(ColorChoice::Auto);
-        term::emit(&mut writer.lock(), &config, &files, &self.diagnostic())
-            .expect("cannot write error");
+        self.emit(Severity::Error, &mut writer.lock(), source, path);
     }
 
     /// Emits a summary of the error to a string.
@@ -79,16 +108,56 @@ impl ParseError {
         P: AsRef<std::path::Path>,
     {
         let path = path.as_ref().display().to_string();
-        let files = SimpleFile::new(path, source);
-        let config = term::Config::default();
         let mut writer = NoColor::new(Vec::new());
-        term::emit(&mut writer, &config, &files, &self.diagnostic()).expect("cannot write error");
+        self.emit(Severity::Error, &mut writer, source, path);
         String::from_utf8(writer.into_inner()).unwrap()
     }
 
+    /// Emits a summary of the error to `writer` as a diagnostic of the given
+    /// `severity`, calling the file `source` came from `path`.
+    ///
+    /// If the error has a [`SourceMap`], its main file stands in for
+    /// `source`, and errors in other files are labeled with their own names.
+    fn emit(&self, severity: Severity, writer: &mut dyn WriteColor, source: &str, path: String) {
+        let config = term::Config::default();
+        match self.source_map {
+            None => {
+                let files = SimpleFile::new(path, source);
+                let diagnostic = self.diagnostic(severity, |span| Some(((), span.to_range()?)));
+                term::emit(writer, &config, &files, &diagnostic)
+            }
+            Some(ref source_map) => {
+                let mut files = SimpleFiles::new();
+                for (index, file) in source_map.files().iter().enumerate() {
+                    let name = match index {
+                        0 => path.clone(),
+                        _ => file.name().to_string(),
+                    };
+                    files.add(name, source_map.source(file));
+                }
+                let diagnostic = self.diagnostic(severity, |span| {
+                    let (file, span) = source_map.file_for_span(span)?;
+                    Some((file, span.to_range()?))
+                });
+                term::emit(writer, &config, &files, &diagnostic)
+            }
+        }
+        .expect("cannot write error");
+    }
+
     /// Returns a [`SourceLocation`] for the first label in the error message.
+    ///
+    /// If the error has a [`SourceMap`], the location is relative to the
+    /// start of the file the label is in, and `source` is ignored.
     pub fn location(&self, source: &str) -> Option<SourceLocation> {
-        self.labels.first().map(|label| label.0.location(source))
+        let &(span, _) = self.labels.first()?;
+        match self.source_map {
+            None => Some(span.location(source)),
+            Some(ref source_map) => {
+                let (file, span) = source_map.file_for_span(span)?;
+                Some(span.location(source_map.source(&source_map.files()[file])))
+            }
+        }
     }
 }
 
@@ -104,6 +173,280 @@ impl std::error::Error for ParseError {
     }
 }
 
+/// All the errors found in a module by [`Frontend::parse_with_recovery`].
+///
+/// The errors are sorted by where they occur in the source.
+///
+/// [`Frontend::parse_with_recovery`]: super::Frontend::parse_with_recovery
+#[derive(Clone, Debug)]
+pub struct ParseErrors {
+    errors: Vec<ParseError>,
+}
+
+impl ParseErrors {
+    pub(super) fn new(mut errors: Vec<ParseError>) -> Self {
+        errors.sort_by_key(|error| {
+            error
+                .labels
+                .first()
+                .and_then(|&(span, _)| span.to_range())
+                .map_or(usize::MAX, |range| range.start)
+        });
+        Self { errors }
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Emits a summary of each error to standard error stream.
+    pub fn emit_to_stderr(&self, source: &str) {
+        self.emit_to_stderr_with_path(source, "wgsl")
+    }
+
+    /// Emits a summary of each error to standard error stream.
+    pub fn emit_to_stderr_with_path<P>(&self, source: &str, path: P)
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref();
+        for error in self.errors.iter() {
+            error.emit_to_stderr_with_path(source, path);
+        }
+    }
+
+    /// Emits a summary of each error to a string.
+    pub fn emit_to_string(&self, source: &str) -> String {
+        self.emit_to_string_with_path(source, "wgsl")
+    }
+
+    /// Emits a summary of each error to a string.
+    pub fn emit_to_string_with_path<P>(&self, source: &str, path: P) -> String
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref();
+        self.errors
+            .iter()
+            .map(|error| error.emit_to_string_with_path(source, path))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseErrors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl IntoIterator for ParseErrors {
+    type Item = ParseError;
+    type IntoIter = std::vec::IntoIter<ParseError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+/// A problem in WGSL source that doesn't stop it from being parsed.
+///
+/// These are returned alongside the module by
+/// [`Frontend::parse_with_warnings`], sorted by where they occur in the
+/// source, and are rendered the same way as [`ParseError`]s.
+///
+/// [`Frontend::parse_with_warnings`]: super::Frontend::parse_with_warnings
+#[derive(Clone, Debug)]
+pub struct ParseWarning {
+    kind: WarningKind,
+    report: ParseError,
+}
+
+/// What a [`ParseWarning`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A `var` or `let` in a function is never used.
+    ///
+    /// Declarations whose names start with `_` are exempt.
+    UnusedVariable,
+    /// A statement follows a `return`, `break`, `continue` or `discard` in
+    /// the same block, and so can never run.
+    UnreachableCode,
+    /// A local declaration or function argument has the same name as a
+    /// declaration in an enclosing scope, including module scope.
+    ShadowedDeclaration,
+    /// An abstract-float value was converted to a concrete floating-point
+    /// type that can't represent it as written.
+    AbstractFloatNarrowing,
+}
+
+impl ParseWarning {
+    pub const fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    pub fn labels(&self) -> impl ExactSizeIterator<Item = (Span, &str)> + '_ {
+        self.report.labels()
+    }
+
+    pub fn message(&self) -> &str {
+        self.report.message()
+    }
+
+    /// Emits a summary of the warning to standard error stream.
+    pub fn emit_to_stderr(&self, source: &str) {
+        self.emit_to_stderr_with_path(source, "wgsl")
+    }
+
+    /// Emits a summary of the warning to standard error stream.
+    pub fn emit_to_stderr_with_path<P>(&self, source: &str, path: P)
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref().display().to_string();
+        let writer = StandardStream::stderr(ColorChoice::Auto);
+        self.report
+            .emit(Severity::Warning, &mut writer.lock(), source, path);
+    }
+
+    /// Emits a summary of the warning to a string.
+    pub fn emit_to_string(&self, source: &str) -> String {
+        self.emit_to_string_with_path(source, "wgsl")
+    }
+
+    /// Emits a summary of the warning to a string.
+    pub fn emit_to_string_with_path<P>(&self, source: &str, path: P) -> String
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref().display().to_string();
+        let mut writer = NoColor::new(Vec::new());
+        self.report
+            .emit(Severity::Warning, &mut writer, source, path);
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    /// Returns a [`SourceLocation`] for the first label in the warning message.
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        self.report.location(source)
+    }
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.report.message)
+    }
+}
+
+/// A warning found while parsing or lowering, before it is rendered as a
+/// [`ParseWarning`].
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Warning<'a> {
+    UnusedVariable(Span),
+    UnreachableCode {
+        span: Span,
+        terminator: Span,
+    },
+    ShadowedDeclaration {
+        name: &'a str,
+        span: Span,
+        previous: Span,
+    },
+    AbstractFloatNarrowing {
+        span: Span,
+        narrowed: f64,
+        scalar: Scalar,
+    },
+}
+
+impl<'a> Warning<'a> {
+    /// The span this warning is primarily about.
+    pub(crate) const fn span(&self) -> Span {
+        match *self {
+            Warning::UnusedVariable(span)
+            | Warning::UnreachableCode { span, .. }
+            | Warning::ShadowedDeclaration { span, .. }
+            | Warning::AbstractFloatNarrowing { span, .. } => span,
+        }
+    }
+
+    pub(crate) fn as_parse_warning(&self, source: &'a str) -> ParseWarning {
+        let (kind, report) = match *self {
+            Warning::UnusedVariable(span) => (
+                WarningKind::UnusedVariable,
+                ParseError {
+                    message: format!("unused variable `{}`", &source[span]),
+                    labels: vec![(span, "this is never used".into())],
+                    notes: vec![format!(
+                        "if this is intentional, prefix it with an underscore: `_{}`",
+                        &source[span]
+                    )],
+                    source_map: None,
+                },
+            ),
+            Warning::UnreachableCode { span, terminator } => (
+                WarningKind::UnreachableCode,
+                ParseError {
+                    message: "unreachable statement".to_string(),
+                    labels: vec![
+                        (span, "unreachable statement".into()),
+                        (
+                            terminator,
+                            "any code following this statement is unreachable".into(),
+                        ),
+                    ],
+                    notes: vec![],
+                    source_map: None,
+                },
+            ),
+            Warning::ShadowedDeclaration {
+                name,
+                span,
+                previous,
+            } => (
+                WarningKind::ShadowedDeclaration,
+                ParseError {
+                    message: format!("declaration of `{name}` shadows an earlier declaration"),
+                    labels: vec![
+                        (span, "this declaration".into()),
+                        (previous, "shadows this one".into()),
+                    ],
+                    notes: vec![],
+                    source_map: None,
+                },
+            ),
+            Warning::AbstractFloatNarrowing {
+                span,
+                narrowed,
+                scalar,
+            } => (
+                WarningKind::AbstractFloatNarrowing,
+                ParseError {
+                    message: format!(
+                        "abstract-float value is narrowed to `{}`, losing precision",
+                        scalar.to_wgsl()
+                    ),
+                    labels: vec![(span, format!("this becomes `{narrowed}`").into())],
+                    notes: vec![],
+                    source_map: None,
+                },
+            ),
+        };
+        ParseWarning { kind, report }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ExpectedToken<'a> {
     Token(Token<'a>),
@@ -132,8 +475,6 @@ pub enum NumberError {
     Invalid,
     #[error("numeric literal not representable by target type")]
     NotRepresentable,
-    #[error("unimplemented f16 type")]
-    UnimplementedF16,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -275,6 +616,79 @@ pub enum Error<'a> {
         limit: u8,
     },
     PipelineConstantIDValue(Span),
+    NotBool(Span),
+    ConstAssertFailed(Span),
+    /// An `enable` directive named an extension that Naga doesn't know about.
+    UnknownEnableExtension(Span, &'a str),
+    /// A language feature was used that requires an `enable` directive that
+    /// the module doesn't have.
+    EnableExtensionNotEnabled {
+        span: Span,
+        kind: ImplementedEnableExtension,
+    },
+    /// An `enable` directive named an extension that Naga recognizes, but
+    /// doesn't support yet.
+    EnableExtensionNotYetImplemented {
+        kind: UnimplementedEnableExtension,
+        span: Span,
+    },
+    /// A `requires` directive named a language extension that Naga doesn't
+    /// know about.
+    UnknownLanguageExtension(Span, &'a str),
+    /// A `requires` directive named a language extension that Naga recognizes,
+    /// but doesn't support yet.
+    LanguageExtensionNotYetImplemented {
+        kind: UnimplementedLanguageExtension,
+        span: Span,
+    },
+    /// A directive appeared after the first global declaration.
+    DirectiveAfterFirstGlobalDecl {
+        directive_span: Span,
+    },
+    /// A diagnostic filter named a severity that doesn't exist.
+    DiagnosticInvalidSeverity {
+        severity_control_name_span: Span,
+    },
+    /// Two diagnostic filters in the same scope named the same triggering
+    /// rule with different severities.
+    DiagnosticDuplicateTriggeringRule(ConflictingDiagnosticRuleError),
+    /// A `@diagnostic` attribute was applied to something other than a
+    /// function or one of the statements that can have it.
+    DiagnosticAttributeNotSupported {
+        on_what_plural: &'static str,
+        spans: Vec<Span>,
+    },
+    /// An `import` declaration was found, but the module is being parsed
+    /// without an [`ImportResolver`].
+    ///
+    /// [`ImportResolver`]: super::ImportResolver
+    ImportWithoutResolver(Span),
+    ImportAfterFirstGlobalDecl {
+        import_span: Span,
+    },
+    /// The [`ImportResolver`] couldn't find the file named by an `import`.
+    ///
+    /// [`ImportResolver`]: super::ImportResolver
+    ImportResolution {
+        path_span: Span,
+        message: String,
+    },
+    /// A name was used that more than one imported file declares.
+    AmbiguousImport {
+        usage: Span,
+        candidates: [Span; 2],
+    },
+    /// An `@if` condition named something not in [`Options::defines`].
+    ///
+    /// [`Options::defines`]: super::Options::defines
+    UnknownDefine(Span),
+    /// An operand in an `@if` condition had the wrong type.
+    BadConditionOperand {
+        span: Span,
+        expected: &'static str,
+    },
+    /// A block comment had no closing `*/`.
+    UnterminatedBlockComment(Span),
 }
 
 impl<'a> Error<'a> {
@@ -289,6 +703,7 @@ impl<'a> Error<'a> {
                             Token::Attribute => "@".to_string(),
                             Token::Number(_) => "number".to_string(),
                             Token::Word(s) => s.to_string(),
+                            Token::String(_) => "string".to_string(),
                             Token::Operation(c) => format!("operation ('{c}')"),
                             Token::LogicalOperation(c) => format!("logical operation ('{c}')"),
                             Token::ShiftOperation(c) => format!("bitshift ('{c}{c}')"),
@@ -319,22 +734,26 @@ impl<'a> Error<'a> {
                     ),
                     labels: vec![(unexpected_span, format!("expected {expected_str}").into())],
                     notes: vec![],
+                    source_map: None,
                 }
             }
             Error::UnexpectedComponents(bad_span) => ParseError {
                 message: "unexpected components".to_string(),
                 labels: vec![(bad_span, "unexpected components".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnexpectedOperationInConstContext(span) => ParseError {
                 message: "this operation is not supported in a const context".to_string(),
                 labels: vec![(span, "operation not supported here".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadNumber(bad_span, ref err) => ParseError {
                 message: format!("{}: `{}`", err, &source[bad_span],),
                 labels: vec![(bad_span, err.to_string().into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadMatrixScalarKind(span, scalar) => ParseError {
                 message: format!(
@@ -343,21 +762,25 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(span, "must be floating-point (e.g. `f32`)".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadAccessor(accessor_span) => ParseError {
                 message: format!("invalid field accessor `{}`", &source[accessor_span],),
                 labels: vec![(accessor_span, "invalid accessor".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownIdent(ident_span, ident) => ParseError {
                 message: format!("no definition in scope for identifier: '{ident}'"),
                 labels: vec![(ident_span, "unknown identifier".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownScalarType(bad_span) => ParseError {
                 message: format!("unknown scalar type: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown scalar type".into())],
-                notes: vec!["Valid scalar types are f32, f64, i32, u32, bool".into()],
+                notes: vec!["Valid scalar types are f16, f32, f64, i32, u32, bool".into()],
+                source_map: None,
             },
             Error::BadTextureSampleType { span, scalar } => ParseError {
                 message: format!(
@@ -366,6 +789,7 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(span, "must be one of f32, i32 or u32".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadIncrDecrReferenceType(span) => ParseError {
                 message:
@@ -373,6 +797,7 @@ impl<'a> Error<'a> {
                         .to_string(),
                 labels: vec![(span, "must be a reference type of i32 or u32".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadTexture(bad_span) => ParseError {
                 message: format!(
@@ -381,6 +806,7 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(bad_span, "not an image".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::BadTypeCast {
                 span,
@@ -392,12 +818,14 @@ impl<'a> Error<'a> {
                     message: msg.clone(),
                     labels: vec![(span, msg.into())],
                     notes: vec![],
+                    source_map: None,
                 }
             }
             Error::InvalidResolve(ref resolve_error) => ParseError {
                 message: resolve_error.to_string(),
                 labels: vec![],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidForInitializer(bad_span) => ParseError {
                 message: format!(
@@ -406,11 +834,13 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(bad_span, "not an assignment or function call".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidBreakIf(bad_span) => ParseError {
                 message: "A break if is only allowed in a continuing block".to_string(),
                 labels: vec![(bad_span, "not in a continuing block".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidGatherComponent(bad_span) => ParseError {
                 message: format!(
@@ -419,11 +849,13 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(bad_span, "invalid component".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidConstructorComponentType(bad_span, component) => ParseError {
                 message: format!("invalid type for constructor component at index [{component}]"),
                 labels: vec![(bad_span, "invalid component type".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidIdentifierUnderscore(bad_span) => ParseError {
                 message: "Identifier can't be '_'".to_string(),
@@ -432,6 +864,7 @@ impl<'a> Error<'a> {
                     "Use phony assignment instead ('_ =' notice the absence of 'let' or 'var')"
                         .to_string(),
                 ],
+                source_map: None,
             },
             Error::ReservedIdentifierPrefix(bad_span) => ParseError {
                 message: format!(
@@ -440,76 +873,91 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(bad_span, "invalid identifier".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownAddressSpace(bad_span) => ParseError {
                 message: format!("unknown address space: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown address space".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::RepeatedAttribute(bad_span) => ParseError {
                 message: format!("repeated attribute: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "repeated attribute".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownAttribute(bad_span) => ParseError {
                 message: format!("unknown attribute: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown attribute".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownBuiltin(bad_span) => ParseError {
                 message: format!("unknown builtin: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown builtin".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownAccess(bad_span) => ParseError {
                 message: format!("unknown access: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown access".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownStorageFormat(bad_span) => ParseError {
                 message: format!("unknown storage format: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown storage format".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownConservativeDepth(bad_span) => ParseError {
                 message: format!("unknown conservative depth: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown conservative depth".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::UnknownType(bad_span) => ParseError {
                 message: format!("unknown type: '{}'", &source[bad_span]),
                 labels: vec![(bad_span, "unknown type".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::SizeAttributeTooLow(bad_span, min_size) => ParseError {
                 message: format!("struct member size must be at least {min_size}"),
                 labels: vec![(bad_span, format!("must be at least {min_size}").into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::AlignAttributeTooLow(bad_span, min_align) => ParseError {
                 message: format!("struct member alignment must be at least {min_align}"),
                 labels: vec![(bad_span, format!("must be at least {min_align}").into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::NonPowerOfTwoAlignAttribute(bad_span) => ParseError {
                 message: "struct member alignment must be a power of 2".to_string(),
                 labels: vec![(bad_span, "must be a power of 2".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InconsistentBinding(span) => ParseError {
                 message: "input/output binding is not consistent".to_string(),
                 labels: vec![(span, "input/output binding is not consistent".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::TypeNotConstructible(span) => ParseError {
                 message: format!("type `{}` is not constructible", &source[span]),
                 labels: vec![(span, "type is not constructible".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::TypeNotInferable(span) => ParseError {
                 message: "type can't be inferred".to_string(),
                 labels: vec![(span, "type can't be inferred".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InitializationTypeMismatch { name, ref expected, ref got } => {
                 ParseError {
@@ -522,6 +970,7 @@ impl<'a> Error<'a> {
                         format!("definition of `{}`", &source[name]).into(),
                     )],
                     notes: vec![],
+                    source_map: None,
                 }
             }
             Error::DeclMissingTypeAndInit(name_span) => ParseError {
@@ -531,6 +980,7 @@ impl<'a> Error<'a> {
                     "needs a type specifier or initializer".into(),
                 )],
                 notes: vec![],
+                source_map: None,
             },
             Error::MissingAttribute(name, name_span) => ParseError {
                 message: format!(
@@ -542,31 +992,37 @@ impl<'a> Error<'a> {
                     format!("definition of `{}`", &source[name_span]).into(),
                 )],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidAtomicPointer(span) => ParseError {
                 message: "atomic operation is done on a pointer to a non-atomic".to_string(),
                 labels: vec![(span, "atomic pointer is invalid".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidAtomicOperandType(span) => ParseError {
                 message: "atomic operand type is inconsistent with the operation".to_string(),
                 labels: vec![(span, "atomic operand type is invalid".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidRayQueryPointer(span) => ParseError {
                 message: "ray query operation is done on a pointer to a non-ray-query".to_string(),
                 labels: vec![(span, "ray query pointer is invalid".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::NotPointer(span) => ParseError {
                 message: "the operand of the `*` operator must be a pointer".to_string(),
                 labels: vec![(span, "expression is not a pointer".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::NotReference(what, span) => ParseError {
                 message: format!("{what} must be a reference"),
                 labels: vec![(span, "expression is not a reference".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidAssignment { span, ty } => {
                 let (extra_label, notes) = match ty {
@@ -593,12 +1049,14 @@ impl<'a> Error<'a> {
                         .chain(extra_label)
                         .collect(),
                     notes,
+                    source_map: None,
                 }
             }
             Error::Pointer(what, span) => ParseError {
                 message: format!("{what} must not be a pointer"),
                 labels: vec![(span, "expression is a pointer".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::ReservedKeyword(name_span) => ParseError {
                 message: format!("name `{}` is a reserved keyword", &source[name_span]),
@@ -607,6 +1065,7 @@ impl<'a> Error<'a> {
                     format!("definition of `{}`", &source[name_span]).into(),
                 )],
                 notes: vec![],
+                source_map: None,
             },
             Error::Redefinition { previous, current } => ParseError {
                 message: format!("redefinition of `{}`", &source[current]),
@@ -621,11 +1080,13 @@ impl<'a> Error<'a> {
                     ),
                 ],
                 notes: vec![],
+                source_map: None,
             },
             Error::RecursiveDeclaration { ident, usage } => ParseError {
                 message: format!("declaration of `{}` is recursive", &source[ident]),
                 labels: vec![(ident, "".into()), (usage, "uses itself here".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::CyclicDeclaration { ident, ref path } => ParseError {
                 message: format!("declaration of `{}` is cyclic", &source[ident]),
@@ -647,6 +1108,7 @@ impl<'a> Error<'a> {
                     })
                     .collect(),
                 notes: vec![],
+                source_map: None,
             },
             Error::InvalidSwitchValue { uint, span } => ParseError {
                 message: "invalid switch value".to_string(),
@@ -668,11 +1130,13 @@ impl<'a> Error<'a> {
                         &source[span.start..span.end - 1]
                     )
                 }],
+                source_map: None,
             },
             Error::CalledEntryPoint(span) => ParseError {
                 message: "entry point cannot be called".to_string(),
                 labels: vec![(span, "entry point cannot be called".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::WrongArgumentCount {
                 span,
@@ -690,6 +1154,7 @@ impl<'a> Error<'a> {
                 ),
                 labels: vec![(span, "wrong number of arguments".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::FunctionReturnsVoid(span) => ParseError {
                 message: "function does not return any value".to_string(),
@@ -697,36 +1162,43 @@ impl<'a> Error<'a> {
                 notes: vec![
                     "perhaps you meant to call the function in a separate statement?".into(),
                 ],
+                source_map: None,
             },
             Error::InvalidWorkGroupUniformLoad(span) => ParseError {
                 message: "incorrect type passed to workgroupUniformLoad".into(),
                 labels: vec![(span, "".into())],
                 notes: vec!["passed type must be a workgroup pointer".into()],
+                source_map: None,
             },
             Error::Internal(message) => ParseError {
                 message: "internal WGSL front end error".to_string(),
                 labels: vec![],
                 notes: vec![message.into()],
+                source_map: None,
             },
             Error::ExpectedConstExprConcreteIntegerScalar(span) => ParseError {
                 message: "must be a const-expression that resolves to a concrete integer scalar (u32 or i32)".to_string(),
                 labels: vec![(span, "must resolve to u32 or i32".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::ExpectedNonNegative(span) => ParseError {
                 message: "must be non-negative (>= 0)".to_string(),
                 labels: vec![(span, "must be non-negative".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::ExpectedPositiveArrayLength(span) => ParseError {
                 message: "array element count must be positive (> 0)".to_string(),
                 labels: vec![(span, "must be positive".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::ConstantEvaluatorError(ref e, span) => ParseError {
                 message: e.to_string(),
                 labels: vec![(span, "see msg".into())],
                 notes: vec![],
+                source_map: None,
             },
             Error::MissingWorkgroupSize(span) => ParseError {
                 message: "workgroup size is missing on compute shader entry point".to_string(),
@@ -735,6 +1207,7 @@ impl<'a> Error<'a> {
                     "must be paired with a @workgroup_size attribute".into(),
                 )],
                 notes: vec![],
+                source_map: None,
             },
             Error::AutoConversion { dest_span, ref dest_type, source_span, ref source_type } => ParseError {
                 message: format!("automatic conversions cannot convert `{source_type}` to `{dest_type}`"),
@@ -749,6 +1222,7 @@ impl<'a> Error<'a> {
                     )
                 ],
                 notes: vec![],
+                source_map: None,
             },
             Error::AutoConversionLeafScalar { dest_span, ref dest_scalar, source_span, ref source_type } => ParseError {
                 message: format!("automatic conversions cannot convert elements of `{source_type}` to `{dest_scalar}`"),
@@ -763,6 +1237,7 @@ impl<'a> Error<'a> {
                     )
                 ],
                 notes: vec![],
+                source_map: None,
             },
             Error::ConcretizationFailed { expr_span, ref expr_type, ref scalar, ref inner } => ParseError {
                 message: format!("failed to convert expression to a concrete type: {}", inner),
@@ -774,7 +1249,8 @@ impl<'a> Error<'a> {
                 ],
                 notes: vec![
                     format!("the expression should have been converted to have {} scalar type", scalar),
-                ]
+                ],
+                source_map: None,
             },
             Error::ExceededLimitForNestedBraces { span, limit } => ParseError {
                 message: "brace nesting limit reached".into(),
@@ -782,6 +1258,7 @@ impl<'a> Error<'a> {
                 notes: vec![
                     format!("nesting limit is currently set to {limit}"),
                 ],
+                source_map: None,
             },
             Error::PipelineConstantIDValue(span) => ParseError {
                 message: "pipeline constant ID must be between 0 and 65535 inclusive".to_string(),
@@ -790,6 +1267,215 @@ impl<'a> Error<'a> {
                     "must be between 0 and 65535 inclusive".into(),
                 )],
                 notes: vec![],
+                source_map: None,
+            },
+            Error::NotBool(span) => ParseError {
+                message: "must be a const-expression that resolves to a bool".to_string(),
+                labels: vec![(span, "must resolve to bool".into())],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::ConstAssertFailed(span) => ParseError {
+                message: "const_assert failure".to_string(),
+                labels: vec![(span, "evaluates to false".into())],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::UnknownEnableExtension(span, word) => ParseError {
+                message: format!("`{word}` is not a valid enable-extension name"),
+                labels: vec![(span, "invalid enable-extension".into())],
+                notes: vec![
+                    "See available extensions at <https://gpuweb.github.io/gpuweb/wgsl/#enable-extension>.".into(),
+                ],
+                source_map: None,
+            },
+            Error::EnableExtensionNotEnabled { span, kind } => ParseError {
+                message: format!(
+                    "the `{}` language extension is not enabled",
+                    kind.to_ident()
+                ),
+                labels: vec![(
+                    span,
+                    format!("the `{}` language extension is needed for this", kind.to_ident())
+                        .into(),
+                )],
+                notes: vec![format!(
+                    "You can enable this extension by adding `enable {};` at the top of the shader.",
+                    kind.to_ident()
+                )],
+                source_map: None,
+            },
+            Error::EnableExtensionNotYetImplemented { kind, span } => ParseError {
+                message: format!(
+                    "the `{}` enable-extension is not yet supported",
+                    EnableExtension::Unimplemented(kind).to_ident()
+                ),
+                labels: vec![(
+                    span,
+                    "this enable-extension specifies standard functionality which is not yet implemented in Naga".into(),
+                )],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::UnknownLanguageExtension(span, word) => ParseError {
+                message: format!("`{word}` is not a valid language extension name"),
+                labels: vec![(span, "invalid language extension".into())],
+                notes: vec![
+                    "See available extensions at <https://gpuweb.github.io/gpuweb/wgsl/#language-extensions-sec>.".into(),
+                ],
+                source_map: None,
+            },
+            Error::LanguageExtensionNotYetImplemented { kind, span } => ParseError {
+                message: format!(
+                    "the `{}` language extension is not yet supported",
+                    LanguageExtension::Unimplemented(kind).to_ident()
+                ),
+                labels: vec![(
+                    span,
+                    "this language extension specifies standard functionality which is not yet implemented in Naga".into(),
+                )],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::DirectiveAfterFirstGlobalDecl { directive_span } => ParseError {
+                message: "expected global declaration, but found a global directive".into(),
+                labels: vec![(
+                    directive_span,
+                    "written after first global declaration".into(),
+                )],
+                notes: vec![concat!(
+                    "global directives are only allowed before global declarations; ",
+                    "maybe hoist this closer to the top of the shader module?"
+                )
+                .into()],
+                source_map: None,
+            },
+            Error::DiagnosticInvalidSeverity {
+                severity_control_name_span,
+            } => ParseError {
+                message: "invalid `diagnostic(…)` severity".into(),
+                labels: vec![(
+                    severity_control_name_span,
+                    "not a valid severity level".into(),
+                )],
+                notes: vec![concat!(
+                    "See available severities at ",
+                    "<https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-severity>."
+                )
+                .into()],
+                source_map: None,
+            },
+            Error::DiagnosticDuplicateTriggeringRule(ConflictingDiagnosticRuleError {
+                ref triggering_rule,
+                triggering_rule_spans,
+            }) => {
+                let [first_span, second_span] = triggering_rule_spans;
+                ParseError {
+                    message: format!(
+                        "found conflicting `diagnostic(…)` rule(s) for `{triggering_rule}`"
+                    ),
+                    labels: vec![
+                        (first_span, "first rule".into()),
+                        (second_span, "second rule".into()),
+                    ],
+                    notes: vec![concat!(
+                        "Multiple `diagnostic(…)` rules with the same rule name ",
+                        "conflict unless the severity is the same; ",
+                        "delete the rule you don't want, or ",
+                        "ensure that all severities with the same rule name match"
+                    )
+                    .into()],
+                    source_map: None,
+                }
+            }
+            Error::DiagnosticAttributeNotSupported {
+                on_what_plural,
+                ref spans,
+            } => ParseError {
+                message: format!(
+                    "`@diagnostic(…)` attribute(s) on {on_what_plural} are not supported",
+                ),
+                labels: spans
+                    .iter()
+                    .cloned()
+                    .map(|span| (span, "".into()))
+                    .collect(),
+                notes: vec![
+                    concat!(
+                        "`@diagnostic(…)` attributes are only permitted on `fn`s, ",
+                        "compound statements, and `if`, `switch`, `loop`, `for` ",
+                        "and `while` statements"
+                    )
+                    .into(),
+                ],
+                source_map: None,
+            },
+            Error::ImportWithoutResolver(span) => ParseError {
+                message: "`import` declarations are not supported here".into(),
+                labels: vec![(span, "imported here".into())],
+                notes: vec![concat!(
+                    "modules that import other files must be parsed ",
+                    "with `Frontend::parse_with_imports`"
+                )
+                .into()],
+                source_map: None,
+            },
+            Error::ImportAfterFirstGlobalDecl { import_span } => ParseError {
+                message: "expected global declaration, but found an `import`".into(),
+                labels: vec![(
+                    import_span,
+                    "written after first global declaration".into(),
+                )],
+                notes: vec![concat!(
+                    "imports are only allowed before global declarations; ",
+                    "maybe hoist this closer to the top of the shader module?"
+                )
+                .into()],
+                source_map: None,
+            },
+            Error::ImportResolution {
+                path_span,
+                ref message,
+            } => ParseError {
+                message: format!("failed to import {}", &source[path_span]),
+                labels: vec![(path_span, message.clone().into())],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::AmbiguousImport { usage, candidates } => ParseError {
+                message: format!(
+                    "`{}` is declared by more than one imported file",
+                    &source[usage]
+                ),
+                labels: vec![
+                    (usage, "ambiguous name".into()),
+                    (candidates[0], "declared here".into()),
+                    (candidates[1], "and here".into()),
+                ],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::UnknownDefine(span) => ParseError {
+                message: format!("no define named `{}`", &source[span]),
+                labels: vec![(span, "unknown define".into())],
+                notes: vec![concat!(
+                    "defines are supplied through `Options::defines`; ",
+                    "use `defined(…)` to test whether one is present"
+                )
+                .into()],
+                source_map: None,
+            },
+            Error::BadConditionOperand { span, expected } => ParseError {
+                message: format!("expected {expected} in `@if` condition"),
+                labels: vec![(span, format!("expected {expected}").into())],
+                notes: vec![],
+                source_map: None,
+            },
+            Error::UnterminatedBlockComment(span) => ParseError {
+                message: "unterminated block comment".to_string(),
+                labels: vec![(span, "comment starts here".into())],
+                notes: vec![],
+                source_map: None,
             },
         }
     }
@@ -0,0 +1,1619 @@
+/*!
+Canonical formatting for WGSL source code.
+
+[`format`] parses the module into the front end's AST and prints it back out.
+Layout is decided by a small pretty printer in the style of Wadler's "A
+prettier printer": the AST is turned into a `Doc`, which says what text to
+print and where lines may break, and `render` fills lines up to `WIDTH`
+columns, breaking the outermost groups that don't fit first. The AST doesn't
+record how the source was laid out, so the output depends only on the module
+itself: copies of a module that differ only in whitespace format identically.
+
+Comments aren't part of the AST. They are found by lexing the source, and
+printed before the first node that starts after them, either on a line of
+their own or, if they were at the end of a line, at the end of the line that
+comes before that node.
+
+Formatting needs some things the AST doesn't otherwise keep, so the parser
+records them too: how loops and blocks were written, the spans of blocks and
+types, the module's directives, and the conditions of `@if` attributes, whose
+code [`Parser::new_for_formatting`] keeps rather than dropping.
+*/
+
+use std::borrow::Cow;
+
+use crate::diagnostic_filter::{DiagnosticFilter, DiagnosticFilterNode};
+use crate::front::wgsl::error::{Error, ParseError};
+use crate::front::wgsl::parse::lexer::{consume_token, Token};
+use crate::front::wgsl::parse::{ast, Parser};
+use crate::{Handle, Span};
+
+/// The column `render` tries to keep lines within.
+const WIDTH: usize = 100;
+
+/// The number of columns each level of indentation adds.
+const INDENT: usize = 4;
+
+/// Format the WGSL module `source`.
+///
+/// The source must parse, but needn't be valid otherwise: names aren't
+/// resolved, and code excluded by `@if` attributes is formatted like the rest.
+/// Comments are kept, as are the order of declarations and the spelling of
+/// literals, but everything else is laid out anew:
+///
+/// - Blocks are indented by four spaces. Statements, declarations and struct
+///   members each go on their own line, and `{` stays on the line that opens
+///   it.
+///
+/// - Lines are kept within 100 columns where possible. A line that is too
+///   long breaks between the items of its outermost argument or parameter
+///   list, or before the operators of its outermost binary expression, and
+///   then the same inside the pieces that are still too long.
+///
+/// - Parentheses are kept only where the expression needs them.
+///
+/// - Declarations of different kinds are separated by a blank line, as are
+///   functions and structs from everything else.
+///
+/// - A blank line is left before a comment on a line of its own, unless the
+///   comment opens a block.
+pub fn format(source: &str) -> Result<String, ParseError> {
+    format_impl(source).map_err(|error| error.as_parse_error(source))
+}
+
+fn format_impl(source: &str) -> Result<String, Error<'_>> {
+    let comments = comments(source)?;
+    let tu = Parser::new_for_formatting().parse(source)?;
+
+    let mut printer = Printer {
+        source,
+        tu: &tu,
+        comments,
+        next_comment: 0,
+    };
+    let doc = printer.module();
+
+    let mut out = render(&doc);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// A comment in the source.
+#[derive(Clone, Copy)]
+struct Comment<'a> {
+    /// The comment's text, without the line break that ends a line comment.
+    text: &'a str,
+    start: usize,
+    /// Whether nothing but whitespace came before the comment on its line.
+    own_line: bool,
+}
+
+impl Comment<'_> {
+    fn is_line_comment(&self) -> bool {
+        self.text.starts_with("//")
+    }
+}
+
+fn comments(source: &str) -> Result<Vec<Comment<'_>>, Error<'_>> {
+    let mut comments = Vec::new();
+    let mut input = source;
+    let mut own_line = true;
+    loop {
+        let start = source.len() - input.len();
+        let (token, rest) = consume_token(input, false);
+        let text = &input[..input.len() - rest.len()];
+        match token {
+            Token::End if input.is_empty() => break,
+            // The lexer gives up on a block comment that is never closed.
+            Token::End => {
+                return Err(Error::UnterminatedBlockComment(Span::from(
+                    start..start + 2,
+                )))
+            }
+            Token::Trivia if text.starts_with("//") || text.starts_with("/*") => {
+                comments.push(Comment {
+                    text: text.trim_end(),
+                    start,
+                    own_line,
+                });
+                // A line comment includes the line break that ends it.
+                own_line = text.starts_with("//");
+            }
+            Token::Trivia => own_line |= text.contains('\n'),
+            _ => own_line = false,
+        }
+        input = rest;
+    }
+    Ok(comments)
+}
+
+/// A document for `render` to lay out.
+enum Doc<'a> {
+    Text(Cow<'a, str>),
+    /// A space, or a line break if the enclosing group is broken.
+    Line,
+    /// Nothing, or a line break if the enclosing group is broken.
+    SoftLine,
+    /// A line break, which breaks every enclosing group.
+    HardLine,
+    /// Nothing, but break every enclosing group, like after a line comment.
+    BreakParent,
+    /// Text printed only if the enclosing group is broken, like the comma
+    /// after the last item of a list.
+    IfBroken(&'static str),
+    Concat(Vec<Doc<'a>>),
+    /// A group, printed on one line if it fits, and broken otherwise.
+    Group(Box<Doc<'a>>),
+    /// Indent the lines that begin inside this by one more level.
+    Indent(Box<Doc<'a>>),
+}
+
+fn text<'a>(text: impl Into<Cow<'a, str>>) -> Doc<'a> {
+    Doc::Text(text.into())
+}
+
+const fn concat(docs: Vec<Doc<'_>>) -> Doc<'_> {
+    Doc::Concat(docs)
+}
+
+fn group(doc: Doc<'_>) -> Doc<'_> {
+    Doc::Group(Box::new(doc))
+}
+
+fn indent(doc: Doc<'_>) -> Doc<'_> {
+    Doc::Indent(Box::new(doc))
+}
+
+/// Return `items` separated by commas, between `open` and `close`.
+///
+/// Each item comes with the comments that follow it on its line, which go
+/// after its comma. If the list doesn't fit on one line, each item goes on a
+/// line of its own, followed by a comma.
+fn list<'a>(open: &'static str, items: Vec<(Doc<'a>, Doc<'a>)>, close: &'static str) -> Doc<'a> {
+    if items.is_empty() {
+        return concat(vec![text(open), text(close)]);
+    }
+    let last = items.len() - 1;
+    let mut inner = vec![Doc::SoftLine];
+    for (i, (item, comments)) in items.into_iter().enumerate() {
+        inner.push(item);
+        if i < last {
+            inner.push(text(","));
+            inner.push(comments);
+            inner.push(Doc::Line);
+        } else {
+            inner.push(Doc::IfBroken(","));
+            inner.push(comments);
+        }
+    }
+    group(concat(vec![
+        text(open),
+        indent(concat(inner)),
+        Doc::SoftLine,
+        text(close),
+    ]))
+}
+
+/// Return each of `attributes` followed by a space, to go before something
+/// on the same line.
+fn inline(attributes: Vec<Doc<'_>>) -> Vec<Doc<'_>> {
+    let mut docs = Vec::with_capacity(attributes.len() * 2);
+    for attribute in attributes {
+        docs.push(attribute);
+        docs.push(text(" "));
+    }
+    docs
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// The text `render` has produced so far.
+#[derive(Default)]
+struct Output {
+    text: String,
+    column: usize,
+    /// The indentation of the current line, if nothing has been written on
+    /// it yet. It is written along with the line's first text, so that blank
+    /// lines stay empty.
+    indent: Option<usize>,
+}
+
+impl Output {
+    fn write(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(indent) = self.indent.take() {
+            self.text.extend(std::iter::repeat(' ').take(indent));
+        }
+        self.text.push_str(text);
+        match text.rfind('\n') {
+            Some(line_start) => self.column = text[line_start + 1..].chars().count(),
+            None => self.column += text.chars().count(),
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.text.push('\n');
+        self.column = indent;
+        self.indent = Some(indent);
+    }
+}
+
+fn render(doc: &Doc<'_>) -> String {
+    let mut out = Output::default();
+    let mut stack = vec![(0, Mode::Break, doc)];
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match *doc {
+            Doc::Text(ref text) => out.write(text),
+            Doc::IfBroken(text) => {
+                if mode == Mode::Break {
+                    out.write(text);
+                }
+            }
+            Doc::Line if mode == Mode::Flat => out.write(" "),
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => out.newline(indent),
+            Doc::BreakParent => {}
+            Doc::Concat(ref docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Group(ref doc) => {
+                let flat =
+                    mode == Mode::Flat || fits(WIDTH.saturating_sub(out.column), doc, &stack);
+                let mode = if flat { Mode::Flat } else { Mode::Break };
+                stack.push((indent, mode, doc));
+            }
+            Doc::Indent(ref doc) => stack.push((indent + INDENT, mode, doc)),
+        }
+    }
+    out.text
+}
+
+/// Whether `doc`, printed flat, and whatever follows it on the same line fit
+/// in `width` columns.
+///
+/// The documents in `rest` are the ones left to print, with the last one
+/// next.
+fn fits(mut width: usize, doc: &Doc<'_>, rest: &[(usize, Mode, &Doc<'_>)]) -> bool {
+    let mut stack = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev().map(|&(_, mode, doc)| (mode, doc));
+    while let Some((mode, doc)) = stack.pop().or_else(|| rest.next()) {
+        let text = match *doc {
+            Doc::Text(ref text) => text,
+            Doc::IfBroken(text) if mode == Mode::Break => text,
+            Doc::IfBroken(_) => continue,
+            Doc::Line | Doc::SoftLine | Doc::HardLine if mode == Mode::Break => return true,
+            Doc::Line => " ",
+            Doc::SoftLine => continue,
+            Doc::HardLine | Doc::BreakParent if mode == Mode::Flat => return false,
+            Doc::HardLine | Doc::BreakParent => continue,
+            Doc::Concat(ref docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (mode, doc)));
+                continue;
+            }
+            Doc::Group(ref doc) | Doc::Indent(ref doc) => {
+                stack.push((mode, doc));
+                continue;
+            }
+        };
+        let first_line = text.split('\n').next().unwrap_or_default();
+        match width.checked_sub(first_line.chars().count()) {
+            Some(left) => width = left,
+            None => return false,
+        }
+        if text.contains('\n') {
+            return mode == Mode::Break;
+        }
+    }
+    true
+}
+
+/// Lines of output being built up, like the statements of a block.
+struct Lines<'a> {
+    docs: Vec<Doc<'a>>,
+    /// Whether there is a line before the next one, either in `docs` or one
+    /// that opens them, like the line ending with a block's `{`.
+    open: bool,
+    /// Whether the current line ends with a line comment.
+    commented: bool,
+}
+
+impl<'a> Lines<'a> {
+    const fn new(open: bool) -> Self {
+        Lines {
+            docs: Vec::new(),
+            open,
+            commented: false,
+        }
+    }
+
+    /// Start a new line with `doc`.
+    fn push(&mut self, doc: Doc<'a>) {
+        if self.open {
+            self.docs.push(Doc::HardLine);
+        }
+        self.docs.push(doc);
+        self.open = true;
+        self.commented = false;
+    }
+
+    /// Leave a blank line before the next line, if there is a line before it.
+    fn blank(&mut self) {
+        if self.open && !self.docs.is_empty() {
+            self.docs.push(Doc::HardLine);
+        }
+    }
+
+    /// Add `comment` at the end of the current line, or on a line of its own
+    /// if that's where it was, or if the current line can't take it.
+    fn comment(&mut self, comment: Comment<'a>) {
+        if comment.own_line || !self.open || self.commented {
+            self.push(text(comment.text));
+        } else {
+            self.docs.push(text(format!(" {}", comment.text)));
+        }
+        self.commented = comment.is_line_comment();
+    }
+
+    /// Return these lines as the body of a `{ … }` block.
+    fn braces(self) -> Doc<'a> {
+        if self.docs.is_empty() {
+            return text("{}");
+        }
+        concat(vec![
+            text("{"),
+            indent(concat(self.docs)),
+            Doc::HardLine,
+            text("}"),
+        ])
+    }
+}
+
+/// The kinds of top-level items, for deciding where blank lines go.
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    Directive,
+    Import,
+    Const,
+    Override,
+    Var,
+    Alias,
+    ConstAssert,
+    Fn,
+    Struct,
+}
+
+/// How tightly an expression binds, from loosest to tightest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    LogicalOr,
+    LogicalAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    /// Comparisons, including `==` and `!=`.
+    Relational,
+    Shift,
+    Additive,
+    Multiplicative,
+    Unary,
+    /// Primary expressions, and indexing and member accesses.
+    Postfix,
+}
+
+const fn binary_precedence(op: crate::BinaryOperator) -> Precedence {
+    use crate::BinaryOperator as Bo;
+    match op {
+        Bo::LogicalOr => Precedence::LogicalOr,
+        Bo::LogicalAnd => Precedence::LogicalAnd,
+        Bo::InclusiveOr => Precedence::BitwiseOr,
+        Bo::ExclusiveOr => Precedence::BitwiseXor,
+        Bo::And => Precedence::BitwiseAnd,
+        Bo::Equal | Bo::NotEqual | Bo::Less | Bo::LessEqual | Bo::Greater | Bo::GreaterEqual => {
+            Precedence::Relational
+        }
+        Bo::ShiftLeft | Bo::ShiftRight => Precedence::Shift,
+        Bo::Add | Bo::Subtract => Precedence::Additive,
+        Bo::Multiply | Bo::Divide | Bo::Modulo => Precedence::Multiplicative,
+    }
+}
+
+/// Whether an expression of precedence `operand` can be the left (or right)
+/// operand of a binary operator of precedence `op` without parentheses.
+///
+/// This follows the WGSL grammar, which is stricter than precedence alone:
+/// `a << b << c` and `a & b | c`, for example, are not allowed.
+fn is_operand(op: Precedence, operand: Precedence, left: bool) -> bool {
+    // Operators that group to the left take a chain of themselves on the left.
+    if left && operand == op && !matches!(op, Precedence::Relational | Precedence::Shift) {
+        return true;
+    }
+    let loosest = match op {
+        Precedence::Additive => Precedence::Multiplicative,
+        Precedence::Relational => Precedence::Shift,
+        Precedence::LogicalAnd | Precedence::LogicalOr => Precedence::Relational,
+        _ => Precedence::Unary,
+    };
+    operand >= loosest
+}
+
+const fn binary_operator(op: crate::BinaryOperator) -> &'static str {
+    use crate::BinaryOperator as Bo;
+    match op {
+        Bo::Add => "+",
+        Bo::Subtract => "-",
+        Bo::Multiply => "*",
+        Bo::Divide => "/",
+        Bo::Modulo => "%",
+        Bo::Equal => "==",
+        Bo::NotEqual => "!=",
+        Bo::Less => "<",
+        Bo::LessEqual => "<=",
+        Bo::Greater => ">",
+        Bo::GreaterEqual => ">=",
+        Bo::And => "&",
+        Bo::ExclusiveOr => "^",
+        Bo::InclusiveOr => "|",
+        Bo::LogicalAnd => "&&",
+        Bo::LogicalOr => "||",
+        Bo::ShiftLeft => "<<",
+        Bo::ShiftRight => ">>",
+    }
+}
+
+const fn unary_operator(op: crate::UnaryOperator) -> &'static str {
+    match op {
+        crate::UnaryOperator::Negate => "-",
+        crate::UnaryOperator::LogicalNot => "!",
+        crate::UnaryOperator::BitwiseNot => "~",
+    }
+}
+
+const fn storage_access(access: crate::StorageAccess) -> &'static str {
+    if access.contains(crate::StorageAccess::STORE) {
+        if access.contains(crate::StorageAccess::LOAD) {
+            "read_write"
+        } else {
+            "write"
+        }
+    } else {
+        "read"
+    }
+}
+
+/// Return the template list for a variable or pointer in `space`, leaving
+/// out the access mode if it is the default.
+fn address_space(space: crate::AddressSpace) -> String {
+    match space {
+        crate::AddressSpace::Function => "<function>".to_string(),
+        crate::AddressSpace::Private => "<private>".to_string(),
+        crate::AddressSpace::WorkGroup => "<workgroup>".to_string(),
+        crate::AddressSpace::Uniform => "<uniform>".to_string(),
+        crate::AddressSpace::Storage { access } if access == crate::StorageAccess::LOAD => {
+            "<storage>".to_string()
+        }
+        crate::AddressSpace::Storage { access } => {
+            format!("<storage, {}>", storage_access(access))
+        }
+        crate::AddressSpace::PushConstant => "<push_constant>".to_string(),
+        // Handles take no template, and WGSL has no way to write
+        // `PhysicalStorage`.
+        crate::AddressSpace::Handle | crate::AddressSpace::PhysicalStorage => String::new(),
+    }
+}
+
+fn image_type(dim: crate::ImageDimension, arrayed: bool, class: crate::ImageClass) -> String {
+    let dim = match dim {
+        crate::ImageDimension::D1 => "1d",
+        crate::ImageDimension::D2 => "2d",
+        crate::ImageDimension::D3 => "3d",
+        crate::ImageDimension::Cube => "cube",
+    };
+    let array = if arrayed { "_array" } else { "" };
+    match class {
+        crate::ImageClass::Sampled { kind, multi } => {
+            let multi = if multi { "multisampled_" } else { "" };
+            let scalar = crate::Scalar { kind, width: 4 };
+            format!("texture_{multi}{dim}{array}<{}>", scalar.to_wgsl())
+        }
+        crate::ImageClass::Depth { multi } => {
+            let multi = if multi { "multisampled_" } else { "" };
+            format!("texture_depth_{multi}{dim}{array}")
+        }
+        crate::ImageClass::Storage { format, access } => format!(
+            "texture_storage_{dim}{array}<{}, {}>",
+            format.to_wgsl(),
+            storage_access(access)
+        ),
+    }
+}
+
+const fn built_in(built_in: crate::BuiltIn) -> &'static str {
+    use crate::BuiltIn as Bi;
+    match built_in {
+        Bi::Position { .. } => "position",
+        Bi::ViewIndex => "view_index",
+        Bi::VertexIndex => "vertex_index",
+        Bi::InstanceIndex => "instance_index",
+        Bi::FrontFacing => "front_facing",
+        Bi::FragDepth => "frag_depth",
+        Bi::PrimitiveIndex => "primitive_index",
+        Bi::SampleIndex => "sample_index",
+        Bi::SampleMask => "sample_mask",
+        Bi::GlobalInvocationId => "global_invocation_id",
+        Bi::LocalInvocationId => "local_invocation_id",
+        Bi::LocalInvocationIndex => "local_invocation_index",
+        Bi::WorkGroupId => "workgroup_id",
+        Bi::NumWorkGroups => "num_workgroups",
+        Bi::NumSubgroups => "num_subgroups",
+        Bi::SubgroupId => "subgroup_id",
+        Bi::SubgroupSize => "subgroup_size",
+        Bi::SubgroupInvocationId => "subgroup_invocation_id",
+        // These have no WGSL spelling, so the parser never makes them.
+        Bi::BaseInstance
+        | Bi::BaseVertex
+        | Bi::ClipDistance
+        | Bi::CullDistance
+        | Bi::PointSize
+        | Bi::PointCoord
+        | Bi::WorkGroupSize => unreachable!(),
+    }
+}
+
+fn diagnostic_filter(filter: &DiagnosticFilter) -> String {
+    format!(
+        "({}, {})",
+        filter.new_severity.to_ident(),
+        filter.triggering_rule
+    )
+}
+
+/// Return the offset at which `span` starts.
+fn start(span: Span) -> usize {
+    span.to_range().map_or(0, |range| range.start)
+}
+
+/// Return the offset of the `}` that ends `span`, if it is known.
+fn closing_brace(span: Span) -> Option<usize> {
+    span.to_range().map(|range| range.end - 1)
+}
+
+struct Printer<'t, 'a> {
+    source: &'a str,
+    tu: &'t ast::TranslationUnit<'a>,
+    /// The comments in the source, in order.
+    comments: Vec<Comment<'a>>,
+    /// The index in `comments` of the first comment not yet printed.
+    next_comment: usize,
+}
+
+impl<'t, 'a> Printer<'t, 'a> {
+    /// Take the comments not yet printed that start before `end`.
+    fn take_comments(&mut self, end: usize) -> Vec<Comment<'a>> {
+        let first = self.next_comment;
+        while self
+            .comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.start < end)
+        {
+            self.next_comment += 1;
+        }
+        self.comments[first..self.next_comment].to_vec()
+    }
+
+    /// Add the comments before `end` to `lines`, as the ones leading the
+    /// line that starts there.
+    ///
+    /// If `blank` is set, leave a blank line before the first of them that
+    /// was on a line of its own. Return whether a blank line was left.
+    fn leading_comments(&mut self, end: usize, lines: &mut Lines<'a>, mut blank: bool) -> bool {
+        let mut left = false;
+        for comment in self.take_comments(end) {
+            if blank && comment.own_line {
+                lines.blank();
+                blank = false;
+                left = true;
+            }
+            lines.comment(comment);
+        }
+        left
+    }
+
+    /// Return the comments before `end`, to go before the list item that
+    /// starts there.
+    fn item_comments(&mut self, end: usize) -> Doc<'a> {
+        let mut docs = Vec::new();
+        for comment in self.take_comments(end) {
+            docs.push(text(comment.text));
+            if comment.is_line_comment() {
+                docs.push(Doc::HardLine);
+            } else {
+                docs.push(text(" "));
+            }
+        }
+        concat(docs)
+    }
+
+    /// Return the comments before `end` that follow a list item on its line,
+    /// to go after its comma.
+    fn trailing_comments(&mut self, end: usize) -> Doc<'a> {
+        let mut docs = Vec::new();
+        while let Some(&comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.start < end && !comment.own_line)
+        {
+            self.next_comment += 1;
+            docs.push(text(format!(" {}", comment.text)));
+            if comment.is_line_comment() {
+                docs.push(Doc::BreakParent);
+            }
+        }
+        concat(docs)
+    }
+
+    fn module(&mut self) -> Doc<'a> {
+        let tu = self.tu;
+        let mut lines = Lines::new(false);
+        let mut previous = None;
+
+        for (handle, directive) in tu.directives.iter() {
+            let span = tu.directives.get_span(handle);
+            self.start_item(&mut lines, &mut previous, Item::Directive, start(span));
+            let doc = match *directive {
+                ast::Directive::Diagnostic(ref filter) => {
+                    format!("diagnostic{};", diagnostic_filter(filter))
+                }
+                ast::Directive::Enable(ref names) => format!("enable {};", join(names)),
+                ast::Directive::Requires(ref names) => format!("requires {};", join(names)),
+            };
+            lines.push(text(doc));
+        }
+
+        for &(_, span) in tu.import_paths.iter() {
+            self.start_item(&mut lines, &mut previous, Item::Import, start(span));
+            lines.push(text(format!("import {};", &self.source[span])));
+        }
+
+        for (handle, decl) in tu.decls.iter() {
+            let span = tu.decls.get_span(handle);
+            let item = match decl.kind {
+                ast::GlobalDeclKind::Fn(_) => Item::Fn,
+                ast::GlobalDeclKind::Var(_) => Item::Var,
+                ast::GlobalDeclKind::Const(_) => Item::Const,
+                ast::GlobalDeclKind::Override(_) => Item::Override,
+                ast::GlobalDeclKind::Struct(_) => Item::Struct,
+                ast::GlobalDeclKind::Type(_) => Item::Alias,
+                ast::GlobalDeclKind::ConstAssert(_) => Item::ConstAssert,
+            };
+            self.start_item(&mut lines, &mut previous, item, start(span));
+            let doc = self.global_decl(&decl.kind, span);
+            lines.push(doc);
+        }
+
+        self.leading_comments(usize::MAX, &mut lines, true);
+        concat(lines.docs)
+    }
+
+    /// Add the comments before a top-level item of kind `item` that starts
+    /// at `start`, and the blank line that separates it from the one before.
+    fn start_item(
+        &mut self,
+        lines: &mut Lines<'a>,
+        previous: &mut Option<Item>,
+        item: Item,
+        start: usize,
+    ) {
+        let blank = self.leading_comments(start, lines, previous.is_some());
+        let separate = match *previous {
+            Some(previous) => previous != item || matches!(item, Item::Fn | Item::Struct),
+            None => false,
+        };
+        if separate && !blank {
+            lines.blank();
+        }
+        *previous = Some(item);
+    }
+
+    fn global_decl(&mut self, kind: &ast::GlobalDeclKind<'a>, span: Span) -> Doc<'a> {
+        let mut attributes = self.conditions(start(span));
+        match *kind {
+            ast::GlobalDeclKind::Fn(ref function) => self.function(function, attributes),
+            ast::GlobalDeclKind::Var(ref var) => {
+                if let Some(ref binding) = var.binding {
+                    attributes.push(self.attribute("group", binding.group));
+                    attributes.push(self.attribute("binding", binding.binding));
+                }
+                let mut docs = inline(attributes);
+                docs.push(text(format!("var{} ", address_space(var.space))));
+                docs.push(text(var.name.name));
+                docs.push(text(": "));
+                docs.push(self.ty(var.ty));
+                if let Some(init) = var.init {
+                    docs.push(text(" = "));
+                    docs.push(self.expression(init));
+                }
+                docs.push(text(";"));
+                concat(docs)
+            }
+            ast::GlobalDeclKind::Const(ref c) => {
+                let mut docs = inline(attributes);
+                docs.push(text("const "));
+                docs.extend(self.declaration(c.name, c.ty, Some(c.init)));
+                concat(docs)
+            }
+            ast::GlobalDeclKind::Override(ref o) => {
+                if let Some(id) = o.id {
+                    attributes.push(self.attribute("id", id));
+                }
+                let mut docs = inline(attributes);
+                docs.push(text("override "));
+                docs.extend(self.declaration(o.name, o.ty, o.init));
+                concat(docs)
+            }
+            ast::GlobalDeclKind::Struct(ref s) => {
+                let mut lines = Lines::new(true);
+                for (i, member) in s.members.iter().enumerate() {
+                    let member_start = start(member.name.span);
+                    self.leading_comments(member_start, &mut lines, i > 0);
+                    let mut attributes = self.conditions(member_start);
+                    if let Some(ref binding) = member.binding {
+                        attributes.extend(self.binding(binding));
+                    }
+                    if let Some(align) = member.align {
+                        attributes.push(self.attribute("align", align));
+                    }
+                    if let Some(size) = member.size {
+                        attributes.push(self.attribute("size", size));
+                    }
+                    let mut docs = inline(attributes);
+                    docs.push(text(member.name.name));
+                    docs.push(text(": "));
+                    docs.push(self.ty(member.ty));
+                    docs.push(text(","));
+                    lines.push(concat(docs));
+                }
+                if let Some(close) = closing_brace(span) {
+                    self.leading_comments(close, &mut lines, false);
+                }
+                let mut docs = inline(attributes);
+                docs.push(text(format!("struct {} ", s.name.name)));
+                docs.push(lines.braces());
+                concat(docs)
+            }
+            ast::GlobalDeclKind::Type(ref alias) => {
+                let mut docs = inline(attributes);
+                docs.push(text(format!("alias {} = ", alias.name.name)));
+                docs.push(self.ty(alias.ty));
+                docs.push(text(";"));
+                concat(docs)
+            }
+            ast::GlobalDeclKind::ConstAssert(condition) => {
+                let mut docs = inline(attributes);
+                docs.push(text("const_assert "));
+                docs.push(self.expression(condition));
+                docs.push(text(";"));
+                concat(docs)
+            }
+        }
+    }
+
+    /// Return the rest of a `const`, `override` or `let` declaration, after
+    /// the keyword.
+    fn declaration(
+        &mut self,
+        name: ast::Ident<'a>,
+        ty: Option<Handle<ast::Type<'a>>>,
+        init: Option<Handle<ast::Expression<'a>>>,
+    ) -> Vec<Doc<'a>> {
+        let mut docs = vec![text(name.name)];
+        if let Some(ty) = ty {
+            docs.push(text(": "));
+            docs.push(self.ty(ty));
+        }
+        if let Some(init) = init {
+            docs.push(text(" = "));
+            docs.push(self.expression(init));
+        }
+        docs.push(text(";"));
+        docs
+    }
+
+    fn function(&mut self, function: &ast::Function<'a>, mut attributes: Vec<Doc<'a>>) -> Doc<'a> {
+        if let Some(ref entry_point) = function.entry_point {
+            attributes.push(text(match entry_point.stage {
+                crate::ShaderStage::Vertex => "@vertex",
+                crate::ShaderStage::Fragment => "@fragment",
+                crate::ShaderStage::Compute => "@compute",
+            }));
+            if let Some(ref workgroup_size) = entry_point.workgroup_size {
+                let sizes = workgroup_size
+                    .iter()
+                    .flatten()
+                    .map(|&size| (self.expression(size), concat(Vec::new())))
+                    .collect();
+                attributes.push(concat(vec![text("@workgroup_size"), list("(", sizes, ")")]));
+            }
+            if let Some(early_depth_test) = entry_point.early_depth_test {
+                attributes.push(text(match early_depth_test.conservative {
+                    None => "@early_depth_test",
+                    Some(crate::ConservativeDepth::GreaterEqual) => {
+                        "@early_depth_test(greater_equal)"
+                    }
+                    Some(crate::ConservativeDepth::LessEqual) => "@early_depth_test(less_equal)",
+                    Some(crate::ConservativeDepth::Unchanged) => "@early_depth_test(unchanged)",
+                }));
+            }
+        }
+        attributes.extend(self.diagnostic_attributes(
+            function.diagnostic_filter_leaf,
+            self.tu.diagnostic_filter_leaf,
+        ));
+
+        let mut docs = Vec::new();
+        for (i, attribute) in attributes.into_iter().enumerate() {
+            if i > 0 {
+                docs.push(text(" "));
+            }
+            docs.push(attribute);
+        }
+        if !docs.is_empty() {
+            docs.push(Doc::HardLine);
+        }
+
+        let mut arguments = Vec::new();
+        for (i, argument) in function.arguments.iter().enumerate() {
+            let mut argument_docs = vec![self.item_comments(start(argument.name.span))];
+            if let Some(ref binding) = argument.binding {
+                argument_docs.extend(inline(self.binding(binding)));
+            }
+            argument_docs.push(text(argument.name.name));
+            argument_docs.push(text(": "));
+            argument_docs.push(self.ty(argument.ty));
+            let comments = match function.arguments.get(i + 1) {
+                Some(next) => self.trailing_comments(start(next.name.span)),
+                None => concat(Vec::new()),
+            };
+            arguments.push((concat(argument_docs), comments));
+        }
+        docs.push(text(format!("fn {}", function.name.name)));
+        docs.push(list("(", arguments, ")"));
+        if let Some(ref result) = function.result {
+            docs.push(text(" -> "));
+            if let Some(ref binding) = result.binding {
+                docs.extend(inline(self.binding(binding)));
+            }
+            docs.push(self.ty(result.ty));
+        }
+        docs.push(text(" "));
+        docs.push(self.braces(
+            &function.body.stmts,
+            function.diagnostic_filter_leaf,
+            closing_brace(function.body.span),
+        ));
+        concat(docs)
+    }
+
+    /// Return `@name(value)`.
+    fn attribute(&mut self, name: &str, value: Handle<ast::Expression<'a>>) -> Doc<'a> {
+        concat(vec![
+            text(format!("@{name}(")),
+            self.expression(value),
+            text(")"),
+        ])
+    }
+
+    /// Return the `@if` attributes on the item that starts at `start`, after
+    /// its attributes.
+    fn conditions(&mut self, start: usize) -> Vec<Doc<'a>> {
+        let tu = self.tu;
+        let Some(conditions) = tu.conditions.get(&start) else {
+            return Vec::new();
+        };
+        conditions
+            .iter()
+            .map(|&condition| self.attribute("if", condition))
+            .collect()
+    }
+
+    /// Return the `@diagnostic(…)` attributes that add the rules from `leaf`
+    /// up to, but not including, `base`.
+    fn diagnostic_attributes(
+        &self,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+        base: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Vec<Doc<'a>> {
+        let mut attributes = Vec::new();
+        let mut next = leaf;
+        while let Some(handle) = next.filter(|&handle| Some(handle) != base) {
+            let node = &self.tu.diagnostic_filters[handle];
+            attributes.push(text(format!(
+                "@diagnostic{}",
+                diagnostic_filter(&node.inner)
+            )));
+            next = node.parent;
+        }
+        attributes.reverse();
+        attributes
+    }
+
+    fn binding(&mut self, binding: &ast::Binding<'a>) -> Vec<Doc<'a>> {
+        match *binding {
+            ast::Binding::BuiltIn(built_in_value) => {
+                let mut attributes = vec![text(format!("@builtin({})", built_in(built_in_value)))];
+                if let crate::BuiltIn::Position { invariant: true } = built_in_value {
+                    attributes.push(text("@invariant"));
+                }
+                attributes
+            }
+            ast::Binding::Location {
+                location,
+                second_blend_source,
+                interpolation,
+                sampling,
+            } => {
+                let mut attributes = vec![self.attribute("location", location)];
+                if second_blend_source {
+                    attributes.push(text("@second_blend_source"));
+                }
+                if let Some(interpolation) = interpolation {
+                    let interpolation = match interpolation {
+                        crate::Interpolation::Perspective => "perspective",
+                        crate::Interpolation::Linear => "linear",
+                        crate::Interpolation::Flat => "flat",
+                    };
+                    let sampling = match sampling {
+                        None => "",
+                        Some(crate::Sampling::Center) => ", center",
+                        Some(crate::Sampling::Centroid) => ", centroid",
+                        Some(crate::Sampling::Sample) => ", sample",
+                    };
+                    attributes.push(text(format!("@interpolate({interpolation}{sampling})")));
+                }
+                attributes
+            }
+        }
+    }
+
+    /// Return `stmts` as the body of a `{ … }` block, with the comments
+    /// before `close`, its `}`.
+    fn braces(
+        &mut self,
+        stmts: &[ast::Statement<'a>],
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+        close: Option<usize>,
+    ) -> Doc<'a> {
+        let mut lines = Lines::new(true);
+        self.statements(stmts, leaf, &mut lines);
+        if let Some(close) = close {
+            self.leading_comments(close, &mut lines, false);
+        }
+        lines.braces()
+    }
+
+    /// Return `block`, with any `@diagnostic(…)` attributes before its `{`.
+    ///
+    /// The diagnostic filter rules in `leaf` are the ones that apply where
+    /// the block is, so only those `block` adds are printed.
+    fn block(
+        &mut self,
+        block: &ast::Block<'a>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let mut docs = inline(self.diagnostic_attributes(block.diagnostic_filter_leaf, leaf));
+        docs.push(self.braces(
+            &block.stmts,
+            block.diagnostic_filter_leaf.or(leaf),
+            closing_brace(block.span),
+        ));
+        concat(docs)
+    }
+
+    /// Add `stmts` to `lines`, each with the comments before it.
+    fn statements(
+        &mut self,
+        stmts: &[ast::Statement<'a>],
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+        lines: &mut Lines<'a>,
+    ) {
+        let mut rest = stmts;
+        let mut first = true;
+        while let Some((mut stmt, mut tail)) = rest.split_first() {
+            // A `for` loop's initializer is the statement before the loop.
+            let mut init = None;
+            if let Some((next, next_tail)) = tail.split_first() {
+                if let ast::StatementKind::Loop {
+                    syntax: ast::LoopSyntax::For { init: true },
+                    ..
+                } = next.kind
+                {
+                    init = Some(stmt);
+                    (stmt, tail) = (next, next_tail);
+                }
+            }
+            rest = tail;
+
+            self.leading_comments(start(stmt.span), lines, !first);
+            first = false;
+            let doc = self.statement(stmt, init, leaf);
+            lines.push(doc);
+        }
+    }
+
+    /// Return `stmt`, which is the loop of a `for` statement if `init` is
+    /// its initializer.
+    fn statement(
+        &mut self,
+        stmt: &ast::Statement<'a>,
+        init: Option<&ast::Statement<'a>>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let mut docs = inline(self.conditions(start(stmt.span)));
+        let doc = match stmt.kind {
+            // The block the parser wraps a statement in to apply its
+            // `@diagnostic(…)` attributes, or to keep a `for` loop's
+            // initializer in scope of only the loop.
+            ast::StatementKind::Block(ref block) if !block.span.is_defined() => {
+                docs.extend(inline(
+                    self.diagnostic_attributes(block.diagnostic_filter_leaf, leaf),
+                ));
+                let leaf = block.diagnostic_filter_leaf.or(leaf);
+                match block.stmts[..] {
+                    [ref init, ref stmt] => self.statement(stmt, Some(init), leaf),
+                    [ref stmt] => self.statement(stmt, None, leaf),
+                    _ => unreachable!(),
+                }
+            }
+            ast::StatementKind::Block(ref block) => self.block(block, leaf),
+            ast::StatementKind::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => self.if_statement(condition, accept, reject, leaf),
+            ast::StatementKind::Switch {
+                selector,
+                ref cases,
+                diagnostic_filter_leaf,
+            } => self.switch(stmt.span, selector, cases, diagnostic_filter_leaf, leaf),
+            ast::StatementKind::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+                syntax,
+            } => match syntax {
+                ast::LoopSyntax::Loop => {
+                    self.loop_statement(stmt.span, body, continuing, break_if, leaf)
+                }
+                ast::LoopSyntax::While => {
+                    let (condition, body) = loop_body(body);
+                    let condition = condition.expect("`while` loops have a condition");
+                    concat(vec![
+                        text("while "),
+                        self.expression(condition),
+                        text(" "),
+                        self.block(body, leaf),
+                    ])
+                }
+                ast::LoopSyntax::For { .. } => self.for_statement(init, body, continuing, leaf),
+            },
+            ast::StatementKind::Break => text("break;"),
+            ast::StatementKind::Continue => text("continue;"),
+            ast::StatementKind::Kill => text("discard;"),
+            ast::StatementKind::Return { value: None } => text("return;"),
+            ast::StatementKind::Return { value: Some(value) } => {
+                concat(vec![text("return "), self.expression(value), text(";")])
+            }
+            ast::StatementKind::ConstAssert(condition) => concat(vec![
+                text("const_assert "),
+                self.expression(condition),
+                text(";"),
+            ]),
+            ast::StatementKind::LocalDecl(_)
+            | ast::StatementKind::Call { .. }
+            | ast::StatementKind::Assign { .. }
+            | ast::StatementKind::Increment(_)
+            | ast::StatementKind::Decrement(_)
+            | ast::StatementKind::Ignore(_) => concat(vec![self.simple_statement(stmt), text(";")]),
+        };
+        docs.push(doc);
+        concat(docs)
+    }
+
+    /// Return a statement that can appear in a `for` loop's header, without
+    /// the `;` that ends it elsewhere.
+    fn simple_statement(&mut self, stmt: &ast::Statement<'a>) -> Doc<'a> {
+        match stmt.kind {
+            ast::StatementKind::LocalDecl(ast::LocalDecl::Let(ref l)) => {
+                let mut docs = vec![text("let ")];
+                docs.extend(self.declaration(l.name, l.ty, Some(l.init)));
+                docs.pop();
+                concat(docs)
+            }
+            ast::StatementKind::LocalDecl(ast::LocalDecl::Var(ref v)) => {
+                let mut docs = vec![text("var ")];
+                docs.extend(self.declaration(v.name, v.ty, v.init));
+                docs.pop();
+                concat(docs)
+            }
+            ast::StatementKind::Call {
+                ref function,
+                ref arguments,
+            } => concat(vec![text(function.name), self.arguments(arguments)]),
+            ast::StatementKind::Assign { target, op, value } => {
+                let op = match op {
+                    Some(op) => format!(" {}= ", binary_operator(op)),
+                    None => " = ".to_string(),
+                };
+                concat(vec![
+                    self.expression(target),
+                    text(op),
+                    self.expression(value),
+                ])
+            }
+            ast::StatementKind::Increment(target) => {
+                concat(vec![self.expression(target), text("++")])
+            }
+            ast::StatementKind::Decrement(target) => {
+                concat(vec![self.expression(target), text("--")])
+            }
+            ast::StatementKind::Ignore(value) => concat(vec![text("_ = "), self.expression(value)]),
+            _ => unreachable!(),
+        }
+    }
+
+    fn if_statement(
+        &mut self,
+        condition: Handle<ast::Expression<'a>>,
+        accept: &ast::Block<'a>,
+        reject: &ast::Block<'a>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let mut docs = vec![
+            text("if "),
+            self.expression(condition),
+            text(" "),
+            self.block(accept, leaf),
+        ];
+        if reject.span.is_defined() {
+            docs.push(text(" else "));
+            docs.push(self.block(reject, leaf));
+        } else if let [ast::Statement {
+            kind:
+                ast::StatementKind::If {
+                    condition,
+                    ref accept,
+                    ref reject,
+                },
+            ..
+        }] = reject.stmts[..]
+        {
+            // The parser puts an `else if` in a block of its own.
+            docs.push(text(" else "));
+            docs.push(self.if_statement(condition, accept, reject, leaf));
+        }
+        concat(docs)
+    }
+
+    fn switch(
+        &mut self,
+        span: Span,
+        selector: Handle<ast::Expression<'a>>,
+        cases: &[ast::SwitchCase<'a>],
+        switch_leaf: Option<Handle<DiagnosticFilterNode>>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let mut docs = vec![text("switch "), self.expression(selector), text(" ")];
+        docs.extend(inline(self.diagnostic_attributes(switch_leaf, leaf)));
+        let leaf = switch_leaf.or(leaf);
+
+        let mut lines = Lines::new(true);
+        // The values of a clause, which the parser splits into a case for
+        // each that falls through to the next.
+        let mut values = Vec::new();
+        for case in cases {
+            values.push(&case.value);
+            if case.fall_through {
+                continue;
+            }
+
+            let case_start = match *values[0] {
+                ast::SwitchValue::Expr(value) => start(self.tu.expressions.get_span(value)),
+                ast::SwitchValue::Default => start(case.body.span),
+            };
+            let first = lines.docs.is_empty();
+            self.leading_comments(case_start, &mut lines, !first);
+
+            let mut case_docs = Vec::new();
+            if let [&ast::SwitchValue::Default] = values[..] {
+                case_docs.push(text("default "));
+            } else {
+                case_docs.push(text("case "));
+                for (i, value) in values.drain(..).enumerate() {
+                    if i > 0 {
+                        case_docs.push(text(", "));
+                    }
+                    case_docs.push(match *value {
+                        ast::SwitchValue::Expr(value) => self.expression(value),
+                        ast::SwitchValue::Default => text("default"),
+                    });
+                }
+                case_docs.push(text(" "));
+            }
+            values.clear();
+            case_docs.push(self.block(&case.body, leaf));
+            lines.push(concat(case_docs));
+        }
+        if let Some(close) = closing_brace(span) {
+            self.leading_comments(close, &mut lines, false);
+        }
+        docs.push(lines.braces());
+        concat(docs)
+    }
+
+    fn loop_statement(
+        &mut self,
+        span: Span,
+        body: &ast::Block<'a>,
+        continuing: &ast::Block<'a>,
+        break_if: Option<Handle<ast::Expression<'a>>>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let mut docs = vec![text("loop ")];
+        docs.extend(inline(
+            self.diagnostic_attributes(body.diagnostic_filter_leaf, leaf),
+        ));
+        let leaf = body.diagnostic_filter_leaf.or(leaf);
+
+        let mut lines = Lines::new(true);
+        self.statements(&body.stmts, leaf, &mut lines);
+        if continuing.span.is_defined() {
+            self.leading_comments(start(continuing.span), &mut lines, !body.stmts.is_empty());
+            let mut continuing_docs = vec![text("continuing ")];
+            continuing_docs.extend(inline(
+                self.diagnostic_attributes(continuing.diagnostic_filter_leaf, leaf),
+            ));
+            let continuing_leaf = continuing.diagnostic_filter_leaf.or(leaf);
+
+            let mut continuing_lines = Lines::new(true);
+            self.statements(&continuing.stmts, continuing_leaf, &mut continuing_lines);
+            if let Some(condition) = break_if {
+                let condition_start = start(self.tu.expressions.get_span(condition));
+                self.leading_comments(
+                    condition_start,
+                    &mut continuing_lines,
+                    !continuing.stmts.is_empty(),
+                );
+                let doc = concat(vec![
+                    text("break if "),
+                    self.expression(condition),
+                    text(";"),
+                ]);
+                continuing_lines.push(doc);
+            }
+            if let Some(close) = closing_brace(continuing.span) {
+                self.leading_comments(close, &mut continuing_lines, false);
+            }
+            continuing_docs.push(continuing_lines.braces());
+            lines.push(concat(continuing_docs));
+        }
+        if let Some(close) = closing_brace(span) {
+            self.leading_comments(close, &mut lines, false);
+        }
+        docs.push(lines.braces());
+        concat(docs)
+    }
+
+    fn for_statement(
+        &mut self,
+        init: Option<&ast::Statement<'a>>,
+        body: &ast::Block<'a>,
+        continuing: &ast::Block<'a>,
+        leaf: Option<Handle<DiagnosticFilterNode>>,
+    ) -> Doc<'a> {
+        let (condition, body) = loop_body(body);
+        let mut docs = vec![text("for (")];
+        if let Some(init) = init {
+            docs.push(self.simple_statement(init));
+        }
+        docs.push(text(";"));
+        if let Some(condition) = condition {
+            docs.push(text(" "));
+            docs.push(self.expression(condition));
+        }
+        docs.push(text(";"));
+        if let Some(update) = continuing.stmts.first() {
+            docs.push(text(" "));
+            docs.push(self.simple_statement(update));
+        }
+        docs.push(text(") "));
+        docs.push(self.block(body, leaf));
+        concat(docs)
+    }
+
+    fn ty(&mut self, handle: Handle<ast::Type<'a>>) -> Doc<'a> {
+        let tu = self.tu;
+        let spelling = &self.source[tu.types.get_span(handle)];
+        match tu.types[handle] {
+            ast::Type::Scalar(scalar) => text(scalar.to_wgsl()),
+            ast::Type::Vector { size, scalar } => alias_or(spelling, || {
+                format!("vec{}<{}>", size as u32, scalar.to_wgsl())
+            }),
+            ast::Type::Matrix {
+                columns,
+                rows,
+                width,
+            } => alias_or(spelling, || {
+                let scalar = crate::Scalar::float(width);
+                format!(
+                    "mat{}x{}<{}>",
+                    columns as u32,
+                    rows as u32,
+                    scalar.to_wgsl()
+                )
+            }),
+            ast::Type::Atomic(scalar) => text(format!("atomic<{}>", scalar.to_wgsl())),
+            ast::Type::Pointer { base, space } => {
+                let space = address_space(space);
+                // Put the base type in place of the template's first comma,
+                // or its `>`.
+                let split = space.find(',').unwrap_or(space.len() - 1);
+                concat(vec![
+                    text(format!("ptr{}, ", &space[..split])),
+                    self.ty(base),
+                    text(space[split..].to_string()),
+                ])
+            }
+            ast::Type::Array { base, size } => self.array_type("array", base, size),
+            ast::Type::BindingArray { base, size } => self.array_type("binding_array", base, size),
+            ast::Type::Image {
+                dim,
+                arrayed,
+                class,
+            } => text(image_type(dim, arrayed, class)),
+            ast::Type::Sampler { comparison: false } => text("sampler"),
+            ast::Type::Sampler { comparison: true } => text("sampler_comparison"),
+            ast::Type::AccelerationStructure => text("acceleration_structure"),
+            ast::Type::RayQuery => text("ray_query"),
+            ast::Type::RayDesc => text("RayDesc"),
+            ast::Type::RayIntersection => text("RayIntersection"),
+            ast::Type::User(ref name) => text(name.name),
+        }
+    }
+
+    fn array_type(
+        &mut self,
+        name: &'static str,
+        base: Handle<ast::Type<'a>>,
+        size: ast::ArraySize<'a>,
+    ) -> Doc<'a> {
+        let mut docs = vec![text(name), text("<"), self.ty(base)];
+        if let ast::ArraySize::Constant(size) = size {
+            docs.push(text(", "));
+            docs.push(self.operand(size, Precedence::Unary));
+        }
+        docs.push(text(">"));
+        concat(docs)
+    }
+
+    fn constructor_type(&mut self, ty: &ast::ConstructorType<'a>, span: Span) -> Doc<'a> {
+        let spelling = &self.source[span];
+        match *ty {
+            ast::ConstructorType::Scalar(scalar) => text(scalar.to_wgsl()),
+            ast::ConstructorType::PartialVector { size } => text(format!("vec{}", size as u32)),
+            ast::ConstructorType::Vector { size, scalar } => alias_or(spelling, || {
+                format!("vec{}<{}>", size as u32, scalar.to_wgsl())
+            }),
+            ast::ConstructorType::PartialMatrix { columns, rows } => {
+                text(format!("mat{}x{}", columns as u32, rows as u32))
+            }
+            ast::ConstructorType::Matrix {
+                columns,
+                rows,
+                width,
+            } => alias_or(spelling, || {
+                let scalar = crate::Scalar::float(width);
+                format!(
+                    "mat{}x{}<{}>",
+                    columns as u32,
+                    rows as u32,
+                    scalar.to_wgsl()
+                )
+            }),
+            ast::ConstructorType::PartialArray => text("array"),
+            ast::ConstructorType::Array { base, size } => self.array_type("array", base, size),
+            // Only lowering makes these.
+            ast::ConstructorType::Type(_) => unreachable!(),
+        }
+    }
+
+    /// Return `arguments` as a parenthesized list.
+    fn arguments(&mut self, arguments: &[Handle<ast::Expression<'a>>]) -> Doc<'a> {
+        let tu = self.tu;
+        let mut items = Vec::with_capacity(arguments.len());
+        for (i, &argument) in arguments.iter().enumerate() {
+            let argument_start = start(tu.expressions.get_span(argument));
+            let item = concat(vec![
+                self.item_comments(argument_start),
+                self.expression(argument),
+            ]);
+            let comments = match arguments.get(i + 1) {
+                Some(&next) => self.trailing_comments(start(tu.expressions.get_span(next))),
+                None => concat(Vec::new()),
+            };
+            items.push((item, comments));
+        }
+        list("(", items, ")")
+    }
+
+    fn precedence(&self, handle: Handle<ast::Expression<'a>>) -> Precedence {
+        match self.tu.expressions[handle] {
+            ast::Expression::Binary { op, .. } => binary_precedence(op),
+            ast::Expression::Unary { .. }
+            | ast::Expression::AddrOf(_)
+            | ast::Expression::Deref(_) => Precedence::Unary,
+            _ => Precedence::Postfix,
+        }
+    }
+
+    /// Return `handle`, parenthesized if it binds more loosely than
+    /// `loosest`.
+    fn operand(&mut self, handle: Handle<ast::Expression<'a>>, loosest: Precedence) -> Doc<'a> {
+        if self.precedence(handle) >= loosest {
+            self.expression(handle)
+        } else {
+            self.parenthesized(handle)
+        }
+    }
+
+    fn parenthesized(&mut self, handle: Handle<ast::Expression<'a>>) -> Doc<'a> {
+        concat(vec![text("("), self.expression(handle), text(")")])
+    }
+
+    fn expression(&mut self, handle: Handle<ast::Expression<'a>>) -> Doc<'a> {
+        let tu = self.tu;
+        match tu.expressions[handle] {
+            // Literals keep their spelling, and local names are only known by
+            // where they're used.
+            ast::Expression::Literal(_) | ast::Expression::Ident(ast::IdentExpr::Local(_)) => {
+                text(&self.source[tu.expressions.get_span(handle)])
+            }
+            ast::Expression::Ident(ast::IdentExpr::Unresolved(name)) => text(name),
+            ast::Expression::Construct {
+                ref ty,
+                ty_span,
+                ref components,
+            } => concat(vec![
+                self.constructor_type(ty, ty_span),
+                self.arguments(components),
+            ]),
+            ast::Expression::Unary { op, expr } => self.prefix(unary_operator(op), expr),
+            ast::Expression::AddrOf(expr) => self.prefix("&", expr),
+            ast::Expression::Deref(expr) => self.prefix("*", expr),
+            ast::Expression::Binary { op, left, right } => self.binary(op, left, right),
+            ast::Expression::Call {
+                ref function,
+                ref arguments,
+            } => concat(vec![text(function.name), self.arguments(arguments)]),
+            ast::Expression::Index { base, index } => concat(vec![
+                self.operand(base, Precedence::Postfix),
+                text("["),
+                self.expression(index),
+                text("]"),
+            ]),
+            ast::Expression::Member { base, ref field } => concat(vec![
+                self.operand(base, Precedence::Postfix),
+                text("."),
+                text(field.name),
+            ]),
+            ast::Expression::Bitcast { expr, to, .. } => concat(vec![
+                text("bitcast<"),
+                self.ty(to),
+                text(">("),
+                self.expression(expr),
+                text(")"),
+            ]),
+        }
+    }
+
+    /// Return the unary operator `op` applied to `operand`.
+    fn prefix(&mut self, op: &'static str, operand: Handle<ast::Expression<'a>>) -> Doc<'a> {
+        // `-(-x)` and `&(&x)` would lex differently without parentheses.
+        let doubled = match self.tu.expressions[operand] {
+            ast::Expression::Unary {
+                op: crate::UnaryOperator::Negate,
+                ..
+            } => op == "-",
+            ast::Expression::AddrOf(_) => op == "&",
+            _ => false,
+        };
+        let operand = if doubled {
+            self.parenthesized(operand)
+        } else {
+            self.operand(operand, Precedence::Unary)
+        };
+        concat(vec![text(op), operand])
+    }
+
+    /// Return a binary expression, breaking before its operator if it doesn't
+    /// fit on one line.
+    ///
+    /// A chain like `a + b - c` breaks before all its operators at once.
+    fn binary(
+        &mut self,
+        op: crate::BinaryOperator,
+        left: Handle<ast::Expression<'a>>,
+        right: Handle<ast::Expression<'a>>,
+    ) -> Doc<'a> {
+        let precedence = binary_precedence(op);
+        let mut chain = vec![(op, right)];
+        let mut first = left;
+        while let ast::Expression::Binary { op, left, right } = self.tu.expressions[first] {
+            if binary_precedence(op) != precedence || !is_operand(precedence, precedence, true) {
+                break;
+            }
+            chain.push((op, right));
+            first = left;
+        }
+
+        let first = if is_operand(precedence, self.precedence(first), true) {
+            self.expression(first)
+        } else {
+            self.parenthesized(first)
+        };
+        let mut rest = Vec::new();
+        for (op, right) in chain.into_iter().rev() {
+            rest.push(Doc::Line);
+            rest.push(text(format!("{} ", binary_operator(op))));
+            rest.push(if is_operand(precedence, self.precedence(right), false) {
+                self.expression(right)
+            } else {
+                self.parenthesized(right)
+            });
+        }
+        group(concat(vec![first, indent(concat(rest))]))
+    }
+}
+
+/// Return a `while` or `for` loop's condition, if it has one, and the block
+/// with the rest of its body.
+fn loop_body<'b, 'a>(
+    body: &'b ast::Block<'a>,
+) -> (Option<Handle<ast::Expression<'a>>>, &'b ast::Block<'a>) {
+    match body.stmts[..] {
+        [ast::Statement {
+            kind: ast::StatementKind::If { condition, .. },
+            ..
+        }, ast::Statement {
+            kind: ast::StatementKind::Block(ref block),
+            ..
+        }] => (Some(condition), block),
+        [ast::Statement {
+            kind: ast::StatementKind::Block(ref block),
+            ..
+        }] => (None, block),
+        _ => unreachable!(),
+    }
+}
+
+/// Return `spelling` if it names a vector or matrix type by one of its
+/// predeclared aliases, like `vec3f`, and `generic()` otherwise.
+fn alias_or<'a>(spelling: &'a str, generic: impl FnOnce() -> String) -> Doc<'a> {
+    if spelling.contains('<') {
+        text(generic())
+    } else {
+        text(spelling)
+    }
+}
+
+fn join(names: &[ast::Ident<'_>]) -> String {
+    names
+        .iter()
+        .map(|name| name.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
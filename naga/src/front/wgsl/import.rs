@@ -0,0 +1,78 @@
+/*!
+Support for WGSL modules split across several files with `import` declarations.
+
+An `import` declaration names another file whose module-scope declarations
+should be visible in the importing file:
+
+```wgsl
+import "lighting.wgsl";
+
+@fragment
+fn main() -> @location(0) vec4<f32> {
+    return shade(ambient);
+}
+```
+
+Imports must come after any directives and before any other declarations.
+The path is handed to an [`ImportResolver`], which supplies the imported
+file's text. Every file reached this way is parsed, and all of their
+declarations are lowered into a single [`Module`].
+
+Each file sees its own declarations, plus those of the files it imports
+directly. A file's own declarations shadow imported ones, and using a name
+that two imported files both declare is an error. Declarations from
+different files may share a name; in the [`Module`], such names are made
+unique with a [`Namer`].
+
+[`Module`]: crate::Module
+[`Namer`]: crate::proc::Namer
+*/
+
+use crate::front::wgsl::error::{Error, ParseError};
+use crate::front::wgsl::parse::lexer::Lexer;
+use crate::SourceMap;
+use std::sync::Arc;
+
+/// Supplies the text of files named in WGSL `import` declarations.
+///
+/// Imports must come after any directives and before any other
+/// declarations. Each file sees its own declarations, plus those of the
+/// files it imports directly. A file's own declarations shadow imported
+/// ones, and using a name that two imported files both declare is an error.
+///
+/// See [`Frontend::parse_with_imports`].
+///
+/// [`Frontend::parse_with_imports`]: super::Frontend::parse_with_imports
+pub trait ImportResolver {
+    /// Find the file that `path` refers to, when imported by the file named
+    /// `importer`.
+    ///
+    /// The returned [`ResolvedImport::name`] decides whether two imports
+    /// refer to the same file, so it should be something like a canonical
+    /// path. On failure, return a message explaining why; it is reported at
+    /// the `import` declaration.
+    fn resolve(&mut self, importer: &str, path: &str) -> Result<ResolvedImport, String>;
+}
+
+/// A file found by an [`ImportResolver`].
+#[derive(Clone, Debug)]
+pub struct ResolvedImport {
+    /// The name of the file, used to identify it and to label errors in it.
+    pub name: String,
+    /// The WGSL source text of the file.
+    pub source: String,
+}
+
+impl SourceMap {
+    pub(super) fn lexer(&self, file: usize) -> Lexer<'_> {
+        Lexer::new_in(self.text(), self.files()[file].range())
+    }
+
+    /// Convert `error`, which occurred in one of these files, to a
+    /// [`ParseError`] that can render itself against the right file.
+    pub(super) fn parse_error<'a>(&'a self, error: &Error<'a>) -> ParseError {
+        error
+            .as_parse_error(self.text())
+            .with_source_map(Arc::new(self.clone()))
+    }
+}
@@ -5,6 +5,10 @@ use crate::{FastHashMap, Handle, Span};
 /// A `GlobalDecl` list in which each definition occurs before all its uses.
 pub struct Index<'a> {
     dependency_order: Vec<Handle<ast::GlobalDecl<'a>>>,
+
+    /// For each source file, the module-scope declarations visible in it,
+    /// by name.
+    scopes: Vec<FastHashMap<&'a str, Handle<ast::GlobalDecl<'a>>>>,
 }
 
 impl<'a> Index<'a> {
@@ -16,23 +20,57 @@ impl<'a> Index<'a> {
     /// Return an error if the graph of references between declarations contains
     /// any cycles.
     pub fn generate(tu: &ast::TranslationUnit<'a>) -> Result<Self, Error<'a>> {
-        // Produce a map from global definitions' names to their `Handle<GlobalDecl>`s.
-        // While doing so, reject conflicting definitions.
-        let mut globals = FastHashMap::with_capacity_and_hasher(tu.decls.len(), Default::default());
+        // Produce a map for each file from its global definitions' names to
+        // their `Handle<GlobalDecl>`s. While doing so, reject conflicting
+        // definitions.
+        let file_count = tu.imports.len().max(1);
+        let mut scopes: Vec<FastHashMap<_, _>> =
+            (0..file_count).map(|_| FastHashMap::default()).collect();
         for (handle, decl) in tu.decls.iter() {
-            let ident = decl_ident(decl);
-            let name = ident.name;
-            if let Some(old) = globals.insert(name, handle) {
-                return Err(Error::Redefinition {
-                    previous: decl_ident(&tu.decls[old]).span,
-                    current: ident.span,
-                });
+            if let Some(ident) = decl_ident(decl) {
+                let name = ident.name;
+                if let Some(old) = scopes[decl.file].insert(name, handle) {
+                    return Err(Error::Redefinition {
+                        previous: decl_ident(&tu.decls[old])
+                            .expect("decl should have ident for redefinition")
+                            .span,
+                        current: ident.span,
+                    });
+                }
+            }
+        }
+
+        // Add the definitions each file imports, unless the file defines the
+        // same name itself. Names that more than one import provides are
+        // only an error if they're used.
+        let mut ambiguous: Vec<FastHashMap<_, _>> =
+            (0..file_count).map(|_| FastHashMap::default()).collect();
+        for (file, imports) in tu.imports.iter().enumerate() {
+            for &imported in imports {
+                for (handle, decl) in tu.decls.iter() {
+                    let Some(ident) = decl_ident(decl) else {
+                        continue;
+                    };
+                    if decl.file != imported || scopes[file].get(ident.name) == Some(&handle) {
+                        continue;
+                    }
+                    match scopes[file].get(ident.name) {
+                        None => {
+                            scopes[file].insert(ident.name, handle);
+                        }
+                        Some(&other) if tu.decls[other].file != file => {
+                            ambiguous[file].entry(ident.name).or_insert([other, handle]);
+                        }
+                        Some(_) => {}
+                    }
+                }
             }
         }
 
         let len = tu.decls.len();
         let solver = DependencySolver {
-            globals: &globals,
+            scopes: &scopes,
+            ambiguous: &ambiguous,
             module: tu,
             visited: vec![false; len],
             temp_visited: vec![false; len],
@@ -41,7 +79,16 @@ impl<'a> Index<'a> {
         };
         let dependency_order = solver.solve()?;
 
-        Ok(Self { dependency_order })
+        Ok(Self {
+            dependency_order,
+            scopes,
+        })
+    }
+
+    /// Return the module-scope declaration that `name` refers to in the
+    /// source file `file`, if any.
+    pub fn lookup(&self, file: usize, name: &str) -> Option<Handle<ast::GlobalDecl<'a>>> {
+        self.scopes.get(file)?.get(name).copied()
     }
 
     /// Iterate over `GlobalDecl`s, visiting each definition before all its uses.
@@ -75,8 +122,13 @@ struct ResolvedDependency<'a> {
 /// has one key benefit - it's much more efficient in storing
 /// the path of each node for error generation.
 struct DependencySolver<'source, 'temp> {
-    /// A map from module-scope definitions' names to their handles.
-    globals: &'temp FastHashMap<&'source str, Handle<ast::GlobalDecl<'source>>>,
+    /// For each source file, a map from the names of the module-scope
+    /// definitions visible in it to their handles.
+    scopes: &'temp [FastHashMap<&'source str, Handle<ast::GlobalDecl<'source>>>],
+
+    /// For each source file, the names that more than one of its imports
+    /// define, and two of those definitions.
+    ambiguous: &'temp [FastHashMap<&'source str, [Handle<ast::GlobalDecl<'source>>; 2]>],
 
     /// The translation unit whose declarations we're ordering.
     module: &'temp ast::TranslationUnit<'source>,
@@ -118,7 +170,17 @@ impl<'a> DependencySolver<'a, '_> {
 
         self.temp_visited[id_usize] = true;
         for dep in decl.dependencies.iter() {
-            if let Some(&dep_id) = self.globals.get(dep.ident) {
+            if let Some(candidates) = self.ambiguous[decl.file].get(dep.ident) {
+                return Err(Error::AmbiguousImport {
+                    usage: dep.usage,
+                    candidates: candidates.map(|candidate| {
+                        decl_ident(&self.module.decls[candidate])
+                            .expect("decl should have ident")
+                            .span
+                    }),
+                });
+            }
+            if let Some(&dep_id) = self.scopes[decl.file].get(dep.ident) {
                 self.path.push(ResolvedDependency {
                     decl: dep_id,
                     usage: dep.usage,
@@ -130,7 +192,7 @@ impl<'a> DependencySolver<'a, '_> {
                     return if dep_id == id {
                         // A declaration refers to itself directly.
                         Err(Error::RecursiveDeclaration {
-                            ident: decl_ident(decl).span,
+                            ident: decl_ident(decl).expect("decl should have ident").span,
                             usage: dep.usage,
                         })
                     } else {
@@ -146,14 +208,19 @@ impl<'a> DependencySolver<'a, '_> {
                             .unwrap_or(0);
 
                         Err(Error::CyclicDeclaration {
-                            ident: decl_ident(&self.module.decls[dep_id]).span,
+                            ident: decl_ident(&self.module.decls[dep_id])
+                                .expect("decl should have ident")
+                                .span,
                             path: self.path[start_at..]
                                 .iter()
                                 .map(|curr_dep| {
                                     let curr_id = curr_dep.decl;
                                     let curr_decl = &self.module.decls[curr_id];
 
-                                    (decl_ident(curr_decl).span, curr_dep.usage)
+                                    (
+                                        decl_ident(curr_decl).expect("decl should have ident").span,
+                                        curr_dep.usage,
+                                    )
                                 })
                                 .collect(),
                         })
@@ -182,13 +249,19 @@ impl<'a> DependencySolver<'a, '_> {
     }
 }
 
-const fn decl_ident<'a>(decl: &ast::GlobalDecl<'a>) -> ast::Ident<'a> {
+/// Return the name `decl` introduces, if any.
+///
+/// Only declarations that can be referred to appear in `Index::generate`'s
+/// name map, so dependency edges only ever point at declarations with
+/// names.
+pub const fn decl_ident<'a>(decl: &ast::GlobalDecl<'a>) -> Option<ast::Ident<'a>> {
     match decl.kind {
-        ast::GlobalDeclKind::Fn(ref f) => f.name,
-        ast::GlobalDeclKind::Var(ref v) => v.name,
-        ast::GlobalDeclKind::Const(ref c) => c.name,
-        ast::GlobalDeclKind::Override(ref o) => o.name,
-        ast::GlobalDeclKind::Struct(ref s) => s.name,
-        ast::GlobalDeclKind::Type(ref t) => t.name,
+        ast::GlobalDeclKind::Fn(ref f) => Some(f.name),
+        ast::GlobalDeclKind::Var(ref v) => Some(v.name),
+        ast::GlobalDeclKind::Const(ref c) => Some(c.name),
+        ast::GlobalDeclKind::Override(ref o) => Some(o.name),
+        ast::GlobalDeclKind::Struct(ref s) => Some(s.name),
+        ast::GlobalDeclKind::Type(ref t) => Some(t.name),
+        ast::GlobalDeclKind::ConstAssert(_) => None,
     }
 }
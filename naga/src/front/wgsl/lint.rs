@@ -0,0 +1,222 @@
+//! Warnings about WGSL that is valid, but probably not what was meant.
+//!
+//! These checks only need the AST. Warnings that depend on types, like
+//! abstract-float narrowing, are found during lowering instead.
+
+use crate::front::wgsl::error::Warning;
+use crate::front::wgsl::index;
+use crate::front::wgsl::parse::ast;
+use crate::{Arena, FastHashMap, FastHashSet, Handle, Span};
+
+/// Check every function in `tu` for unused variables, unreachable code and
+/// shadowed declarations.
+pub fn check<'a>(tu: &ast::TranslationUnit<'a>) -> Vec<Warning<'a>> {
+    // The module-scope declarations visible in each source file.
+    let mut module_scopes: Vec<FastHashMap<&'a str, Span>> = (0..tu.imports.len().max(1))
+        .map(|_| FastHashMap::default())
+        .collect();
+    for (_, decl) in tu.decls.iter() {
+        if let Some(ident) = index::decl_ident(decl) {
+            module_scopes[decl.file].insert(ident.name, ident.span);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (_, decl) in tu.decls.iter() {
+        if let ast::GlobalDeclKind::Fn(ref f) = decl.kind {
+            let mut checker = FunctionChecker {
+                expressions: &tu.expressions,
+                scopes: vec![module_scopes[decl.file].clone()],
+                declared: Vec::new(),
+                used: FastHashSet::default(),
+                warnings: &mut warnings,
+            };
+            checker.function(f);
+        }
+    }
+    warnings
+}
+
+struct FunctionChecker<'a, 'temp> {
+    expressions: &'temp Arena<ast::Expression<'a>>,
+
+    /// The names declared in each enclosing scope, outermost first.
+    scopes: Vec<FastHashMap<&'a str, Span>>,
+
+    /// The `var` and `let` declarations seen so far.
+    declared: Vec<(Handle<ast::Local>, ast::Ident<'a>)>,
+
+    /// The locals referred to by some expression.
+    used: FastHashSet<Handle<ast::Local>>,
+
+    warnings: &'temp mut Vec<Warning<'a>>,
+}
+
+impl<'a> FunctionChecker<'a, '_> {
+    fn function(&mut self, f: &ast::Function<'a>) {
+        self.scopes.push(FastHashMap::default());
+        for arg in f.arguments.iter() {
+            self.declare(arg.name);
+        }
+        self.block(&f.body);
+        self.scopes.pop();
+
+        for &(handle, name) in self.declared.iter() {
+            if !self.used.contains(&handle) && !name.name.starts_with('_') {
+                self.warnings.push(Warning::UnusedVariable(name.span));
+            }
+        }
+    }
+
+    fn block(&mut self, block: &ast::Block<'a>) {
+        self.scopes.push(FastHashMap::default());
+        self.statements(block);
+        self.scopes.pop();
+    }
+
+    /// Check `block`'s statements in the current scope.
+    fn statements(&mut self, block: &ast::Block<'a>) {
+        let mut terminator = None;
+        for stmt in block.stmts.iter() {
+            if let Some(terminator) = terminator.take() {
+                let end = block.stmts.last().map_or(stmt.span, |last| last.span);
+                self.warnings.push(Warning::UnreachableCode {
+                    span: stmt.span.until(&end),
+                    terminator,
+                });
+            }
+            self.statement(stmt);
+            if let ast::StatementKind::Return { .. }
+            | ast::StatementKind::Break
+            | ast::StatementKind::Continue
+            | ast::StatementKind::Kill = stmt.kind
+            {
+                terminator = Some(stmt.span);
+            }
+        }
+    }
+
+    fn statement(&mut self, stmt: &ast::Statement<'a>) {
+        match stmt.kind {
+            ast::StatementKind::LocalDecl(ref decl) => {
+                let (name, handle, init) = match *decl {
+                    ast::LocalDecl::Var(ref v) => (v.name, v.handle, v.init),
+                    ast::LocalDecl::Let(ref l) => (l.name, l.handle, Some(l.init)),
+                };
+                if let Some(init) = init {
+                    self.expression(init);
+                }
+                self.declare(name);
+                self.declared.push((handle, name));
+            }
+            ast::StatementKind::Block(ref block) => self.block(block),
+            ast::StatementKind::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => {
+                self.expression(condition);
+                self.block(accept);
+                self.block(reject);
+            }
+            ast::StatementKind::Switch {
+                selector,
+                ref cases,
+                diagnostic_filter_leaf: _,
+            } => {
+                self.expression(selector);
+                for case in cases.iter() {
+                    if let ast::SwitchValue::Expr(value) = case.value {
+                        self.expression(value);
+                    }
+                    self.block(&case.body);
+                }
+            }
+            ast::StatementKind::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+                syntax: _,
+            } => {
+                // The `continuing` block can see the body's declarations.
+                self.scopes.push(FastHashMap::default());
+                self.statements(body);
+                self.scopes.push(FastHashMap::default());
+                self.statements(continuing);
+                if let Some(break_if) = break_if {
+                    self.expression(break_if);
+                }
+                self.scopes.pop();
+                self.scopes.pop();
+            }
+            ast::StatementKind::Break
+            | ast::StatementKind::Continue
+            | ast::StatementKind::Kill
+            | ast::StatementKind::Return { value: None } => {}
+            ast::StatementKind::Return { value: Some(expr) }
+            | ast::StatementKind::Increment(expr)
+            | ast::StatementKind::Decrement(expr)
+            | ast::StatementKind::Ignore(expr)
+            | ast::StatementKind::ConstAssert(expr) => self.expression(expr),
+            ast::StatementKind::Call { ref arguments, .. } => {
+                for &arg in arguments.iter() {
+                    self.expression(arg);
+                }
+            }
+            ast::StatementKind::Assign { target, value, .. } => {
+                self.expression(target);
+                self.expression(value);
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: Handle<ast::Expression<'a>>) {
+        match self.expressions[expr] {
+            ast::Expression::Literal(_) | ast::Expression::Ident(ast::IdentExpr::Unresolved(_)) => {
+            }
+            ast::Expression::Ident(ast::IdentExpr::Local(local)) => {
+                self.used.insert(local);
+            }
+            ast::Expression::Construct { ref components, .. }
+            | ast::Expression::Call {
+                arguments: ref components,
+                ..
+            } => {
+                for &component in components.iter() {
+                    self.expression(component);
+                }
+            }
+            ast::Expression::Unary { expr, .. }
+            | ast::Expression::AddrOf(expr)
+            | ast::Expression::Deref(expr)
+            | ast::Expression::Member { base: expr, .. }
+            | ast::Expression::Bitcast { expr, .. } => self.expression(expr),
+            ast::Expression::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            ast::Expression::Index { base, index } => {
+                self.expression(base);
+                self.expression(index);
+            }
+        }
+    }
+
+    /// Add `name` to the innermost scope, warning if it shadows a
+    /// declaration in an enclosing one.
+    fn declare(&mut self, name: ast::Ident<'a>) {
+        if let Some(&previous) = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.name))
+        {
+            self.warnings.push(Warning::ShadowedDeclaration {
+                name: name.name,
+                span: name.span,
+                previous,
+            });
+        }
+        self.scopes.last_mut().unwrap().insert(name.name, name.span);
+    }
+}
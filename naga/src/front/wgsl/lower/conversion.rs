@@ -112,6 +112,7 @@ impl<'source, 'temp, 'out> super::ExpressionContext<'source, 'temp, 'out> {
         expr_span: Span,
         goal_scalar: crate::Scalar,
     ) -> Result<Handle<crate::Expression>, super::Error<'source>> {
+        self.check_narrowing(expr, goal_scalar);
         let expr_inner = super::resolve_inner!(self, expr);
         if let crate::TypeInner::Array { .. } = *expr_inner {
             self.as_const_evaluator()
@@ -199,6 +200,7 @@ impl<'source, 'temp, 'out> super::ExpressionContext<'source, 'temp, 'out> {
         // Do nothing if `inner` doesn't even have leaf scalars;
         // it's a type error that validation will catch.
         if inner.scalar() != Some(goal) {
+            self.check_narrowing(*expr, goal);
             let cast = crate::Expression::As {
                 expr: *expr,
                 kind: goal.kind,
@@ -245,6 +247,7 @@ impl<'source, 'temp, 'out> super::ExpressionContext<'source, 'temp, 'out> {
             let concretized = scalar.concretize();
             if concretized != scalar {
                 assert!(scalar.is_abstract());
+                self.check_narrowing(expr, concretized);
                 let expr_span = self.get_expression_span(expr);
                 expr = self
                     .as_const_evaluator()
@@ -267,6 +270,43 @@ impl<'source, 'temp, 'out> super::ExpressionContext<'source, 'temp, 'out> {
         Ok(expr)
     }
 
+    /// Warn about abstract-float literals in `expr` that converting to `goal`
+    /// would change.
+    ///
+    /// This looks through compositions and splats, so that narrowing a
+    /// `vec3(0.1, 3.14159265358979, 2.0)` warns about the one component
+    /// that can't be represented as written.
+    fn check_narrowing(&mut self, expr: Handle<crate::Expression>, goal: crate::Scalar) {
+        if goal.kind != crate::ScalarKind::Float {
+            return;
+        }
+        let expressions = match self.expr_type {
+            super::ExpressionContextType::Runtime(ref ctx) => &ctx.function.expressions,
+            super::ExpressionContextType::Constant | super::ExpressionContextType::Override => {
+                &self.module.global_expressions
+            }
+        };
+        let mut stack = vec![expr];
+        while let Some(expr) = stack.pop() {
+            match expressions[expr] {
+                crate::Expression::Literal(crate::Literal::AbstractFloat(value)) => {
+                    if let Some(narrowed) = lossy_narrowing(value, goal) {
+                        self.warnings.push(super::Warning::AbstractFloatNarrowing {
+                            span: expressions.get_span(expr),
+                            narrowed,
+                            scalar: goal,
+                        });
+                    }
+                }
+                crate::Expression::Compose { ref components, .. } => {
+                    stack.extend(components.iter().rev());
+                }
+                crate::Expression::Splat { value, .. } => stack.push(value),
+                _ => {}
+            }
+        }
+    }
+
     /// Find the consensus scalar of `components` under WGSL's automatic
     /// conversions.
     ///
@@ -501,3 +541,26 @@ impl crate::Scalar {
         }
     }
 }
+
+/// If narrowing `value` to the floating-point type `scalar` loses precision,
+/// return the narrowed value.
+///
+/// Values whose narrowed form is written the same way, like `0.1` as an
+/// `f32`, don't count: only values written with more precision than
+/// `scalar` can hold, or outside its range, lose precision.
+fn lossy_narrowing(value: f64, scalar: crate::Scalar) -> Option<f64> {
+    let narrow = |value: f64| match scalar.width {
+        2 => half::f16::from_f64(value).to_f64(),
+        4 => value as f32 as f64,
+        _ => value,
+    };
+    let narrowed = narrow(value);
+    if narrowed == value {
+        return None;
+    }
+    // The shortest decimal that narrows to the same value.
+    let shortest = (0..17)
+        .filter_map(|digits| format!("{narrowed:.digits$e}").parse::<f64>().ok())
+        .find(|&decimal| narrow(decimal) == narrowed)?;
+    (shortest != value).then_some(shortest)
+}
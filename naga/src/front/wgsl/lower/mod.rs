@@ -1,14 +1,17 @@
 use std::num::NonZeroU32;
 
-use crate::front::wgsl::error::{Error, ExpectedToken, InvalidAssignmentType};
-use crate::front::wgsl::index::Index;
+use crate::front::wgsl::error::{Error, ExpectedToken, InvalidAssignmentType, Warning};
+use crate::front::wgsl::index::{self, Index};
+use crate::front::wgsl::parse::directive::enable_extension::{
+    EnableExtensions, ImplementedEnableExtension,
+};
 use crate::front::wgsl::parse::number::Number;
-use crate::front::wgsl::parse::{ast, conv};
+use crate::front::wgsl::parse::{ast, conv, Recovery};
 use crate::front::Typifier;
 use crate::proc::{
-    ensure_block_returns, Alignment, ConstantEvaluator, Emitter, Layouter, ResolveContext,
+    ensure_block_returns, Alignment, ConstantEvaluator, Emitter, Layouter, Namer, ResolveContext,
 };
-use crate::{Arena, FastHashMap, FastIndexMap, Handle, Span};
+use crate::{Arena, FastHashMap, FastHashSet, FastIndexMap, Handle, Span};
 
 mod construction;
 mod conversion;
@@ -88,6 +91,12 @@ pub struct GlobalContext<'source, 'temp, 'out> {
     const_typifier: &'temp mut Typifier,
 
     global_expression_kind_tracker: &'temp mut crate::proc::ExpressionKindTracker,
+
+    /// Where to record warnings found while lowering.
+    warnings: &'temp mut Vec<Warning<'source>>,
+
+    /// The extensions requested by `enable` directives in the module.
+    enable_extensions: EnableExtensions,
 }
 
 impl<'source> GlobalContext<'source, '_, '_> {
@@ -100,6 +109,8 @@ impl<'source> GlobalContext<'source, '_, '_> {
             const_typifier: self.const_typifier,
             expr_type: ExpressionContextType::Constant,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
         }
     }
 
@@ -112,6 +123,8 @@ impl<'source> GlobalContext<'source, '_, '_> {
             const_typifier: self.const_typifier,
             expr_type: ExpressionContextType::Override,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
         }
     }
 
@@ -181,6 +194,12 @@ pub struct StatementContext<'source, 'temp, 'out> {
     /// the code for lowering `let` bindings.
     local_expression_kind_tracker: &'temp mut crate::proc::ExpressionKindTracker,
     global_expression_kind_tracker: &'temp mut crate::proc::ExpressionKindTracker,
+
+    /// Where to record warnings found while lowering.
+    warnings: &'temp mut Vec<Warning<'source>>,
+
+    /// The extensions requested by `enable` directives in the module.
+    enable_extensions: EnableExtensions,
 }
 
 impl<'a, 'temp> StatementContext<'a, 'temp, '_> {
@@ -198,6 +217,8 @@ impl<'a, 'temp> StatementContext<'a, 'temp, '_> {
             ast_expressions: self.ast_expressions,
             const_typifier: self.const_typifier,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
             module: self.module,
             expr_type: ExpressionContextType::Runtime(RuntimeExpressionContext {
                 local_table: self.local_table,
@@ -218,6 +239,8 @@ impl<'a, 'temp> StatementContext<'a, 'temp, '_> {
             module: self.module,
             const_typifier: self.const_typifier,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
         }
     }
 
@@ -339,9 +362,15 @@ pub struct ExpressionContext<'source, 'temp, 'out> {
     const_typifier: &'temp mut Typifier,
     global_expression_kind_tracker: &'temp mut crate::proc::ExpressionKindTracker,
 
+    /// Where to record warnings found while lowering.
+    warnings: &'temp mut Vec<Warning<'source>>,
+
     /// Whether we are lowering a constant expression or a general
     /// runtime expression, and the data needed in each case.
     expr_type: ExpressionContextType<'temp, 'out>,
+
+    /// The extensions requested by `enable` directives in the module.
+    enable_extensions: EnableExtensions,
 }
 
 impl<'source, 'temp, 'out> ExpressionContext<'source, 'temp, 'out> {
@@ -354,6 +383,8 @@ impl<'source, 'temp, 'out> ExpressionContext<'source, 'temp, 'out> {
             module: self.module,
             expr_type: ExpressionContextType::Constant,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
         }
     }
 
@@ -365,6 +396,8 @@ impl<'source, 'temp, 'out> ExpressionContext<'source, 'temp, 'out> {
             module: self.module,
             const_typifier: self.const_typifier,
             global_expression_kind_tracker: self.global_expression_kind_tracker,
+            warnings: self.warnings,
+            enable_extensions: self.enable_extensions,
         }
     }
 
@@ -819,6 +852,7 @@ impl Components {
 }
 
 /// An `ast::GlobalDecl` for which we have built the Naga IR equivalent.
+#[derive(Clone, Copy)]
 enum LoweredGlobalDecl {
     Function(Handle<crate::Function>),
     Var(Handle<crate::GlobalVariable>),
@@ -828,6 +862,64 @@ enum LoweredGlobalDecl {
     EntryPoint,
 }
 
+/// Record the documentation comments on `decl`, and on its members if it is
+/// a struct, in `module`'s [`DocComments`].
+///
+/// [`DocComments`]: crate::DocComments
+fn record_doc_comments(
+    decl: &ast::GlobalDecl,
+    lowered: LoweredGlobalDecl,
+    module: &mut crate::Module,
+) {
+    let members = match decl.kind {
+        ast::GlobalDeclKind::Struct(ref s) => &s.members[..],
+        // A type alias's comments describe the alias, which the IR doesn't
+        // have, not the type it names.
+        ast::GlobalDeclKind::Type(_) => return,
+        _ => &[],
+    };
+    if decl.doc_comments.is_empty() && members.iter().all(|m| m.doc_comments.is_empty()) {
+        return;
+    }
+
+    let entry_point = module.entry_points.len().wrapping_sub(1);
+    let doc_comments = module.doc_comments.get_or_insert_with(Default::default);
+    let lines = |comments: &[&str]| comments.iter().map(|&c| c.to_string()).collect();
+    if !decl.doc_comments.is_empty() {
+        let comments = lines(&decl.doc_comments);
+        match lowered {
+            LoweredGlobalDecl::Function(handle) => {
+                doc_comments.functions.insert(handle, comments);
+            }
+            LoweredGlobalDecl::Var(handle) => {
+                doc_comments.global_variables.insert(handle, comments);
+            }
+            LoweredGlobalDecl::Const(handle) => {
+                doc_comments.constants.insert(handle, comments);
+            }
+            LoweredGlobalDecl::Override(handle) => {
+                doc_comments.overrides.insert(handle, comments);
+            }
+            LoweredGlobalDecl::Type(handle) => {
+                doc_comments.types.insert(handle, comments);
+            }
+            LoweredGlobalDecl::EntryPoint => {
+                doc_comments.entry_points.insert(entry_point, comments);
+            }
+        }
+    }
+    if let LoweredGlobalDecl::Type(handle) = lowered {
+        for (index, member) in members.iter().enumerate() {
+            if !member.doc_comments.is_empty() {
+                let comments = lines(&member.doc_comments);
+                doc_comments
+                    .struct_members
+                    .insert((handle, index), comments);
+            }
+        }
+    }
+}
+
 enum Texture {
     Gather,
     GatherCompare,
@@ -900,6 +992,20 @@ impl SubgroupGather {
 pub struct Lowerer<'source, 'temp> {
     index: &'temp Index<'source>,
     layouter: Layouter,
+
+    /// Generates replacements for module-scope names that are already
+    /// taken, when declarations in different files share a name.
+    namer: Namer,
+
+    /// The names given to module-scope declarations so far.
+    ir_names: FastHashSet<String>,
+
+    /// Warnings found by the last call to [`lower`] or
+    /// [`lower_with_recovery`].
+    ///
+    /// [`lower`]: Self::lower
+    /// [`lower_with_recovery`]: Self::lower_with_recovery
+    warnings: Vec<Warning<'source>>,
 }
 
 impl<'source, 'temp> Lowerer<'source, 'temp> {
@@ -907,208 +1013,327 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Self {
             index,
             layouter: Layouter::default(),
+            namer: Namer::default(),
+            ir_names: FastHashSet::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Take the warnings found while lowering.
+    pub fn take_warnings(&mut self) -> Vec<Warning<'source>> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Return the name to use in the IR for the module-scope declaration
+    /// `name`.
+    ///
+    /// This is `name` itself, unless a declaration in another file has
+    /// already claimed it.
+    fn ir_name(&mut self, name: &str) -> String {
+        // Always consult the namer, so that it knows which names are taken.
+        let renamed = self.namer.call(name);
+        let mut ir_name = if self.ir_names.contains(name) {
+            renamed
+        } else {
+            name.to_string()
+        };
+        while !self.ir_names.insert(ir_name.clone()) {
+            ir_name = self.namer.call(name);
         }
+        ir_name
     }
 
     pub fn lower(
         &mut self,
         tu: &'temp ast::TranslationUnit<'source>,
     ) -> Result<crate::Module, Error<'source>> {
-        let mut module = crate::Module::default();
-
-        let mut ctx = GlobalContext {
-            ast_expressions: &tu.expressions,
-            globals: &mut FastHashMap::default(),
-            types: &tu.types,
-            module: &mut module,
-            const_typifier: &mut Typifier::new(),
-            global_expression_kind_tracker: &mut crate::proc::ExpressionKindTracker::new(),
+        self.lower_impl(tu, None)
+    }
+
+    /// Like [`lower`], but record errors in `recovery` and carry on, rather
+    /// than stopping at the first declaration that fails to lower.
+    ///
+    /// Declarations named in [`Recovery::poisoned`], and declarations that
+    /// refer to them, are skipped, so that a broken declaration doesn't
+    /// produce a cascade of errors in everything that uses it. Declarations
+    /// that fail to lower are added to the poisoned set in turn.
+    ///
+    /// [`lower`]: Self::lower
+    pub fn lower_with_recovery(
+        &mut self,
+        tu: &'temp ast::TranslationUnit<'source>,
+        recovery: &mut Recovery<'source>,
+    ) -> Result<crate::Module, Error<'source>> {
+        self.lower_impl(tu, Some(recovery))
+    }
+
+    fn lower_impl(
+        &mut self,
+        tu: &'temp ast::TranslationUnit<'source>,
+        mut recovery: Option<&mut Recovery<'source>>,
+    ) -> Result<crate::Module, Error<'source>> {
+        let mut module = crate::Module {
+            diagnostic_filters: tu.diagnostic_filters.clone(),
+            diagnostic_filter_leaf: tu.diagnostic_filter_leaf,
+            ..Default::default()
         };
 
+        // The declarations visible in each source file, by name.
+        let mut globals: Vec<FastHashMap<_, _>> = (0..tu.imports.len().max(1))
+            .map(|_| FastHashMap::default())
+            .collect();
+        let mut const_typifier = Typifier::new();
+        let mut global_expression_kind_tracker = crate::proc::ExpressionKindTracker::new();
+        let mut warnings = Vec::new();
+
         for decl_handle in self.index.visit_ordered() {
-            let span = tu.decls.get_span(decl_handle);
             let decl = &tu.decls[decl_handle];
+            let name = index::decl_ident(decl).map(|ident| ident.name);
+            let mut ctx = GlobalContext {
+                ast_expressions: &tu.expressions,
+                globals: &mut globals[decl.file],
+                types: &tu.types,
+                module: &mut module,
+                const_typifier: &mut const_typifier,
+                global_expression_kind_tracker: &mut global_expression_kind_tracker,
+                warnings: &mut warnings,
+                enable_extensions: tu.enable_extensions,
+            };
 
-            match decl.kind {
-                ast::GlobalDeclKind::Fn(ref f) => {
-                    let lowered_decl = self.function(f, span, &mut ctx)?;
-                    ctx.globals.insert(f.name.name, lowered_decl);
-                }
-                ast::GlobalDeclKind::Var(ref v) => {
-                    let ty = self.resolve_ast_type(v.ty, &mut ctx)?;
-
-                    let init;
-                    if let Some(init_ast) = v.init {
-                        let mut ectx = ctx.as_override();
-                        let lowered = self.expression_for_abstract(init_ast, &mut ectx)?;
-                        let ty_res = crate::proc::TypeResolution::Handle(ty);
-                        let converted = ectx
-                            .try_automatic_conversions(lowered, &ty_res, v.name.span)
-                            .map_err(|error| match error {
-                                Error::AutoConversion {
-                                    dest_span: _,
-                                    dest_type,
-                                    source_span: _,
-                                    source_type,
-                                } => Error::InitializationTypeMismatch {
-                                    name: v.name.span,
-                                    expected: dest_type,
-                                    got: source_type,
-                                },
-                                other => other,
-                            })?;
-                        init = Some(converted);
-                    } else {
-                        init = None;
+            match recovery.as_deref_mut() {
+                None => self.global_decl(decl_handle, tu, &mut ctx)?,
+                Some(recovery) => {
+                    let poisoned = name.is_some_and(|name| recovery.poisoned.contains(name))
+                        || decl
+                            .dependencies
+                            .iter()
+                            .any(|dep| recovery.poisoned.contains(dep.ident));
+                    if poisoned {
+                        recovery.poisoned.extend(name);
+                        continue;
                     }
 
-                    let binding = if let Some(ref binding) = v.binding {
-                        Some(crate::ResourceBinding {
-                            group: self.const_u32(binding.group, &mut ctx.as_const())?.0,
-                            binding: self.const_u32(binding.binding, &mut ctx.as_const())?.0,
-                        })
-                    } else {
-                        None
-                    };
+                    if let Err(error) = self.global_decl(decl_handle, tu, &mut ctx) {
+                        recovery.errors.push(error);
+                        recovery.poisoned.extend(name);
+                        continue;
+                    }
+                }
+            }
 
-                    let handle = ctx.module.global_variables.append(
-                        crate::GlobalVariable {
-                            name: Some(v.name.name.to_string()),
-                            space: v.space,
-                            binding,
-                            ty,
-                            init,
-                        },
-                        span,
-                    );
+            // Make the declaration visible to the files that import it.
+            let Some(name) = name else {
+                continue;
+            };
+            let lowered = globals[decl.file][name];
+            for (file, file_globals) in globals.iter_mut().enumerate() {
+                if file != decl.file && self.index.lookup(file, name) == Some(decl_handle) {
+                    file_globals.insert(name, lowered);
+                }
+            }
+        }
 
-                    ctx.globals
-                        .insert(v.name.name, LoweredGlobalDecl::Var(handle));
+        // Constant evaluation may leave abstract-typed literals and
+        // compositions in expression arenas, so we need to compact the module
+        // to remove unused expressions and types.
+        crate::compact::compact(&mut module);
+
+        self.warnings = warnings;
+        Ok(module)
+    }
+
+    fn global_decl(
+        &mut self,
+        decl_handle: Handle<ast::GlobalDecl<'source>>,
+        tu: &'temp ast::TranslationUnit<'source>,
+        ctx: &mut GlobalContext<'source, '_, '_>,
+    ) -> Result<(), Error<'source>> {
+        let span = tu.decls.get_span(decl_handle);
+        let decl = &tu.decls[decl_handle];
+
+        match decl.kind {
+            ast::GlobalDeclKind::Fn(ref f) => {
+                let lowered_decl = self.function(f, span, ctx)?;
+                ctx.globals.insert(f.name.name, lowered_decl);
+            }
+            ast::GlobalDeclKind::Var(ref v) => {
+                let ty = self.resolve_ast_type(v.ty, ctx)?;
+
+                let init;
+                if let Some(init_ast) = v.init {
+                    let mut ectx = ctx.as_override();
+                    let lowered = self.expression_for_abstract(init_ast, &mut ectx)?;
+                    let ty_res = crate::proc::TypeResolution::Handle(ty);
+                    let converted = ectx
+                        .try_automatic_conversions(lowered, &ty_res, v.name.span)
+                        .map_err(|error| match error {
+                            Error::AutoConversion {
+                                dest_span: _,
+                                dest_type,
+                                source_span: _,
+                                source_type,
+                            } => Error::InitializationTypeMismatch {
+                                name: v.name.span,
+                                expected: dest_type,
+                                got: source_type,
+                            },
+                            other => other,
+                        })?;
+                    init = Some(converted);
+                } else {
+                    init = None;
                 }
-                ast::GlobalDeclKind::Const(ref c) => {
-                    let mut ectx = ctx.as_const();
-                    let mut init = self.expression_for_abstract(c.init, &mut ectx)?;
 
-                    let ty;
-                    if let Some(explicit_ty) = c.ty {
-                        let explicit_ty =
-                            self.resolve_ast_type(explicit_ty, &mut ectx.as_global())?;
-                        let explicit_ty_res = crate::proc::TypeResolution::Handle(explicit_ty);
-                        init = ectx
-                            .try_automatic_conversions(init, &explicit_ty_res, c.name.span)
-                            .map_err(|error| match error {
-                                Error::AutoConversion {
-                                    dest_span: _,
-                                    dest_type,
-                                    source_span: _,
-                                    source_type,
-                                } => Error::InitializationTypeMismatch {
-                                    name: c.name.span,
-                                    expected: dest_type,
-                                    got: source_type,
-                                },
-                                other => other,
-                            })?;
-                        ty = explicit_ty;
-                    } else {
-                        init = ectx.concretize(init)?;
-                        ty = ectx.register_type(init)?;
-                    }
+                let binding = if let Some(ref binding) = v.binding {
+                    Some(crate::ResourceBinding {
+                        group: self.const_u32(binding.group, &mut ctx.as_const())?.0,
+                        binding: self.const_u32(binding.binding, &mut ctx.as_const())?.0,
+                    })
+                } else {
+                    None
+                };
 
-                    let handle = ctx.module.constants.append(
-                        crate::Constant {
-                            name: Some(c.name.name.to_string()),
-                            ty,
-                            init,
-                        },
-                        span,
-                    );
+                let handle = ctx.module.global_variables.append(
+                    crate::GlobalVariable {
+                        name: Some(self.ir_name(v.name.name)),
+                        space: v.space,
+                        binding,
+                        ty,
+                        init,
+                    },
+                    span,
+                );
 
-                    ctx.globals
-                        .insert(c.name.name, LoweredGlobalDecl::Const(handle));
+                ctx.globals
+                    .insert(v.name.name, LoweredGlobalDecl::Var(handle));
+            }
+            ast::GlobalDeclKind::Const(ref c) => {
+                let mut ectx = ctx.as_const();
+                let mut init = self.expression_for_abstract(c.init, &mut ectx)?;
+
+                let ty;
+                if let Some(explicit_ty) = c.ty {
+                    let explicit_ty = self.resolve_ast_type(explicit_ty, &mut ectx.as_global())?;
+                    let explicit_ty_res = crate::proc::TypeResolution::Handle(explicit_ty);
+                    init = ectx
+                        .try_automatic_conversions(init, &explicit_ty_res, c.name.span)
+                        .map_err(|error| match error {
+                            Error::AutoConversion {
+                                dest_span: _,
+                                dest_type,
+                                source_span: _,
+                                source_type,
+                            } => Error::InitializationTypeMismatch {
+                                name: c.name.span,
+                                expected: dest_type,
+                                got: source_type,
+                            },
+                            other => other,
+                        })?;
+                    ty = explicit_ty;
+                } else {
+                    init = ectx.concretize(init)?;
+                    ty = ectx.register_type(init)?;
                 }
-                ast::GlobalDeclKind::Override(ref o) => {
-                    let init = o
-                        .init
-                        .map(|init| self.expression(init, &mut ctx.as_override()))
-                        .transpose()?;
-                    let inferred_type = init
-                        .map(|init| ctx.as_const().register_type(init))
-                        .transpose()?;
 
-                    let explicit_ty =
-                        o.ty.map(|ty| self.resolve_ast_type(ty, &mut ctx))
-                            .transpose()?;
+                let handle = ctx.module.constants.append(
+                    crate::Constant {
+                        name: Some(self.ir_name(c.name.name)),
+                        ty,
+                        init,
+                    },
+                    span,
+                );
 
-                    let id =
-                        o.id.map(|id| self.const_u32(id, &mut ctx.as_const()))
-                            .transpose()?;
+                ctx.globals
+                    .insert(c.name.name, LoweredGlobalDecl::Const(handle));
+            }
+            ast::GlobalDeclKind::Override(ref o) => {
+                let init = o
+                    .init
+                    .map(|init| self.expression(init, &mut ctx.as_override()))
+                    .transpose()?;
+                let inferred_type = init
+                    .map(|init| ctx.as_const().register_type(init))
+                    .transpose()?;
 
-                    let id = if let Some((id, id_span)) = id {
-                        Some(
-                            u16::try_from(id)
-                                .map_err(|_| Error::PipelineConstantIDValue(id_span))?,
-                        )
-                    } else {
-                        None
-                    };
+                let explicit_ty = o.ty.map(|ty| self.resolve_ast_type(ty, ctx)).transpose()?;
 
-                    let ty = match (explicit_ty, inferred_type) {
-                        (Some(explicit_ty), Some(inferred_type)) => {
-                            if explicit_ty == inferred_type {
-                                explicit_ty
-                            } else {
-                                let gctx = ctx.module.to_ctx();
-                                return Err(Error::InitializationTypeMismatch {
-                                    name: o.name.span,
-                                    expected: explicit_ty.to_wgsl(&gctx),
-                                    got: inferred_type.to_wgsl(&gctx),
-                                });
-                            }
-                        }
-                        (Some(explicit_ty), None) => explicit_ty,
-                        (None, Some(inferred_type)) => inferred_type,
-                        (None, None) => {
-                            return Err(Error::DeclMissingTypeAndInit(o.name.span));
+                let id =
+                    o.id.map(|id| self.const_u32(id, &mut ctx.as_const()))
+                        .transpose()?;
+
+                let id = if let Some((id, id_span)) = id {
+                    Some(u16::try_from(id).map_err(|_| Error::PipelineConstantIDValue(id_span))?)
+                } else {
+                    None
+                };
+
+                let ty = match (explicit_ty, inferred_type) {
+                    (Some(explicit_ty), Some(inferred_type)) => {
+                        if explicit_ty == inferred_type {
+                            explicit_ty
+                        } else {
+                            let gctx = ctx.module.to_ctx();
+                            return Err(Error::InitializationTypeMismatch {
+                                name: o.name.span,
+                                expected: explicit_ty.to_wgsl(&gctx),
+                                got: inferred_type.to_wgsl(&gctx),
+                            });
                         }
-                    };
+                    }
+                    (Some(explicit_ty), None) => explicit_ty,
+                    (None, Some(inferred_type)) => inferred_type,
+                    (None, None) => {
+                        return Err(Error::DeclMissingTypeAndInit(o.name.span));
+                    }
+                };
 
-                    let handle = ctx.module.overrides.append(
-                        crate::Override {
-                            name: Some(o.name.name.to_string()),
-                            id,
-                            ty,
-                            init,
-                        },
-                        span,
-                    );
+                let handle = ctx.module.overrides.append(
+                    crate::Override {
+                        name: Some(self.ir_name(o.name.name)),
+                        id,
+                        ty,
+                        init,
+                    },
+                    span,
+                );
 
-                    ctx.globals
-                        .insert(o.name.name, LoweredGlobalDecl::Override(handle));
-                }
-                ast::GlobalDeclKind::Struct(ref s) => {
-                    let handle = self.r#struct(s, span, &mut ctx)?;
-                    ctx.globals
-                        .insert(s.name.name, LoweredGlobalDecl::Type(handle));
-                }
-                ast::GlobalDeclKind::Type(ref alias) => {
-                    let ty = self.resolve_named_ast_type(
-                        alias.ty,
-                        Some(alias.name.name.to_string()),
-                        &mut ctx,
-                    )?;
-                    ctx.globals
-                        .insert(alias.name.name, LoweredGlobalDecl::Type(ty));
+                ctx.globals
+                    .insert(o.name.name, LoweredGlobalDecl::Override(handle));
+            }
+            ast::GlobalDeclKind::Struct(ref s) => {
+                let handle = self.r#struct(s, span, ctx)?;
+                ctx.globals
+                    .insert(s.name.name, LoweredGlobalDecl::Type(handle));
+            }
+            ast::GlobalDeclKind::ConstAssert(condition) => {
+                let condition = self.expression_for_abstract(condition, &mut ctx.as_const())?;
+
+                let span = ctx.module.global_expressions.get_span(condition);
+                match ctx
+                    .module
+                    .to_ctx()
+                    .eval_expr_to_bool_from(condition, &ctx.module.global_expressions)
+                {
+                    Some(true) => {}
+                    Some(false) => return Err(Error::ConstAssertFailed(span)),
+                    None => return Err(Error::NotBool(span)),
                 }
             }
+            ast::GlobalDeclKind::Type(ref alias) => {
+                let name = self.ir_name(alias.name.name);
+                let ty = self.resolve_named_ast_type(alias.ty, Some(name), ctx)?;
+                ctx.globals
+                    .insert(alias.name.name, LoweredGlobalDecl::Type(ty));
+            }
         }
 
-        // Constant evaluation may leave abstract-typed literals and
-        // compositions in expression arenas, so we need to compact the module
-        // to remove unused expressions and types.
-        crate::compact::compact(&mut module);
+        if let Some(ident) = index::decl_ident(decl) {
+            record_doc_comments(decl, ctx.globals[ident.name], ctx.module);
+        }
 
-        Ok(module)
+        Ok(())
     }
 
     fn function(
@@ -1154,14 +1379,16 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             })
             .transpose()?;
 
+        let name = self.ir_name(f.name.name);
         let mut function = crate::Function {
-            name: Some(f.name.name.to_string()),
+            name: Some(name.clone()),
             arguments,
             result,
             local_variables: Arena::new(),
             expressions,
             named_expressions: crate::NamedExpressions::default(),
             body: crate::Block::default(),
+            diagnostic_filter_leaf: f.diagnostic_filter_leaf,
         };
 
         let mut typifier = Typifier::default();
@@ -1177,6 +1404,8 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             module: ctx.module,
             local_expression_kind_tracker: &mut local_expression_kind_tracker,
             global_expression_kind_tracker: ctx.global_expression_kind_tracker,
+            warnings: ctx.warnings,
+            enable_extensions: ctx.enable_extensions,
         };
         let mut body = self.block(&f.body, false, &mut stmt_ctx)?;
         ensure_block_returns(&mut body);
@@ -1202,7 +1431,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             };
 
             ctx.module.entry_points.push(crate::EntryPoint {
-                name: f.name.name.to_string(),
+                name,
                 stage: entry.stage,
                 early_depth_test: entry.early_depth_test,
                 workgroup_size,
@@ -1223,6 +1452,11 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
     ) -> Result<crate::Block, Error<'source>> {
         let mut block = crate::Block::default();
 
+        if let Some(leaf) = b.diagnostic_filter_leaf {
+            let span = ctx.module.diagnostic_filters.get_span(leaf);
+            block.push(crate::Statement::DiagnosticFilter(leaf), span);
+        }
+
         for stmt in b.stmts.iter() {
             self.statement(stmt, &mut block, is_inside_loop, ctx)?;
         }
@@ -1400,6 +1634,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             ast::StatementKind::Switch {
                 selector,
                 ref cases,
+                diagnostic_filter_leaf: _,
             } => {
                 let mut emitter = Emitter::default();
                 emitter.start(&ctx.function.expressions);
@@ -1446,6 +1681,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                 ref body,
                 ref continuing,
                 break_if,
+                syntax: _,
             } => {
                 let body = self.block(body, true, ctx)?;
                 let mut continuing = self.block(continuing, true, ctx)?;
@@ -1606,6 +1842,23 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                     value,
                 }
             }
+            ast::StatementKind::ConstAssert(condition) => {
+                // Const assertions are evaluated in the module's constant
+                // context, so they can't refer to this function's locals.
+                let mut gctx = ctx.as_global();
+                let condition = self.expression_for_abstract(condition, &mut gctx.as_const())?;
+
+                let span = gctx.module.global_expressions.get_span(condition);
+                match gctx
+                    .module
+                    .to_ctx()
+                    .eval_expr_to_bool_from(condition, &gctx.module.global_expressions)
+                {
+                    Some(true) => return Ok(()),
+                    Some(false) => return Err(Error::ConstAssertFailed(span)),
+                    None => return Err(Error::NotBool(span)),
+                }
+            }
             ast::StatementKind::Ignore(expr) => {
                 let mut emitter = Emitter::default();
                 emitter.start(&ctx.function.expressions);
@@ -1657,6 +1910,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         let expr: Typed<crate::Expression> = match *expr {
             ast::Expression::Literal(literal) => {
                 let literal = match literal {
+                    ast::Literal::Number(Number::F16(f)) => crate::Literal::F16(f),
                     ast::Literal::Number(Number::F32(f)) => crate::Literal::F32(f),
                     ast::Literal::Number(Number::I32(i)) => crate::Literal::I32(i),
                     ast::Literal::Number(Number::U32(u)) => crate::Literal::U32(u),
@@ -2080,10 +2334,14 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                 } else if let Some(fun) = Texture::map(function.name) {
                     self.texture_sample_helper(fun, arguments, span, ctx)?
                 } else if let Some((op, cop)) = conv::map_subgroup_operation(function.name) {
+                    ctx.enable_extensions
+                        .require(ImplementedEnableExtension::Subgroups, function.span)?;
                     return Ok(Some(
                         self.subgroup_operation_helper(span, op, cop, arguments, ctx)?,
                     ));
                 } else if let Some(mode) = SubgroupGather::map(function.name) {
+                    ctx.enable_extensions
+                        .require(ImplementedEnableExtension::Subgroups, function.span)?;
                     return Ok(Some(
                         self.subgroup_gather_helper(span, mode, arguments, ctx)?,
                     ));
@@ -2193,6 +2451,8 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                             return Ok(None);
                         }
                         "subgroupBarrier" => {
+                            ctx.enable_extensions
+                                .require(ImplementedEnableExtension::Subgroups, function.span)?;
                             ctx.prepare_args(arguments, 0, span).finish()?;
 
                             let rctx = ctx.runtime_expression_ctx(span)?;
@@ -2408,6 +2668,8 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                             return Ok(Some(handle));
                         }
                         "subgroupBallot" => {
+                            ctx.enable_extensions
+                                .require(ImplementedEnableExtension::Subgroups, function.span)?;
                             let mut args = ctx.prepare_args(arguments, 0, span);
                             let predicate = if arguments.len() == 1 {
                                 Some(self.expression(args.next()?, ctx)?)
@@ -2768,7 +3030,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
 
         let handle = ctx.module.types.insert(
             crate::Type {
-                name: Some(s.name.name.to_string()),
+                name: Some(self.ir_name(s.name.name)),
                 inner,
             },
             span,
@@ -2804,7 +3066,26 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         Ok(match size {
             ast::ArraySize::Constant(expr) => {
                 let span = ctx.ast_expressions.get_span(expr);
-                let const_expr = self.expression(expr, &mut ctx.as_const())?;
+                let const_expr = self.expression(expr, &mut ctx.as_override())?;
+                if !ctx.global_expression_kind_tracker.is_const(const_expr) {
+                    // The element count depends on overrides, so it can
+                    // only be determined during pipeline creation.
+                    let ty = ctx.as_override().register_type(const_expr)?;
+                    match ctx.module.types[ty].inner {
+                        crate::TypeInner::Scalar(crate::Scalar {
+                            kind: crate::ScalarKind::Sint | crate::ScalarKind::Uint,
+                            width: 4,
+                        }) => {}
+                        _ => return Err(Error::ExpectedConstExprConcreteIntegerScalar(span)),
+                    }
+                    let pending = match ctx.module.global_expressions[const_expr] {
+                        crate::Expression::Override(handle) => {
+                            crate::PendingArraySize::Override(handle)
+                        }
+                        _ => crate::PendingArraySize::Expression(const_expr),
+                    };
+                    return Ok(crate::ArraySize::Pending(pending));
+                }
                 let len =
                     ctx.module
                         .to_ctx()
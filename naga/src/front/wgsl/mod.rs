@@ -4,8 +4,12 @@ Frontend for [WGSL][wgsl] (WebGPU Shading Language).
 [wgsl]: https://gpuweb.github.io/gpuweb/wgsl.html
 */
 
+mod analysis;
 mod error;
+mod format;
+mod import;
 mod index;
+mod lint;
 mod lower;
 mod parse;
 #[cfg(test)]
@@ -13,12 +17,54 @@ mod tests;
 mod to_wgsl;
 
 use crate::front::wgsl::error::Error;
-use crate::front::wgsl::parse::Parser;
+use crate::front::wgsl::parse::{Parser, Recovery};
 use thiserror::Error;
 
-pub use crate::front::wgsl::error::ParseError;
+pub use crate::front::wgsl::analysis::{Analysis, ExpressionType, Symbol, SymbolKind};
+pub use crate::front::wgsl::error::{ParseError, ParseErrors, ParseWarning, WarningKind};
+pub use crate::front::wgsl::format::format;
+pub use crate::front::wgsl::import::{ImportResolver, ResolvedImport};
 use crate::front::wgsl::lower::Lowerer;
-use crate::Scalar;
+use crate::{FastHashMap, Scalar, SourceMap, Span};
+
+/// The value of a define, as tested by `@if` attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Define {
+    Bool(bool),
+    Int(i64),
+}
+
+impl From<bool> for Define {
+    fn from(value: bool) -> Self {
+        Define::Bool(value)
+    }
+}
+
+impl From<i64> for Define {
+    fn from(value: i64) -> Self {
+        Define::Int(value)
+    }
+}
+
+/// Options for parsing WGSL, passed to [`Frontend::new_with_options`].
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Values for the names used in `@if` conditions.
+    ///
+    /// A global declaration, structure member, or statement with an
+    /// `@if(condition)` attribute is only included in the module if
+    /// `condition` holds, given these defines. For example:
+    ///
+    /// ```wgsl
+    /// @if(SHADOWS && CASCADES > 2)
+    /// fn blend_cascades() { … }
+    /// ```
+    ///
+    /// Excluded code must still parse, but is otherwise ignored, so it may
+    /// refer to declarations that are themselves excluded. Spans in the
+    /// module still refer to the original source.
+    pub defines: FastHashMap<String, Define>,
+}
 
 pub struct Frontend {
     parser: Parser,
@@ -31,6 +77,12 @@ impl Frontend {
         }
     }
 
+    pub fn new_with_options(options: Options) -> Self {
+        Self {
+            parser: Parser::new_with_defines(options.defines),
+        }
+    }
+
     pub fn parse(&mut self, source: &str) -> Result<crate::Module, ParseError> {
         self.inner(source).map_err(|x| x.as_parse_error(source))
     }
@@ -42,6 +94,150 @@ impl Frontend {
 
         Ok(module)
     }
+
+    /// Parse `source`, also returning warnings about code that is valid but
+    /// probably not what was meant.
+    ///
+    /// See [`WarningKind`] for what is checked. The warnings are sorted by
+    /// where they occur in the source.
+    pub fn parse_with_warnings(
+        &mut self,
+        source: &str,
+    ) -> Result<(crate::Module, Vec<ParseWarning>), ParseError> {
+        let (module, warnings) = self
+            .inner_with_warnings(source)
+            .map_err(|x| x.as_parse_error(source))?;
+        let warnings = warnings
+            .iter()
+            .map(|warning| warning.as_parse_warning(source))
+            .collect();
+        Ok((module, warnings))
+    }
+
+    fn inner_with_warnings<'a>(
+        &mut self,
+        source: &'a str,
+    ) -> Result<(crate::Module, Vec<error::Warning<'a>>), Error<'a>> {
+        let tu = self.parser.parse(source)?;
+        let index = index::Index::generate(&tu)?;
+        let mut lowerer = Lowerer::new(&index);
+        let module = lowerer.lower(&tu)?;
+
+        let mut warnings = lint::check(&tu);
+        warnings.extend(lowerer.take_warnings());
+        warnings.sort_by_key(|warning| warning.span().to_range().map_or(0, |range| range.start));
+        warnings.dedup();
+        Ok((module, warnings))
+    }
+
+    /// Parse `source`, which may `import` other files, and everything it
+    /// imports into a single module.
+    ///
+    /// The file `source` came from is called `path`; this is passed to
+    /// `resolver` along with the paths in `source`'s `import` declarations,
+    /// and likewise for the files they import in turn. See
+    /// [`ImportResolver`] for how names are shared between files.
+    ///
+    /// Spans in the returned module are offsets into the returned
+    /// [`SourceMap`]'s [`text`]. Errors carry their own [`SourceMap`], so
+    /// they can point at the right file.
+    ///
+    /// [`text`]: SourceMap::text
+    pub fn parse_with_imports(
+        &mut self,
+        path: &str,
+        source: &str,
+        resolver: &mut dyn ImportResolver,
+    ) -> Result<(crate::Module, SourceMap), ParseError> {
+        let mut source_map = SourceMap::new(path, source);
+
+        // Load every file reachable through imports, noting which files
+        // each one imports.
+        let mut imports = Vec::new();
+        while imports.len() < source_map.files().len() {
+            let file = imports.len();
+            let paths: Vec<(String, Span)> = Parser::imports(source_map.lexer(file))
+                .map_err(|error| source_map.parse_error(&error))?
+                .into_iter()
+                .map(|(path, span)| (path.to_string(), span))
+                .collect();
+            let mut file_imports = Vec::with_capacity(paths.len());
+            for (path, path_span) in paths {
+                let importer = source_map.files()[file].name();
+                let resolved = resolver.resolve(importer, &path).map_err(|message| {
+                    source_map.parse_error(&Error::ImportResolution { path_span, message })
+                })?;
+                let imported = match source_map.file_index(&resolved.name) {
+                    Some(index) => index,
+                    None => source_map.add(resolved.name, &resolved.source),
+                };
+                file_imports.push(imported);
+            }
+            imports.push(file_imports);
+        }
+
+        let module = self
+            .inner_with_imports(&source_map, imports)
+            .map_err(|error| source_map.parse_error(&error))?;
+        Ok((module, source_map))
+    }
+
+    fn inner_with_imports<'a>(
+        &mut self,
+        source_map: &'a SourceMap,
+        imports: Vec<Vec<usize>>,
+    ) -> Result<crate::Module, Error<'a>> {
+        let tu = self.parser.parse_files(source_map, imports)?;
+        let index = index::Index::generate(&tu)?;
+        let module = Lowerer::new(&index).lower(&tu)?;
+
+        Ok(module)
+    }
+
+    /// Parse `source`, reporting every error found rather than only the first.
+    ///
+    /// Parsing resumes after syntax errors at the next statement or
+    /// declaration, and lowering carries on past declarations that can't be
+    /// lowered. Declarations that depend on one with errors are skipped,
+    /// rather than reported as errors themselves.
+    pub fn parse_with_recovery(&mut self, source: &str) -> Result<crate::Module, ParseErrors> {
+        let mut recovery = Recovery::default();
+        let result = self.inner_with_recovery(source, &mut recovery);
+        let mut errors = recovery.errors;
+        match result {
+            Ok(module) if errors.is_empty() => return Ok(module),
+            Ok(_) => {}
+            Err(error) => errors.push(error),
+        }
+        Err(ParseErrors::new(
+            errors
+                .into_iter()
+                .map(|error| error.as_parse_error(source))
+                .collect(),
+        ))
+    }
+
+    /// Analyze `source` for an editor or language server.
+    ///
+    /// See [`Analysis`] for the questions the result can answer. Like
+    /// [`parse_with_recovery`], this carries on past errors where it can.
+    ///
+    /// [`parse_with_recovery`]: Frontend::parse_with_recovery
+    pub fn analyze(&mut self, source: &str) -> Analysis {
+        Analysis::new(&mut self.parser, source)
+    }
+
+    fn inner_with_recovery<'a>(
+        &mut self,
+        source: &'a str,
+        recovery: &mut Recovery<'a>,
+    ) -> Result<crate::Module, Error<'a>> {
+        let tu = self.parser.parse_with_recovery(source, recovery)?;
+        let index = index::Index::generate(&tu)?;
+        let module = Lowerer::new(&index).lower_with_recovery(&tu, recovery)?;
+
+        Ok(module)
+    }
 }
 
 /// <div class="warning">
@@ -1,10 +1,22 @@
+use crate::diagnostic_filter::{DiagnosticFilter, DiagnosticFilterNode};
+use crate::front::wgsl::parse::directive::enable_extension::EnableExtensions;
 use crate::front::wgsl::parse::number::Number;
 use crate::front::wgsl::Scalar;
-use crate::{Arena, FastIndexSet, Handle, Span};
+use crate::{Arena, FastHashMap, FastIndexSet, Handle, Span};
 use std::hash::Hash;
 
 #[derive(Debug, Default)]
 pub struct TranslationUnit<'a> {
+    /// The extensions requested by `enable` directives at the top of the module.
+    pub enable_extensions: EnableExtensions,
+
+    /// The directives at the top of the module, in the order they appear.
+    pub directives: Arena<Directive<'a>>,
+
+    /// The path and span of each `import` declaration, in the order they
+    /// appear.
+    pub import_paths: Vec<(&'a str, Span)>,
+
     pub decls: Arena<GlobalDecl<'a>>,
     /// The common expressions arena for the entire translation unit.
     ///
@@ -19,11 +31,53 @@ pub struct TranslationUnit<'a> {
     /// [`Function`]: crate::Function
     pub expressions: Arena<Expression<'a>>,
 
+    /// Arena for all diagnostic filter rules parsed in this module, including those in functions.
+    ///
+    /// See [`DiagnosticFilterNode`] for details on how the tree is represented and used in
+    /// validation.
+    pub diagnostic_filters: Arena<DiagnosticFilterNode>,
+    /// The leaf of all `diagnostic(…)` directives in this module.
+    ///
+    /// See [`DiagnosticFilterNode`] for details on how the tree is represented and used in
+    /// validation.
+    pub diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
+
     /// Non-user-defined types, like `vec4<f32>` or `array<i32, 10>`.
     ///
     /// These are referred to by `Handle<ast::Type<'a>>` values.
     /// User-defined types are referred to by name until lowering.
     pub types: Arena<Type<'a>>,
+
+    /// For each source file making up this translation unit, the indices of
+    /// the files it imports.
+    ///
+    /// This is empty if the translation unit was parsed from a single file
+    /// without an [`ImportResolver`].
+    ///
+    /// [`ImportResolver`]: crate::front::wgsl::ImportResolver
+    pub imports: Vec<Vec<usize>>,
+
+    /// The conditions of the `@if` attributes on each declaration, structure
+    /// member and statement, keyed by the byte offset at which it starts,
+    /// after its attributes.
+    ///
+    /// This is only filled in by a [`Parser::new_for_formatting`] parser,
+    /// which keeps the code these conditions would exclude. Other parsers
+    /// evaluate the conditions as they go.
+    ///
+    /// [`Parser::new_for_formatting`]: super::Parser::new_for_formatting
+    pub conditions: FastHashMap<usize, Vec<Handle<Expression<'a>>>>,
+}
+
+/// A directive at the top of a module.
+#[derive(Debug)]
+pub enum Directive<'a> {
+    /// `diagnostic(severity, rule);`
+    Diagnostic(DiagnosticFilter),
+    /// `enable extension, …;`
+    Enable(Vec<Ident<'a>>),
+    /// `requires extension, …;`
+    Requires(Vec<Ident<'a>>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -75,6 +129,15 @@ pub struct GlobalDecl<'a> {
     /// Names of all module-scope or predeclared objects this
     /// declaration uses.
     pub dependencies: FastIndexSet<Dependency<'a>>,
+
+    /// The index of the source file this declaration appears in, which
+    /// determines how the names in [`dependencies`] are resolved.
+    ///
+    /// [`dependencies`]: GlobalDecl::dependencies
+    pub file: usize,
+
+    /// The `///` comments before this declaration, one per line.
+    pub doc_comments: Vec<&'a str>,
 }
 
 #[derive(Debug)]
@@ -85,6 +148,7 @@ pub enum GlobalDeclKind<'a> {
     Override(Override<'a>),
     Struct(Struct<'a>),
     Type(TypeAlias<'a>),
+    ConstAssert(Handle<Expression<'a>>),
 }
 
 #[derive(Debug)]
@@ -145,6 +209,13 @@ pub struct Function<'a> {
     pub locals: Arena<Local>,
 
     pub body: Block<'a>,
+
+    /// The leaf of the diagnostic filter rules that apply to this function:
+    /// its own `@diagnostic(…)` attributes, followed by the module's
+    /// `diagnostic(…)` directives.
+    ///
+    /// See [`DiagnosticFilterNode`] for details.
+    pub diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
 }
 
 #[derive(Debug)]
@@ -180,6 +251,7 @@ pub struct StructMember<'a> {
     pub binding: Option<Binding<'a>>,
     pub align: Option<Handle<Expression<'a>>>,
     pub size: Option<Handle<Expression<'a>>>,
+    pub doc_comments: Vec<&'a str>,
 }
 
 #[derive(Debug)]
@@ -265,6 +337,20 @@ pub enum Type<'a> {
 #[derive(Debug, Default)]
 pub struct Block<'a> {
     pub stmts: Vec<Statement<'a>>,
+
+    /// The block's location in the source, from any attributes before its
+    /// opening brace to its closing brace.
+    ///
+    /// This is [`Span::UNDEFINED`] for blocks the parser makes up, like the
+    /// `else` block holding an `else if`.
+    pub span: Span,
+
+    /// The leaf of the diagnostic filter rules that apply to this block, if
+    /// `@diagnostic(…)` attributes on the block or on the statement it
+    /// belongs to add to those of the enclosing block.
+    ///
+    /// See [`DiagnosticFilterNode`] for details.
+    pub diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
 }
 
 #[derive(Debug)]
@@ -285,11 +371,18 @@ pub enum StatementKind<'a> {
     Switch {
         selector: Handle<Expression<'a>>,
         cases: Vec<SwitchCase<'a>>,
+        /// The leaf of the diagnostic filter rules added by `@diagnostic(…)`
+        /// attributes on the switch body, if any.
+        ///
+        /// These already apply to each case's body; this records where they
+        /// were written.
+        diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
     },
     Loop {
         body: Block<'a>,
         continuing: Block<'a>,
         break_if: Option<Handle<Expression<'a>>>,
+        syntax: LoopSyntax,
     },
     Break,
     Continue,
@@ -309,6 +402,32 @@ pub enum StatementKind<'a> {
     Increment(Handle<Expression<'a>>),
     Decrement(Handle<Expression<'a>>),
     Ignore(Handle<Expression<'a>>),
+    ConstAssert(Handle<Expression<'a>>),
+}
+
+/// How a [`Loop`] statement was written.
+///
+/// `while` and `for` loops are parsed into the same form as `loop`
+/// statements, but this records which they were:
+///
+/// - The body of a `while` loop, and of a `for` loop with a condition, starts
+///   with an `if` statement that breaks out of the loop unless the condition
+///   holds. The rest of the body is a single [`Block`] statement.
+///
+/// - A `for` loop's initializer statement is added to the enclosing block,
+///   just before the loop, and its update statement is the only statement in
+///   the loop's `continuing` block.
+///
+/// [`Loop`]: StatementKind::Loop
+/// [`Block`]: StatementKind::Block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopSyntax {
+    Loop,
+    While,
+    For {
+        /// Whether the loop has an initializer statement.
+        init: bool,
+    },
 }
 
 #[derive(Debug)]
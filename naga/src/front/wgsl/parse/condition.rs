@@ -0,0 +1,224 @@
+/*!
+Evaluation of `@if` conditions.
+
+An `@if(condition)` attribute on a global declaration, structure member, or
+statement causes it to be dropped from the module unless `condition` holds.
+Conditions are evaluated during parsing, using the values in
+[`Options::defines`], so dropped code is still parsed, but never lowered.
+
+A condition is an expression built from:
+
+- `true` and `false`, and integer literals,
+
+- the names of defines,
+
+- `defined(NAME)`, which is `true` if there is a define named `NAME`,
+
+- the operators `!`, unary `-`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&`
+  and `||`, with their usual WGSL precedence, and parentheses.
+
+As in WGSL, `&&` and `||` short-circuit: once the left operand decides the
+result, the right operand is parsed but not evaluated. This lets a condition
+like `defined(CASCADES) && CASCADES > 2` guard the use of a define.
+
+[`Options::defines`]: crate::front::wgsl::Options::defines
+*/
+
+use crate::front::wgsl::error::{Error, ExpectedToken};
+use crate::front::wgsl::parse::lexer::{Lexer, Token};
+use crate::front::wgsl::parse::number::Number;
+use crate::front::wgsl::Define;
+use crate::{FastHashMap, Span};
+
+/// Parse an `@if` attribute's parenthesized condition, and return its value.
+pub(super) fn evaluate<'a>(
+    lexer: &mut Lexer<'a>,
+    defines: Option<&FastHashMap<String, Define>>,
+) -> Result<bool, Error<'a>> {
+    lexer.expect(Token::Paren('('))?;
+    let evaluator = Evaluator {
+        defines,
+        evaluate: true,
+    };
+    let (value, span) = evaluator.logical_or(lexer)?;
+    lexer.skip(Token::Separator(','));
+    lexer.expect(Token::Paren(')'))?;
+    value.as_bool(span)
+}
+
+#[derive(Clone, Copy)]
+struct Evaluator<'d> {
+    defines: Option<&'d FastHashMap<String, Define>>,
+
+    /// Whether to evaluate the condition, or only parse it.
+    ///
+    /// When this is false, the values returned are placeholders.
+    evaluate: bool,
+}
+
+impl<'d> Evaluator<'d> {
+    /// Return an evaluator for the right operand of `&&` or `||`, which only
+    /// parses it if the left operand has `decided` the result.
+    const fn right_operand(self, decided: bool) -> Evaluator<'d> {
+        Evaluator {
+            defines: self.defines,
+            evaluate: self.evaluate && !decided,
+        }
+    }
+
+    /// Return the value `op` computes, unless we are only parsing.
+    fn apply<'a>(
+        &self,
+        op: impl FnOnce() -> Result<Define, Error<'a>>,
+    ) -> Result<Define, Error<'a>> {
+        if self.evaluate {
+            op()
+        } else {
+            Ok(Define::Bool(false))
+        }
+    }
+
+    fn logical_or<'a>(&self, lexer: &mut Lexer<'a>) -> Result<(Define, Span), Error<'a>> {
+        let (mut value, mut span) = self.logical_and(lexer)?;
+        while lexer.skip(Token::LogicalOperation('|')) {
+            let decided = self.evaluate && value.as_bool(span)?;
+            let (right, right_span) = self.right_operand(decided).logical_and(lexer)?;
+            value = self.apply(|| Ok(Define::Bool(decided || right.as_bool(right_span)?)))?;
+            span = span.until(&right_span);
+        }
+        Ok((value, span))
+    }
+
+    fn logical_and<'a>(&self, lexer: &mut Lexer<'a>) -> Result<(Define, Span), Error<'a>> {
+        let (mut value, mut span) = self.relational(lexer)?;
+        while lexer.skip(Token::LogicalOperation('&')) {
+            let decided = self.evaluate && !value.as_bool(span)?;
+            let (right, right_span) = self.right_operand(decided).relational(lexer)?;
+            value = self.apply(|| Ok(Define::Bool(!decided && right.as_bool(right_span)?)))?;
+            span = span.until(&right_span);
+        }
+        Ok((value, span))
+    }
+
+    fn relational<'a>(&self, lexer: &mut Lexer<'a>) -> Result<(Define, Span), Error<'a>> {
+        let (left, left_span) = self.unary(lexer)?;
+        let op = match lexer.peek().0 {
+            Token::LogicalOperation(op @ ('=' | '!' | '<' | '>')) => (op, true),
+            Token::Paren(op @ ('<' | '>')) => (op, false),
+            _ => return Ok((left, left_span)),
+        };
+        let _ = lexer.next();
+        let (right, right_span) = self.unary(lexer)?;
+        let span = left_span.until(&right_span);
+        let value = self.apply(|| {
+            let value = match (op, left, right) {
+                (('=', _), Define::Bool(left), right) => left == right.as_bool(right_span)?,
+                (('!', _), Define::Bool(left), right) => left != right.as_bool(right_span)?,
+                (_, left, right) => {
+                    let left = left.as_int(left_span)?;
+                    let right = right.as_int(right_span)?;
+                    match op {
+                        ('=', _) => left == right,
+                        ('!', _) => left != right,
+                        ('<', true) => left <= right,
+                        ('<', false) => left < right,
+                        ('>', true) => left >= right,
+                        _ => left > right,
+                    }
+                }
+            };
+            Ok(Define::Bool(value))
+        })?;
+        Ok((value, span))
+    }
+
+    fn unary<'a>(&self, lexer: &mut Lexer<'a>) -> Result<(Define, Span), Error<'a>> {
+        let start = lexer.start_byte_offset();
+        match lexer.peek().0 {
+            Token::Operation('!') => {
+                let _ = lexer.next();
+                let (value, span) = self.unary(lexer)?;
+                let value = self.apply(|| Ok(Define::Bool(!value.as_bool(span)?)))?;
+                Ok((value, lexer.span_from(start)))
+            }
+            Token::Operation('-') => {
+                let _ = lexer.next();
+                let (value, span) = self.unary(lexer)?;
+                let value = self.apply(|| Ok(Define::Int(value.as_int(span)?.wrapping_neg())))?;
+                Ok((value, lexer.span_from(start)))
+            }
+            _ => self.primary(lexer),
+        }
+    }
+
+    fn primary<'a>(&self, lexer: &mut Lexer<'a>) -> Result<(Define, Span), Error<'a>> {
+        let start = lexer.start_byte_offset();
+        let value = match lexer.next() {
+            (Token::Paren('('), _) => {
+                let (value, _) = self.logical_or(lexer)?;
+                lexer.expect(Token::Paren(')'))?;
+                value
+            }
+            (Token::Word("true"), _) => Define::Bool(true),
+            (Token::Word("false"), _) => Define::Bool(false),
+            (Token::Word("defined"), _) => {
+                lexer.expect(Token::Paren('('))?;
+                let name = lexer.next_ident()?;
+                lexer.expect(Token::Paren(')'))?;
+                Define::Bool(self.lookup(name.name).is_some())
+            }
+            (Token::Word(name), span) => match self.lookup(name) {
+                Some(&value) => value,
+                None if !self.evaluate => Define::Bool(false),
+                None => return Err(Error::UnknownDefine(span)),
+            },
+            (Token::Number(Ok(number)), span) => {
+                let value = match number {
+                    Number::AbstractInt(value) | Number::I64(value) => Some(value),
+                    Number::I32(value) => Some(value.into()),
+                    Number::U32(value) => Some(value.into()),
+                    Number::U64(value) => value.try_into().ok(),
+                    _ => None,
+                };
+                match value {
+                    Some(value) => Define::Int(value),
+                    None => {
+                        return Err(Error::BadConditionOperand {
+                            span,
+                            expected: "an integer or boolean",
+                        })
+                    }
+                }
+            }
+            (Token::Number(Err(e)), span) => return Err(Error::BadNumber(span, e)),
+            (_, span) => return Err(Error::Unexpected(span, ExpectedToken::PrimaryExpression)),
+        };
+        Ok((value, lexer.span_from(start)))
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Define> {
+        self.defines.and_then(|defines| defines.get(name))
+    }
+}
+
+impl Define {
+    const fn as_bool<'a>(self, span: Span) -> Result<bool, Error<'a>> {
+        match self {
+            Define::Bool(value) => Ok(value),
+            Define::Int(_) => Err(Error::BadConditionOperand {
+                span,
+                expected: "a boolean",
+            }),
+        }
+    }
+
+    const fn as_int<'a>(self, span: Span) -> Result<i64, Error<'a>> {
+        match self {
+            Define::Int(value) => Ok(value),
+            Define::Bool(_) => Err(Error::BadConditionOperand {
+                span,
+                expected: "an integer",
+            }),
+        }
+    }
+}
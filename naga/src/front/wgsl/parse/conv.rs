@@ -1,3 +1,4 @@
+use super::directive::enable_extension::{EnableExtensions, ImplementedEnableExtension};
 use super::Error;
 use crate::front::wgsl::Scalar;
 use crate::Span;
@@ -16,8 +17,12 @@ pub fn map_address_space(word: &str, span: Span) -> Result<crate::AddressSpace,
     }
 }
 
-pub fn map_built_in(word: &str, span: Span) -> Result<crate::BuiltIn, Error<'_>> {
-    Ok(match word {
+pub fn map_built_in(
+    enable_extensions: &EnableExtensions,
+    word: &str,
+    span: Span,
+) -> Result<crate::BuiltIn, Error<'static>> {
+    let built_in = match word {
         "position" => crate::BuiltIn::Position { invariant: false },
         // vertex
         "vertex_index" => crate::BuiltIn::VertexIndex,
@@ -41,7 +46,17 @@ pub fn map_built_in(word: &str, span: Span) -> Result<crate::BuiltIn, Error<'_>>
         "subgroup_size" => crate::BuiltIn::SubgroupSize,
         "subgroup_invocation_id" => crate::BuiltIn::SubgroupInvocationId,
         _ => return Err(Error::UnknownBuiltin(span)),
-    })
+    };
+    match built_in {
+        crate::BuiltIn::NumSubgroups
+        | crate::BuiltIn::SubgroupId
+        | crate::BuiltIn::SubgroupSize
+        | crate::BuiltIn::SubgroupInvocationId => {
+            enable_extensions.require(ImplementedEnableExtension::Subgroups, span)?;
+        }
+        _ => {}
+    }
+    Ok(built_in)
 }
 
 pub fn map_interpolation(word: &str, span: Span) -> Result<crate::Interpolation, Error<'_>> {
@@ -109,10 +124,17 @@ pub fn map_storage_format(word: &str, span: Span) -> Result<crate::StorageFormat
     })
 }
 
-pub fn get_scalar_type(word: &str) -> Option<Scalar> {
+pub fn get_scalar_type(
+    enable_extensions: &EnableExtensions,
+    span: Span,
+    word: &str,
+) -> Result<Option<Scalar>, Error<'static>> {
     use crate::ScalarKind as Sk;
-    match word {
-        // "f16" => Some(Scalar { kind: Sk::Float, width: 2 }),
+    let scalar = match word {
+        "f16" => Some(Scalar {
+            kind: Sk::Float,
+            width: 2,
+        }),
         "f32" => Some(Scalar {
             kind: Sk::Float,
             width: 4,
@@ -142,7 +164,53 @@ pub fn get_scalar_type(word: &str) -> Option<Scalar> {
             width: crate::BOOL_WIDTH,
         }),
         _ => None,
+    };
+
+    if matches!(scalar, Some(Scalar::F16)) {
+        enable_extensions.require(ImplementedEnableExtension::F16, span)?;
+    }
+
+    Ok(scalar)
+}
+
+/// A predeclared alias for an `f16` vector or matrix type, like `vec3h` or
+/// `mat2x4h`.
+pub enum F16Alias {
+    Vector(crate::VectorSize),
+    Matrix {
+        columns: crate::VectorSize,
+        rows: crate::VectorSize,
+    },
+}
+
+/// Map `word` to the `f16` vector or matrix type it's an alias for, if any,
+/// requiring the `f16` extension if it is.
+pub fn get_f16_alias(
+    enable_extensions: &EnableExtensions,
+    span: Span,
+    word: &str,
+) -> Result<Option<F16Alias>, Error<'static>> {
+    use crate::VectorSize as Vs;
+
+    let size = |digit| match digit {
+        b'2' => Some(Vs::Bi),
+        b'3' => Some(Vs::Tri),
+        b'4' => Some(Vs::Quad),
+        _ => None,
+    };
+    let alias = match *word.as_bytes() {
+        [b'v', b'e', b'c', n, b'h'] => size(n).map(F16Alias::Vector),
+        [b'm', b'a', b't', c, b'x', r, b'h'] => size(c)
+            .zip(size(r))
+            .map(|(columns, rows)| F16Alias::Matrix { columns, rows }),
+        _ => None,
+    };
+
+    if alias.is_some() {
+        enable_extensions.require(ImplementedEnableExtension::F16, span)?;
     }
+
+    Ok(alias)
 }
 
 pub fn map_derivative(word: &str) -> Option<(crate::DerivativeAxis, crate::DerivativeControl)> {
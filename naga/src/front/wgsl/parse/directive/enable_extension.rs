@@ -0,0 +1,172 @@
+//! `enable …;` extensions in WGSL.
+//!
+//! The focal point of this module is the [`EnableExtension`] API.
+//!
+//! Enable-extensions are checked at parse time only: the parser rejects uses
+//! of a feature whose extension the module doesn't enable. This is
+//! intentional. Naga IR doesn't record which extensions a module enabled,
+//! since other front ends have no such directives, so the validator keeps
+//! gating these features on its [`Capabilities`] alone.
+//!
+//! [`Capabilities`]: crate::valid::Capabilities
+
+use crate::{front::wgsl::error::Error, Span};
+
+/// Tracks the status of every enable-extension known to Naga.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnableExtensions {
+    /// Whether `enable f16;` was written earlier in the shader module.
+    f16: bool,
+    /// Whether `enable dual_source_blending;` was written earlier in the shader module.
+    dual_source_blending: bool,
+    /// Whether `enable subgroups;` was written earlier in the shader module.
+    subgroups: bool,
+}
+
+impl EnableExtensions {
+    pub(crate) const fn empty() -> Self {
+        Self {
+            f16: false,
+            dual_source_blending: false,
+            subgroups: false,
+        }
+    }
+
+    /// Add an enable-extension to the set requested by a module.
+    pub(crate) fn add(&mut self, ext: ImplementedEnableExtension) {
+        let field = match ext {
+            ImplementedEnableExtension::F16 => &mut self.f16,
+            ImplementedEnableExtension::DualSourceBlending => &mut self.dual_source_blending,
+            ImplementedEnableExtension::Subgroups => &mut self.subgroups,
+        };
+        *field = true;
+    }
+
+    /// Add all the enable-extensions in `other` to this set.
+    pub(crate) fn extend(&mut self, other: Self) {
+        let Self {
+            f16,
+            dual_source_blending,
+            subgroups,
+        } = other;
+        self.f16 |= f16;
+        self.dual_source_blending |= dual_source_blending;
+        self.subgroups |= subgroups;
+    }
+
+    /// Query whether an enable-extension tracked here has been requested.
+    pub(crate) const fn contains(&self, ext: ImplementedEnableExtension) -> bool {
+        match ext {
+            ImplementedEnableExtension::F16 => self.f16,
+            ImplementedEnableExtension::DualSourceBlending => self.dual_source_blending,
+            ImplementedEnableExtension::Subgroups => self.subgroups,
+        }
+    }
+
+    /// Return an error if `ext` has not been requested.
+    ///
+    /// `span` is the location of the source construct that needs `ext`.
+    pub(crate) const fn require(
+        &self,
+        ext: ImplementedEnableExtension,
+        span: Span,
+    ) -> Result<(), Error<'static>> {
+        if self.contains(ext) {
+            Ok(())
+        } else {
+            Err(Error::EnableExtensionNotEnabled { span, kind: ext })
+        }
+    }
+}
+
+/// An enable-extension not guaranteed to be present in all environments.
+///
+/// WGSL spec.: <https://gpuweb.github.io/gpuweb/wgsl/#enable-extensions-sec>
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum EnableExtension {
+    Implemented(ImplementedEnableExtension),
+    Unimplemented(UnimplementedEnableExtension),
+}
+
+impl EnableExtension {
+    const F16: &'static str = "f16";
+    const CLIP_DISTANCES: &'static str = "clip_distances";
+    const DUAL_SOURCE_BLENDING: &'static str = "dual_source_blending";
+    const SUBGROUPS: &'static str = "subgroups";
+
+    /// Convert from a sentinel word in WGSL into its associated [`EnableExtension`], if possible.
+    pub(crate) fn from_ident(word: &str, span: Span) -> Result<Self, Error<'_>> {
+        Ok(match word {
+            Self::F16 => Self::Implemented(ImplementedEnableExtension::F16),
+            Self::CLIP_DISTANCES => {
+                Self::Unimplemented(UnimplementedEnableExtension::ClipDistances)
+            }
+            Self::DUAL_SOURCE_BLENDING => {
+                Self::Implemented(ImplementedEnableExtension::DualSourceBlending)
+            }
+            Self::SUBGROUPS => Self::Implemented(ImplementedEnableExtension::Subgroups),
+            _ => return Err(Error::UnknownEnableExtension(span, word)),
+        })
+    }
+
+    /// Maps this [`EnableExtension`] into the sentinel word associated with it in WGSL.
+    pub const fn to_ident(self) -> &'static str {
+        match self {
+            Self::Implemented(kind) => match kind {
+                ImplementedEnableExtension::F16 => Self::F16,
+                ImplementedEnableExtension::DualSourceBlending => Self::DUAL_SOURCE_BLENDING,
+                ImplementedEnableExtension::Subgroups => Self::SUBGROUPS,
+            },
+            Self::Unimplemented(kind) => match kind {
+                UnimplementedEnableExtension::ClipDistances => Self::CLIP_DISTANCES,
+            },
+        }
+    }
+}
+
+/// A variant of [`EnableExtension::Implemented`].
+///
+/// Parsing an `enable` directive naming one of these items enables the
+/// associated language features for the rest of the module.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum ImplementedEnableExtension {
+    /// Enables `f16`/`half` primitive support in all shader languages.
+    ///
+    /// In the WGSL standard, this corresponds to [`enable f16;`].
+    ///
+    /// [`enable f16;`]: https://gpuweb.github.io/gpuweb/wgsl/#extension-f16
+    F16,
+    /// Enables the `second_blend_source` attribute in WGSL.
+    ///
+    /// In the WGSL standard, this corresponds to [`enable dual_source_blending;`].
+    ///
+    /// [`enable dual_source_blending;`]: https://gpuweb.github.io/gpuweb/wgsl/#extension-dual_source_blending
+    DualSourceBlending,
+    /// Enables subgroup built-in values and functions in WGSL.
+    ///
+    /// In the WGSL standard, this corresponds to [`enable subgroups;`].
+    ///
+    /// [`enable subgroups;`]: https://gpuweb.github.io/gpuweb/wgsl/#extension-subgroups
+    Subgroups,
+}
+
+impl ImplementedEnableExtension {
+    /// Maps this [`ImplementedEnableExtension`] into the sentinel word associated with it in WGSL.
+    pub const fn to_ident(self) -> &'static str {
+        EnableExtension::Implemented(self).to_ident()
+    }
+}
+
+/// A variant of [`EnableExtension::Unimplemented`].
+///
+/// Naga recognizes these names, but does not yet support the features they
+/// enable, so naming one in an `enable` directive is an error.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum UnimplementedEnableExtension {
+    /// Enables the `clip_distances` variable in WGSL.
+    ///
+    /// In the WGSL standard, this corresponds to [`enable clip_distances;`].
+    ///
+    /// [`enable clip_distances;`]: https://gpuweb.github.io/gpuweb/wgsl/#extension-clip_distances
+    ClipDistances,
+}
@@ -0,0 +1,86 @@
+//! `requires …;` extensions in WGSL.
+//!
+//! The focal point of this module is the [`LanguageExtension`] API.
+
+/// A language extension not guaranteed to be present in all environments.
+///
+/// WGSL spec.: <https://gpuweb.github.io/gpuweb/wgsl/#language-extensions-sec>
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum LanguageExtension {
+    Implemented(ImplementedLanguageExtension),
+    Unimplemented(UnimplementedLanguageExtension),
+}
+
+impl LanguageExtension {
+    const READONLY_AND_READWRITE_STORAGE_TEXTURES: &'static str =
+        "readonly_and_readwrite_storage_textures";
+    const PACKED_4X8_INTEGER_DOT_PRODUCT: &'static str = "packed_4x8_integer_dot_product";
+    const UNRESTRICTED_POINTER_PARAMETERS: &'static str = "unrestricted_pointer_parameters";
+    const POINTER_COMPOSITE_ACCESS: &'static str = "pointer_composite_access";
+
+    /// Convert from a sentinel word in WGSL into its associated [`LanguageExtension`], if possible.
+    pub fn from_ident(s: &str) -> Option<Self> {
+        Some(match s {
+            Self::READONLY_AND_READWRITE_STORAGE_TEXTURES => {
+                Self::Implemented(ImplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures)
+            }
+            Self::PACKED_4X8_INTEGER_DOT_PRODUCT => {
+                Self::Unimplemented(UnimplementedLanguageExtension::Packed4x8IntegerDotProduct)
+            }
+            Self::UNRESTRICTED_POINTER_PARAMETERS => {
+                Self::Implemented(ImplementedLanguageExtension::UnrestrictedPointerParameters)
+            }
+            Self::POINTER_COMPOSITE_ACCESS => {
+                Self::Unimplemented(UnimplementedLanguageExtension::PointerCompositeAccess)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Maps this [`LanguageExtension`] into the sentinel word associated with it in WGSL.
+    pub const fn to_ident(self) -> &'static str {
+        match self {
+            Self::Implemented(kind) => match kind {
+                ImplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures => {
+                    Self::READONLY_AND_READWRITE_STORAGE_TEXTURES
+                }
+                ImplementedLanguageExtension::UnrestrictedPointerParameters => {
+                    Self::UNRESTRICTED_POINTER_PARAMETERS
+                }
+            },
+            Self::Unimplemented(kind) => match kind {
+                UnimplementedLanguageExtension::Packed4x8IntegerDotProduct => {
+                    Self::PACKED_4X8_INTEGER_DOT_PRODUCT
+                }
+                UnimplementedLanguageExtension::PointerCompositeAccess => {
+                    Self::POINTER_COMPOSITE_ACCESS
+                }
+            },
+        }
+    }
+}
+
+/// A variant of [`LanguageExtension::Implemented`].
+///
+/// Naga always supports the features these extensions describe, so a
+/// `requires` directive naming one of them has no further effect.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum ImplementedLanguageExtension {
+    /// `texture_storage_*` types with `read` and `read_write` access modes.
+    ReadOnlyAndReadWriteStorageTextures,
+    /// Pointers into the `storage`, `uniform` and `workgroup` address spaces,
+    /// and to parts of composites, as function arguments.
+    UnrestrictedPointerParameters,
+}
+
+/// A variant of [`LanguageExtension::Unimplemented`].
+///
+/// Naga recognizes these names, but does not yet support the features they
+/// describe, so naming one in a `requires` directive is an error.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum UnimplementedLanguageExtension {
+    /// The `dot4I8Packed` and `dot4U8Packed` built-in functions, and friends.
+    Packed4x8IntegerDotProduct,
+    /// Member and index access through pointers, without an explicit `*`.
+    PointerCompositeAccess,
+}
@@ -0,0 +1,33 @@
+//! WGSL directives. The focal point of this API is [`DirectiveKind`].
+//!
+//! See also <https://gpuweb.github.io/gpuweb/wgsl/#directives>.
+
+pub(crate) mod enable_extension;
+pub(crate) mod language_extension;
+
+/// A parsed sentinel word indicating the type of directive to be parsed next.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub(crate) enum DirectiveKind {
+    /// A [`crate::diagnostic_filter`] directive.
+    Diagnostic,
+    /// An [`enable_extension`] directive.
+    Enable,
+    /// A [`language_extension`] directive.
+    Requires,
+}
+
+impl DirectiveKind {
+    const DIAGNOSTIC: &'static str = "diagnostic";
+    const ENABLE: &'static str = "enable";
+    const REQUIRES: &'static str = "requires";
+
+    /// Convert from a sentinel word in WGSL into its associated [`DirectiveKind`], if possible.
+    pub fn from_ident(s: &str) -> Option<Self> {
+        Some(match s {
+            Self::DIAGNOSTIC => Self::Diagnostic,
+            Self::ENABLE => Self::Enable,
+            Self::REQUIRES => Self::Requires,
+            _ => return None,
+        })
+    }
+}
@@ -1,8 +1,11 @@
-use super::{number::consume_number, Error, ExpectedToken};
+use super::{
+    directive::enable_extension::EnableExtensions, number::consume_number, Error, ExpectedToken,
+};
 use crate::front::wgsl::error::NumberError;
 use crate::front::wgsl::parse::{conv, Number};
 use crate::front::wgsl::Scalar;
 use crate::Span;
+use std::ops::Range;
 
 type TokenSpan<'a> = (Token<'a>, Span);
 
@@ -13,6 +16,9 @@ pub enum Token<'a> {
     Attribute,
     Number(Result<Number, NumberError>),
     Word(&'a str),
+    /// A double-quoted string, as used by `import` declarations. The quotes
+    /// are not included.
+    String(&'a str),
     Operation(char),
     LogicalOperation(char),
     ShiftOperation(char),
@@ -45,7 +51,7 @@ fn consume_any(input: &str, what: impl Fn(char) -> bool) -> (&str, &str) {
 ///     `Token::LogicalOperation` tokens.
 ///
 /// [§3.1 Parsing]: https://gpuweb.github.io/gpuweb/wgsl/#parsing
-fn consume_token(input: &str, generic: bool) -> (Token<'_>, &str) {
+pub(in crate::front::wgsl) fn consume_token(input: &str, generic: bool) -> (Token<'_>, &str) {
     let mut chars = input.chars();
     let cur = match chars.next() {
         Some(c) => c,
@@ -61,6 +67,15 @@ fn consume_token(input: &str, generic: bool) -> (Token<'_>, &str) {
             }
         }
         '@' => (Token::Attribute, chars.as_str()),
+        '"' => {
+            let rest = chars.as_str();
+            match rest.find(|c| c == '"' || is_comment_end(c)) {
+                Some(end) if rest[end..].starts_with('"') => {
+                    (Token::String(&rest[..end]), &rest[end + 1..])
+                }
+                _ => (Token::Unknown(cur), rest),
+            }
+        }
         '(' | ')' | '{' | '}' | '[' | ']' => (Token::Paren(cur), chars.as_str()),
         '<' | '>' => {
             let og_chars = chars.as_str();
@@ -204,6 +219,9 @@ pub(in crate::front::wgsl) struct Lexer<'a> {
     pub(in crate::front::wgsl) source: &'a str,
     // The byte offset of the end of the last non-trivia token.
     last_end_offset: usize,
+    /// The extensions requested by `enable` directives, which determine which
+    /// types and literals are accepted.
+    pub(in crate::front::wgsl) enable_extensions: EnableExtensions,
 }
 
 impl<'a> Lexer<'a> {
@@ -212,6 +230,22 @@ impl<'a> Lexer<'a> {
             input,
             source: input,
             last_end_offset: 0,
+            enable_extensions: EnableExtensions::empty(),
+        }
+    }
+
+    /// Create a lexer for the text of `source` in `range`.
+    ///
+    /// Spans are still measured from the start of `source`, so that several
+    /// files can be parsed out of a single [`SourceMap`] text.
+    ///
+    /// [`SourceMap`]: crate::SourceMap
+    pub(in crate::front::wgsl) fn new_in(source: &'a str, range: Range<usize>) -> Self {
+        Lexer {
+            input: &source[range.clone()],
+            source: &source[..range.end],
+            last_end_offset: range.start,
+            enable_extensions: EnableExtensions::empty(),
         }
     }
 
@@ -246,6 +280,27 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Return the `///` documentation comments before the next token.
+    ///
+    /// Each comment is returned without its `///` marker, or the single
+    /// space that usually follows it.
+    pub(in crate::front::wgsl) fn doc_comments(&self) -> Vec<&'a str> {
+        let start = self.clone().start_byte_offset();
+        let mut trivia = &self.source[self.last_end_offset..start];
+        let mut comments = Vec::new();
+        while !trivia.is_empty() {
+            let (_, rest) = consume_token(trivia, false);
+            let text = &trivia[..trivia.len() - rest.len()];
+            // `////` starts an ordinary comment, as in Rust.
+            if let Some(comment) = text.strip_prefix("///").filter(|c| !c.starts_with('/')) {
+                let comment = comment.trim_end();
+                comments.push(comment.strip_prefix(' ').unwrap_or(comment));
+            }
+            trivia = rest;
+        }
+        comments
+    }
+
     fn peek_token_and_rest(&mut self) -> (TokenSpan<'a>, &'a str) {
         let mut cloned = self.clone();
         let token = cloned.next();
@@ -340,6 +395,7 @@ impl<'a> Lexer<'a> {
         let (peeked_token, rest) = self.peek_token_and_rest();
         if peeked_token.0 == what {
             self.input = rest;
+            self.last_end_offset = self.current_byte_offset();
             true
         } else {
             false
@@ -378,7 +434,8 @@ impl<'a> Lexer<'a> {
         self.expect_generic_paren('<')?;
         let pair = match self.next() {
             (Token::Word(word), span) => {
-                conv::get_scalar_type(word).ok_or(Error::UnknownScalarType(span))
+                conv::get_scalar_type(&self.enable_extensions, span, word)?
+                    .ok_or(Error::UnknownScalarType(span))
             }
             (_, span) => Err(Error::UnknownScalarType(span)),
         }?;
@@ -394,9 +451,11 @@ impl<'a> Lexer<'a> {
     ) -> Result<(Scalar, Span), Error<'a>> {
         self.expect_generic_paren('<')?;
         let pair = match self.next() {
-            (Token::Word(word), span) => conv::get_scalar_type(word)
-                .map(|scalar| (scalar, span))
-                .ok_or(Error::UnknownScalarType(span)),
+            (Token::Word(word), span) => {
+                conv::get_scalar_type(&self.enable_extensions, span, word)?
+                    .map(|scalar| (scalar, span))
+                    .ok_or(Error::UnknownScalarType(span))
+            }
             (_, span) => Err(Error::UnknownScalarType(span)),
         }?;
         self.expect_generic_paren('>')?;
@@ -482,14 +541,14 @@ fn test_numbers() {
             Token::Number(Ok(Number::AbstractFloat(0.01))),
             Token::Number(Ok(Number::AbstractFloat(12.34))),
             Token::Number(Ok(Number::F32(0.))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(0.)))),
             Token::Number(Ok(Number::AbstractFloat(0.001))),
             Token::Number(Ok(Number::AbstractFloat(43.75))),
             Token::Number(Ok(Number::F32(16.))),
             Token::Number(Ok(Number::AbstractFloat(0.1875))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(12.)))),
             Token::Number(Ok(Number::AbstractFloat(0.12109375))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(12.5)))),
         ],
     );
 
@@ -604,6 +663,31 @@ fn test_numbers() {
             Token::Number(Err(NumberError::NotRepresentable)),
         ],
     );
+
+    // half precision floating point
+    sub_test(
+        "65504h 0x1.ffcp15h 6e-8h 65520h 0x1.001p0h",
+        &[
+            Token::Number(Ok(Number::F16(half::f16::MAX))),
+            Token::Number(Ok(Number::F16(half::f16::MAX))),
+            Token::Number(Ok(Number::F16(half::f16::from_bits(1)))), // ≈ 2^-24
+            Token::Number(Err(NumberError::NotRepresentable)),       // rounds to infinity
+            Token::Number(Err(NumberError::NotRepresentable)),       // inexact
+        ],
+    );
+    // Literals at and near the halfway point between 1 and the next `f16`,
+    // 1.00048828125, which `f32` and `f64` both round to exactly.
+    sub_test(
+        "1.00048828125h 1.000488281250000000000000001h 1.000488281249999999999999999h \
+         0.000000000100048828125000000000000001e10h 65519.99999999999999999h",
+        &[
+            Token::Number(Ok(Number::F16(half::f16::ONE))), // tie to even
+            Token::Number(Ok(Number::F16(half::f16::from_bits(0x3c01)))),
+            Token::Number(Ok(Number::F16(half::f16::ONE))),
+            Token::Number(Ok(Number::F16(half::f16::from_bits(0x3c01)))),
+            Token::Number(Ok(Number::F16(half::f16::MAX))),
+        ],
+    );
 }
 
 #[test]
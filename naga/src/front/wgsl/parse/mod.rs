@@ -1,12 +1,23 @@
+use crate::diagnostic_filter::{
+    DiagnosticFilter, DiagnosticFilterMap, DiagnosticFilterNode, FilterableTriggeringRule,
+    Severity, StandardFilterableTriggeringRule,
+};
 use crate::front::wgsl::error::{Error, ExpectedToken};
+use crate::front::wgsl::parse::directive::enable_extension::{
+    EnableExtension, EnableExtensions, ImplementedEnableExtension,
+};
+use crate::front::wgsl::parse::directive::language_extension::LanguageExtension;
+use crate::front::wgsl::parse::directive::DirectiveKind;
 use crate::front::wgsl::parse::lexer::{Lexer, Token};
 use crate::front::wgsl::parse::number::Number;
-use crate::front::wgsl::Scalar;
+use crate::front::wgsl::{Define, Scalar};
 use crate::front::SymbolTable;
-use crate::{Arena, FastIndexSet, Handle, ShaderStage, Span};
+use crate::{Arena, FastHashMap, FastHashSet, FastIndexSet, Handle, ShaderStage, SourceMap, Span};
 
 pub mod ast;
+mod condition;
 pub mod conv;
+pub mod directive;
 pub mod lexer;
 pub mod number;
 
@@ -53,6 +64,28 @@ struct ExpressionContext<'input, 'temp, 'out> {
     /// [`GlobalDecl`]: ast::GlobalDecl
     /// [`dependencies`]: ast::GlobalDecl::dependencies
     unresolved: &'out mut FastIndexSet<ast::Dependency<'input>>,
+
+    /// The [`TranslationUnit::diagnostic_filters`] arena to which we should
+    /// contribute the filters from `@diagnostic(…)` attributes on statements.
+    ///
+    /// [`TranslationUnit::diagnostic_filters`]: ast::TranslationUnit::diagnostic_filters
+    diagnostic_filters: &'out mut Arena<DiagnosticFilterNode>,
+
+    /// The leaf of the diagnostic filter rules in effect at the statement
+    /// being parsed.
+    diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
+
+    /// The [`TranslationUnit::conditions`] map, to which a formatting parser
+    /// adds the conditions of `@if` attributes.
+    ///
+    /// [`TranslationUnit::conditions`]: ast::TranslationUnit::conditions
+    conditions: &'out mut FastHashMap<usize, Vec<Handle<ast::Expression<'input>>>>,
+
+    /// Where to record statements' syntax errors, if we are recovering from
+    /// them rather than giving up at the first one.
+    ///
+    /// See [`Parser::parse_with_recovery`].
+    errors: Option<&'temp mut Vec<Error<'input>>>,
 }
 
 impl<'a> ExpressionContext<'a, '_, '_> {
@@ -99,7 +132,9 @@ impl<'a> ExpressionContext<'a, '_, '_> {
 /// as expected.
 #[derive(Clone, Debug, PartialEq)]
 enum Rule {
+    Directive,
     Attribute,
+    DiagnosticFilter,
     VariableDecl,
     TypeDecl,
     FunctionDecl,
@@ -160,8 +195,10 @@ impl<'a> BindingParser<'a> {
             "builtin" => {
                 lexer.expect(Token::Paren('('))?;
                 let (raw, span) = lexer.next_ident_with_span()?;
-                self.built_in
-                    .set(conv::map_built_in(raw, span)?, name_span)?;
+                self.built_in.set(
+                    conv::map_built_in(&lexer.enable_extensions, raw, span)?,
+                    name_span,
+                )?;
                 lexer.expect(Token::Paren(')'))?;
             }
             "interpolate" => {
@@ -177,6 +214,9 @@ impl<'a> BindingParser<'a> {
                 lexer.expect(Token::Paren(')'))?;
             }
             "second_blend_source" => {
+                lexer
+                    .enable_extensions
+                    .require(ImplementedEnableExtension::DualSourceBlending, name_span)?;
                 self.second_blend_source.set(true, name_span)?;
             }
             "invariant" => {
@@ -219,13 +259,66 @@ impl<'a> BindingParser<'a> {
     }
 }
 
+/// Errors that [`Parser::parse_with_recovery`] and
+/// [`Lowerer::lower_with_recovery`] have recovered from so far.
+///
+/// [`Lowerer::lower_with_recovery`]: super::lower::Lowerer::lower_with_recovery
+#[derive(Default)]
+pub struct Recovery<'a> {
+    /// Every error encountered, in the order it was found.
+    pub errors: Vec<Error<'a>>,
+
+    /// The names of global declarations that had errors.
+    ///
+    /// Declarations that refer to these are not lowered, since any errors
+    /// they produced would most likely be consequences of the first.
+    pub poisoned: FastHashSet<&'a str>,
+}
+
 pub struct Parser {
     rules: Vec<(Rule, usize)>,
+    /// The defines that `@if` conditions are evaluated against, if any
+    /// were given.
+    defines: Option<FastHashMap<String, Define>>,
+    /// Whether we are parsing for [`format`], rather than for lowering.
+    ///
+    /// [`format`]: super::format
+    formatting: bool,
 }
 
 impl Parser {
     pub const fn new() -> Self {
-        Parser { rules: Vec::new() }
+        Parser {
+            rules: Vec::new(),
+            defines: None,
+            formatting: false,
+        }
+    }
+
+    pub const fn new_with_defines(defines: FastHashMap<String, Define>) -> Self {
+        Parser {
+            rules: Vec::new(),
+            defines: Some(defines),
+            formatting: false,
+        }
+    }
+
+    /// Return a parser that produces a translation unit for [`format`] to
+    /// print, rather than one to lower.
+    ///
+    /// Code that `@if` attributes exclude is kept, and their conditions are
+    /// recorded in [`TranslationUnit::conditions`] rather than evaluated.
+    /// `import` declarations are accepted without an [`ImportResolver`].
+    ///
+    /// [`format`]: super::format
+    /// [`TranslationUnit::conditions`]: ast::TranslationUnit::conditions
+    /// [`ImportResolver`]: super::ImportResolver
+    pub const fn new_for_formatting() -> Self {
+        Parser {
+            rules: Vec::new(),
+            defines: None,
+            formatting: true,
+        }
     }
 
     fn reset(&mut self) {
@@ -246,6 +339,27 @@ impl Parser {
         lexer.span_from(initial)
     }
 
+    /// Parse an `@if` attribute's parenthesized condition, and return whether
+    /// it holds.
+    ///
+    /// When formatting, the condition is parsed as an expression and added to
+    /// `conditions` instead, and is taken to hold.
+    fn if_attribute<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        ctx: &mut ExpressionContext<'a, '_, '_>,
+        conditions: &mut Vec<Handle<ast::Expression<'a>>>,
+    ) -> Result<bool, Error<'a>> {
+        if !self.formatting {
+            return condition::evaluate(lexer, self.defines.as_ref());
+        }
+        lexer.expect(Token::Paren('('))?;
+        conditions.push(self.general_expression(lexer, ctx)?);
+        lexer.skip(Token::Separator(','));
+        lexer.expect(Token::Paren(')'))?;
+        Ok(true)
+    }
+
     fn switch_value<'a>(
         &mut self,
         lexer: &mut Lexer<'a>,
@@ -278,9 +392,22 @@ impl Parser {
         span: Span,
         ctx: &mut ExpressionContext<'a, '_, '_>,
     ) -> Result<Option<ast::ConstructorType<'a>>, Error<'a>> {
-        if let Some(scalar) = conv::get_scalar_type(word) {
+        if let Some(scalar) = conv::get_scalar_type(&lexer.enable_extensions, span, word)? {
             return Ok(Some(ast::ConstructorType::Scalar(scalar)));
         }
+        if let Some(alias) = conv::get_f16_alias(&lexer.enable_extensions, span, word)? {
+            return Ok(Some(match alias {
+                conv::F16Alias::Vector(size) => ast::ConstructorType::Vector {
+                    size,
+                    scalar: Scalar::F16,
+                },
+                conv::F16Alias::Matrix { columns, rows } => ast::ConstructorType::Matrix {
+                    columns,
+                    rows,
+                    width: 2,
+                },
+            }));
+        }
 
         let partial = match word {
             "vec2" => ast::ConstructorType::PartialVector {
@@ -631,6 +758,11 @@ impl Parser {
             (Token::Number(res), span) => {
                 let _ = lexer.next();
                 let num = res.map_err(|err| Error::BadNumber(span, err))?;
+                if let Number::F16(_) = num {
+                    lexer
+                        .enable_extensions
+                        .require(ImplementedEnableExtension::F16, span)?;
+                }
                 ast::Expression::Literal(ast::Literal::Number(num))
             }
             (Token::Word("RAY_FLAG_NONE"), _) => {
@@ -996,11 +1128,17 @@ impl Parser {
                     ExpectedToken::Token(Token::Separator(',')),
                 ));
             }
+            let doc_comments = lexer.doc_comments();
             let (mut size, mut align) = (ParsedAttribute::default(), ParsedAttribute::default());
+            let mut included = true;
+            let mut conditions = Vec::new();
             self.push_rule_span(Rule::Attribute, lexer);
             let mut bind_parser = BindingParser::default();
             while lexer.skip(Token::Attribute) {
                 match lexer.next_ident_with_span()? {
+                    ("if", _) => {
+                        included &= self.if_attribute(lexer, ctx, &mut conditions)?;
+                    }
                     ("size", name_span) => {
                         lexer.expect(Token::Paren('('))?;
                         let expr = self.general_expression(lexer, ctx)?;
@@ -1021,17 +1159,24 @@ impl Parser {
             let binding = bind_parser.finish(bind_span)?;
 
             let name = lexer.next_ident()?;
+            if !conditions.is_empty() {
+                ctx.conditions
+                    .insert(name.span.to_range().unwrap().start, conditions);
+            }
             lexer.expect(Token::Separator(':'))?;
             let ty = self.type_decl(lexer, ctx)?;
             ready = lexer.skip(Token::Separator(','));
 
-            members.push(ast::StructMember {
-                name,
-                ty,
-                binding,
-                size: size.value,
-                align: align.value,
-            });
+            if included {
+                members.push(ast::StructMember {
+                    name,
+                    ty,
+                    binding,
+                    size: size.value,
+                    align: align.value,
+                    doc_comments,
+                });
+            }
         }
 
         Ok(members)
@@ -1058,11 +1203,25 @@ impl Parser {
         &mut self,
         lexer: &mut Lexer<'a>,
         word: &'a str,
+        span: Span,
         ctx: &mut ExpressionContext<'a, '_, '_>,
     ) -> Result<Option<ast::Type<'a>>, Error<'a>> {
-        if let Some(scalar) = conv::get_scalar_type(word) {
+        if let Some(scalar) = conv::get_scalar_type(&lexer.enable_extensions, span, word)? {
             return Ok(Some(ast::Type::Scalar(scalar)));
         }
+        if let Some(alias) = conv::get_f16_alias(&lexer.enable_extensions, span, word)? {
+            return Ok(Some(match alias {
+                conv::F16Alias::Vector(size) => ast::Type::Vector {
+                    size,
+                    scalar: Scalar::F16,
+                },
+                conv::F16Alias::Matrix { columns, rows } => ast::Type::Matrix {
+                    columns,
+                    rows,
+                    width: 2,
+                },
+            }));
+        }
 
         Ok(Some(match word {
             "vec2" => {
@@ -1463,7 +1622,7 @@ impl Parser {
 
         let (name, span) = lexer.next_ident_with_span()?;
 
-        let ty = match self.type_decl_impl(lexer, name, ctx)? {
+        let ty = match self.type_decl_impl(lexer, name, span, ctx)? {
             Some(ty) => ty,
             None => {
                 ctx.unresolved.insert(ast::Dependency {
@@ -1474,9 +1633,8 @@ impl Parser {
             }
         };
 
-        self.pop_rule_span(lexer);
-
-        let handle = ctx.types.append(ty, Span::UNDEFINED);
+        let span = self.pop_rule_span(lexer);
+        let handle = ctx.types.append(ty, span);
         Ok(handle)
     }
 
@@ -1621,6 +1779,10 @@ impl Parser {
         block: &mut ast::Block<'a>,
         brace_nesting_level: u8,
     ) -> Result<(), Error<'a>> {
+        if lexer.peek().0 == Token::Attribute {
+            return self.conditional_statement(lexer, ctx, block, brace_nesting_level);
+        }
+
         self.push_rule_span(Rule::Statement, lexer);
         match lexer.peek() {
             (Token::Separator(';'), _) => {
@@ -1695,6 +1857,12 @@ impl Parser {
                             handle,
                         }))
                     }
+                    "const_assert" => {
+                        let _ = lexer.next();
+                        let condition = self.general_expression(lexer, ctx)?;
+                        lexer.expect(Token::Separator(';'))?;
+                        ast::StatementKind::ConstAssert(condition)
+                    }
                     "return" => {
                         let _ = lexer.next();
                         let value = if lexer.peek().0 != Token::Separator(';') {
@@ -1755,62 +1923,7 @@ impl Parser {
                             reject,
                         }
                     }
-                    "switch" => {
-                        let _ = lexer.next();
-                        let selector = self.general_expression(lexer, ctx)?;
-                        let brace_span = lexer.expect_span(Token::Paren('{'))?;
-                        let brace_nesting_level =
-                            Self::increase_brace_nesting(brace_nesting_level, brace_span)?;
-                        let mut cases = Vec::new();
-
-                        loop {
-                            // cases + default
-                            match lexer.next() {
-                                (Token::Word("case"), _) => {
-                                    // parse a list of values
-                                    let value = loop {
-                                        let value = self.switch_value(lexer, ctx)?;
-                                        if lexer.skip(Token::Separator(',')) {
-                                            if lexer.skip(Token::Separator(':')) {
-                                                break value;
-                                            }
-                                        } else {
-                                            lexer.skip(Token::Separator(':'));
-                                            break value;
-                                        }
-                                        cases.push(ast::SwitchCase {
-                                            value,
-                                            body: ast::Block::default(),
-                                            fall_through: true,
-                                        });
-                                    };
-
-                                    let body = self.block(lexer, ctx, brace_nesting_level)?.0;
-
-                                    cases.push(ast::SwitchCase {
-                                        value,
-                                        body,
-                                        fall_through: false,
-                                    });
-                                }
-                                (Token::Word("default"), _) => {
-                                    lexer.skip(Token::Separator(':'));
-                                    let body = self.block(lexer, ctx, brace_nesting_level)?.0;
-                                    cases.push(ast::SwitchCase {
-                                        value: ast::SwitchValue::Default,
-                                        body,
-                                        fall_through: false,
-                                    });
-                                }
-                                (Token::Paren('}'), _) => break,
-                                (_, span) => {
-                                    return Err(Error::Unexpected(span, ExpectedToken::SwitchItem))
-                                }
-                            }
-                        }
-
-                        ast::StatementKind::Switch { selector, cases }
-                    }
+                    "switch" => self.switch(lexer, ctx, brace_nesting_level)?,
                     "loop" => self.r#loop(lexer, ctx, brace_nesting_level)?,
                     "while" => {
                         let _ = lexer.next();
@@ -1845,6 +1958,7 @@ impl Parser {
                             body,
                             continuing: ast::Block::default(),
                             break_if: None,
+                            syntax: ast::LoopSyntax::While,
                         }
                     }
                     "for" => {
@@ -1853,7 +1967,8 @@ impl Parser {
 
                         ctx.local_table.push_scope();
 
-                        if !lexer.skip(Token::Separator(';')) {
+                        let init = !lexer.skip(Token::Separator(';'));
+                        if init {
                             let num_statements = block.stmts.len();
                             let (_, span) = {
                                 let ctx = &mut *ctx;
@@ -1917,6 +2032,7 @@ impl Parser {
                             body,
                             continuing,
                             break_if: None,
+                            syntax: ast::LoopSyntax::For { init },
                         }
                     }
                     "break" => {
@@ -1963,6 +2079,84 @@ impl Parser {
         Ok(())
     }
 
+    fn switch<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        ctx: &mut ExpressionContext<'a, '_, '_>,
+        brace_nesting_level: u8,
+    ) -> Result<ast::StatementKind<'a>, Error<'a>> {
+        let _ = lexer.next();
+        let selector = self.general_expression(lexer, ctx)?;
+        let outer_leaf = ctx.diagnostic_filter_leaf;
+        ctx.diagnostic_filter_leaf = self.diagnostic_filter_attributes(lexer, ctx)?;
+        let brace_span = lexer.expect_span(Token::Paren('{'))?;
+        let brace_nesting_level = Self::increase_brace_nesting(brace_nesting_level, brace_span)?;
+        let mut cases = Vec::new();
+
+        loop {
+            // cases + default
+            match lexer.next() {
+                (Token::Word("case"), _) => {
+                    // parse a list of values
+                    let value = loop {
+                        let value = self.switch_value(lexer, ctx)?;
+                        if lexer.skip(Token::Separator(',')) {
+                            if lexer.skip(Token::Separator(':')) {
+                                break value;
+                            }
+                        } else {
+                            lexer.skip(Token::Separator(':'));
+                            break value;
+                        }
+                        cases.push(ast::SwitchCase {
+                            value,
+                            body: ast::Block::default(),
+                            fall_through: true,
+                        });
+                    };
+
+                    let body = self.block(lexer, ctx, brace_nesting_level)?.0;
+
+                    cases.push(ast::SwitchCase {
+                        value,
+                        body,
+                        fall_through: false,
+                    });
+                }
+                (Token::Word("default"), _) => {
+                    lexer.skip(Token::Separator(':'));
+                    let body = self.block(lexer, ctx, brace_nesting_level)?.0;
+                    cases.push(ast::SwitchCase {
+                        value: ast::SwitchValue::Default,
+                        body,
+                        fall_through: false,
+                    });
+                }
+                (Token::Paren('}'), _) => break,
+                (_, span) => return Err(Error::Unexpected(span, ExpectedToken::SwitchItem)),
+            }
+        }
+
+        // Attributes on the switch body apply to each case.
+        let diagnostic_filter_leaf = if ctx.diagnostic_filter_leaf != outer_leaf {
+            for case in cases.iter_mut().filter(|case| !case.fall_through) {
+                case.body
+                    .diagnostic_filter_leaf
+                    .get_or_insert(ctx.diagnostic_filter_leaf.unwrap());
+            }
+            ctx.diagnostic_filter_leaf
+        } else {
+            None
+        };
+        ctx.diagnostic_filter_leaf = outer_leaf;
+
+        Ok(ast::StatementKind::Switch {
+            selector,
+            cases,
+            diagnostic_filter_leaf,
+        })
+    }
+
     fn r#loop<'a>(
         &mut self,
         lexer: &mut Lexer<'a>,
@@ -1974,6 +2168,9 @@ impl Parser {
         let mut continuing = ast::Block::default();
         let mut break_if = None;
 
+        let body_start = lexer.start_byte_offset();
+        let outer_leaf = ctx.diagnostic_filter_leaf;
+        ctx.diagnostic_filter_leaf = self.diagnostic_filter_attributes(lexer, ctx)?;
         let brace_span = lexer.expect_span(Token::Paren('{'))?;
         let brace_nesting_level = Self::increase_brace_nesting(brace_nesting_level, brace_span)?;
 
@@ -1981,9 +2178,18 @@ impl Parser {
 
         loop {
             if lexer.skip(Token::Word("continuing")) {
+                let continuing_start = lexer.start_byte_offset();
                 // Branch for the `continuing` block, this must be
                 // the last thing in the loop body
 
+                // The `continuing` block is inside the loop body, so it gets
+                // the body's diagnostic filters along with its own.
+                let body_leaf = ctx.diagnostic_filter_leaf;
+                ctx.diagnostic_filter_leaf = self.diagnostic_filter_attributes(lexer, ctx)?;
+                if ctx.diagnostic_filter_leaf != outer_leaf {
+                    continuing.diagnostic_filter_leaf = ctx.diagnostic_filter_leaf;
+                }
+
                 // Expect a opening brace to start the continuing block
                 let brace_span = lexer.expect_span(Token::Paren('{'))?;
                 let brace_nesting_level =
@@ -2016,9 +2222,16 @@ impl Parser {
                         break;
                     } else {
                         // Otherwise try to parse a statement
-                        self.statement(lexer, ctx, &mut continuing, brace_nesting_level)?;
+                        self.recovering_statement(
+                            lexer,
+                            ctx,
+                            &mut continuing,
+                            brace_nesting_level,
+                        )?;
                     }
                 }
+                continuing.span = lexer.span_from(continuing_start);
+                ctx.diagnostic_filter_leaf = body_leaf;
                 // Since the continuing block must be the last part of the loop body,
                 // we expect to see a closing brace to end the loop body
                 lexer.expect(Token::Paren('}'))?;
@@ -2030,18 +2243,164 @@ impl Parser {
                 break;
             }
             // Otherwise try to parse a statement
-            self.statement(lexer, ctx, &mut body, brace_nesting_level)?;
+            self.recovering_statement(lexer, ctx, &mut body, brace_nesting_level)?;
         }
 
         ctx.local_table.pop_scope();
 
+        if ctx.diagnostic_filter_leaf != outer_leaf {
+            body.diagnostic_filter_leaf = ctx.diagnostic_filter_leaf;
+        }
+        ctx.diagnostic_filter_leaf = outer_leaf;
+        body.span = lexer.span_from(body_start);
+
         Ok(ast::StatementKind::Loop {
             body,
             continuing,
             break_if,
+            syntax: ast::LoopSyntax::Loop,
         })
     }
 
+    /// Parse a statement with attributes.
+    ///
+    /// A statement with `@if` attributes is added to `block` only if their
+    /// conditions hold. A statement with `@diagnostic(…)` attributes is added
+    /// inside a new block that applies their diagnostic filters.
+    ///
+    /// This is kept out of [`Parser::statement`] to keep that function's
+    /// stack frame small, since it recurses for every nested block.
+    fn conditional_statement<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        ctx: &mut ExpressionContext<'a, '_, '_>,
+        block: &mut ast::Block<'a>,
+        brace_nesting_level: u8,
+    ) -> Result<(), Error<'a>> {
+        let start = lexer.start_byte_offset();
+        let mut included = true;
+        let mut conditions = Vec::new();
+        let mut diagnostic_filters = DiagnosticFilterMap::new();
+        let mut diagnostic_filter_spans = Vec::new();
+        while lexer.skip(Token::Attribute) {
+            match lexer.next_ident_with_span()? {
+                ("if", _) => {
+                    included &= self.if_attribute(lexer, ctx, &mut conditions)?;
+                }
+                ("diagnostic", name_span) => {
+                    let filter = self.diagnostic_filter(lexer)?;
+                    let span = lexer.span_from(name_span.to_range().unwrap().start);
+                    diagnostic_filters
+                        .add(filter, span)
+                        .map_err(Error::DiagnosticDuplicateTriggeringRule)?;
+                    diagnostic_filter_spans.push(span);
+                }
+                (_, word_span) => return Err(Error::UnknownAttribute(word_span)),
+            }
+        }
+
+        // Only compound statements, `if`, `switch`, and loops may have
+        // `@diagnostic(…)` attributes.
+        if !diagnostic_filter_spans.is_empty() {
+            match lexer.peek().0 {
+                Token::Paren('{') | Token::Word("if" | "switch" | "loop" | "for" | "while") => {}
+                _ => {
+                    return Err(Error::DiagnosticAttributeNotSupported {
+                        on_what_plural: "this statement",
+                        spans: diagnostic_filter_spans,
+                    })
+                }
+            }
+        }
+
+        let conditional = !conditions.is_empty();
+        if conditional {
+            ctx.conditions.insert(lexer.start_byte_offset(), conditions);
+        }
+
+        if included && !diagnostic_filter_spans.is_empty() {
+            let outer_leaf = ctx.diagnostic_filter_leaf;
+            let leaf = diagnostic_filters.into_nodes(ctx.diagnostic_filters, outer_leaf);
+            ctx.diagnostic_filter_leaf = leaf;
+            let mut filtered = ast::Block::default();
+            let result = self.statement(lexer, ctx, &mut filtered, brace_nesting_level);
+            ctx.diagnostic_filter_leaf = outer_leaf;
+            result?;
+
+            // A compound statement can take the filters itself, but
+            // anything else needs a block around it.
+            match filtered.stmts[..] {
+                [ast::Statement {
+                    kind: ast::StatementKind::Block(ref mut inner),
+                    ..
+                }] => inner.diagnostic_filter_leaf = leaf,
+                _ => {
+                    filtered.diagnostic_filter_leaf = leaf;
+                    filtered = ast::Block {
+                        stmts: vec![ast::Statement {
+                            kind: ast::StatementKind::Block(filtered),
+                            span: lexer.span_from(start),
+                        }],
+                        span: Span::UNDEFINED,
+                        diagnostic_filter_leaf: None,
+                    };
+                }
+            }
+            block.stmts.append(&mut filtered.stmts);
+            return Ok(());
+        }
+        if included && !conditional {
+            return self.statement(lexer, ctx, block, brace_nesting_level);
+        }
+
+        // Parse the statement in its own scope, so that nothing it declares
+        // is visible afterwards. Drop it if it is excluded; a statement kept
+        // for formatting might be excluded in other builds.
+        ctx.local_table.push_scope();
+        let mut excluded = ast::Block::default();
+        let target = if included { block } else { &mut excluded };
+        self.statement(lexer, ctx, target, brace_nesting_level)?;
+        ctx.local_table.pop_scope();
+        Ok(())
+    }
+
+    /// Parse a statement in a block, recovering from any syntax error in it
+    /// if `ctx` asks us to.
+    ///
+    /// When recovering, the error is recorded in [`ExpressionContext::errors`],
+    /// and we skip ahead to what looks like the end of the statement, so
+    /// that the rest of the block can still be checked.
+    fn recovering_statement<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        ctx: &mut ExpressionContext<'a, '_, '_>,
+        block: &mut ast::Block<'a>,
+        brace_nesting_level: u8,
+    ) -> Result<(), Error<'a>> {
+        let rule_depth = self.rules.len();
+        let diagnostic_filter_leaf = ctx.diagnostic_filter_leaf;
+        let statement_start = lexer.clone();
+        let error = match self.statement(lexer, ctx, block, brace_nesting_level) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let Some(errors) = ctx.errors.as_deref_mut() else {
+            return Err(error);
+        };
+        self.rules.truncate(rule_depth);
+        ctx.diagnostic_filter_leaf = diagnostic_filter_leaf;
+        // The error may have consumed a token that belongs to the enclosing
+        // block, so skip from the start of the statement, not the error.
+        *lexer = statement_start;
+        skip_statement(lexer);
+        // If there's nothing left, let the enclosing declaration report it.
+        if lexer.peek().0 == Token::End {
+            return Err(error);
+        }
+        errors.push(error);
+        Ok(())
+    }
+
     /// compound_statement
     fn block<'a>(
         &mut self,
@@ -2053,16 +2412,23 @@ impl Parser {
 
         ctx.local_table.push_scope();
 
+        let outer_leaf = ctx.diagnostic_filter_leaf;
+        ctx.diagnostic_filter_leaf = self.diagnostic_filter_attributes(lexer, ctx)?;
         let brace_span = lexer.expect_span(Token::Paren('{'))?;
         let brace_nesting_level = Self::increase_brace_nesting(brace_nesting_level, brace_span)?;
         let mut block = ast::Block::default();
         while !lexer.skip(Token::Paren('}')) {
-            self.statement(lexer, ctx, &mut block, brace_nesting_level)?;
+            self.recovering_statement(lexer, ctx, &mut block, brace_nesting_level)?;
         }
+        if ctx.diagnostic_filter_leaf != outer_leaf {
+            block.diagnostic_filter_leaf = ctx.diagnostic_filter_leaf;
+        }
+        ctx.diagnostic_filter_leaf = outer_leaf;
 
         ctx.local_table.pop_scope();
 
         let span = self.pop_rule_span(lexer);
+        block.span = span;
         Ok((block, span))
     }
 
@@ -2086,8 +2452,10 @@ impl Parser {
     fn function_decl<'a>(
         &mut self,
         lexer: &mut Lexer<'a>,
+        diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
         out: &mut ast::TranslationUnit<'a>,
         dependencies: &mut FastIndexSet<ast::Dependency<'a>>,
+        errors: Option<&mut Vec<Error<'a>>>,
     ) -> Result<ast::Function<'a>, Error<'a>> {
         self.push_rule_span(Rule::FunctionDecl, lexer);
         // read function name
@@ -2101,6 +2469,10 @@ impl Parser {
             locals: &mut locals,
             types: &mut out.types,
             unresolved: dependencies,
+            diagnostic_filters: &mut out.diagnostic_filters,
+            diagnostic_filter_leaf,
+            conditions: &mut out.conditions,
+            errors,
         };
 
         // start a scope that contains arguments as well as the function body
@@ -2143,15 +2515,21 @@ impl Parser {
         };
 
         // do not use `self.block` here, since we must not push a new scope
+        let body_start = lexer.start_byte_offset();
+        ctx.diagnostic_filter_leaf = self.diagnostic_filter_attributes(lexer, &mut ctx)?;
         lexer.expect(Token::Paren('{'))?;
         let brace_nesting_level = 1;
         let mut body = ast::Block::default();
         while !lexer.skip(Token::Paren('}')) {
-            self.statement(lexer, &mut ctx, &mut body, brace_nesting_level)?;
+            self.recovering_statement(lexer, &mut ctx, &mut body, brace_nesting_level)?;
         }
+        body.span = lexer.span_from(body_start);
 
         ctx.local_table.pop_scope();
 
+        // Attributes on the body apply to the whole function, like those on
+        // the function itself.
+        let diagnostic_filter_leaf = ctx.diagnostic_filter_leaf;
         let fun = ast::Function {
             entry_point: None,
             name: fun_name,
@@ -2159,6 +2537,7 @@ impl Parser {
             result,
             body,
             locals,
+            diagnostic_filter_leaf,
         };
 
         // done
@@ -2171,7 +2550,11 @@ impl Parser {
         &mut self,
         lexer: &mut Lexer<'a>,
         out: &mut ast::TranslationUnit<'a>,
+        file: usize,
+        errors: Option<&mut Vec<Error<'a>>>,
     ) -> Result<(), Error<'a>> {
+        let doc_comments = lexer.doc_comments();
+
         // read attributes
         let mut binding = None;
         let mut stage = ParsedAttribute::default();
@@ -2181,6 +2564,10 @@ impl Parser {
         let (mut bind_index, mut bind_group) =
             (ParsedAttribute::default(), ParsedAttribute::default());
         let mut id = ParsedAttribute::default();
+        let mut diagnostic_filters = DiagnosticFilterMap::new();
+        let mut diagnostic_filter_spans = Vec::new();
+        let mut included = true;
+        let mut conditions = Vec::new();
 
         let mut dependencies = FastIndexSet::default();
         let mut ctx = ExpressionContext {
@@ -2189,11 +2576,18 @@ impl Parser {
             locals: &mut Arena::new(),
             types: &mut out.types,
             unresolved: &mut dependencies,
+            diagnostic_filters: &mut out.diagnostic_filters,
+            diagnostic_filter_leaf: out.diagnostic_filter_leaf,
+            conditions: &mut out.conditions,
+            errors: None,
         };
 
         self.push_rule_span(Rule::Attribute, lexer);
         while lexer.skip(Token::Attribute) {
             match lexer.next_ident_with_span()? {
+                ("if", _) => {
+                    included &= self.if_attribute(lexer, &mut ctx, &mut conditions)?;
+                }
                 ("binding", name_span) => {
                     lexer.expect(Token::Paren('('))?;
                     bind_index.set(self.general_expression(lexer, &mut ctx)?, name_span)?;
@@ -2248,6 +2642,14 @@ impl Parser {
                     };
                     early_depth_test.set(crate::EarlyDepthTest { conservative }, name_span)?;
                 }
+                ("diagnostic", name_span) => {
+                    let filter = self.diagnostic_filter(lexer)?;
+                    let span = lexer.span_from(name_span.to_range().unwrap().start);
+                    diagnostic_filters
+                        .add(filter, span)
+                        .map_err(Error::DiagnosticDuplicateTriggeringRule)?;
+                    diagnostic_filter_spans.push(span);
+                }
                 (_, word_span) => return Err(Error::UnknownAttribute(word_span)),
             }
         }
@@ -2265,6 +2667,26 @@ impl Parser {
             (None, None) => {}
         }
 
+        // Only functions may have `@diagnostic(…)` attributes.
+        if !diagnostic_filter_spans.is_empty() {
+            let on_what_plural = match lexer.peek().0 {
+                Token::Word("fn") => None,
+                Token::Word("struct") => Some("structs"),
+                Token::Word("alias") => Some("type aliases"),
+                Token::Word("const") => Some("`const`s"),
+                Token::Word("override") => Some("`override`s"),
+                Token::Word("const_assert") => Some("`const_assert`s"),
+                Token::Word("var") => Some("module-scope `var`s"),
+                _ => Some("this item"),
+            };
+            if let Some(on_what_plural) = on_what_plural {
+                return Err(Error::DiagnosticAttributeNotSupported {
+                    on_what_plural,
+                    spans: diagnostic_filter_spans,
+                });
+            }
+        }
+
         // read item
         let start = lexer.start_byte_offset();
         let kind = match lexer.next() {
@@ -2323,13 +2745,26 @@ impl Parser {
                     init,
                 }))
             }
+            (Token::Word("const_assert"), _) => {
+                let condition = self.general_expression(lexer, &mut ctx)?;
+                lexer.expect(Token::Separator(';'))?;
+                Some(ast::GlobalDeclKind::ConstAssert(condition))
+            }
             (Token::Word("var"), _) => {
                 let mut var = self.variable_decl(lexer, &mut ctx)?;
                 var.binding = binding.take();
                 Some(ast::GlobalDeclKind::Var(var))
             }
             (Token::Word("fn"), _) => {
-                let function = self.function_decl(lexer, out, &mut dependencies)?;
+                let diagnostic_filter_leaf = diagnostic_filters
+                    .into_nodes(&mut out.diagnostic_filters, out.diagnostic_filter_leaf);
+                let function = self.function_decl(
+                    lexer,
+                    diagnostic_filter_leaf,
+                    out,
+                    &mut dependencies,
+                    errors,
+                )?;
                 Some(ast::GlobalDeclKind::Fn(ast::Function {
                     entry_point: if let Some(stage) = stage.value {
                         if stage == ShaderStage::Compute && workgroup_size.value.is_none() {
@@ -2347,12 +2782,26 @@ impl Parser {
                 }))
             }
             (Token::End, _) => return Ok(()),
+            (Token::Word(word), directive_span) if DirectiveKind::from_ident(word).is_some() => {
+                return Err(Error::DirectiveAfterFirstGlobalDecl { directive_span });
+            }
+            (Token::Word("import"), import_span) => {
+                return Err(Error::ImportAfterFirstGlobalDecl { import_span });
+            }
             other => return Err(Error::Unexpected(other.1, ExpectedToken::GlobalItem)),
         };
 
-        if let Some(kind) = kind {
+        if let Some(kind) = kind.filter(|_| included) {
+            if !conditions.is_empty() {
+                out.conditions.insert(start, conditions);
+            }
             out.decls.append(
-                ast::GlobalDecl { kind, dependencies },
+                ast::GlobalDecl {
+                    kind,
+                    dependencies,
+                    file,
+                    doc_comments,
+                },
                 lexer.span_from(start),
             );
         }
@@ -2370,22 +2819,340 @@ impl Parser {
     }
 
     pub fn parse<'a>(&mut self, source: &'a str) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
+        self.parse_impl(source, None)
+    }
+
+    /// Like [`parse`], but recover from syntax errors at directive,
+    /// declaration, and statement boundaries, rather than stopping at the
+    /// first one.
+    ///
+    /// Errors are recorded in `recovery`, along with the names of any global
+    /// declarations they affected. The returned translation unit contains
+    /// everything that could be parsed, including functions whose bodies had
+    /// errors, so callers should consult [`Recovery::poisoned`] before
+    /// lowering it.
+    ///
+    /// [`parse`]: Self::parse
+    pub fn parse_with_recovery<'a>(
+        &mut self,
+        source: &'a str,
+        recovery: &mut Recovery<'a>,
+    ) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
+        self.parse_impl(source, Some(recovery))
+    }
+
+    fn parse_impl<'a>(
+        &mut self,
+        source: &'a str,
+        recovery: Option<&mut Recovery<'a>>,
+    ) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
         self.reset();
 
-        let mut lexer = Lexer::new(source);
         let mut tu = ast::TranslationUnit::default();
+        self.parse_file(Lexer::new(source), 0, &mut tu, recovery)?;
+        Ok(tu)
+    }
+
+    /// Parse all the files in `source_map` into a single translation unit.
+    ///
+    /// `imports` holds, for each file, the indices of the files it imports,
+    /// as found by [`Parser::imports`].
+    pub fn parse_files<'a>(
+        &mut self,
+        source_map: &'a SourceMap,
+        imports: Vec<Vec<usize>>,
+    ) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
+        self.reset();
+
+        let mut tu = ast::TranslationUnit {
+            imports,
+            ..Default::default()
+        };
+        let mut diagnostic_filter_leaf = None;
+        for file in 0..source_map.files().len() {
+            self.parse_file(source_map.lexer(file), file, &mut tu, None)?;
+            if file == 0 {
+                diagnostic_filter_leaf = tu.diagnostic_filter_leaf;
+            }
+        }
+        // Other files' `diagnostic` directives apply only to their own
+        // functions, which have already been given their filter chains.
+        tu.diagnostic_filter_leaf = diagnostic_filter_leaf;
+
+        Ok(tu)
+    }
+
+    /// Return the path and span of each `import` declaration at the top of
+    /// the file `lexer` is reading.
+    ///
+    /// Directives are skipped without being checked; [`Parser::parse_files`]
+    /// takes care of that.
+    pub fn imports(mut lexer: Lexer<'_>) -> Result<Vec<(&str, Span)>, Error<'_>> {
+        while let (Token::Word(word), _) = lexer.peek() {
+            if DirectiveKind::from_ident(word).is_none() {
+                break;
+            }
+            skip_statement(&mut lexer);
+        }
+        let mut imports = Vec::new();
+        while lexer.peek().0 == Token::Word("import") {
+            imports.push(Self::import_decl(&mut lexer)?);
+        }
+        Ok(imports)
+    }
+
+    /// Parse an `import "path";` declaration, and return the path and its
+    /// span.
+    fn import_decl<'a>(lexer: &mut Lexer<'a>) -> Result<(&'a str, Span), Error<'a>> {
+        lexer.expect(Token::Word("import"))?;
+        let path = match lexer.next() {
+            (Token::String(path), span) => (path, span),
+            (_, span) => {
+                return Err(Error::Unexpected(
+                    span,
+                    ExpectedToken::Token(Token::String("")),
+                ))
+            }
+        };
+        lexer.expect(Token::Separator(';'))?;
+        Ok(path)
+    }
+
+    /// Parse the file `lexer` is reading, which is file number `file` of
+    /// `tu`, adding its declarations to `tu`.
+    fn parse_file<'a>(
+        &mut self,
+        mut lexer: Lexer<'a>,
+        file: usize,
+        tu: &mut ast::TranslationUnit<'a>,
+        mut recovery: Option<&mut Recovery<'a>>,
+    ) -> Result<(), Error<'a>> {
+        let mut enable_extensions = EnableExtensions::empty();
+        let mut diagnostic_filters = DiagnosticFilterMap::new();
+
+        // Parse directives, which must all come before any global declaration.
+        while let (Token::Word(word), _) = lexer.peek() {
+            let Some(kind) = DirectiveKind::from_ident(word) else {
+                break;
+            };
+            let directive_start = lexer.clone();
+            self.push_rule_span(Rule::Directive, &mut lexer);
+            let _ = lexer.next();
+            let mut names = Vec::new();
+            let result = match kind {
+                DirectiveKind::Diagnostic => {
+                    self.diagnostic_filter(&mut lexer).and_then(|filter| {
+                        let span = self.peek_rule_span(&lexer);
+                        diagnostic_filters
+                            .add(filter.clone(), span)
+                            .map_err(Error::DiagnosticDuplicateTriggeringRule)?;
+                        lexer.expect(Token::Separator(';'))?;
+                        Ok(ast::Directive::Diagnostic(filter))
+                    })
+                }
+                DirectiveKind::Enable => self
+                    .directive_ident_list(&mut lexer, |name, span| {
+                        names.push(ast::Ident { name, span });
+                        let kind = EnableExtension::from_ident(name, span)?;
+                        let extension = match kind {
+                            EnableExtension::Implemented(kind) => kind,
+                            EnableExtension::Unimplemented(kind) => {
+                                return Err(Error::EnableExtensionNotYetImplemented { kind, span })
+                            }
+                        };
+                        enable_extensions.add(extension);
+                        Ok(())
+                    })
+                    .map(|()| ast::Directive::Enable(names)),
+                DirectiveKind::Requires => self
+                    .directive_ident_list(&mut lexer, |name, span| {
+                        names.push(ast::Ident { name, span });
+                        match LanguageExtension::from_ident(name)
+                            .ok_or(Error::UnknownLanguageExtension(span, name))?
+                        {
+                            LanguageExtension::Implemented(_) => Ok(()),
+                            LanguageExtension::Unimplemented(kind) => {
+                                Err(Error::LanguageExtensionNotYetImplemented { kind, span })
+                            }
+                        }
+                    })
+                    .map(|()| ast::Directive::Requires(names)),
+            };
+            match result {
+                Ok(directive) => {
+                    let span = self.pop_rule_span(&lexer);
+                    tu.directives.append(directive, span);
+                }
+                Err(error) => {
+                    let Some(recovery) = recovery.as_deref_mut() else {
+                        return Err(error);
+                    };
+                    recovery.errors.push(error);
+                    self.rules.clear();
+                    lexer = directive_start;
+                    skip_statement(&mut lexer);
+                }
+            }
+        }
+
+        lexer.enable_extensions = enable_extensions;
+        tu.enable_extensions.extend(enable_extensions);
+        tu.diagnostic_filter_leaf = diagnostic_filters.into_nodes(&mut tu.diagnostic_filters, None);
+
+        // Parse imports, which must come before any other global declaration.
+        // The files they name have already been loaded by the time we get
+        // here, so there's nothing to do but check their syntax.
+        while lexer.peek().0 == Token::Word("import") {
+            let import_start = lexer.clone();
+            let result = Self::import_decl(&mut lexer).and_then(|(path, span)| {
+                tu.import_paths.push((path, span));
+                if tu.imports.is_empty() && !self.formatting {
+                    Err(Error::ImportWithoutResolver(span))
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(error) = result {
+                let Some(recovery) = recovery.as_deref_mut() else {
+                    return Err(error);
+                };
+                recovery.errors.push(error);
+                lexer = import_start;
+                skip_statement(&mut lexer);
+            }
+        }
+
         loop {
-            match self.global_decl(&mut lexer, &mut tu) {
-                Err(error) => return Err(error),
-                Ok(()) => {
-                    if lexer.peek().0 == Token::End {
-                        break;
-                    }
+            let decl_start = lexer.clone();
+            let Some(recovery) = recovery.as_deref_mut() else {
+                self.global_decl(&mut lexer, tu, file, None)?;
+                if lexer.peek().0 == Token::End {
+                    break;
                 }
+                continue;
+            };
+
+            let error_count = recovery.errors.len();
+            if let Err(error) = self.global_decl(&mut lexer, tu, file, Some(&mut recovery.errors)) {
+                recovery.errors.push(error);
+                self.rules.clear();
+                lexer = decl_start.clone();
+                skip_global_decl(&mut lexer);
+            }
+            if recovery.errors.len() > error_count {
+                recovery.poisoned.extend(declared_name(decl_start));
+            }
+            if lexer.peek().0 == Token::End {
+                break;
             }
         }
 
-        Ok(tu)
+        Ok(())
+    }
+
+    /// Parse any `@diagnostic(…)` attributes before a compound statement's
+    /// opening brace, returning the leaf of the diagnostic filter rules in
+    /// effect within it.
+    fn diagnostic_filter_attributes<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        ctx: &mut ExpressionContext<'a, '_, '_>,
+    ) -> Result<Option<Handle<DiagnosticFilterNode>>, Error<'a>> {
+        let mut diagnostic_filters = DiagnosticFilterMap::new();
+        while lexer.skip(Token::Attribute) {
+            match lexer.next_ident_with_span()? {
+                ("diagnostic", name_span) => {
+                    let filter = self.diagnostic_filter(lexer)?;
+                    let span = lexer.span_from(name_span.to_range().unwrap().start);
+                    diagnostic_filters
+                        .add(filter, span)
+                        .map_err(Error::DiagnosticDuplicateTriggeringRule)?;
+                }
+                (_, word_span) => return Err(Error::UnknownAttribute(word_span)),
+            }
+        }
+        Ok(diagnostic_filters.into_nodes(ctx.diagnostic_filters, ctx.diagnostic_filter_leaf))
+    }
+
+    /// Parse the parenthesized body of a `diagnostic` directive or
+    /// `@diagnostic` attribute, like `(off, derivative_uniformity)`.
+    fn diagnostic_filter<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+    ) -> Result<DiagnosticFilter, Error<'a>> {
+        self.push_rule_span(Rule::DiagnosticFilter, lexer);
+
+        lexer.expect(Token::Paren('('))?;
+
+        let (severity_control_name, severity_control_name_span) = lexer.next_ident_with_span()?;
+        let new_severity = Severity::from_ident(severity_control_name).ok_or(
+            Error::DiagnosticInvalidSeverity {
+                severity_control_name_span,
+            },
+        )?;
+
+        lexer.expect(Token::Separator(','))?;
+
+        let (diagnostic_name_token, diagnostic_name_token_span) = lexer.next_ident_with_span()?;
+        let triggering_rule = if lexer.skip(Token::Separator('.')) {
+            let (ident, _span) = lexer.next_ident_with_span()?;
+            FilterableTriggeringRule::User(diagnostic_name_token.to_owned(), ident.to_owned())
+        } else {
+            match StandardFilterableTriggeringRule::from_ident(diagnostic_name_token) {
+                Some(rule) => FilterableTriggeringRule::Standard(rule),
+                None => {
+                    // The standard asks us to warn about unrecognized rules,
+                    // rather than reject them.
+                    log::warn!(
+                        "unrecognized diagnostic filter triggering rule `{}` at {:?}",
+                        diagnostic_name_token,
+                        diagnostic_name_token_span
+                    );
+                    FilterableTriggeringRule::Unknown(diagnostic_name_token.to_owned())
+                }
+            }
+        };
+
+        lexer.skip(Token::Separator(','));
+        lexer.expect(Token::Paren(')'))?;
+
+        self.pop_rule_span(lexer);
+
+        Ok(DiagnosticFilter {
+            new_severity,
+            triggering_rule,
+        })
+    }
+
+    /// Parse a comma-separated list of identifiers terminated by a `;`, as
+    /// found in directives like `enable f16, clip_distances;`.
+    fn directive_ident_list<'a>(
+        &self,
+        lexer: &mut Lexer<'a>,
+        mut handler: impl FnMut(&'a str, Span) -> Result<(), Error<'a>>,
+    ) -> Result<(), Error<'a>> {
+        loop {
+            let (ident, span) = lexer.next_ident_with_span()?;
+            handler(ident, span)?;
+            let expected_token = match lexer.peek().0 {
+                Token::Separator(',') => {
+                    let _ = lexer.next();
+                    if matches!(lexer.peek().0, Token::Word(..)) {
+                        continue;
+                    }
+                    Token::Separator(';')
+                }
+                Token::Separator(';') => {
+                    let _ = lexer.next();
+                    return Ok(());
+                }
+                _ => Token::Separator(','),
+            };
+            return Err(Error::Unexpected(
+                lexer.next().1,
+                ExpectedToken::Token(expected_token),
+            ));
+        }
     }
 
     const fn increase_brace_nesting(
@@ -2414,3 +3181,118 @@ impl Parser {
         Ok(brace_nesting_level + 1)
     }
 }
+
+/// Skip the rest of a statement after a syntax error in it.
+///
+/// This consumes tokens up to and including the next `;` that isn't nested
+/// in brackets, or up to the end of the next balanced `{ ... }` group (and
+/// any `else` clauses following it). A `}` closing the enclosing block is
+/// left for the caller.
+fn skip_statement(lexer: &mut Lexer<'_>) {
+    let mut depth = 0usize;
+    loop {
+        match lexer.peek().0 {
+            Token::End => return,
+            Token::Paren('}') if depth == 0 => return,
+            Token::Paren('{' | '(' | '[') => depth += 1,
+            Token::Paren('}') => {
+                depth -= 1;
+                if depth == 0 {
+                    let _ = lexer.next();
+                    if lexer.peek().0 != Token::Word("else") {
+                        return;
+                    }
+                    continue;
+                }
+            }
+            // An unmatched closing bracket belongs to something that was
+            // already open when the error occurred.
+            Token::Paren(')' | ']') => depth = depth.saturating_sub(1),
+            Token::Separator(';') if depth == 0 => {
+                let _ = lexer.next();
+                return;
+            }
+            _ => {}
+        }
+        let _ = lexer.next();
+    }
+}
+
+/// Skip over a global declaration that has a syntax error in it.
+///
+/// `lexer` should be positioned at the start of the declaration. This
+/// consumes its attributes and first token, and then everything up to and
+/// including the next `;` or balanced `{ ... }` group that isn't nested in
+/// brackets, stopping early at anything that looks like the start of the
+/// next declaration.
+fn skip_global_decl(lexer: &mut Lexer<'_>) {
+    skip_attributes(lexer);
+    let _ = lexer.next();
+    let mut depth = 0usize;
+    loop {
+        match lexer.peek().0 {
+            Token::End => return,
+            Token::Word(
+                "fn" | "struct" | "alias" | "const" | "override" | "var" | "const_assert",
+            ) if depth == 0 => return,
+            Token::Separator(';') if depth == 0 => {
+                let _ = lexer.next();
+                return;
+            }
+            Token::Paren('}') if depth <= 1 => {
+                let _ = lexer.next();
+                return;
+            }
+            Token::Paren('{' | '(' | '[') => depth += 1,
+            Token::Paren('}' | ')' | ']') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let _ = lexer.next();
+    }
+}
+
+/// Skip any attributes at `lexer`, without checking them.
+fn skip_attributes(lexer: &mut Lexer<'_>) {
+    while lexer.skip(Token::Attribute) {
+        let _ = lexer.next();
+        if lexer.skip(Token::Paren('(')) {
+            let mut depth = 1usize;
+            while depth > 0 {
+                match lexer.next().0 {
+                    Token::End => return,
+                    Token::Paren('(') => depth += 1,
+                    Token::Paren(')') => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Return the name declared by the global declaration starting at `lexer`,
+/// if it gets far enough to have one.
+///
+/// This is used after a declaration has failed to parse, so it makes no
+/// attempt to validate what it skips over.
+fn declared_name(mut lexer: Lexer<'_>) -> Option<&str> {
+    skip_attributes(&mut lexer);
+    match lexer.next().0 {
+        Token::Word("fn" | "struct" | "alias" | "const" | "override") => {}
+        Token::Word("var") => {
+            if lexer.skip(Token::Paren('<')) {
+                loop {
+                    match lexer.next().0 {
+                        Token::End => return None,
+                        Token::Paren('>') => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        _ => return None,
+    }
+    match lexer.next().0 {
+        Token::Word(name) => Some(name),
+        _ => None,
+    }
+}
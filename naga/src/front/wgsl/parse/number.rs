@@ -1,5 +1,6 @@
 use crate::front::wgsl::error::NumberError;
 use crate::front::wgsl::parse::lexer::Token;
+use half::f16;
 
 /// When using this type assume no Abstract Int/Float for now
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -16,6 +17,8 @@ pub enum Number {
     I64(i64),
     /// Concrete u64
     U64(u64),
+    /// Concrete f16
+    F16(f16),
     /// Concrete f32
     F32(f32),
     /// Concrete f64
@@ -362,7 +365,17 @@ fn parse_hex_float(input: &str, kind: Option<FloatKind>) -> Result<Number, Numbe
             // can only be ParseHexfErrorKind::Inexact but we can't check since it's private
             _ => Err(NumberError::NotRepresentable),
         },
-        Some(FloatKind::F16) => Err(NumberError::UnimplementedF16),
+        Some(FloatKind::F16) => match hexf_parse::parse_hexf64(input, false) {
+            Ok(num) => {
+                // `hexf_parse` has no `f16` support, so check that the
+                // conversion from `f64` is exact ourselves
+                let half = f16::from_f64(num);
+                (half.to_f64() == num)
+                    .then_some(Number::F16(half))
+                    .ok_or(NumberError::NotRepresentable)
+            }
+            _ => Err(NumberError::NotRepresentable),
+        },
         Some(FloatKind::F32) => match hexf_parse::parse_hexf32(input, false) {
             Ok(num) => Ok(Number::F32(num)),
             // can only be ParseHexfErrorKind::Inexact but we can't check since it's private
@@ -398,10 +411,81 @@ fn parse_dec_float(input: &str, kind: Option<FloatKind>) -> Result<Number, Numbe
                 .then_some(Number::F64(num))
                 .ok_or(NumberError::NotRepresentable)
         }
-        Some(FloatKind::F16) => Err(NumberError::UnimplementedF16),
+        Some(FloatKind::F16) => {
+            let num = parse_dec_f16(input);
+            num.is_finite()
+                .then_some(Number::F16(num))
+                .ok_or(NumberError::NotRepresentable)
+        }
     }
 }
 
+/// Parse a decimal floating point literal as an `f16`, rounding correctly.
+///
+/// There's no decimal parser for `f16`, and parsing as a wider type first
+/// rounds twice: a literal just past the halfway point between two `f16`
+/// values can round to exactly that point, and then to the wrong neighbor.
+/// So when the `f64` value is halfway between two `f16` values, we compare
+/// `input` with it exactly to decide which way to round.
+fn parse_dec_f16(input: &str) -> f16 {
+    let num = input.parse::<f64>().unwrap(); // will never fail
+    let half = f16::from_f64(num);
+
+    // Literals are never negative, so the neighbors of `half` are one
+    // apart in its bit pattern. Rounding up from `f16::MAX` gives infinity,
+    // but the rounding boundary is where the next power of two would be.
+    let (below, above) = if half.to_f64() <= num {
+        (half, f16::from_bits(half.to_bits() + 1))
+    } else {
+        (f16::from_bits(half.to_bits() - 1), half)
+    };
+    let above_f64 = if above.is_infinite() {
+        f64::from(f16::MAX) + 32.0
+    } else {
+        above.to_f64()
+    };
+    let midpoint = (below.to_f64() + above_f64) / 2.0;
+    if num != midpoint {
+        return half;
+    }
+
+    match cmp_dec_float(input, midpoint) {
+        core::cmp::Ordering::Less => below,
+        core::cmp::Ordering::Greater => above,
+        // `from_f64` rounds ties to even, as WGSL requires.
+        core::cmp::Ordering::Equal => half,
+    }
+}
+
+/// Compare the decimal floating point literal `input` with `value` exactly.
+///
+/// `value` must be positive and have a short exact decimal representation,
+/// like the halfway points between `f16` values.
+fn cmp_dec_float(input: &str, value: f64) -> core::cmp::Ordering {
+    /// Return the power of ten to scale `0.digits` by to get the value of
+    /// a decimal number, and its significant `digits`.
+    fn normalize(mantissa: &str, exponent: i64) -> (i64, String) {
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{int}{frac}");
+        let significant = digits.trim_start_matches('0');
+        let exponent = exponent + int.len() as i64 - (digits.len() - significant.len()) as i64;
+        (exponent, significant.trim_end_matches('0').to_string())
+    }
+
+    // A literal that parses to `value` can't have a huge exponent.
+    let input = match input.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => normalize(mantissa, exponent.parse().unwrap()),
+        None => normalize(input, 0),
+    };
+
+    // Rust prints floats exactly, given enough precision.
+    let value = format!("{value:.40e}");
+    let (mantissa, exponent) = value.split_once('e').unwrap();
+    let value = normalize(mantissa, exponent.parse().unwrap());
+
+    input.cmp(&value)
+}
+
 fn parse_int(input: &str, kind: Option<IntKind>, radix: u32) -> Result<Number, NumberError> {
     fn map_err(e: core::num::ParseIntError) -> NumberError {
         match *e.kind() {
@@ -636,3 +636,376 @@ fn parse_missing_workgroup_size() {
         Error::MissingWorkgroupSize(span) if span == Span::new(1, 8)
     ));
 }
+
+#[test]
+fn parse_imports() {
+    use super::{Frontend, ImportResolver, ResolvedImport};
+
+    struct Resolver;
+
+    impl ImportResolver for Resolver {
+        fn resolve(&mut self, importer: &str, path: &str) -> Result<ResolvedImport, String> {
+            let source = match (importer, path) {
+                ("main.wgsl", "a.wgsl") => {
+                    "
+                    fn helper() -> f32 { return 1.0; }
+                    fn a() -> f32 { return helper(); }
+                    "
+                }
+                ("main.wgsl", "b.wgsl") => {
+                    "
+                    import \"a.wgsl\";
+                    struct Light { color: vec3<f32> }
+                    fn helper() -> f32 { return a() * 2.0; }
+                    fn b() -> f32 { return helper(); }
+                    "
+                }
+                ("b.wgsl", "a.wgsl") => return self.resolve("main.wgsl", path),
+                _ => return Err("not found".to_string()),
+            };
+            Ok(ResolvedImport {
+                name: path.to_string(),
+                source: source.to_string(),
+            })
+        }
+    }
+
+    let source = "
+        import \"a.wgsl\";
+        import \"b.wgsl\";
+        struct Light { intensity: f32 }
+        @fragment
+        fn main() -> @location(0) vec4<f32> {
+            let light = Light(a() + b());
+            return vec4(light.intensity);
+        }
+    ";
+    let (module, source_map) = Frontend::new()
+        .parse_with_imports("main.wgsl", source, &mut Resolver)
+        .unwrap();
+
+    let names: Vec<_> = module
+        .functions
+        .iter()
+        .map(|(_, function)| function.name.as_deref().unwrap())
+        .collect();
+    assert_eq!(names, ["helper", "a", "helper_1", "b"]);
+
+    let (file, span) = source_map
+        .file_for_span(
+            module
+                .functions
+                .get_span(module.functions.iter().nth(2).unwrap().0),
+        )
+        .unwrap();
+    let file = &source_map.files()[file];
+    assert_eq!(file.name(), "b.wgsl");
+    assert!(source_map.source(file)[span].starts_with("fn helper()"));
+
+    crate::valid::Validator::new(Default::default(), crate::valid::Capabilities::all())
+        .validate(&module)
+        .unwrap();
+}
+
+#[test]
+fn parse_conditional_compilation() {
+    use super::{Define, Frontend, Options};
+
+    let source = "
+        struct Light {
+            color: vec3<f32>,
+            @if(CASCADES > 1)
+            cascade_splits: vec4<f32>,
+        }
+
+        @if(SHADOWS && !defined(NO_PCF))
+        fn shadow() -> f32 { return 0.5; }
+
+        @if(!SHADOWS || defined(NO_PCF))
+        fn shadow() -> f32 { return 1.0; }
+
+        fn main() -> f32 {
+            var total = shadow();
+            @if(CASCADES >= 4) {
+                total *= 2.0;
+            }
+            return total;
+        }
+    ";
+
+    let parse = |defines: &[(&str, Define)]| {
+        let options = Options {
+            defines: defines
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value))
+                .collect(),
+        };
+        Frontend::new_with_options(options).parse(source).unwrap()
+    };
+
+    let module = parse(&[("SHADOWS", true.into()), ("CASCADES", 4.into())]);
+    let (_, light) = module
+        .types
+        .iter()
+        .find(|&(_, ty)| ty.name.is_some())
+        .unwrap();
+    match light.inner {
+        crate::TypeInner::Struct { ref members, .. } => assert_eq!(members.len(), 2),
+        _ => unreachable!(),
+    }
+    let (shadow, _) = module.functions.iter().next().unwrap();
+    let span = module.functions.get_span(shadow);
+    assert!(source[span].contains("return 0.5;"));
+    let (_, main) = module.functions.iter().nth(1).unwrap();
+    let body_len = main.body.len();
+
+    let module = parse(&[
+        ("SHADOWS", true.into()),
+        ("NO_PCF", true.into()),
+        ("CASCADES", 1.into()),
+    ]);
+    let (_, light) = module
+        .types
+        .iter()
+        .find(|&(_, ty)| ty.name.is_some())
+        .unwrap();
+    match light.inner {
+        crate::TypeInner::Struct { ref members, .. } => assert_eq!(members.len(), 1),
+        _ => unreachable!(),
+    }
+    let (shadow, _) = module.functions.iter().next().unwrap();
+    let span = module.functions.get_span(shadow);
+    assert!(source[span].contains("return 1.0;"));
+    let (_, main) = module.functions.iter().nth(1).unwrap();
+    assert_eq!(main.body.len(), body_len - 1);
+}
+
+#[test]
+fn parse_conditional_compilation_short_circuit() {
+    use super::{Define, Frontend, Options};
+
+    let source = "
+        @if(defined(CASCADES) && CASCADES > 2)
+        fn cascades() {}
+
+        @if(!defined(CASCADES) || CASCADES <= 2)
+        fn no_cascades() {}
+    ";
+
+    let parse = |defines: &[(&str, Define)]| {
+        let options = Options {
+            defines: defines
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value))
+                .collect(),
+        };
+        let module = Frontend::new_with_options(options).parse(source).unwrap();
+        module
+            .functions
+            .iter()
+            .map(|(_, function)| function.name.clone().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(parse(&[]), ["no_cascades"]);
+    assert_eq!(parse(&[("CASCADES", 4.into())]), ["cascades"]);
+    assert_eq!(parse(&[("CASCADES", 2.into())]), ["no_cascades"]);
+
+    // A right operand that is not evaluated must still parse.
+    let options = Options::default();
+    assert!(Frontend::new_with_options(options)
+        .parse("@if(false && 1x) fn f() {}")
+        .is_err());
+}
+
+#[test]
+fn analyze() {
+    use super::{Frontend, SymbolKind};
+
+    let source = "
+        struct Light {
+            color: vec3<f32>,
+            intensity: f32,
+        }
+
+        @group(0) @binding(0)
+        var<uniform> light: Light;
+
+        fn scale(value: f32) -> f32 {
+            return value * light.intensity;
+        }
+
+        @fragment
+        fn main() -> @location(0) vec4<f32> {
+            let brightness = scale(2.0);
+            var color = light.color;
+            color *= brightness;
+            return vec4(color, 1.0);
+        }
+    ";
+    let analysis = Frontend::new().analyze(source);
+    assert!(analysis.errors().is_empty());
+
+    let offset = |text: &str| source.find(text).unwrap();
+    let text = |span: crate::Span| &source[span];
+
+    let summary: Vec<_> = analysis
+        .symbols()
+        .iter()
+        .map(|symbol| {
+            let children: Vec<_> = symbol
+                .children
+                .iter()
+                .map(|child| (child.name.as_str(), child.kind))
+                .collect();
+            (symbol.name.as_str(), symbol.kind, children)
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "Light",
+                SymbolKind::Struct,
+                vec![
+                    ("color", SymbolKind::Member),
+                    ("intensity", SymbolKind::Member)
+                ]
+            ),
+            ("light", SymbolKind::GlobalVariable, vec![]),
+            (
+                "scale",
+                SymbolKind::Function,
+                vec![("value", SymbolKind::Argument)]
+            ),
+            (
+                "main",
+                SymbolKind::EntryPoint(crate::ShaderStage::Fragment),
+                vec![
+                    ("brightness", SymbolKind::Let),
+                    ("color", SymbolKind::LocalVariable)
+                ]
+            ),
+        ]
+    );
+
+    let symbol = analysis.declaration_at(offset("2.0")).unwrap();
+    assert_eq!(symbol.name, "brightness");
+    assert_eq!(text(symbol.span), "let brightness = scale(2.0);");
+
+    // Go to definition, for globals, types, functions and locals.
+    let definition = analysis.definition_at(offset("light.intensity")).unwrap();
+    assert_eq!(definition, analysis.symbols()[1].name_span);
+    let definition = analysis.definition_at(offset("Light;")).unwrap();
+    assert_eq!(definition, analysis.symbols()[0].name_span);
+    let definition = analysis.definition_at(offset("scale(2.0)")).unwrap();
+    assert_eq!(definition, analysis.symbols()[2].name_span);
+    let definition = analysis.definition_at(offset("color, 1.0")).unwrap();
+    assert_eq!(definition, analysis.symbols()[3].children[1].name_span);
+    assert_eq!(analysis.definition_at(offset("vec4(")), None);
+
+    let references: Vec<_> = analysis
+        .references_to(analysis.symbols()[3].children[1].name_span)
+        .map(text)
+        .collect();
+    assert_eq!(references, ["color", "color"]);
+
+    // Types, for expressions and for names bound to values.
+    let ty = analysis.type_at(offset("value *") + 6).unwrap();
+    assert_eq!(text(ty.span), "value * light.intensity");
+    assert_eq!(ty.name, "f32");
+    let ty = analysis.type_at(offset("light.color")).unwrap();
+    assert_eq!(ty.name, "Light");
+    let ty = analysis.type_at(offset("brightness;")).unwrap();
+    assert_eq!(ty.name, "f32");
+    let ty = analysis.type_at(offset("color, 1.0")).unwrap();
+    assert_eq!(text(ty.span), "color");
+    assert_eq!(ty.name, "vec3<f32>");
+    let ty = analysis.type_at(offset("vec4(")).unwrap();
+    assert_eq!(ty.name, "vec4<f32>");
+
+    // Analysis carries on past errors.
+    let analysis = Frontend::new().analyze("fn f() { let x = ; }\nfn g() {}");
+    assert_eq!(analysis.errors().len(), 1);
+    let names: Vec<_> = analysis.symbols().iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["f", "g"]);
+}
+
+#[test]
+fn format() {
+    let source = "// Lighting.
+enable f16;
+struct Light{color:vec3<f32>,// linear
+  intensity : f32}
+
+
+@group(0)@binding(0) var<uniform> light:Light;
+const scale=-2.0*3e1f;
+fn shade(n:vec3<f32>)->f32{let x=array<i32,2>(1,2)[0];
+if x>=1&&x<3{return 1.0;}else if(x==0){return-1.0;}
+/* nothing
+   here */
+for(var i=0u;i<4u;i++){ }
+  var m: mat2x2<f32>;
+  let v = vec2(1.0,
+      2.0) * m;
+return dot(n,light.color)*light.intensity+
+  scale;}
+";
+    let expected = "// Lighting.
+enable f16;
+
+struct Light {
+    color: vec3<f32>, // linear
+    intensity: f32,
+}
+
+@group(0) @binding(0) var<uniform> light: Light;
+
+const scale = -2.0 * 3e1f;
+
+fn shade(n: vec3<f32>) -> f32 {
+    let x = array<i32, 2>(1, 2)[0];
+    if x >= 1 && x < 3 {
+        return 1.0;
+    } else if x == 0 {
+        return -1.0;
+    }
+
+    /* nothing
+   here */
+    for (var i = 0u; i < 4u; i++) {}
+    var m: mat2x2<f32>;
+    let v = vec2(1.0, 2.0) * m;
+    return dot(n, light.color) * light.intensity + scale;
+}
+";
+    let formatted = super::format(source).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(super::format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn format_breaks_long_lines() {
+    let expected = "fn f(
+    first_argument: vec4<f32>,
+    second_argument: vec4<f32>,
+    third_argument: vec4<f32>,
+) -> vec4<f32> {
+    let sum = first_argument * 2.0
+        + second_argument * third_argument
+        - first_argument * second_argument * 0.5;
+    return max(
+        first_argument,
+        min(second_argument, clamp(third_argument, vec4(0.0), vec4(1.0, 2.0, 3.0, 4.0))),
+    );
+}
+";
+    assert_eq!(super::format(expected).unwrap(), expected);
+
+    // Line breaks depend only on the module, not on how the source was wrapped.
+    let one_line = expected.split_whitespace().collect::<Vec<_>>().join(" ");
+    assert_eq!(super::format(&one_line).unwrap(), expected);
+    let wrapped = expected.replace(", ", ",\n").replace("(", "(\n  ");
+    assert_eq!(super::format(&wrapped).unwrap(), expected);
+}
@@ -67,6 +67,9 @@ impl crate::TypeInner {
                 let base = base.to_wgsl(gctx);
                 match size {
                     crate::ArraySize::Constant(size) => format!("array<{base}, {size}>"),
+                    crate::ArraySize::Pending(size) => {
+                        format!("array<{base}, {}>", size.to_wgsl(gctx))
+                    }
                     crate::ArraySize::Dynamic => format!("array<{base}>"),
                 }
             }
@@ -123,6 +126,9 @@ impl crate::TypeInner {
                 let base = member_type.name.as_deref().unwrap_or("unknown");
                 match size {
                     crate::ArraySize::Constant(size) => format!("binding_array<{base}, {size}>"),
+                    crate::ArraySize::Pending(size) => {
+                        format!("binding_array<{base}, {}>", size.to_wgsl(gctx))
+                    }
                     crate::ArraySize::Dynamic => format!("binding_array<{base}>"),
                 }
             }
@@ -130,6 +136,21 @@ impl crate::TypeInner {
     }
 }
 
+impl crate::PendingArraySize {
+    /// Format an override-dependent array size for use in diagnostics.
+    ///
+    /// Sizes that aren't a bare override name are written as `_`.
+    fn to_wgsl(self, gctx: &GlobalCtx) -> String {
+        match self {
+            crate::PendingArraySize::Override(handle) => gctx.overrides[handle]
+                .name
+                .clone()
+                .unwrap_or_else(|| "_".to_string()),
+            crate::PendingArraySize::Expression(_) => "_".to_string(),
+        }
+    }
+}
+
 impl crate::Scalar {
     /// Format a scalar kind+width as a type is written in wgsl.
     ///
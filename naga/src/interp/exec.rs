@@ -0,0 +1,790 @@
+//! Running invocations, one statement at a time.
+//!
+//! Each invocation keeps an explicit stack of [`Frame`]s, and each frame an
+//! explicit stack of [`Cursor`]s into the blocks it is executing, rather than
+//! using Rust's own stack. That lets [`Executor::run`] suspend an invocation
+//! at a barrier and resume it once the rest of its workgroup catches up.
+
+use std::borrow::Cow;
+
+use super::{
+    ops,
+    value::{self, Pointer, Region, Value},
+    Error, Interpreter,
+};
+use crate::{
+    arena::HandleVec, proc::BoundsCheckPolicy, valid::FunctionInfo, AddressSpace, ArraySize,
+    AtomicFunction, BinaryOperator, Block, Expression, Function, GlobalVariable, Handle, Literal,
+    LocalVariable, Scalar, Statement, SwitchCase, SwitchValue, TypeInner,
+};
+
+/// The memory backing a global variable during a dispatch.
+pub(super) enum Memory<'r> {
+    /// A storage or uniform buffer supplied by the caller.
+    Buffer(&'r mut [u8]),
+    /// Push constants, or a workgroup variable.
+    Owned(Vec<u8>),
+    /// A private variable: each [`Invocation`] has its own copy.
+    Private,
+    /// A variable the entry point doesn't use, or an opaque handle.
+    None,
+}
+
+/// Why [`Executor::run`] returned.
+pub(super) enum Status {
+    /// The invocation reached a barrier.
+    Barrier,
+    /// The invocation returned from its entry point.
+    Finished,
+}
+
+/// The state of a single invocation of an entry point.
+pub(super) struct Invocation<'m> {
+    frames: Vec<Frame<'m>>,
+    private: HandleVec<GlobalVariable, Vec<u8>>,
+    /// A [`WorkGroupUniformLoad`] waiting for the barrier before it.
+    ///
+    /// [`WorkGroupUniformLoad`]: Statement::WorkGroupUniformLoad
+    pending_load: Option<PendingLoad>,
+}
+
+struct PendingLoad {
+    pointer: Handle<Expression>,
+    result: Handle<Expression>,
+}
+
+/// A function call in progress.
+struct Frame<'m> {
+    function: &'m Function,
+    info: &'m FunctionInfo,
+    arguments: Vec<Value>,
+    /// The values of the expressions evaluated by [`Emit`] statements so far,
+    /// and of call and atomic results.
+    ///
+    /// [`Emit`]: Statement::Emit
+    values: HandleVec<Expression, Option<Value>>,
+    locals: HandleVec<LocalVariable, Vec<u8>>,
+    cursors: Vec<Cursor<'m>>,
+    /// The expression in the caller that receives this call's result.
+    result: Option<Handle<Expression>>,
+}
+
+/// A position in a block, and what to do when the block is done.
+struct Cursor<'m> {
+    block: &'m Block,
+    next: usize,
+    kind: CursorKind<'m>,
+}
+
+enum CursorKind<'m> {
+    /// The body of a function: finishing it returns from the function.
+    Body,
+    /// A plain nested block, or an arm of an `If`.
+    Block,
+    /// A loop's body: finishing it runs the continuing block.
+    LoopBody(Loop<'m>),
+    /// A loop's continuing block: finishing it checks `break_if`, and then
+    /// runs the body again.
+    Continuing(Loop<'m>),
+    /// The body of `cases[index]`, which may fall through to the next case.
+    Case {
+        cases: &'m [SwitchCase],
+        index: usize,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Loop<'m> {
+    body: &'m Block,
+    continuing: &'m Block,
+    break_if: Option<Handle<Expression>>,
+}
+
+impl<'m> Cursor<'m> {
+    const fn new(block: &'m Block, kind: CursorKind<'m>) -> Self {
+        Cursor {
+            block,
+            next: 0,
+            kind,
+        }
+    }
+}
+
+impl<'m> Invocation<'m> {
+    pub const fn new(private: HandleVec<GlobalVariable, Vec<u8>>) -> Self {
+        Invocation {
+            frames: Vec::new(),
+            private,
+            pending_load: None,
+        }
+    }
+
+    fn top(&self) -> &Frame<'m> {
+        self.frames.last().expect("invocation has no frames")
+    }
+
+    fn top_mut(&mut self) -> &mut Frame<'m> {
+        self.frames.last_mut().expect("invocation has no frames")
+    }
+}
+
+/// Runs the invocations of a single dispatch.
+pub(super) struct Executor<'m, 'r> {
+    pub interp: &'m Interpreter<'m>,
+    pub memory: HandleVec<GlobalVariable, Memory<'r>>,
+    /// The number of statements left to run before [`Error::StepLimit`].
+    pub steps_left: Option<u64>,
+}
+
+const fn check_index(
+    policy: BoundsCheckPolicy,
+    index: u64,
+    len: u64,
+) -> Result<Option<u64>, Error> {
+    if index < len {
+        return Ok(Some(index));
+    }
+    match policy {
+        BoundsCheckPolicy::Restrict => Ok(Some(len.saturating_sub(1))),
+        BoundsCheckPolicy::ReadZeroSkipWrite => Ok(None),
+        BoundsCheckPolicy::Unchecked => Err(Error::OutOfBounds),
+    }
+}
+
+impl<'m, 'r> Executor<'m, 'r> {
+    /// Start a call to `function` in `inv`.
+    ///
+    /// The call's value, if any, is stored as `result` in the caller's frame.
+    pub fn call(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        function: &'m Function,
+        info: &'m FunctionInfo,
+        arguments: Vec<Value>,
+        result: Option<Handle<Expression>>,
+    ) -> Result<(), Error> {
+        let mut locals = HandleVec::with_capacity(function.local_variables.len());
+        for (handle, local) in function.local_variables.iter() {
+            let size = self.interp.layouter[local.ty].size;
+            locals.insert(handle, vec![0; size as usize]);
+        }
+        let mut values = HandleVec::new();
+        values.resize(function.expressions.len(), None);
+        inv.frames.push(Frame {
+            function,
+            info,
+            arguments,
+            values,
+            locals,
+            cursors: vec![Cursor::new(&function.body, CursorKind::Body)],
+            result,
+        });
+
+        let frame = inv.frames.len() - 1;
+        for (variable, local) in function.local_variables.iter() {
+            if let Some(init) = local.init {
+                let value = self.eval(inv, init)?;
+                let pointer = Pointer {
+                    region: Region::Local { frame, variable },
+                    offset: 0,
+                    skip: false,
+                };
+                let inner = &self.interp.module.types[local.ty].inner;
+                self.store(inv, pointer, inner, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `inv` until it reaches a barrier or finishes.
+    pub fn run(&mut self, inv: &mut Invocation<'m>) -> Result<Status, Error> {
+        if let Some(load) = inv.pending_load.take() {
+            // The barrier before the load is done: load the value, and wait
+            // at the barrier after it.
+            let pointer = self.eval(inv, load.pointer)?.pointer()?;
+            let inner = self.pointee(inv.top().info, load.pointer)?;
+            let value = self.load(inv, pointer, &inner)?;
+            inv.top_mut().values[load.result] = Some(value);
+            return Ok(Status::Barrier);
+        }
+
+        loop {
+            let Some(frame) = inv.frames.last_mut() else {
+                return Ok(Status::Finished);
+            };
+            let cursor = frame.cursors.last_mut().expect("frame has no cursors");
+            let block: &'m Block = cursor.block;
+            let Some(statement) = block.get(cursor.next) else {
+                let cursor = frame.cursors.pop().expect("frame has no cursors");
+                self.finish(inv, cursor)?;
+                continue;
+            };
+            cursor.next += 1;
+
+            if let Some(ref mut steps_left) = self.steps_left {
+                *steps_left = steps_left.checked_sub(1).ok_or(Error::StepLimit)?;
+            }
+            if let Some(status) = self.execute(inv, statement)? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Handle reaching the end of `cursor`'s block.
+    fn finish(&mut self, inv: &mut Invocation<'m>, cursor: Cursor<'m>) -> Result<(), Error> {
+        match cursor.kind {
+            CursorKind::Body => self.return_(inv, None),
+            CursorKind::Block => {}
+            CursorKind::LoopBody(loop_) => inv
+                .top_mut()
+                .cursors
+                .push(Cursor::new(loop_.continuing, CursorKind::Continuing(loop_))),
+            CursorKind::Continuing(loop_) => {
+                let done = match loop_.break_if {
+                    Some(condition) => self.eval(inv, condition)?.bool()?,
+                    None => false,
+                };
+                if !done {
+                    inv.top_mut()
+                        .cursors
+                        .push(Cursor::new(loop_.body, CursorKind::LoopBody(loop_)));
+                }
+            }
+            CursorKind::Case { cases, index } => {
+                if cases[index].fall_through {
+                    if let Some(next) = cases.get(index + 1) {
+                        let kind = CursorKind::Case {
+                            cases,
+                            index: index + 1,
+                        };
+                        inv.top_mut().cursors.push(Cursor::new(&next.body, kind));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pop the current frame, passing `value` to the caller.
+    fn return_(&mut self, inv: &mut Invocation<'m>, value: Option<Value>) {
+        let frame = inv.frames.pop().expect("invocation has no frames");
+        if let (Some(result), Some(caller)) = (frame.result, inv.frames.last_mut()) {
+            caller.values[result] = value;
+        }
+    }
+
+    /// Execute `statement`, returning a status if `inv` must stop.
+    fn execute(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        statement: &'m Statement,
+    ) -> Result<Option<Status>, Error> {
+        let module = self.interp.module;
+        match *statement {
+            Statement::Emit(ref range) => {
+                for expr in range.clone() {
+                    let value = self.compute(inv, expr)?;
+                    inv.top_mut().values[expr] = Some(value);
+                }
+            }
+            Statement::Block(ref block) => {
+                inv.top_mut()
+                    .cursors
+                    .push(Cursor::new(block, CursorKind::Block));
+            }
+            Statement::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => {
+                let block = if self.eval(inv, condition)?.bool()? {
+                    accept
+                } else {
+                    reject
+                };
+                inv.top_mut()
+                    .cursors
+                    .push(Cursor::new(block, CursorKind::Block));
+            }
+            Statement::Switch {
+                selector,
+                ref cases,
+            } => {
+                let selector = self.eval(inv, selector)?.scalar()?;
+                let index = cases
+                    .iter()
+                    .position(|case| match case.value {
+                        SwitchValue::I32(value) => selector == Literal::I32(value),
+                        SwitchValue::U32(value) => selector == Literal::U32(value),
+                        SwitchValue::Default => false,
+                    })
+                    .or_else(|| {
+                        cases
+                            .iter()
+                            .position(|case| case.value == SwitchValue::Default)
+                    });
+                if let Some(index) = index {
+                    let kind = CursorKind::Case { cases, index };
+                    inv.top_mut()
+                        .cursors
+                        .push(Cursor::new(&cases[index].body, kind));
+                }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+            } => {
+                let loop_ = Loop {
+                    body,
+                    continuing,
+                    break_if,
+                };
+                inv.top_mut()
+                    .cursors
+                    .push(Cursor::new(body, CursorKind::LoopBody(loop_)));
+            }
+            Statement::Break => {
+                let cursors = &mut inv.top_mut().cursors;
+                while let Some(cursor) = cursors.pop() {
+                    match cursor.kind {
+                        CursorKind::LoopBody(_)
+                        | CursorKind::Continuing(_)
+                        | CursorKind::Case { .. } => break,
+                        CursorKind::Body | CursorKind::Block => {}
+                    }
+                }
+            }
+            Statement::Continue => {
+                let cursors = &mut inv.top_mut().cursors;
+                while let Some(cursor) = cursors.pop() {
+                    if let CursorKind::LoopBody(loop_) = cursor.kind {
+                        let continuing =
+                            Cursor::new(loop_.continuing, CursorKind::Continuing(loop_));
+                        cursors.push(continuing);
+                        break;
+                    }
+                }
+            }
+            Statement::Return { value } => {
+                let value = match value {
+                    Some(value) => Some(self.eval(inv, value)?),
+                    None => None,
+                };
+                self.return_(inv, value);
+            }
+            Statement::Kill => return Err(Error::Unsupported("discarding".to_string())),
+            Statement::Barrier(_) => return Ok(Some(Status::Barrier)),
+            Statement::Store { pointer, value } => {
+                let address = self.eval(inv, pointer)?.pointer()?;
+                let value = self.eval(inv, value)?;
+                let inner = self.pointee(inv.top().info, pointer)?;
+                self.store(inv, address, &inner, &value)?;
+            }
+            Statement::Atomic {
+                pointer,
+                ref fun,
+                value,
+                result,
+            } => {
+                let address = self.eval(inv, pointer)?.pointer()?;
+                let operand = self.eval(inv, value)?.scalar()?;
+                let inner = self.pointee(inv.top().info, pointer)?;
+                let old = self.load(inv, address, &inner)?.scalar()?;
+                let binary = |op| ops::binary_scalar(op, old, operand);
+                let (new, result_value) = match *fun {
+                    AtomicFunction::Add => (binary(BinaryOperator::Add)?, Value::Scalar(old)),
+                    AtomicFunction::Subtract => {
+                        (binary(BinaryOperator::Subtract)?, Value::Scalar(old))
+                    }
+                    AtomicFunction::And => (binary(BinaryOperator::And)?, Value::Scalar(old)),
+                    AtomicFunction::ExclusiveOr => {
+                        (binary(BinaryOperator::ExclusiveOr)?, Value::Scalar(old))
+                    }
+                    AtomicFunction::InclusiveOr => {
+                        (binary(BinaryOperator::InclusiveOr)?, Value::Scalar(old))
+                    }
+                    AtomicFunction::Min => (ops::min(old, operand)?, Value::Scalar(old)),
+                    AtomicFunction::Max => (ops::max(old, operand)?, Value::Scalar(old)),
+                    AtomicFunction::Exchange { compare: None } => (operand, Value::Scalar(old)),
+                    AtomicFunction::Exchange {
+                        compare: Some(compare),
+                    } => {
+                        let exchanged = old == self.eval(inv, compare)?.scalar()?;
+                        let new = if exchanged { operand } else { old };
+                        let result = Value::Composite(vec![
+                            Value::Scalar(old),
+                            Value::Scalar(Literal::Bool(exchanged)),
+                        ]);
+                        (new, result)
+                    }
+                };
+                self.store(inv, address, &inner, &Value::Scalar(new))?;
+                if let Some(result) = result {
+                    inv.top_mut().values[result] = Some(result_value);
+                }
+            }
+            Statement::WorkGroupUniformLoad { pointer, result } => {
+                // This is a barrier, the load, and another barrier. Stop at
+                // the first one, and let `run` do the rest.
+                inv.pending_load = Some(PendingLoad { pointer, result });
+                return Ok(Some(Status::Barrier));
+            }
+            Statement::Call {
+                function,
+                ref arguments,
+                result,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|&argument| self.eval(inv, argument))
+                    .collect::<Result<_, _>>()?;
+                let info = &self.interp.info[function];
+                self.call(inv, &module.functions[function], info, arguments, result)?;
+            }
+            Statement::ImageStore { .. } => return Err(Error::Unsupported("images".to_string())),
+            Statement::RayQuery { .. } => {
+                return Err(Error::Unsupported("ray queries".to_string()))
+            }
+            Statement::SubgroupBallot { .. }
+            | Statement::SubgroupGather { .. }
+            | Statement::SubgroupCollectiveOperation { .. } => {
+                return Err(Error::Unsupported("subgroup operations".to_string()))
+            }
+            Statement::DebugPrintf { .. } | Statement::DiagnosticFilter(_) => {}
+        }
+        Ok(None)
+    }
+
+    /// Return the value of `expr` in the current frame.
+    fn eval(&mut self, inv: &mut Invocation<'m>, expr: Handle<Expression>) -> Result<Value, Error> {
+        match inv.top().values[expr] {
+            Some(ref value) => Ok(value.clone()),
+            None => self.compute(inv, expr),
+        }
+    }
+
+    /// Compute the value of `expr` in the current frame from its operands.
+    fn compute(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        expr: Handle<Expression>,
+    ) -> Result<Value, Error> {
+        let module = self.interp.module;
+        let types = &module.types;
+        let Frame { function, info, .. } = *inv.top();
+        Ok(match function.expressions[expr] {
+            Expression::Literal(literal) => Value::Scalar(literal),
+            Expression::Constant(handle) => self.interp.constant(module.constants[handle].init)?,
+            Expression::Override(_) => return Err(super::overrides()),
+            Expression::ZeroValue(ty) => Value::zero(types, &types[ty].inner)?,
+            Expression::Compose { ty, ref components } => {
+                let components = components
+                    .iter()
+                    .map(|&component| self.eval(inv, component))
+                    .collect::<Result<_, _>>()?;
+                Value::compose(&types[ty].inner, components)
+            }
+            Expression::Access { base, index } => {
+                let index = self.eval(inv, index)?.index()?;
+                self.access(inv, expr, base, index)?
+            }
+            Expression::AccessIndex { base, index } => {
+                self.access(inv, expr, base, index as u64)?
+            }
+            Expression::Splat { size, value } => {
+                Value::Composite(vec![self.eval(inv, value)?; size as usize])
+            }
+            Expression::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let components = self.eval(inv, vector)?.components()?;
+                Value::Composite(
+                    pattern[..size as usize]
+                        .iter()
+                        .map(|&component| components[component as usize].clone())
+                        .collect(),
+                )
+            }
+            Expression::FunctionArgument(index) => inv.top().arguments[index as usize].clone(),
+            Expression::GlobalVariable(handle) => {
+                if module.global_variables[handle].space == AddressSpace::Handle {
+                    return Err(Error::Unsupported("images and samplers".to_string()));
+                }
+                Value::Pointer(Pointer {
+                    region: Region::Global(handle),
+                    offset: 0,
+                    skip: false,
+                })
+            }
+            Expression::LocalVariable(variable) => Value::Pointer(Pointer {
+                region: Region::Local {
+                    frame: inv.frames.len() - 1,
+                    variable,
+                },
+                offset: 0,
+                skip: false,
+            }),
+            Expression::Load { pointer } => {
+                let address = self.eval(inv, pointer)?.pointer()?;
+                let inner = self.pointee(info, pointer)?;
+                self.load(inv, address, &inner)?
+            }
+            Expression::ImageSample { .. }
+            | Expression::ImageLoad { .. }
+            | Expression::ImageQuery { .. } => {
+                return Err(Error::Unsupported("images".to_string()))
+            }
+            Expression::Unary { op, expr } => ops::unary(op, &self.eval(inv, expr)?)?,
+            Expression::Binary { op, left, right } => {
+                let left_value = self.eval(inv, left)?;
+                let right_value = self.eval(inv, right)?;
+                ops::binary(
+                    op,
+                    &left_value,
+                    info[left].ty.inner_with(types),
+                    &right_value,
+                    info[right].ty.inner_with(types),
+                )?
+            }
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition = self.eval(inv, condition)?;
+                let accept = self.eval(inv, accept)?;
+                let reject = self.eval(inv, reject)?;
+                ops::select(&condition, accept, reject)?
+            }
+            Expression::Derivative { .. } => {
+                return Err(Error::Unsupported("derivatives".to_string()))
+            }
+            Expression::Relational { fun, argument } => {
+                ops::relational(fun, &self.eval(inv, argument)?)?
+            }
+            Expression::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let args = [Some(arg), arg1, arg2, arg3]
+                    .into_iter()
+                    .flatten()
+                    .map(|arg| self.eval(inv, arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                ops::math(fun, &args)?
+            }
+            Expression::As {
+                expr,
+                kind,
+                convert,
+            } => {
+                let value = self.eval(inv, expr)?;
+                Value::map(&[value], &mut |args| match convert {
+                    Some(width) => ops::convert(args[0], Scalar { kind, width }),
+                    None => ops::bitcast(args[0], kind),
+                })?
+            }
+            Expression::ArrayLength(pointer) => {
+                let address = self.eval(inv, pointer)?.pointer()?;
+                match *self.pointee(info, pointer)? {
+                    TypeInner::Array {
+                        size: ArraySize::Dynamic,
+                        stride,
+                        ..
+                    } => {
+                        Value::Scalar(Literal::U32(self.dynamic_len(inv, address, stride)? as u32))
+                    }
+                    TypeInner::Array {
+                        size: ArraySize::Constant(len),
+                        ..
+                    } => Value::Scalar(Literal::U32(len.get())),
+                    ref other => {
+                        return Err(Error::Unsupported(format!("the length of {other:?}")))
+                    }
+                }
+            }
+            Expression::CallResult(_)
+            | Expression::AtomicResult { .. }
+            | Expression::WorkGroupUniformLoadResult { .. }
+            | Expression::SubgroupBallotResult
+            | Expression::SubgroupOperationResult { .. }
+            | Expression::RayQueryProceedResult => {
+                return Err(Error::Unsupported(format!(
+                    "using {expr:?} before the statement that produces it"
+                )))
+            }
+            Expression::RayQueryGetIntersection { .. } => {
+                return Err(Error::Unsupported("ray queries".to_string()))
+            }
+        })
+    }
+
+    /// Evaluate an `Access` or `AccessIndex` expression `expr`.
+    fn access(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        expr: Handle<Expression>,
+        base: Handle<Expression>,
+        index: u64,
+    ) -> Result<Value, Error> {
+        let types = &self.interp.module.types;
+        let info = inv.top().info;
+        let policy = self
+            .interp
+            .options
+            .bounds_check_policies
+            .choose_policy(base, types, info);
+
+        let pointer = match self.eval(inv, base)? {
+            Value::Pointer(pointer) => pointer,
+            Value::Composite(mut components) => {
+                let len = components.len() as u64;
+                return match check_index(policy, index, len)? {
+                    Some(index) => Ok(components.swap_remove(index as usize)),
+                    None => Value::zero(types, info[expr].ty.inner_with(types)),
+                };
+            }
+            Value::Scalar(literal) => {
+                return Err(Error::Unsupported(format!("indexing {literal:?}")))
+            }
+        };
+
+        let (len, stride) = match *info[base].ty.inner_with(types) {
+            TypeInner::Pointer { base: pointee, .. } => match types[pointee].inner {
+                TypeInner::Struct { ref members, .. } => {
+                    let member = &members[index as usize];
+                    return Ok(Value::Pointer(Pointer {
+                        offset: pointer.offset + member.offset,
+                        ..pointer
+                    }));
+                }
+                TypeInner::Array { size, stride, .. } => {
+                    let len = match size {
+                        ArraySize::Constant(len) => len.get() as u64,
+                        ArraySize::Dynamic => self.dynamic_len(inv, pointer, stride)?,
+                        ArraySize::Pending(_) => return Err(super::overrides()),
+                    };
+                    (len, stride)
+                }
+                TypeInner::Vector { size, scalar } => (size as u64, scalar.width as u32),
+                TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                } => (columns as u64, value::column_stride(rows, scalar)),
+                ref other => return Err(Error::Unsupported(format!("indexing {other:?}"))),
+            },
+            TypeInner::ValuePointer {
+                size: Some(size),
+                scalar,
+                ..
+            } => (size as u64, scalar.width as u32),
+            ref other => return Err(Error::Unsupported(format!("indexing {other:?}"))),
+        };
+
+        Ok(Value::Pointer(match check_index(policy, index, len)? {
+            Some(index) => Pointer {
+                offset: pointer.offset + index as u32 * stride,
+                ..pointer
+            },
+            None => Pointer {
+                skip: true,
+                ..pointer
+            },
+        }))
+    }
+
+    /// The type that the pointer `expr` points to.
+    fn pointee(
+        &self,
+        info: &'m FunctionInfo,
+        expr: Handle<Expression>,
+    ) -> Result<Cow<'m, TypeInner>, Error> {
+        let types = &self.interp.module.types;
+        match *info[expr].ty.inner_with(types) {
+            TypeInner::Pointer { base, .. } => Ok(Cow::Borrowed(&types[base].inner)),
+            TypeInner::ValuePointer {
+                size: Some(size),
+                scalar,
+                ..
+            } => Ok(Cow::Owned(TypeInner::Vector { size, scalar })),
+            TypeInner::ValuePointer {
+                size: None, scalar, ..
+            } => Ok(Cow::Owned(TypeInner::Scalar(scalar))),
+            ref other => Err(Error::Unsupported(format!("dereferencing {other:?}"))),
+        }
+    }
+
+    /// The number of elements in the runtime-sized array at `pointer`.
+    fn dynamic_len(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        pointer: Pointer,
+        stride: u32,
+    ) -> Result<u64, Error> {
+        let len = self.bytes(inv, pointer.region)?.len() as u64;
+        Ok(len.saturating_sub(pointer.offset as u64) / stride as u64)
+    }
+
+    fn bytes<'b>(
+        &'b mut self,
+        inv: &'b mut Invocation<'m>,
+        region: Region,
+    ) -> Result<&'b mut [u8], Error> {
+        match region {
+            Region::Global(handle) => match self.memory[handle] {
+                Memory::Buffer(ref mut bytes) => Ok(bytes),
+                Memory::Owned(ref mut bytes) => Ok(bytes),
+                Memory::Private => Ok(&mut inv.private[handle]),
+                Memory::None => Err(Error::Unsupported(format!(
+                    "accessing {:?}",
+                    self.interp.module.global_variables[handle].name
+                ))),
+            },
+            Region::Local { frame, variable } => Ok(&mut inv.frames[frame].locals[variable]),
+        }
+    }
+
+    fn load(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        pointer: Pointer,
+        inner: &TypeInner,
+    ) -> Result<Value, Error> {
+        let types = &self.interp.module.types;
+        if pointer.skip {
+            return Value::zero(types, inner);
+        }
+        Value::read(
+            types,
+            inner,
+            self.bytes(inv, pointer.region)?,
+            pointer.offset,
+        )
+    }
+
+    fn store(
+        &mut self,
+        inv: &mut Invocation<'m>,
+        pointer: Pointer,
+        inner: &TypeInner,
+        value: &Value,
+    ) -> Result<(), Error> {
+        if pointer.skip {
+            return Ok(());
+        }
+        let types = &self.interp.module.types;
+        value.write(
+            types,
+            inner,
+            self.bytes(inv, pointer.region)?,
+            pointer.offset,
+        )
+    }
+}
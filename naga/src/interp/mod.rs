@@ -0,0 +1,354 @@
+/*!
+A reference interpreter for Naga IR.
+
+[`Interpreter`] runs compute entry points of a validated [`Module`] on the
+CPU, reading and writing buffers supplied by the caller as byte slices. It
+can serve as a golden model: shaders can be unit-tested, and the output of
+the backends compared against it, on machines with no GPU.
+
+The interpreter aims to be simple and predictable rather than fast:
+
+- Values in memory are laid out as [`Layouter`] and the module's own struct
+  offsets and array strides specify.
+
+- Arithmetic follows [`ConstantEvaluator`], except where that reports errors
+  for conditions only detectable at runtime, like integer overflow or
+  division by zero. In those cases, the interpreter follows the WGSL runtime
+  semantics that the backends implement.
+
+- Out-of-bounds indexing follows the [`BoundsCheckPolicies`] given in
+  [`Options`]. Under [`BoundsCheckPolicy::Unchecked`], where real hardware
+  would have undefined behavior, the interpreter reports
+  [`Error::OutOfBounds`].
+
+- The invocations of each workgroup run one at a time, each up to its next
+  barrier, in order of [`LocalInvocationIndex`]. Workgroups run one at a
+  time, in order of their ids with `x` varying fastest. A shader whose
+  results depend on any other interleaving has a race.
+
+Images, samplers, derivatives, ray queries and subgroup operations are not
+supported, and neither are pipeline-overridable constants: apply
+`back::pipeline_constants::process_overrides` to the module first.
+
+[`Module`]: crate::Module
+[`Layouter`]: crate::proc::Layouter
+[`ConstantEvaluator`]: crate::proc::ConstantEvaluator
+[`BoundsCheckPolicy::Unchecked`]: crate::proc::BoundsCheckPolicy::Unchecked
+[`LocalInvocationIndex`]: crate::BuiltIn::LocalInvocationIndex
+*/
+
+mod exec;
+mod ops;
+mod value;
+
+use crate::{
+    arena::HandleVec,
+    proc::{BoundsCheckPolicies, LayoutError, Layouter},
+    valid::ModuleInfo,
+    AddressSpace, Binding, BuiltIn, Expression, FastHashMap, FunctionArgument, Handle, Module,
+    ResourceBinding, ShaderStage, TypeInner,
+};
+use exec::{Executor, Invocation, Memory, Status};
+use value::Value;
+
+/// Options for the interpreter.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// How to handle out-of-bounds indexing.
+    pub bounds_check_policies: BoundsCheckPolicies,
+    /// The maximum number of statements a single dispatch may execute, across
+    /// all invocations, before failing with [`Error::StepLimit`].
+    ///
+    /// This keeps shaders that never terminate from hanging the caller.
+    pub max_steps: Option<u64>,
+}
+
+/// The memory a dispatch reads and writes.
+#[derive(Debug, Default)]
+pub struct Resources<'a> {
+    /// The contents of the storage and uniform buffers, by binding.
+    ///
+    /// Each buffer must be at least as large as its global's type.
+    pub buffers: FastHashMap<ResourceBinding, &'a mut [u8]>,
+    /// The contents of the push constant block, if the entry point uses one.
+    pub push_constants: Option<&'a [u8]>,
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Layout(#[from] LayoutError),
+    #[error("Entry point {0:?} not found")]
+    MissingEntryPoint(String),
+    #[error("Only compute entry points can be dispatched, not {0:?} ones")]
+    UnsupportedStage(ShaderStage),
+    #[error("No buffer is bound at {0:?}")]
+    MissingBuffer(ResourceBinding),
+    #[error("The buffer at {binding:?} is {size} bytes long, but must be at least {required}")]
+    BufferTooSmall {
+        binding: ResourceBinding,
+        size: usize,
+        required: u32,
+    },
+    #[error("The entry point uses push constants, but none were provided")]
+    MissingPushConstants,
+    #[error("The push constants are {size} bytes long, but must be at least {required}")]
+    PushConstantsTooSmall { size: usize, required: u32 },
+    #[error("Memory access out of bounds")]
+    OutOfBounds,
+    #[error("The interpreter does not support {0}")]
+    Unsupported(String),
+    #[error("The dispatch did not finish within the step limit")]
+    StepLimit,
+}
+
+fn overrides() -> Error {
+    Error::Unsupported(
+        "pipeline-overridable constants; apply `process_overrides` first".to_string(),
+    )
+}
+
+/// An interpreter for the entry points of a validated [`Module`].
+#[derive(Debug)]
+pub struct Interpreter<'a> {
+    module: &'a Module,
+    info: &'a ModuleInfo,
+    options: Options,
+    layouter: Layouter,
+    /// The values of `module.global_expressions`, or `None` for those that
+    /// depend on overrides.
+    constants: HandleVec<Expression, Option<Value>>,
+}
+
+/// The ids of a single invocation.
+struct InvocationIds {
+    global: [u32; 3],
+    local: [u32; 3],
+    local_index: u32,
+    workgroup: [u32; 3],
+    num_workgroups: [u32; 3],
+}
+
+impl InvocationIds {
+    fn builtin(&self, binding: &Binding) -> Result<Value, Error> {
+        let vector = |ids: [u32; 3]| {
+            Value::Composite(
+                ids.iter()
+                    .map(|&id| Value::Scalar(crate::Literal::U32(id)))
+                    .collect(),
+            )
+        };
+        match *binding {
+            Binding::BuiltIn(BuiltIn::GlobalInvocationId) => Ok(vector(self.global)),
+            Binding::BuiltIn(BuiltIn::LocalInvocationId) => Ok(vector(self.local)),
+            Binding::BuiltIn(BuiltIn::LocalInvocationIndex) => {
+                Ok(Value::Scalar(crate::Literal::U32(self.local_index)))
+            }
+            Binding::BuiltIn(BuiltIn::WorkGroupId) => Ok(vector(self.workgroup)),
+            Binding::BuiltIn(BuiltIn::NumWorkGroups) => Ok(vector(self.num_workgroups)),
+            ref other => Err(Error::Unsupported(format!("the {other:?} input"))),
+        }
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    /// Prepare to run entry points of `module`, which `info` validated.
+    pub fn new(module: &'a Module, info: &'a ModuleInfo, options: Options) -> Result<Self, Error> {
+        let mut layouter = Layouter::default();
+        layouter.update(module.to_ctx())?;
+
+        let mut constants: HandleVec<Expression, Option<Value>> =
+            HandleVec::with_capacity(module.global_expressions.len());
+        for (handle, expression) in module.global_expressions.iter() {
+            let value = match *expression {
+                Expression::Literal(literal) => Some(Value::Scalar(literal)),
+                Expression::Constant(constant) => {
+                    constants[module.constants[constant].init].clone()
+                }
+                Expression::ZeroValue(ty) => {
+                    Value::zero(&module.types, &module.types[ty].inner).ok()
+                }
+                Expression::Compose { ty, ref components } => components
+                    .iter()
+                    .map(|&component| constants[component].clone())
+                    .collect::<Option<Vec<_>>>()
+                    .map(|components| Value::compose(&module.types[ty].inner, components)),
+                Expression::Splat { size, value } => constants[value]
+                    .clone()
+                    .map(|value| Value::Composite(vec![value; size as usize])),
+                _ => None,
+            };
+            constants.insert(handle, value);
+        }
+
+        Ok(Interpreter {
+            module,
+            info,
+            options,
+            layouter,
+            constants,
+        })
+    }
+
+    fn constant(&self, handle: Handle<Expression>) -> Result<Value, Error> {
+        self.constants[handle].clone().ok_or_else(overrides)
+    }
+
+    fn argument(&self, argument: &FunctionArgument, ids: &InvocationIds) -> Result<Value, Error> {
+        if let Some(ref binding) = argument.binding {
+            return ids.builtin(binding);
+        }
+        match self.module.types[argument.ty].inner {
+            TypeInner::Struct { ref members, .. } => members
+                .iter()
+                .map(|member| match member.binding {
+                    Some(ref binding) => ids.builtin(binding),
+                    None => Err(Error::Unsupported("unbound inputs".to_string())),
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Composite),
+            _ => Err(Error::Unsupported("unbound inputs".to_string())),
+        }
+    }
+
+    /// Run the compute entry point named `entry_point` over a grid of
+    /// `workgroups`, using the buffers and push constants in `resources`.
+    ///
+    /// Only the resources the entry point actually uses need to be present.
+    pub fn dispatch(
+        &self,
+        entry_point: &str,
+        workgroups: [u32; 3],
+        resources: &mut Resources,
+    ) -> Result<(), Error> {
+        let module = self.module;
+        let (index, ep) = module
+            .entry_points
+            .iter()
+            .enumerate()
+            .find(|&(_, ep)| ep.name == entry_point)
+            .ok_or_else(|| Error::MissingEntryPoint(entry_point.to_string()))?;
+        if ep.stage != ShaderStage::Compute {
+            return Err(Error::UnsupportedStage(ep.stage));
+        }
+        let ep_info = self.info.get_entry_point(index);
+
+        let mut buffers: FastHashMap<_, _> = resources
+            .buffers
+            .iter_mut()
+            .map(|(binding, buffer)| (binding, &mut **buffer))
+            .collect();
+        let mut memory = HandleVec::with_capacity(module.global_variables.len());
+        let mut private = HandleVec::with_capacity(module.global_variables.len());
+        for (handle, var) in module.global_variables.iter() {
+            let required = self.layouter[var.ty].size;
+            let mut private_bytes = Vec::new();
+            let used = !ep_info[handle].is_empty();
+            let global_memory = match (var.space, var.binding.as_ref()) {
+                _ if !used => Memory::None,
+                (AddressSpace::Storage { .. } | AddressSpace::Uniform, Some(binding)) => {
+                    let buffer = buffers
+                        .remove(binding)
+                        .ok_or_else(|| Error::MissingBuffer(binding.clone()))?;
+                    if buffer.len() < required as usize {
+                        return Err(Error::BufferTooSmall {
+                            binding: binding.clone(),
+                            size: buffer.len(),
+                            required,
+                        });
+                    }
+                    Memory::Buffer(buffer)
+                }
+                (AddressSpace::PushConstant, _) => {
+                    let bytes = resources
+                        .push_constants
+                        .ok_or(Error::MissingPushConstants)?;
+                    if bytes.len() < required as usize {
+                        return Err(Error::PushConstantsTooSmall {
+                            size: bytes.len(),
+                            required,
+                        });
+                    }
+                    Memory::Owned(bytes.to_vec())
+                }
+                (AddressSpace::WorkGroup, _) => Memory::Owned(vec![0; required as usize]),
+                (AddressSpace::Private, _) => {
+                    private_bytes = vec![0; required as usize];
+                    if let Some(init) = var.init {
+                        let inner = &module.types[var.ty].inner;
+                        self.constant(init)?
+                            .write(&module.types, inner, &mut private_bytes, 0)?;
+                    }
+                    Memory::Private
+                }
+                _ => Memory::None,
+            };
+            memory.insert(handle, global_memory);
+            private.insert(handle, private_bytes);
+        }
+
+        let mut executor = Executor {
+            interp: self,
+            memory,
+            steps_left: self.options.max_steps,
+        };
+        let [size_x, size_y, size_z] = ep.workgroup_size;
+        for wz in 0..workgroups[2] {
+            for wy in 0..workgroups[1] {
+                for wx in 0..workgroups[0] {
+                    // Workgroup variables start out zeroed in every workgroup.
+                    for (handle, var) in module.global_variables.iter() {
+                        if let (AddressSpace::WorkGroup, &mut Memory::Owned(ref mut bytes)) =
+                            (var.space, &mut executor.memory[handle])
+                        {
+                            bytes.fill(0);
+                        }
+                    }
+
+                    let mut invocations = Vec::new();
+                    for lz in 0..size_z {
+                        for ly in 0..size_y {
+                            for lx in 0..size_x {
+                                let ids = InvocationIds {
+                                    global: [wx * size_x + lx, wy * size_y + ly, wz * size_z + lz],
+                                    local: [lx, ly, lz],
+                                    local_index: (lz * size_y + ly) * size_x + lx,
+                                    workgroup: [wx, wy, wz],
+                                    num_workgroups: workgroups,
+                                };
+                                let arguments = ep
+                                    .function
+                                    .arguments
+                                    .iter()
+                                    .map(|argument| self.argument(argument, &ids))
+                                    .collect::<Result<_, _>>()?;
+                                let mut invocation = Invocation::new(private.clone());
+                                executor.call(
+                                    &mut invocation,
+                                    &ep.function,
+                                    ep_info,
+                                    arguments,
+                                    None,
+                                )?;
+                                invocations.push(invocation);
+                            }
+                        }
+                    }
+
+                    // Run each invocation up to its next barrier, until they
+                    // have all finished.
+                    let mut waiting = true;
+                    while waiting {
+                        waiting = false;
+                        for invocation in invocations.iter_mut() {
+                            if let Status::Barrier = executor.run(invocation)? {
+                                waiting = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
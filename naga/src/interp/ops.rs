@@ -0,0 +1,884 @@
+//! Arithmetic on interpreter values.
+//!
+//! Where [`ConstantEvaluator`] implements an operation, the functions here
+//! compute it the same way. Where it reports an error for a condition that
+//! can only be detected at runtime, like integer division by zero or
+//! overflow, these follow the WGSL runtime semantics that the backends
+//! implement instead: integer arithmetic wraps, and division by zero
+//! produces the dividend.
+//!
+//! [`ConstantEvaluator`]: crate::proc::ConstantEvaluator
+
+use super::{
+    value::{self, Value},
+    Error,
+};
+use crate::{
+    BinaryOperator as Bo, Literal, MathFunction as Mf, RelationalFunction as Rf, Scalar,
+    ScalarKind, TypeInner, UnaryOperator as Uo,
+};
+use half::f16;
+
+fn unsupported(what: impl std::fmt::Debug, operands: &[Literal]) -> Error {
+    Error::Unsupported(format!("{what:?} on {operands:?}"))
+}
+
+/// Apply `$body` to a float, computing `f16` operations in `f32`.
+macro_rules! float {
+    ($what:expr, $a:expr, |$x:ident| $body:expr) => {
+        match $a {
+            Literal::F64($x) => Ok(Literal::F64($body)),
+            Literal::F32($x) => Ok(Literal::F32($body)),
+            Literal::F16(a) => {
+                let $x = a.to_f32();
+                Ok(Literal::F16(f16::from_f32($body)))
+            }
+            a => Err(unsupported($what, &[a])),
+        }
+    };
+    ($what:expr, $a:expr, $b:expr, |$x:ident, $y:ident| $body:expr) => {
+        match ($a, $b) {
+            (Literal::F64($x), Literal::F64($y)) => Ok(Literal::F64($body)),
+            (Literal::F32($x), Literal::F32($y)) => Ok(Literal::F32($body)),
+            (Literal::F16(a), Literal::F16(b)) => {
+                let ($x, $y) = (a.to_f32(), b.to_f32());
+                Ok(Literal::F16(f16::from_f32($body)))
+            }
+            (a, b) => Err(unsupported($what, &[a, b])),
+        }
+    };
+    ($what:expr, $a:expr, $b:expr, $c:expr, |$x:ident, $y:ident, $z:ident| $body:expr) => {
+        match ($a, $b, $c) {
+            (Literal::F64($x), Literal::F64($y), Literal::F64($z)) => Ok(Literal::F64($body)),
+            (Literal::F32($x), Literal::F32($y), Literal::F32($z)) => Ok(Literal::F32($body)),
+            (Literal::F16(a), Literal::F16(b), Literal::F16(c)) => {
+                let ($x, $y, $z) = (a.to_f32(), b.to_f32(), c.to_f32());
+                Ok(Literal::F16(f16::from_f32($body)))
+            }
+            (a, b, c) => Err(unsupported($what, &[a, b, c])),
+        }
+    };
+}
+
+/// Apply `$body` to two numbers of the same type, producing that type.
+macro_rules! numeric {
+    ($what:expr, $a:expr, $b:expr, |$x:ident, $y:ident| $body:expr) => {
+        match ($a, $b) {
+            (Literal::F64($x), Literal::F64($y)) => Ok(Literal::F64($body)),
+            (Literal::F32($x), Literal::F32($y)) => Ok(Literal::F32($body)),
+            (Literal::F16($x), Literal::F16($y)) => Ok(Literal::F16($body)),
+            (Literal::U32($x), Literal::U32($y)) => Ok(Literal::U32($body)),
+            (Literal::I32($x), Literal::I32($y)) => Ok(Literal::I32($body)),
+            (Literal::U64($x), Literal::U64($y)) => Ok(Literal::U64($body)),
+            (Literal::I64($x), Literal::I64($y)) => Ok(Literal::I64($body)),
+            (a, b) => Err(unsupported($what, &[a, b])),
+        }
+    };
+}
+
+/// Apply `$body` to an integer, producing an integer of the same type.
+macro_rules! integer {
+    ($what:expr, $a:expr, |$x:ident| $body:expr) => {
+        match $a {
+            Literal::U32($x) => Ok(Literal::U32($body)),
+            Literal::I32($x) => Ok(Literal::I32($body)),
+            Literal::U64($x) => Ok(Literal::U64($body)),
+            Literal::I64($x) => Ok(Literal::I64($body)),
+            a => Err(unsupported($what, &[a])),
+        }
+    };
+}
+
+macro_rules! integer_binary {
+    ($op:expr, $variant:ident, $a:expr, $b:expr) => {{
+        let (a, b) = ($a, $b);
+        match $op {
+            Bo::Add => Literal::$variant(a.wrapping_add(b)),
+            Bo::Subtract => Literal::$variant(a.wrapping_sub(b)),
+            Bo::Multiply => Literal::$variant(a.wrapping_mul(b)),
+            Bo::Divide => Literal::$variant(a.checked_div(b).unwrap_or(a)),
+            Bo::Modulo => Literal::$variant(a.checked_rem(b).unwrap_or(0)),
+            Bo::And => Literal::$variant(a & b),
+            Bo::ExclusiveOr => Literal::$variant(a ^ b),
+            Bo::InclusiveOr => Literal::$variant(a | b),
+            Bo::Equal => Literal::Bool(a == b),
+            Bo::NotEqual => Literal::Bool(a != b),
+            Bo::Less => Literal::Bool(a < b),
+            Bo::LessEqual => Literal::Bool(a <= b),
+            Bo::Greater => Literal::Bool(a > b),
+            Bo::GreaterEqual => Literal::Bool(a >= b),
+            op => {
+                return Err(unsupported(
+                    op,
+                    &[Literal::$variant(a), Literal::$variant(b)],
+                ))
+            }
+        }
+    }};
+}
+
+macro_rules! float_binary {
+    ($op:expr, $variant:ident, $a:expr, $b:expr) => {{
+        let (a, b) = ($a, $b);
+        match $op {
+            Bo::Add => Literal::$variant(a + b),
+            Bo::Subtract => Literal::$variant(a - b),
+            Bo::Multiply => Literal::$variant(a * b),
+            Bo::Divide => Literal::$variant(a / b),
+            Bo::Modulo => Literal::$variant(a % b),
+            Bo::Equal => Literal::Bool(a == b),
+            Bo::NotEqual => Literal::Bool(a != b),
+            Bo::Less => Literal::Bool(a < b),
+            Bo::LessEqual => Literal::Bool(a <= b),
+            Bo::Greater => Literal::Bool(a > b),
+            Bo::GreaterEqual => Literal::Bool(a >= b),
+            op => {
+                return Err(unsupported(
+                    op,
+                    &[Literal::$variant(a), Literal::$variant(b)],
+                ))
+            }
+        }
+    }};
+}
+
+pub(super) fn unary(op: Uo, operand: &Value) -> Result<Value, Error> {
+    Value::map(std::slice::from_ref(operand), &mut |args| {
+        Ok(match (op, args[0]) {
+            (Uo::Negate, Literal::F64(a)) => Literal::F64(-a),
+            (Uo::Negate, Literal::F32(a)) => Literal::F32(-a),
+            (Uo::Negate, Literal::F16(a)) => Literal::F16(-a),
+            (Uo::Negate, Literal::I32(a)) => Literal::I32(a.wrapping_neg()),
+            (Uo::Negate, Literal::I64(a)) => Literal::I64(a.wrapping_neg()),
+            (Uo::LogicalNot | Uo::BitwiseNot, Literal::Bool(a)) => Literal::Bool(!a),
+            (Uo::BitwiseNot, a) => integer!(op, a, |a| !a)?,
+            (op, a) => return Err(unsupported(op, &[a])),
+        })
+    })
+}
+
+pub(super) fn binary_scalar(op: Bo, a: Literal, b: Literal) -> Result<Literal, Error> {
+    Ok(match (a, b) {
+        (Literal::U32(a), Literal::U32(b)) => match op {
+            Bo::ShiftLeft => Literal::U32(a.wrapping_shl(b)),
+            Bo::ShiftRight => Literal::U32(a.wrapping_shr(b)),
+            _ => integer_binary!(op, U32, a, b),
+        },
+        (Literal::I32(a), Literal::I32(b)) => integer_binary!(op, I32, a, b),
+        (Literal::U64(a), Literal::U64(b)) => match op {
+            Bo::ShiftLeft => Literal::U64(a.wrapping_shl(b as u32)),
+            Bo::ShiftRight => Literal::U64(a.wrapping_shr(b as u32)),
+            _ => integer_binary!(op, U64, a, b),
+        },
+        (Literal::I64(a), Literal::I64(b)) => integer_binary!(op, I64, a, b),
+        (Literal::I32(a), Literal::U32(b)) if op == Bo::ShiftLeft => {
+            Literal::I32(a.wrapping_shl(b))
+        }
+        (Literal::I32(a), Literal::U32(b)) if op == Bo::ShiftRight => {
+            Literal::I32(a.wrapping_shr(b))
+        }
+        (Literal::I64(a), Literal::U64(b)) if op == Bo::ShiftLeft => {
+            Literal::I64(a.wrapping_shl(b as u32))
+        }
+        (Literal::I64(a), Literal::U64(b)) if op == Bo::ShiftRight => {
+            Literal::I64(a.wrapping_shr(b as u32))
+        }
+        (Literal::F64(a), Literal::F64(b)) => float_binary!(op, F64, a, b),
+        (Literal::F32(a), Literal::F32(b)) => float_binary!(op, F32, a, b),
+        (Literal::F16(a), Literal::F16(b)) => float_binary!(op, F16, a, b),
+        (Literal::Bool(a), Literal::Bool(b)) => Literal::Bool(match op {
+            Bo::Equal => a == b,
+            Bo::NotEqual => a != b,
+            Bo::And | Bo::LogicalAnd => a && b,
+            Bo::InclusiveOr | Bo::LogicalOr => a || b,
+            Bo::ExclusiveOr => a != b,
+            op => return Err(unsupported(op, &[Literal::Bool(a), Literal::Bool(b)])),
+        }),
+        (a, b) => return Err(unsupported(op, &[a, b])),
+    })
+}
+
+/// Apply a binary operator to values of types `left_ty` and `right_ty`.
+pub(super) fn binary(
+    op: Bo,
+    left: &Value,
+    left_ty: &TypeInner,
+    right: &Value,
+    right_ty: &TypeInner,
+) -> Result<Value, Error> {
+    match (op, left_ty, right_ty) {
+        (Bo::Multiply, &TypeInner::Matrix { .. }, &TypeInner::Vector { .. }) => {
+            // The sum of the columns, scaled by the vector's components.
+            let columns = left.clone().components()?;
+            let scales = right.clone().components()?;
+            let mut sum = None;
+            for (column, scale) in columns.iter().zip(scales) {
+                let term = Value::map(&[column.clone(), scale], &mut |args| {
+                    binary_scalar(Bo::Multiply, args[0], args[1])
+                })?;
+                sum = Some(match sum {
+                    Some(sum) => Value::map(&[sum, term], &mut |args| {
+                        binary_scalar(Bo::Add, args[0], args[1])
+                    })?,
+                    None => term,
+                });
+            }
+            sum.ok_or_else(|| Error::Unsupported("empty matrix".to_string()))
+        }
+        (Bo::Multiply, &TypeInner::Vector { .. }, &TypeInner::Matrix { .. }) => {
+            let columns = right.clone().components()?;
+            let products = columns
+                .iter()
+                .map(|column| dot(left, column).map(Value::Scalar))
+                .collect::<Result<_, _>>()?;
+            Ok(Value::Composite(products))
+        }
+        (
+            Bo::Multiply,
+            &TypeInner::Matrix {
+                columns, scalar, ..
+            },
+            &TypeInner::Matrix { .. },
+        ) => {
+            // Each column of the product is `left` times a column of `right`.
+            let column_ty = TypeInner::Vector {
+                size: columns,
+                scalar,
+            };
+            let products = right
+                .clone()
+                .components()?
+                .iter()
+                .map(|column| binary(op, left, left_ty, column, &column_ty))
+                .collect::<Result<_, _>>()?;
+            Ok(Value::Composite(products))
+        }
+        _ => Value::map(&[left.clone(), right.clone()], &mut |args| {
+            binary_scalar(op, args[0], args[1])
+        }),
+    }
+}
+
+fn dot(a: &Value, b: &Value) -> Result<Literal, Error> {
+    let products = Value::map(&[a.clone(), b.clone()], &mut |args| {
+        binary_scalar(Bo::Multiply, args[0], args[1])
+    })?;
+    let mut sum = None;
+    for product in products.components()? {
+        let product = product.scalar()?;
+        sum = Some(match sum {
+            Some(sum) => binary_scalar(Bo::Add, sum, product)?,
+            None => product,
+        });
+    }
+    sum.ok_or_else(|| Error::Unsupported("empty vector".to_string()))
+}
+
+pub(super) fn min(a: Literal, b: Literal) -> Result<Literal, Error> {
+    numeric!(Mf::Min, a, b, |x, y| if y < x { y } else { x })
+}
+
+pub(super) fn max(a: Literal, b: Literal) -> Result<Literal, Error> {
+    numeric!(Mf::Max, a, b, |x, y| if y > x { y } else { x })
+}
+
+pub(super) fn relational(fun: Rf, argument: &Value) -> Result<Value, Error> {
+    match fun {
+        Rf::All | Rf::Any => {
+            let components = match *argument {
+                Value::Composite(ref components) => components.clone(),
+                ref scalar => vec![scalar.clone()],
+            };
+            let mut bools = components.iter().map(Value::bool);
+            let result = if fun == Rf::All {
+                bools.try_fold(true, |all, b| b.map(|b| all && b))?
+            } else {
+                bools.try_fold(false, |any, b| b.map(|b| any || b))?
+            };
+            Ok(Value::Scalar(Literal::Bool(result)))
+        }
+        Rf::IsNan | Rf::IsInf => Value::map(std::slice::from_ref(argument), &mut |args| {
+            let value = to_f64(args[0])?;
+            Ok(Literal::Bool(if fun == Rf::IsNan {
+                value.is_nan()
+            } else {
+                value.is_infinite()
+            }))
+        }),
+    }
+}
+
+pub(super) fn select(condition: &Value, accept: Value, reject: Value) -> Result<Value, Error> {
+    match *condition {
+        Value::Scalar(Literal::Bool(true)) => Ok(accept),
+        Value::Scalar(Literal::Bool(false)) => Ok(reject),
+        _ => Value::map(&[condition.clone(), accept, reject], &mut |args| {
+            Ok(if args[0] == Literal::Bool(true) {
+                args[1]
+            } else {
+                args[2]
+            })
+        }),
+    }
+}
+
+/// Convert `literal` to `target`, as [`Expression::As`] does with a
+/// `convert` width.
+///
+/// Conversions from floating-point to integer types saturate.
+///
+/// [`Expression::As`]: crate::Expression::As
+pub(super) fn convert(literal: Literal, target: Scalar) -> Result<Literal, Error> {
+    enum Number {
+        Float(f64),
+        Int(i128),
+    }
+    let number = match literal {
+        Literal::F64(value) => Number::Float(value),
+        Literal::F32(value) => Number::Float(value as f64),
+        Literal::F16(value) => Number::Float(value.to_f64()),
+        Literal::U32(value) => Number::Int(value as i128),
+        Literal::I32(value) => Number::Int(value as i128),
+        Literal::U64(value) => Number::Int(value as i128),
+        Literal::I64(value) => Number::Int(value as i128),
+        Literal::Bool(value) => Number::Int(value as i128),
+        other => return Err(unsupported("conversion", &[other])),
+    };
+    Ok(match (number, target.kind, target.width) {
+        (Number::Float(value), ScalarKind::Bool, _) => Literal::Bool(value != 0.0),
+        (Number::Int(value), ScalarKind::Bool, _) => Literal::Bool(value != 0),
+        (Number::Float(value), ScalarKind::Float, _) => from_f64(value, target)?,
+        (Number::Int(value), ScalarKind::Float, _) => from_f64(value as f64, target)?,
+        (Number::Float(value), ScalarKind::Uint, 4) => Literal::U32(value as u32),
+        (Number::Float(value), ScalarKind::Sint, 4) => Literal::I32(value as i32),
+        (Number::Float(value), ScalarKind::Uint, 8) => Literal::U64(value as u64),
+        (Number::Float(value), ScalarKind::Sint, 8) => Literal::I64(value as i64),
+        (Number::Int(value), ScalarKind::Uint, 4) => Literal::U32(value as u32),
+        (Number::Int(value), ScalarKind::Sint, 4) => Literal::I32(value as i32),
+        (Number::Int(value), ScalarKind::Uint, 8) => Literal::U64(value as u64),
+        (Number::Int(value), ScalarKind::Sint, 8) => Literal::I64(value as i64),
+        _ => return Err(unsupported(target, &[literal])),
+    })
+}
+
+/// Reinterpret the bits of `literal` as a `kind` value of the same width.
+pub(super) fn bitcast(literal: Literal, kind: ScalarKind) -> Result<Literal, Error> {
+    value::from_bits(
+        value::to_bits(literal)?,
+        Scalar {
+            kind,
+            width: literal.width(),
+        },
+    )
+}
+
+pub(super) fn to_f64(literal: Literal) -> Result<f64, Error> {
+    match literal {
+        Literal::F64(value) => Ok(value),
+        Literal::F32(value) => Ok(value as f64),
+        Literal::F16(value) => Ok(value.to_f64()),
+        other => Err(unsupported("conversion to float", &[other])),
+    }
+}
+
+pub(super) fn from_f64(value: f64, scalar: Scalar) -> Result<Literal, Error> {
+    match scalar.width {
+        8 => Ok(Literal::F64(value)),
+        4 => Ok(Literal::F32(value as f32)),
+        2 => Ok(Literal::F16(f16::from_f64(value))),
+        _ => Err(Error::Unsupported(format!("{scalar:?} values"))),
+    }
+}
+
+/// Round to the nearest integer, with ties going to the even one.
+fn round_ties_even(x: f64) -> f64 {
+    let rounded = x.round();
+    if (x - x.trunc()).abs() == 0.5 && rounded % 2.0 != 0.0 {
+        rounded - x.signum()
+    } else {
+        rounded
+    }
+}
+
+/// Return the components of a float vector as `f64`s, along with their type.
+fn float_vector(value: &Value) -> Result<(Vec<f64>, Scalar), Error> {
+    let components = value.clone().components()?;
+    let mut scalar = Scalar::F32;
+    let values = components
+        .iter()
+        .map(|component| {
+            let literal = component.scalar()?;
+            scalar = literal.scalar();
+            to_f64(literal)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((values, scalar))
+}
+
+fn float_vector_value(
+    values: impl IntoIterator<Item = f64>,
+    scalar: Scalar,
+) -> Result<Value, Error> {
+    values
+        .into_iter()
+        .map(|value| from_f64(value, scalar).map(Value::Scalar))
+        .collect::<Result<_, _>>()
+        .map(Value::Composite)
+}
+
+fn float_matrix(value: &Value) -> Result<(Vec<Vec<f64>>, Scalar), Error> {
+    let mut scalar = Scalar::F32;
+    let columns = value
+        .clone()
+        .components()?
+        .iter()
+        .map(|column| {
+            let (values, s) = float_vector(column)?;
+            scalar = s;
+            Ok(values)
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok((columns, scalar))
+}
+
+fn float_matrix_value(columns: Vec<Vec<f64>>, scalar: Scalar) -> Result<Value, Error> {
+    columns
+        .into_iter()
+        .map(|column| float_vector_value(column, scalar))
+        .collect::<Result<_, _>>()
+        .map(Value::Composite)
+}
+
+/// The determinant of the square matrix `m`, by cofactor expansion.
+fn determinant(m: &[Vec<f64>]) -> f64 {
+    match m.len() {
+        0 => 1.0,
+        1 => m[0][0],
+        n => (0..n)
+            .map(|column| {
+                let sign = if column % 2 == 0 { 1.0 } else { -1.0 };
+                sign * m[column][0] * determinant(&minor(m, column, 0))
+            })
+            .sum(),
+    }
+}
+
+/// `m` without the given column and row.
+fn minor(m: &[Vec<f64>], column: usize, row: usize) -> Vec<Vec<f64>> {
+    m.iter()
+        .enumerate()
+        .filter(|&(c, _)| c != column)
+        .map(|(_, values)| {
+            values
+                .iter()
+                .enumerate()
+                .filter(|&(r, _)| r != row)
+                .map(|(_, &value)| value)
+                .collect()
+        })
+        .collect()
+}
+
+/// Extract `count` bits of `value`, starting at `offset`.
+///
+/// The bits are sign-extended if `signed` is true.
+fn extract_bits(value: u64, width: u32, signed: bool, offset: u32, count: u32) -> u64 {
+    let offset = offset.min(width);
+    let count = count.min(width - offset);
+    if count == 0 {
+        return 0;
+    }
+    let bits = (value >> offset) & (u64::MAX >> (64 - count));
+    if signed && bits >> (count - 1) & 1 == 1 {
+        bits | (u64::MAX << count)
+    } else {
+        bits
+    }
+}
+
+fn insert_bits(value: u64, new_bits: u64, width: u32, offset: u32, count: u32) -> u64 {
+    let offset = offset.min(width);
+    let count = count.min(width - offset);
+    if count == 0 {
+        return value;
+    }
+    let mask = (u64::MAX >> (64 - count)) << offset;
+    (value & !mask) | ((new_bits << offset) & mask)
+}
+
+fn int_bits(literal: Literal) -> Result<(u64, u32, bool), Error> {
+    Ok(match literal {
+        Literal::U32(value) => (value as u64, 32, false),
+        Literal::I32(value) => (value as u32 as u64, 32, true),
+        Literal::U64(value) => (value, 64, false),
+        Literal::I64(value) => (value as u64, 64, true),
+        other => return Err(unsupported("bit operation", &[other])),
+    })
+}
+
+fn from_int_bits(bits: u64, like: Literal) -> Result<Literal, Error> {
+    value::from_bits(bits, like.scalar())
+}
+
+/// Evaluate a [`MathFunction`](crate::MathFunction) on `args`.
+pub(super) fn math(fun: Mf, args: &[Value]) -> Result<Value, Error> {
+    let arg = &args[0];
+    let scalar_fn = |f: &mut dyn FnMut(&[Literal]) -> Result<Literal, Error>| {
+        Value::map(args, &mut |args: &[Literal]| f(args))
+    };
+    match fun {
+        // comparison
+        Mf::Abs => scalar_fn(&mut |args| match args[0] {
+            Literal::I32(a) => Ok(Literal::I32(a.wrapping_abs())),
+            Literal::I64(a) => Ok(Literal::I64(a.wrapping_abs())),
+            a @ (Literal::U32(_) | Literal::U64(_)) => Ok(a),
+            a => float!(fun, a, |x| x.abs()),
+        }),
+        Mf::Min => scalar_fn(&mut |args| min(args[0], args[1])),
+        Mf::Max => scalar_fn(&mut |args| max(args[0], args[1])),
+        Mf::Clamp => scalar_fn(&mut |args| min(max(args[0], args[1])?, args[2])),
+        Mf::Saturate => scalar_fn(&mut |args| float!(fun, args[0], |x| x.clamp(0.0, 1.0))),
+
+        // trigonometry
+        Mf::Cos => scalar_fn(&mut |args| float!(fun, args[0], |x| x.cos())),
+        Mf::Cosh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.cosh())),
+        Mf::Sin => scalar_fn(&mut |args| float!(fun, args[0], |x| x.sin())),
+        Mf::Sinh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.sinh())),
+        Mf::Tan => scalar_fn(&mut |args| float!(fun, args[0], |x| x.tan())),
+        Mf::Tanh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.tanh())),
+        Mf::Acos => scalar_fn(&mut |args| float!(fun, args[0], |x| x.acos())),
+        Mf::Asin => scalar_fn(&mut |args| float!(fun, args[0], |x| x.asin())),
+        Mf::Atan => scalar_fn(&mut |args| float!(fun, args[0], |x| x.atan())),
+        Mf::Atan2 => scalar_fn(&mut |args| float!(fun, args[0], args[1], |y, x| y.atan2(x))),
+        Mf::Asinh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.asinh())),
+        Mf::Acosh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.acosh())),
+        Mf::Atanh => scalar_fn(&mut |args| float!(fun, args[0], |x| x.atanh())),
+        Mf::Radians => scalar_fn(&mut |args| float!(fun, args[0], |x| x.to_radians())),
+        Mf::Degrees => scalar_fn(&mut |args| float!(fun, args[0], |x| x.to_degrees())),
+
+        // decomposition
+        Mf::Ceil => scalar_fn(&mut |args| float!(fun, args[0], |x| x.ceil())),
+        Mf::Floor => scalar_fn(&mut |args| float!(fun, args[0], |x| x.floor())),
+        Mf::Round => scalar_fn(&mut |args| {
+            let literal = args[0];
+            from_f64(round_ties_even(to_f64(literal)?), literal.scalar())
+        }),
+        // Rust's `fract` is `x - x.trunc()`, but WGSL's is `x - x.floor()`.
+        Mf::Fract => scalar_fn(&mut |args| float!(fun, args[0], |x| x - x.floor())),
+        Mf::Trunc => scalar_fn(&mut |args| float!(fun, args[0], |x| x.trunc())),
+        Mf::Modf => {
+            let fract = Value::map(args, &mut |args| float!(fun, args[0], |x| x - x.trunc()))?;
+            let whole = Value::map(args, &mut |args| float!(fun, args[0], |x| x.trunc()))?;
+            Ok(Value::Composite(vec![fract, whole]))
+        }
+        Mf::Frexp => {
+            fn frexp(x: f64) -> (f64, i32) {
+                if x == 0.0 || !x.is_finite() {
+                    return (x, 0);
+                }
+                let mut exponent = x.abs().log2().floor() as i32 + 1;
+                let mut fraction = x / 2f64.powi(exponent);
+                if fraction.abs() >= 1.0 {
+                    exponent += 1;
+                    fraction /= 2.0;
+                } else if fraction.abs() < 0.5 {
+                    exponent -= 1;
+                    fraction *= 2.0;
+                }
+                (fraction, exponent)
+            }
+            let fract = Value::map(args, &mut |args| {
+                from_f64(frexp(to_f64(args[0])?).0, args[0].scalar())
+            })?;
+            let exp = Value::map(args, &mut |args| {
+                Ok(Literal::I32(frexp(to_f64(args[0])?).1))
+            })?;
+            Ok(Value::Composite(vec![fract, exp]))
+        }
+        Mf::Ldexp => scalar_fn(&mut |args| {
+            let exponent = match args[1] {
+                Literal::I32(exponent) => exponent,
+                other => return Err(unsupported(fun, &[other])),
+            };
+            from_f64(to_f64(args[0])? * 2f64.powi(exponent), args[0].scalar())
+        }),
+
+        // exponent
+        Mf::Exp => scalar_fn(&mut |args| float!(fun, args[0], |x| x.exp())),
+        Mf::Exp2 => scalar_fn(&mut |args| float!(fun, args[0], |x| x.exp2())),
+        Mf::Log => scalar_fn(&mut |args| float!(fun, args[0], |x| x.ln())),
+        Mf::Log2 => scalar_fn(&mut |args| float!(fun, args[0], |x| x.log2())),
+        Mf::Pow => scalar_fn(&mut |args| float!(fun, args[0], args[1], |x, y| x.powf(y))),
+
+        // geometry
+        Mf::Dot => dot(arg, &args[1]).map(Value::Scalar),
+        Mf::Outer => {
+            let rows = arg;
+            let columns = args[1].clone().components()?;
+            let columns = columns
+                .into_iter()
+                .map(|scale| {
+                    Value::map(&[rows.clone(), scale], &mut |args| {
+                        binary_scalar(Bo::Multiply, args[0], args[1])
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Value::Composite(columns))
+        }
+        Mf::Cross => {
+            let (a, scalar) = float_vector(arg)?;
+            let (b, _) = float_vector(&args[1])?;
+            float_vector_value(
+                [
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ],
+                scalar,
+            )
+        }
+        Mf::Distance => {
+            let difference = Value::map(args, &mut |args| {
+                binary_scalar(Bo::Subtract, args[0], args[1])
+            })?;
+            math(Mf::Length, &[difference])
+        }
+        Mf::Length => match *arg {
+            Value::Scalar(literal) => float!(fun, literal, |x| x.abs()).map(Value::Scalar),
+            _ => {
+                let (a, scalar) = float_vector(arg)?;
+                let length = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+                from_f64(length, scalar).map(Value::Scalar)
+            }
+        },
+        Mf::Normalize => {
+            let (a, scalar) = float_vector(arg)?;
+            let length = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+            float_vector_value(a.iter().map(|x| x / length), scalar)
+        }
+        Mf::FaceForward => {
+            let (n, scalar) = float_vector(arg)?;
+            let (i, _) = float_vector(&args[1])?;
+            let (reference, _) = float_vector(&args[2])?;
+            let d: f64 = i.iter().zip(&reference).map(|(a, b)| a * b).sum();
+            let sign = if d < 0.0 { 1.0 } else { -1.0 };
+            float_vector_value(n.iter().map(|x| x * sign), scalar)
+        }
+        Mf::Reflect => {
+            let (i, scalar) = float_vector(arg)?;
+            let (n, _) = float_vector(&args[1])?;
+            let d: f64 = n.iter().zip(&i).map(|(a, b)| a * b).sum();
+            float_vector_value(i.iter().zip(&n).map(|(i, n)| i - 2.0 * d * n), scalar)
+        }
+        Mf::Refract => {
+            let (i, scalar) = float_vector(arg)?;
+            let (n, _) = float_vector(&args[1])?;
+            let eta = to_f64(args[2].scalar()?)?;
+            let d: f64 = n.iter().zip(&i).map(|(a, b)| a * b).sum();
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            if k < 0.0 {
+                float_vector_value(i.iter().map(|_| 0.0), scalar)
+            } else {
+                float_vector_value(
+                    i.iter()
+                        .zip(&n)
+                        .map(|(i, n)| eta * i - (eta * d + k.sqrt()) * n),
+                    scalar,
+                )
+            }
+        }
+
+        // computational
+        Mf::Sign => scalar_fn(&mut |args| match args[0] {
+            Literal::I32(a) => Ok(Literal::I32(a.signum())),
+            Literal::I64(a) => Ok(Literal::I64(a.signum())),
+            a => float!(fun, a, |x| if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                0.0
+            }),
+        }),
+        Mf::Fma => {
+            scalar_fn(&mut |args| float!(fun, args[0], args[1], args[2], |x, y, z| x.mul_add(y, z)))
+        }
+        Mf::Mix => scalar_fn(&mut |args| {
+            float!(fun, args[0], args[1], args[2], |x, y, t| x * (1.0 - t)
+                + y * t)
+        }),
+        Mf::Step => scalar_fn(&mut |args| {
+            float!(fun, args[0], args[1], |edge, x| if edge <= x {
+                1.0
+            } else {
+                0.0
+            })
+        }),
+        Mf::SmoothStep => scalar_fn(&mut |args| {
+            float!(fun, args[0], args[1], args[2], |low, high, x| {
+                let t = ((x - low) / (high - low)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            })
+        }),
+        Mf::Sqrt => scalar_fn(&mut |args| float!(fun, args[0], |x| x.sqrt())),
+        Mf::InverseSqrt => scalar_fn(&mut |args| float!(fun, args[0], |x| 1.0 / x.sqrt())),
+        Mf::Inverse => {
+            let (m, scalar) = float_matrix(arg)?;
+            let det = determinant(&m);
+            let n = m.len();
+            // The inverse is the transposed matrix of cofactors, over the
+            // determinant.
+            let columns = (0..n)
+                .map(|column| {
+                    (0..n)
+                        .map(|row| {
+                            let sign = if (row + column) % 2 == 0 { 1.0 } else { -1.0 };
+                            sign * determinant(&minor(&m, row, column)) / det
+                        })
+                        .collect()
+                })
+                .collect();
+            float_matrix_value(columns, scalar)
+        }
+        Mf::Transpose => {
+            let columns = arg.clone().components()?;
+            let rows = columns
+                .first()
+                .cloned()
+                .map_or(Ok(vec![]), Value::components)?;
+            let transposed = (0..rows.len())
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|column| column.clone().components().map(|c| c[row].clone()))
+                        .collect::<Result<_, _>>()
+                        .map(Value::Composite)
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Value::Composite(transposed))
+        }
+        Mf::Determinant => {
+            let (m, scalar) = float_matrix(arg)?;
+            from_f64(determinant(&m), scalar).map(Value::Scalar)
+        }
+
+        // bits
+        Mf::CountTrailingZeros => scalar_fn(&mut |args| {
+            let (bits, width, _) = int_bits(args[0])?;
+            from_int_bits(bits.trailing_zeros().min(width) as u64, args[0])
+        }),
+        Mf::CountLeadingZeros => scalar_fn(&mut |args| {
+            let (bits, width, _) = int_bits(args[0])?;
+            from_int_bits((bits.leading_zeros() - (64 - width)) as u64, args[0])
+        }),
+        Mf::CountOneBits => scalar_fn(&mut |args| {
+            let (bits, _, _) = int_bits(args[0])?;
+            from_int_bits(bits.count_ones() as u64, args[0])
+        }),
+        Mf::ReverseBits => scalar_fn(&mut |args| integer!(fun, args[0], |x| x.reverse_bits())),
+        Mf::ExtractBits => scalar_fn(&mut |args| {
+            let (bits, width, signed) = int_bits(args[0])?;
+            let (Literal::U32(offset), Literal::U32(count)) = (args[1], args[2]) else {
+                return Err(unsupported(fun, args));
+            };
+            from_int_bits(extract_bits(bits, width, signed, offset, count), args[0])
+        }),
+        Mf::InsertBits => scalar_fn(&mut |args| {
+            let (bits, width, _) = int_bits(args[0])?;
+            let (new_bits, _, _) = int_bits(args[1])?;
+            let (Literal::U32(offset), Literal::U32(count)) = (args[2], args[3]) else {
+                return Err(unsupported(fun, args));
+            };
+            from_int_bits(insert_bits(bits, new_bits, width, offset, count), args[0])
+        }),
+        Mf::FindLsb => scalar_fn(&mut |args| {
+            let (bits, _, _) = int_bits(args[0])?;
+            let index = if bits == 0 {
+                u64::MAX
+            } else {
+                bits.trailing_zeros() as u64
+            };
+            from_int_bits(index, args[0])
+        }),
+        Mf::FindMsb => scalar_fn(&mut |args| {
+            let (bits, width, signed) = int_bits(args[0])?;
+            // For negative signed values, look for the most significant zero.
+            let negative = signed && bits >> (width - 1) & 1 == 1;
+            let bits = if negative { !bits } else { bits } & (u64::MAX >> (64 - width));
+            let index = if bits == 0 {
+                u64::MAX
+            } else {
+                63 - bits.leading_zeros() as u64
+            };
+            from_int_bits(index, args[0])
+        }),
+
+        // data packing
+        Mf::Pack4x8snorm | Mf::Pack4x8unorm | Mf::Pack2x16snorm | Mf::Pack2x16unorm => {
+            let (values, _) = float_vector(arg)?;
+            let (bits, scale, low) = match fun {
+                Mf::Pack4x8snorm => (8, 127.0, -1.0),
+                Mf::Pack4x8unorm => (8, 255.0, 0.0),
+                Mf::Pack2x16snorm => (16, 32767.0, -1.0),
+                _ => (16, 65535.0, 0.0),
+            };
+            let mask = (1u32 << bits) - 1;
+            let packed = values.iter().enumerate().fold(0, |packed, (i, &x)| {
+                let x = round_ties_even(x.clamp(low, 1.0) * scale) as i32 as u32;
+                packed | (x & mask) << (i * bits)
+            });
+            Ok(Value::Scalar(Literal::U32(packed)))
+        }
+        Mf::Pack2x16float => {
+            let (values, _) = float_vector(arg)?;
+            let packed = values.iter().enumerate().fold(0, |packed, (i, &x)| {
+                packed | (f16::from_f64(x).to_bits() as u32) << (i * 16)
+            });
+            Ok(Value::Scalar(Literal::U32(packed)))
+        }
+        Mf::Pack4xI8 | Mf::Pack4xU8 => {
+            let packed = arg
+                .clone()
+                .components()?
+                .iter()
+                .enumerate()
+                .map(|(i, component)| {
+                    let (bits, _, _) = int_bits(component.scalar()?)?;
+                    Ok(((bits & 0xFF) as u32) << (i * 8))
+                })
+                .sum::<Result<u32, Error>>()?;
+            Ok(Value::Scalar(Literal::U32(packed)))
+        }
+        Mf::Unpack4x8snorm
+        | Mf::Unpack4x8unorm
+        | Mf::Unpack2x16snorm
+        | Mf::Unpack2x16unorm
+        | Mf::Unpack2x16float
+        | Mf::Unpack4xI8
+        | Mf::Unpack4xU8 => {
+            let Literal::U32(packed) = arg.scalar()? else {
+                return Err(unsupported(fun, &[arg.scalar()?]));
+            };
+            let (count, bits) = match fun {
+                Mf::Unpack2x16snorm | Mf::Unpack2x16unorm | Mf::Unpack2x16float => (2, 16),
+                _ => (4, 8),
+            };
+            let components = (0..count)
+                .map(|i| {
+                    let raw = extract_bits(packed as u64, 32, false, i * bits, bits);
+                    let signed = extract_bits(packed as u64, 32, true, i * bits, bits) as i64;
+                    Value::Scalar(match fun {
+                        Mf::Unpack4x8snorm => Literal::F32((signed as f32 / 127.0).max(-1.0)),
+                        Mf::Unpack4x8unorm => Literal::F32(raw as f32 / 255.0),
+                        Mf::Unpack2x16snorm => Literal::F32((signed as f32 / 32767.0).max(-1.0)),
+                        Mf::Unpack2x16unorm => Literal::F32(raw as f32 / 65535.0),
+                        Mf::Unpack2x16float => Literal::F32(f16::from_bits(raw as u16).to_f32()),
+                        Mf::Unpack4xI8 => Literal::I32(signed as i32),
+                        _ => Literal::U32(raw as u32),
+                    })
+                })
+                .collect();
+            Ok(Value::Composite(components))
+        }
+    }
+}
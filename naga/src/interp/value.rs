@@ -0,0 +1,349 @@
+//! Runtime values, and their representation in memory.
+
+use super::Error;
+use crate::{
+    proc::Alignment, ArraySize, Handle, Literal, Scalar, ScalarKind, Type, TypeInner, UniqueArena,
+};
+
+/// A value produced by evaluating an [`Expression`].
+///
+/// Values do not carry their types: the interpreter consults the
+/// [`FunctionInfo`] when it needs to know how to interpret one.
+///
+/// [`Expression`]: crate::Expression
+/// [`FunctionInfo`]: crate::valid::FunctionInfo
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Value {
+    Scalar(Literal),
+    /// The components of a vector, matrix, array or struct.
+    ///
+    /// A matrix is a list of column vectors.
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+}
+
+/// A pointer into some variable's memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Pointer {
+    pub region: Region,
+    /// The byte offset of the pointee from the start of `region`.
+    pub offset: u32,
+    /// True if this pointer was produced by an out-of-bounds access under the
+    /// [`ReadZeroSkipWrite`] policy: loads produce zero and stores are ignored.
+    ///
+    /// [`ReadZeroSkipWrite`]: crate::proc::BoundsCheckPolicy::ReadZeroSkipWrite
+    pub skip: bool,
+}
+
+/// The memory a [`Pointer`] points into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Region {
+    Global(Handle<crate::GlobalVariable>),
+    Local {
+        /// The index of the frame owning the variable in the invocation's
+        /// call stack.
+        frame: usize,
+        variable: Handle<crate::LocalVariable>,
+    },
+}
+
+impl Value {
+    pub fn scalar(&self) -> Result<Literal, Error> {
+        match *self {
+            Self::Scalar(literal) => Ok(literal),
+            _ => Err(Error::Unsupported(format!("{self:?} as a scalar"))),
+        }
+    }
+
+    pub fn bool(&self) -> Result<bool, Error> {
+        match *self {
+            Self::Scalar(Literal::Bool(value)) => Ok(value),
+            _ => Err(Error::Unsupported(format!("{self:?} as a boolean"))),
+        }
+    }
+
+    /// Return this integer as an index.
+    ///
+    /// Negative signed integers become large indices, which are always out of
+    /// bounds.
+    pub fn index(&self) -> Result<u64, Error> {
+        match *self {
+            Self::Scalar(Literal::U32(value)) => Ok(value as u64),
+            Self::Scalar(Literal::I32(value)) => Ok(value as u32 as u64),
+            Self::Scalar(Literal::U64(value)) => Ok(value),
+            Self::Scalar(Literal::I64(value)) => Ok(value as u64),
+            _ => Err(Error::Unsupported(format!("{self:?} as an index"))),
+        }
+    }
+
+    pub fn pointer(&self) -> Result<Pointer, Error> {
+        match *self {
+            Self::Pointer(pointer) => Ok(pointer),
+            _ => Err(Error::Unsupported(format!("{self:?} as a pointer"))),
+        }
+    }
+
+    pub fn components(self) -> Result<Vec<Value>, Error> {
+        match self {
+            Self::Composite(components) => Ok(components),
+            _ => Err(Error::Unsupported(format!("{self:?} as a composite"))),
+        }
+    }
+
+    /// Apply `f` to every scalar in `args`, component by component.
+    ///
+    /// Scalar arguments are broadcast across composite ones, so this serves
+    /// for mixed vector and scalar operands as well.
+    pub fn map(
+        args: &[Value],
+        f: &mut impl FnMut(&[Literal]) -> Result<Literal, Error>,
+    ) -> Result<Value, Error> {
+        let len = args.iter().find_map(|arg| match *arg {
+            Self::Composite(ref components) => Some(components.len()),
+            _ => None,
+        });
+        let Some(len) = len else {
+            let scalars = args
+                .iter()
+                .map(Value::scalar)
+                .collect::<Result<Vec<_>, _>>()?;
+            return f(&scalars).map(Self::Scalar);
+        };
+
+        let mut components = Vec::with_capacity(len);
+        for index in 0..len {
+            let column = args
+                .iter()
+                .map(|arg| match *arg {
+                    Self::Composite(ref components) => {
+                        components.get(index).cloned().ok_or_else(|| {
+                            Error::Unsupported("operands of different lengths".to_string())
+                        })
+                    }
+                    _ => Ok(arg.clone()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            components.push(Self::map(&column, f)?);
+        }
+        Ok(Self::Composite(components))
+    }
+
+    /// Build a value of type `inner` from `components`, as [`Expression::Compose`] does.
+    ///
+    /// [`Expression::Compose`]: crate::Expression::Compose
+    pub fn compose(inner: &TypeInner, components: Vec<Value>) -> Value {
+        match *inner {
+            // Vectors may be built from smaller vectors.
+            TypeInner::Vector { .. } => Self::Composite(
+                components
+                    .into_iter()
+                    .flat_map(|component| match component {
+                        Self::Composite(components) => components,
+                        other => vec![other],
+                    })
+                    .collect(),
+            ),
+            _ => Self::Composite(components),
+        }
+    }
+
+    /// Return the zero value of `inner`.
+    pub fn zero(types: &UniqueArena<Type>, inner: &TypeInner) -> Result<Value, Error> {
+        Ok(match *inner {
+            TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => Self::Scalar(zero(scalar)?),
+            TypeInner::Vector { size, scalar } => {
+                Self::Composite(vec![Self::Scalar(zero(scalar)?); size as usize])
+            }
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => {
+                let column = Self::Composite(vec![Self::Scalar(zero(scalar)?); rows as usize]);
+                Self::Composite(vec![column; columns as usize])
+            }
+            TypeInner::Array {
+                base,
+                size: ArraySize::Constant(count),
+                ..
+            } => Self::Composite(vec![
+                Self::zero(types, &types[base].inner)?;
+                count.get() as usize
+            ]),
+            TypeInner::Struct { ref members, .. } => Self::Composite(
+                members
+                    .iter()
+                    .map(|member| Self::zero(types, &types[member.ty].inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ref other => return Err(Error::Unsupported(format!("zero value of {other:?}"))),
+        })
+    }
+
+    /// Decode a value of type `inner` from `bytes`, starting at `offset`.
+    pub fn read(
+        types: &UniqueArena<Type>,
+        inner: &TypeInner,
+        bytes: &[u8],
+        offset: u32,
+    ) -> Result<Value, Error> {
+        Ok(match *inner {
+            TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => {
+                Self::Scalar(read_scalar(scalar, bytes, offset)?)
+            }
+            TypeInner::Vector { size, scalar } => Self::Composite(
+                (0..size as u32)
+                    .map(|i| read_scalar(scalar, bytes, offset + i * scalar.width as u32))
+                    .map(|literal| literal.map(Self::Scalar))
+                    .collect::<Result<_, _>>()?,
+            ),
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => {
+                let column = TypeInner::Vector { size: rows, scalar };
+                let stride = column_stride(rows, scalar);
+                Self::Composite(
+                    (0..columns as u32)
+                        .map(|i| Self::read(types, &column, bytes, offset + i * stride))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            TypeInner::Array {
+                base,
+                size: ArraySize::Constant(count),
+                stride,
+            } => Self::Composite(
+                (0..count.get())
+                    .map(|i| Self::read(types, &types[base].inner, bytes, offset + i * stride))
+                    .collect::<Result<_, _>>()?,
+            ),
+            TypeInner::Struct { ref members, .. } => Self::Composite(
+                members
+                    .iter()
+                    .map(|member| {
+                        Self::read(
+                            types,
+                            &types[member.ty].inner,
+                            bytes,
+                            offset + member.offset,
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            ref other => return Err(Error::Unsupported(format!("loading {other:?}"))),
+        })
+    }
+
+    /// Encode this value, of type `inner`, into `bytes` at `offset`.
+    pub fn write(
+        &self,
+        types: &UniqueArena<Type>,
+        inner: &TypeInner,
+        bytes: &mut [u8],
+        offset: u32,
+    ) -> Result<(), Error> {
+        match (inner, self) {
+            (&(TypeInner::Scalar(_) | TypeInner::Atomic(_)), &Self::Scalar(literal)) => {
+                write_scalar(literal, bytes, offset)
+            }
+            (&TypeInner::Vector { scalar, .. }, &Self::Composite(ref components)) => {
+                let width = scalar.width as u32;
+                let scalar = TypeInner::Scalar(scalar);
+                for (i, component) in components.iter().enumerate() {
+                    component.write(types, &scalar, bytes, offset + i as u32 * width)?;
+                }
+                Ok(())
+            }
+            (&TypeInner::Matrix { rows, scalar, .. }, &Self::Composite(ref columns)) => {
+                let column = TypeInner::Vector { size: rows, scalar };
+                let stride = column_stride(rows, scalar);
+                for (i, value) in columns.iter().enumerate() {
+                    value.write(types, &column, bytes, offset + i as u32 * stride)?;
+                }
+                Ok(())
+            }
+            (&TypeInner::Array { base, stride, .. }, &Self::Composite(ref elements)) => {
+                for (i, element) in elements.iter().enumerate() {
+                    element.write(types, &types[base].inner, bytes, offset + i as u32 * stride)?;
+                }
+                Ok(())
+            }
+            (&TypeInner::Struct { ref members, .. }, &Self::Composite(ref values)) => {
+                for (member, value) in members.iter().zip(values) {
+                    value.write(
+                        types,
+                        &types[member.ty].inner,
+                        bytes,
+                        offset + member.offset,
+                    )?;
+                }
+                Ok(())
+            }
+            (other, _) => Err(Error::Unsupported(format!("storing {other:?}"))),
+        }
+    }
+}
+
+/// The distance between the columns of a matrix, as laid out by
+/// [`Layouter`](crate::proc::Layouter).
+pub(super) fn column_stride(rows: crate::VectorSize, scalar: Scalar) -> u32 {
+    Alignment::from(rows) * scalar.width as u32
+}
+
+pub(super) fn zero(scalar: Scalar) -> Result<Literal, Error> {
+    Literal::zero(scalar).ok_or_else(|| Error::Unsupported(format!("{scalar:?} values")))
+}
+
+fn read_scalar(scalar: Scalar, bytes: &[u8], offset: u32) -> Result<Literal, Error> {
+    let start = offset as usize;
+    let bytes = bytes
+        .get(start..start + scalar.width as usize)
+        .ok_or(Error::OutOfBounds)?;
+    let mut bits = [0; 8];
+    bits[..bytes.len()].copy_from_slice(bytes);
+    from_bits(u64::from_le_bytes(bits), scalar)
+}
+
+fn write_scalar(literal: Literal, bytes: &mut [u8], offset: u32) -> Result<(), Error> {
+    let start = offset as usize;
+    let width = literal.width() as usize;
+    let bytes = bytes
+        .get_mut(start..start + width)
+        .ok_or(Error::OutOfBounds)?;
+    bytes.copy_from_slice(&to_bits(literal)?.to_le_bytes()[..width]);
+    Ok(())
+}
+
+/// Return the bits of `literal`, zero-extended to 64 bits.
+pub(super) fn to_bits(literal: Literal) -> Result<u64, Error> {
+    Ok(match literal {
+        Literal::F64(value) => value.to_bits(),
+        Literal::F32(value) => value.to_bits() as u64,
+        Literal::F16(value) => value.to_bits() as u64,
+        Literal::U32(value) => value as u64,
+        Literal::I32(value) => value as u32 as u64,
+        Literal::U64(value) => value,
+        Literal::I64(value) => value as u64,
+        Literal::Bool(value) => value as u64,
+        Literal::AbstractInt(_) | Literal::AbstractFloat(_) => {
+            return Err(Error::Unsupported(format!("{literal:?}")))
+        }
+    })
+}
+
+/// Build a literal of type `scalar` from the low bits of `bits`.
+pub(super) fn from_bits(bits: u64, scalar: Scalar) -> Result<Literal, Error> {
+    Ok(match (scalar.kind, scalar.width) {
+        (ScalarKind::Float, 8) => Literal::F64(f64::from_bits(bits)),
+        (ScalarKind::Float, 4) => Literal::F32(f32::from_bits(bits as u32)),
+        (ScalarKind::Float, 2) => Literal::F16(half::f16::from_bits(bits as u16)),
+        (ScalarKind::Uint, 4) => Literal::U32(bits as u32),
+        (ScalarKind::Sint, 4) => Literal::I32(bits as u32 as i32),
+        (ScalarKind::Uint, 8) => Literal::U64(bits),
+        (ScalarKind::Sint, 8) => Literal::I64(bits as i64),
+        (ScalarKind::Bool, crate::BOOL_WIDTH) => Literal::Bool(bits & 0xFF != 0),
+        _ => return Err(Error::Unsupported(format!("{scalar:?} values"))),
+    })
+}
@@ -54,6 +54,7 @@ pub const RESERVED: &[&str] = &[
     "break",
     "case",
     "const",
+    "const_assert",
     "continue",
     "continuing",
     "default",
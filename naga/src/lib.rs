@@ -255,19 +255,25 @@ pub mod back;
 mod block;
 #[cfg(feature = "compact")]
 pub mod compact;
+pub mod diagnostic_filter;
 pub mod error;
 pub mod front;
+#[cfg(feature = "interp")]
+pub mod interp;
 pub mod keywords;
 mod non_max_u32;
+#[cfg(feature = "opt")]
+pub mod opt;
 pub mod proc;
 mod span;
 pub mod valid;
 
 pub use crate::arena::{Arena, Handle, Range, UniqueArena};
 
-pub use crate::span::{SourceLocation, Span, SpanContext, WithSpan};
+pub use crate::span::{SourceFile, SourceLocation, SourceMap, Span, SpanContext, WithSpan};
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
+use diagnostic_filter::DiagnosticFilterNode;
 #[cfg(feature = "deserialize")]
 use serde::Deserialize;
 #[cfg(feature = "serialize")]
@@ -382,6 +388,17 @@ pub enum AddressSpace {
     Handle,
     /// Push constants.
     PushConstant,
+    /// Buffer memory reached through a device address, as in Vulkan's
+    /// `PhysicalStorageBuffer` storage class and GLSL's `buffer_reference`.
+    ///
+    /// No variable can be declared in this address space. It is only reached
+    /// through [`Pointer`] values in it, which unlike other pointers are
+    /// [`DATA`] types, 8 bytes in size and alignment, and so can be stored in
+    /// buffers, push constants and variables.
+    ///
+    /// [`Pointer`]: TypeInner::Pointer
+    /// [`DATA`]: valid::TypeFlags::DATA
+    PhysicalStorage,
 }
 
 /// Built-in inputs and outputs.
@@ -492,10 +509,41 @@ pub struct Scalar {
 pub enum ArraySize {
     /// The array size is constant.
     Constant(std::num::NonZeroU32),
+    /// The array size is an override-expression, known once the pipeline is
+    /// created.
+    ///
+    /// Only the store types of [`WorkGroup`] global variables may use this.
+    /// Back ends can't write these types; override processing in
+    /// `back::pipeline_constants::process_overrides` replaces them with
+    /// [`Constant`] sizes.
+    ///
+    /// [`WorkGroup`]: AddressSpace::WorkGroup
+    /// [`Constant`]: ArraySize::Constant
+    Pending(PendingArraySize),
     /// The array size can change at runtime.
     Dynamic,
 }
 
+/// The size of an array whose length is an override-expression.
+///
+/// See [`ArraySize::Pending`].
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum PendingArraySize {
+    /// The size is the value of an [`Override`].
+    ///
+    /// Arrays sized by the same override are the same type.
+    Override(Handle<Override>),
+    /// The size is the value of an expression in
+    /// [`Module::global_expressions`] that refers to overrides.
+    ///
+    /// Each such expression gives a distinct type, even if two are written
+    /// the same way.
+    Expression(Handle<Expression>),
+}
+
 /// The interpolation qualifier of a binding or struct field.
 #[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serialize", derive(Serialize))]
@@ -723,6 +771,13 @@ pub enum TypeInner {
     /// [`ValuePointer`]: TypeInner::ValuePointer
     /// [`GlobalVariable`]: Expression::GlobalVariable
     /// [`AccessIndex`]: Expression::AccessIndex
+    ///
+    /// ## Pointers in the `PhysicalStorage` address space
+    ///
+    /// Pointers into [`AddressSpace::PhysicalStorage`] are device addresses.
+    /// They are `DATA` whatever their `base` is, so unlike other pointers they
+    /// can be struct members, array elements and the types of variables, and
+    /// are loaded and stored like any other value.
     Pointer {
         base: Handle<Type>,
         space: AddressSpace,
@@ -863,6 +918,8 @@ pub enum Literal {
     F64(f64),
     /// May not be NaN or infinity.
     F32(f32),
+    /// May not be NaN or infinity.
+    F16(#[cfg_attr(feature = "arbitrary", arbitrary(with = arbitrary_f16))] half::f16),
     U32(u32),
     I32(i32),
     U64(u64),
@@ -872,6 +929,12 @@ pub enum Literal {
     AbstractFloat(f64),
 }
 
+/// Generate an arbitrary `f16`, since `half` doesn't implement `Arbitrary`.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_f16(u: &mut arbitrary::Unstructured) -> arbitrary::Result<half::f16> {
+    Ok(half::f16::from_bits(u.arbitrary()?))
+}
+
 /// Pipeline-overridable constant.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serialize", derive(Serialize))]
@@ -2050,6 +2113,29 @@ pub enum Statement {
         /// [`SubgroupOperationResult`]: Expression::SubgroupOperationResult
         result: Handle<Expression>,
     },
+    /// Print a message for debugging, where the platform supports it.
+    ///
+    /// This has no effect on the shader's results. Back ends for languages
+    /// with no way to print write nothing for it.
+    DebugPrintf {
+        /// A `printf`-style format string, with a `%` conversion for each
+        /// argument, as used by SPIR-V's `NonSemantic.DebugPrintf`.
+        format: String,
+        /// The values to print. These must be scalars or vectors.
+        arguments: Vec<Handle<Expression>>,
+    },
+    /// Change the diagnostic filters in effect for the rest of the enclosing
+    /// [`Block`], including any blocks nested in it.
+    ///
+    /// The handle is the new leaf of the diagnostic filter tree, stored in
+    /// [`Module::diagnostic_filters`]. It is normally a descendant of the leaf
+    /// that was in effect before, like the function's
+    /// [`diagnostic_filter_leaf`](Function::diagnostic_filter_leaf).
+    ///
+    /// WGSL front ends produce these for `@diagnostic(…)` attributes on
+    /// statements. This has no effect on the shader's behavior, only on what
+    /// validation reports, so most back ends write nothing for it.
+    DiagnosticFilter(Handle<DiagnosticFilterNode>),
 }
 
 /// A function argument.
@@ -2113,6 +2199,15 @@ pub struct Function {
     pub named_expressions: NamedExpressions,
     /// Block of instructions comprising the body of the function.
     pub body: Block,
+    /// The leaf of all diagnostic filter rules tree (stored in [`Module::diagnostic_filters`])
+    /// parsed on this function.
+    ///
+    /// In WGSL, this corresponds to `@diagnostic(…)` attributes, chained onto
+    /// [`Module::diagnostic_filter_leaf`].
+    ///
+    /// See [`DiagnosticFilterNode`] for details on how the tree is represented and used in
+    /// validation.
+    pub diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
 }
 
 /// The main function for a pipeline stage.
@@ -2220,6 +2315,35 @@ pub struct SpecialTypes {
     pub predeclared_types: FastIndexMap<PredeclaredType, Handle<Type>>,
 }
 
+/// Documentation comments for the items in a [`Module`].
+///
+/// Front ends that understand documentation comments, like WGSL's `///`
+/// comments, record them here, and back ends write them out again before the
+/// items they describe. Each comment is a list of lines, without comment
+/// markers. Items with no documentation have no entry.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct DocComments {
+    /// Comments on types, usually structs.
+    pub types: FastIndexMap<Handle<Type>, Vec<String>>,
+    /// Comments on struct members, keyed by the struct's type and the
+    /// member's index.
+    pub struct_members: FastIndexMap<(Handle<Type>, usize), Vec<String>>,
+    /// Comments on entry points, keyed by their index in
+    /// [`Module::entry_points`].
+    pub entry_points: FastIndexMap<usize, Vec<String>>,
+    /// Comments on functions.
+    pub functions: FastIndexMap<Handle<Function>, Vec<String>>,
+    /// Comments on constants.
+    pub constants: FastIndexMap<Handle<Constant>, Vec<String>>,
+    /// Comments on pipeline-overridable constants.
+    pub overrides: FastIndexMap<Handle<Override>, Vec<String>>,
+    /// Comments on global variables.
+    pub global_variables: FastIndexMap<Handle<GlobalVariable>, Vec<String>>,
+}
+
 /// Shader module.
 ///
 /// A module is a set of constants, global variables and functions, as well as
@@ -2265,4 +2389,22 @@ pub struct Module {
     pub functions: Arena<Function>,
     /// Entry points.
     pub entry_points: Vec<EntryPoint>,
+    /// Arena for all diagnostic filter rules parsed in this module, including those in functions
+    /// and statements.
+    ///
+    /// This arena contains elements of a _tree_ of diagnostic filter rules. When nodes are built
+    /// by a front-end, they refer to a parent scope, so the chain from any node to the root lists
+    /// every rule in effect there, most specific first.
+    pub diagnostic_filters: Arena<DiagnosticFilterNode>,
+    /// The leaf of all diagnostic filter rules tree parsed from directives in this module.
+    ///
+    /// In WGSL, this corresponds to `diagnostic(…);` directives.
+    ///
+    /// See [`DiagnosticFilterNode`] for details on how the tree is represented and used in
+    /// validation.
+    pub diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
+    /// Documentation comments for this module's items, if any.
+    ///
+    /// See [`DocComments`] for details.
+    pub doc_comments: Option<Box<DocComments>>,
 }
@@ -0,0 +1,295 @@
+//! Branch folding and removal of unreachable statements.
+
+use super::known_scalar;
+use crate::{
+    Arena, Block, Expression, Function, Handle, Literal, Module, Statement, SwitchCase, SwitchValue,
+};
+use std::mem;
+
+/// Simplify the control flow of `function`'s body.
+///
+/// Replace [`If`] and [`Switch`] statements whose conditions or selectors are
+/// known with the statements they would run, flatten nested [`Block`]s, and
+/// remove statements that can never run or do nothing.
+///
+/// Return true if anything changed.
+///
+/// [`If`]: Statement::If
+/// [`Switch`]: Statement::Switch
+/// [`Block`]: Statement::Block
+pub(super) fn fold_branches(module: &Module, function: &mut Function) -> bool {
+    let mut folder = BranchFolder {
+        module,
+        expressions: &function.expressions,
+        changed: false,
+    };
+    folder.block(&mut function.body, false);
+    folder.changed
+}
+
+struct BranchFolder<'a> {
+    module: &'a Module,
+    expressions: &'a Arena<Expression>,
+    changed: bool,
+}
+
+impl BranchFolder<'_> {
+    /// Simplify `block`.
+    ///
+    /// If `keep_scope` is true, `block` is the body of a [`Loop`] that
+    /// [`Continue`] statements can reach the `continuing` block of. Since
+    /// that block may use any expression `block` emits, we must not let a
+    /// folded branch make the statements of `block` that emit them
+    /// unreachable.
+    ///
+    /// [`Loop`]: Statement::Loop
+    /// [`Continue`]: Statement::Continue
+    fn block(&mut self, block: &mut Block, keep_scope: bool) {
+        let original = mem::replace(block, Block::with_capacity(block.len()));
+        let mut statements = original.span_into_iter().peekable();
+        while let Some((stmt, span)) = statements.next() {
+            let len = block.len();
+            // Whether we may replace this statement with ones that leave
+            // `block` early.
+            let may_leave = !keep_scope || statements.peek().is_none();
+            match stmt {
+                Statement::Block(mut body) => {
+                    self.block(&mut body, false);
+                    block.append(&mut body);
+                    self.changed = true;
+                }
+                Statement::If {
+                    condition,
+                    mut accept,
+                    mut reject,
+                } => {
+                    self.block(&mut accept, false);
+                    self.block(&mut reject, false);
+                    match known_scalar(self.module, self.expressions, condition) {
+                        Some(Literal::Bool(value)) => {
+                            let (taken, other) = if value {
+                                (&mut accept, &mut reject)
+                            } else {
+                                (&mut reject, &mut accept)
+                            };
+                            if may_leave || !leaves(taken) {
+                                block.append(taken);
+                                self.changed = true;
+                            } else {
+                                // We can still drop the branch not taken.
+                                if !other.is_empty() {
+                                    *other = Block::new();
+                                    self.changed = true;
+                                }
+                                block.push(
+                                    Statement::If {
+                                        condition,
+                                        accept,
+                                        reject,
+                                    },
+                                    span,
+                                );
+                            }
+                        }
+                        // Conditions have no side effects.
+                        _ if accept.is_empty() && reject.is_empty() => self.changed = true,
+                        _ => block.push(
+                            Statement::If {
+                                condition,
+                                accept,
+                                reject,
+                            },
+                            span,
+                        ),
+                    }
+                }
+                Statement::Switch {
+                    selector,
+                    mut cases,
+                } => {
+                    for case in cases.iter_mut() {
+                        self.block(&mut case.body, false);
+                    }
+                    self.switch(block, selector, cases, span, may_leave);
+                }
+                Statement::Loop {
+                    mut body,
+                    mut continuing,
+                    mut break_if,
+                } => {
+                    let continues = continues(&body);
+                    self.block(&mut body, continues);
+                    self.block(&mut continuing, false);
+                    if let Some(condition) = break_if {
+                        if known_scalar(self.module, self.expressions, condition)
+                            == Some(Literal::Bool(false))
+                        {
+                            break_if = None;
+                            self.changed = true;
+                        }
+                    }
+                    // If nothing reaches the `continuing` block, drop it.
+                    if !continues && leaves(&body) && (!continuing.is_empty() || break_if.is_some())
+                    {
+                        continuing = Block::new();
+                        break_if = None;
+                        self.changed = true;
+                    }
+                    // A loop that always breaks at the end of its body runs
+                    // the body once.
+                    if let Some((&Statement::Break, rest)) = body.split_last() {
+                        if !continues && !breaks(rest) {
+                            body.cull(rest.len()..);
+                            block.append(&mut body);
+                            self.changed = true;
+                            continue;
+                        }
+                    }
+                    block.push(
+                        Statement::Loop {
+                            body,
+                            continuing,
+                            break_if,
+                        },
+                        span,
+                    );
+                }
+                stmt => block.push(stmt, span),
+            }
+
+            // Drop the statements following one that leaves the block.
+            if leaves(&block[len..]) {
+                if statements.next().is_some() {
+                    self.changed = true;
+                }
+                break;
+            }
+        }
+    }
+
+    /// Append a simplified form of the `Switch` statement with the given
+    /// `selector` and `cases` to `block`.
+    ///
+    /// If `may_leave` is false, the statements appended must not leave
+    /// `block` early.
+    fn switch(
+        &mut self,
+        block: &mut Block,
+        selector: Handle<Expression>,
+        mut cases: Vec<SwitchCase>,
+        span: crate::Span,
+        may_leave: bool,
+    ) {
+        // Find the case that will run, if we know the selector, or if there
+        // is only the default case.
+        let index = match known_scalar(self.module, self.expressions, selector) {
+            Some(value) => cases
+                .iter()
+                .position(|case| match case.value {
+                    SwitchValue::I32(case_value) => value == Literal::I32(case_value),
+                    SwitchValue::U32(case_value) => value == Literal::U32(case_value),
+                    SwitchValue::Default => false,
+                })
+                .or_else(|| {
+                    cases
+                        .iter()
+                        .position(|case| case.value == SwitchValue::Default)
+                }),
+            None if cases.len() == 1 => Some(0),
+            None => None,
+        };
+        let Some(index) = index else {
+            if cases.iter().all(|case| case.body.is_empty()) {
+                // Selectors have no side effects.
+                self.changed = true;
+            } else {
+                block.push(Statement::Switch { selector, cases }, span);
+            }
+            return;
+        };
+
+        // Collect the statements the chosen case runs, falling through to
+        // the following cases as needed.
+        let mut changed = cases.len() > 1;
+        let mut body = Block::new();
+        for mut case in cases.drain(index..) {
+            body.append(&mut case.body);
+            if !case.fall_through {
+                break;
+            }
+        }
+
+        // A `Break` at the end of the body is redundant. Any other `Break`
+        // leaves the switch from the middle of its body, so we need to keep a
+        // `Switch` statement for it to refer to.
+        if let Some(&Statement::Break) = body.last() {
+            body.cull(body.len() - 1..);
+            changed = true;
+        }
+        if breaks(&body) || (!may_leave && leaves(&body)) {
+            self.changed |= changed;
+            let case = SwitchCase {
+                value: SwitchValue::Default,
+                body,
+                fall_through: false,
+            };
+            block.push(
+                Statement::Switch {
+                    selector,
+                    cases: vec![case],
+                },
+                span,
+            );
+        } else {
+            block.append(&mut body);
+            self.changed = true;
+        }
+    }
+}
+
+/// Return true if one of `statements` leaves the block they belong to.
+fn leaves(statements: &[Statement]) -> bool {
+    statements.iter().any(|stmt| {
+        matches!(
+            *stmt,
+            Statement::Break | Statement::Continue | Statement::Return { .. } | Statement::Kill
+        )
+    })
+}
+
+/// Return true if `statements` contain a [`Break`] that leaves the statement
+/// they belong to.
+///
+/// [`Break`]: Statement::Break
+fn breaks(statements: &[Statement]) -> bool {
+    statements.iter().any(|stmt| match *stmt {
+        Statement::Break => true,
+        Statement::Block(ref body) => breaks(body),
+        Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => breaks(accept) || breaks(reject),
+        // A `Break` in any other statement leaves that statement.
+        _ => false,
+    })
+}
+
+/// Return true if `statements` contain a [`Continue`] that goes to the
+/// `continuing` block of the loop they belong to.
+///
+/// [`Continue`]: Statement::Continue
+fn continues(statements: &[Statement]) -> bool {
+    statements.iter().any(|stmt| match *stmt {
+        Statement::Continue => true,
+        Statement::Block(ref body) => continues(body),
+        Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => continues(accept) || continues(reject),
+        Statement::Switch { ref cases, .. } => cases.iter().any(|case| continues(&case.body)),
+        // A `Continue` in a nested loop belongs to that loop.
+        _ => false,
+    })
+}
@@ -0,0 +1,244 @@
+//! Dead code elimination.
+
+use super::{local_root, visit};
+use crate::{
+    arena::{HandleSet, HandleVec},
+    Arena, Block, Expression, Function, LocalVariable, Range, Statement,
+};
+use std::mem;
+
+/// Return the set of expressions in `function` whose values are used by its
+/// statements, directly or indirectly.
+///
+/// The initializers of local variables are not included.
+pub(super) fn live_expressions(function: &mut Function) -> HandleSet<Expression> {
+    let mut live = HandleSet::for_arena(&function.expressions);
+    visit::block(&mut function.body, &mut |expr| {
+        live.insert(*expr);
+    });
+    propagate(&mut function.expressions, &mut live);
+    live
+}
+
+/// Add the operands of every expression in `live` to it, recursively.
+fn propagate(expressions: &mut Arena<Expression>, live: &mut HandleSet<Expression>) {
+    // Operands always come before the expressions that use them.
+    for (handle, expr) in expressions.iter_mut().rev() {
+        if live.contains(handle) {
+            visit::expression(expr, |operand| {
+                live.insert(*operand);
+            });
+        }
+    }
+}
+
+/// How the statements and live expressions of a function use its local
+/// variables.
+pub(super) struct LocalUsage {
+    /// Local variables whose values may be read.
+    pub read: HandleSet<LocalVariable>,
+
+    /// Local variables that may be written, other than by their
+    /// initializers.
+    pub written: HandleSet<LocalVariable>,
+}
+
+impl LocalUsage {
+    pub fn new(function: &mut Function, live: &HandleSet<Expression>) -> Self {
+        let mut usage = LocalUsage {
+            read: HandleSet::for_arena(&function.local_variables),
+            written: HandleSet::for_arena(&function.local_variables),
+        };
+
+        // In a valid module, these are the only expressions that can take
+        // pointers to local variables as operands, other than the `Access`
+        // and `AccessIndex` expressions producing such pointers.
+        for (handle, expr) in function.expressions.iter() {
+            match *expr {
+                Expression::Load { pointer: operand }
+                | Expression::RayQueryGetIntersection { query: operand, .. }
+                    if live.contains(handle) =>
+                {
+                    if let Some(local) = local_root(&function.expressions, operand) {
+                        usage.read.insert(local);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        usage.block(&mut function.body, &function.expressions);
+        usage
+    }
+
+    fn block(&mut self, block: &mut Block, expressions: &Arena<Expression>) {
+        for stmt in block.iter_mut() {
+            match *stmt {
+                Statement::Store { pointer, .. } => {
+                    if let Some(local) = local_root(expressions, pointer) {
+                        self.written.insert(local);
+                    }
+                }
+                Statement::Block(ref mut body) => self.block(body, expressions),
+                Statement::If {
+                    ref mut accept,
+                    ref mut reject,
+                    ..
+                } => {
+                    self.block(accept, expressions);
+                    self.block(reject, expressions);
+                }
+                Statement::Switch { ref mut cases, .. } => {
+                    for case in cases.iter_mut() {
+                        self.block(&mut case.body, expressions);
+                    }
+                }
+                Statement::Loop {
+                    ref mut body,
+                    ref mut continuing,
+                    ..
+                } => {
+                    self.block(body, expressions);
+                    self.block(continuing, expressions);
+                }
+                // Any other use of a pointer, such as passing it to a
+                // function, may both read and write the variable.
+                _ => visit::statement(stmt, &mut |expr| {
+                    if let Some(local) = local_root(expressions, *expr) {
+                        self.read.insert(local);
+                        self.written.insert(local);
+                    }
+                }),
+            }
+        }
+    }
+}
+
+/// Remove stores to local variables of `function` that are never read.
+///
+/// Return true if anything changed.
+pub(super) fn remove_dead_stores(function: &mut Function) -> bool {
+    let live = live_expressions(function);
+    let usage = LocalUsage::new(function, &live);
+    let mut dead = HandleSet::for_arena(&function.local_variables);
+    for handle in usage.written.iter() {
+        if !usage.read.contains(handle) {
+            dead.insert(handle);
+        }
+    }
+    if dead.iter().next().is_none() {
+        return false;
+    }
+    remove_stores(&mut function.body, &function.expressions, &dead);
+    true
+}
+
+/// Remove stores to the `dead` local variables from `block`.
+fn remove_stores(
+    block: &mut Block,
+    expressions: &Arena<Expression>,
+    dead: &HandleSet<LocalVariable>,
+) {
+    let original = mem::replace(block, Block::with_capacity(block.len()));
+    for (mut stmt, span) in original.span_into_iter() {
+        match stmt {
+            Statement::Store { pointer, .. }
+                if local_root(expressions, pointer).is_some_and(|local| dead.contains(local)) =>
+            {
+                continue;
+            }
+            Statement::Block(ref mut body) => remove_stores(body, expressions, dead),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                remove_stores(accept, expressions, dead);
+                remove_stores(reject, expressions, dead);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    remove_stores(&mut case.body, expressions, dead);
+                }
+            }
+            Statement::Loop {
+                ref mut body,
+                ref mut continuing,
+                ..
+            } => {
+                remove_stores(body, expressions, dead);
+                remove_stores(continuing, expressions, dead);
+            }
+            _ => {}
+        }
+        block.push(stmt, span);
+    }
+}
+
+/// Remove the expressions and local variables that `function` doesn't use.
+pub(super) fn remove_unused(function: &mut Function) {
+    let mut live = live_expressions(function);
+
+    let mut locals_used = HandleSet::for_arena(&function.local_variables);
+    for handle in live.iter() {
+        if let Expression::LocalVariable(local) = function.expressions[handle] {
+            locals_used.insert(local);
+        }
+    }
+    for (handle, local) in function.local_variables.iter() {
+        if let Some(init) = local.init {
+            if locals_used.contains(handle) {
+                live.insert(init);
+            }
+        }
+    }
+    propagate(&mut function.expressions, &mut live);
+
+    let mut local_map = HandleVec::with_capacity(function.local_variables.len());
+    for (handle, local, span) in mem::take(&mut function.local_variables).drain() {
+        let new_handle = locals_used
+            .contains(handle)
+            .then(|| function.local_variables.append(local, span));
+        local_map.insert(handle, new_handle);
+    }
+
+    let mut new_pos: HandleVec<Expression, Option<_>> =
+        HandleVec::with_capacity(function.expressions.len());
+    for (handle, mut expr, span) in mem::take(&mut function.expressions).drain() {
+        let new_handle = live.contains(handle).then(|| {
+            visit::expression(&mut expr, |operand| *operand = new_pos[*operand].unwrap());
+            if let Expression::LocalVariable(ref mut local) = expr {
+                *local = local_map[*local].unwrap();
+            }
+            function.expressions.append(expr, span)
+        });
+        new_pos.insert(handle, new_handle);
+    }
+
+    visit::block(&mut function.body, &mut |expr| {
+        *expr = new_pos[*expr].unwrap()
+    });
+
+    // The live expressions an `Emit` statement covered are still
+    // contiguous, since we've removed everything between them.
+    visit::emits(&mut function.body, &mut |range| {
+        let mut live = range.filter_map(|expr| new_pos[expr]);
+        let Some(first) = live.next() else {
+            return Vec::new();
+        };
+        let last = live.last().unwrap_or(first);
+        vec![Range::new_from_bounds(first, last)]
+    });
+
+    for (_, local) in function.local_variables.iter_mut() {
+        if let Some(ref mut init) = local.init {
+            *init = new_pos[*init].unwrap();
+        }
+    }
+
+    let named_expressions = mem::take(&mut function.named_expressions);
+    function.named_expressions = named_expressions
+        .into_iter()
+        .filter_map(|(handle, name)| Some((new_pos[handle]?, name)))
+        .collect();
+}
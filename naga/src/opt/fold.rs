@@ -0,0 +1,286 @@
+//! Constant propagation.
+
+use super::{dce, known_scalar, visit};
+use crate::{
+    arena::{HandleSet, HandleVec},
+    proc::{ConstantEvaluator, Emitter, ExpressionKind, ExpressionKindTracker},
+    Arena, BinaryOperator, Block, Expression, Function, Handle, Literal, LocalVariable, Module,
+    Range, Span, TypeInner,
+};
+use std::{mem, ops};
+
+/// Rebuild `function`'s expression arena, evaluating every expression whose
+/// value is known.
+///
+/// Besides what [`ConstantEvaluator`] can evaluate, this simplifies [`Select`]
+/// expressions and logical operators with a known operand, and replaces loads
+/// of scalars and vectors from local variables that are only ever written by
+/// their initializer with the initializer's value.
+///
+/// [`Select`]: Expression::Select
+pub(super) fn fold_expressions(module: &mut Module, function: &mut Function) {
+    let live = dce::live_expressions(function);
+    let usage = dce::LocalUsage::new(function, &live);
+    let mut constant_locals = HandleSet::for_arena(&function.local_variables);
+    for (handle, local) in function.local_variables.iter() {
+        if local.init.is_some() && !usage.written.contains(handle) {
+            constant_locals.insert(handle);
+        }
+    }
+
+    let old = mem::take(&mut function.expressions);
+    let mut folder = Folder {
+        module,
+        expressions: &mut function.expressions,
+        tracker: ExpressionKindTracker::new(),
+        emitter: Emitter::default(),
+        block: Block::new(),
+    };
+
+    // For each expression in `old`, its new handle, and the indices of the
+    // expressions we appended to the new arena while processing it.
+    let mut new_pos = HandleVec::with_capacity(old.len());
+    let mut appended: HandleVec<Expression, ops::Range<u32>> = HandleVec::with_capacity(old.len());
+
+    for (old_handle, expr) in old.iter() {
+        let span = old.get_span(old_handle);
+        let start = folder.expressions.len();
+
+        let forwarded = match *expr {
+            Expression::Load { pointer } => folder.forward_load(
+                &old,
+                &new_pos,
+                &function.local_variables,
+                &constant_locals,
+                pointer,
+                span,
+            ),
+            _ => None,
+        };
+        let mut handle = forwarded.unwrap_or_else(|| {
+            let mut expr = expr.clone();
+            visit::expression(&mut expr, |operand| *operand = new_pos[*operand]);
+            match folder.simplify(&expr) {
+                Some(handle) => handle,
+                None => folder.append(expr, span),
+            }
+        });
+
+        // If we have reduced this expression to one we had already appended,
+        // make sure it is emitted wherever this one was. Run-time
+        // expressions always are, but constant ones may not be.
+        if handle.index() < start
+            && folder.tracker.is_const(handle)
+            && !folder.expressions[handle].needs_pre_emit()
+        {
+            handle = folder.copy(handle, span);
+        }
+
+        new_pos.insert(old_handle, handle);
+        appended.insert(old_handle, start as u32..folder.expressions.len() as u32);
+    }
+
+    visit::block(&mut function.body, &mut |expr| *expr = new_pos[*expr]);
+
+    // Each `Emit` statement now covers the expressions appended for the
+    // expressions it covered before, except for those that must not be
+    // emitted.
+    let expressions = &function.expressions;
+    visit::emits(&mut function.body, &mut |range| {
+        let Some((first, last)) = range.first_and_last() else {
+            return Vec::new();
+        };
+        let range = Range::from_index_range(appended[first].start..appended[last].end, expressions);
+        let mut ranges = Vec::new();
+        let mut current = None;
+        for handle in range {
+            if expressions[handle].needs_pre_emit() {
+                if let Some((first, last)) = current.take() {
+                    ranges.push(Range::new_from_bounds(first, last));
+                }
+            } else if let Some((_, ref mut last)) = current {
+                *last = handle;
+            } else {
+                current = Some((handle, handle));
+            }
+        }
+        if let Some((first, last)) = current {
+            ranges.push(Range::new_from_bounds(first, last));
+        }
+        ranges
+    });
+
+    for (_, local) in function.local_variables.iter_mut() {
+        if let Some(ref mut init) = local.init {
+            *init = new_pos[*init];
+        }
+    }
+
+    // Several expressions may have been reduced to the same one. Keep the
+    // first name given to it.
+    let named_expressions = mem::take(&mut function.named_expressions);
+    for (handle, name) in named_expressions {
+        function
+            .named_expressions
+            .entry(new_pos[handle])
+            .or_insert(name);
+    }
+}
+
+struct Folder<'a> {
+    module: &'a mut Module,
+    expressions: &'a mut Arena<Expression>,
+    tracker: ExpressionKindTracker,
+
+    /// Dummy `emitter` and `block` for the constant evaluator, which only
+    /// uses them when the emitter is running.
+    emitter: Emitter,
+    block: Block,
+}
+
+impl Folder<'_> {
+    /// Append `expr`, evaluating it if possible.
+    fn append(&mut self, expr: Expression, span: Span) -> Handle<Expression> {
+        let mut evaluator = ConstantEvaluator::for_wgsl_function(
+            self.module,
+            self.expressions,
+            &mut self.tracker,
+            &mut self.emitter,
+            &mut self.block,
+        );
+        match evaluator.try_eval_and_append(expr.clone(), span) {
+            Ok(handle) => handle,
+            Err(error) => {
+                log::trace!("leaving {expr:?} for run time: {error}");
+                let handle = self.expressions.append(expr, span);
+                self.tracker.insert(handle, ExpressionKind::Runtime);
+                handle
+            }
+        }
+    }
+
+    /// Append a copy of the constant expression `expr`, and of its operands.
+    fn copy(&mut self, expr: Handle<Expression>, span: Span) -> Handle<Expression> {
+        let mut copy = self.expressions[expr].clone();
+        visit::expression(&mut copy, |operand| *operand = self.copy(*operand, span));
+        self.append(copy, span)
+    }
+
+    /// Return the handle of an existing expression with the same value as
+    /// `expr`, if a known operand makes the others irrelevant.
+    fn simplify(&self, expr: &Expression) -> Option<Handle<Expression>> {
+        let known_bool = |expr| match known_scalar(self.module, self.expressions, expr) {
+            Some(Literal::Bool(value)) => Some(value),
+            _ => None,
+        };
+        match *expr {
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => known_bool(condition).map(|value| if value { accept } else { reject }),
+            // Expressions have no side effects, so the other operand of a
+            // short-circuiting operator can be dropped even if the operator
+            // would have evaluated it.
+            Expression::Binary {
+                op: op @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr),
+                left,
+                right,
+            } => {
+                // The value of `a && b` is `b` if `a` is `true`, and `a`
+                // otherwise. Similarly, `a || b` is `b` if `a` is `false`.
+                let identity = op == BinaryOperator::LogicalAnd;
+                if let Some(value) = known_bool(left) {
+                    Some(if value == identity { right } else { left })
+                } else {
+                    known_bool(right).map(|value| if value == identity { left } else { right })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// If `pointer` points to a scalar or vector in one of the
+    /// `constant_locals`, return an expression for its value.
+    ///
+    /// Here `pointer` is a handle in the `old` arena, and `new_pos` holds the
+    /// new handles of the expressions before it.
+    fn forward_load(
+        &mut self,
+        old: &Arena<Expression>,
+        new_pos: &HandleVec<Expression, Handle<Expression>>,
+        locals: &Arena<LocalVariable>,
+        constant_locals: &HandleSet<LocalVariable>,
+        mut pointer: Handle<Expression>,
+        span: Span,
+    ) -> Option<Handle<Expression>> {
+        // The indices applied to the variable, outermost first.
+        let mut indices = Vec::new();
+        let local = loop {
+            match old[pointer] {
+                Expression::LocalVariable(local) => break local,
+                Expression::AccessIndex { base, index } => {
+                    indices.push(index);
+                    pointer = base;
+                }
+                Expression::Access { base, index } => {
+                    let index = match known_scalar(self.module, self.expressions, new_pos[index])? {
+                        Literal::U32(index) => index,
+                        Literal::I32(index) => u32::try_from(index).ok()?,
+                        _ => return None,
+                    };
+                    indices.push(index);
+                    pointer = base;
+                }
+                _ => return None,
+            }
+        };
+        if !constant_locals.contains(local) {
+            return None;
+        }
+
+        // Don't make copies of larger values.
+        if !is_small(
+            &self.module.types,
+            locals[local].ty,
+            indices.iter().rev().copied(),
+        ) {
+            return None;
+        }
+
+        // The initializer may come after the load in the arena, in which
+        // case we haven't processed it yet.
+        let init = *new_pos.get(locals[local].init?)?;
+        if !self.tracker.is_const(init) {
+            return None;
+        }
+        let mut value = self.copy(init, span);
+        for &index in indices.iter().rev() {
+            value = self.append(Expression::AccessIndex { base: value, index }, span);
+        }
+        Some(value)
+    }
+}
+
+/// Return true if applying `indices` to a value of type `ty` produces a scalar
+/// or a vector.
+fn is_small(
+    types: &crate::UniqueArena<crate::Type>,
+    ty: Handle<crate::Type>,
+    indices: impl Iterator<Item = u32>,
+) -> bool {
+    let mut inner = &types[ty].inner;
+    for index in indices {
+        inner = match *inner {
+            // Any component of these is small.
+            TypeInner::Vector { .. } | TypeInner::Matrix { .. } => return true,
+            TypeInner::Array { base, .. } => &types[base].inner,
+            TypeInner::Struct { ref members, .. } => match members.get(index as usize) {
+                Some(member) => &types[member.ty].inner,
+                None => return false,
+            },
+            _ => return false,
+        };
+    }
+    matches!(*inner, TypeInner::Scalar(_) | TypeInner::Vector { .. })
+}
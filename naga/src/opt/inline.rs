@@ -0,0 +1,720 @@
+//! Function inlining.
+
+use super::visit;
+use crate::{
+    arena::{HandleSet, HandleVec},
+    Arena, Block, Expression, FastHashMap, Function, Handle, LocalVariable, Module, Range,
+    Statement,
+};
+use std::mem;
+
+/// Whether to inline calls to a function, regardless of its size.
+///
+/// These play the role of Rust's `#[inline(always)]` and `#[inline(never)]`
+/// attributes, which the IR has no equivalent of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inline {
+    /// Inline every call to the function, if it can be inlined at all.
+    Always,
+    /// Never inline calls to the function.
+    Never,
+}
+
+/// Options for [`inline_calls`].
+#[derive(Clone, Debug)]
+pub struct InlineOptions {
+    /// Inline calls to functions with at most this many statements and
+    /// expressions, counted after inlining the calls they make themselves.
+    pub max_size: usize,
+    /// Functions to inline, or not, regardless of `max_size`.
+    pub hints: FastHashMap<Handle<Function>, Inline>,
+}
+
+impl Default for InlineOptions {
+    fn default() -> Self {
+        InlineOptions {
+            max_size: 32,
+            hints: FastHashMap::default(),
+        }
+    }
+}
+
+/// Replace calls to functions in `module` with the bodies of the functions
+/// called.
+///
+/// Which calls are inlined is decided by `options`. Calls to a function can
+/// only be inlined if every [`Return`] statement in it comes at the end of
+/// the function, perhaps in the branches of [`If`] and [`Switch`]
+/// statements, but not in a [`Loop`]. Functions whose body ends with a
+/// [`Kill`] statement aren't inlined either.
+///
+/// Functions that were called before, and aren't called anymore since all
+/// calls to them have been inlined, are removed from the module. This lets
+/// back ends that can't write some argument types, like pointers in GLSL ES,
+/// handle modules that only pass such arguments to functions that can be
+/// inlined. As with any removal from an arena, handles to functions that
+/// come after a removed one change.
+///
+/// The module must have passed validation, and must be validated again
+/// afterwards. Inlining leaves a function's arguments in place of its
+/// parameters, and its return value in a new local variable when it has
+/// more than one [`Return`] statement; [`optimize`] can simplify the result.
+///
+/// # Panics
+///
+/// If `module` has not passed validation, this may panic.
+///
+/// [`Return`]: Statement::Return
+/// [`If`]: Statement::If
+/// [`Switch`]: Statement::Switch
+/// [`Loop`]: Statement::Loop
+/// [`Kill`]: Statement::Kill
+/// [`optimize`]: super::optimize
+pub fn inline_calls(module: &mut Module, options: &InlineOptions) {
+    let called_before = called_functions(module);
+
+    // A function can only call the functions that come before it in the
+    // arena, so by the time we get to a function, we're done with all the
+    // functions it might call.
+    let mut inlinable = HandleVec::with_capacity(module.functions.len());
+    let handles: Vec<_> = module.functions.iter().map(|(handle, _)| handle).collect();
+    for handle in handles {
+        let mut function = mem::take(&mut module.functions[handle]);
+        inline_into(&mut function, &module.functions, &inlinable);
+        let inline = can_inline(&function)
+            && match options.hints.get(&handle) {
+                Some(&Inline::Always) => true,
+                Some(&Inline::Never) => false,
+                None => size(&function) <= options.max_size,
+            };
+        log::trace!("function {:?} inlinable: {inline}", function.name);
+        module.functions[handle] = function;
+        inlinable.insert(handle, inline);
+    }
+    for entry_point in module.entry_points.iter_mut() {
+        inline_into(&mut entry_point.function, &module.functions, &inlinable);
+    }
+
+    let called_after = called_functions(module);
+    let mut unused = HandleSet::for_arena(&module.functions);
+    unused.insert_iter(
+        called_before
+            .iter()
+            .filter(|&handle| !called_after.contains(handle)),
+    );
+    if unused.iter().next().is_some() {
+        remove_functions(module, &unused);
+    }
+}
+
+/// A call to be inlined.
+struct CallSite {
+    function: Handle<Function>,
+    arguments: Vec<Handle<Expression>>,
+    result: Option<Handle<Expression>>,
+    /// Whether the call is in a loop, and may run more than once.
+    in_loop: bool,
+}
+
+/// The expressions and local variables added to a caller for a [`CallSite`].
+struct Inlined {
+    /// The new handles of the callee's expressions in the caller.
+    expressions: HandleVec<Expression, Handle<Expression>>,
+    /// The pointer to the local variable holding the result, and the
+    /// expression loading it, if the result doesn't come from a single
+    /// `Return` statement at the end of the callee.
+    result: Option<(Handle<Expression>, Handle<Expression>)>,
+    /// Stores that give the callee's local variables their initial values,
+    /// if the call may run more than once.
+    initializers: Block,
+}
+
+/// Inline the calls that `caller` makes to the functions marked in
+/// `inlinable`.
+fn inline_into(
+    caller: &mut Function,
+    functions: &Arena<Function>,
+    inlinable: &HandleVec<Function, bool>,
+) {
+    // Decide which calls to inline, in the order `replace_calls` visits them.
+    let mut sites = Vec::new();
+    let mut decisions = Vec::new();
+    replace_calls(&mut caller.body, false, &mut |stmt, in_loop| {
+        if let Statement::Call {
+            function,
+            ref arguments,
+            result,
+        } = *stmt
+        {
+            // We put the callee's expressions where the call's result is, so
+            // the arguments must come before it.
+            let inline = inlinable.get(function).copied().unwrap_or(false)
+                && result.map_or(true, |result| arguments.iter().all(|&arg| arg < result))
+                && (!in_loop || can_reinitialize(&functions[function]));
+            if inline {
+                sites.push(CallSite {
+                    function,
+                    arguments: arguments.clone(),
+                    result,
+                    in_loop,
+                });
+            }
+            decisions.push(inline);
+        }
+        None
+    });
+    if sites.is_empty() {
+        return;
+    }
+
+    // Rebuild the caller's expression arena, putting copies of each callee's
+    // expressions in place of the call's result, if it has one, and at the
+    // end otherwise. None of these are in the range of an `Emit` statement,
+    // so the ranges stay contiguous.
+    let by_result: FastHashMap<_, _> = sites
+        .iter()
+        .enumerate()
+        .filter_map(|(index, site)| Some((site.result?, index)))
+        .collect();
+    let caller_locals = caller.local_variables.len();
+    let mut inlined: Vec<Option<Inlined>> = sites.iter().map(|_| None).collect();
+    let mut new_pos = HandleVec::with_capacity(caller.expressions.len());
+    for (handle, mut expr, span) in mem::take(&mut caller.expressions).drain() {
+        let new_handle = match by_result.get(&handle) {
+            Some(&index) => {
+                let site = &sites[index];
+                let arguments: Vec<_> = site.arguments.iter().map(|&arg| new_pos[arg]).collect();
+                let (copy, value) = copy_callee(caller, functions, site, &arguments);
+                inlined[index] = Some(copy);
+                value.unwrap()
+            }
+            None => {
+                visit::expression(&mut expr, |operand| *operand = new_pos[*operand]);
+                caller.expressions.append(expr, span)
+            }
+        };
+        new_pos.insert(handle, new_handle);
+    }
+    for (site, inlined) in sites.iter().zip(inlined.iter_mut()) {
+        if site.result.is_none() {
+            let arguments: Vec<_> = site.arguments.iter().map(|&arg| new_pos[arg]).collect();
+            *inlined = Some(copy_callee(caller, functions, site, &arguments).0);
+        }
+    }
+
+    visit::block(&mut caller.body, &mut |expr| *expr = new_pos[*expr]);
+    visit::emits(&mut caller.body, &mut |range| {
+        range
+            .first_and_last()
+            .map(|(first, last)| Range::new_from_bounds(new_pos[first], new_pos[last]))
+            .into_iter()
+            .collect()
+    });
+    for (_, local) in caller.local_variables.iter_mut().take(caller_locals) {
+        if let Some(ref mut init) = local.init {
+            *init = new_pos[*init];
+        }
+    }
+    let named_expressions = mem::take(&mut caller.named_expressions);
+    caller.named_expressions = named_expressions
+        .into_iter()
+        .map(|(handle, name)| (new_pos[handle], name))
+        .collect();
+
+    // Replace the calls with the callees' bodies.
+    let mut decisions = decisions.into_iter();
+    let mut inlined = sites.iter().zip(inlined);
+    replace_calls(&mut caller.body, false, &mut |_, _| {
+        if !decisions.next().unwrap() {
+            return None;
+        }
+        let (site, inlined) = inlined.next().unwrap();
+        let inlined = inlined.unwrap();
+        let callee = &functions[site.function];
+        let map = &inlined.expressions;
+
+        // Keep the callee's names for expressions the caller hasn't named.
+        for (&handle, name) in callee.named_expressions.iter() {
+            caller
+                .named_expressions
+                .entry(map[handle])
+                .or_insert_with(|| name.clone());
+        }
+
+        let mut callee_body = callee.body.clone();
+        sink_returns(&mut callee_body);
+        visit::block(&mut callee_body, &mut |expr| *expr = map[*expr]);
+        visit::emits(&mut callee_body, &mut |range| {
+            range
+                .first_and_last()
+                .map(|(first, last)| Range::new_from_bounds(map[first], map[last]))
+                .into_iter()
+                .collect()
+        });
+        let mut body = inlined.initializers;
+        body.append(&mut callee_body);
+        match inlined.result {
+            Some((pointer, load)) => {
+                replace_returns(&mut body, Some(pointer), false);
+                body.push(
+                    Statement::Emit(Range::new_from_bounds(load, load)),
+                    functions.get_span(site.function),
+                );
+            }
+            None if callee.result.is_some() => {
+                // The value of the single `Return` at the end of the body
+                // took the place of the call's result.
+                body.cull(body.len() - 1..);
+            }
+            None => replace_returns(&mut body, None, false),
+        }
+        Some(body)
+    });
+}
+
+/// Append copies of the local variables and expressions of the function
+/// `site` calls to `caller`, with `arguments` in place of its parameters.
+///
+/// Return the new handles of the callee's expressions, and the expression
+/// holding its return value, if it has one.
+fn copy_callee(
+    caller: &mut Function,
+    functions: &Arena<Function>,
+    site: &CallSite,
+    arguments: &[Handle<Expression>],
+) -> (Inlined, Option<Handle<Expression>>) {
+    let span = functions.get_span(site.function);
+    let callee = &functions[site.function];
+
+    let mut local_map = HandleVec::with_capacity(callee.local_variables.len());
+    for (handle, local) in callee.local_variables.iter() {
+        let new_handle = caller.local_variables.append(
+            LocalVariable {
+                init: None,
+                ..local.clone()
+            },
+            callee.local_variables.get_span(handle),
+        );
+        local_map.insert(handle, new_handle);
+    }
+
+    let mut map = HandleVec::with_capacity(callee.expressions.len());
+    for (handle, expr) in callee.expressions.iter() {
+        let new_handle = match *expr {
+            Expression::FunctionArgument(index) => arguments[index as usize],
+            _ => {
+                let mut expr = expr.clone();
+                visit::expression(&mut expr, |operand| *operand = map[*operand]);
+                if let Expression::LocalVariable(ref mut local) = expr {
+                    *local = local_map[*local];
+                }
+                caller
+                    .expressions
+                    .append(expr, callee.expressions.get_span(handle))
+            }
+        };
+        map.insert(handle, new_handle);
+    }
+
+    // The caller's local variables are only initialized once, so if the call
+    // may run more than once, the inlined body needs to do that itself.
+    let mut initializers = Block::new();
+    for (handle, local) in callee.local_variables.iter() {
+        let new_handle = local_map[handle];
+        if !site.in_loop {
+            caller.local_variables[new_handle].init = local.init.map(|init| map[init]);
+            continue;
+        }
+        let span = callee.local_variables.get_span(handle);
+        let value = match local.init {
+            Some(init) => map[init],
+            None => caller
+                .expressions
+                .append(Expression::ZeroValue(local.ty), span),
+        };
+        let pointer = caller
+            .expressions
+            .append(Expression::LocalVariable(new_handle), span);
+        initializers.push(Statement::Store { pointer, value }, span);
+    }
+
+    let Some(ref function_result) = callee.result else {
+        let inlined = Inlined {
+            expressions: map,
+            result: None,
+            initializers,
+        };
+        return (inlined, None);
+    };
+    let single_return = match callee.body.split_last() {
+        Some((&Statement::Return { value: Some(value) }, rest)) if !returns(rest) => Some(value),
+        _ => None,
+    };
+    if let Some(value) = single_return {
+        let value = map[value];
+        let inlined = Inlined {
+            expressions: map,
+            result: None,
+            initializers,
+        };
+        return (inlined, Some(value));
+    }
+
+    let local = caller.local_variables.append(
+        LocalVariable {
+            name: callee.name.as_ref().map(|name| format!("{name}_result")),
+            ty: function_result.ty,
+            init: None,
+        },
+        span,
+    );
+    let pointer = caller
+        .expressions
+        .append(Expression::LocalVariable(local), span);
+    let load = caller
+        .expressions
+        .append(Expression::Load { pointer }, span);
+    let inlined = Inlined {
+        expressions: map,
+        result: Some((pointer, load)),
+        initializers,
+    };
+    (inlined, Some(load))
+}
+
+/// Replace every [`Call`] statement in `block` with the statements `f`
+/// returns for it, if any.
+///
+/// The second argument to `f` says whether the call is in a [`Loop`]. The
+/// statements `f` returns are not visited.
+///
+/// [`Call`]: Statement::Call
+/// [`Loop`]: Statement::Loop
+fn replace_calls(
+    block: &mut Block,
+    in_loop: bool,
+    f: &mut impl FnMut(&Statement, bool) -> Option<Block>,
+) {
+    let original = mem::replace(block, Block::with_capacity(block.len()));
+    for (mut stmt, span) in original.span_into_iter() {
+        match stmt {
+            Statement::Call { .. } => {
+                if let Some(mut body) = f(&stmt, in_loop) {
+                    block.append(&mut body);
+                    continue;
+                }
+            }
+            Statement::Block(ref mut body) => replace_calls(body, in_loop, f),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                replace_calls(accept, in_loop, f);
+                replace_calls(reject, in_loop, f);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    replace_calls(&mut case.body, in_loop, f);
+                }
+            }
+            Statement::Loop {
+                ref mut body,
+                ref mut continuing,
+                ..
+            } => {
+                replace_calls(body, true, f);
+                replace_calls(continuing, true, f);
+            }
+            _ => {}
+        }
+        block.push(stmt, span);
+    }
+}
+
+/// Replace the [`Return`] statements in `block`, which must all be at the
+/// end of the function, with stores of their values to `pointer`.
+///
+/// If `in_switch` is true, `block` is part of a [`Switch`] statement, which
+/// we need to leave in place of returning.
+///
+/// [`Return`]: Statement::Return
+/// [`Switch`]: Statement::Switch
+fn replace_returns(block: &mut Block, pointer: Option<Handle<Expression>>, in_switch: bool) {
+    let original = mem::replace(block, Block::with_capacity(block.len()));
+    for (mut stmt, span) in original.span_into_iter() {
+        match stmt {
+            Statement::Return { value } => {
+                if let (Some(pointer), Some(value)) = (pointer, value) {
+                    block.push(Statement::Store { pointer, value }, span);
+                }
+                if in_switch {
+                    block.push(Statement::Break, span);
+                }
+                continue;
+            }
+            Statement::Block(ref mut body) => replace_returns(body, pointer, in_switch),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                replace_returns(accept, pointer, in_switch);
+                replace_returns(reject, pointer, in_switch);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    replace_returns(&mut case.body, pointer, true);
+                }
+            }
+            // Loops contain no `Return` statements.
+            _ => {}
+        }
+        block.push(stmt, span);
+    }
+}
+
+/// Return true if calls to `function` can be inlined.
+fn can_inline(function: &Function) -> bool {
+    let mut body = function.body.clone();
+    sink_returns(&mut body);
+    returns_at_end(&body, true) && !kills(&body)
+}
+
+/// Return true if the initializers of `function`'s local variables can be
+/// used as the values of [`Store`] statements anywhere in its body.
+///
+/// Expressions that need to be emitted can't, so we don't inline calls
+/// to such functions in loops.
+///
+/// [`Store`]: Statement::Store
+fn can_reinitialize(function: &Function) -> bool {
+    function.local_variables.iter().all(|(_, local)| {
+        local
+            .init
+            .map_or(true, |init| function.expressions[init].needs_pre_emit())
+    })
+}
+
+/// Move the statements following an [`If`] statement with a branch that
+/// ends in a [`Return`] into its other branch.
+///
+/// This turns early returns in `block`, which must come at the end of the
+/// function it belongs to, into returns at the end of the function.
+///
+/// [`If`]: Statement::If
+/// [`Return`]: Statement::Return
+fn sink_returns(block: &mut Block) {
+    let returns = |block: &Block| matches!(block.last(), Some(&Statement::Return { .. }));
+    let original = mem::replace(block, Block::with_capacity(block.len()));
+    let mut statements = original.span_into_iter();
+    while let Some((mut stmt, span)) = statements.next() {
+        if let Statement::If {
+            ref mut accept,
+            ref mut reject,
+            ..
+        } = stmt
+        {
+            let (accept_returns, reject_returns) = (returns(accept), returns(reject));
+            if accept_returns || reject_returns {
+                let rest = statements.by_ref();
+                // If both branches return, the rest is unreachable.
+                if !accept_returns {
+                    rest.for_each(|(stmt, span)| accept.push(stmt, span));
+                } else if !reject_returns {
+                    rest.for_each(|(stmt, span)| reject.push(stmt, span));
+                }
+            }
+            sink_returns(accept);
+            sink_returns(reject);
+        }
+        block.push(stmt, span);
+    }
+}
+
+/// Return true if all [`Return`] statements in `statements` come at the end
+/// of the function they belong to.
+///
+/// If `at_end` is false, `statements` are followed by others in the function.
+///
+/// [`Return`]: Statement::Return
+fn returns_at_end(statements: &[Statement], at_end: bool) -> bool {
+    let last = statements.len().saturating_sub(1);
+    statements.iter().enumerate().all(|(index, stmt)| {
+        let at_end = at_end && index == last;
+        match *stmt {
+            Statement::Return { .. } => at_end,
+            Statement::Block(ref body) => returns_at_end(body, at_end),
+            Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => returns_at_end(accept, at_end) && returns_at_end(reject, at_end),
+            // Leaving a `Switch` takes us to the end too.
+            Statement::Switch { ref cases, .. } => {
+                cases.iter().all(|case| returns_at_end(&case.body, at_end))
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => returns_at_end(body, false) && returns_at_end(continuing, false),
+            _ => true,
+        }
+    })
+}
+
+/// Return true if `statements` contain a [`Return`] statement.
+///
+/// [`Return`]: Statement::Return
+fn returns(statements: &[Statement]) -> bool {
+    statements.iter().any(|stmt| match *stmt {
+        Statement::Return { .. } => true,
+        Statement::Block(ref body) => returns(body),
+        Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => returns(accept) || returns(reject),
+        Statement::Switch { ref cases, .. } => cases.iter().any(|case| returns(&case.body)),
+        Statement::Loop {
+            ref body,
+            ref continuing,
+            ..
+        } => returns(body) || returns(continuing),
+        _ => false,
+    })
+}
+
+/// Return true if `statements` end with a [`Kill`] statement, which would
+/// make the statements following them in a caller unreachable.
+///
+/// [`Kill`]: Statement::Kill
+fn kills(statements: &[Statement]) -> bool {
+    statements.iter().any(|stmt| match *stmt {
+        Statement::Kill => true,
+        Statement::Block(ref body) => kills(body),
+        _ => false,
+    })
+}
+
+/// Return the number of statements and expressions in `function`.
+fn size(function: &Function) -> usize {
+    fn statements(block: &[Statement]) -> usize {
+        block
+            .iter()
+            .map(|stmt| {
+                1 + match *stmt {
+                    Statement::Block(ref body) => statements(body),
+                    Statement::If {
+                        ref accept,
+                        ref reject,
+                        ..
+                    } => statements(accept) + statements(reject),
+                    Statement::Switch { ref cases, .. } => {
+                        cases.iter().map(|case| statements(&case.body)).sum()
+                    }
+                    Statement::Loop {
+                        ref body,
+                        ref continuing,
+                        ..
+                    } => statements(body) + statements(continuing),
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+    function.expressions.len() + statements(&function.body)
+}
+
+/// Call `f` on the function handle of every [`Call`] statement in `block`.
+///
+/// [`Call`]: Statement::Call
+fn calls(block: &mut Block, f: &mut impl FnMut(&mut Handle<Function>)) {
+    for stmt in block.iter_mut() {
+        match *stmt {
+            Statement::Call {
+                ref mut function, ..
+            } => f(function),
+            Statement::Block(ref mut body) => calls(body, f),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                calls(accept, f);
+                calls(reject, f);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    calls(&mut case.body, f);
+                }
+            }
+            Statement::Loop {
+                ref mut body,
+                ref mut continuing,
+                ..
+            } => {
+                calls(body, f);
+                calls(continuing, f);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Return the set of functions that `module`'s functions and entry points
+/// call.
+fn called_functions(module: &mut Module) -> HandleSet<Function> {
+    let mut called = HandleSet::for_arena(&module.functions);
+    let bodies = module
+        .functions
+        .iter_mut()
+        .map(|(_, function)| &mut function.body)
+        .chain(
+            module
+                .entry_points
+                .iter_mut()
+                .map(|ep| &mut ep.function.body),
+        );
+    for body in bodies {
+        calls(body, &mut |function| {
+            called.insert(*function);
+        });
+    }
+    called
+}
+
+/// Remove the `unused` functions from `module`, which must not call them.
+fn remove_functions(module: &mut Module, unused: &HandleSet<Function>) {
+    let mut function_map = HandleVec::with_capacity(module.functions.len());
+    for (handle, function, span) in mem::take(&mut module.functions).drain() {
+        let new_handle =
+            (!unused.contains(handle)).then(|| module.functions.append(function, span));
+        function_map.insert(handle, new_handle);
+    }
+
+    let functions = module
+        .functions
+        .iter_mut()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter_mut().map(|ep| &mut ep.function));
+    for function in functions {
+        calls(&mut function.body, &mut |handle| {
+            *handle = function_map[*handle].unwrap();
+        });
+        for (_, expr) in function.expressions.iter_mut() {
+            if let Expression::CallResult(ref mut handle) = *expr {
+                *handle = function_map[*handle].unwrap();
+            }
+        }
+    }
+
+    if let Some(ref mut doc_comments) = module.doc_comments {
+        let comments = mem::take(&mut doc_comments.functions);
+        doc_comments.functions = comments
+            .into_iter()
+            .filter_map(|(handle, comment)| Some((function_map[handle]?, comment)))
+            .collect();
+    }
+}
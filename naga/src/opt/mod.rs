@@ -0,0 +1,143 @@
+/*!
+Optimizations of function bodies.
+
+[`optimize`] simplifies the [`Function`]s and entry points of a [`Module`],
+removing code that has no effect on their results. It is most useful after
+`back::pipeline_constants::process_overrides` has replaced
+pipeline-overridable constants with their values, since shaders specialized
+that way tend to contain code that can never run.
+
+[`inline_calls`] replaces calls to functions with the functions' bodies, for
+back ends and drivers that handle calls poorly. Running [`optimize`]
+afterwards can take advantage of the arguments passed at each call site.
+
+The module passed to either must have passed validation, and must be
+validated again before being handed to a back end, since the [`ModuleInfo`]
+for the original module no longer applies. The optimizations only touch
+function bodies, so types and constants that are no longer used remain in the
+module; `compact::compact` removes those.
+
+[`ModuleInfo`]: crate::valid::ModuleInfo
+*/
+
+mod branch;
+mod dce;
+mod fold;
+mod inline;
+mod visit;
+
+pub use inline::{inline_calls, Inline, InlineOptions};
+
+use crate::{Expression, Function, Handle, Literal, LocalVariable, Module};
+use std::mem;
+
+/// Simplify the bodies of all functions and entry points in `module`.
+///
+/// In each function, this:
+///
+/// - evaluates expressions whose operands are known, following the rules
+///   of [`ConstantEvaluator`], and simplifies [`Select`] expressions and
+///   logical operators with a known operand;
+///
+/// - replaces loads from local variables that are never written, other than
+///   by their initializer, with the initializer's value;
+///
+/// - replaces [`If`] and [`Switch`] statements with known conditions or
+///   selectors by the statements that they would run;
+///
+/// - removes statements that can never run, such as those following a
+///   [`Return`] in the same block, and statements with nothing in them;
+///
+/// - removes stores to local variables that are never read, expressions
+///   whose values are never used, and local variables that are no longer
+///   referred to.
+///
+/// Expressions are pure, so an expression whose value is never used is
+/// removed even if it was given a name by a `let` declaration.
+///
+/// Constant evaluation follows the WGSL rules. If evaluating an expression
+/// fails, perhaps because it overflows, it is left for the shader to evaluate
+/// at run time.
+///
+/// # Panics
+///
+/// If `module` has not passed validation, this may panic.
+///
+/// [`ConstantEvaluator`]: crate::proc::ConstantEvaluator
+/// [`Select`]: Expression::Select
+/// [`If`]: crate::Statement::If
+/// [`Switch`]: crate::Statement::Switch
+/// [`Return`]: crate::Statement::Return
+pub fn optimize(module: &mut Module) {
+    // The constant evaluator needs to borrow the module mutably, so take the
+    // functions out of it while we work on them.
+    let mut functions = mem::take(&mut module.functions);
+    for (_, function) in functions.iter_mut() {
+        log::trace!("optimizing function {:?}", function.name);
+        optimize_function(module, function);
+    }
+    module.functions = functions;
+
+    let mut entry_points = mem::take(&mut module.entry_points);
+    for entry_point in entry_points.iter_mut() {
+        log::trace!("optimizing entry point {:?}", entry_point.name);
+        optimize_function(module, &mut entry_point.function);
+    }
+    module.entry_points = entry_points;
+}
+
+fn optimize_function(module: &mut Module, function: &mut Function) {
+    // Folding branches and removing stores can make more values known, so
+    // repeat until neither finds anything left to do. Both only ever remove
+    // statements, so this terminates.
+    loop {
+        fold::fold_expressions(module, function);
+        let mut changed = branch::fold_branches(module, function);
+        changed |= dce::remove_dead_stores(function);
+        if !changed {
+            break;
+        }
+    }
+    dce::remove_unused(function);
+}
+
+/// Return the value of `expr` in `function`, if it is a known scalar.
+///
+/// This sees through [`Constant`] expressions to their values in the module's
+/// global expression arena.
+///
+/// [`Constant`]: Expression::Constant
+fn known_scalar(
+    module: &Module,
+    expressions: &crate::Arena<Expression>,
+    expr: Handle<Expression>,
+) -> Option<Literal> {
+    let (expressions, expr) = match expressions[expr] {
+        Expression::Constant(constant) => {
+            (&module.global_expressions, module.constants[constant].init)
+        }
+        _ => (expressions, expr),
+    };
+    match expressions[expr] {
+        Expression::Literal(literal) => Some(literal),
+        Expression::ZeroValue(ty) => match module.types[ty].inner {
+            crate::TypeInner::Scalar(scalar) => Literal::zero(scalar),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Return the local variable that the pointer `expr` refers to, or a part of.
+fn local_root(
+    expressions: &crate::Arena<Expression>,
+    mut expr: Handle<Expression>,
+) -> Option<Handle<LocalVariable>> {
+    loop {
+        match expressions[expr] {
+            Expression::LocalVariable(local) => return Some(local),
+            Expression::Access { base, .. } | Expression::AccessIndex { base, .. } => expr = base,
+            _ => return None,
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Visiting the expression handles used by expressions and statements.
+
+use crate::{Block, Expression, Handle, Range, Statement};
+
+/// Call `f` on every expression handle that `expr` uses as an operand.
+pub(super) fn expression(expr: &mut Expression, mut f: impl FnMut(&mut Handle<Expression>)) {
+    match *expr {
+        Expression::Compose {
+            ref mut components,
+            ty: _,
+        } => {
+            for component in components.iter_mut() {
+                f(component);
+            }
+        }
+        Expression::Access {
+            ref mut base,
+            ref mut index,
+        } => {
+            f(base);
+            f(index);
+        }
+        Expression::AccessIndex {
+            ref mut base,
+            index: _,
+        } => f(base),
+        Expression::Splat {
+            ref mut value,
+            size: _,
+        } => f(value),
+        Expression::Swizzle {
+            ref mut vector,
+            size: _,
+            pattern: _,
+        } => f(vector),
+        Expression::Load { ref mut pointer } => f(pointer),
+        Expression::ImageSample {
+            ref mut image,
+            ref mut sampler,
+            ref mut coordinate,
+            ref mut array_index,
+            // This refers to the global expression arena.
+            offset: _,
+            ref mut level,
+            ref mut depth_ref,
+            gather: _,
+        } => {
+            f(image);
+            f(sampler);
+            f(coordinate);
+            if let Some(ref mut expr) = *array_index {
+                f(expr);
+            }
+            match *level {
+                crate::SampleLevel::Auto | crate::SampleLevel::Zero => {}
+                crate::SampleLevel::Exact(ref mut expr)
+                | crate::SampleLevel::Bias(ref mut expr) => f(expr),
+                crate::SampleLevel::Gradient {
+                    ref mut x,
+                    ref mut y,
+                } => {
+                    f(x);
+                    f(y);
+                }
+            }
+            if let Some(ref mut expr) = *depth_ref {
+                f(expr);
+            }
+        }
+        Expression::ImageLoad {
+            ref mut image,
+            ref mut coordinate,
+            ref mut array_index,
+            ref mut sample,
+            ref mut level,
+        } => {
+            f(image);
+            f(coordinate);
+            if let Some(ref mut expr) = *array_index {
+                f(expr);
+            }
+            if let Some(ref mut expr) = *sample {
+                f(expr);
+            }
+            if let Some(ref mut expr) = *level {
+                f(expr);
+            }
+        }
+        Expression::ImageQuery {
+            ref mut image,
+            ref mut query,
+        } => {
+            f(image);
+            if let crate::ImageQuery::Size {
+                level: Some(ref mut level),
+            } = *query
+            {
+                f(level);
+            }
+        }
+        Expression::Unary {
+            ref mut expr,
+            op: _,
+        } => f(expr),
+        Expression::Binary {
+            ref mut left,
+            ref mut right,
+            op: _,
+        } => {
+            f(left);
+            f(right);
+        }
+        Expression::Select {
+            ref mut condition,
+            ref mut accept,
+            ref mut reject,
+        } => {
+            f(condition);
+            f(accept);
+            f(reject);
+        }
+        Expression::Derivative {
+            ref mut expr,
+            axis: _,
+            ctrl: _,
+        } => f(expr),
+        Expression::Relational {
+            ref mut argument,
+            fun: _,
+        } => f(argument),
+        Expression::Math {
+            ref mut arg,
+            ref mut arg1,
+            ref mut arg2,
+            ref mut arg3,
+            fun: _,
+        } => {
+            f(arg);
+            if let Some(ref mut expr) = *arg1 {
+                f(expr);
+            }
+            if let Some(ref mut expr) = *arg2 {
+                f(expr);
+            }
+            if let Some(ref mut expr) = *arg3 {
+                f(expr);
+            }
+        }
+        Expression::As {
+            ref mut expr,
+            kind: _,
+            convert: _,
+        } => f(expr),
+        Expression::ArrayLength(ref mut expr) => f(expr),
+        Expression::RayQueryGetIntersection {
+            ref mut query,
+            committed: _,
+        } => f(query),
+        Expression::Literal(_)
+        | Expression::Constant(_)
+        | Expression::Override(_)
+        | Expression::ZeroValue(_)
+        | Expression::FunctionArgument(_)
+        | Expression::GlobalVariable(_)
+        | Expression::LocalVariable(_)
+        | Expression::CallResult(_)
+        | Expression::AtomicResult { .. }
+        | Expression::WorkGroupUniformLoadResult { .. }
+        | Expression::RayQueryProceedResult
+        | Expression::SubgroupBallotResult
+        | Expression::SubgroupOperationResult { .. } => {}
+    }
+}
+
+/// Call `f` on every expression handle that the statements of `block` use,
+/// including the results they produce.
+///
+/// The ranges of [`Emit`] statements are not visited.
+///
+/// [`Emit`]: Statement::Emit
+pub(super) fn block(block: &mut Block, f: &mut impl FnMut(&mut Handle<Expression>)) {
+    for statement in block.iter_mut() {
+        self::statement(statement, f);
+    }
+}
+
+/// Call `f` on every expression handle that `stmt` uses, including those used
+/// by the statements nested within it.
+///
+/// The ranges of [`Emit`] statements are not visited.
+///
+/// [`Emit`]: Statement::Emit
+pub(super) fn statement(stmt: &mut Statement, f: &mut impl FnMut(&mut Handle<Expression>)) {
+    match *stmt {
+        Statement::Emit(_)
+        | Statement::Break
+        | Statement::Continue
+        | Statement::Kill
+        | Statement::Barrier(_)
+        | Statement::DiagnosticFilter(_) => {}
+        Statement::Block(ref mut body) => block(body, f),
+        Statement::If {
+            ref mut condition,
+            ref mut accept,
+            ref mut reject,
+        } => {
+            f(condition);
+            block(accept, f);
+            block(reject, f);
+        }
+        Statement::Switch {
+            ref mut selector,
+            ref mut cases,
+        } => {
+            f(selector);
+            for case in cases.iter_mut() {
+                block(&mut case.body, f);
+            }
+        }
+        Statement::Loop {
+            ref mut body,
+            ref mut continuing,
+            ref mut break_if,
+        } => {
+            block(body, f);
+            block(continuing, f);
+            if let Some(ref mut expr) = *break_if {
+                f(expr);
+            }
+        }
+        Statement::Return { ref mut value } => {
+            if let Some(ref mut expr) = *value {
+                f(expr);
+            }
+        }
+        Statement::Store {
+            ref mut pointer,
+            ref mut value,
+        } => {
+            f(pointer);
+            f(value);
+        }
+        Statement::ImageStore {
+            ref mut image,
+            ref mut coordinate,
+            ref mut array_index,
+            ref mut value,
+        } => {
+            f(image);
+            f(coordinate);
+            if let Some(ref mut expr) = *array_index {
+                f(expr);
+            }
+            f(value);
+        }
+        Statement::Atomic {
+            ref mut pointer,
+            ref mut fun,
+            ref mut value,
+            ref mut result,
+        } => {
+            f(pointer);
+            if let crate::AtomicFunction::Exchange {
+                compare: Some(ref mut compare),
+            } = *fun
+            {
+                f(compare);
+            }
+            f(value);
+            if let Some(ref mut expr) = *result {
+                f(expr);
+            }
+        }
+        Statement::WorkGroupUniformLoad {
+            ref mut pointer,
+            ref mut result,
+        } => {
+            f(pointer);
+            f(result);
+        }
+        Statement::Call {
+            function: _,
+            ref mut arguments,
+            ref mut result,
+        } => {
+            for argument in arguments.iter_mut() {
+                f(argument);
+            }
+            if let Some(ref mut expr) = *result {
+                f(expr);
+            }
+        }
+        Statement::RayQuery {
+            ref mut query,
+            ref mut fun,
+        } => {
+            f(query);
+            match *fun {
+                crate::RayQueryFunction::Initialize {
+                    ref mut acceleration_structure,
+                    ref mut descriptor,
+                } => {
+                    f(acceleration_structure);
+                    f(descriptor);
+                }
+                crate::RayQueryFunction::Proceed { ref mut result } => f(result),
+                crate::RayQueryFunction::Terminate => {}
+            }
+        }
+        Statement::SubgroupBallot {
+            ref mut result,
+            ref mut predicate,
+        } => {
+            if let Some(ref mut expr) = *predicate {
+                f(expr);
+            }
+            f(result);
+        }
+        Statement::SubgroupGather {
+            ref mut mode,
+            ref mut argument,
+            ref mut result,
+        } => {
+            match *mode {
+                crate::GatherMode::BroadcastFirst => {}
+                crate::GatherMode::Broadcast(ref mut index)
+                | crate::GatherMode::Shuffle(ref mut index)
+                | crate::GatherMode::ShuffleDown(ref mut index)
+                | crate::GatherMode::ShuffleUp(ref mut index)
+                | crate::GatherMode::ShuffleXor(ref mut index) => f(index),
+            }
+            f(argument);
+            f(result);
+        }
+        Statement::SubgroupCollectiveOperation {
+            op: _,
+            collective_op: _,
+            ref mut argument,
+            ref mut result,
+        } => {
+            f(argument);
+            f(result);
+        }
+        Statement::DebugPrintf {
+            format: _,
+            ref mut arguments,
+        } => {
+            for argument in arguments.iter_mut() {
+                f(argument);
+            }
+        }
+    }
+}
+
+/// Replace the range of every [`Emit`] statement in `block` with the ranges
+/// `f` returns for it, dropping the statement if there are none.
+///
+/// [`Emit`]: Statement::Emit
+pub(super) fn emits(
+    block: &mut Block,
+    f: &mut impl FnMut(Range<Expression>) -> Vec<Range<Expression>>,
+) {
+    let original = std::mem::replace(block, Block::with_capacity(block.len()));
+    for (mut stmt, span) in original.span_into_iter() {
+        match stmt {
+            Statement::Emit(range) => {
+                for range in f(range) {
+                    block.push(Statement::Emit(range), span);
+                }
+                continue;
+            }
+            Statement::Block(ref mut body) => emits(body, f),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                emits(accept, f);
+                emits(reject, f);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    emits(&mut case.body, f);
+                }
+            }
+            Statement::Loop {
+                ref mut body,
+                ref mut continuing,
+                ..
+            } => {
+                emits(body, f);
+                emits(continuing, f);
+            }
+            _ => {}
+        }
+        block.push(stmt, span);
+    }
+}
@@ -1,6 +1,7 @@
 use std::iter;
 
 use arrayvec::ArrayVec;
+use half::f16;
 
 use crate::{
     arena::{Arena, Handle, HandleVec, UniqueArena},
@@ -389,14 +390,18 @@ impl ExpressionKindTracker {
         let mut tracker = Self {
             inner: HandleVec::with_capacity(arena.len()),
         };
-        for (handle, expr) in arena.iter() {
-            tracker
-                .inner
-                .insert(handle, tracker.type_of_with_expr(expr));
-        }
+        tracker.extend_from_arena(arena);
         tracker
     }
 
+    /// Track the expressions appended to `arena` since this tracker last
+    /// saw it.
+    pub(crate) fn extend_from_arena(&mut self, arena: &Arena<Expression>) {
+        for (handle, expr) in arena.iter().skip(self.inner.len()) {
+            self.inner.insert(handle, self.type_of_with_expr(expr));
+        }
+    }
+
     fn type_of_with_expr(&self, expr: &Expression) -> ExpressionKind {
         match *expr {
             Expression::Literal(_) | Expression::ZeroValue(_) | Expression::Constant(_) => {
@@ -497,6 +502,8 @@ pub enum ConstantEvaluatorError {
     InvalidBinaryOpArgs,
     #[error("Cannot apply math function to type")]
     InvalidMathArg,
+    #[error("Cannot select between the arguments with the condition")]
+    InvalidSelectArgs,
     #[error("{0:?} built-in function expects {1:?} arguments but {2:?} were supplied")]
     InvalidMathArgCount(crate::MathFunction, usize, usize),
     #[error("value of `low` is greater than `high` for clamp built-in function")]
@@ -860,6 +867,19 @@ impl<'a> ConstantEvaluator<'a> {
                     )),
                 }
             }
+            // Only fold `select` in global initializers, where there is no
+            // run-time expression to fall back on.
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } if self.is_global_arena() => {
+                let condition = self.check_and_get(condition)?;
+                let accept = self.check_and_get(accept)?;
+                let reject = self.check_and_get(reject)?;
+
+                self.select(condition, accept, reject, span)
+            }
             Expression::Select { .. } => Err(ConstantEvaluatorError::NotImplemented(
                 "select built-in function".into(),
             )),
@@ -1027,6 +1047,27 @@ impl<'a> ConstantEvaluator<'a> {
             ));
         }
 
+        // `f16` math is evaluated in `f32` and rounded back to `f16`. Every
+        // `f16` value is exactly representable as an `f32`, and `f32` carries
+        // enough extra precision that the final rounding gives the same result
+        // as computing in `f16` directly would.
+        if self.has_f16_components(arg)? {
+            let arg = self.cast(arg, crate::Scalar::F32, span)?;
+            let mut rest = [arg1, arg2, arg3];
+            for other in rest.iter_mut().flatten() {
+                if self.has_f16_components(*other)? {
+                    *other = self.cast(*other, crate::Scalar::F32, span)?;
+                }
+            }
+            let [arg1, arg2, arg3] = rest;
+            let result = self.math(arg, arg1, arg2, arg3, fun, span)?;
+            let result_scalar = self.resolve_type(result)?.inner_with(self.types).scalar();
+            return match result_scalar {
+                Some(crate::Scalar::F32) => self.cast(result, crate::Scalar::F16, span),
+                _ => Ok(result),
+            };
+        }
+
         // NOTE: We try to match the declaration order of `MathFunction` here.
         match fun {
             // comparison
@@ -1251,7 +1292,9 @@ impl<'a> ConstantEvaluator<'a> {
                             let expr = Expression::Literal(Literal::U32(len.get()));
                             self.register_evaluated_expr(expr, span)
                         }
-                        ArraySize::Dynamic => Err(ConstantEvaluatorError::ArrayLengthDynamic),
+                        ArraySize::Pending(_) | ArraySize::Dynamic => {
+                            Err(ConstantEvaluatorError::ArrayLengthDynamic)
+                        }
                     },
                     _ => Err(ConstantEvaluatorError::InvalidArrayLengthArg),
                 }
@@ -1469,6 +1512,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v,
                         Literal::U32(v) => v as i32,
                         Literal::F32(v) => v as i32,
+                        Literal::F16(v) => v.to_f32() as i32,
                         Literal::Bool(v) => v as i32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1480,6 +1524,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as u32,
                         Literal::U32(v) => v,
                         Literal::F32(v) => v as u32,
+                        Literal::F16(v) => v.to_f32() as u32,
                         Literal::Bool(v) => v as u32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1491,6 +1536,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as i64,
                         Literal::U32(v) => v as i64,
                         Literal::F32(v) => v as i64,
+                        Literal::F16(v) => v.to_f32() as i64,
                         Literal::Bool(v) => v as i64,
                         Literal::F64(v) => v as i64,
                         Literal::I64(v) => v,
@@ -1502,6 +1548,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as u64,
                         Literal::U32(v) => v as u64,
                         Literal::F32(v) => v as u64,
+                        Literal::F16(v) => v.to_f32() as u64,
                         Literal::Bool(v) => v as u64,
                         Literal::F64(v) => v as u64,
                         Literal::I64(v) => v as u64,
@@ -1509,10 +1556,23 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::AbstractInt(v) => u64::try_from_abstract(v)?,
                         Literal::AbstractFloat(v) => u64::try_from_abstract(v)?,
                     }),
+                    Sc::F16 => Literal::F16(match literal {
+                        Literal::I32(v) => f16::from_f64(v as f64),
+                        Literal::U32(v) => f16::from_f64(v as f64),
+                        Literal::F32(v) => f16::from_f32(v),
+                        Literal::F16(v) => v,
+                        Literal::Bool(v) => f16::from_f32(v as u32 as f32),
+                        Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
+                            return make_error();
+                        }
+                        Literal::AbstractInt(v) => f16::try_from_abstract(v)?,
+                        Literal::AbstractFloat(v) => f16::try_from_abstract(v)?,
+                    }),
                     Sc::F32 => Literal::F32(match literal {
                         Literal::I32(v) => v as f32,
                         Literal::U32(v) => v as f32,
                         Literal::F32(v) => v,
+                        Literal::F16(v) => v.to_f32(),
                         Literal::Bool(v) => v as u32 as f32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1524,6 +1584,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as f64,
                         Literal::U32(v) => v as f64,
                         Literal::F32(v) => v as f64,
+                        Literal::F16(v) => v.to_f64(),
                         Literal::F64(v) => v,
                         Literal::Bool(v) => v as u32 as f64,
                         Literal::I64(_) | Literal::U64(_) => return make_error(),
@@ -1534,6 +1595,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v != 0,
                         Literal::U32(v) => v != 0,
                         Literal::F32(v) => v != 0.0,
+                        Literal::F16(v) => v != f16::ZERO,
                         Literal::Bool(v) => v,
                         Literal::F64(_)
                         | Literal::I64(_)
@@ -1683,6 +1745,7 @@ impl<'a> ConstantEvaluator<'a> {
                 UnaryOperator::Negate => match value {
                     Literal::I32(v) => Literal::I32(v.wrapping_neg()),
                     Literal::F32(v) => Literal::F32(-v),
+                    Literal::F16(v) => Literal::F16(-v),
                     Literal::AbstractInt(v) => Literal::AbstractInt(v.wrapping_neg()),
                     Literal::AbstractFloat(v) => Literal::AbstractFloat(-v),
                     _ => return Err(ConstantEvaluatorError::InvalidUnaryOpArg),
@@ -1720,6 +1783,61 @@ impl<'a> ConstantEvaluator<'a> {
         self.register_evaluated_expr(expr, span)
     }
 
+    /// Evaluate a [`Select`] expression.
+    ///
+    /// A scalar `condition` picks one of `accept` and `reject` whole; a vector
+    /// `condition` picks each component separately. Either way, `accept` and
+    /// `reject` must have the same type.
+    ///
+    /// [`Select`]: Expression::Select
+    fn select(
+        &mut self,
+        condition: Handle<Expression>,
+        accept: Handle<Expression>,
+        reject: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>, ConstantEvaluatorError> {
+        let condition = self.eval_zero_value_and_splat(condition, span)?;
+
+        let conditions = match self.expressions[condition] {
+            Expression::Literal(Literal::Bool(value)) => {
+                let accept_ty = self.resolve_type(accept)?;
+                let reject_ty = self.resolve_type(reject)?;
+                if accept_ty.inner_with(self.types) != reject_ty.inner_with(self.types) {
+                    return Err(ConstantEvaluatorError::InvalidSelectArgs);
+                }
+                return Ok(if value { accept } else { reject });
+            }
+            Expression::Compose { ref components, .. } => components.clone(),
+            _ => return Err(ConstantEvaluatorError::InvalidSelectArgs),
+        };
+
+        let accept = self.eval_zero_value_and_splat(accept, span)?;
+        let reject = self.eval_zero_value_and_splat(reject, span)?;
+        let (ty, accepts, rejects) = match (&self.expressions[accept], &self.expressions[reject]) {
+            (
+                &Expression::Compose {
+                    ty,
+                    components: ref accepts,
+                },
+                &Expression::Compose {
+                    ty: reject_ty,
+                    components: ref rejects,
+                },
+            ) if ty == reject_ty && accepts.len() == conditions.len() => {
+                (ty, accepts.clone(), rejects.clone())
+            }
+            _ => return Err(ConstantEvaluatorError::InvalidSelectArgs),
+        };
+
+        let mut components = Vec::with_capacity(conditions.len());
+        for ((condition, accept), reject) in conditions.into_iter().zip(accepts).zip(rejects) {
+            components.push(self.select(condition, accept, reject, span)?);
+        }
+
+        self.register_evaluated_expr(Expression::Compose { ty, components }, span)
+    }
+
     fn binary_op(
         &mut self,
         op: BinaryOperator,
@@ -1814,6 +1932,14 @@ impl<'a> ConstantEvaluator<'a> {
                             BinaryOperator::Modulo => a % b,
                             _ => return Err(ConstantEvaluatorError::InvalidBinaryOpArgs),
                         }),
+                        (Literal::F16(a), Literal::F16(b)) => Literal::F16(match op {
+                            BinaryOperator::Add => a + b,
+                            BinaryOperator::Subtract => a - b,
+                            BinaryOperator::Multiply => a * b,
+                            BinaryOperator::Divide => a / b,
+                            BinaryOperator::Modulo => a % b,
+                            _ => return Err(ConstantEvaluatorError::InvalidBinaryOpArgs),
+                        }),
                         (Literal::AbstractInt(a), Literal::AbstractInt(b)) => {
                             Literal::AbstractInt(match op {
                                 BinaryOperator::Add => a.checked_add(b).ok_or_else(|| {
@@ -2070,6 +2196,12 @@ impl<'a> ConstantEvaluator<'a> {
         h
     }
 
+    /// Return `true` if the scalar components of `expr` are `f16` values.
+    fn has_f16_components(&self, expr: Handle<Expression>) -> Result<bool, ConstantEvaluatorError> {
+        let resolution = self.resolve_type(expr)?;
+        Ok(resolution.inner_with(self.types).scalar() == Some(crate::Scalar::F16))
+    }
+
     fn resolve_type(
         &self,
         expr: Handle<Expression>,
@@ -2172,6 +2304,32 @@ impl TryFromAbstract<f64> for f32 {
     }
 }
 
+impl TryFromAbstract<i64> for f16 {
+    fn try_from_abstract(value: i64) -> Result<Self, ConstantEvaluatorError> {
+        let f = f16::from_f64(value as f64);
+        if f.is_infinite() {
+            return Err(ConstantEvaluatorError::AutomaticConversionLossy {
+                value: format!("{value:?}"),
+                to_type: "f16",
+            });
+        }
+        Ok(f)
+    }
+}
+
+impl TryFromAbstract<f64> for f16 {
+    fn try_from_abstract(value: f64) -> Result<f16, ConstantEvaluatorError> {
+        let f = f16::from_f64(value);
+        if f.is_infinite() {
+            return Err(ConstantEvaluatorError::AutomaticConversionLossy {
+                value: format!("{value:?}"),
+                to_type: "f16",
+            });
+        }
+        Ok(f)
+    }
+}
+
 impl TryFromAbstract<i64> for f64 {
     fn try_from_abstract(value: i64) -> Result<Self, ConstantEvaluatorError> {
         let f = value as f64;
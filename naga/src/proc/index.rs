@@ -328,6 +328,13 @@ pub fn access_needs_check(
     // length constants, but `access_needs_check` is only used by back ends, so
     // validation should have caught those problems.
     let length = base_inner.indexable_length(module).unwrap();
+    // Runtime-sized arrays reached through a device address have no length to
+    // check against.
+    if matches!(length, IndexableLength::Dynamic)
+        && base_inner.pointer_space() == Some(crate::AddressSpace::PhysicalStorage)
+    {
+        return None;
+    }
     index.try_resolve_to_constant(function, module);
     if let (&GuardedIndex::Known(index), &IndexableLength::Known(length)) = (&index, &length) {
         if index < length {
@@ -418,6 +425,10 @@ pub enum IndexableLength {
     /// Values of this type always have the given number of elements.
     Known(u32),
 
+    /// The number of elements is an override-expression, not known until the
+    /// pipeline is created.
+    Pending,
+
     /// The number of elements is determined at runtime.
     Dynamic,
 }
@@ -429,6 +440,7 @@ impl crate::ArraySize {
     ) -> Result<IndexableLength, IndexableLengthError> {
         Ok(match self {
             Self::Constant(length) => IndexableLength::Known(length.get()),
+            Self::Pending(_) => IndexableLength::Pending,
             Self::Dynamic => IndexableLength::Dynamic,
         })
     }
@@ -90,6 +90,10 @@ impl super::Scalar {
         kind: crate::ScalarKind::Uint,
         width: 4,
     };
+    pub const F16: Self = Self {
+        kind: crate::ScalarKind::Float,
+        width: 2,
+    };
     pub const F32: Self = Self {
         kind: crate::ScalarKind::Float,
         width: 4,
@@ -157,6 +161,7 @@ impl super::Scalar {
 pub enum HashableLiteral {
     F64(u64),
     F32(u32),
+    F16(u16),
     U32(u32),
     I32(i32),
     U64(u64),
@@ -171,6 +176,7 @@ impl From<crate::Literal> for HashableLiteral {
         match l {
             crate::Literal::F64(v) => Self::F64(v.to_bits()),
             crate::Literal::F32(v) => Self::F32(v.to_bits()),
+            crate::Literal::F16(v) => Self::F16(v.to_bits()),
             crate::Literal::U32(v) => Self::U32(v),
             crate::Literal::I32(v) => Self::I32(v),
             crate::Literal::U64(v) => Self::U64(v),
@@ -187,6 +193,9 @@ impl crate::Literal {
         match (value, scalar.kind, scalar.width) {
             (value, crate::ScalarKind::Float, 8) => Some(Self::F64(value as _)),
             (value, crate::ScalarKind::Float, 4) => Some(Self::F32(value as _)),
+            (value, crate::ScalarKind::Float, 2) => {
+                Some(Self::F16(half::f16::from_f32_const(value as _)))
+            }
             (value, crate::ScalarKind::Uint, 4) => Some(Self::U32(value as _)),
             (value, crate::ScalarKind::Sint, 4) => Some(Self::I32(value as _)),
             (value, crate::ScalarKind::Uint, 8) => Some(Self::U64(value as _)),
@@ -209,6 +218,7 @@ impl crate::Literal {
         match *self {
             Self::F64(_) | Self::I64(_) | Self::U64(_) => 8,
             Self::F32(_) | Self::U32(_) | Self::I32(_) => 4,
+            Self::F16(_) => 2,
             Self::Bool(_) => crate::BOOL_WIDTH,
             Self::AbstractInt(_) | Self::AbstractFloat(_) => crate::ABSTRACT_WIDTH,
         }
@@ -217,6 +227,7 @@ impl crate::Literal {
         match *self {
             Self::F64(_) => crate::Scalar::F64,
             Self::F32(_) => crate::Scalar::F32,
+            Self::F16(_) => crate::Scalar::F16,
             Self::U32(_) => crate::Scalar::U32,
             Self::I32(_) => crate::Scalar::I32,
             Self::U64(_) => crate::Scalar::U64,
@@ -236,6 +247,11 @@ impl crate::Literal {
 
 pub const POINTER_SPAN: u32 = 4;
 
+/// The size and alignment of pointers in the [`PhysicalStorage`] address space.
+///
+/// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
+pub const PHYSICAL_POINTER_SPAN: u32 = 8;
+
 impl super::TypeInner {
     /// Return the scalar type of `self`.
     ///
@@ -288,6 +304,14 @@ impl super::TypeInner {
                 rows,
                 scalar,
             } => Alignment::from(rows) * scalar.width as u32 * columns as u32,
+            Self::Pointer {
+                space: crate::AddressSpace::PhysicalStorage,
+                ..
+            }
+            | Self::ValuePointer {
+                space: crate::AddressSpace::PhysicalStorage,
+                ..
+            } => PHYSICAL_POINTER_SPAN,
             Self::Pointer { .. } | Self::ValuePointer { .. } => POINTER_SPAN,
             Self::Array {
                 base: _,
@@ -296,8 +320,9 @@ impl super::TypeInner {
             } => {
                 let count = match size {
                     super::ArraySize::Constant(count) => count.get(),
-                    // A dynamically-sized array has to have at least one element
-                    super::ArraySize::Dynamic => 1,
+                    // The length isn't known until overrides are processed, and
+                    // a dynamically-sized array has to have at least one element
+                    super::ArraySize::Pending(_) | super::ArraySize::Dynamic => 1,
                 };
                 count * stride
             }
@@ -412,6 +437,7 @@ impl super::AddressSpace {
             crate::AddressSpace::Storage { access } => access,
             crate::AddressSpace::Handle => Sa::LOAD,
             crate::AddressSpace::PushConstant => Sa::LOAD,
+            crate::AddressSpace::PhysicalStorage => Sa::LOAD | Sa::STORE,
         }
     }
 }
@@ -674,6 +700,19 @@ impl GlobalCtx<'_> {
         }
     }
 
+    /// Try to evaluate the expression in `arena` using its `handle` and return it as a `bool`.
+    #[allow(dead_code)]
+    pub(crate) fn eval_expr_to_bool_from(
+        &self,
+        handle: crate::Handle<crate::Expression>,
+        arena: &crate::Arena<crate::Expression>,
+    ) -> Option<bool> {
+        match self.eval_expr_to_literal_from(handle, arena) {
+            Some(crate::Literal::Bool(value)) => Some(value),
+            _ => None,
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn eval_expr_to_literal(
         &self,
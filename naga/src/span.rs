@@ -122,6 +122,116 @@ pub struct SourceLocation {
     pub length: u32,
 }
 
+/// One file in a [`SourceMap`].
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    name: String,
+    range: Range<usize>,
+}
+
+impl SourceFile {
+    /// The name of this file, as given by the front end's import or include
+    /// resolver, or by the module's debug information. This may be empty,
+    /// for example for the main file of a GLSL shader.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The byte range of this file's text within [`SourceMap::text`].
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+/// The text of several source files that a module's [`Span`]s refer to.
+///
+/// Front ends that read more than one file, like WGSL with `import`s, GLSL
+/// with `#include`s, or SPIR-V with source text in its debug information,
+/// store the files one after another in a single string, so that a [`Span`]
+/// can refer to text in any of them. Use [`file_for_span`] to find the file a
+/// span belongs to.
+///
+/// [`file_for_span`]: SourceMap::file_for_span
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    text: String,
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Return a map holding the single file `text`, called `name`.
+    pub fn new(name: &str, text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            files: vec![SourceFile {
+                name: name.to_string(),
+                range: 0..text.len(),
+            }],
+        }
+    }
+
+    /// Append a file, and return its index.
+    pub fn add(&mut self, name: String, text: &str) -> usize {
+        // Keep a gap between files, so that the end of one file and the
+        // start of the next have different offsets.
+        if !self.files.is_empty() {
+            self.text.push('\n');
+        }
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.files.push(SourceFile {
+            name,
+            range: start..self.text.len(),
+        });
+        self.files.len() - 1
+    }
+
+    /// Append `text` to the last file added.
+    #[cfg(feature = "spv-in")]
+    pub(crate) fn continue_last(&mut self, text: &str) {
+        if let Some(file) = self.files.last_mut() {
+            self.text.push_str(text);
+            file.range.end = self.text.len();
+        }
+    }
+
+    /// The text of all the files, one after another.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// All the files, in the order they were added.
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// The text of `file`.
+    pub fn source(&self, file: &SourceFile) -> &str {
+        &self.text[file.range()]
+    }
+
+    /// Return the index in [`files`] of the file named `name`, if any.
+    ///
+    /// [`files`]: SourceMap::files
+    pub fn file_index(&self, name: &str) -> Option<usize> {
+        self.files.iter().position(|file| file.name == name)
+    }
+
+    /// Return the index in [`files`] of the file containing `span`, and
+    /// `span` relative to the start of that file.
+    ///
+    /// [`files`]: SourceMap::files
+    pub fn file_for_span(&self, span: Span) -> Option<(usize, Span)> {
+        let range = span.to_range()?;
+        let index = self
+            .files
+            .iter()
+            .position(|file| file.range.start <= range.start && range.end <= file.range.end)?;
+        let start = self.files[index].range.start;
+        Some((index, Span::from(range.start - start..range.end - start)))
+    }
+}
+
 /// A source code span together with "context", a user-readable description of what part of the error it refers to.
 pub type SpanContext = (Span, String);
 
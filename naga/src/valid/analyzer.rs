@@ -7,6 +7,7 @@ Figures out the following properties:
 !*/
 
 use super::{ExpressionError, FunctionError, ModuleInfo, ShaderStages, ValidationFlags};
+use crate::diagnostic_filter::{DiagnosticFilterNode, StandardFilterableTriggeringRule};
 use crate::span::{AddSpan as _, WithSpan};
 use crate::{
     arena::{Arena, Handle},
@@ -16,10 +17,6 @@ use std::ops;
 
 pub type NonUniformResult = Option<Handle<crate::Expression>>;
 
-// Remove this once we update our uniformity analysis and
-// add support for the `derivative_uniformity` diagnostic
-const DISABLE_UNIFORMITY_REQ_FOR_FRAGMENT_STAGE: bool = true;
-
 bitflags::bitflags! {
     /// Kinds of expressions that require uniform control flow.
     #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -27,8 +24,8 @@ bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
     pub struct UniformityRequirements: u8 {
         const WORK_GROUP_BARRIER = 0x1;
-        const DERIVATIVE = if DISABLE_UNIFORMITY_REQ_FOR_FRAGMENT_STAGE { 0 } else { 0x2 };
-        const IMPLICIT_LEVEL = if DISABLE_UNIFORMITY_REQ_FOR_FRAGMENT_STAGE { 0 } else { 0x4 };
+        const DERIVATIVE = 0x2;
+        const IMPLICIT_LEVEL = 0x4;
     }
 }
 
@@ -290,6 +287,16 @@ pub struct FunctionInfo {
 
     /// Indicates that the function is using dual source blending.
     pub dual_source_blending: bool,
+
+    /// The leaf of the diagnostic filter rules that apply to this function.
+    ///
+    /// This is copied from [`crate::Function::diagnostic_filter_leaf`]. See
+    /// [`DiagnosticFilterNode`] for details on how the tree is represented and
+    /// used in validation. Within the function's body,
+    /// [`DiagnosticFilter`] statements may add to it.
+    ///
+    /// [`DiagnosticFilter`]: crate::Statement::DiagnosticFilter
+    diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
 }
 
 impl FunctionInfo {
@@ -621,8 +628,9 @@ impl FunctionInfo {
                 assignable_global = Some(gh);
                 let var = &resolve_context.global_vars[gh];
                 let uniform = match var.space {
-                    // local data is non-uniform
-                    As::Function | As::Private => false,
+                    // local data is non-uniform, and no global can be in
+                    // the physical storage address space
+                    As::Function | As::Private | As::PhysicalStorage => false,
                     // workgroup memory is exclusively accessed by the group
                     As::WorkGroup => true,
                     // uniform data
@@ -640,10 +648,19 @@ impl FunctionInfo {
                 non_uniform_result: Some(handle),
                 requirements: UniformityRequirements::empty(),
             },
-            E::Load { pointer } => Uniformity {
-                non_uniform_result: self.add_ref(pointer),
-                requirements: UniformityRequirements::empty(),
-            },
+            E::Load { pointer } => {
+                // Memory behind a device address may be written by any
+                // invocation, even through a uniform pointer.
+                let physical = self[pointer]
+                    .ty
+                    .inner_with(resolve_context.types)
+                    .pointer_space()
+                    == Some(crate::AddressSpace::PhysicalStorage);
+                Uniformity {
+                    non_uniform_result: self.add_ref(pointer).or(physical.then_some(handle)),
+                    requirements: UniformityRequirements::empty(),
+                }
+            }
             E::ImageSample {
                 image,
                 sampler,
@@ -820,8 +837,12 @@ impl FunctionInfo {
     ///
     /// The parent control flow is uniform if `disruptor.is_none()`.
     ///
-    /// Returns a `NonUniformControlFlow` error if any of the expressions in the block
-    /// require uniformity, but the current flow is non-uniform.
+    /// Reports a `NonUniformControlFlow` diagnostic if any of the expressions
+    /// in the block require uniformity, but the current flow is non-uniform.
+    /// Whether that is an error depends on the severity of the
+    /// `derivative_uniformity` rule at this point, as determined by the
+    /// diagnostic filters in `diagnostic_filter_arena`, starting from
+    /// `diagnostic_filter_leaf`, the leaf in effect where the block begins.
     #[allow(clippy::or_fun_call)]
     fn process_block(
         &mut self,
@@ -829,6 +850,8 @@ impl FunctionInfo {
         other_functions: &[FunctionInfo],
         mut disruptor: Option<UniformityDisruptor>,
         expression_arena: &Arena<crate::Expression>,
+        diagnostic_filter_arena: &Arena<DiagnosticFilterNode>,
+        mut diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
     ) -> Result<FunctionUniformity, WithSpan<FunctionError>> {
         use crate::Statement as S;
 
@@ -845,8 +868,15 @@ impl FunctionInfo {
                             && !req.is_empty()
                         {
                             if let Some(cause) = disruptor {
-                                return Err(FunctionError::NonUniformControlFlow(req, expr, cause)
-                                    .with_span_handle(expr, expression_arena));
+                                let severity = DiagnosticFilterNode::search(
+                                    diagnostic_filter_leaf,
+                                    diagnostic_filter_arena,
+                                    StandardFilterableTriggeringRule::DerivativeUniformity,
+                                );
+                                severity.report_diag(
+                                    FunctionError::NonUniformControlFlow(req, expr, cause)
+                                        .with_span_handle(expr, expression_arena),
+                                )?;
                             }
                         }
                         requirements |= req;
@@ -904,9 +934,14 @@ impl FunctionInfo {
                         exit: ExitFlags::empty(),
                     }
                 }
-                S::Block(ref b) => {
-                    self.process_block(b, other_functions, disruptor, expression_arena)?
-                }
+                S::Block(ref b) => self.process_block(
+                    b,
+                    other_functions,
+                    disruptor,
+                    expression_arena,
+                    diagnostic_filter_arena,
+                    diagnostic_filter_leaf,
+                )?,
                 S::If {
                     condition,
                     ref accept,
@@ -920,12 +955,16 @@ impl FunctionInfo {
                         other_functions,
                         branch_disruptor,
                         expression_arena,
+                        diagnostic_filter_arena,
+                        diagnostic_filter_leaf,
                     )?;
                     let reject_uniformity = self.process_block(
                         reject,
                         other_functions,
                         branch_disruptor,
                         expression_arena,
+                        diagnostic_filter_arena,
+                        diagnostic_filter_leaf,
                     )?;
                     accept_uniformity | reject_uniformity
                 }
@@ -944,6 +983,8 @@ impl FunctionInfo {
                             other_functions,
                             case_disruptor,
                             expression_arena,
+                            diagnostic_filter_arena,
+                            diagnostic_filter_leaf,
                         )?;
                         case_disruptor = if case.fall_through {
                             case_disruptor.or(case_uniformity.exit_disruptor())
@@ -959,14 +1000,22 @@ impl FunctionInfo {
                     ref continuing,
                     break_if,
                 } => {
-                    let body_uniformity =
-                        self.process_block(body, other_functions, disruptor, expression_arena)?;
+                    let body_uniformity = self.process_block(
+                        body,
+                        other_functions,
+                        disruptor,
+                        expression_arena,
+                        diagnostic_filter_arena,
+                        diagnostic_filter_leaf,
+                    )?;
                     let continuing_disruptor = disruptor.or(body_uniformity.exit_disruptor());
                     let continuing_uniformity = self.process_block(
                         continuing,
                         other_functions,
                         continuing_disruptor,
                         expression_arena,
+                        diagnostic_filter_arena,
+                        diagnostic_filter_leaf,
                     )?;
                     if let Some(expr) = break_if {
                         let _ = self.add_ref(expr);
@@ -1079,6 +1128,19 @@ impl FunctionInfo {
                     }
                     FunctionUniformity::new()
                 }
+                S::DebugPrintf {
+                    format: _,
+                    ref arguments,
+                } => {
+                    for &argument in arguments {
+                        let _ = self.add_ref(argument);
+                    }
+                    FunctionUniformity::new()
+                }
+                S::DiagnosticFilter(leaf) => {
+                    diagnostic_filter_leaf = Some(leaf);
+                    FunctionUniformity::new()
+                }
             };
 
             disruptor = disruptor.or(uniformity.exit_disruptor());
@@ -1120,6 +1182,7 @@ impl ModuleInfo {
             expressions: vec![ExpressionInfo::new(); fun.expressions.len()].into_boxed_slice(),
             sampling: crate::FastHashSet::default(),
             dual_source_blending: false,
+            diagnostic_filter_leaf: fun.diagnostic_filter_leaf,
         };
         let resolve_context =
             ResolveContext::with_locals(module, &fun.local_variables, &fun.arguments);
@@ -1143,7 +1206,14 @@ impl ModuleInfo {
             }
         }
 
-        let uniformity = info.process_block(&fun.body, &self.functions, None, &fun.expressions)?;
+        let uniformity = info.process_block(
+            &fun.body,
+            &self.functions,
+            None,
+            &fun.expressions,
+            &module.diagnostic_filters,
+            info.diagnostic_filter_leaf,
+        )?;
         info.uniformity = uniformity.result;
         info.may_kill = uniformity.exit.contains(ExitFlags::MAY_KILL);
 
@@ -1223,6 +1293,7 @@ fn uniform_control_flow() {
     );
     let emit_range_query_access_globals = expressions.range_from(2);
 
+    let mut diagnostic_filters = Arena::new();
     let mut info = FunctionInfo {
         flags: ValidationFlags::all(),
         available_stages: ShaderStages::all(),
@@ -1233,6 +1304,7 @@ fn uniform_control_flow() {
         expressions: vec![ExpressionInfo::new(); expressions.len()].into_boxed_slice(),
         sampling: crate::FastHashSet::default(),
         dual_source_blending: false,
+        diagnostic_filter_leaf: None,
     };
     let resolve_context = ResolveContext {
         constants: &Arena::new(),
@@ -1279,7 +1351,9 @@ fn uniform_control_flow() {
             &vec![stmt_emit1, stmt_if_uniform].into(),
             &[],
             None,
-            &expressions
+            &expressions,
+            &diagnostic_filters,
+            None,
         ),
         Ok(FunctionUniformity {
             result: Uniformity {
@@ -1306,26 +1380,42 @@ fn uniform_control_flow() {
         reject: crate::Block::new(),
     };
     {
+        // `derivative_uniformity` is off by default, so make it an error.
+        let error_leaf = diagnostic_filters.append(
+            DiagnosticFilterNode {
+                inner: crate::diagnostic_filter::DiagnosticFilter {
+                    new_severity: crate::diagnostic_filter::Severity::Error,
+                    triggering_rule: crate::diagnostic_filter::FilterableTriggeringRule::Standard(
+                        StandardFilterableTriggeringRule::DerivativeUniformity,
+                    ),
+                },
+                parent: None,
+            },
+            Default::default(),
+        );
         let block_info = info.process_block(
-            &vec![stmt_emit2, stmt_if_non_uniform].into(),
+            &vec![
+                S::DiagnosticFilter(error_leaf),
+                stmt_emit2,
+                stmt_if_non_uniform,
+            ]
+            .into(),
             &[],
             None,
             &expressions,
+            &diagnostic_filters,
+            None,
         );
-        if DISABLE_UNIFORMITY_REQ_FOR_FRAGMENT_STAGE {
-            assert_eq!(info[derivative_expr].ref_count, 2);
-        } else {
-            assert_eq!(
-                block_info,
-                Err(FunctionError::NonUniformControlFlow(
-                    UniformityRequirements::DERIVATIVE,
-                    derivative_expr,
-                    UniformityDisruptor::Expression(non_uniform_global_expr)
-                )
-                .with_span()),
-            );
-            assert_eq!(info[derivative_expr].ref_count, 1);
-        }
+        assert_eq!(
+            block_info,
+            Err(FunctionError::NonUniformControlFlow(
+                UniformityRequirements::DERIVATIVE,
+                derivative_expr,
+                UniformityDisruptor::Expression(non_uniform_global_expr)
+            )
+            .with_span()),
+        );
+        assert_eq!(info[derivative_expr].ref_count, 1);
     }
     assert_eq!(info[non_uniform_global], GlobalUse::READ);
 
@@ -1338,7 +1428,9 @@ fn uniform_control_flow() {
             &vec![stmt_emit3, stmt_return_non_uniform].into(),
             &[],
             Some(UniformityDisruptor::Return),
-            &expressions
+            &expressions,
+            &diagnostic_filters,
+            None,
         ),
         Ok(FunctionUniformity {
             result: Uniformity {
@@ -1365,7 +1457,9 @@ fn uniform_control_flow() {
             &vec![stmt_emit4, stmt_assign, stmt_kill, stmt_return_pointer].into(),
             &[],
             Some(UniformityDisruptor::Discard),
-            &expressions
+            &expressions,
+            &diagnostic_filters,
+            None,
         ),
         Ok(FunctionUniformity {
             result: Uniformity {
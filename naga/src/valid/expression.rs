@@ -1597,6 +1597,11 @@ impl super::Validator {
                 }
             }
             E::ArrayLength(expr) => match resolver[expr] {
+                // Memory reached through a device address has no known size.
+                Ti::Pointer {
+                    space: crate::AddressSpace::PhysicalStorage,
+                    ..
+                } => return Err(ExpressionError::InvalidArrayType(expr)),
                 Ti::Pointer { base, .. } => {
                     let base_ty = &resolver.types[base];
                     if let Ti::Array {
@@ -1678,6 +1683,7 @@ pub fn check_literal_value(literal: crate::Literal) -> Result<(), LiteralError>
     let is_nan = match literal {
         crate::Literal::F64(v) => v.is_nan(),
         crate::Literal::F32(v) => v.is_nan(),
+        crate::Literal::F16(v) => v.is_nan(),
         _ => false,
     };
     if is_nan {
@@ -1687,6 +1693,7 @@ pub fn check_literal_value(literal: crate::Literal) -> Result<(), LiteralError>
     let is_infinite = match literal {
         crate::Literal::F64(v) => v.is_infinite(),
         crate::Literal::F32(v) => v.is_infinite(),
+        crate::Literal::F16(v) => v.is_infinite(),
         _ => false,
     };
     if is_infinite {
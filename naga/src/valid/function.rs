@@ -186,6 +186,8 @@ pub enum FunctionError {
     WorkgroupUniformLoadInvalidPointer(Handle<crate::Expression>),
     #[error("Subgroup operation is invalid")]
     InvalidSubgroup(#[from] SubgroupError),
+    #[error("The value {0:?} can not be printed, as it is not a numeric scalar or vector")]
+    InvalidDebugPrintfArgument(Handle<crate::Expression>),
     #[error("Emit statement should not cover \"result\" expressions like {0:?}")]
     EmitResult(Handle<crate::Expression>),
     #[error("Expression not visited by the appropriate statement")]
@@ -953,8 +955,16 @@ impl super::Validator {
                             | crate::Expression::GlobalVariable(_)
                             | crate::Expression::FunctionArgument(_) => break,
                             _ => {
+                                // Pointers into the physical storage address
+                                // space are values any expression can produce.
+                                let current_ty =
+                                    context.resolve_type(current, &self.valid_expression_set)?;
+                                if current_ty.pointer_space() == Some(AddressSpace::PhysicalStorage)
+                                {
+                                    break;
+                                }
                                 return Err(FunctionError::InvalidStorePointer(current)
-                                    .with_span_handle(pointer, context.expressions))
+                                    .with_span_handle(pointer, context.expressions));
                             }
                         }
                     }
@@ -1306,6 +1316,27 @@ impl super::Validator {
                     }
                     self.validate_subgroup_gather(mode, argument, result, context)?;
                 }
+                S::DebugPrintf {
+                    format: _,
+                    ref arguments,
+                } => {
+                    for &argument in arguments {
+                        let printable =
+                            match *context.resolve_type(argument, &self.valid_expression_set)? {
+                                Ti::Scalar(scalar) | Ti::Vector { scalar, .. } => {
+                                    scalar.kind != crate::ScalarKind::Bool
+                                }
+                                _ => false,
+                            };
+                        if !printable {
+                            return Err(FunctionError::InvalidDebugPrintfArgument(argument)
+                                .with_span_handle(argument, context.expressions));
+                        }
+                    }
+                }
+                // The handle was checked by `handles`, and there's nothing
+                // else to check.
+                S::DiagnosticFilter(_) => {}
             }
         }
         Ok(BlockInfo { stages, finished })
@@ -1382,7 +1413,14 @@ impl super::Validator {
 
         for (index, argument) in fun.arguments.iter().enumerate() {
             match module.types[argument.ty].inner.pointer_space() {
-                Some(crate::AddressSpace::Private | crate::AddressSpace::Function) | None => {}
+                Some(
+                    crate::AddressSpace::Private
+                    | crate::AddressSpace::Function
+                    | crate::AddressSpace::Storage { .. }
+                    | crate::AddressSpace::Uniform
+                    | crate::AddressSpace::WorkGroup,
+                )
+                | None => {}
                 Some(other) => {
                     return Err(FunctionError::InvalidArgumentPointerSpace {
                         index,
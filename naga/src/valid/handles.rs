@@ -39,6 +39,9 @@ impl super::Validator {
             ref types,
             ref special_types,
             ref global_expressions,
+            ref diagnostic_filters,
+            diagnostic_filter_leaf,
+            ref doc_comments,
         } = module;
 
         // NOTE: Types being first is important. All other forms of validation depend on this.
@@ -56,9 +59,18 @@ impl super::Validator {
                 crate::TypeInner::Pointer { base, space: _ } => {
                     this_handle.check_dep(base)?;
                 }
-                crate::TypeInner::Array { base, .. }
-                | crate::TypeInner::BindingArray { base, .. } => {
+                crate::TypeInner::Array { base, size, .. }
+                | crate::TypeInner::BindingArray { base, size, .. } => {
                     this_handle.check_dep(base)?;
+                    match size {
+                        crate::ArraySize::Pending(crate::PendingArraySize::Override(handle)) => {
+                            Self::validate_override_handle(handle, overrides)?;
+                        }
+                        crate::ArraySize::Pending(crate::PendingArraySize::Expression(handle)) => {
+                            Self::validate_expression_handle(handle, global_expressions)?;
+                        }
+                        crate::ArraySize::Constant(_) | crate::ArraySize::Dynamic => {}
+                    }
                 }
                 crate::TypeInner::Struct {
                     ref members,
@@ -119,6 +131,7 @@ impl super::Validator {
                 ref expressions,
                 ref named_expressions,
                 ref body,
+                diagnostic_filter_leaf,
             } = function;
 
             for arg in arguments.iter() {
@@ -160,7 +173,11 @@ impl super::Validator {
                 )?;
             }
 
-            Self::validate_block_handles(body, expressions, functions)?;
+            Self::validate_block_handles(body, expressions, functions, diagnostic_filters)?;
+
+            if let Some(handle) = diagnostic_filter_leaf {
+                handle.check_valid_for(diagnostic_filters)?;
+            }
 
             Ok(())
         };
@@ -180,6 +197,44 @@ impl super::Validator {
             validate_type(ty)?;
         }
 
+        for (handle, node) in diagnostic_filters.iter() {
+            let &crate::diagnostic_filter::DiagnosticFilterNode { inner: _, parent } = node;
+            handle.check_dep_opt(parent)?;
+        }
+        if let Some(handle) = diagnostic_filter_leaf {
+            handle.check_valid_for(diagnostic_filters)?;
+        }
+
+        if let Some(doc_comments) = doc_comments.as_deref() {
+            let crate::DocComments {
+                types: ref type_comments,
+                ref struct_members,
+                entry_points: _,
+                functions: ref function_comments,
+                constants: ref constant_comments,
+                overrides: ref override_comments,
+                global_variables: ref global_variable_comments,
+            } = *doc_comments;
+            for &ty in type_comments.keys() {
+                validate_type(ty)?;
+            }
+            for &(ty, _) in struct_members.keys() {
+                validate_type(ty)?;
+            }
+            for &handle in function_comments.keys() {
+                handle.check_valid_for(functions)?;
+            }
+            for &handle in constant_comments.keys() {
+                handle.check_valid_for(constants)?;
+            }
+            for &handle in override_comments.keys() {
+                handle.check_valid_for(overrides)?;
+            }
+            for &handle in global_variable_comments.keys() {
+                handle.check_valid_for(global_variables)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -441,8 +496,10 @@ impl super::Validator {
         block: &crate::Block,
         expressions: &Arena<crate::Expression>,
         functions: &Arena<crate::Function>,
+        diagnostic_filters: &Arena<crate::diagnostic_filter::DiagnosticFilterNode>,
     ) -> Result<(), InvalidHandleError> {
-        let validate_block = |block| Self::validate_block_handles(block, expressions, functions);
+        let validate_block =
+            |block| Self::validate_block_handles(block, expressions, functions, diagnostic_filters);
         let validate_expr = |handle| Self::validate_expression_handle(handle, expressions);
         let validate_expr_opt = |handle_opt| {
             if let Some(handle) = handle_opt {
@@ -602,6 +659,19 @@ impl super::Validator {
                 validate_expr(result)?;
                 Ok(())
             }
+            crate::Statement::DebugPrintf {
+                format: _,
+                ref arguments,
+            } => {
+                for arg in arguments.iter().copied() {
+                    validate_expr(arg)?;
+                }
+                Ok(())
+            }
+            crate::Statement::DiagnosticFilter(leaf) => {
+                leaf.check_valid_for(diagnostic_filters)?;
+                Ok(())
+            }
             crate::Statement::Break
             | crate::Statement::Continue
             | crate::Statement::Kill
@@ -39,6 +39,8 @@ pub enum GlobalVariableError {
     InitializerNotAllowed(crate::AddressSpace),
     #[error("Storage address space doesn't support write-only access")]
     StorageAddressSpaceWriteOnlyNotSupported,
+    #[error("Array size must be an override-expression of type `i32` or `u32`")]
+    InvalidPendingArraySize,
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -432,6 +434,35 @@ impl super::Validator {
         use super::TypeFlags;
 
         log::debug!("var {:?}", var);
+        if let crate::TypeInner::Array {
+            size: crate::ArraySize::Pending(size),
+            ..
+        } = gctx.types[var.ty].inner
+        {
+            if var.space != crate::AddressSpace::WorkGroup {
+                return Err(GlobalVariableError::InvalidType(var.space));
+            }
+            let size_ty = match size {
+                crate::PendingArraySize::Override(handle) => {
+                    &gctx.types[gctx.overrides[handle].ty].inner
+                }
+                crate::PendingArraySize::Expression(expr) => {
+                    // Sizes that don't depend on overrides should be `Constant`.
+                    if global_expr_kind.is_const(expr) {
+                        return Err(GlobalVariableError::InvalidPendingArraySize);
+                    }
+                    mod_info[expr].inner_with(gctx.types)
+                }
+            };
+            match *size_ty {
+                crate::TypeInner::Scalar(crate::Scalar {
+                    kind: crate::ScalarKind::Sint | crate::ScalarKind::Uint,
+                    width: 4,
+                }) => {}
+                _ => return Err(GlobalVariableError::InvalidPendingArraySize),
+            }
+        }
+
         let inner_ty = match gctx.types[var.ty].inner {
             // A binding array is (mostly) supposed to behave the same as a
             // series of individually bound resources, so we can (mostly)
@@ -447,7 +478,7 @@ impl super::Validator {
         let type_info = &self.types[inner_ty.index()];
 
         let (required_type_flags, is_resource) = match var.space {
-            crate::AddressSpace::Function => {
+            crate::AddressSpace::Function | crate::AddressSpace::PhysicalStorage => {
                 return Err(GlobalVariableError::InvalidUsage(var.space))
             }
             crate::AddressSpace::Storage { access } => {
@@ -700,7 +731,9 @@ impl super::Validator {
             }
 
             let allowed_usage = match var.space {
-                crate::AddressSpace::Function => unreachable!(),
+                crate::AddressSpace::Function | crate::AddressSpace::PhysicalStorage => {
+                    unreachable!()
+                }
                 crate::AddressSpace::Uniform => GlobalUse::READ | GlobalUse::QUERY,
                 crate::AddressSpace::Storage { access } => storage_usage(access),
                 crate::AddressSpace::Handle => match module.types[var.ty].inner {
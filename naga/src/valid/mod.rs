@@ -143,6 +143,12 @@ bitflags::bitflags! {
         const SHADER_INT64_ATOMIC_MIN_MAX = 0x80000;
         /// Support for all atomic operations on 64-bit integers.
         const SHADER_INT64_ATOMIC_ALL_OPS = 0x100000;
+        /// Float values with width = 2.
+        const SHADER_FLOAT16 = 0x200000;
+        /// Support for pointers in the [`PhysicalStorage`] address space.
+        ///
+        /// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
+        const PHYSICAL_STORAGE_BUFFER = 0x400000;
     }
 }
 
@@ -537,6 +543,7 @@ impl Validator {
                 crate::Scalar::BOOL
                 | crate::Scalar::I32
                 | crate::Scalar::U32
+                | crate::Scalar::F16
                 | crate::Scalar::F32
                 | crate::Scalar::F64 => {}
                 _ => return Err(OverrideError::TypeNotScalar),
@@ -61,7 +61,10 @@ bitflags::bitflags! {
         ///
         /// The constructible types are scalars, vectors, matrices, fixed-size
         /// arrays of constructible types, and structs whose members are all
-        /// constructible.
+        /// constructible. Pointers in the [`PhysicalStorage`] address space
+        /// are constructible too, so that they can be held in local variables.
+        ///
+        /// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
         ///
         /// [constructible]: https://gpuweb.github.io/gpuweb/wgsl/#constructible
         const CONSTRUCTIBLE = 0x80;
@@ -127,6 +130,8 @@ pub enum TypeError {
     InvalidDynamicArray(String, Handle<crate::Type>),
     #[error("The base handle {0:?} has to be a struct")]
     BindingArrayBaseTypeNotStruct(Handle<crate::Type>),
+    #[error("Array type {0:?} is sized by an override-expression, so it can only be the store type of a `workgroup` variable")]
+    InvalidPendingArray(Handle<crate::Type>),
     #[error("Structure member[{index}] at {offset} overlaps the previous member")]
     MemberOverlap { index: u32, offset: u32 },
     #[error(
@@ -195,17 +200,33 @@ fn check_member_layout(
 /// `TypeFlags::empty()`.
 ///
 /// Pointers passed as arguments to user-defined functions must be in the
-/// `Function` or `Private` address space.
+/// `Function`, `Private`, `Storage`, `Uniform` or `WorkGroup` address space.
+/// Not every back end can express the last three directly; those that can't
+/// rely on `back::pointer_arguments` to specialize such functions away.
 const fn ptr_space_argument_flag(space: crate::AddressSpace) -> TypeFlags {
     use crate::AddressSpace as As;
     match space {
-        As::Function | As::Private => TypeFlags::ARGUMENT,
-        As::Uniform | As::Storage { .. } | As::Handle | As::PushConstant | As::WorkGroup => {
-            TypeFlags::empty()
-        }
+        As::Function
+        | As::Private
+        | As::Uniform
+        | As::Storage { .. }
+        | As::WorkGroup
+        | As::PhysicalStorage => TypeFlags::ARGUMENT,
+        As::Handle | As::PushConstant => TypeFlags::empty(),
     }
 }
 
+/// Return true if `handle` is an array sized by an override-expression.
+fn is_pending_array(gctx: crate::proc::GlobalCtx, handle: Handle<crate::Type>) -> bool {
+    matches!(
+        gctx.types[handle].inner,
+        crate::TypeInner::Array {
+            size: crate::ArraySize::Pending(_),
+            ..
+        }
+    )
+}
+
 #[derive(Clone, Debug)]
 pub(super) struct TypeInfo {
     pub flags: TypeFlags,
@@ -232,6 +253,22 @@ impl TypeInfo {
 }
 
 impl super::Validator {
+    /// The [`TypeInfo`] of pointers in the [`PhysicalStorage`] address space,
+    /// which are plain 64-bit values.
+    ///
+    /// [`PhysicalStorage`]: crate::AddressSpace::PhysicalStorage
+    fn physical_pointer_info() -> TypeInfo {
+        TypeInfo::new(
+            TypeFlags::DATA
+                | TypeFlags::SIZED
+                | TypeFlags::COPY
+                | TypeFlags::HOST_SHAREABLE
+                | TypeFlags::ARGUMENT
+                | TypeFlags::CONSTRUCTIBLE,
+            Alignment::EIGHT,
+        )
+    }
+
     const fn require_type_capability(&self, capability: Capabilities) -> Result<(), TypeError> {
         if self.capabilities.contains(capability) {
             Ok(())
@@ -252,6 +289,14 @@ impl super::Validator {
                         });
                     }
                     true
+                } else if scalar.width == 2 {
+                    if !self.capabilities.contains(Capabilities::SHADER_FLOAT16) {
+                        return Err(WidthError::MissingCapability {
+                            name: "f16",
+                            flag: "SHADER_FLOAT16",
+                        });
+                    }
+                    true
                 } else {
                     scalar.width == 4
                 }
@@ -395,9 +440,9 @@ impl super::Validator {
                     return Err(TypeError::InvalidPointerBase(base));
                 }
 
-                // Runtime-sized values can only live in the `Storage` address
-                // space, so it's useless to have a pointer to such a type in
-                // any other space.
+                // Runtime-sized values can only live in the `Storage` and
+                // `PhysicalStorage` address spaces, so it's useless to have a
+                // pointer to such a type in any other space.
                 //
                 // Detecting this problem here prevents the definition of
                 // functions like:
@@ -408,13 +453,20 @@ impl super::Validator {
                 // may also present difficulties in code generation).
                 if !base_info.flags.contains(TypeFlags::SIZED) {
                     match space {
-                        As::Storage { .. } => {}
+                        As::Storage { .. } | As::PhysicalStorage => {}
                         _ => {
                             return Err(TypeError::InvalidPointerToUnsized { base, space });
                         }
                     }
                 }
 
+                // Pointers into physical storage are device addresses, which
+                // can be stored anywhere other data can.
+                if space == As::PhysicalStorage {
+                    self.require_type_capability(Capabilities::PHYSICAL_STORAGE_BUFFER)?;
+                    return Ok(Self::physical_pointer_info());
+                }
+
                 // `Validator::validate_function` actually checks the address
                 // space of pointer arguments explicitly before checking the
                 // `ARGUMENT` flag, to give better error messages. But it seems
@@ -442,6 +494,13 @@ impl super::Validator {
                 // `InvalidPointerBase` or `InvalidPointerToUnsized`.
                 self.check_width(scalar)?;
 
+                // Pointers into physical storage are device addresses, which
+                // can be stored anywhere other data can.
+                if space == crate::AddressSpace::PhysicalStorage {
+                    self.require_type_capability(Capabilities::PHYSICAL_STORAGE_BUFFER)?;
+                    return Ok(Self::physical_pointer_info());
+                }
+
                 // `Validator::validate_function` actually checks the address
                 // space of pointer arguments explicitly before checking the
                 // `ARGUMENT` flag, to give better error messages. But it seems
@@ -460,6 +519,9 @@ impl super::Validator {
                 if !base_info.flags.contains(TypeFlags::DATA | TypeFlags::SIZED) {
                     return Err(TypeError::InvalidArrayBaseType(base));
                 }
+                if is_pending_array(gctx, base) {
+                    return Err(TypeError::InvalidPendingArray(base));
+                }
 
                 let base_layout = self.layouter[base];
                 let general_alignment = base_layout.alignment;
@@ -497,6 +559,9 @@ impl super::Validator {
                             | TypeFlags::ARGUMENT
                             | TypeFlags::CONSTRUCTIBLE
                     }
+                    // These can only be the store type of workgroup variables,
+                    // and so can't be loaded, passed, or shared with the host.
+                    crate::ArraySize::Pending(_) => TypeFlags::DATA | TypeFlags::SIZED,
                     crate::ArraySize::Dynamic => {
                         // Non-SIZED types may only appear as the last element of a structure.
                         // This is enforced by checks for SIZED-ness for all compound types,
@@ -536,6 +601,9 @@ impl super::Validator {
                     if !base_info.flags.contains(TypeFlags::DATA) {
                         return Err(TypeError::InvalidData(member.ty));
                     }
+                    if is_pending_array(gctx, member.ty) {
+                        return Err(TypeError::InvalidPendingArray(member.ty));
+                    }
                     if !base_info.flags.contains(TypeFlags::HOST_SHAREABLE) {
                         if ti.uniform_layout.is_ok() {
                             ti.uniform_layout = Err((member.ty, Disalignment::NonHostShareable));
@@ -669,6 +737,9 @@ impl super::Validator {
                 }
                 let type_info_mask = match size {
                     crate::ArraySize::Constant(_) => TypeFlags::SIZED | TypeFlags::HOST_SHAREABLE,
+                    crate::ArraySize::Pending(_) => {
+                        return Err(TypeError::InvalidPendingArray(handle))
+                    }
                     crate::ArraySize::Dynamic => {
                         // Final type is non-sized
                         TypeFlags::HOST_SHAREABLE
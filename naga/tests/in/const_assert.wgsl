@@ -0,0 +1,9 @@
+// Sourced from https://gpuweb.github.io/gpuweb/wgsl/#const-assert-statement
+const x = 1;
+const y = 2;
+const_assert x < y; // valid at module-scope.
+const_assert(y != 0); // parentheses are optional.
+
+fn foo() {
+    const_assert x < y; // valid in functions.
+}
@@ -0,0 +1,35 @@
+// Diagnostic filters control how uniformity findings are reported.
+diagnostic(off, derivative_uniformity);
+diagnostic(warning, naga.unknown_rule);
+
+@fragment
+@diagnostic(warning, derivative_uniformity)
+fn main(@location(0) x: f32) -> @location(0) f32 {
+    if x > 0.0 {
+        return dpdx(x);
+    }
+    return 0.0;
+}
+
+// Filters on statements apply only within them.
+fn filtered(x: f32) -> f32 {
+    @diagnostic(off, derivative_uniformity) if x > 0.0 {
+        return dpdx(x);
+    }
+    loop @diagnostic(info, derivative_uniformity) {
+        if x > 1.0 {
+            return dpdy(x);
+        }
+        continuing {
+            break if x < 2.0;
+        }
+    }
+    switch i32(x) @diagnostic(off, derivative_uniformity) {
+        case 1: {
+            return fwidth(x);
+        }
+        default: @diagnostic(warning, derivative_uniformity) {
+            return dpdx(x);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+/// The number of lights to shade with.
+@id(0) override light_count: u32 = 1u;
+
+/// An override whose type is inferred from its initializer.
+override falloff = 2.718;
+
+/// Never given a value by the pipeline.
+override exposure: f32 = 1.0;
+
+@compute @workgroup_size(1)
+fn main() {
+    var t: f32 = f32(light_count) * falloff * exposure;
+}
@@ -0,0 +1,34 @@
+//! Module documentation is not recorded.
+
+/// A light source.
+///
+/// Lights are additive.
+struct Light {
+    /// Linear color.
+    color: vec3<f32>,
+    // Not a doc comment.
+    intensity: f32,
+    //// Not a doc comment either.
+    range: f32,
+}
+
+/// The scene's light.
+@group(0) @binding(0)
+var<uniform> light: Light;
+
+/// Scales every result.
+const scale = 2.0;
+
+/// Shade a surface with normal `n`.
+fn shade(n: vec3<f32>) -> f32 {
+    return dot(n, light.color) * light.intensity * scale;
+}
+
+/// Aliases are not documented in the IR.
+alias Color = vec4<f32>;
+
+/// The fragment shader.
+@fragment
+fn main() -> @location(0) Color {
+    return Color(shade(vec3(1.0)));
+}
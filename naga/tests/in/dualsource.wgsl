@@ -1,4 +1,6 @@
 /* Simple test for multiple output sources from fragment shaders */
+enable dual_source_blending;
+
 struct FragmentOutput{
     @location(0) color: vec4<f32>,
     @location(0) @second_blend_source mask: vec4<f32>,
@@ -0,0 +1,17 @@
+#version 450
+
+out gl_PerVertex {
+    invariant vec4 gl_Position;
+    float gl_PointSize;
+};
+
+layout(location = 0) in vec4 a_position;
+layout(location = 0) invariant out vec4 v_color;
+
+float scale = 2.0;
+
+void main() {
+    vec4 position = a_position * scale + vec4(1.0);
+    v_color = a_position;
+    gl_Position = position;
+}
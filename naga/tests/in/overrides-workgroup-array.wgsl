@@ -0,0 +1,21 @@
+// Workgroup arrays whose element counts are set at pipeline creation.
+
+override tile_size: u32 = 16u;
+@id(1) override halo: i32;
+
+var<workgroup> tile: array<f32, tile_size>;
+var<workgroup> padded: array<vec2<f32>, (tile_size + u32(halo) * 2)>;
+// Same size as `tile` once overrides are resolved.
+var<workgroup> scratch: array<f32, 8>;
+
+@group(0) @binding(0)
+var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(8)
+fn main(@builtin(local_invocation_index) index: u32) {
+    tile[index] = f32(index);
+    padded[index] = vec2(tile[index]);
+    scratch[index] = padded[index].x;
+    workgroupBarrier();
+    output[index] = tile[tile_size - 1 - index] + scratch[index];
+}
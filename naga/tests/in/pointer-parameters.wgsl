@@ -0,0 +1,59 @@
+requires unrestricted_pointer_parameters;
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+}
+
+struct Particles {
+    count: u32,
+    items: array<Particle>,
+}
+
+struct Params {
+    scale: f32,
+    offsets: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> particles: Particles;
+@group(0) @binding(1)
+var<storage, read_write> previous: Particles;
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+var<workgroup> totals: array<f32, 64>;
+
+fn step(particle: ptr<storage, Particle, read_write>, dt: f32) {
+    (*particle).position += (*particle).velocity * dt;
+}
+
+fn advance(buffer: ptr<storage, Particles, read_write>, index: u32, dt: f32) {
+    step(&(*buffer).items[index], dt);
+}
+
+fn total(items: ptr<storage, array<Particle>, read_write>) -> f32 {
+    var sum = 0.0;
+    for (var i = 0u; i < arrayLength(items); i++) {
+        sum += length((*items)[i].velocity);
+    }
+    return sum;
+}
+
+fn offset(scale: ptr<uniform, f32>, offset: ptr<uniform, vec4<f32>>) -> vec4<f32> {
+    return *offset * *scale;
+}
+
+fn accumulate(slot: ptr<workgroup, f32>, value: f32) {
+    *slot += value;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(local_invocation_index) local: u32) {
+    advance(&particles, id.x, 0.5);
+    advance(&previous, id.x, 0.25);
+    advance(&particles, id.y, 0.125);
+    accumulate(&totals[local], total(&particles.items));
+    accumulate(&totals[local], offset(&params.scale, &params.offsets[2]).x);
+    accumulate(&totals[0], f32(local));
+}
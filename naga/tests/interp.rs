@@ -0,0 +1,366 @@
+/*!
+Tests for the reference interpreter.
+*/
+
+#![cfg(all(feature = "wgsl-in", feature = "interp"))]
+
+use naga::{
+    interp::{self, Interpreter},
+    proc::{BoundsCheckPolicies, BoundsCheckPolicy},
+    valid, ResourceBinding,
+};
+
+fn to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+fn to_words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+/// Run `main` from `source` over `workgroups`, with `buffers[i]` bound at
+/// group 0, binding `i`.
+fn dispatch(
+    source: &str,
+    options: interp::Options,
+    workgroups: [u32; 3],
+    buffers: &mut [&mut Vec<u8>],
+    push_constants: Option<&[u8]>,
+) -> Result<(), interp::Error> {
+    let module = naga::front::wgsl::parse_str(source).unwrap_or_else(|e| {
+        panic!(
+            "expected WGSL to parse successfully:\n{}",
+            e.emit_to_string(source)
+        );
+    });
+    let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .expect("validation failed");
+
+    let interpreter = Interpreter::new(&module, &info, options)?;
+    let mut resources = interp::Resources {
+        push_constants,
+        ..Default::default()
+    };
+    for (binding, buffer) in buffers.iter_mut().enumerate() {
+        let binding = ResourceBinding {
+            group: 0,
+            binding: binding as u32,
+        };
+        resources.buffers.insert(binding, buffer.as_mut_slice());
+    }
+    interpreter.dispatch("main", workgroups, &mut resources)
+}
+
+fn run(source: &str, workgroups: [u32; 3], data: &[u32]) -> Vec<u32> {
+    let mut buffer = to_bytes(data);
+    dispatch(
+        source,
+        interp::Options::default(),
+        workgroups,
+        &mut [&mut buffer],
+        None,
+    )
+    .unwrap();
+    to_words(&buffer)
+}
+
+#[test]
+fn global_invocation_id() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        @compute @workgroup_size(2)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            data[id.x] = data[id.x] * 3u + id.x;
+        }
+    ";
+    assert_eq!(run(source, [2, 1, 1], &[10, 20, 30, 40]), [30, 61, 92, 123]);
+}
+
+#[test]
+fn workgroup_memory_and_barriers() {
+    // Reverse each group of four elements, through workgroup memory.
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+        var<workgroup> scratch: array<u32, 4>;
+
+        @compute @workgroup_size(4)
+        fn main(
+            @builtin(global_invocation_id) global: vec3<u32>,
+            @builtin(local_invocation_index) local: u32,
+        ) {
+            scratch[local] = data[global.x];
+            workgroupBarrier();
+            data[global.x] = scratch[3u - local];
+        }
+    ";
+    assert_eq!(
+        run(source, [2, 1, 1], &[1, 2, 3, 4, 5, 6, 7, 8]),
+        [4, 3, 2, 1, 8, 7, 6, 5]
+    );
+}
+
+#[test]
+fn workgroup_uniform_load() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+        var<workgroup> shared_value: u32;
+
+        @compute @workgroup_size(4)
+        fn main(@builtin(local_invocation_index) local: u32) {
+            if local == 2u {
+                shared_value = data[local] + 100u;
+            }
+            let value = workgroupUniformLoad(&shared_value);
+            data[local] = value;
+        }
+    ";
+    assert_eq!(run(source, [1, 1, 1], &[0, 1, 2, 3]), [102; 4]);
+}
+
+#[test]
+fn control_flow() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        fn collatz_steps(start: u32) -> u32 {
+            var n = start;
+            var steps = 0u;
+            loop {
+                if n <= 1u {
+                    break;
+                }
+                steps++;
+                if n % 2u == 0u {
+                    n /= 2u;
+                    continue;
+                }
+                n = 3u * n + 1u;
+            }
+            return steps;
+        }
+
+        fn classify(n: u32) -> u32 {
+            switch n {
+                case 0u, 1u: {
+                    return 100u;
+                }
+                case 2u: {
+                    return 200u;
+                }
+                default: {
+                    var total = 0u;
+                    for (var i = 0u; i < n; i++) {
+                        total += i;
+                    }
+                    return total;
+                }
+            }
+        }
+
+        @compute @workgroup_size(1)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            let n = data[id.x];
+            data[id.x] = collatz_steps(n) * 1000u + classify(n);
+        }
+    ";
+    assert_eq!(
+        run(source, [4, 1, 1], &[1, 2, 6, 27]),
+        [100, 1200, 8015, 111351]
+    );
+}
+
+#[test]
+fn integer_edge_cases() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<i32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let min = data[0];
+            let zero = data[1];
+            data[0] = min - 1;
+            data[1] = min / -1;
+            data[2] = 7 / zero;
+            data[3] = 7 % zero;
+            data[4] = (zero - 7) >> 1u;
+            data[5] = countOneBits(min);
+        }
+    ";
+    let data = [i32::MIN as u32, 0, 0, 0, 0, 0];
+    assert_eq!(
+        run(source, [1, 1, 1], &data),
+        [i32::MAX as u32, i32::MIN as u32, 7, 0, -4i32 as u32, 1]
+    );
+}
+
+#[test]
+fn floats_and_matrices() {
+    let source = "
+        struct Input {
+            m: mat2x3<f32>,
+            v: vec2<f32>,
+        }
+
+        @group(0) @binding(0) var<storage, read_write> input: Input;
+        @group(0) @binding(1) var<storage, read_write> output: array<f32, 6>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let product = input.m * input.v;
+            output[0] = product.x;
+            output[1] = product.y;
+            output[2] = product.z;
+            output[3] = length(vec2(3.0, 4.0));
+            output[4] = dot(input.v, input.v);
+            output[5] = f32(i32(input.v.x - 12.75));
+        }
+    ";
+    // `mat2x3<f32>` columns are padded to 16 bytes, and `Input` to 48.
+    let mut input: Vec<u8> = [
+        1.0f32, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0, 10.0, 100.0, 0.0, 0.0,
+    ]
+    .iter()
+    .flat_map(|f| f.to_le_bytes())
+    .collect();
+    let mut output = vec![0; 6 * 4];
+    dispatch(
+        source,
+        interp::Options::default(),
+        [1, 1, 1],
+        &mut [&mut input, &mut output],
+        None,
+    )
+    .unwrap();
+    let output: Vec<f32> = to_words(&output).into_iter().map(f32::from_bits).collect();
+    assert_eq!(output, [410.0, 520.0, 630.0, 5.0, 10100.0, -2.0]);
+}
+
+#[test]
+fn atomics() {
+    let source = "
+        struct Counters {
+            count: atomic<u32>,
+            max_id: atomic<u32>,
+        }
+
+        @group(0) @binding(0) var<storage, read_write> counters: Counters;
+
+        @compute @workgroup_size(8)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            atomicAdd(&counters.count, 1u);
+            atomicMax(&counters.max_id, id.x);
+        }
+    ";
+    assert_eq!(run(source, [4, 1, 1], &[0, 0]), [32, 31]);
+}
+
+#[test]
+fn push_constants() {
+    let source = "
+        struct PushConstants {
+            offset: u32,
+        }
+
+        var<push_constant> pc: PushConstants;
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            data[id.x] += pc.offset;
+        }
+    ";
+    let mut buffer = to_bytes(&[1, 2]);
+    let push_constants = to_bytes(&[5]);
+    dispatch(
+        source,
+        interp::Options::default(),
+        [2, 1, 1],
+        &mut [&mut buffer],
+        Some(&push_constants),
+    )
+    .unwrap();
+    assert_eq!(to_words(&buffer), [6, 7]);
+}
+
+#[test]
+fn bounds_check_policies() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32, 4>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let i = data[0];
+            data[i] = data[i + 1u] + 10u;
+        }
+    ";
+    let run_with = |policy| {
+        let mut buffer = to_bytes(&[5, 1, 2, 3]);
+        let options = interp::Options {
+            bounds_check_policies: BoundsCheckPolicies {
+                index: policy,
+                buffer: policy,
+                image_load: policy,
+                image_store: policy,
+                binding_array: policy,
+            },
+            ..Default::default()
+        };
+        dispatch(source, options, [1, 1, 1], &mut [&mut buffer], None).map(|()| to_words(&buffer))
+    };
+
+    assert_eq!(
+        run_with(BoundsCheckPolicy::Restrict).unwrap(),
+        [5, 1, 2, 13]
+    );
+    assert_eq!(
+        run_with(BoundsCheckPolicy::ReadZeroSkipWrite).unwrap(),
+        [5, 1, 2, 3]
+    );
+    assert!(matches!(
+        run_with(BoundsCheckPolicy::Unchecked),
+        Err(interp::Error::OutOfBounds)
+    ));
+}
+
+#[test]
+fn errors() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            loop {
+                data[0] += 1u;
+            }
+        }
+    ";
+    assert!(matches!(
+        dispatch(source, interp::Options::default(), [1, 1, 1], &mut [], None),
+        Err(interp::Error::MissingBuffer(_))
+    ));
+    assert!(matches!(
+        dispatch(
+            source,
+            interp::Options::default(),
+            [1, 1, 1],
+            &mut [&mut vec![0; 2]],
+            None
+        ),
+        Err(interp::Error::BufferTooSmall { .. })
+    ));
+
+    let mut buffer = vec![0; 4];
+    let options = interp::Options {
+        max_steps: Some(1000),
+        ..Default::default()
+    };
+    assert!(matches!(
+        dispatch(source, options, [1, 1, 1], &mut [&mut buffer], None),
+        Err(interp::Error::StepLimit)
+    ));
+}
@@ -0,0 +1,428 @@
+/*!
+Tests for the optimizations in `naga::opt`.
+*/
+
+#![cfg(all(feature = "wgsl-in", feature = "wgsl-out", feature = "opt"))]
+
+use naga::valid;
+
+fn validate(module: &naga::Module) -> valid::ModuleInfo {
+    valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(module)
+        .unwrap_or_else(|e| panic!("validation failed: {e:?}"))
+}
+
+fn optimize(source: &str) -> String {
+    let mut module = naga::front::wgsl::parse_str(source).unwrap();
+    validate(&module);
+    naga::opt::optimize(&mut module);
+    let info = validate(&module);
+    naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty()).unwrap()
+}
+
+/// Check that optimizing the snapshot tests' inputs leaves them valid.
+#[test]
+fn snapshot_inputs_stay_valid() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/in");
+    let inputs = |subdirectory: &str| {
+        std::fs::read_dir(dir.join(subdirectory))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>()
+    };
+    let check = |path: &std::path::Path, mut module: naga::Module| {
+        let mut validator =
+            valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all());
+        if validator.validate(&module).is_err() {
+            return;
+        }
+        let mut inlined = module.clone();
+        naga::opt::optimize(&mut module);
+        if let Err(error) = validator.validate(&module) {
+            panic!(
+                "optimized module for {} is invalid: {error:?}",
+                path.display()
+            );
+        }
+
+        let options = naga::opt::InlineOptions {
+            max_size: usize::MAX,
+            ..Default::default()
+        };
+        naga::opt::inline_calls(&mut inlined, &options);
+        if let Err(error) = validator.validate(&inlined) {
+            panic!(
+                "module for {} is invalid after inlining: {error:?}",
+                path.display()
+            );
+        }
+    };
+
+    for path in inputs("") {
+        if path.extension().and_then(|e| e.to_str()) == Some("wgsl") {
+            let source = std::fs::read_to_string(&path).unwrap();
+            if let Ok(module) = naga::front::wgsl::parse_str(&source) {
+                check(&path, module);
+            }
+        }
+    }
+
+    #[cfg(feature = "glsl-in")]
+    for path in inputs("glsl") {
+        let stage = match path.extension().and_then(|e| e.to_str()) {
+            Some("vert") => naga::ShaderStage::Vertex,
+            Some("frag") => naga::ShaderStage::Fragment,
+            Some("comp") => naga::ShaderStage::Compute,
+            _ => continue,
+        };
+        let source = std::fs::read_to_string(&path).unwrap();
+        let mut frontend = naga::front::glsl::Frontend::default();
+        if let Ok(module) = frontend.parse(&stage.into(), &source) {
+            check(&path, module);
+        }
+    }
+
+    #[cfg(feature = "spv-in")]
+    for path in inputs("spv") {
+        if path.extension().and_then(|e| e.to_str()) == Some("spv") {
+            let bytes = std::fs::read(&path).unwrap();
+            let options = naga::front::spv::Options::default();
+            if let Ok(module) = naga::front::spv::parse_u8_slice(&bytes, &options) {
+                check(&path, module);
+            }
+        }
+    }
+}
+
+/// Check that the WGSL `output` contains `expected`.
+#[track_caller]
+fn assert_contains(output: &str, expected: &str) {
+    assert!(
+        output.contains(expected),
+        "expected:\n{expected}\nin output:\n{output}"
+    );
+}
+
+#[test]
+fn constant_branches() {
+    let output = optimize(
+        "
+        const FEATURE: bool = false;
+        const MODE: u32 = 2u;
+
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            if FEATURE {
+                data[0] = 1u;
+            } else {
+                data[0] = 2u;
+            }
+            if !FEATURE && data[1] > 0u {
+                data[1] = 3u;
+            }
+            switch MODE {
+                case 1u: {
+                    data[2] = 1u;
+                }
+                case 2u, 3u: {
+                    data[2] = 2u;
+                }
+                default: {
+                    data[2] = 0u;
+                }
+            }
+            data[3] = select(data[3], 7u, FEATURE || data[3] == 0u);
+        }
+        ",
+    );
+    assert_contains(
+        &output,
+        "\
+fn main() {
+    data[0] = 2u;
+    let _e5 = data[1];
+    if (_e5 > 0u) {
+        data[1] = 3u;
+    }
+    data[2] = 2u;
+    let _e18 = data[3];
+    let _e22 = data[3];
+    data[3] = select(_e18, 7u, (_e22 == 0u));
+    return;
+}",
+    );
+}
+
+#[test]
+fn constant_locals_and_dead_code() {
+    let output = optimize(
+        "
+        @group(0) @binding(0) var<storage, read_write> data: array<f32>;
+
+        fn scaled(x: f32) -> f32 {
+            var scale = 2.0;
+            var offsets = array(1.0, 2.0, 3.0);
+            var unused: f32;
+            var written = x;
+            written = x * 3.0;
+            let also_unused = x + 1.0;
+            return x * scale + offsets[1];
+        }
+
+        @compute @workgroup_size(1)
+        fn main() {
+            data[0] = scaled(data[0]);
+        }
+        ",
+    );
+    assert_contains(
+        &output,
+        "\
+fn scaled(x: f32) -> f32 {
+    return ((x * 2f) + 2f);
+}",
+    );
+}
+
+#[test]
+fn loops() {
+    let output = optimize(
+        "
+        const FEATURE: bool = true;
+
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            var i = 0u;
+            loop {
+                if data[i] == 0u {
+                    continue;
+                }
+                if FEATURE {
+                    break;
+                }
+                let next = i + 1u;
+                continuing {
+                    i = next;
+                    break if i >= 4u;
+                }
+            }
+            loop {
+                if FEATURE {
+                    break;
+                }
+                let next = i + 1u;
+                continuing {
+                    i = next;
+                    break if i >= 4u;
+                }
+            }
+            data[0] = i;
+        }
+        ",
+    );
+    // The `continuing` block of the first loop uses `next`, so the `break`
+    // must not make the statement that emits it unreachable. The second
+    // loop always breaks in its first iteration.
+    assert_contains(
+        &output,
+        "\
+fn main() {
+    var i: u32 = 0u;
+
+    loop {
+        let _e3 = i;
+        let _e5 = data[_e3];
+        if (_e5 == 0u) {
+            continue;
+        }
+        if FEATURE {
+            break;
+        }
+        let _e9 = i;
+        let next = (_e9 + 1u);
+        continuing {
+            i = next;
+            let _e12 = i;
+            break if (_e12 >= 4u);
+        }
+    }
+    let _e17 = i;
+    data[0] = _e17;
+    return;
+}",
+    );
+}
+
+fn inline(source: &str, options: &naga::opt::InlineOptions) -> String {
+    let mut module = naga::front::wgsl::parse_str(source).unwrap();
+    validate(&module);
+    naga::opt::inline_calls(&mut module, options);
+    let info = validate(&module);
+    naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty()).unwrap()
+}
+
+#[test]
+fn inline_calls() {
+    let output = inline(
+        "
+        @group(0) @binding(0) var<storage, read_write> data: array<f32>;
+
+        fn square(x: f32) -> f32 {
+            return x * x;
+        }
+
+        fn clamped(x: f32, limit: f32) -> f32 {
+            if x > limit {
+                return limit;
+            }
+            return x;
+        }
+
+        fn sign_of(x: f32) -> f32 {
+            if x < 0.0 {
+                return -1.0;
+            } else {
+                return 1.0;
+            }
+        }
+
+        fn bump(p: ptr<function, f32>, by: f32) {
+            var amount = by;
+            *p += amount;
+        }
+
+        @compute @workgroup_size(1)
+        fn main() {
+            var value = square(data[0]);
+            bump(&value, 1.0);
+            data[1] = sign_of(value) + clamped(value, 4.0);
+        }
+        ",
+        &Default::default(),
+    );
+    // The functions are no longer called, so they have been removed.
+    assert!(!output.contains("fn square"), "{output}");
+    assert_contains(
+        &output,
+        "\
+fn main() {
+    var value: f32;
+    var sign_of_result: f32;
+    var clamped_result: f32;
+    var amount: f32;
+
+    let x = data[0];
+    value = (x * x);
+    amount = 1f;
+    let _e22 = amount;
+    let _e23 = value;
+    value = (_e23 + _e22);
+    let x_1 = value;
+    if (x_1 < 0f) {
+        sign_of_result = -1f;
+    } else {
+        sign_of_result = 1f;
+    }
+    let _e14 = sign_of_result;
+    let x_2 = value;
+    if (x_2 > 4f) {
+        clamped_result = 4f;
+    } else {
+        clamped_result = x_2;
+    }
+    let _e19 = clamped_result;
+    data[1] = (_e14 + _e19);
+    return;
+}",
+    );
+}
+
+#[test]
+fn inline_calls_in_loops() {
+    let output = inline(
+        "
+        @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+        fn count_bits(x: u32) -> u32 {
+            var count = 0u;
+            var rest: u32;
+            rest = x;
+            while rest != 0u {
+                count += rest & 1u;
+                rest >>= 1u;
+            }
+            return count;
+        }
+
+        @compute @workgroup_size(1)
+        fn main() {
+            for (var i = 0u; i < 4u; i++) {
+                data[i] = count_bits(data[i]);
+            }
+        }
+        ",
+        &Default::default(),
+    );
+    // Each call must start with fresh local variables.
+    assert_contains(
+        &output,
+        "\
+        {
+            let _e6 = i;
+            let _e9 = i;
+            let x = data[_e9];
+            count = 0u;
+            rest = u32();
+            rest = x;
+            loop {",
+    );
+}
+
+#[test]
+fn inline_hints() {
+    let source = "
+        fn one() -> f32 {
+            return 1.0;
+        }
+
+        fn two() -> f32 {
+            return one() + one();
+        }
+
+        @fragment
+        fn main() -> @location(0) vec4<f32> {
+            return vec4(two());
+        }
+    ";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let handle = |name: &str| {
+        module
+            .functions
+            .iter()
+            .find(|&(_, function)| function.name.as_deref() == Some(name))
+            .unwrap()
+            .0
+    };
+
+    let mut options = naga::opt::InlineOptions {
+        max_size: 0,
+        ..Default::default()
+    };
+    options
+        .hints
+        .insert(handle("one"), naga::opt::Inline::Always);
+    let output = inline(source, &options);
+    assert!(!output.contains("fn one"), "{output}");
+    assert_contains(&output, "fn two() -> f32 {\n    return (1f + 1f);\n}");
+
+    let mut options = naga::opt::InlineOptions::default();
+    options
+        .hints
+        .insert(handle("two"), naga::opt::Inline::Never);
+    let output = inline(source, &options);
+    assert_contains(&output, "let _e0 = two();");
+}
@@ -0,0 +1,19 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+/// The number of lights to shade with.
+const uint light_count = 4u;
+/// An override whose type is inferred from its initializer.
+const float falloff = 0.5;
+/// Never given a value by the pipeline.
+const float exposure = 1.0;
+
+
+void main() {
+    float t = 2.0;
+}
+
@@ -0,0 +1,36 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+/// A light source.
+///
+/// Lights are additive.
+struct Light {
+    /// Linear color.
+    vec3 color;
+    float intensity;
+    float range;
+};
+/// Scales every result.
+const float scale = 2.0;
+
+/// The scene's light.
+uniform Light_block_0Fragment { Light _group_0_binding_0_fs; };
+
+layout(location = 0) out vec4 _fs2p_location0;
+
+/// Shade a surface with normal `n`.
+float shade(vec3 n) {
+    vec3 _e3 = _group_0_binding_0_fs.color;
+    float _e7 = _group_0_binding_0_fs.intensity;
+    return ((dot(n, _e3) * _e7) * scale);
+}
+
+/// The fragment shader.
+void main() {
+    float _e2 = shade(vec3(1.0));
+    _fs2p_location0 = vec4(_e2);
+    return;
+}
+
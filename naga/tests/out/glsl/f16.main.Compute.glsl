@@ -0,0 +1,103 @@
+#version 450 core
+#extension GL_ARB_compute_shader : require
+#extension GL_ARB_shader_storage_buffer_object : require
+#extension GL_EXT_shader_explicit_arithmetic_types_float16 : require
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+struct UniformCompatible {
+    uint val_u32_;
+    int val_i32_;
+    float val_f32_;
+    float16_t val_f16_;
+    f16vec2 val_f16_2_;
+    f16vec3 val_f16_3_;
+    f16vec4 val_f16_4_;
+    float16_t final_value;
+    f16mat2x2 val_mat2x2_;
+    f16mat4x4 val_mat4x4_;
+};
+struct StorageCompatible {
+    float16_t val_f16_array_2_[2];
+};
+const float16_t constant_variable = 15.203125HF;
+
+uniform UniformCompatible_block_0Compute { UniformCompatible _group_0_binding_0_cs; };
+
+layout(std430) readonly buffer UniformCompatible_block_1Compute { UniformCompatible _group_0_binding_1_cs; };
+
+layout(std430) readonly buffer StorageCompatible_block_2Compute { StorageCompatible _group_0_binding_2_cs; };
+
+layout(std430) buffer UniformCompatible_block_3Compute { UniformCompatible _group_0_binding_3_cs; };
+
+layout(std430) buffer StorageCompatible_block_4Compute { StorageCompatible _group_0_binding_4_cs; };
+
+
+float16_t f16_function(float16_t x) {
+    float16_t val = 15.203125HF;
+    float16_t _e4 = val;
+    val = (_e4 + -33344.0HF);
+    float16_t _e6 = val;
+    float16_t _e9 = val;
+    val = (_e9 + (_e6 + 5.0HF));
+    float _e13 = _group_0_binding_0_cs.val_f32_;
+    float16_t _e14 = val;
+    float16_t _e18 = val;
+    val = (_e18 + float16_t((_e13 + float(_e14))));
+    float16_t _e22 = _group_0_binding_0_cs.val_f16_;
+    float16_t _e25 = val;
+    val = (_e25 + f16vec3(_e22).z);
+    float16_t _e31 = _group_0_binding_0_cs.val_f16_;
+    float16_t _e34 = _group_0_binding_1_cs.val_f16_;
+    _group_0_binding_3_cs.val_f16_ = (_e31 + _e34);
+    f16vec2 _e40 = _group_0_binding_0_cs.val_f16_2_;
+    f16vec2 _e43 = _group_0_binding_1_cs.val_f16_2_;
+    _group_0_binding_3_cs.val_f16_2_ = (_e40 + _e43);
+    f16vec3 _e49 = _group_0_binding_0_cs.val_f16_3_;
+    f16vec3 _e52 = _group_0_binding_1_cs.val_f16_3_;
+    _group_0_binding_3_cs.val_f16_3_ = (_e49 + _e52);
+    f16vec4 _e58 = _group_0_binding_0_cs.val_f16_4_;
+    f16vec4 _e61 = _group_0_binding_1_cs.val_f16_4_;
+    _group_0_binding_3_cs.val_f16_4_ = (_e58 + _e61);
+    f16mat2x2 _e67 = _group_0_binding_0_cs.val_mat2x2_;
+    _group_0_binding_3_cs.val_mat2x2_ = _e67;
+    f16mat4x4 _e72 = _group_0_binding_0_cs.val_mat4x4_;
+    _group_0_binding_3_cs.val_mat4x4_ = _e72;
+    float16_t _e77[2] = _group_0_binding_2_cs.val_f16_array_2_;
+    _group_0_binding_4_cs.val_f16_array_2_ = _e77;
+    float16_t _e78 = val;
+    float16_t _e80 = val;
+    val = (_e80 + abs(_e78));
+    float16_t _e82 = val;
+    float16_t _e83 = val;
+    float16_t _e84 = val;
+    float16_t _e86 = val;
+    val = (_e86 + clamp(_e82, _e83, _e84));
+    float16_t _e88 = val;
+    float16_t _e90 = val;
+    float16_t _e93 = val;
+    val = (_e93 + dot(f16vec2(_e88), f16vec2(_e90)));
+    float16_t _e95 = val;
+    float16_t _e96 = val;
+    float16_t _e98 = val;
+    val = (_e98 + max(_e95, _e96));
+    float16_t _e100 = val;
+    float16_t _e101 = val;
+    float16_t _e103 = val;
+    val = (_e103 + min(_e100, _e101));
+    float16_t _e105 = val;
+    float16_t _e107 = val;
+    val = (_e107 + sign(_e105));
+    float16_t _e110 = val;
+    val = (_e110 + 1.0HF);
+    float16_t _e113 = val;
+    val = (_e113 + 5.0HF);
+    float16_t _e115 = val;
+    return _e115;
+}
+
+void main() {
+    float16_t _e3 = f16_function(2.0HF);
+    _group_0_binding_3_cs.final_value = _e3;
+    return;
+}
+
@@ -0,0 +1,41 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 8, local_size_y = 1, local_size_z = 1) in;
+
+const uint tile_size = 8u;
+const int halo = 1;
+
+shared float tile[8];
+
+shared vec2 padded[10];
+
+shared float scratch[8];
+
+layout(std430) buffer type_6_block_0Compute { float _group_0_binding_0_cs[]; };
+
+
+void main() {
+    if (gl_LocalInvocationID == uvec3(0u)) {
+        tile = float[8](0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        padded = vec2[10](vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0), vec2(0.0));
+        scratch = float[8](0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    }
+    memoryBarrierShared();
+    barrier();
+    uint index = gl_LocalInvocationIndex;
+    tile[index] = float(index);
+    float _e8 = tile[index];
+    padded[index] = vec2(_e8);
+    float _e15 = padded[index].x;
+    scratch[index] = _e15;
+    memoryBarrierShared();
+    barrier();
+    float _e25 = tile[(7u - index)];
+    float _e28 = scratch[index];
+    _group_0_binding_0_cs[index] = (_e25 + _e28);
+    return;
+}
+
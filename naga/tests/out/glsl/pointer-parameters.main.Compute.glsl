@@ -0,0 +1,117 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+struct Params {
+    float scale;
+    vec4 offsets[4];
+};
+layout(std430) buffer Particles_block_0Compute {
+    uint count;
+    Particle items[];
+} _group_0_binding_0_cs;
+
+layout(std430) buffer Particles_block_1Compute {
+    uint count;
+    Particle items[];
+} _group_0_binding_1_cs;
+
+uniform Params_block_2Compute { Params _group_0_binding_2_cs; };
+
+shared float totals[64];
+
+
+void step_particles(float dt, uint particle_index) {
+    vec2 _e7 = _group_0_binding_0_cs.items[particle_index].velocity;
+    vec2 _e9 = _group_0_binding_0_cs.items[particle_index].position;
+    _group_0_binding_0_cs.items[particle_index].position = (_e9 + (_e7 * dt));
+    return;
+}
+
+void advance_particles(uint index, float dt_1) {
+    step_particles(dt_1, index);
+    return;
+}
+
+void step_previous(float dt_2, uint particle_index_1) {
+    vec2 _e7 = _group_0_binding_1_cs.items[particle_index_1].velocity;
+    vec2 _e9 = _group_0_binding_1_cs.items[particle_index_1].position;
+    _group_0_binding_1_cs.items[particle_index_1].position = (_e9 + (_e7 * dt_2));
+    return;
+}
+
+void advance_previous(uint index_1, float dt_3) {
+    step_previous(dt_3, index_1);
+    return;
+}
+
+float total_particles() {
+    float sum = 0.0;
+    uint i = 0u;
+    bool loop_init = true;
+    while(true) {
+        if (!loop_init) {
+            uint _e17 = i;
+            i = (_e17 + 1u);
+        }
+        loop_init = false;
+        uint _e6 = i;
+        if ((_e6 < uint(_group_0_binding_0_cs.items.length()))) {
+        } else {
+            break;
+        }
+        {
+            uint _e9 = i;
+            vec2 _e12 = _group_0_binding_0_cs.items[_e9].velocity;
+            float _e14 = sum;
+            sum = (_e14 + length(_e12));
+        }
+    }
+    float _e19 = sum;
+    return _e19;
+}
+
+void accumulate_totals(float value, uint slot_index) {
+    float _e4 = totals[slot_index];
+    totals[slot_index] = (_e4 + value);
+    return;
+}
+
+vec4 offset_params_params() {
+    vec4 _e5 = _group_0_binding_2_cs.offsets[2];
+    float _e6 = _group_0_binding_2_cs.scale;
+    return (_e5 * _e6);
+}
+
+void accumulate_totals_1(float value_1) {
+    float _e3 = totals[0];
+    totals[0] = (_e3 + value_1);
+    return;
+}
+
+void main() {
+    if (gl_LocalInvocationID == uvec3(0u)) {
+        totals = float[64](0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    }
+    memoryBarrierShared();
+    barrier();
+    uvec3 id = gl_GlobalInvocationID;
+    uint local = gl_LocalInvocationIndex;
+    advance_particles(id.x, 0.5);
+    advance_previous(id.x, 0.25);
+    advance_particles(id.y, 0.125);
+    float _e15 = total_particles();
+    accumulate_totals(_e15, local);
+    vec4 _e23 = offset_params_params();
+    accumulate_totals(_e23.x, local);
+    accumulate_totals_1(float(local));
+    return;
+}
+
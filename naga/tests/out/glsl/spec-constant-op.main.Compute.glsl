@@ -0,0 +1,45 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+struct Output {
+    uvec2 size;
+    uvec2 swapped;
+    uint first;
+    uint pick;
+    uint shifted;
+    float scale;
+    uint flip;
+};
+const uint BLOCK = 16u;
+const float SCALE = 2.0;
+const bool FLIP = true;
+const uint BLOCK2_ = 32u;
+const uint SHIFTED = 32u;
+const uint FIRST = 16u;
+const bool SMALL = false;
+const uint PICK = 32u;
+const float NEG_SCALE = -2.0;
+const bool NOT_FLIP = false;
+
+layout(std430) buffer Output_block_0Compute { Output _group_0_binding_0_cs; };
+
+
+void main_1() {
+    _group_0_binding_0_cs.size = uvec2(16u, 32u);
+    _group_0_binding_0_cs.swapped = uvec2(32u, 16u);
+    _group_0_binding_0_cs.first = FIRST;
+    _group_0_binding_0_cs.pick = PICK;
+    _group_0_binding_0_cs.shifted = SHIFTED;
+    _group_0_binding_0_cs.scale = NEG_SCALE;
+    _group_0_binding_0_cs.flip = (NOT_FLIP ? 1u : 0u);
+    return;
+}
+
+void main() {
+    main_1();
+}
+
@@ -0,0 +1,172 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+struct Data {
+    int input_;
+    int output_;
+};
+layout(std430) buffer Data_block_0Compute { Data _group_0_binding_0_cs; };
+
+
+int search(int param) {
+    int phi_28_ = 0;
+    int local = 0;
+    int local_1 = 0;
+    int local_2 = 0;
+    int phi_38_ = 0;
+    int local_3 = 0;
+    phi_28_ = 0;
+    while(true) {
+        int _e6 = phi_28_;
+        local = _e6;
+        local_1 = _e6;
+        local_2 = _e6;
+        local_3 = _e6;
+        if ((_e6 < param)) {
+            int _e9 = local;
+            int _e11 = local_1;
+            if (((_e9 * _e11) == 49)) {
+                int _e25 = local_3;
+                phi_38_ = _e25;
+            } else {
+                int _e15 = local_2;
+                phi_28_ = (_e15 + 1);
+                continue;
+            }
+        } else {
+            phi_38_ = -1;
+        }
+        int _e18 = phi_38_;
+        return _e18;
+    }
+}
+
+int nested(int param_1) {
+    int local_4 = 0;
+    int local_5 = 0;
+    int local_6 = 0;
+    int local_7 = 0;
+    int local_8 = 0;
+    int local_9 = 0;
+    int local_10 = 0;
+    uint branch_destination = 0u;
+    while(true) {
+        int _e11 = local_5;
+        local_7 = _e11;
+        local_10 = _e11;
+        if ((_e11 < param_1)) {
+            local_6 = 0;
+            while(true) {
+                int _e13 = local_6;
+                int _e15 = local_7;
+                int _e16 = (_e15 * _e13);
+                local_8 = _e16;
+                local_9 = _e13;
+                if ((_e16 == 6)) {
+                    break;
+                } else {
+                    int _e18 = local_4;
+                    int _e20 = local_8;
+                    local_4 = (_e18 + _e20);
+                    int _e23 = local_9;
+                    int _e24 = (_e23 + 1);
+                    local_6 = _e24;
+                    if ((_e24 < 4)) {
+                    } else {
+                        int _e27 = local_10;
+                        local_5 = (_e27 + 1);
+                        branch_destination = 1u;
+                        break;
+                    }
+                }
+            }
+            uint _e35 = branch_destination;
+            if ((_e35 == 1u)) {
+                branch_destination = 0u;
+                continue;
+            }
+        }
+        int _e29 = local_4;
+        return _e29;
+    }
+}
+
+int classify(int param_2) {
+    int phi_71_ = 0;
+    uint branch_destination_1 = 0u;
+    switch(0) {
+        default: {
+            switch(0) {
+                default: {
+                    switch(0) {
+                        default: {
+                            switch(param_2) {
+                                case 0: {
+                                    if ((param_2 < 0)) {
+                                        branch_destination_1 = 1u;
+                                        break;
+                                    } else {
+                                        break;
+                                    }
+                                    break;
+                                }
+                                case 1:
+                                case 2: {
+                                    break;
+                                }
+                                case 4: {
+                                    return -1;
+                                }
+                                case 3:
+                                default: {
+                                    branch_destination_1 = 1u;
+                                    break;
+                                }
+                            }
+                            uint _e17 = branch_destination_1;
+                            if ((_e17 == 1u)) {
+                                break;
+                            }
+                            break;
+                        }
+                    }
+                    uint _e22 = branch_destination_1;
+                    if ((_e22 == 1u)) {
+                        branch_destination_1 = 0u;
+                        break;
+                    }
+                    phi_71_ = (param_2 + 10);
+                    branch_destination_1 = 2u;
+                    break;
+                }
+            }
+            uint _e31 = branch_destination_1;
+            if ((_e31 == 2u)) {
+                branch_destination_1 = 0u;
+                break;
+            }
+            phi_71_ = (param_2 * 2);
+            break;
+        }
+    }
+    int _e9 = phi_71_;
+    return _e9;
+}
+
+void main_1() {
+    int _e2 = _group_0_binding_0_cs.input_;
+    int _e3 = search(_e2);
+    int _e4 = nested(_e2);
+    int _e5 = classify(_e2);
+    _group_0_binding_0_cs.output_ = ((_e3 + _e4) + _e5);
+    return;
+}
+
+void main() {
+    main_1();
+}
+
@@ -0,0 +1,21 @@
+struct Data {
+    float a;
+    uint b;
+};
+
+RWByteAddressBuffer data : register(u0);
+
+void main_1()
+{
+    float _e3 = asfloat(data.Load(0));
+    uint _e5 = asuint(data.Load(4));
+    printf("a = %f, b = %u\n", _e3, _e5);
+    data.Store(4, asuint((_e5 + 1u)));
+    return;
+}
+
+[numthreads(1, 1, 1)]
+void main()
+{
+    main_1();
+}
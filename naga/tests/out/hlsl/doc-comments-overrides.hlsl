@@ -0,0 +1,13 @@
+/// The number of lights to shade with.
+static const uint light_count = 4u;
+/// An override whose type is inferred from its initializer.
+static const float falloff = 0.5;
+/// Never given a value by the pipeline.
+static const float exposure = 1.0;
+
+[numthreads(1, 1, 1)]
+void main()
+{
+    float t = 2.0;
+
+}
@@ -0,0 +1,33 @@
+/// A light source.
+///
+/// Lights are additive.
+struct Light {
+    /// Linear color.
+    float3 color;
+    float intensity;
+    float range;
+    int _end_pad_0;
+    int _end_pad_1;
+    int _end_pad_2;
+};
+
+/// Scales every result.
+static const float scale = 2.0;
+
+/// The scene's light.
+cbuffer light : register(b0) { Light light; }
+
+/// Shade a surface with normal `n`.
+float shade(float3 n)
+{
+    float3 _e3 = light.color;
+    float _e7 = light.intensity;
+    return ((dot(n, _e3) * _e7) * scale);
+}
+
+/// The fragment shader.
+float4 main() : SV_Target0
+{
+    const float _e2 = shade((1.0).xxx);
+    return (_e2).xxxx;
+}
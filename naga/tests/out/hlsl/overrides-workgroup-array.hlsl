@@ -0,0 +1,28 @@
+static const uint tile_size = 8u;
+static const int halo = 1;
+
+groupshared float tile[8];
+groupshared float2 padded[10];
+groupshared float scratch[8];
+RWByteAddressBuffer output : register(u0);
+
+[numthreads(8, 1, 1)]
+void main(uint index : SV_GroupIndex, uint3 __local_invocation_id : SV_GroupThreadID)
+{
+    if (all(__local_invocation_id == uint3(0u, 0u, 0u))) {
+        tile = (float[8])0;
+        padded = (float2[10])0;
+        scratch = (float[8])0;
+    }
+    GroupMemoryBarrierWithGroupSync();
+    tile[index] = float(index);
+    float _e8 = tile[index];
+    padded[index] = (_e8).xx;
+    float _e15 = padded[index].x;
+    scratch[index] = _e15;
+    GroupMemoryBarrierWithGroupSync();
+    float _e25 = tile[(7u - index)];
+    float _e28 = scratch[index];
+    output.Store(index*4, asuint((_e25 + _e28)));
+    return;
+}
@@ -0,0 +1,119 @@
+struct Particle {
+    float2 position;
+    float2 velocity;
+};
+
+struct Params {
+    float scale;
+    int _pad1_0;
+    int _pad1_1;
+    int _pad1_2;
+    float4 offsets[4];
+};
+
+RWByteAddressBuffer particles : register(u0);
+RWByteAddressBuffer previous : register(u1);
+cbuffer params : register(b2) { Params params; }
+groupshared float totals[64];
+
+void step_particles(float dt, uint particle_index)
+{
+    float2 _e7 = asfloat(particles.Load2(8+particle_index*16+8));
+    float2 _e9 = asfloat(particles.Load2(0+particle_index*16+8));
+    particles.Store2(0+particle_index*16+8, asuint((_e9 + (_e7 * dt))));
+    return;
+}
+
+void advance_particles(uint index, float dt_1)
+{
+    step_particles(dt_1, index);
+    return;
+}
+
+void step_previous(float dt_2, uint particle_index_1)
+{
+    float2 _e7 = asfloat(previous.Load2(8+particle_index_1*16+8));
+    float2 _e9 = asfloat(previous.Load2(0+particle_index_1*16+8));
+    previous.Store2(0+particle_index_1*16+8, asuint((_e9 + (_e7 * dt_2))));
+    return;
+}
+
+void advance_previous(uint index_1, float dt_3)
+{
+    step_previous(dt_3, index_1);
+    return;
+}
+
+uint NagaBufferLengthRW(RWByteAddressBuffer buffer)
+{
+    uint ret;
+    buffer.GetDimensions(ret);
+    return ret;
+}
+
+float total_particles()
+{
+    float sum = 0.0;
+    uint i = 0u;
+
+    bool loop_init = true;
+    while(true) {
+        if (!loop_init) {
+            uint _e17 = i;
+            i = (_e17 + 1u);
+        }
+        loop_init = false;
+        uint _e6 = i;
+        if ((_e6 < ((NagaBufferLengthRW(particles) - 8) / 16))) {
+        } else {
+            break;
+        }
+        {
+            uint _e9 = i;
+            float2 _e12 = asfloat(particles.Load2(8+_e9*16+8));
+            float _e14 = sum;
+            sum = (_e14 + length(_e12));
+        }
+    }
+    float _e19 = sum;
+    return _e19;
+}
+
+void accumulate_totals(float value, uint slot_index)
+{
+    float _e4 = totals[slot_index];
+    totals[slot_index] = (_e4 + value);
+    return;
+}
+
+float4 offset_params_params()
+{
+    float4 _e5 = params.offsets[2];
+    float _e6 = params.scale;
+    return (_e5 * _e6);
+}
+
+void accumulate_totals_1(float value_1)
+{
+    float _e3 = totals[0];
+    totals[0] = (_e3 + value_1);
+    return;
+}
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID, uint local : SV_GroupIndex, uint3 __local_invocation_id : SV_GroupThreadID)
+{
+    if (all(__local_invocation_id == uint3(0u, 0u, 0u))) {
+        totals = (float[64])0;
+    }
+    GroupMemoryBarrierWithGroupSync();
+    advance_particles(id.x, 0.5);
+    advance_previous(id.x, 0.25);
+    advance_particles(id.y, 0.125);
+    const float _e15 = total_particles();
+    accumulate_totals(_e15, local);
+    const float4 _e23 = offset_params_params();
+    accumulate_totals(_e23.x, local);
+    accumulate_totals_1(float(local));
+    return;
+}
@@ -0,0 +1,41 @@
+struct Output {
+    uint2 size;
+    uint2 swapped;
+    uint first;
+    uint pick;
+    uint shifted;
+    float scale;
+    uint flip;
+    int _end_pad_0;
+};
+
+static const uint BLOCK = 16u;
+static const float SCALE = 2.0;
+static const bool FLIP = true;
+static const uint BLOCK2_ = 32u;
+static const uint SHIFTED = 32u;
+static const uint FIRST = 16u;
+static const bool SMALL = false;
+static const uint PICK = 32u;
+static const float NEG_SCALE = -2.0;
+static const bool NOT_FLIP = false;
+
+RWByteAddressBuffer out_ : register(u0);
+
+void main_1()
+{
+    out_.Store2(0, asuint(uint2(16u, 32u)));
+    out_.Store2(8, asuint(uint2(32u, 16u)));
+    out_.Store(16, asuint(FIRST));
+    out_.Store(20, asuint(PICK));
+    out_.Store(24, asuint(SHIFTED));
+    out_.Store(28, asuint(NEG_SCALE));
+    out_.Store(32, asuint((NOT_FLIP ? 1u : 0u)));
+    return;
+}
+
+[numthreads(1, 1, 1)]
+void main()
+{
+    main_1();
+}
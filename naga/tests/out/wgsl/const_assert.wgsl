@@ -0,0 +1,7 @@
+const x: i32 = 1i;
+const y: i32 = 2i;
+
+fn foo() {
+    return;
+}
+
@@ -0,0 +1,19 @@
+struct Data {
+    a: f32,
+    b: u32,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> data: Data;
+
+fn main_1() {
+    let _e3 = data.a;
+    let _e5 = data.b;
+    data.b = (_e5 + 1u);
+    return;
+}
+
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    main_1();
+}
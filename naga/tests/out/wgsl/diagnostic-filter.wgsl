@@ -0,0 +1,40 @@
+diagnostic(off, derivative_uniformity);
+diagnostic(warning, naga.unknown_rule);
+
+fn filtered(x_1: f32) -> f32 {
+    @diagnostic(off, derivative_uniformity) {
+        if (x_1 > 0f) {
+            let _e3 = dpdx(x_1);
+            return _e3;
+        }
+    }
+    loop @diagnostic(info, derivative_uniformity) {
+        if (x_1 > 1f) {
+            let _e6 = dpdy(x_1);
+            return _e6;
+        }
+        continuing {
+            break if (x_1 < 2f);
+        }
+    }
+    switch i32(x_1) {
+        case 1: @diagnostic(off, derivative_uniformity) {
+            let _e10 = fwidth(x_1);
+            return _e10;
+        }
+        default: @diagnostic(warning, derivative_uniformity) {
+            let _e11 = dpdx(x_1);
+            return _e11;
+        }
+    }
+}
+
+@diagnostic(warning, derivative_uniformity)
+@fragment 
+fn main(@location(0) x: f32) -> @location(0) f32 {
+    if (x > 0f) {
+        let _e3 = dpdx(x);
+        return _e3;
+    }
+    return 0f;
+}
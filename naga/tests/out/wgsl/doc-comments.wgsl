@@ -0,0 +1,30 @@
+/// A light source.
+///
+/// Lights are additive.
+struct Light {
+    /// Linear color.
+    color: vec3<f32>,
+    intensity: f32,
+    range: f32,
+}
+
+/// Scales every result.
+const scale: f32 = 2f;
+
+/// The scene's light.
+@group(0) @binding(0) 
+var<uniform> light: Light;
+
+/// Shade a surface with normal `n`.
+fn shade(n: vec3<f32>) -> f32 {
+    let _e3 = light.color;
+    let _e7 = light.intensity;
+    return ((dot(n, _e3) * _e7) * scale);
+}
+
+/// The fragment shader.
+@fragment 
+fn main() -> @location(0) vec4<f32> {
+    let _e2 = shade(vec3(1f));
+    return vec4(_e2);
+}
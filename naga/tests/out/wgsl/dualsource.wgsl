@@ -1,3 +1,5 @@
+enable dual_source_blending;
+
 struct FragmentOutput {
     @location(0) color: vec4<f32>,
     @location(0) @second_blend_source mask: vec4<f32>,
@@ -0,0 +1,103 @@
+enable f16;
+
+struct UniformCompatible {
+    val_u32_: u32,
+    val_i32_: i32,
+    val_f32_: f32,
+    val_f16_: f16,
+    val_f16_2_: vec2<f16>,
+    val_f16_3_: vec3<f16>,
+    val_f16_4_: vec4<f16>,
+    final_value: f16,
+    val_mat2x2_: mat2x2<f16>,
+    val_mat4x4_: mat4x4<f16>,
+}
+
+struct StorageCompatible {
+    val_f16_array_2_: array<f16, 2>,
+}
+
+const constant_variable: f16 = 15.203125h;
+
+var<private> private_variable: f16 = 1.0h;
+@group(0) @binding(0) 
+var<uniform> input_uniform: UniformCompatible;
+@group(0) @binding(1) 
+var<storage> input_storage: UniformCompatible;
+@group(0) @binding(2) 
+var<storage> input_arrays: StorageCompatible;
+@group(0) @binding(3) 
+var<storage, read_write> output: UniformCompatible;
+@group(0) @binding(4) 
+var<storage, read_write> output_arrays: StorageCompatible;
+
+fn f16_function(x: f16) -> f16 {
+    var val: f16 = 15.203125h;
+
+    let _e4 = val;
+    val = (_e4 + -33344.0h);
+    let _e6 = val;
+    let _e9 = val;
+    val = (_e9 + (_e6 + 5.0h));
+    let _e13 = input_uniform.val_f32_;
+    let _e14 = val;
+    let _e18 = val;
+    val = (_e18 + f16((_e13 + f32(_e14))));
+    let _e22 = input_uniform.val_f16_;
+    let _e25 = val;
+    val = (_e25 + vec3(_e22).z);
+    let _e31 = input_uniform.val_f16_;
+    let _e34 = input_storage.val_f16_;
+    output.val_f16_ = (_e31 + _e34);
+    let _e40 = input_uniform.val_f16_2_;
+    let _e43 = input_storage.val_f16_2_;
+    output.val_f16_2_ = (_e40 + _e43);
+    let _e49 = input_uniform.val_f16_3_;
+    let _e52 = input_storage.val_f16_3_;
+    output.val_f16_3_ = (_e49 + _e52);
+    let _e58 = input_uniform.val_f16_4_;
+    let _e61 = input_storage.val_f16_4_;
+    output.val_f16_4_ = (_e58 + _e61);
+    let _e67 = input_uniform.val_mat2x2_;
+    output.val_mat2x2_ = _e67;
+    let _e72 = input_uniform.val_mat4x4_;
+    output.val_mat4x4_ = _e72;
+    let _e77 = input_arrays.val_f16_array_2_;
+    output_arrays.val_f16_array_2_ = _e77;
+    let _e78 = val;
+    let _e80 = val;
+    val = (_e80 + abs(_e78));
+    let _e82 = val;
+    let _e83 = val;
+    let _e84 = val;
+    let _e86 = val;
+    val = (_e86 + clamp(_e82, _e83, _e84));
+    let _e88 = val;
+    let _e90 = val;
+    let _e93 = val;
+    val = (_e93 + dot(vec2(_e88), vec2(_e90)));
+    let _e95 = val;
+    let _e96 = val;
+    let _e98 = val;
+    val = (_e98 + max(_e95, _e96));
+    let _e100 = val;
+    let _e101 = val;
+    let _e103 = val;
+    val = (_e103 + min(_e100, _e101));
+    let _e105 = val;
+    let _e107 = val;
+    val = (_e107 + sign(_e105));
+    let _e110 = val;
+    val = (_e110 + 1.0h);
+    let _e113 = val;
+    val = (_e113 + 5.0h);
+    let _e115 = val;
+    return _e115;
+}
+
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    let _e3 = f16_function(2.0h);
+    output.final_value = _e3;
+    return;
+}
@@ -0,0 +1,136 @@
+struct Params {
+    offset: vec4<f32>,
+}
+
+struct gl_PerVertex {
+    gl_Position: vec4<f32>,
+}
+
+struct GeometryInputVertex {
+    gl_Position: vec4<f32>,
+    v_color: vec3<f32>,
+}
+
+struct GeometryOutputVertex {
+    gl_Position: vec4<f32>,
+    f_color: vec3<f32>,
+}
+
+struct GeometryOutput {
+    vertex_count: atomic<u32>,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+    vertices: array<GeometryOutputVertex>,
+}
+
+var<private> v_color: array<vec3<f32>, 3>;
+var<private> f_color: vec3<f32>;
+@group(1) @binding(0) 
+var<uniform> global: Params;
+var<private> gl_Position: vec4<f32>;
+var<private> gl_in: array<gl_PerVertex, 3>;
+var<private> gl_PrimitiveIDIn: u32;
+@group(0) @binding(0) 
+var<storage> geometry_input: array<GeometryInputVertex>;
+@group(0) @binding(1) 
+var<storage, read_write> geometry_output: GeometryOutput;
+var<private> geometry_emitted: u32;
+var<private> geometry_strip: array<GeometryOutputVertex, 2>;
+var<private> geometry_strip_length: u32;
+
+fn EmitVertex() {
+    let _e8 = geometry_emitted;
+    if (_e8 >= 6u) {
+        return;
+    }
+    geometry_emitted = (_e8 + 1u);
+    let _e14 = gl_Position;
+    let _e16 = f_color;
+    let _e17 = GeometryOutputVertex(_e14, _e16);
+    let _e20 = geometry_strip_length;
+    if (_e20 >= 2u) {
+        let _e24 = geometry_strip[0];
+        let _e26 = geometry_strip[1];
+        let _e30 = atomicAdd((&geometry_output.vertex_count), 3u);
+        if ((_e30 + 3u) <= arrayLength((&geometry_output.vertices))) {
+            geometry_output.vertices[_e30] = _e24;
+            geometry_output.vertices[(_e30 + 1u)] = _e26;
+            geometry_output.vertices[(_e30 + 2u)] = _e17;
+        } else {
+            atomicSub((&geometry_output.vertex_count), 3u);
+        }
+    }
+    geometry_strip[(_e20 % 2u)] = _e17;
+    geometry_strip_length = (_e20 + 1u);
+}
+
+fn EndPrimitive() {
+    geometry_strip_length = 0u;
+}
+
+fn main_1() {
+    var copy: i32 = 0i;
+    var i: i32;
+
+    loop {
+        let _e6 = copy;
+        if !((_e6 < 2i)) {
+            break;
+        }
+        {
+            i = 0i;
+            loop {
+                let _e15 = i;
+                if !((_e15 < 3i)) {
+                    break;
+                }
+                {
+                    let _e24 = i;
+                    let _e26 = gl_in[_e24];
+                    let _e28 = global.offset;
+                    let _e29 = copy;
+                    gl_Position = (_e26.gl_Position + (_e28 * f32(_e29)));
+                    let _e34 = i;
+                    let _e36 = v_color[_e34];
+                    let _e37 = gl_PrimitiveIDIn;
+                    f_color = (_e36 * f32(_e37));
+                    EmitVertex();
+                }
+                continuing {
+                    let _e19 = i;
+                    i = (_e19 + 1i);
+                }
+            }
+            EndPrimitive();
+        }
+        continuing {
+            let _e10 = copy;
+            copy = (_e10 + 1i);
+        }
+    }
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if ((global_id.x >= (arrayLength((&geometry_input)) / 3u)) || (global_id.y >= 1u)) {
+        return;
+    }
+    gl_PrimitiveIDIn = global_id.x;
+    let _e18 = (global_id.x * 3u);
+    let _e24 = geometry_input[_e18].gl_Position;
+    gl_in[0].gl_Position = _e24;
+    let _e28 = geometry_input[_e18].v_color;
+    v_color[0] = _e28;
+    let _e36 = geometry_input[(_e18 + 1u)].gl_Position;
+    gl_in[1].gl_Position = _e36;
+    let _e40 = geometry_input[(_e18 + 1u)].v_color;
+    v_color[1] = _e40;
+    let _e48 = geometry_input[(_e18 + 2u)].gl_Position;
+    gl_in[2].gl_Position = _e48;
+    let _e52 = geometry_input[(_e18 + 2u)].v_color;
+    v_color[2] = _e52;
+    main_1();
+    return;
+}
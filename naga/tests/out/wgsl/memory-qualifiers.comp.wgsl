@@ -0,0 +1,53 @@
+struct Input {
+    inputs: array<u32>,
+}
+
+struct Output {
+    outputs: array<u32>,
+}
+
+struct Params {
+    count: u32,
+    offset: u32,
+}
+
+@group(0) @binding(0) 
+var<storage> global: Input;
+@group(0) @binding(1) 
+var<storage, read_write> global_1: Output;
+@group(0) @binding(2) 
+var<storage> global_2: Params;
+@group(0) @binding(3) 
+var counters: texture_storage_2d<r32uint,read_write>;
+var<private> gl_GlobalInvocationID_1: vec3<u32>;
+
+fn main_1() {
+    var index: u32;
+
+    let _e10 = gl_GlobalInvocationID_1;
+    index = _e10.x;
+    let _e13 = index;
+    let _e14 = global_2.count;
+    if (_e13 < _e14) {
+        {
+            let _e16 = index;
+            let _e18 = index;
+            let _e19 = global_2.offset;
+            let _e22 = global.inputs[(_e18 + _e19)];
+            let _e23 = index;
+            let _e27 = index;
+            let _e31 = textureLoad(counters, vec2<i32>(i32(_e27), 0i));
+            global_1.outputs[_e16] = (_e22 + _e31.x);
+            return;
+        }
+    } else {
+        return;
+    }
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) gl_GlobalInvocationID: vec3<u32>) {
+    gl_GlobalInvocationID_1 = gl_GlobalInvocationID;
+    main_1();
+    return;
+}
@@ -0,0 +1,84 @@
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+}
+
+struct Particles {
+    count: u32,
+    items: array<Particle>,
+}
+
+struct Params {
+    scale: f32,
+    offsets: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> particles: Particles;
+@group(0) @binding(1) 
+var<storage, read_write> previous: Particles;
+@group(0) @binding(2) 
+var<uniform> params: Params;
+var<workgroup> totals: array<f32, 64>;
+
+fn step(particle: ptr<storage, Particle, read_write>, dt: f32) {
+    let _e4 = (*particle).velocity;
+    let _e6 = (*particle).position;
+    (*particle).position = (_e6 + (_e4 * dt));
+    return;
+}
+
+fn advance(buffer: ptr<storage, Particles, read_write>, index: u32, dt_1: f32) {
+    step((&(*buffer).items[index]), dt_1);
+    return;
+}
+
+fn total(items: ptr<storage, array<Particle>, read_write>) -> f32 {
+    var sum: f32 = 0f;
+    var i: u32 = 0u;
+
+    loop {
+        let _e5 = i;
+        if (_e5 < arrayLength(items)) {
+        } else {
+            break;
+        }
+        {
+            let _e8 = i;
+            let _e11 = (*items)[_e8].velocity;
+            let _e13 = sum;
+            sum = (_e13 + length(_e11));
+        }
+        continuing {
+            let _e16 = i;
+            i = (_e16 + 1u);
+        }
+    }
+    let _e18 = sum;
+    return _e18;
+}
+
+fn offset(scale: ptr<uniform, f32>, offset_1: ptr<uniform, vec4<f32>>) -> vec4<f32> {
+    let _e2 = (*offset_1);
+    let _e3 = (*scale);
+    return (_e2 * _e3);
+}
+
+fn accumulate(slot: ptr<workgroup, f32>, value: f32) {
+    let _e2 = (*slot);
+    (*slot) = (_e2 + value);
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(local_invocation_index) local: u32) {
+    advance((&particles), id.x, 0.5f);
+    advance((&previous), id.x, 0.25f);
+    advance((&particles), id.y, 0.125f);
+    let _e15 = total((&particles.items));
+    accumulate((&totals[local]), _e15);
+    let _e23 = offset((&params.scale), (&params.offsets[2]));
+    accumulate((&totals[local]), _e23.x);
+    accumulate((&totals[0]), f32(local));
+    return;
+}
@@ -1,3 +1,5 @@
+enable subgroups;
+
 var<private> num_subgroups_1: u32;
 var<private> subgroup_id_1: u32;
 var<private> subgroup_size_1: u32;
@@ -0,0 +1,143 @@
+enable subgroups;
+
+struct Data {
+    values: array<u32>,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> global: Data;
+var<private> gl_SubgroupInvocationID_1: u32;
+var<private> gl_SubgroupSize_1: u32;
+var<private> gl_SubgroupID_1: u32;
+var<private> gl_NumSubgroups_1: u32;
+var<private> gl_GlobalInvocationID_1: vec3<u32>;
+
+fn main_1() {
+    var id: u32;
+    var size: u32;
+    var group: u32;
+    var ballot: vec4<u32>;
+    var all: bool;
+    var any: bool;
+    var sum: u32;
+    var product: i32;
+    var least: f32;
+    var most: vec2<f32>;
+    var bits: u32;
+    var inclusive: vec3<u32>;
+    var scan: f32;
+    var first: u32;
+    var lane: u32;
+    var shuffled: vec4<i32>;
+    var xored: u32;
+    var up: u32;
+    var down: f32;
+
+    let _e3 = gl_SubgroupInvocationID_1;
+    id = _e3;
+    let _e6 = gl_SubgroupSize_1;
+    size = _e6;
+    let _e10 = gl_SubgroupID_1;
+    let _e11 = gl_NumSubgroups_1;
+    group = (_e10 + _e11);
+    subgroupBarrier();
+    let _e14 = id;
+    let _e19 = id;
+    let _e24 = subgroupBallot(((_e19 & 1u) == 1u));
+    ballot = _e24;
+    let _e26 = id;
+    let _e29 = id;
+    let _e32 = subgroupAll((_e29 != 0u));
+    all = _e32;
+    let _e34 = id;
+    let _e37 = id;
+    let _e40 = subgroupAny((_e37 == 0u));
+    any = _e40;
+    let _e43 = id;
+    let _e44 = subgroupAdd(_e43);
+    sum = _e44;
+    let _e46 = id;
+    let _e48 = id;
+    let _e50 = subgroupMul(i32(_e48));
+    product = _e50;
+    let _e52 = id;
+    let _e54 = id;
+    let _e56 = subgroupMin(f32(_e54));
+    least = _e56;
+    let _e58 = id;
+    let _e61 = id;
+    let _e64 = subgroupMax(vec2(f32(_e61)));
+    most = _e64;
+    let _e67 = id;
+    let _e68 = subgroupAnd(_e67);
+    let _e70 = id;
+    let _e71 = subgroupOr(_e70);
+    let _e74 = id;
+    let _e75 = subgroupXor(_e74);
+    bits = ((_e68 | _e71) | _e75);
+    let _e78 = id;
+    let _e80 = id;
+    let _e82 = subgroupInclusiveAdd(vec3(_e80));
+    inclusive = _e82;
+    let _e84 = id;
+    let _e86 = id;
+    let _e88 = subgroupExclusiveMul(f32(_e86));
+    let _e91 = subgroupInclusiveMul(1f);
+    let _e95 = subgroupExclusiveAdd(2f);
+    scan = ((_e88 + _e91) + _e95);
+    let _e99 = id;
+    let _e100 = subgroupBroadcastFirst(_e99);
+    first = _e100;
+    let _e104 = id;
+    let _e106 = subgroupBroadcast(_e104, 4u);
+    lane = _e106;
+    let _e108 = id;
+    let _e111 = size;
+    let _e112 = id;
+    let _e116 = id;
+    let _e119 = size;
+    let _e120 = id;
+    let _e124 = subgroupShuffle(vec4(i32(_e116)), ((_e119 - _e120) - 1u));
+    shuffled = _e124;
+    let _e128 = id;
+    let _e130 = subgroupShuffleXor(_e128, 2u);
+    xored = _e130;
+    let _e134 = id;
+    let _e136 = subgroupShuffleUp(_e134, 1u);
+    up = _e136;
+    let _e138 = id;
+    let _e141 = id;
+    let _e144 = subgroupShuffleDown(f32(_e141), 1u);
+    down = _e144;
+    let _e147 = gl_GlobalInvocationID_1;
+    let _e150 = ballot;
+    let _e152 = group;
+    let _e154 = sum;
+    let _e156 = product;
+    let _e159 = least;
+    let _e162 = most;
+    let _e166 = bits;
+    let _e168 = inclusive;
+    let _e171 = scan;
+    let _e174 = first;
+    let _e176 = lane;
+    let _e178 = shuffled;
+    let _e182 = xored;
+    let _e184 = up;
+    let _e186 = down;
+    let _e189 = all;
+    let _e194 = any;
+    global.values[_e147.x] = ((((((((((((((((_e150.x + _e152) + _e154) + u32(_e156)) + u32(_e159)) + u32(_e162.y)) + _e166) + _e168.z) + u32(_e171)) + _e174) + _e176) + u32(_e178.w)) + _e182) + _e184) + u32(_e186)) + select(0u, 1u, _e189)) + select(0u, 1u, _e194));
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(subgroup_invocation_id) gl_SubgroupInvocationID: u32, @builtin(subgroup_size) gl_SubgroupSize: u32, @builtin(subgroup_id) gl_SubgroupID: u32, @builtin(num_subgroups) gl_NumSubgroups: u32, @builtin(global_invocation_id) gl_GlobalInvocationID: vec3<u32>) {
+    gl_SubgroupInvocationID_1 = gl_SubgroupInvocationID;
+    gl_SubgroupSize_1 = gl_SubgroupSize;
+    gl_SubgroupID_1 = gl_SubgroupID;
+    gl_NumSubgroups_1 = gl_NumSubgroups;
+    gl_GlobalInvocationID_1 = gl_GlobalInvocationID;
+    main_1();
+    return;
+}
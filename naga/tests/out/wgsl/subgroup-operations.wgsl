@@ -1,3 +1,5 @@
+enable subgroups;
+
 struct Structure {
     @builtin(num_subgroups) num_subgroups: u32,
     @builtin(subgroup_size) subgroup_size: u32,
@@ -0,0 +1,165 @@
+struct Data {
+    input: i32,
+    output: i32,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> data: Data;
+
+fn search(param: i32) -> i32 {
+    var phi_28_: i32;
+    var local: i32;
+    var local_1: i32;
+    var local_2: i32;
+    var phi_38_: i32;
+    var local_3: i32;
+
+    phi_28_ = 0i;
+    loop {
+        let _e6 = phi_28_;
+        local = _e6;
+        local_1 = _e6;
+        local_2 = _e6;
+        local_3 = _e6;
+        if (_e6 < param) {
+            let _e9 = local;
+            let _e11 = local_1;
+            if ((_e9 * _e11) == 49i) {
+                let _e25 = local_3;
+                phi_38_ = _e25;
+            } else {
+                let _e15 = local_2;
+                phi_28_ = (_e15 + 1i);
+                continue;
+            }
+        } else {
+            phi_38_ = -1i;
+        }
+        let _e18 = phi_38_;
+        return _e18;
+    }
+}
+
+fn nested(param_1: i32) -> i32 {
+    var local_4: i32 = 0i;
+    var local_5: i32 = 0i;
+    var local_6: i32 = 0i;
+    var local_7: i32;
+    var local_8: i32;
+    var local_9: i32;
+    var local_10: i32;
+    var branch_destination: u32 = 0u;
+
+    loop {
+        let _e11 = local_5;
+        local_7 = _e11;
+        local_10 = _e11;
+        if (_e11 < param_1) {
+            local_6 = 0i;
+            loop {
+                let _e13 = local_6;
+                let _e15 = local_7;
+                let _e16 = (_e15 * _e13);
+                local_8 = _e16;
+                local_9 = _e13;
+                if (_e16 == 6i) {
+                    break;
+                } else {
+                    let _e18 = local_4;
+                    let _e20 = local_8;
+                    local_4 = (_e18 + _e20);
+                    let _e23 = local_9;
+                    let _e24 = (_e23 + 1i);
+                    local_6 = _e24;
+                    if (_e24 < 4i) {
+                    } else {
+                        let _e27 = local_10;
+                        local_5 = (_e27 + 1i);
+                        branch_destination = 1u;
+                        break;
+                    }
+                }
+            }
+            let _e35 = branch_destination;
+            if (_e35 == 1u) {
+                branch_destination = 0u;
+                continue;
+            }
+        }
+        let _e29 = local_4;
+        return _e29;
+    }
+}
+
+fn classify(param_2: i32) -> i32 {
+    var phi_71_: i32;
+    var branch_destination_1: u32 = 0u;
+
+    switch 0i {
+        default: {
+            switch 0i {
+                default: {
+                    switch 0i {
+                        default: {
+                            switch param_2 {
+                                case 0: {
+                                    if (param_2 < 0i) {
+                                        branch_destination_1 = 1u;
+                                        break;
+                                    } else {
+                                        break;
+                                    }
+                                    break;
+                                }
+                                case 1, 2: {
+                                    break;
+                                }
+                                case 4: {
+                                    return -1i;
+                                }
+                                case 3, default: {
+                                    branch_destination_1 = 1u;
+                                    break;
+                                }
+                            }
+                            let _e17 = branch_destination_1;
+                            if (_e17 == 1u) {
+                                break;
+                            }
+                        }
+                    }
+                    let _e22 = branch_destination_1;
+                    if (_e22 == 1u) {
+                        branch_destination_1 = 0u;
+                        break;
+                    }
+                    phi_71_ = (param_2 + 10i);
+                    branch_destination_1 = 2u;
+                    break;
+                }
+            }
+            let _e31 = branch_destination_1;
+            if (_e31 == 2u) {
+                branch_destination_1 = 0u;
+                break;
+            }
+            phi_71_ = (param_2 * 2i);
+        }
+    }
+    let _e9 = phi_71_;
+    return _e9;
+}
+
+fn main_1() {
+    let _e2 = data.input;
+    let _e3 = search(_e2);
+    let _e4 = nested(_e2);
+    let _e5 = classify(_e2);
+    data.output = ((_e3 + _e4) + _e5);
+    return;
+}
+
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    main_1();
+}
@@ -0,0 +1,31 @@
+struct VertexOutput {
+    @builtin(position) @invariant gl_Position: vec4<f32>,
+    @location(0) v_color: vec4<f32>,
+}
+
+var<private> gl_Position: vec4<f32>;
+var<private> a_position_1: vec4<f32>;
+var<private> v_color: vec4<f32>;
+var<private> scale: f32 = 2f;
+
+fn main_1() {
+    var position: vec4<f32>;
+
+    let _e4 = a_position_1;
+    let _e5 = scale;
+    position = ((_e4 * _e5) + vec4(1f));
+    let _e11 = a_position_1;
+    v_color = _e11;
+    let _e12 = position;
+    gl_Position = _e12;
+    return;
+}
+
+@vertex 
+fn main(@location(0) a_position: vec4<f32>) -> VertexOutput {
+    a_position_1 = a_position;
+    main_1();
+    let _e11 = gl_Position;
+    let _e13 = v_color;
+    return VertexOutput(_e11, _e13);
+}
@@ -1,4 +1,6 @@
 mod example_wgsl;
+mod interp;
+mod opt;
 mod snapshots;
 mod spirv_capabilities;
 mod validation;
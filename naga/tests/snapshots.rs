@@ -772,6 +772,10 @@ fn convert_wgsl() {
             Targets::SPIRV | Targets::WGSL,
         ),
         ("pointers", Targets::SPIRV | Targets::WGSL),
+        (
+            "pointer-parameters",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
         (
             "control-flow",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -881,10 +885,16 @@ fn convert_wgsl() {
             "int64",
             Targets::SPIRV | Targets::HLSL | Targets::WGSL | Targets::METAL,
         ),
+        (
+            "f16",
+            Targets::SPIRV | Targets::HLSL | Targets::WGSL | Targets::METAL | Targets::GLSL,
+        ),
         (
             "subgroup-operations",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
         ),
+        ("diagnostic-filter", Targets::IR | Targets::WGSL),
+        ("const_assert", Targets::IR | Targets::WGSL),
         (
             "overrides",
             Targets::IR
@@ -902,7 +912,19 @@ fn convert_wgsl() {
             "overrides-ray-query",
             Targets::IR | Targets::SPIRV | Targets::METAL,
         ),
+        (
+            "overrides-workgroup-array",
+            Targets::IR | Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL,
+        ),
         ("vertex-pulling-transform", Targets::METAL),
+        (
+            "doc-comments",
+            Targets::IR | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
+        (
+            "doc-comments-overrides",
+            Targets::IR | Targets::METAL | Targets::HLSL | Targets::GLSL,
+        ),
     ];
 
     for &(name, targets) in inputs.iter() {
@@ -942,6 +964,73 @@ fn convert_wgsl() {
     }
 }
 
+/// Check that formatting each WGSL input in `tests/in` doesn't change the
+/// module it parses to.
+///
+/// Spans aren't serialized, so comparing the RON of the two modules checks
+/// everything but where each part of the module was in the source.
+#[cfg(all(feature = "wgsl-in", feature = "serialize"))]
+#[test]
+fn format_wgsl_round_trip() {
+    let _ = env_logger::try_init();
+
+    let input_directory = Path::new(CRATE_ROOT).join(BASE_DIR_IN);
+    let mut names: Vec<_> = fs::read_dir(input_directory)
+        .unwrap()
+        .map(|entry| PathBuf::from(entry.unwrap().file_name()))
+        .filter(|file_name| file_name.extension().is_some_and(|ext| ext == "wgsl"))
+        .collect();
+    names.sort();
+    assert!(!names.is_empty());
+
+    let to_ron = |module: &naga::Module| {
+        let config = ron::ser::PrettyConfig::default().new_line("\n".to_string());
+        ron::ser::to_string_pretty(module, config).unwrap()
+    };
+
+    for file_name in names {
+        let name = file_name.file_stem().unwrap().to_str().unwrap();
+        let input = Input::new(None, name, "wgsl");
+        let source = input.read_source();
+        let module = match naga::front::wgsl::parse_str(&source) {
+            Ok(module) => module,
+            Err(e) => panic!(
+                "{}",
+                e.emit_to_string_with_path(&source, input.input_path())
+            ),
+        };
+
+        let formatted = match naga::front::wgsl::format(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => panic!(
+                "{}",
+                e.emit_to_string_with_path(&source, input.input_path())
+            ),
+        };
+        let reparsed = match naga::front::wgsl::parse_str(&formatted) {
+            Ok(module) => module,
+            Err(e) => panic!(
+                "formatting '{}' broke it:\n{}",
+                input.file_name.display(),
+                e.emit_to_string(&formatted)
+            ),
+        };
+
+        if to_ron(&module) != to_ron(&reparsed) {
+            panic!(
+                "formatting '{}' changed its module",
+                input.file_name.display()
+            );
+        }
+        assert_eq!(
+            naga::front::wgsl::format(&formatted).unwrap(),
+            formatted,
+            "formatting '{}' twice changed it",
+            input.file_name.display()
+        );
+    }
+}
+
 #[cfg(feature = "spv-in")]
 fn convert_spv(name: &str, adjust_coordinate_space: bool, targets: Targets) {
     let _ = env_logger::try_init();
@@ -953,6 +1042,7 @@ fn convert_spv(name: &str, adjust_coordinate_space: bool, targets: Targets) {
             adjust_coordinate_space,
             strict_capabilities: false,
             block_ctx_dump_prefix: None,
+            debug_printf: true,
         },
     )
     .unwrap();
@@ -994,11 +1084,31 @@ fn convert_spv_all() {
     convert_spv("builtin-accessed-outside-entrypoint", true, Targets::WGSL);
     convert_spv("spec-constants", true, Targets::IR);
     convert_spv("spec-constants-issue-5598", true, Targets::GLSL);
+    convert_spv(
+        "spec-constant-op",
+        true,
+        Targets::IR | Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL,
+    );
+    convert_spv(
+        "debug-printf",
+        true,
+        Targets::IR | Targets::SPIRV | Targets::HLSL | Targets::WGSL,
+    );
+    convert_spv(
+        "unstructured",
+        true,
+        Targets::IR | Targets::WGSL | Targets::GLSL,
+    );
     convert_spv(
         "subgroup-operations-s",
         false,
         Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
     );
+    convert_spv(
+        "buffer-reference",
+        true,
+        Targets::IR | Targets::SPIRV | Targets::METAL,
+    );
     convert_spv(
         "atomic_i_increment",
         false,
@@ -1018,6 +1128,8 @@ fn convert_glsl_variations_check() {
             &naga::front::glsl::Options {
                 stage: naga::ShaderStage::Fragment,
                 defines: Default::default(),
+                include_resolver: None,
+                geometry: None,
             },
             &source,
         )
@@ -1042,17 +1154,20 @@ fn convert_glsl_folder() {
             continue;
         }
 
+        let extension = file_name.extension().and_then(|s| s.to_str()).unwrap();
         let mut parser = naga::front::glsl::Frontend::default();
         let module = parser
             .parse(
                 &naga::front::glsl::Options {
-                    stage: match file_name.extension().and_then(|s| s.to_str()).unwrap() {
+                    stage: match extension {
                         "vert" => naga::ShaderStage::Vertex,
                         "frag" => naga::ShaderStage::Fragment,
-                        "comp" => naga::ShaderStage::Compute,
+                        "comp" | "geom" => naga::ShaderStage::Compute,
                         ext => panic!("Unknown extension for glsl file {ext}"),
                     },
                     defines: Default::default(),
+                    include_resolver: None,
+                    geometry: (extension == "geom").then(Default::default),
                 },
                 &input.read_source(),
             )
@@ -7,8 +7,81 @@ fn check(input: &str, snapshot: &str) {
     let output = naga::front::wgsl::parse_str(input)
         .expect_err("expected parser error")
         .emit_to_string(input);
+    compare_snapshot(&output, snapshot);
+}
+
+fn check_warnings(input: &str, snapshot: &str) {
+    let (_, warnings) = naga::front::wgsl::Frontend::new()
+        .parse_with_warnings(input)
+        .expect("expected the module to parse");
+    let output: String = warnings
+        .iter()
+        .map(|warning| warning.emit_to_string(input))
+        .collect();
+    compare_snapshot(&output, snapshot);
+}
+
+fn check_with_recovery(input: &str, snapshot: &str) {
+    let output = naga::front::wgsl::Frontend::new()
+        .parse_with_recovery(input)
+        .expect_err("expected parser errors")
+        .emit_to_string(input);
+    compare_snapshot(&output, snapshot);
+}
+
+/// Resolves imports by looking up their paths in a list of files.
+struct Files<'a>(&'a [(&'a str, &'a str)]);
+
+impl naga::front::wgsl::ImportResolver for Files<'_> {
+    fn resolve(
+        &mut self,
+        _importer: &str,
+        path: &str,
+    ) -> Result<naga::front::wgsl::ResolvedImport, String> {
+        let &(name, source) = self
+            .0
+            .iter()
+            .find(|&&(name, _)| name == path)
+            .ok_or_else(|| "file not found".to_string())?;
+        Ok(naga::front::wgsl::ResolvedImport {
+            name: name.to_string(),
+            source: source.to_string(),
+        })
+    }
+}
+
+fn check_with_imports(input: &str, files: &[(&str, &str)], snapshot: &str) {
+    let output = naga::front::wgsl::Frontend::new()
+        .parse_with_imports("main.wgsl", input, &mut Files(files))
+        .expect_err("expected parser error")
+        .emit_to_string_with_path(input, "main.wgsl");
+    compare_snapshot(&output, snapshot);
+}
+
+fn check_with_defines(input: &str, defines: &[(&str, naga::front::wgsl::Define)], snapshot: &str) {
+    let options = naga::front::wgsl::Options {
+        defines: defines
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect(),
+    };
+    let output = naga::front::wgsl::Frontend::new_with_options(options)
+        .parse(input)
+        .expect_err("expected parser error")
+        .emit_to_string(input);
+    compare_snapshot(&output, snapshot);
+}
+
+fn check_format(input: &str, snapshot: &str) {
+    let output = naga::front::wgsl::format(input)
+        .expect_err("expected formatting error")
+        .emit_to_string(input);
+    compare_snapshot(&output, snapshot);
+}
+
+fn compare_snapshot(output: &str, snapshot: &str) {
     if output != snapshot {
-        for diff in diff::lines(snapshot, &output) {
+        for diff in diff::lines(snapshot, output) {
             match diff {
                 diff::Result::Left(l) => println!("-{l}"),
                 diff::Result::Both(l, _) => println!(" {l}"),
@@ -371,7 +444,7 @@ fn unknown_scalar_type() {
 2 │             const a: vec2<something>;
   │                           ^^^^^^^^^ unknown scalar type
   │
-  = note: Valid scalar types are f32, f64, i32, u32, bool
+  = note: Valid scalar types are f16, f32, f64, i32, u32, bool
 
 "#,
     );
@@ -1054,6 +1127,44 @@ fn invalid_arrays() {
 
 "###,
     );
+
+    check(
+        r#"
+            override length: f32;
+            alias Bad = array<f32, length>;
+        "#,
+        r###"error: must be a const-expression that resolves to a concrete integer scalar (u32 or i32)
+  ┌─ wgsl:3:36
+  │
+3 │             alias Bad = array<f32, length>;
+  │                                    ^^^^^^ must resolve to u32 or i32
+
+"###,
+    );
+
+    check_validation! {
+        "override n: u32; var<private> p: array<f32, n>;",
+        "override n: u32; var<private> p: array<f32, (n * 2)>;":
+        Err(naga::valid::ValidationError::GlobalVariable {
+            source: naga::valid::GlobalVariableError::InvalidType(naga::AddressSpace::Private),
+            ..
+        })
+    }
+
+    check_validation! {
+        "override n: u32; struct S { a: array<f32, n> } var<workgroup> s: S;",
+        "override n: u32; var<workgroup> a: array<array<f32, n>, 2>;":
+        Err(naga::valid::ValidationError::Type {
+            source: naga::valid::TypeError::InvalidPendingArray(_),
+            ..
+        })
+    }
+
+    check_validation! {
+        "override n: u32; var<workgroup> a: array<f32, n>;",
+        "override n: i32; var<workgroup> a: array<f32, (n + 1)>;":
+        Ok(_)
+    }
 }
 
 #[test]
@@ -1151,41 +1262,24 @@ fn invalid_functions() {
         })
     }
 
-    // Pointers of these address spaces cannot be passed as arguments.
-    check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<storage, array<f32>>) { }":
-        Err(naga::valid::ValidationError::Function {
-            name: function_name,
-            source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
-                index: 0,
-                name: argument_name,
-                space: naga::AddressSpace::Storage { .. },
-            },
-            ..
-        })
-        if function_name == "unacceptable_ptr_space" && argument_name == "arg"
-    }
+    // Pointers into these address spaces can be passed as arguments.
     check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<uniform, f32>) { }":
-        Err(naga::valid::ValidationError::Function {
-            name: function_name,
-            source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
-                index: 0,
-                name: argument_name,
-                space: naga::AddressSpace::Uniform,
-            },
-            ..
-        })
-        if function_name == "unacceptable_ptr_space" && argument_name == "arg"
+        "fn acceptable_ptr_space(arg: ptr<storage, array<f32>>) { }",
+        "fn acceptable_ptr_space(arg: ptr<storage, f32, read_write>) { }",
+        "fn acceptable_ptr_space(arg: ptr<uniform, f32>) { }",
+        "fn acceptable_ptr_space(arg: ptr<workgroup, f32>) { }":
+        Ok(_)
     }
+
+    // Pointers of these address spaces cannot be passed as arguments.
     check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<workgroup, f32>) { }":
+        "fn unacceptable_ptr_space(arg: ptr<push_constant, f32>) { }":
         Err(naga::valid::ValidationError::Function {
             name: function_name,
             source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
                 index: 0,
                 name: argument_name,
-                space: naga::AddressSpace::WorkGroup,
+                space: naga::AddressSpace::PushConstant,
             },
             ..
         })
@@ -1549,6 +1643,11 @@ fn select() {
             let s = select(x, y, which);
             return s;
         }
+        ",
+        "
+        fn select_mismatched() {
+            let a = select(1i, vec2<i32>(1i, 2i), true);
+        }
         ":
         Err(
             naga::valid::ValidationError::Function {
@@ -2277,3 +2376,986 @@ fn too_many_unclosed_loops() {
         .join()
         .unwrap()
 }
+
+#[test]
+fn f16_requires_enable() {
+    check(
+        "const a: f16 = 1h;",
+        r###"error: the `f16` language extension is not enabled
+  ┌─ wgsl:1:10
+  │
+1 │ const a: f16 = 1h;
+  │          ^^^ the `f16` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable f16;` at the top of the shader.
+
+"###,
+    );
+}
+
+#[test]
+fn f16_alias_requires_enable() {
+    check(
+        "var<private> a: mat2x3h;",
+        r###"error: the `f16` language extension is not enabled
+  ┌─ wgsl:1:17
+  │
+1 │ var<private> a: mat2x3h;
+  │                 ^^^^^^^ the `f16` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable f16;` at the top of the shader.
+
+"###,
+    );
+    check(
+        "const a = vec4h();",
+        r###"error: the `f16` language extension is not enabled
+  ┌─ wgsl:1:11
+  │
+1 │ const a = vec4h();
+  │           ^^^^^ the `f16` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable f16;` at the top of the shader.
+
+"###,
+    );
+}
+
+#[test]
+fn f16_literal_requires_enable() {
+    check(
+        "const a = 1.5h;",
+        r###"error: the `f16` language extension is not enabled
+  ┌─ wgsl:1:11
+  │
+1 │ const a = 1.5h;
+  │           ^^^^ the `f16` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable f16;` at the top of the shader.
+
+"###,
+    );
+}
+
+#[test]
+fn unknown_enable_extension() {
+    check(
+        "enable f16, f32;",
+        r###"error: `f32` is not a valid enable-extension name
+  ┌─ wgsl:1:13
+  │
+1 │ enable f16, f32;
+  │             ^^^ invalid enable-extension
+  │
+  = note: See available extensions at <https://gpuweb.github.io/gpuweb/wgsl/#enable-extension>.
+
+"###,
+    );
+}
+
+#[test]
+fn enable_after_global_decl() {
+    check(
+        "const a = 1;
+enable f16;",
+        r###"error: expected global declaration, but found a global directive
+  ┌─ wgsl:2:1
+  │
+2 │ enable f16;
+  │ ^^^^^^ written after first global declaration
+  │
+  = note: global directives are only allowed before global declarations; maybe hoist this closer to the top of the shader module?
+
+"###,
+    );
+}
+
+#[test]
+fn unimplemented_enable_extension() {
+    check(
+        "enable clip_distances;",
+        r###"error: the `clip_distances` enable-extension is not yet supported
+  ┌─ wgsl:1:8
+  │
+1 │ enable clip_distances;
+  │        ^^^^^^^^^^^^^^ this enable-extension specifies standard functionality which is not yet implemented in Naga
+
+"###,
+    );
+}
+
+#[test]
+fn dual_source_blending_requires_enable() {
+    check(
+        "struct FragmentOutput {
+    @location(0) @second_blend_source mask: vec4<f32>,
+}",
+        r###"error: the `dual_source_blending` language extension is not enabled
+  ┌─ wgsl:2:19
+  │
+2 │     @location(0) @second_blend_source mask: vec4<f32>,
+  │                   ^^^^^^^^^^^^^^^^^^^ the `dual_source_blending` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable dual_source_blending;` at the top of the shader.
+
+"###,
+    );
+}
+
+#[test]
+fn subgroups_require_enable() {
+    check(
+        "@compute @workgroup_size(1)
+fn main(@builtin(subgroup_size) size: u32) {}",
+        r###"error: the `subgroups` language extension is not enabled
+  ┌─ wgsl:2:18
+  │
+2 │ fn main(@builtin(subgroup_size) size: u32) {}
+  │                  ^^^^^^^^^^^^^ the `subgroups` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable subgroups;` at the top of the shader.
+
+"###,
+    );
+
+    check(
+        "fn main() -> u32 {
+    return subgroupAdd(1u);
+}",
+        r###"error: the `subgroups` language extension is not enabled
+  ┌─ wgsl:2:12
+  │
+2 │     return subgroupAdd(1u);
+  │            ^^^^^^^^^^^ the `subgroups` language extension is needed for this
+  │
+  = note: You can enable this extension by adding `enable subgroups;` at the top of the shader.
+
+"###,
+    );
+}
+
+#[test]
+fn unknown_language_extension() {
+    check(
+        "requires im_a_lil_teapot;",
+        r###"error: `im_a_lil_teapot` is not a valid language extension name
+  ┌─ wgsl:1:10
+  │
+1 │ requires im_a_lil_teapot;
+  │          ^^^^^^^^^^^^^^^ invalid language extension
+  │
+  = note: See available extensions at <https://gpuweb.github.io/gpuweb/wgsl/#language-extensions-sec>.
+
+"###,
+    );
+}
+
+#[test]
+fn unimplemented_language_extension() {
+    check(
+        "requires readonly_and_readwrite_storage_textures, packed_4x8_integer_dot_product;",
+        r###"error: the `packed_4x8_integer_dot_product` language extension is not yet supported
+  ┌─ wgsl:1:51
+  │
+1 │ requires readonly_and_readwrite_storage_textures, packed_4x8_integer_dot_product;
+  │                                                   ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ this language extension specifies standard functionality which is not yet implemented in Naga
+
+"###,
+    );
+}
+
+#[test]
+fn invalid_diagnostic_severity() {
+    check(
+        "diagnostic(shout, derivative_uniformity);",
+        r###"error: invalid `diagnostic(…)` severity
+  ┌─ wgsl:1:12
+  │
+1 │ diagnostic(shout, derivative_uniformity);
+  │            ^^^^^ not a valid severity level
+  │
+  = note: See available severities at <https://gpuweb.github.io/gpuweb/wgsl/#diagnostic-severity>.
+
+"###,
+    );
+}
+
+#[test]
+fn conflicting_diagnostic_rules() {
+    check(
+        "diagnostic(off, derivative_uniformity);
+diagnostic(error, derivative_uniformity);",
+        r###"error: found conflicting `diagnostic(…)` rule(s) for `derivative_uniformity`
+  ┌─ wgsl:1:1
+  │
+1 │ diagnostic(off, derivative_uniformity);
+  │ ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ first rule
+2 │ diagnostic(error, derivative_uniformity);
+  │ ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ second rule
+  │
+  = note: Multiple `diagnostic(…)` rules with the same rule name conflict unless the severity is the same; delete the rule you don't want, or ensure that all severities with the same rule name match
+
+"###,
+    );
+
+    check(
+        "@diagnostic(warning, derivative_uniformity)
+@diagnostic(info, derivative_uniformity)
+fn main() {}",
+        r###"error: found conflicting `diagnostic(…)` rule(s) for `derivative_uniformity`
+  ┌─ wgsl:1:2
+  │
+1 │ @diagnostic(warning, derivative_uniformity)
+  │  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ first rule
+2 │ @diagnostic(info, derivative_uniformity)
+  │  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ second rule
+  │
+  = note: Multiple `diagnostic(…)` rules with the same rule name conflict unless the severity is the same; delete the rule you don't want, or ensure that all severities with the same rule name match
+
+"###,
+    );
+}
+
+#[test]
+fn diagnostic_attribute_on_non_function() {
+    check(
+        "@diagnostic(off, derivative_uniformity)
+const a = 1;",
+        r###"error: `@diagnostic(…)` attribute(s) on `const`s are not supported
+  ┌─ wgsl:1:2
+  │
+1 │ @diagnostic(off, derivative_uniformity)
+  │  ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+  │
+  = note: `@diagnostic(…)` attributes are only permitted on `fn`s, compound statements, and `if`, `switch`, `loop`, `for` and `while` statements
+
+"###,
+    );
+
+    check(
+        "fn f() {
+    @diagnostic(off, derivative_uniformity) let a = 1;
+}",
+        r###"error: `@diagnostic(…)` attribute(s) on this statement are not supported
+  ┌─ wgsl:2:6
+  │
+2 │     @diagnostic(off, derivative_uniformity) let a = 1;
+  │      ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+  │
+  = note: `@diagnostic(…)` attributes are only permitted on `fn`s, compound statements, and `if`, `switch`, `loop`, `for` and `while` statements
+
+"###,
+    );
+}
+
+#[test]
+fn derivative_uniformity_diagnostic() {
+    let shader = "
+@fragment
+fn main(@location(0) x: f32) -> @location(0) f32 {
+    if x > 0.0 {
+        return dpdx(x);
+    }
+    return 0.0;
+}
+";
+
+    // Off by default, and when explicitly asked for.
+    for prefix in ["", "diagnostic(off, derivative_uniformity);"] {
+        let source = format!("{prefix}{shader}");
+        assert!(validation_error(&source, naga::valid::Capabilities::default()).is_ok());
+    }
+
+    // Warnings and info are logged, but do not fail validation.
+    for prefix in [
+        "diagnostic(warning, derivative_uniformity);",
+        "diagnostic(info, derivative_uniformity);",
+    ] {
+        let source = format!("{prefix}{shader}");
+        assert!(validation_error(&source, naga::valid::Capabilities::default()).is_ok());
+    }
+
+    let source = format!("diagnostic(error, derivative_uniformity);{shader}");
+    assert!(matches!(
+        validation_error(&source, naga::valid::Capabilities::default()),
+        Err(naga::valid::ValidationError::EntryPoint {
+            source: naga::valid::EntryPointError::Function(
+                naga::valid::FunctionError::NonUniformControlFlow(..)
+            ),
+            ..
+        })
+    ));
+
+    // A function's `@diagnostic` attribute overrides the module's directive.
+    let source = format!(
+        "diagnostic(error, derivative_uniformity);{}",
+        shader.replace(
+            "@fragment",
+            "@fragment @diagnostic(off, derivative_uniformity)"
+        )
+    );
+    assert!(validation_error(&source, naga::valid::Capabilities::default()).is_ok());
+
+    // So do `@diagnostic` attributes on statements and compound statements,
+    // but only within them.
+    for (from, to) in [
+        ("if x", "@diagnostic(off, derivative_uniformity) if x"),
+        ("0.0 {", "0.0 @diagnostic(off, derivative_uniformity) {"),
+        (
+            "if x > 0.0 {\n        return dpdx(x);\n    }",
+            "loop @diagnostic(off, derivative_uniformity) {\n        if x > 0.0 {\n            return dpdx(x);\n        }\n        break;\n    }",
+        ),
+        (
+            "if x > 0.0 {\n        return dpdx(x);\n    }",
+            "switch i32(x) @diagnostic(off, derivative_uniformity) {\n        case 1: { return dpdx(x); }\n        default: {}\n    }",
+        ),
+    ] {
+        let filtered = shader.replace(from, to);
+        assert_ne!(filtered, shader);
+        let source = format!("diagnostic(error, derivative_uniformity);{filtered}");
+        assert!(
+            validation_error(&source, naga::valid::Capabilities::default()).is_ok(),
+            "{source}"
+        );
+
+        let source = format!(
+            "diagnostic(error, derivative_uniformity);{}",
+            filtered.replace(
+                "    return 0.0;",
+                "    if x > 1.0 {\n        return dpdy(x);\n    }\n    return 0.0;"
+            )
+        );
+        assert!(
+            matches!(
+                validation_error(&source, naga::valid::Capabilities::default()),
+                Err(naga::valid::ValidationError::EntryPoint {
+                    source: naga::valid::EntryPointError::Function(
+                        naga::valid::FunctionError::NonUniformControlFlow(..)
+                    ),
+                    ..
+                })
+            ),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn const_assert_must_be_const() {
+    check(
+        "
+        fn foo() {
+            let a = 5;
+            const_assert a != 0;
+        }
+        ",
+        r###"error: this operation is not supported in a const context
+  ┌─ wgsl:4:26
+  │
+4 │             const_assert a != 0;
+  │                          ^ operation not supported here
+
+"###,
+    );
+}
+
+#[test]
+fn const_assert_must_be_bool() {
+    check(
+        "
+        const_assert(5); // 5 is not bool
+        ",
+        r###"error: must be a const-expression that resolves to a bool
+  ┌─ wgsl:2:22
+  │
+2 │         const_assert(5); // 5 is not bool
+  │                      ^ must resolve to bool
+
+"###,
+    );
+}
+
+#[test]
+fn const_assert_failed() {
+    check(
+        "
+        const_assert(false);
+        ",
+        r###"error: const_assert failure
+  ┌─ wgsl:2:22
+  │
+2 │         const_assert(false);
+  │                      ^^^^^ evaluates to false
+
+"###,
+    );
+
+    check(
+        "
+        const SIZE = 16u;
+        fn foo() {
+            const_assert SIZE % 3u == 0u;
+        }
+        ",
+        r###"error: const_assert failure
+  ┌─ wgsl:4:26
+  │
+4 │             const_assert SIZE % 3u == 0u;
+  │                          ^^^^^^^^^^^^^^^ evaluates to false
+
+"###,
+    );
+}
+
+#[test]
+fn recover_from_statement_errors() {
+    check_with_recovery(
+        "
+        fn f() -> i32 {
+            let a = ;
+            var b: i32 = 1
+            let c = 2;
+            if c > 1 { return 1 } else { return 2; }
+            let d = c +;
+            return d;
+        }
+        ",
+        r###"error: expected expression, found ';'
+  ┌─ wgsl:3:21
+  │
+3 │             let a = ;
+  │                     ^ expected expression
+
+error: expected ';', found 'let'
+  ┌─ wgsl:5:13
+  │
+5 │             let c = 2;
+  │             ^^^ expected ';'
+
+error: expected ';', found '}'
+  ┌─ wgsl:6:33
+  │
+6 │             if c > 1 { return 1 } else { return 2; }
+  │                                 ^ expected ';'
+
+error: expected expression, found ';'
+  ┌─ wgsl:7:24
+  │
+7 │             let d = c +;
+  │                        ^ expected expression
+
+"###,
+    );
+}
+
+#[test]
+fn recover_from_global_decl_errors() {
+    check_with_recovery(
+        "
+        enable f17;
+        struct S { a: f32 b: f32 }
+        const x = 1 +;
+        fn f() -> i32 { return 1; }
+        @group(0) @binding(0) var<storage, read> v: array<u32;
+        alias T = ;
+        ",
+        r###"error: `f17` is not a valid enable-extension name
+  ┌─ wgsl:2:16
+  │
+2 │         enable f17;
+  │                ^^^ invalid enable-extension
+  │
+  = note: See available extensions at <https://gpuweb.github.io/gpuweb/wgsl/#enable-extension>.
+
+error: expected ',', found 'b'
+  ┌─ wgsl:3:27
+  │
+3 │         struct S { a: f32 b: f32 }
+  │                           ^ expected ','
+
+error: expected expression, found ';'
+  ┌─ wgsl:4:22
+  │
+4 │         const x = 1 +;
+  │                      ^ expected expression
+
+error: expected '>', found ';'
+  ┌─ wgsl:6:62
+  │
+6 │         @group(0) @binding(0) var<storage, read> v: array<u32;
+  │                                                              ^ expected '>'
+
+error: expected identifier, found ';'
+  ┌─ wgsl:7:19
+  │
+7 │         alias T = ;
+  │                   ^ expected identifier
+
+"###,
+    );
+}
+
+#[test]
+fn recover_from_lowering_errors() {
+    check_with_recovery(
+        "
+        const bad = 1 +;
+        const uses_bad = bad * 2;
+        const unknown = nope;
+
+        fn a() -> i32 {
+            return missing;
+        }
+
+        fn b() {
+            let x: u32 = 1.5;
+        }
+
+        fn c() -> i32 {
+            return a() + uses_bad;
+        }
+
+        fn d() -> i32 {
+            return 1;
+        }
+        ",
+        r###"error: expected expression, found ';'
+  ┌─ wgsl:2:24
+  │
+2 │         const bad = 1 +;
+  │                        ^ expected expression
+
+error: no definition in scope for identifier: 'nope'
+  ┌─ wgsl:4:25
+  │
+4 │         const unknown = nope;
+  │                         ^^^^ unknown identifier
+
+error: no definition in scope for identifier: 'missing'
+  ┌─ wgsl:7:20
+  │
+7 │             return missing;
+  │                    ^^^^^^^ unknown identifier
+
+error: the type of `x` is expected to be `u32`, but got `f32`
+   ┌─ wgsl:11:17
+   │
+11 │             let x: u32 = 1.5;
+   │                 ^ definition of `x`
+
+"###,
+    );
+}
+
+#[test]
+fn recover_without_errors() {
+    let source = "fn f() -> i32 { return 1; }";
+    let module = naga::front::wgsl::Frontend::new()
+        .parse_with_recovery(source)
+        .unwrap();
+    assert_eq!(module.functions.len(), 1);
+}
+
+#[test]
+fn import_without_resolver() {
+    check(
+        r#"
+        import "lighting.wgsl";
+        "#,
+        r###"error: `import` declarations are not supported here
+  ┌─ wgsl:2:16
+  │
+2 │         import "lighting.wgsl";
+  │                ^^^^^^^^^^^^^^^ imported here
+  │
+  = note: modules that import other files must be parsed with `Frontend::parse_with_imports`
+
+"###,
+    );
+}
+
+#[test]
+fn import_after_global_decl() {
+    check_with_imports(
+        r#"
+        const x = 1;
+        import "lighting.wgsl";
+        "#,
+        &[("lighting.wgsl", "")],
+        r###"error: expected global declaration, but found an `import`
+  ┌─ main.wgsl:3:9
+  │
+3 │         import "lighting.wgsl";
+  │         ^^^^^^ written after first global declaration
+  │
+  = note: imports are only allowed before global declarations; maybe hoist this closer to the top of the shader module?
+
+"###,
+    );
+}
+
+#[test]
+fn import_not_found() {
+    check_with_imports(
+        r#"
+        import "lighting.wgsl";
+        import "shadows.wgsl";
+        "#,
+        &[("lighting.wgsl", "")],
+        r###"error: failed to import "shadows.wgsl"
+  ┌─ main.wgsl:3:16
+  │
+3 │         import "shadows.wgsl";
+  │                ^^^^^^^^^^^^^^ file not found
+
+"###,
+    );
+}
+
+#[test]
+fn error_in_imported_file() {
+    check_with_imports(
+        r#"
+        import "lighting.wgsl";
+
+        fn main() -> f32 {
+            return shade();
+        }
+        "#,
+        &[(
+            "lighting.wgsl",
+            "
+fn shade() -> f32 {
+    return 1.0 +;
+}
+",
+        )],
+        r###"error: expected expression, found ';'
+  ┌─ lighting.wgsl:3:17
+  │
+3 │     return 1.0 +;
+  │                 ^ expected expression
+
+"###,
+    );
+}
+
+#[test]
+fn ambiguous_import() {
+    check_with_imports(
+        r#"
+        import "a.wgsl";
+        import "b.wgsl";
+
+        fn main() -> f32 {
+            return scale;
+        }
+        "#,
+        &[
+            ("a.wgsl", "const scale = 1.0;"),
+            ("b.wgsl", "const scale = 2.0;"),
+        ],
+        r###"error: `scale` is declared by more than one imported file
+  ┌─ main.wgsl:6:20
+  │
+6 │             return scale;
+  │                    ^^^^^ ambiguous name
+  │
+  ┌─ a.wgsl:1:7
+  │
+1 │ const scale = 1.0;
+  │       ^^^^^ declared here
+  │
+  ┌─ b.wgsl:1:7
+  │
+1 │ const scale = 2.0;
+  │       ^^^^^ and here
+
+"###,
+    );
+}
+
+#[test]
+fn imports_are_not_transitive() {
+    check_with_imports(
+        r#"
+        import "a.wgsl";
+
+        fn main() -> f32 {
+            return scale;
+        }
+        "#,
+        &[
+            ("a.wgsl", "import \"b.wgsl\";"),
+            ("b.wgsl", "const scale = 2.0;"),
+        ],
+        r###"error: no definition in scope for identifier: 'scale'
+  ┌─ main.wgsl:5:20
+  │
+5 │             return scale;
+  │                    ^^^^^ unknown identifier
+
+"###,
+    );
+}
+
+#[test]
+fn unknown_define() {
+    check(
+        "@if(SHADOWS) fn shadow() {}",
+        r###"error: no define named `SHADOWS`
+  ┌─ wgsl:1:5
+  │
+1 │ @if(SHADOWS) fn shadow() {}
+  │     ^^^^^^^ unknown define
+  │
+  = note: defines are supplied through `Options::defines`; use `defined(…)` to test whether one is present
+
+"###,
+    );
+}
+
+#[test]
+fn bad_condition_operand() {
+    use naga::front::wgsl::Define;
+
+    check_with_defines(
+        "
+        struct Light {
+            @if(CASCADES)
+            cascades: u32,
+        }
+        ",
+        &[("CASCADES", Define::Int(4))],
+        r###"error: expected a boolean in `@if` condition
+  ┌─ wgsl:3:17
+  │
+3 │             @if(CASCADES)
+  │                 ^^^^^^^^ expected a boolean
+
+"###,
+    );
+
+    check_with_defines(
+        "@if(SHADOWS < 2) fn shadow() {}",
+        &[("SHADOWS", Define::Bool(true))],
+        r###"error: expected an integer in `@if` condition
+  ┌─ wgsl:1:5
+  │
+1 │ @if(SHADOWS < 2) fn shadow() {}
+  │     ^^^^^^^ expected an integer
+
+"###,
+    );
+}
+
+#[test]
+fn excluded_code_is_not_visible() {
+    use naga::front::wgsl::Define;
+
+    check_with_defines(
+        "
+        @if(SHADOWS)
+        fn shadow() -> f32 { return 1.0; }
+
+        fn main() -> f32 {
+            return shadow();
+        }
+        ",
+        &[("SHADOWS", Define::Bool(false))],
+        r###"error: no definition in scope for identifier: 'shadow'
+  ┌─ wgsl:6:20
+  │
+6 │             return shadow();
+  │                    ^^^^^^ unknown identifier
+
+"###,
+    );
+
+    check_with_defines(
+        "
+        fn main() -> f32 {
+            @if(SHADOWS) let shadow = 0.5;
+            return shadow;
+        }
+        ",
+        &[("SHADOWS", Define::Bool(false))],
+        r###"error: no definition in scope for identifier: 'shadow'
+  ┌─ wgsl:4:20
+  │
+4 │             return shadow;
+  │                    ^^^^^^ unknown identifier
+
+"###,
+    );
+}
+
+#[test]
+fn format_unclosed_delimiter() {
+    check_format(
+        "fn f() {\n    let x = (1 + 2);\n",
+        r###"error: expected expression, found ''
+  ┌─ wgsl:3:1
+  │
+3 │ 
+  │ ^ expected expression
+
+"###,
+    );
+}
+
+#[test]
+fn format_mismatched_delimiter() {
+    check_format(
+        "fn f() {\n    let x = a[1);\n}\n",
+        r###"error: expected ']', found ')'
+  ┌─ wgsl:2:16
+  │
+2 │     let x = a[1);
+  │                ^ expected ']'
+
+"###,
+    );
+}
+
+#[test]
+fn format_unterminated_comment() {
+    check_format(
+        "fn f() {}\n/* unfinished\n",
+        r###"error: unterminated block comment
+  ┌─ wgsl:2:1
+  │
+2 │ /* unfinished
+  │ ^^ comment starts here
+
+"###,
+    );
+}
+
+#[test]
+fn warn_unused_variables() {
+    check_warnings(
+        r#"
+        fn f(a: i32) -> i32 {
+            var unused = 1;
+            let _ignored = 2;
+            let used = a + 1;
+            return used;
+        }
+        "#,
+        r###"warning: unused variable `unused`
+  ┌─ wgsl:3:17
+  │
+3 │             var unused = 1;
+  │                 ^^^^^^ this is never used
+  │
+  = note: if this is intentional, prefix it with an underscore: `_unused`
+
+"###,
+    );
+}
+
+#[test]
+fn warn_unreachable_code() {
+    check_warnings(
+        r#"
+        fn f(a: i32) -> i32 {
+            if a > 0 {
+                return 1;
+                _ = a;
+                _ = a;
+            }
+            loop {
+                break;
+                continuing {}
+            }
+            return 0;
+        }
+        "#,
+        r###"warning: unreachable statement
+  ┌─ wgsl:4:17
+  │  
+4 │                   return 1;
+  │                   ^^^^^^^^^ any code following this statement is unreachable
+5 │ ╭                 _ = a;
+6 │ │                 _ = a;
+  │ ╰──────────────────────^ unreachable statement
+
+"###,
+    );
+}
+
+#[test]
+fn warn_shadowed_declarations() {
+    check_warnings(
+        r#"
+        const scale = 2;
+        fn f(a: i32) -> i32 {
+            let scale = 3;
+            var total = a * scale;
+            if total > 0 {
+                let a = total;
+                total = a;
+            }
+            return total;
+        }
+        "#,
+        r###"warning: declaration of `scale` shadows an earlier declaration
+  ┌─ wgsl:2:15
+  │
+2 │         const scale = 2;
+  │               ^^^^^ shadows this one
+3 │         fn f(a: i32) -> i32 {
+4 │             let scale = 3;
+  │                 ^^^^^ this declaration
+
+warning: declaration of `a` shadows an earlier declaration
+  ┌─ wgsl:3:14
+  │
+3 │         fn f(a: i32) -> i32 {
+  │              ^ shadows this one
+  ·
+7 │                 let a = total;
+  │                     ^ this declaration
+
+"###,
+    );
+}
+
+#[test]
+fn warn_abstract_float_narrowing() {
+    check_warnings(
+        r#"
+        const PI = 3.14159265358979;
+        const tenth: f32 = 0.1;
+        const exact: f32 = 0.5;
+        fn f() -> vec2<f32> {
+            let tiny: f32 = 1e-50;
+            return vec2(PI, tiny) * tenth * exact;
+        }
+        "#,
+        r###"warning: abstract-float value is narrowed to `f32`, losing precision
+  ┌─ wgsl:2:20
+  │
+2 │         const PI = 3.14159265358979;
+  │                    ^^^^^^^^^^^^^^^^ this becomes `3.1415927`
+
+warning: abstract-float value is narrowed to `f32`, losing precision
+  ┌─ wgsl:6:29
+  │
+6 │             let tiny: f32 = 1e-50;
+  │                             ^^^^^ this becomes `0`
+
+"###,
+    );
+}
+
+#[test]
+fn no_warnings() {
+    let source = "fn f(a: f32) -> f32 { let b = a * 0.25; return b; }";
+    let (module, warnings) = naga::front::wgsl::Frontend::new()
+        .parse_with_warnings(source)
+        .unwrap();
+    assert_eq!(module.functions.len(), 1);
+    assert!(warnings.is_empty());
+}
@@ -1,3 +1,5 @@
+enable subgroups;
+
 @group(0)
 @binding(0)
 var<storage, read_write> storage_buffer: array<u32>;
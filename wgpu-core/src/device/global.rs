@@ -1288,6 +1288,9 @@ impl Global {
                 Ok(shader) => shader,
                 Err(e) => break 'error e,
             };
+            for warning in shader.warnings.iter() {
+                log::warn!("Device::create_shader_module warning: {}", warning.message);
+            }
 
             let (id, _) = fid.assign(Arc::new(shader));
             api_log!("Device::create_shader_module -> {id:?}");
@@ -1358,6 +1361,21 @@ impl Global {
         A::hub(self).shader_modules.label_for_resource(id)
     }
 
+    /// Return the warnings found while parsing `shader_module_id`'s source.
+    ///
+    /// Only WGSL sources produce warnings; modules created from other sources,
+    /// and invalid modules, have none.
+    pub fn shader_module_warnings<A: HalApi>(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+    ) -> Vec<pipeline::ShaderModuleWarning> {
+        A::hub(self)
+            .shader_modules
+            .get(shader_module_id)
+            .map(|shader_module| shader_module.warnings.clone())
+            .unwrap_or_default()
+    }
+
     pub fn shader_module_drop<A: HalApi>(&self, shader_module_id: id::ShaderModuleId) {
         profiling::scope!("ShaderModule::drop");
         api_log!("ShaderModule::drop {shader_module_id:?}");
@@ -1405,31 +1405,45 @@ impl<A: HalApi> Device<A> {
     ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
         self.check_is_valid()?;
 
-        let (module, source) = match source {
+        let (module, source, warnings) = match source {
             #[cfg(feature = "wgsl")]
             pipeline::ShaderModuleSource::Wgsl(code) => {
-                profiling::scope!("naga::front::wgsl::parse_str");
-                let module = naga::front::wgsl::parse_str(&code).map_err(|inner| {
-                    pipeline::CreateShaderModuleError::Parsing(naga::error::ShaderError {
-                        source: code.to_string(),
-                        label: desc.label.as_ref().map(|l| l.to_string()),
-                        inner: Box::new(inner),
+                profiling::scope!("naga::front::wgsl::parse_with_warnings");
+                let (module, warnings) = naga::front::wgsl::Frontend::new()
+                    .parse_with_warnings(&code)
+                    .map_err(|inner| {
+                        pipeline::CreateShaderModuleError::Parsing(naga::error::ShaderError {
+                            source: code.to_string(),
+                            label: desc.label.as_ref().map(|l| l.to_string()),
+                            inner: Box::new(inner),
+                        })
+                    })?;
+                let warnings = warnings
+                    .iter()
+                    .map(|warning| pipeline::ShaderModuleWarning {
+                        message: warning.emit_to_string(&code),
+                        location: warning.location(&code),
                     })
-                })?;
-                (Cow::Owned(module), code.into_owned())
+                    .collect();
+                (Cow::Owned(module), code.into_owned(), warnings)
             }
             #[cfg(feature = "spirv")]
             pipeline::ShaderModuleSource::SpirV(spv, options) => {
                 let parser = naga::front::spv::Frontend::new(spv.iter().cloned(), &options);
                 profiling::scope!("naga::front::spv::Frontend");
-                let module = parser.parse().map_err(|inner| {
+                let (module, source_map) = parser.parse_with_source_map().map_err(|inner| {
                     pipeline::CreateShaderModuleError::ParsingSpirV(naga::error::ShaderError {
                         source: String::new(),
                         label: desc.label.as_ref().map(|l| l.to_string()),
                         inner: Box::new(inner),
                     })
                 })?;
-                (Cow::Owned(module), String::new())
+                // Spans refer to the source recorded in the module's debug
+                // info, so that's the text to show validation errors against.
+                let source = source_map
+                    .map(|map| map.text().to_string())
+                    .unwrap_or_default();
+                (Cow::Owned(module), source, Vec::new())
             }
             #[cfg(feature = "glsl")]
             pipeline::ShaderModuleSource::Glsl(code, options) => {
@@ -1442,9 +1456,9 @@ impl<A: HalApi> Device<A> {
                         inner: Box::new(inner),
                     })
                 })?;
-                (Cow::Owned(module), code.into_owned())
+                (Cow::Owned(module), code.into_owned(), Vec::new())
             }
-            pipeline::ShaderModuleSource::Naga(module) => (module, String::new()),
+            pipeline::ShaderModuleSource::Naga(module) => (module, String::new(), Vec::new()),
             pipeline::ShaderModuleSource::Dummy(_) => panic!("found `ShaderModuleSource::Dummy`"),
         };
         for (_, var) in module.global_variables.iter() {
@@ -1524,6 +1538,7 @@ impl<A: HalApi> Device<A> {
             raw: Some(raw),
             device: self.clone(),
             interface: Some(interface),
+            warnings,
             info: ResourceInfo::new(&desc.label, None),
         })
     }
@@ -1566,6 +1581,7 @@ impl<A: HalApi> Device<A> {
             raw: Some(raw),
             device: self.clone(),
             interface: None,
+            warnings: Vec::new(),
             info: ResourceInfo::new(&desc.label, None),
         })
     }
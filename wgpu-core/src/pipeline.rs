@@ -45,11 +45,23 @@ pub struct ShaderModuleDescriptor<'a> {
     pub shader_bound_checks: wgt::ShaderBoundChecks,
 }
 
+/// A warning found while parsing a shader module's source.
+///
+/// See [`Global::shader_module_warnings`](crate::global::Global::shader_module_warnings).
+#[derive(Clone, Debug)]
+pub struct ShaderModuleWarning {
+    /// The warning, rendered with the source it points at.
+    pub message: String,
+    /// Where in the source the warning points, if anywhere.
+    pub location: Option<naga::SourceLocation>,
+}
+
 #[derive(Debug)]
 pub struct ShaderModule<A: HalApi> {
     pub(crate) raw: Option<A::ShaderModule>,
     pub(crate) device: Arc<Device<A>>,
     pub(crate) interface: Option<validation::Interface>,
+    pub(crate) warnings: Vec<ShaderModuleWarning>,
     pub(crate) info: ResourceInfo<ShaderModule<A>>,
 }
 
@@ -384,7 +384,9 @@ impl PhysicalDeviceFeatures {
                     vk::PhysicalDeviceShaderFloat16Int8Features::default().shader_float16(true),
                     vk::PhysicalDevice16BitStorageFeatures::default()
                         .storage_buffer16_bit_access(true)
-                        .uniform_and_storage_buffer16_bit_access(true),
+                        .uniform_and_storage_buffer16_bit_access(true)
+                        .storage_input_output16(private_caps.storage_input_output16)
+                        .storage_push_constant16(private_caps.storage_push_constant16),
                 ))
             } else {
                 None
@@ -1478,6 +1480,12 @@ impl super::Instance {
                 }),
             image_format_list: phd_capabilities.device_api_version >= vk::API_VERSION_1_2
                 || phd_capabilities.supports_extension(khr::image_format_list::NAME),
+            storage_input_output16: phd_features
+                .shader_float16
+                .map_or(false, |(_, bit16)| bit16.storage_input_output16 != 0),
+            storage_push_constant16: phd_features
+                .shader_float16
+                .map_or(false, |(_, bit16)| bit16.storage_push_constant16 != 0),
         };
         let capabilities = crate::Capabilities {
             limits: phd_capabilities.to_wgpu_limits(),
@@ -1715,6 +1723,21 @@ impl super::Adapter {
                 capabilities.push(spv::Capability::Int64);
             }
 
+            if features.contains(wgt::Features::SHADER_F16) {
+                capabilities.push(spv::Capability::Float16);
+                capabilities.push(spv::Capability::StorageBuffer16BitAccess);
+                capabilities.push(spv::Capability::UniformAndStorageBuffer16BitAccess);
+                // These are optional, so without them the SPIR-V back end
+                // rejects shaders that keep `f16` values in shader
+                // interfaces or push constants.
+                if self.private_caps.storage_input_output16 {
+                    capabilities.push(spv::Capability::StorageInputOutput16);
+                }
+                if self.private_caps.storage_push_constant16 {
+                    capabilities.push(spv::Capability::StoragePushConstant16);
+                }
+            }
+
             if features.intersects(
                 wgt::Features::SHADER_INT64_ATOMIC_ALL_OPS
                     | wgt::Features::SHADER_INT64_ATOMIC_MIN_MAX,
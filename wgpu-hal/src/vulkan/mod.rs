@@ -419,6 +419,10 @@ struct PrivateCapabilities {
     robust_image_access2: bool,
     zero_initialize_workgroup_memory: bool,
     image_format_list: bool,
+    /// `f16` values can be shader inputs and outputs, if `SHADER_F16` is enabled.
+    storage_input_output16: bool,
+    /// `f16` values can be push constants, if `SHADER_F16` is enabled.
+    storage_push_constant16: bool,
 }
 
 bitflags::bitflags!(
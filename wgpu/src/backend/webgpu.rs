@@ -1553,6 +1553,7 @@ impl crate::context::Context for ContextWebGpu {
                     adjust_coordinate_space: false,
                     strict_capabilities: true,
                     block_ctx_dump_prefix: None,
+                    debug_printf: false,
                 };
                 let spv_parser = front::spv::Frontend::new(spv.iter().cloned(), &options);
                 spv_parser
@@ -1587,6 +1588,8 @@ impl crate::context::Context for ContextWebGpu {
                 let options = front::glsl::Options {
                     stage,
                     defines: defines.clone(),
+                    include_resolver: None,
+                    geometry: None,
                 };
                 let mut parser = front::glsl::Frontend::default();
                 parser
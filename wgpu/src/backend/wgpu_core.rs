@@ -897,6 +897,7 @@ impl crate::Context for ContextWgpuCore {
                     adjust_coordinate_space: false, // we require NDC_Y_UP feature
                     strict_capabilities: true,
                     block_ctx_dump_prefix: None,
+                    debug_printf: false,
                 };
                 wgc::pipeline::ShaderModuleSource::SpirV(Borrowed(spv), options)
             }
@@ -906,7 +907,12 @@ impl crate::Context for ContextWgpuCore {
                 stage,
                 defines,
             } => {
-                let options = naga::front::glsl::Options { stage, defines };
+                let options = naga::front::glsl::Options {
+                    stage,
+                    defines,
+                    include_resolver: None,
+                    geometry: None,
+                };
                 wgc::pipeline::ShaderModuleSource::Glsl(Borrowed(shader), options)
             }
             #[cfg(feature = "wgsl")]
@@ -929,7 +935,12 @@ impl crate::Context for ContextWgpuCore {
                 );
                 CompilationInfo::from(cause)
             }
-            None => CompilationInfo { messages: vec![] },
+            None => {
+                let warnings = wgc::gfx_select!(id => self.0.shader_module_warnings(id));
+                CompilationInfo {
+                    messages: warnings.into_iter().map(CompilationMessage::from).collect(),
+                }
+            }
         };
 
         (id, ShaderModule { compilation_info })
@@ -3524,6 +3535,16 @@ impl From<CreateShaderModuleError> for CompilationInfo {
     }
 }
 
+impl From<wgc::pipeline::ShaderModuleWarning> for CompilationMessage {
+    fn from(value: wgc::pipeline::ShaderModuleWarning) -> Self {
+        CompilationMessage {
+            message: value.message,
+            message_type: CompilationMessageType::Warning,
+            location: value.location.map(Into::into),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct QueueWriteBuffer {
     buffer_id: wgc::id::StagingBufferId,